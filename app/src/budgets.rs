@@ -0,0 +1,13 @@
+//! Monthly budgets of instances and labels.
+
+use crate::error::Result;
+use crate::profiles::config_store;
+use aicred_core::budget::{Budgets, BUDGETS_FILE};
+use std::path::Path;
+
+/// Loads the budgets of the active profile.
+pub fn load_budgets(home: Option<&Path>) -> Result<Budgets> {
+    Ok(config_store(home)?
+        .load_yaml(BUDGETS_FILE)?
+        .unwrap_or_default())
+}
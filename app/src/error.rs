@@ -0,0 +1,21 @@
+//! Errors of the application logic.
+
+/// Error of an operation on tags, labels, profiles or settings.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Error of the core library, such as an unreadable store file
+    #[error(transparent)]
+    Core(#[from] aicred_core::error::Error),
+    /// A tag, label or profile that does not exist
+    #[error("{0}")]
+    NotFound(String),
+    /// A tag or assignment that exists already
+    #[error("{0}")]
+    AlreadyExists(String),
+    /// An invalid argument or setting
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Result of an application operation.
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,211 @@
+//! Provider instances as the front ends show them.
+
+use crate::error::Result;
+use crate::plan::{ChangeAction, ChangeKind, ChangePlan};
+use aicred_core::bulk::{self, InstanceSelector};
+use aicred_core::models::{LabelTarget, Provenance, ProviderInstance};
+use aicred_core::store::instance_file_name;
+use aicred_core::{ConfigStore, SecretBackends};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A provider instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceEntry {
+    pub id: String,
+    pub provider_type: String,
+    pub base_url: String,
+    pub active: bool,
+    /// Whether a non-empty API key is configured
+    pub has_api_key: bool,
+    /// The API key, only where the front end reveals it, such as with
+    /// `--include-values`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    pub models: Vec<String>,
+    /// Instance metadata, including the health check results
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Where the instance's key was discovered, if it came from a scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+impl From<&ProviderInstance> for InstanceEntry {
+    fn from(instance: &ProviderInstance) -> Self {
+        Self {
+            id: instance.id.clone(),
+            provider_type: instance.provider_type.clone(),
+            base_url: instance.base_url.clone(),
+            active: instance.active,
+            has_api_key: instance.has_non_empty_api_key(),
+            api_key: None,
+            models: instance.models.clone(),
+            metadata: instance
+                .metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            provenance: instance.provenance.clone(),
+        }
+    }
+}
+
+/// Plan adding an instance, with its API key kept in `secret_backend` if one is given
+#[must_use]
+pub fn plan_add(
+    store: &ConfigStore,
+    instance: &ProviderInstance,
+    secret_backend: Option<&str>,
+) -> ChangePlan {
+    let mut plan = ChangePlan::new("instances add");
+    let path = store.instances_dir().join(instance_file_name(instance));
+    let change = plan
+        .add(ChangeAction::Create, ChangeKind::Instance, &instance.id)
+        .at(&path)
+        .detail(format!("type: {}", instance.provider_type))
+        .detail(format!("base URL: {}", instance.base_url));
+    if !instance.models.is_empty() {
+        change.detail(format!("models: {}", instance.models.join(", ")));
+    }
+    if let Some(backend) = secret_backend.filter(|backend| *backend != "file") {
+        plan.add(ChangeAction::Create, ChangeKind::Secret, &instance.id)
+            .detail(format!("stored in {backend}"));
+    }
+    plan
+}
+
+/// Plan updating the instance stored at `path` from `before` to `after`
+///
+/// `secret_backend` names the backend a replaced API key is stored in, if not
+/// the instance file.
+#[must_use]
+pub fn plan_update(
+    path: &Path,
+    before: &ProviderInstance,
+    after: &ProviderInstance,
+    key_replaced: bool,
+    secret_backend: Option<&str>,
+) -> ChangePlan {
+    let mut plan = ChangePlan::new("instances update");
+    let change = plan
+        .add(ChangeAction::Update, ChangeKind::Instance, &after.id)
+        .at(path);
+    if before.base_url != after.base_url {
+        change.detail(format!(
+            "base URL: {} -> {}",
+            before.base_url, after.base_url
+        ));
+    }
+    if before.active != after.active {
+        change.detail(format!("active: {} -> {}", before.active, after.active));
+    }
+    if before.models != after.models {
+        change.detail(format!("models: {}", after.models.join(", ")));
+    }
+    if key_replaced {
+        change.detail("API key replaced");
+    }
+    if let Some(backend) = secret_backend {
+        plan.add(ChangeAction::Update, ChangeKind::Secret, &after.id)
+            .detail(format!("stored in {backend}"));
+    }
+    plan
+}
+
+/// Plan removing the selected instances, their secrets and assignments
+pub fn plan_remove(store: &ConfigStore, selector: &InstanceSelector) -> Result<ChangePlan> {
+    let removal = bulk::plan_remove(store, selector)?;
+    let backends = SecretBackends::builtin();
+    let mut plan = ChangePlan::new("instances remove");
+    for (path, instance) in &removal.instances {
+        plan.add(ChangeAction::Delete, ChangeKind::Instance, &instance.id)
+            .at(path)
+            .detail("the file is kept as a .bak backup");
+        if let Some(backend) = backends.owner(&instance.api_key) {
+            plan.add(ChangeAction::Delete, ChangeKind::Secret, &instance.id)
+                .detail(format!("stored in {}", backend.name()));
+        }
+    }
+    for change in &removal.assignments {
+        let name = &change.assignment.label_name;
+        let path = store.dir().join(change.file);
+        match &change.after {
+            Some(after) => {
+                plan.add(ChangeAction::Update, ChangeKind::Assignment, name)
+                    .at(&path)
+                    .detail(format!("now targets {}", describe_target(&after.target)));
+            }
+            None => {
+                plan.add(ChangeAction::Delete, ChangeKind::Assignment, name)
+                    .at(&path);
+            }
+        }
+    }
+    Ok(plan)
+}
+
+/// Describe a label target as `instance[:model]`
+fn describe_target(target: &LabelTarget) -> String {
+    match target.model_id() {
+        Some(model) => format!("{}:{}", target.instance_id(), model),
+        None => target.instance_id().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_entry_hides_the_key_unless_set() {
+        let instance = ProviderInstance::new(
+            "work".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            "sk-secret".to_string(),
+            vec!["gpt-4o".to_string()],
+        );
+        let mut entry = InstanceEntry::from(&instance);
+        assert!(entry.has_api_key);
+        let json = serde_json::to_value(&entry).unwrap();
+        assert!(json.get("api_key").is_none());
+        assert_eq!(json["models"][0], "gpt-4o");
+
+        entry.api_key = Some("sk-secret".to_string());
+        let json = serde_json::to_string(&entry).unwrap();
+        let parsed: InstanceEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, entry);
+    }
+
+    #[test]
+    fn test_plan_update_lists_the_changed_fields() {
+        let before = ProviderInstance::new(
+            "work".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            String::new(),
+            vec!["gpt-4o".to_string()],
+        );
+        let mut after = before.clone();
+        after.active = !before.active;
+
+        let plan = plan_update(
+            Path::new("work.yaml"),
+            &before,
+            &after,
+            true,
+            Some("keyring"),
+        );
+        assert_eq!(plan.changes.len(), 2);
+        assert_eq!(
+            plan.changes[0].details,
+            vec![
+                format!("active: {} -> {}", before.active, after.active),
+                "API key replaced".to_string(),
+            ]
+        );
+        assert_eq!(plan.changes[1].kind, ChangeKind::Secret);
+    }
+}
@@ -0,0 +1,324 @@
+//! Labels: names pointing at one instance or model, with fallbacks tried in
+//! order when it is unavailable.
+
+use crate::error::{Error, Result};
+use crate::profiles::config_store;
+use aicred_core::models::{
+    Label, LabelAssignment, LabelTarget, ModelCapabilities, ProviderCollection, ProviderInstance,
+};
+use aicred_core::resolve::LABELS_FILE;
+use aicred_core::utils::ProviderModelTuple;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::info;
+
+/// File of the label descriptions in the configuration store.
+pub const LABELS_METADATA_FILE: &str = "labels_metadata.yaml";
+
+/// Load all label assignments of the active profile
+pub fn load_label_assignments(home: Option<&Path>) -> Result<Vec<LabelAssignment>> {
+    Ok(config_store(home)?
+        .load_yaml(LABELS_FILE)?
+        .unwrap_or_default())
+}
+
+/// Save the label assignments of the active profile
+pub fn save_label_assignments(labels: &[LabelAssignment], home: Option<&Path>) -> Result<()> {
+    let path = config_store(home)?.save_yaml(LABELS_FILE, &labels)?;
+    info!(
+        "Successfully saved label assignments atomically to {:?}",
+        path
+    );
+    Ok(())
+}
+
+/// Load the label descriptions of the active profile by label name
+pub fn load_labels(home: Option<&Path>) -> Result<HashMap<String, Label>> {
+    let labels: Vec<Label> = config_store(home)?
+        .load_yaml(LABELS_METADATA_FILE)?
+        .unwrap_or_default();
+    Ok(labels.into_iter().map(|l| (l.name.clone(), l)).collect())
+}
+
+/// Save the label descriptions of the active profile
+pub fn save_labels(labels: &HashMap<String, Label>, home: Option<&Path>) -> Result<()> {
+    let labels_vec: Vec<Label> = labels.values().cloned().collect();
+    config_store(home)?.save_yaml(LABELS_METADATA_FILE, &labels_vec)?;
+    Ok(())
+}
+
+/// The assignment of a label, if it is set
+pub fn find_label(name: &str, home: Option<&Path>) -> Result<Option<LabelAssignment>> {
+    Ok(load_label_assignments(home)?
+        .into_iter()
+        .find(|assignment| assignment.label_name == name))
+}
+
+/// Parses a label target, an `instance:model` tuple or an instance ID alone,
+/// erroring unless `instances` has the instance and model.
+///
+/// An ID no instance has may name a provider type instead, such as
+/// `openai:gpt-4o`; the first instance of that type, by ID, with the model is
+/// the target.
+pub fn label_target(target: &str, instances: &ProviderCollection) -> Result<LabelTarget> {
+    let (name, model_id) = if target.contains(':') {
+        let tuple = ProviderModelTuple::parse(target)
+            .map_err(|e| Error::Invalid(format!("Invalid instance:model tuple '{target}': {e}")))?;
+        (
+            tuple.provider().to_string(),
+            Some(tuple.model().to_string()),
+        )
+    } else {
+        (target.trim().to_string(), None)
+    };
+
+    let by_id = instances.get_instance(&name);
+    let mut candidates: Vec<&ProviderInstance> = match by_id {
+        Some(instance) => vec![instance],
+        None => instances
+            .all_instances()
+            .into_iter()
+            .filter(|instance| instance.provider_type == name)
+            .collect(),
+    };
+    candidates.sort_by(|a, b| a.id.cmp(&b.id));
+    if candidates.is_empty() {
+        return Err(Error::NotFound(format!(
+            "Provider instance with ID '{name}' not found"
+        )));
+    }
+
+    let Some(model_id) = model_id else {
+        return Ok(LabelTarget::ProviderInstance {
+            instance_id: candidates[0].id.clone(),
+        });
+    };
+    // Models may be listed with a provider prefix, such as `openai/gpt-4o` on
+    // OpenRouter
+    candidates
+        .iter()
+        .find_map(|instance| {
+            instance
+                .models
+                .iter()
+                .find(|model| {
+                    **model == model_id || model.rsplit('/').next() == Some(model_id.as_str())
+                })
+                .map(|model| LabelTarget::ProviderModel {
+                    instance_id: instance.id.clone(),
+                    model_id: model.clone(),
+                })
+        })
+        .ok_or_else(|| {
+            Error::NotFound(if by_id.is_some() {
+                format!("Provider instance '{name}' has no model '{model_id}'")
+            } else {
+                format!("No provider instance of type '{name}' has model '{model_id}'")
+            })
+        })
+}
+
+/// Errors unless every capability a label requires is known
+pub fn check_capabilities(requires: &[String]) -> Result<()> {
+    match requires
+        .iter()
+        .find(|capability| !ModelCapabilities::is_known(capability))
+    {
+        Some(unknown) => Err(Error::Invalid(format!(
+            "Unknown capability '{}'. Known capabilities: {}",
+            unknown,
+            ModelCapabilities::NAMES.join(", ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Points a label at a target, creating the label if needed.
+///
+/// Returns whether an existing assignment was moved.
+pub fn assign_label(
+    label_name: &str,
+    target: LabelTarget,
+    description: Option<String>,
+    home: Option<&Path>,
+) -> Result<bool> {
+    assign_label_with_fallbacks(
+        label_name,
+        target,
+        Vec::new(),
+        Vec::new(),
+        description,
+        home,
+    )
+}
+
+/// Points a label at a target and the fallbacks tried after it, then at the
+/// cheapest configured model with the required capabilities, creating the
+/// label if needed.
+///
+/// Returns whether an existing assignment was moved.
+pub fn assign_label_with_fallbacks(
+    label_name: &str,
+    target: LabelTarget,
+    fallbacks: Vec<LabelTarget>,
+    requires: Vec<String>,
+    description: Option<String>,
+    home: Option<&Path>,
+) -> Result<bool> {
+    let mut assignments = load_label_assignments(home)?;
+    let mut labels_metadata = load_labels(home)?;
+
+    // Check if this label already exists and update it, or create new one
+    let existing_assignment_index = assignments
+        .iter()
+        .position(|assignment| assignment.label_name == label_name);
+
+    let updated = if let Some(index) = existing_assignment_index {
+        // Update existing assignment
+        assignments[index].target = target;
+        assignments[index].fallbacks = fallbacks;
+        assignments[index].requires = requires;
+        assignments[index].assigned_at = chrono::Utc::now();
+        true
+    } else {
+        // Create new assignment
+        assignments.push(LabelAssignment {
+            label_name: label_name.to_string(),
+            target,
+            assigned_at: chrono::Utc::now(),
+            assigned_by: None,
+            fallbacks,
+            requires,
+        });
+        false
+    };
+
+    // Update label metadata
+    if description.is_some() || !labels_metadata.contains_key(label_name) {
+        let label = labels_metadata
+            .entry(label_name.to_string())
+            .or_insert_with(|| Label {
+                name: label_name.to_string(),
+                description: None,
+                created_at: chrono::Utc::now(),
+                metadata: HashMap::new(),
+            });
+
+        label.description = description;
+    }
+
+    // Save to disk
+    save_label_assignments(&assignments, home)?;
+    save_labels(&labels_metadata, home)?;
+
+    Ok(updated)
+}
+
+/// Removes the assignment of a label, and its description once nothing
+/// references it, returning the removed assignment
+pub fn unset_label(name: &str, home: Option<&Path>) -> Result<LabelAssignment> {
+    let mut assignments = load_label_assignments(home)?;
+    let index = assignments
+        .iter()
+        .position(|assignment| assignment.label_name == name)
+        .ok_or_else(|| Error::NotFound(format!("Label '{name}' not found")))?;
+
+    let assignment = assignments.remove(index);
+    save_label_assignments(&assignments, home)?;
+
+    if !assignments.iter().any(|a| a.label_name == name) {
+        let mut labels_metadata = load_labels(home)?;
+        labels_metadata.remove(name);
+        save_labels(&labels_metadata, home)?;
+    }
+    Ok(assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn instances() -> ProviderCollection {
+        let mut instances = ProviderCollection::new();
+        instances
+            .add_instance(ProviderInstance::new(
+                "router".to_string(),
+                "openrouter".to_string(),
+                "https://openrouter.ai/api/v1".to_string(),
+                String::new(),
+                vec!["openai/gpt-4o".to_string()],
+            ))
+            .unwrap();
+        instances
+    }
+
+    #[test]
+    fn test_label_target_needs_a_configured_instance_and_model() {
+        let instances = instances();
+        assert_eq!(
+            label_target("router", &instances).unwrap(),
+            LabelTarget::ProviderInstance {
+                instance_id: "router".to_string()
+            }
+        );
+        assert_eq!(
+            label_target("router:gpt-4o", &instances).unwrap(),
+            LabelTarget::ProviderModel {
+                instance_id: "router".to_string(),
+                model_id: "openai/gpt-4o".to_string()
+            }
+        );
+        assert!(matches!(
+            label_target("router:claude", &instances),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            label_target("unknown:placeholder", &instances),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            label_target("router:", &instances),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_label_target_may_name_a_provider_type() {
+        let instances = instances();
+        assert_eq!(
+            label_target("openrouter:gpt-4o", &instances).unwrap(),
+            LabelTarget::ProviderModel {
+                instance_id: "router".to_string(),
+                model_id: "openai/gpt-4o".to_string()
+            }
+        );
+        assert!(label_target("openrouter:claude", &instances)
+            .unwrap_err()
+            .to_string()
+            .contains("No provider instance of type 'openrouter' has model 'claude'"));
+    }
+
+    #[test]
+    fn test_assign_and_unset_label() {
+        let temp = TempDir::new().unwrap();
+        let home = Some(temp.path());
+        let target = LabelTarget::ProviderInstance {
+            instance_id: "router".to_string(),
+        };
+
+        assert!(!assign_label("fast", target.clone(), None, home).unwrap());
+        assert!(assign_label("fast", target.clone(), Some("Fast".to_string()), home).unwrap());
+        assert_eq!(
+            load_labels(home).unwrap()["fast"].description.as_deref(),
+            Some("Fast")
+        );
+        assert!(check_capabilities(&["vision".to_string()]).is_ok());
+        assert!(check_capabilities(&["telepathy".to_string()]).is_err());
+
+        assert_eq!(unset_label("fast", home).unwrap().target, target);
+        assert!(find_label("fast", home).unwrap().is_none());
+        assert!(load_labels(home).unwrap().is_empty());
+        assert!(matches!(unset_label("fast", home), Err(Error::NotFound(_))));
+    }
+}
@@ -0,0 +1,22 @@
+//! Application logic of aicred shared by the CLI and the desktop GUI.
+//!
+//! The configuration store of the active profile, the settings of
+//! `config.yaml`, tags, labels, budgets and the plans of dry runs, as typed
+//! values and errors. Nothing
+//! here prints or prompts; each front end presents the results its own way.
+
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::implicit_hasher)]
+
+pub mod budgets;
+pub mod error;
+pub mod instances;
+pub mod labels;
+pub mod plan;
+pub mod profiles;
+pub mod settings;
+pub mod tags;
+
+pub use error::{Error, Result};
@@ -0,0 +1,125 @@
+//! Plans of the changes a mutating command would make.
+//!
+//! A dry run builds a [`ChangePlan`] instead of writing anything; the CLI
+//! prints it as text or JSON and the GUI renders it.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What a planned change does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// What a planned change applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A file outside the instance store, such as a rewritten config file
+    File,
+    /// A provider instance in the store
+    Instance,
+    /// A key held in a secret backend such as the OS keyring
+    Secret,
+    /// A key at the provider, through its key management API
+    ProviderKey,
+    /// A label or tag assignment
+    Assignment,
+}
+
+/// One change of a plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub action: ChangeAction,
+    pub kind: ChangeKind,
+    /// What is changed: an instance ID, label name, file path or key ID
+    pub target: String,
+    /// File written or removed for the change, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Further lines describing the change, such as changed fields
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl PlannedChange {
+    /// Set the file written or removed for the change
+    pub fn at(&mut self, path: &Path) -> &mut Self {
+        self.path = Some(path.display().to_string());
+        self
+    }
+
+    /// Add a line describing the change
+    pub fn detail(&mut self, detail: impl Into<String>) -> &mut Self {
+        self.details.push(detail.into());
+        self
+    }
+}
+
+/// The changes a command would make
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangePlan {
+    /// Command the plan is for, e.g. `instances remove`
+    pub command: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl ChangePlan {
+    /// Create an empty plan for a command
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Add a change, returning it for further details
+    pub fn add(
+        &mut self,
+        action: ChangeAction,
+        kind: ChangeKind,
+        target: impl Into<String>,
+    ) -> &mut PlannedChange {
+        let index = self.changes.len();
+        self.changes.push(PlannedChange {
+            action,
+            kind,
+            target: target.into(),
+            path: None,
+            details: Vec::new(),
+        });
+        &mut self.changes[index]
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_serializes_without_empty_fields() {
+        let mut plan = ChangePlan::new("instances remove");
+        plan.add(ChangeAction::Delete, ChangeKind::Instance, "openai-work")
+            .at(Path::new("/store/inference_services/openai-work.yaml"));
+        plan.add(ChangeAction::Delete, ChangeKind::Secret, "openai-work")
+            .detail("stored in keyring");
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["changes"][0]["action"], "delete");
+        assert!(json["changes"][0].get("details").is_none());
+        assert!(json["changes"][1].get("path").is_none());
+        assert_eq!(json["changes"][1]["kind"], "secret");
+
+        let parsed: ChangePlan = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, plan);
+    }
+}
@@ -0,0 +1,72 @@
+//! Profiles: separate configuration stores kept under the base store, one of
+//! which is active at a time.
+
+use crate::error::{Error, Result};
+use aicred_core::ConfigStore;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name of the profile kept directly in the base store
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// File in the base store recording the profile chosen with 'aicred profile use'
+pub const ACTIVE_PROFILE_FILE: &str = "active_profile.yaml";
+
+/// Environment variable selecting a profile when `--profile` is not given
+pub const PROFILE_ENV_VAR: &str = "AICRED_PROFILE";
+
+/// Profile chosen for the whole process, such as with `--profile`
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Select the profile for this process, overriding the stored choice
+pub fn set_profile_override(name: String) {
+    let _ = PROFILE_OVERRIDE.set(name);
+}
+
+/// Get the base configuration store for a home directory, or for the current
+/// user, ignoring profiles
+pub fn base_config_store(home: Option<&Path>) -> Result<ConfigStore> {
+    match home {
+        Some(h) => Ok(ConfigStore::for_home(h)),
+        None => Ok(ConfigStore::default_location()?),
+    }
+}
+
+/// Name of the active profile: the override, then `AICRED_PROFILE`, then the
+/// profile chosen with 'aicred profile use', then the default profile
+pub fn active_profile(home: Option<&Path>) -> Result<String> {
+    if let Some(name) = PROFILE_OVERRIDE.get() {
+        return Ok(name.clone());
+    }
+    if let Some(name) = std::env::var(PROFILE_ENV_VAR)
+        .ok()
+        .filter(|name| !name.is_empty())
+    {
+        return Ok(name);
+    }
+    Ok(base_config_store(home)?
+        .load_yaml::<String>(ACTIVE_PROFILE_FILE)?
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+}
+
+/// Get the store for a profile; the default profile is the base store itself
+pub fn profile_store(home: Option<&Path>, name: &str) -> Result<ConfigStore> {
+    let base = base_config_store(home)?;
+    if name == DEFAULT_PROFILE {
+        return Ok(base);
+    }
+    Ok(base.profile(name)?)
+}
+
+/// Get the configuration store of the active profile for a home directory, or
+/// for the current user
+pub fn config_store(home: Option<&Path>) -> Result<ConfigStore> {
+    let name = active_profile(home)?;
+    let store = profile_store(home, &name)?;
+    if name != DEFAULT_PROFILE && !store.dir().is_dir() {
+        return Err(Error::NotFound(format!(
+            "Profile '{name}' does not exist. Create it with 'aicred profile create {name}'"
+        )));
+    }
+    Ok(store)
+}
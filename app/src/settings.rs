@@ -0,0 +1,219 @@
+//! Settings of `config.yaml` in the config directory: defaults for the
+//! options of the CLI, and what the GUI scans with.
+//!
+//! ```yaml
+//! format: json             # output format of scan and the listing commands
+//! parallelism: 4           # instances probed at once by `instances check` and `doctor`
+//! exclude: [ollama]        # providers `scan` skips
+//! redaction: last4         # none, mask, last4, first8 or fingerprint, like --redaction
+//! secret_backend: keyring  # where `instances add` and `cleanup` store keys
+//! hash_algorithm: hmac-sha256  # how findings are fingerprinted, see `aicred fingerprint`
+//! notify: false            # stop `scan` posting to the notification sinks
+//! notifications: [...]     # the sinks, see aicred_core::notifications
+//! scanners:                # settings of individual scanners, see aicred_core::ScannerContext
+//!   claude-desktop:
+//!     extra_paths: [~/Apps/Claude/claude.json]
+//! network:                 # proxy, CA bundle and retries of model probing, see aicred_core::http
+//!   ca_bundle: /etc/ssl/corp-ca.pem
+//! ```
+//!
+//! A flag given on the command line wins over the matching `AICRED_*`
+//! environment variable, which wins over the file.
+
+use crate::error::{Error, Result};
+use crate::profiles::base_config_store;
+use aicred_core::fingerprint::HashAlgorithm;
+use aicred_core::http::NetworkOptions;
+use aicred_core::notifications::CONFIG_FILE;
+use aicred_core::{RedactionMode, ScannerContext};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Settings from `config.yaml` and the environment
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Output format used where the command supports it
+    pub format: Option<String>,
+    /// Most probes run at once (default: all)
+    pub parallelism: Option<usize>,
+    /// Providers and scanners `scan` skips
+    pub exclude: Vec<String>,
+    /// How `scan` redacts key values
+    pub redaction: RedactionMode,
+    /// Secret backend for new keys (default: file)
+    pub secret_backend: Option<String>,
+    /// Hash of the findings of `scan`, `cleanup` and `revoke` (default: sha256)
+    pub hash_algorithm: HashAlgorithm,
+    /// Whether `scan` posts to the notification sinks (default: true)
+    pub notify: Option<bool>,
+    /// Settings of individual scanners by name
+    pub scanners: HashMap<String, ScannerContext>,
+    /// Network settings of model probing
+    pub network: NetworkOptions,
+}
+
+impl Settings {
+    /// Load the settings of the config directory, then apply the environment
+    pub fn load(home: Option<&Path>) -> Result<Self> {
+        let store = base_config_store(home)?;
+        let mut config: Self = store
+            .load_yaml(CONFIG_FILE)
+            .map_err(|e| {
+                Error::Invalid(format!(
+                    "Invalid settings in {}: {}",
+                    store.dir().join(CONFIG_FILE).display(),
+                    e
+                ))
+            })?
+            .unwrap_or_default();
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Override settings with `AICRED_*` variables read through `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        if let Some(format) = var("AICRED_FORMAT") {
+            self.format = Some(format);
+        }
+        if let Some(parallelism) = var("AICRED_PARALLELISM") {
+            self.parallelism =
+                Some(parallelism.parse().map_err(|_| {
+                    Error::Invalid("AICRED_PARALLELISM must be a number".to_string())
+                })?);
+        }
+        if let Some(exclude) = var("AICRED_EXCLUDE") {
+            self.exclude = exclude
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(redaction) = var("AICRED_REDACTION") {
+            self.redaction = redaction.parse().map_err(Error::Invalid)?;
+        }
+        if let Some(backend) = var("AICRED_SECRET_BACKEND") {
+            self.secret_backend = Some(backend);
+        }
+        if let Some(algorithm) = var("AICRED_HASH_ALGORITHM") {
+            self.hash_algorithm = algorithm.parse().map_err(Error::Invalid)?;
+        }
+        if let Some(notify) = var("AICRED_NOTIFY") {
+            self.notify = Some(!matches!(notify.as_str(), "0" | "false" | "no" | "off"));
+        }
+        if self.parallelism == Some(0) {
+            return Err(Error::Invalid("parallelism must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The format to use: the flag, else the configured one if the command
+    /// supports it, else the command's default
+    #[must_use]
+    pub fn format(&self, flag: Option<String>, supported: &[&str], default: &str) -> String {
+        flag.or_else(|| {
+            self.format
+                .clone()
+                .filter(|format| supported.contains(&format.as_str()))
+        })
+        .unwrap_or_else(|| default.to_string())
+    }
+
+    /// The secret backend to use: the flag, else the configured one, else `file`
+    #[must_use]
+    pub fn secret_backend(&self, flag: Option<String>) -> String {
+        flag.or_else(|| self.secret_backend.clone())
+            .unwrap_or_else(|| "file".to_string())
+    }
+
+    /// The providers to exclude: the flag, else the configured ones
+    #[must_use]
+    pub fn exclude(&self, flag: Option<String>) -> Option<String> {
+        flag.or_else(|| (!self.exclude.is_empty()).then(|| self.exclude.join(",")))
+    }
+
+    /// The redaction mode to use: none with `--include-values`, else the
+    /// `--redaction` flag, else the configured one
+    #[must_use]
+    pub fn redaction(&self, flag: Option<RedactionMode>, include_values: bool) -> RedactionMode {
+        if include_values {
+            RedactionMode::None
+        } else {
+            flag.unwrap_or(self.redaction)
+        }
+    }
+
+    /// The hash algorithm to use: the flag, else the configured one
+    #[must_use]
+    pub fn hash_algorithm(&self, flag: Option<HashAlgorithm>) -> HashAlgorithm {
+        flag.unwrap_or(self.hash_algorithm)
+    }
+
+    /// Whether `scan` notifies, unless `--no-notify` is given
+    #[must_use]
+    pub fn notify(&self, no_notify: bool) -> bool {
+        !no_notify && self.notify.unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_flags_override_env_which_overrides_the_file() {
+        let mut config: Settings = serde_yaml::from_str(
+            "format: json\nexclude: [ollama]\nsecret_backend: keyring\nnotifications: []\n\
+             scanners:\n  gsh:\n    disabled_patterns: ['*_MODEL_ID']\n\
+             network:\n  proxy: http://proxy.corp:3128\n  retries: 0\n",
+        )
+        .unwrap();
+        let env = HashMap::from([
+            ("AICRED_SECRET_BACKEND", "vault"),
+            ("AICRED_PARALLELISM", "2"),
+            ("AICRED_NOTIFY", "off"),
+            ("AICRED_REDACTION", "last4"),
+            ("AICRED_HASH_ALGORITHM", "hmac"),
+        ]);
+        config
+            .apply_env(|name| env.get(name).map(ToString::to_string))
+            .unwrap();
+
+        assert_eq!(config.format(None, &["table", "json"], "table"), "json");
+        assert_eq!(config.format(None, &["table", "csv"], "table"), "table");
+        assert_eq!(
+            config.format(Some("csv".to_string()), &["table", "csv"], "table"),
+            "csv"
+        );
+        assert_eq!(config.secret_backend(None), "vault");
+        assert_eq!(config.secret_backend(Some("file".to_string())), "file");
+        assert_eq!(config.exclude(None).as_deref(), Some("ollama"));
+        assert_eq!(config.parallelism, Some(2));
+        assert_eq!(config.scanners["gsh"].disabled_patterns, vec!["*_MODEL_ID"]);
+        assert_eq!(
+            config.network.proxy.as_deref(),
+            Some("http://proxy.corp:3128")
+        );
+        assert_eq!(config.network.retries, 0);
+        assert_eq!(config.network.timeout_secs, 30);
+        assert!(!config.notify(false));
+        assert_eq!(config.hash_algorithm(None), HashAlgorithm::HmacSha256);
+        assert_eq!(
+            config.hash_algorithm(Some(HashAlgorithm::Sha256)),
+            HashAlgorithm::Sha256
+        );
+        assert_eq!(config.redaction(None, false), RedactionMode::Last4);
+        assert_eq!(config.redaction(None, true), RedactionMode::None);
+        assert_eq!(
+            config.redaction(Some(RedactionMode::Mask), false),
+            RedactionMode::Mask
+        );
+
+        assert!(config
+            .apply_env(|name| (name == "AICRED_REDACTION").then(|| "partial".to_string()))
+            .is_err());
+    }
+}
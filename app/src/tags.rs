@@ -0,0 +1,211 @@
+//! Tags: free-form names assigned to any number of instances and models.
+
+use crate::error::{Error, Result};
+use crate::profiles::config_store;
+use aicred_core::bulk::TAG_ASSIGNMENTS_FILE;
+use aicred_core::models::{Label, LabelAssignment, LabelTarget};
+use std::path::Path;
+
+/// File of the tags in the configuration store.
+pub const TAGS_FILE: &str = "tags.yaml";
+
+/// Load all tags of the active profile
+pub fn load_tags(home: Option<&Path>) -> Result<Vec<Label>> {
+    Ok(config_store(home)?
+        .load_yaml(TAGS_FILE)?
+        .unwrap_or_default())
+}
+
+/// Save the tags of the active profile
+pub fn save_tags(tags: &[Label], home: Option<&Path>) -> Result<()> {
+    config_store(home)?.save_yaml(TAGS_FILE, &tags)?;
+    Ok(())
+}
+
+/// Load all tag assignments of the active profile
+pub fn load_tag_assignments(home: Option<&Path>) -> Result<Vec<LabelAssignment>> {
+    Ok(config_store(home)?
+        .load_yaml(TAG_ASSIGNMENTS_FILE)?
+        .unwrap_or_default())
+}
+
+/// Save the tag assignments of the active profile
+pub fn save_tag_assignments(assignments: &[LabelAssignment], home: Option<&Path>) -> Result<()> {
+    config_store(home)?.save_yaml(TAG_ASSIGNMENTS_FILE, &assignments)?;
+    Ok(())
+}
+
+fn not_found(name: &str) -> Error {
+    Error::NotFound(format!("Tag with name '{name}' not found"))
+}
+
+/// The target of a tag: an instance, or one of its models
+pub fn tag_target(instance_id: Option<String>, model_id: Option<String>) -> Result<LabelTarget> {
+    match (instance_id, model_id) {
+        (Some(instance_id), None) => Ok(LabelTarget::ProviderInstance { instance_id }),
+        (Some(instance_id), Some(model_id)) => Ok(LabelTarget::ProviderModel {
+            instance_id,
+            model_id,
+        }),
+        (None, Some(_)) => Err(Error::Invalid(
+            "Instance ID is required when specifying a model".to_string(),
+        )),
+        (None, None) => Err(Error::Invalid(
+            "Either instance ID or model ID must be specified".to_string(),
+        )),
+    }
+}
+
+/// Creates a tag, returning it
+pub fn add_tag(name: &str, description: Option<String>, home: Option<&Path>) -> Result<Label> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Invalid("Tag name cannot be empty".to_string()));
+    }
+
+    let mut tags = load_tags(home)?;
+    if tags.iter().any(|tag| tag.name == name) {
+        return Err(Error::AlreadyExists(format!(
+            "Tag with name '{name}' already exists"
+        )));
+    }
+    let tag = Label {
+        name: name.to_string(),
+        description,
+        created_at: chrono::Utc::now(),
+        metadata: std::collections::HashMap::new(),
+    };
+    tags.push(tag.clone());
+    save_tags(&tags, home)?;
+    Ok(tag)
+}
+
+/// Updates the description of a tag unless it is `None`, returning the tag
+pub fn update_tag(name: &str, description: Option<String>, home: Option<&Path>) -> Result<Label> {
+    let mut tags = load_tags(home)?;
+    let tag = tags
+        .iter_mut()
+        .find(|tag| tag.name == name)
+        .ok_or_else(|| not_found(name))?;
+    if description.is_some() {
+        tag.description = description;
+    }
+    let tag = tag.clone();
+    save_tags(&tags, home)?;
+    Ok(tag)
+}
+
+/// Removes a tag and its assignments, returning how many assignments it had
+pub fn remove_tag(name: &str, home: Option<&Path>) -> Result<usize> {
+    let mut tags = load_tags(home)?;
+    let index = tags
+        .iter()
+        .position(|tag| tag.name == name)
+        .ok_or_else(|| not_found(name))?;
+
+    let mut assignments = load_tag_assignments(home)?;
+    let count = assignments.len();
+    assignments.retain(|assignment| assignment.label_name != name);
+    let removed = count - assignments.len();
+    if removed > 0 {
+        save_tag_assignments(&assignments, home)?;
+    }
+
+    tags.remove(index);
+    save_tags(&tags, home)?;
+    Ok(removed)
+}
+
+/// Assigns a tag to a target, returning the assignment
+pub fn assign_tag(name: &str, target: LabelTarget, home: Option<&Path>) -> Result<LabelAssignment> {
+    let tags = load_tags(home)?;
+    let tag = tags
+        .iter()
+        .find(|tag| tag.name == name)
+        .ok_or_else(|| not_found(name))?;
+
+    let mut assignments = load_tag_assignments(home)?;
+    if assignments
+        .iter()
+        .any(|existing| existing.label_name == tag.name && existing.target == target)
+    {
+        return Err(Error::AlreadyExists(format!(
+            "Tag '{name}' is already assigned to the specified target"
+        )));
+    }
+
+    let assignment = LabelAssignment {
+        label_name: tag.name.clone(),
+        target,
+        assigned_at: chrono::Utc::now(),
+        assigned_by: None,
+        fallbacks: Vec::new(),
+        requires: Vec::new(),
+    };
+    assignments.push(assignment.clone());
+    save_tag_assignments(&assignments, home)?;
+    Ok(assignment)
+}
+
+/// Removes the assignment of a tag to a target
+pub fn unassign_tag(name: &str, target: &LabelTarget, home: Option<&Path>) -> Result<()> {
+    let tags = load_tags(home)?;
+    if !tags.iter().any(|tag| tag.name == name) {
+        return Err(not_found(name));
+    }
+
+    let mut assignments = load_tag_assignments(home)?;
+    let count = assignments.len();
+    assignments
+        .retain(|assignment| !(assignment.label_name == name && assignment.target == *target));
+    if assignments.len() == count {
+        return Err(Error::NotFound(format!(
+            "Tag '{name}' is not assigned to the specified target"
+        )));
+    }
+    save_tag_assignments(&assignments, home)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tag_lifecycle() {
+        let temp = TempDir::new().unwrap();
+        let home = Some(temp.path());
+        let target = tag_target(Some("work".to_string()), Some("gpt-4o".to_string())).unwrap();
+
+        assert_eq!(add_tag(" prod ", None, home).unwrap().name, "prod");
+        assert!(matches!(
+            add_tag("prod", None, home),
+            Err(Error::AlreadyExists(_))
+        ));
+        assert!(matches!(add_tag(" ", None, home), Err(Error::Invalid(_))));
+        let tag = update_tag("prod", Some("Production".to_string()), home).unwrap();
+        assert_eq!(tag.description.as_deref(), Some("Production"));
+
+        assign_tag("prod", target.clone(), home).unwrap();
+        assert!(matches!(
+            assign_tag("prod", target.clone(), home),
+            Err(Error::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            assign_tag("staging", target.clone(), home),
+            Err(Error::NotFound(_))
+        ));
+        unassign_tag("prod", &target, home).unwrap();
+        assert!(matches!(
+            unassign_tag("prod", &target, home),
+            Err(Error::NotFound(_))
+        ));
+
+        assign_tag("prod", target, home).unwrap();
+        assert_eq!(remove_tag("prod", home).unwrap(), 1);
+        assert!(load_tags(home).unwrap().is_empty());
+        assert!(load_tag_assignments(home).unwrap().is_empty());
+        assert!(tag_target(None, Some("gpt-4o".to_string())).is_err());
+    }
+}
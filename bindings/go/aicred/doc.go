@@ -10,7 +10,7 @@ Basic Usage:
 		"fmt"
 		"log"
 
-		aicred "github.com/robottwo/aicred/bindings/go/genai_keyfinder"
+		aicred "github.com/robottwo/aicred/bindings/go/aicred"
 	)
 
 	func main() {
@@ -26,6 +26,21 @@ Basic Usage:
 		}
 	}
 
+Scans can be cancelled with ScanContext, and ScanStream reports progress and
+each key as it is found:
+
+	result, err := aicred.ScanStream(ctx, aicred.ScanOptions{}, func(event aicred.Event) {
+		if event.Key != nil {
+			fmt.Printf("found a %s key\n", event.Key.Provider)
+		}
+	})
+
+ListInstances, AddInstance, RemoveInstance and ResolveLabel manage the
+configuration store of the CLI.
+
+Errors of the library are *Error values with the stable codes of the FFI, and
+match ErrNotFound and ErrCancelled with errors.Is.
+
 Security:
 
 By default, all secrets are redacted. Only use IncludeFullValues: true in secure environments.
@@ -0,0 +1,61 @@
+package aicred
+
+/*
+#include "../../../ffi/include/aicred.h"
+*/
+import "C"
+import "errors"
+
+// Codes of the errors of the core library and the FFI, as in ffi/README.md
+const (
+	codeNotFound  = 102
+	codeCancelled = 700
+)
+
+// Error is an error reported by the aicred library, with its stable code
+type Error struct {
+	// Code is the error code, such as 102 for a missing instance
+	Code uint32
+	// Message describes the error
+	Message string
+}
+
+func (e *Error) Error() string {
+	return e.Message
+}
+
+// Is reports whether target is an *Error with the same code, so that
+// errors.Is(err, ErrNotFound) matches every not-found error
+func (e *Error) Is(target error) bool {
+	var other *Error
+	return errors.As(target, &other) && other.Code == e.Code
+}
+
+var (
+	// ErrNotFound is matched by errors about something that does not exist,
+	// such as a missing instance or an unassigned label
+	ErrNotFound error = &Error{Code: codeNotFound, Message: "not found"}
+	// ErrCancelled is matched by errors of a cancelled scan
+	ErrCancelled error = &Error{Code: codeCancelled, Message: "scan cancelled"}
+)
+
+// lastError is the last error the FFI reported on this thread
+//
+// The FFI keeps the error per thread, so callers lock the OS thread from the
+// failing call until here.
+func lastError() error {
+	message := "aicred: unknown error"
+	if ptr := C.aicred_last_error(); ptr != nil {
+		message = C.GoString(ptr)
+	}
+	return &Error{Code: uint32(C.aicred_last_error_code()), Message: message}
+}
+
+// errorCode is the code of an *Error, 0 for other errors
+func errorCode(err error) uint32 {
+	var e *Error
+	if errors.As(err, &e) {
+		return e.Code
+	}
+	return 0
+}
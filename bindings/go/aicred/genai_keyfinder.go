@@ -1,51 +1,59 @@
 package aicred
 
 /*
-#cgo LDFLAGS: -L../../../target/release -laicred_ffi
-#cgo darwin LDFLAGS: -Wl,-rpath,../../../target/release
-#cgo linux LDFLAGS: -Wl,-rpath,../../../target/release
+#cgo LDFLAGS: -L${SRCDIR}/../../../target/release -laicred_ffi
+#cgo darwin LDFLAGS: -Wl,-rpath,${SRCDIR}/../../../target/release
+#cgo linux LDFLAGS: -Wl,-rpath,${SRCDIR}/../../../target/release
 #cgo windows LDFLAGS: -lws2_32 -luserenv -ladvapi32 -lbcrypt -lntdll -lkernel32 -luser32
 #include <stdlib.h>
-
-// Declare the FFI functions that might not be in the header yet
-extern char* aicred_list_providers();
-extern char* aicred_list_scanners();
-extern char* aicred_scan(const char* home_path, const char* options_json);
-extern void aicred_free(char* ptr);
-extern const char* aicred_version(void);
-extern const char* aicred_last_error(void);
-
-// Include the header for existing functions
-#include "../../../ffi/include/genai_keyfinder.h"
+#include "../../../ffi/include/aicred.h"
 */
 import "C"
 import (
+	"context"
 	"encoding/json"
-	"errors"
 	"fmt"
 	"os"
+	"runtime"
 	"unsafe"
 )
 
 // ScanOptions contains options for scanning
 type ScanOptions struct {
-	HomeDir           string   `json:"home_dir,omitempty"`
-	IncludeFullValues bool     `json:"include_full_values"`
-	MaxFileSize       int      `json:"max_file_size"`
-	OnlyProviders     []string `json:"only_providers,omitempty"`
-	ExcludeProviders  []string `json:"exclude_providers,omitempty"`
+	// HomeDir is the home directory to scan, by default the user's
+	HomeDir string `json:"home_dir,omitempty"`
+	// IncludeFullValues reports full secret values (DANGEROUS)
+	IncludeFullValues bool `json:"include_full_values"`
+	// Redaction is how values are shown: "none", "mask", "last4", "first8"
+	// or "fingerprint"; it takes precedence over IncludeFullValues
+	Redaction string `json:"redaction,omitempty"`
+	// MaxFileSize is the largest file read in bytes, 0 for the default
+	MaxFileSize      int      `json:"max_file_size,omitempty"`
+	OnlyProviders    []string `json:"only_providers,omitempty"`
+	ExcludeProviders []string `json:"exclude_providers,omitempty"`
 }
 
 // DiscoveredKey represents a discovered API key
 type DiscoveredKey struct {
-	Provider   string `json:"provider"`
-	Source     string `json:"source"`
-	ValueType  string `json:"value_type"`
+	Provider string `json:"provider"`
+	// Source is the file the key was found in
+	Source string `json:"source"`
+	// Line is the line of the file the key was found on, 0 if unknown
+	Line int `json:"line,omitempty"`
+	// ValueType is the display name of the value type, such as "API Key"
+	ValueType string `json:"value_type"`
+	// Value is the value as the redaction of the scan shows it, the full
+	// value with IncludeFullValues
 	Value      string `json:"value,omitempty"`
 	Confidence string `json:"confidence"`
-	Hash       string `json:"hash"`
-	Redacted   string `json:"redacted"`
-	Locked     bool   `json:"locked"`
+	Risk       string `json:"risk"`
+	// Hash is the SHA-256 of the full value
+	Hash string `json:"hash"`
+	// Redacted is the value redacted even if full values were requested
+	Redacted     string `json:"redacted"`
+	DiscoveredAt string `json:"discovered_at"`
+	// Deprecated: keys are never locked; always false.
+	Locked bool `json:"locked"`
 }
 
 // ConfigInstance represents an application configuration instance
@@ -53,6 +61,7 @@ type ConfigInstance struct {
 	InstanceID   string            `json:"instance_id"`
 	AppName      string            `json:"app_name"`
 	ConfigPath   string            `json:"config_path"`
+	AppVersion   string            `json:"app_version,omitempty"`
 	DiscoveredAt string            `json:"discovered_at"`
 	Keys         []DiscoveredKey   `json:"keys"`
 	Metadata     map[string]string `json:"metadata"`
@@ -60,67 +69,109 @@ type ConfigInstance struct {
 
 // ScanResult contains the results of a scan
 type ScanResult struct {
-	Keys             []DiscoveredKey  `json:"keys"`
-	ConfigInstances  []ConfigInstance `json:"config_instances"`
-	HomeDir          string           `json:"home_directory"`
-	ScannedAt        string           `json:"scan_started_at"`
-	ProvidersScanned []string         `json:"providers_scanned"`
+	Keys               []DiscoveredKey  `json:"keys"`
+	ConfigInstances    []ConfigInstance `json:"config_instances"`
+	HomeDir            string           `json:"home_directory"`
+	ScannedAt          string           `json:"scan_started_at"`
+	CompletedAt        string           `json:"scan_completed_at"`
+	ProvidersScanned   []string         `json:"providers_scanned"`
+	FilesScanned       int              `json:"files_scanned"`
+	DirectoriesScanned int              `json:"directories_scanned"`
 }
 
 // Scan performs a scan for GenAI credentials and configurations
 func Scan(options ScanOptions) (*ScanResult, error) {
-	// Validate HomeDir if provided
-	if options.HomeDir != "" {
-		info, err := os.Stat(options.HomeDir)
-		if err != nil || !info.IsDir() {
-			return nil, fmt.Errorf("invalid HomeDir: %s", options.HomeDir)
+	return ScanContext(context.Background(), options)
+}
+
+// ScanContext is Scan cancelled once ctx is done, returning ctx.Err()
+func ScanContext(ctx context.Context, options ScanOptions) (*ScanResult, error) {
+	home, optionsJSON, err := prepare(options)
+	if err != nil {
+		return nil, err
+	}
+	defer C.free(unsafe.Pointer(home))
+	defer C.free(unsafe.Pointer(optionsJSON))
+
+	cancellation, release := cancelOnDone(ctx)
+	defer release()
+
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	result := C.aicred_scan_ex(home, optionsJSON, cancellation)
+	if result == nil {
+		return nil, scanError(ctx)
+	}
+	defer C.aicred_result_free(result)
+
+	return decodeResult(C.aicred_result_json(result))
+}
+
+// prepare validates the options and converts them for the FFI, which the
+// caller frees
+func prepare(options ScanOptions) (*C.char, *C.char, error) {
+	if options.HomeDir == "" {
+		home, err := os.UserHomeDir()
+		if err != nil {
+			return nil, nil, fmt.Errorf("no home directory to scan: %w", err)
 		}
+		options.HomeDir = home
+	}
+	info, err := os.Stat(options.HomeDir)
+	if err != nil || !info.IsDir() {
+		return nil, nil, fmt.Errorf("invalid HomeDir: %s", options.HomeDir)
 	}
 
-	// Convert options to JSON
 	optionsJSON, err := json.Marshal(options)
 	if err != nil {
-		return nil, fmt.Errorf("failed to marshal options to JSON: %v", err)
+		return nil, nil, fmt.Errorf("failed to marshal options to JSON: %w", err)
 	}
+	return C.CString(options.HomeDir), C.CString(string(optionsJSON)), nil
+}
 
-	// Convert home directory to C string
-	var homeDir *C.char
-	if options.HomeDir != "" {
-		homeDir = C.CString(options.HomeDir)
-	} else {
-		homeDir = C.CString("")
+// cancelOnDone returns a cancellation handle cancelled once ctx is done, and
+// the function releasing it once the scan returned
+func cancelOnDone(ctx context.Context) (*C.struct_aicred_cancellation_t, func()) {
+	cancellation := C.aicred_cancellation_new()
+	if ctx.Err() != nil {
+		C.aicred_cancellation_cancel(cancellation)
 	}
-	defer C.free(unsafe.Pointer(homeDir))
-
-	// Convert options JSON to C string
-	optionsStr := C.CString(string(optionsJSON))
-	defer C.free(unsafe.Pointer(optionsStr))
-
-	// Call C function with error handling
-	resultPtr := C.aicred_scan(homeDir, optionsStr)
-	if resultPtr == nil {
-		// Get error message
-		errPtr := C.aicred_last_error()
-		if errPtr != nil {
-			errMsg := C.GoString(errPtr)
-			return nil, fmt.Errorf("FFI scan failed: %s", errMsg)
+	if ctx.Done() == nil {
+		return cancellation, func() { C.aicred_cancellation_free(cancellation) }
+	}
+
+	finished := make(chan struct{})
+	stopped := make(chan struct{})
+	go func() {
+		defer close(stopped)
+		select {
+		case <-ctx.Done():
+			C.aicred_cancellation_cancel(cancellation)
+		case <-finished:
 		}
-		return nil, errors.New("scan failed with unknown error (FFI returned null)")
+	}()
+	return cancellation, func() {
+		close(finished)
+		<-stopped
+		C.aicred_cancellation_free(cancellation)
 	}
-	defer C.aicred_free(resultPtr)
+}
 
-	// Convert result to Go string
-	resultJSON := C.GoString(resultPtr)
-	if resultJSON == "" {
-		return nil, errors.New("FFI returned empty result")
+// scanError is the error of a failed scan, ctx.Err() if it was cancelled
+func scanError(ctx context.Context) error {
+	err := lastError()
+	if ctx.Err() != nil && errorCode(err) == codeCancelled {
+		return ctx.Err()
 	}
+	return err
+}
 
-	// Parse JSON result
+// decodeResult parses and frees the scan result JSON returned by the FFI
+func decodeResult(resultPtr *C.char) (*ScanResult, error) {
 	var result ScanResult
-	if err := json.Unmarshal([]byte(resultJSON), &result); err != nil {
-		return nil, fmt.Errorf("failed to parse JSON result: %v (raw: %s)", err, resultJSON)
+	if err := decodeJSON(resultPtr, &result); err != nil {
+		return nil, err
 	}
-
 	return &result, nil
 }
 
@@ -132,46 +183,33 @@ func Version() string {
 
 // ListProviders returns a list of available provider plugins
 func ListProviders() []string {
-	// Call the FFI function to get the list of providers
-	providersPtr := C.aicred_list_providers()
-	if providersPtr == nil {
-		// If FFI is not available, return empty slice to avoid misleading consumers
-		return []string{}
-	}
-	defer C.aicred_free(providersPtr)
-
-	// Convert C string to Go string
-	providersJSON := C.GoString(providersPtr)
-
-	// Parse JSON array
-	var providers []string
-	if err := json.Unmarshal([]byte(providersJSON), &providers); err != nil {
-		// If parsing fails, return empty slice
-		return []string{}
-	}
-
-	return providers
+	return stringList(C.aicred_list_providers())
 }
 
 // ListScanners returns a list of available application scanners
 func ListScanners() []string {
-	// Call the FFI function to get the list of scanners
-	scannersPtr := C.aicred_list_scanners()
-	if scannersPtr == nil {
-		// If FFI is not available, return empty slice to avoid misleading consumers
+	return stringList(C.aicred_list_scanners())
+}
+
+// stringList parses and frees a JSON array of strings returned by the FFI,
+// empty if there is none
+func stringList(listPtr *C.char) []string {
+	if listPtr == nil {
 		return []string{}
 	}
-	defer C.aicred_free(scannersPtr)
+	defer C.aicred_free(listPtr)
 
-	// Convert C string to Go string
-	scannersJSON := C.GoString(scannersPtr)
-
-	// Parse JSON array
-	var scanners []string
-	if err := json.Unmarshal([]byte(scannersJSON), &scanners); err != nil {
-		// If parsing fails, return empty slice
+	var list []string
+	if err := json.Unmarshal([]byte(C.GoString(listPtr)), &list); err != nil {
 		return []string{}
 	}
+	return list
+}
 
-	return scanners
+// cString converts s for the FFI, NULL if empty; the caller frees it
+func cString(s string) *C.char {
+	if s == "" {
+		return nil
+	}
+	return C.CString(s)
 }
@@ -1,11 +1,41 @@
 package aicred
 
 import (
+	"context"
+	"errors"
 	"os"
+	"path/filepath"
+	"strings"
 	"sync"
 	"testing"
 )
 
+// testKey is a key the scan of a home with it in .env finds
+const testKey = "sk-ant-REDACTED"
+
+// homeWithKey creates a home directory with testKey in .env
+func homeWithKey(t *testing.T) string {
+	t.Helper()
+	home := t.TempDir()
+	env := []byte("ANTHROPIC_API_KEY=" + testKey + "\n")
+	if err := os.WriteFile(filepath.Join(home, ".env"), env, 0600); err != nil {
+		t.Fatal(err)
+	}
+	return home
+}
+
+// anthropicKey returns the anthropic key among keys
+func anthropicKey(t *testing.T, keys []DiscoveredKey) DiscoveredKey {
+	t.Helper()
+	for _, key := range keys {
+		if key.Provider == "anthropic" {
+			return key
+		}
+	}
+	t.Fatalf("No anthropic key among %v", keys)
+	return DiscoveredKey{}
+}
+
 func TestVersion(t *testing.T) {
 	version := Version()
 	if version == "" {
@@ -200,3 +230,132 @@ func TestConcurrentScans(t *testing.T) {
 	}
 	wg.Wait()
 }
+
+func TestScanFindsKey(t *testing.T) {
+	home := homeWithKey(t)
+
+	result, err := Scan(ScanOptions{HomeDir: home})
+	if err != nil {
+		t.Fatalf("Scan failed: %v", err)
+	}
+	key := anthropicKey(t, result.Keys)
+	if key.Value != "sk-ant-1****" || key.Redacted != key.Value {
+		t.Errorf("Value should be redacted, got %q and %q", key.Value, key.Redacted)
+	}
+	if key.ValueType != "API Key" {
+		t.Errorf("ValueType should be API Key, got %q", key.ValueType)
+	}
+	if !strings.HasSuffix(key.Source, ".env") || key.Line != 1 {
+		t.Errorf("Key should be on line 1 of .env, got %s:%d", key.Source, key.Line)
+	}
+	if key.Hash == "" || key.Risk == "" {
+		t.Error("Hash and Risk should be set")
+	}
+
+	result, err = Scan(ScanOptions{HomeDir: home, IncludeFullValues: true})
+	if err != nil {
+		t.Fatalf("Scan failed: %v", err)
+	}
+	key = anthropicKey(t, result.Keys)
+	if key.Value != testKey || strings.Contains(key.Redacted, "1234567890") {
+		t.Errorf("Value should be full and Redacted not, got %q and %q", key.Value, key.Redacted)
+	}
+
+	result, err = Scan(ScanOptions{HomeDir: home, Redaction: "fingerprint"})
+	if err != nil {
+		t.Fatalf("Scan failed: %v", err)
+	}
+	if key = anthropicKey(t, result.Keys); !strings.HasPrefix(key.Value, "sha256:") {
+		t.Errorf("Value should be a fingerprint, got %q", key.Value)
+	}
+}
+
+func TestScanContextCancelled(t *testing.T) {
+	ctx, cancel := context.WithCancel(context.Background())
+	cancel()
+
+	_, err := ScanContext(ctx, ScanOptions{HomeDir: homeWithKey(t)})
+	if !errors.Is(err, context.Canceled) {
+		t.Errorf("Expected context.Canceled, got %v", err)
+	}
+}
+
+func TestScanStream(t *testing.T) {
+	var progress []Progress
+	var keys []DiscoveredKey
+	result, err := ScanStream(context.Background(), ScanOptions{HomeDir: homeWithKey(t)}, func(event Event) {
+		switch {
+		case event.Progress != nil:
+			progress = append(progress, *event.Progress)
+		case event.Key != nil:
+			keys = append(keys, *event.Key)
+		}
+	})
+	if err != nil {
+		t.Fatalf("ScanStream failed: %v", err)
+	}
+
+	if len(progress) == 0 || progress[0].Total == 0 {
+		t.Errorf("Expected progress events, got %v", progress)
+	}
+	key := anthropicKey(t, keys)
+	if key.Value != "sk-ant-1****" || key.ValueType != "API Key" || key.Line != 1 {
+		t.Errorf("Unexpected key event %+v", key)
+	}
+	if len(result.Keys) == 0 {
+		t.Error("Result should have the keys")
+	}
+}
+
+func TestScanStreamPanic(t *testing.T) {
+	defer func() {
+		if r := recover(); r != "boom" {
+			t.Errorf("Expected the panic of the callback, got %v", r)
+		}
+	}()
+	_, _ = ScanStream(context.Background(), ScanOptions{HomeDir: homeWithKey(t)}, func(Event) {
+		panic("boom")
+	})
+}
+
+func TestInstances(t *testing.T) {
+	home := t.TempDir()
+
+	added, err := AddInstance(home, NewInstance{
+		ID:           "openai-work",
+		ProviderType: "openai",
+		BaseURL:      "https://api.openai.com/v1",
+		APIKey:       "sk-test-1234567890",
+		Models:       []string{"gpt-4o"},
+	})
+	if err != nil {
+		t.Fatalf("AddInstance failed: %v", err)
+	}
+	if added.ID != "openai-work" || !added.Active {
+		t.Errorf("Unexpected instance %+v", added)
+	}
+	if _, err := AddInstance(home, NewInstance{ID: "openai-work", ProviderType: "openai", BaseURL: "https://api.openai.com/v1"}); err == nil {
+		t.Error("Adding an instance twice should fail")
+	}
+
+	instances, err := ListInstances(home)
+	if err != nil {
+		t.Fatalf("ListInstances failed: %v", err)
+	}
+	if instance, ok := instances["openai-work"]; !ok || len(instance.Models) != 1 || instance.Models[0] != "gpt-4o" {
+		t.Errorf("Expected openai-work among %v", instances)
+	}
+
+	if _, err := ResolveLabel(home, "fast"); !errors.Is(err, ErrNotFound) {
+		t.Errorf("Expected ErrNotFound for an unassigned label, got %v", err)
+	}
+
+	if err := RemoveInstance(home, "openai-work"); err != nil {
+		t.Fatalf("RemoveInstance failed: %v", err)
+	}
+	err = RemoveInstance(home, "openai-work")
+	var aicredErr *Error
+	if !errors.Is(err, ErrNotFound) || !errors.As(err, &aicredErr) || aicredErr.Message == "" {
+		t.Errorf("Expected ErrNotFound with a message, got %v", err)
+	}
+}
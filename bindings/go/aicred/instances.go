@@ -0,0 +1,161 @@
+package aicred
+
+/*
+#include <stdlib.h>
+#include "../../../ffi/include/aicred.h"
+*/
+import "C"
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"runtime"
+	"unsafe"
+)
+
+// The functions below read and write the configuration store of the CLI in
+// the config directory of homeDir, or of the current user if homeDir is "".
+
+// ProviderInstance is a provider instance of the configuration store
+type ProviderInstance struct {
+	ID           string            `json:"id"`
+	ProviderType string            `json:"provider_type"`
+	BaseURL      string            `json:"base_url"`
+	APIKey       string            `json:"api_key"`
+	Models       []string          `json:"models"`
+	Capabilities map[string]bool   `json:"capabilities"`
+	Active       bool              `json:"active"`
+	Metadata     map[string]string `json:"metadata"`
+	// Provenance is set for instances imported from a scan
+	Provenance *Provenance `json:"provenance,omitempty"`
+}
+
+// Provenance records where an imported instance was found
+type Provenance struct {
+	Scanner     string   `json:"scanner,omitempty"`
+	SourceFiles []string `json:"source_files"`
+	ScannedAt   string   `json:"scanned_at"`
+}
+
+// NewInstance is an instance to add with AddInstance
+type NewInstance struct {
+	ID           string   `json:"id"`
+	ProviderType string   `json:"provider_type"`
+	BaseURL      string   `json:"base_url"`
+	APIKey       string   `json:"api_key,omitempty"`
+	Models       []string `json:"models,omitempty"`
+	// Inactive adds the instance deactivated
+	Inactive bool `json:"-"`
+}
+
+// Model is the model a label points at
+type Model struct {
+	ID            string          `json:"id"`
+	Provider      string          `json:"provider"`
+	Name          string          `json:"name"`
+	Capabilities  map[string]bool `json:"capabilities"`
+	ContextWindow int             `json:"context_window,omitempty"`
+}
+
+// ResolvedLabel is a label followed to the instance and model it points at
+type ResolvedLabel struct {
+	Label string `json:"label"`
+	// Instance is the instance, with its API key revealed
+	Instance ProviderInstance `json:"instance"`
+	Model    Model            `json:"model"`
+	// Skipped gives why the targets tried before this one were passed over
+	Skipped []string `json:"skipped,omitempty"`
+}
+
+// ListInstances returns the stored provider instances by ID
+func ListInstances(homeDir string) (map[string]ProviderInstance, error) {
+	if homeDir == "" {
+		home, err := os.UserHomeDir()
+		if err != nil {
+			return nil, fmt.Errorf("no home directory: %w", err)
+		}
+		homeDir = home
+	}
+	home := C.CString(homeDir)
+	defer C.free(unsafe.Pointer(home))
+
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	instances := map[string]ProviderInstance{}
+	if err := decodeJSON(C.aicred_list_instances(home), &instances); err != nil {
+		return nil, err
+	}
+	return instances, nil
+}
+
+// AddInstance adds a provider instance, refused if one has its ID, and
+// returns it as stored
+func AddInstance(homeDir string, instance NewInstance) (*ProviderInstance, error) {
+	instanceJSON, err := json.Marshal(struct {
+		NewInstance
+		Active bool `json:"active"`
+	}{instance, !instance.Inactive})
+	if err != nil {
+		return nil, fmt.Errorf("failed to marshal instance to JSON: %w", err)
+	}
+	home := cString(homeDir)
+	defer C.free(unsafe.Pointer(home))
+	instanceStr := C.CString(string(instanceJSON))
+	defer C.free(unsafe.Pointer(instanceStr))
+
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	var stored ProviderInstance
+	if err := decodeJSON(C.aicred_instance_add(home, instanceStr), &stored); err != nil {
+		return nil, err
+	}
+	return &stored, nil
+}
+
+// RemoveInstance removes a provider instance like `aicred instances remove`,
+// keeping the file as a backup and dropping the labels and tags pointing at
+// it; the error matches ErrNotFound if no instance has the ID
+func RemoveInstance(homeDir, id string) error {
+	home := cString(homeDir)
+	defer C.free(unsafe.Pointer(home))
+	idStr := C.CString(id)
+	defer C.free(unsafe.Pointer(idStr))
+
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	if !C.aicred_instance_remove(home, idStr) {
+		return lastError()
+	}
+	return nil
+}
+
+// ResolveLabel follows a label like `aicred resolve`; the error matches
+// ErrNotFound if the label is not assigned
+func ResolveLabel(homeDir, name string) (*ResolvedLabel, error) {
+	home := cString(homeDir)
+	defer C.free(unsafe.Pointer(home))
+	nameStr := C.CString(name)
+	defer C.free(unsafe.Pointer(nameStr))
+
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	var resolved ResolvedLabel
+	if err := decodeJSON(C.aicred_label_resolve(home, nameStr), &resolved); err != nil {
+		return nil, err
+	}
+	return &resolved, nil
+}
+
+// decodeJSON parses and frees JSON returned by the FFI, or returns the last
+// error if there is none
+func decodeJSON(jsonPtr *C.char, value any) error {
+	if jsonPtr == nil {
+		return lastError()
+	}
+	defer C.aicred_free(jsonPtr)
+
+	if err := json.Unmarshal([]byte(C.GoString(jsonPtr)), value); err != nil {
+		return fmt.Errorf("failed to parse JSON result: %w", err)
+	}
+	return nil
+}
@@ -0,0 +1,104 @@
+package aicred
+
+import "encoding/json"
+
+// valueTypeNames are the display names of the value types of the library,
+// which the scan result JSON gives by variant name
+var valueTypeNames = map[string]string{
+	"ApiKey":            "API Key",
+	"AccessToken":       "Access Token",
+	"SecretKey":         "Secret Key",
+	"BearerToken":       "Bearer Token",
+	"ModelId":           "Model ID",
+	"BaseUrl":           "Base URL",
+	"Temperature":       "Temperature",
+	"ParallelToolCalls": "Parallel Tool Calls",
+	"Headers":           "Headers",
+}
+
+// credentialValue is a key value of the scan result JSON, either full or
+// redacted
+type credentialValue struct {
+	Full     *string `json:"Full"`
+	Redacted *struct {
+		SHA256 string `json:"sha256"`
+		Prefix string `json:"prefix"`
+		Suffix string `json:"suffix"`
+		Mode   string `json:"mode"`
+	} `json:"Redacted"`
+}
+
+// redact shows a full value with its last four characters, as the library
+// does where a value must be redacted
+func redact(value string) string {
+	chars := []rune(value)
+	if len(chars) <= 8 {
+		return string(chars[:min(2, len(chars))]) + "****"
+	}
+	return "****" + string(chars[len(chars)-4:])
+}
+
+// display returns the value as shown and the value redacted
+func (v credentialValue) display() (string, string) {
+	switch {
+	case v.Full != nil:
+		return *v.Full, redact(*v.Full)
+	case v.Redacted != nil:
+		var shown string
+		switch r := v.Redacted; r.Mode {
+		case "last4":
+			shown = "****" + r.Suffix
+		case "mask":
+			shown = "********"
+		case "fingerprint":
+			shown = "sha256:" + r.SHA256[:min(12, len(r.SHA256))]
+		default:
+			shown = r.Prefix + "****"
+		}
+		return shown, shown
+	}
+	return "", ""
+}
+
+// UnmarshalJSON decodes a key of the scan result JSON of the library, or one
+// encoded by this package
+func (k *DiscoveredKey) UnmarshalJSON(data []byte) error {
+	type plain DiscoveredKey
+	var key struct {
+		plain
+		Value      json.RawMessage `json:"value"`
+		ValueType  json.RawMessage `json:"value_type"`
+		SourceFile string          `json:"source_file"`
+		SourceLine int             `json:"source_line"`
+	}
+	if err := json.Unmarshal(data, &key); err != nil {
+		return err
+	}
+	*k = DiscoveredKey(key.plain)
+
+	if err := json.Unmarshal(key.Value, &k.Value); err != nil && len(key.Value) > 0 {
+		var value credentialValue
+		if err := json.Unmarshal(key.Value, &value); err != nil {
+			return err
+		}
+		k.Value, k.Redacted = value.display()
+	}
+
+	// A variant name such as "ApiKey", a display name, or {"Custom": name}
+	var custom struct {
+		Custom string `json:"Custom"`
+	}
+	if err := json.Unmarshal(key.ValueType, &k.ValueType); err == nil {
+		if name, ok := valueTypeNames[k.ValueType]; ok {
+			k.ValueType = name
+		}
+	} else if err := json.Unmarshal(key.ValueType, &custom); err == nil {
+		k.ValueType = custom.Custom
+	}
+
+	if key.SourceFile != "" {
+		k.Source = key.SourceFile
+		k.Line = key.SourceLine
+	}
+	return nil
+}
@@ -0,0 +1,117 @@
+package aicred
+
+/*
+#include <stdlib.h>
+#include "../../../ffi/include/aicred.h"
+
+extern void aicredGoScanEvent(aicred_scan_event_t*, void*);
+*/
+import "C"
+import (
+	"context"
+	"runtime"
+	"runtime/cgo"
+	"unsafe"
+)
+
+// Progress reports a scanner starting on a scan root
+type Progress struct {
+	// Scanner is the name of the scanner
+	Scanner string
+	// Root is the scan root being scanned
+	Root string
+	// Completed is the number of scanner runs finished so far
+	Completed int
+	// Total is the number of scanner runs of the whole scan
+	Total int
+}
+
+// Event is an event of a streaming scan; exactly one field is set
+type Event struct {
+	// Progress is set before each scanner runs
+	Progress *Progress
+	// Key is set for each new key, with Provider, Source, Line, ValueType
+	// and Value
+	Key *DiscoveredKey
+}
+
+// stream is the state of a streaming scan, reached by the callback through a
+// cgo.Handle
+type stream struct {
+	onEvent func(Event)
+	// panicked holds a panic of onEvent, raised again once the scan returned
+	panicked any
+}
+
+// ScanStream is ScanContext calling onEvent for each event of the scan
+//
+// onEvent runs on the goroutine of ScanStream, one event at a time, before
+// the scan continues.
+func ScanStream(ctx context.Context, options ScanOptions, onEvent func(Event)) (*ScanResult, error) {
+	home, optionsJSON, err := prepare(options)
+	if err != nil {
+		return nil, err
+	}
+	defer C.free(unsafe.Pointer(home))
+	defer C.free(unsafe.Pointer(optionsJSON))
+
+	cancellation, release := cancelOnDone(ctx)
+	defer release()
+
+	state := &stream{onEvent: onEvent}
+	handle := cgo.NewHandle(state)
+	defer handle.Delete()
+
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	// The callback only runs during the call, so &handle may be passed
+	resultPtr := C.aicred_scan_with_callback(
+		home,
+		optionsJSON,
+		(C.aicred_scan_callback_t)(unsafe.Pointer(C.aicredGoScanEvent)),
+		unsafe.Pointer(&handle),
+		cancellation,
+	)
+	if state.panicked != nil {
+		C.aicred_free(resultPtr)
+		panic(state.panicked)
+	}
+	if resultPtr == nil {
+		return nil, scanError(ctx)
+	}
+	return decodeResult(resultPtr)
+}
+
+//export aicredGoScanEvent
+func aicredGoScanEvent(event *C.aicred_scan_event_t, userData unsafe.Pointer) {
+	state := (*cgo.Handle)(userData).Value().(*stream)
+	if state.panicked != nil || state.onEvent == nil {
+		return
+	}
+	// A panic must not unwind through the frames of the library
+	defer func() {
+		if r := recover(); r != nil {
+			state.panicked = r
+		}
+	}()
+
+	switch event.kind {
+	case C.AICRED_SCAN_EVENT_PROGRESS:
+		progress := event.progress
+		state.onEvent(Event{Progress: &Progress{
+			Scanner:   C.GoString(progress.scanner),
+			Root:      C.GoString(progress.root),
+			Completed: int(progress.completed),
+			Total:     int(progress.total),
+		}})
+	case C.AICRED_SCAN_EVENT_KEY:
+		key := event.key
+		state.onEvent(Event{Key: &DiscoveredKey{
+			Provider:  C.GoString(key.provider),
+			Source:    C.GoString(key.source),
+			Line:      int(key.line),
+			ValueType: C.GoString(key.value_type),
+			Value:     C.GoString(key.value),
+		}})
+	}
+}
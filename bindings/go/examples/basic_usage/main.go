@@ -1,6 +1,7 @@
 package main
 
 import (
+	"context"
 	"encoding/json"
 	"fmt"
 	"log"
@@ -32,7 +33,12 @@ func main() {
 		OnlyProviders:     []string{"openai", "anthropic"},
 	}
 
-	result, err := aicred.Scan(options)
+	result, err := aicred.ScanStream(context.Background(), options, func(event aicred.Event) {
+		if event.Progress != nil {
+			fmt.Printf("  [%d/%d] %s\n",
+				event.Progress.Completed+1, event.Progress.Total, event.Progress.Scanner)
+		}
+	})
 	if err != nil {
 		log.Fatalf("Scan failed: %v", err)
 	}
@@ -44,8 +50,8 @@ func main() {
 	if len(result.Keys) > 0 {
 		fmt.Println("\nDiscovered Keys:")
 		for _, key := range result.Keys {
-			fmt.Printf("  %s: %s (confidence: %s)\n",
-				key.Provider, key.Redacted, key.Confidence)
+			fmt.Printf("  %s %s: %s (confidence: %s)\n",
+				key.Provider, key.ValueType, key.Redacted, key.Confidence)
 		}
 	}
 
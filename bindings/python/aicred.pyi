@@ -1,4 +1,5 @@
-from typing import Optional, List, Dict, Any
+from dataclasses import dataclass
+from typing import Optional, List, Dict, Any, Awaitable, Callable, Iterator, Union, overload
 
 class TokenCost:
     """Token cost tracking for model usage."""
@@ -62,12 +63,10 @@ class Model:
     cost: Optional[TokenCost]
     metadata: Optional[Dict[str, Any]]
 
-    def set_quantization(self, quantization: str) -> None: ...
-    def set_context_window(self, size: int) -> None: ...
     def set_temperature(self, temperature: float) -> None: ...
+    def add_tag(self, tag: str) -> None: ...
     def set_tags(self, tags: List[str]) -> None: ...
     def set_cost(self, cost: TokenCost) -> None: ...
-    def set_metadata(self, metadata: Dict[str, Any]) -> None: ...
     def validate(self) -> None: ...
     def supports_text_generation(self) -> bool: ...
     def supports_image_generation(self) -> bool: ...
@@ -95,7 +94,6 @@ class ProviderInstance:
     def add_keys(self, keys: List[Any]) -> None: ...
     def add_model(self, model: Model) -> None: ...
     def add_models(self, models: List[Model]) -> None: ...
-    def set_metadata(self, metadata: Dict[str, str]) -> None: ...
     def set_active(self, active: bool) -> None: ...
     def key_count(self) -> int: ...
     def model_count(self) -> int: ...
@@ -106,6 +104,16 @@ class ProviderInstances:
     """Collection of provider instances with lookup and filtering capabilities."""
 
     def __init__(self) -> None: ...
+    @staticmethod
+    def load(home_dir: Optional[str] = None) -> ProviderInstances:
+        """Load the instances of the configuration store, keys as stored."""
+        ...
+    def save(self, home_dir: Optional[str] = None) -> None:
+        """
+        Add the instances to the configuration store, replacing the stored
+        ones with their IDs; instances not in the collection are kept.
+        """
+        ...
     def add_instance(self, instance: ProviderInstance) -> None: ...
     def add_or_replace_instance(self, instance: ProviderInstance) -> None: ...
     def get_instance(self, id: str) -> Optional[ProviderInstance]: ...
@@ -123,41 +131,513 @@ class ProviderInstances:
     def validate(self) -> None: ...
     def clear(self) -> None: ...
     def merge(self, other: ProviderInstances) -> None: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[ProviderInstance]:
+        """Iterate over the instances in order of their IDs."""
+        ...
+    def __contains__(self, id: str) -> bool: ...
+    def __repr__(self) -> str: ...
+
+class AicredError(RuntimeError):
+    """Error of the core library."""
+    code: int
+    retryable: bool
+
+class ScanError(AicredError):
+    """Files could not be read or a scan did not complete, as when it was cancelled."""
+
+class ConfigError(AicredError):
+    """The configuration or the store holding it is invalid or cannot be used."""
+
+class PluginError(AicredError):
+    """A provider or scanner plugin failed or could not be registered."""
+
+class NetworkError(AicredError):
+    """A request to a provider failed."""
+
+class IoDeniedError(ScanError):
+    """A file could not be accessed for lack of permission."""
+
+class ParseFailedError(ScanError):
+    """A file could not be parsed."""
+
+class PluginConflictError(PluginError):
+    """A plugin name is already registered."""
+
+class StoreCorruptError(ConfigError):
+    """A file of the configuration store is corrupt."""
+
+class NetworkTimeoutError(NetworkError):
+    """A network request got no answer in time."""
+
+class DiscoveredKey:
+    """A credential found by a scan."""
+
+    @property
+    def provider(self) -> str: ...
+    @property
+    def value(self) -> str: ...
+    @property
+    def value_type(self) -> str: ...
+    @property
+    def confidence(self) -> str: ...
+    @property
+    def risk(self) -> str: ...
+    @property
+    def hash(self) -> str: ...
+    @property
+    def source_file(self) -> str: ...
+    @property
+    def source_line(self) -> Optional[int]: ...
+    @property
+    def scanner(self) -> Optional[str]: ...
+    @property
+    def discovered_at(self) -> str: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    def __repr__(self) -> str: ...
+
+class ConfigInstance:
+    """An application configuration found by a scan."""
+
+    @property
+    def instance_id(self) -> str: ...
+    @property
+    def app_name(self) -> str: ...
+    @property
+    def config_path(self) -> str: ...
+    @property
+    def app_version(self) -> Optional[str]: ...
+    @property
+    def keys(self) -> List[DiscoveredKey]: ...
+    @property
+    def metadata(self) -> Dict[str, str]: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[DiscoveredKey]:
+        """Iterate over the keys found in the configuration."""
+        ...
+    def __repr__(self) -> str: ...
+
+class ScanResult:
+    """Result of scan(); indexing it like a dictionary still works."""
+
+    @property
+    def keys(self) -> List[DiscoveredKey]: ...
+    @property
+    def instances(self) -> List[ConfigInstance]: ...
+    @property
+    def summary(self) -> Dict[str, Any]: ...
+    @property
+    def home_directory(self) -> str: ...
+    @property
+    def providers_scanned(self) -> List[str]: ...
+    @property
+    def scan_started_at(self) -> str: ...
+    @property
+    def scan_completed_at(self) -> str: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    def to_dataframe(self, library: str = "pandas") -> Any:
+        """
+        The keys as a pandas or polars DataFrame, one row per key of the result
+        and of its configurations, with provider, value_type, confidence, risk,
+        path, line, app_name and instance_id columns but no values.
+
+        Raises:
+            ImportError: If the library is not installed
+            ValueError: If the library is neither "pandas" nor "polars"
+        """
+        ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[DiscoveredKey]:
+        """Iterate over the keys found; iterate over to_dict() for the old dictionary keys."""
+        ...
+    def __getitem__(self, key: str) -> Any: ...
+    def __contains__(self, key: str) -> bool: ...
+    def __repr__(self) -> str: ...
+
+@dataclass(frozen=True)
+class DiscoveredKeyData:
+    """A DiscoveredKey as a dataclass, returned by as_dataclasses()."""
+
+    provider: str
+    value: str
+    value_type: str
+    confidence: str
+    risk: str
+    hash: str
+    source_file: str
+    source_line: Optional[int]
+    scanner: Optional[str]
+    discovered_at: str
+
+@dataclass(frozen=True)
+class ConfigInstanceData:
+    """A ConfigInstance as a dataclass, returned by as_dataclasses()."""
+
+    instance_id: str
+    app_name: str
+    config_path: str
+    app_version: Optional[str]
+    keys: List[DiscoveredKeyData]
+    metadata: Dict[str, str]
+
+@dataclass(frozen=True)
+class ScanResultData:
+    """A ScanResult as a dataclass, returned by as_dataclasses()."""
+
+    keys: List[DiscoveredKeyData]
+    instances: List[ConfigInstanceData]
+    summary: Dict[str, Any]
+    home_directory: str
+    providers_scanned: List[str]
+    scan_started_at: str
+    scan_completed_at: str
+
+@overload
+def as_dataclasses(value: ScanResult) -> ScanResultData: ...
+@overload
+def as_dataclasses(value: ConfigInstance) -> ConfigInstanceData: ...
+@overload
+def as_dataclasses(value: DiscoveredKey) -> DiscoveredKeyData: ...
+@overload
+def as_dataclasses(value: List[ScanResult]) -> List[ScanResultData]: ...
+@overload
+def as_dataclasses(value: List[ConfigInstance]) -> List[ConfigInstanceData]: ...
+@overload
+def as_dataclasses(value: List[DiscoveredKey]) -> List[DiscoveredKeyData]: ...
+def as_dataclasses(value: Any) -> Any:
+    """
+    Convert a result, or a list of results, into frozen dataclasses with the
+    same fields, for tools that expect plain dataclasses such as
+    dataclasses.asdict().
+
+    Raises:
+        TypeError: If the value is not a result or a list of results
+    """
+    ...
+
+class ScanOptions:
+    """Options of a scan; every argument is also an attribute."""
+
+    def __init__(
+        self,
+        home_dir: Optional[str] = None,
+        *,
+        extra_roots: List[str] = ...,
+        redaction: Optional[str] = None,
+        max_file_size: int = 1048576,
+        only_providers: Optional[List[str]] = None,
+        exclude_providers: Optional[List[str]] = None,
+        only_value_types: Optional[List[str]] = None,
+        scan_archives: bool = False,
+        probe_models: bool = False,
+        probe_timeout_secs: int = 30,
+        parallelism: Optional[int] = None,
+        proxy: Optional[str] = None,
+        ca_bundle: Optional[str] = None,
+        offline: bool = False,
+    ) -> None:
+        """
+        Args:
+            home_dir: Home directory to scan. Defaults to the user's.
+            extra_roots: Further directories scanned as home directories
+            redaction: "none", "mask", "last4", "first8" or "fingerprint".
+                Default: "first8"
+            max_file_size: Largest file read, in bytes
+            only_providers: Only scan for these providers
+            exclude_providers: Do not scan for these providers
+            only_value_types: Only report values of these types, such as "ApiKey"
+            scan_archives: Extract and scan archives among the scan roots
+            probe_models: Probe the instances found for their models
+            probe_timeout_secs: Seconds model probing gives up after
+            parallelism: Most model probes of one provider run at once
+            proxy: Proxy of model probing
+            ca_bundle: PEM file of further CA certificates of model probing
+            offline: Never touch the network, skipping model probing
+
+        Raises:
+            ValueError: If the redaction, a value type or the parallelism is invalid
+        """
+        ...
+
+    home_dir: Optional[str]
+    extra_roots: List[str]
+    redaction: str
+    max_file_size: int
+    only_providers: Optional[List[str]]
+    exclude_providers: Optional[List[str]]
+    only_value_types: Optional[List[str]]
+    scan_archives: bool
+    probe_models: bool
+    probe_timeout_secs: int
+    parallelism: Optional[int]
+    proxy: Optional[str]
+    ca_bundle: Optional[str]
+    offline: bool
+
     def __repr__(self) -> str: ...
 
 def scan(
-    home_dir: Optional[str] = None,
-    include_full_values: bool = False,
-    max_file_size: int = 1048576,
+    options: Union[ScanOptions, str, None] = None,
+    include_full_values: Optional[bool] = None,
+    max_file_size: Optional[int] = None,
     only_providers: Optional[List[str]] = None,
     exclude_providers: Optional[List[str]] = None,
-) -> Dict[str, Any]:
+    redaction: Optional[str] = None,
+    on_progress: Optional[Callable[[str, str, int, int], None]] = None,
+    *,
+    home_dir: Optional[str] = None,
+) -> ScanResult:
     """
     Scan for AI credentials and configurations.
 
     Args:
-        home_dir: Home directory to scan. Defaults to user's home directory.
-        include_full_values: Include full secret values (DANGEROUS). Default: False
-        max_file_size: Maximum file size to read in bytes. Default: 1MB
-        only_providers: Only scan these providers
-        exclude_providers: Exclude these providers
+        options: Options of the scan. A string is taken as the home directory.
+        on_progress: Called as on_progress(scanner, root, completed, total)
+            before each scanner runs. An exception it raises stops the scan.
+
+    The separate arguments home_dir, include_full_values (DANGEROUS),
+    max_file_size, only_providers, exclude_providers and redaction are the
+    options as scan() took them before ScanOptions; they cannot be combined
+    with a ScanOptions.
 
     Returns:
-       Dictionary containing:
-       - keys: List of discovered keys
-       - config_instances: List of config instances
-       - home_directory: Scanned home directory
-       - scan_started_at: Timestamp of scan
-       - providers_scanned: List of providers scanned
+       The keys and application configurations found. ``to_dict()`` gives
+       the result as a dictionary.
 
     Example:
-        >>> result = scan()
-        >>> print(f"Found {len(result['keys'])} keys")
-        >>> for key in result['keys']:
-        ...     print(f"{key['provider']}: {key['redacted']}")
+        >>> result = scan(ScanOptions(redaction="last4"))
+        >>> print(f"Found {len(result.keys)} keys")
+        >>> for key in result.keys:
+        ...     print(f"{key.provider}: {key.value}")
+    """
+    ...
+
+class ScanIterator(Iterator[DiscoveredKey]):
+    """Keys of a running scan, yielded as they are found."""
+
+    @property
+    def result(self) -> Optional[ScanResult]:
+        """The whole result, once exhausted."""
+        ...
+    def cancel(self) -> None: ...
+    def __iter__(self) -> ScanIterator: ...
+    def __next__(self) -> DiscoveredKey: ...
+
+def scan_iter(
+    options: Union[ScanOptions, str, None] = None,
+    include_full_values: Optional[bool] = None,
+    max_file_size: Optional[int] = None,
+    only_providers: Optional[List[str]] = None,
+    exclude_providers: Optional[List[str]] = None,
+    redaction: Optional[str] = None,
+    on_progress: Optional[Callable[[str, str, int, int], None]] = None,
+    *,
+    home_dir: Optional[str] = None,
+) -> ScanIterator:
+    """
+    Scan like scan(), yielding each key as it is found.
+
+    The scan runs on a thread of its own and on_progress is called on the
+    iterating thread. Dropping the iterator early stops the scan.
+    """
+    ...
+
+def scan_async(
+    options: Union[ScanOptions, str, None] = None,
+    include_full_values: Optional[bool] = None,
+    max_file_size: Optional[int] = None,
+    only_providers: Optional[List[str]] = None,
+    exclude_providers: Optional[List[str]] = None,
+    redaction: Optional[str] = None,
+    *,
+    home_dir: Optional[str] = None,
+) -> Awaitable[ScanResult]:
+    """
+    Scan like scan() without blocking the running event loop.
+
+    Cancelling the awaited future stops the scan.
+    """
+    ...
+
+def probe_models_async(
+    provider: str,
+    api_key: str,
+    base_url: Optional[str] = None,
+    timeout: float = 30.0,
+) -> Awaitable[List[Dict[str, Any]]]:
+    """
+    Probe a provider for the models an API key can use, without blocking the
+    running event loop.
+    """
+    ...
+
+def remove_instance(id: str, home_dir: Optional[str] = None) -> bool:
+    """
+    Remove a provider instance from the configuration store, with the label
+    and tag assignments pointing at it. Returns whether an instance had the ID.
+    """
+    ...
+
+def resolve_label(name: str, home_dir: Optional[str] = None) -> Dict[str, Any]:
+    """
+    Resolve a label to the instance, with its API key revealed, and model it
+    points at, following its fallbacks like `aicred resolve`.
     """
     ...
 
+def list_labels(home_dir: Optional[str] = None) -> List[Dict[str, Any]]:
+    """List the label assignments, with their descriptions."""
+    ...
+
+def set_label(
+    name: str,
+    instance_id: str,
+    model_id: Optional[str] = None,
+    description: Optional[str] = None,
+    home_dir: Optional[str] = None,
+) -> bool:
+    """
+    Point a label at an instance, or one of its models, replacing its target
+    and fallbacks. Returns whether an existing label was moved.
+    """
+    ...
+
+def unset_label(name: str, home_dir: Optional[str] = None) -> bool:
+    """Remove a label. Returns whether it existed."""
+    ...
+
+def list_tags(home_dir: Optional[str] = None) -> List[Dict[str, Any]]:
+    """List the tags, with the targets they are assigned to."""
+    ...
+
+def add_tag(
+    name: str, description: Optional[str] = None, home_dir: Optional[str] = None
+) -> None:
+    """Create a tag. Raises ValueError if a tag already has the name."""
+    ...
+
+def remove_tag(name: str, home_dir: Optional[str] = None) -> bool:
+    """Remove a tag and its assignments. Returns whether it existed."""
+    ...
+
+def assign_tag(
+    name: str,
+    instance_id: str,
+    model_id: Optional[str] = None,
+    home_dir: Optional[str] = None,
+) -> None:
+    """Assign a tag to an instance, or one of its models."""
+    ...
+
+def unassign_tag(
+    name: str,
+    instance_id: str,
+    model_id: Optional[str] = None,
+    home_dir: Optional[str] = None,
+) -> bool:
+    """Remove a tag from an instance or model. Returns whether it was assigned there."""
+    ...
+
+class ModelPricing:
+    """Price of a model per token."""
+
+    @property
+    def input_cost_per_token(self) -> float: ...
+    @property
+    def output_cost_per_token(self) -> float: ...
+    @property
+    def input_cost_per_million(self) -> float: ...
+    @property
+    def output_cost_per_million(self) -> float: ...
+    @property
+    def currency(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+class ModelCost:
+    """Cost of a number of tokens, returned by ModelEntry.cost()."""
+
+    @property
+    def total_cost(self) -> float: ...
+    @property
+    def input_cost(self) -> float: ...
+    @property
+    def output_cost(self) -> float: ...
+    @property
+    def currency(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+class ModelEntry:
+    """A model known to the registry."""
+
+    @property
+    def id(self) -> str: ...
+    @property
+    def provider(self) -> str: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def capabilities(self) -> List[str]:
+        """Names of the supported capabilities, such as "vision"."""
+        ...
+    @property
+    def context_window(self) -> Optional[int]: ...
+    @property
+    def pricing(self) -> Optional[ModelPricing]: ...
+    @property
+    def status(self) -> str:
+        """Lifecycle status today: "active", "deprecated" or "archived"."""
+        ...
+    @property
+    def pricing_updated_at(self) -> Optional[str]: ...
+    @property
+    def deprecated_at(self) -> Optional[str]: ...
+    @property
+    def sunset_at(self) -> Optional[str]: ...
+    @property
+    def aliases(self) -> List[str]: ...
+    def supports(self, capability: str) -> bool: ...
+    def cost(self, input_tokens: int, output_tokens: int = 0) -> Optional[ModelCost]: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    def __repr__(self) -> str: ...
+
+class ModelRegistry:
+    """
+    Registry of well-known models with their capabilities and pricing.
+
+    The builtin entries, then model data cached by a refresh, then the
+    override files of the user with the home directory, by default the current
+    user; builtin=True gives the builtin entries alone.
+    """
+
+    def __init__(self, home_dir: Optional[str] = None, *, builtin: bool = False) -> None: ...
+    def get(self, model_id: str) -> Optional[ModelEntry]:
+        """Look up a model by ID, or an alias or variant such as "openai/gpt-4o"."""
+        ...
+    def canonical_id(self, model_id: str) -> str: ...
+    def all(self) -> List[ModelEntry]: ...
+    def by_provider(self, provider: str) -> List[ModelEntry]: ...
+    def by_capability(self, capability: str) -> List[ModelEntry]:
+        """Models supporting a capability; raises ValueError if it is unknown."""
+        ...
+    def search(self, text: str, provider: Optional[str] = None) -> List[ModelEntry]: ...
+    def recommend(
+        self,
+        capabilities: List[str] = ...,
+        max_input_cost: Optional[float] = None,
+        max_output_cost: Optional[float] = None,
+        min_context_window: Optional[int] = None,
+    ) -> List[ModelEntry]:
+        """Active models meeting the requirements, cheapest first; prices per million tokens."""
+        ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[ModelEntry]: ...
+    def __contains__(self, model_id: str) -> bool: ...
+    def __repr__(self) -> str: ...
+
+models: ModelRegistry
+"""The model registry of the current user, loaded on import."""
+
 def version() -> str:
     """Get library version."""
     ...
@@ -167,17 +647,24 @@ def list_providers() -> List[str]:
     ...
 
 def list_scanners() -> List[str]:
-    """List available application scanners."""
+    """List available application scanners, those registered from Python last."""
     ...
 
-def migrate_provider_configs(configs: List[Any]) -> ProviderInstances:
+def register_scanner(scanner: Any) -> None:
     """
-    Migration utilities for converting legacy configurations to new instance-based architecture.
+    Register an application scanner written in Python, run by every later scan.
 
-    Args:
-        configs: List of legacy provider configuration objects
+    The object needs a `name`, `scan_paths(home_dir)` returning the paths to
+    read, and `parse_config(path, content)` returning the keys found as
+    dictionaries with `provider` and `value`, and optionally `value_type`
+    (default "ApiKey"), `confidence` from 0 to 1 (default 0.8) and `line`. It
+    may also have an `app_name` and `can_handle_file(path)`.
 
-    Returns:
-        ProviderInstances collection with migrated instances
+    Raises:
+        PluginConflictError: If a scanner already has the name
     """
     ...
+
+def unregister_scanner(name: str) -> bool:
+    """Unregister a scanner registered from Python. Returns whether one had the name."""
+    ...
@@ -29,24 +29,22 @@ def main():
     )
 
     # Display results
-    print(f"\nFound {len(result['keys'])} keys")
-    print(f"Found {len(result['config_instances'])} config instances")
+    print(f"\nFound {len(result.keys)} keys")
+    print(f"Found {len(result.instances)} config instances")
 
-    if result["keys"]:
+    if result.keys:
         print("\nDiscovered Keys:")
-        for key in result["keys"]:
-            print(
-                f"  {key['provider']}: {key['redacted']} (confidence: {key['confidence']})"
-            )
+        for key in result.keys:
+            print(f"  {key.provider}: {key.value} (confidence: {key.confidence})")
 
-    if result["config_instances"]:
+    if result.instances:
         print("\nConfig Instances:")
-        for instance in result["config_instances"]:
-            print(f"  {instance['app_name']}: {instance['config_path']}")
+        for instance in result.instances:
+            print(f"  {instance.app_name}: {instance.config_path}")
 
     # Save to JSON
     with open("scan_result.json", "w") as f:
-        json.dump(result, f, indent=2)
+        json.dump(result.to_dict(), f, indent=2)
     # Set restrictive permissions (owner read/write only)
     os.chmod("scan_result.json", 0o600)
     print("\nResults saved to scan_result.json")
@@ -0,0 +1,95 @@
+//! Awaitable scans and probes for asyncio
+//!
+//! The work runs on a thread of its own and completes an `asyncio.Future` of
+//! the running event loop through `call_soon_threadsafe`, so callers need
+//! neither an executor nor a thread pool. Cancelling the future cancels the
+//! scan.
+
+use crate::core_error;
+use aicred_core::{error::Result, CancellationToken};
+use pyo3::prelude::*;
+use std::sync::{Mutex, PoisonError};
+
+/// Sets the outcome of a future on its event loop, unless it is done already
+#[pyclass]
+struct Completion {
+    future: Py<PyAny>,
+    outcome: Mutex<Option<PyResult<Py<PyAny>>>>,
+}
+
+#[pymethods]
+impl Completion {
+    fn __call__(&self, py: Python<'_>) -> PyResult<()> {
+        let future = self.future.bind(py);
+        if future.call_method0("done")?.is_truthy()? {
+            return Ok(());
+        }
+        let outcome = self
+            .outcome
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        match outcome {
+            Some(Ok(value)) => future.call_method1("set_result", (value,))?,
+            Some(Err(err)) => future.call_method1("set_exception", (err.into_value(py),))?,
+            None => return Ok(()),
+        };
+        Ok(())
+    }
+}
+
+/// Cancels a scan once its future is cancelled
+#[pyclass]
+struct CancelOnDone(CancellationToken);
+
+#[pymethods]
+impl CancelOnDone {
+    fn __call__(&self, future: &Bound<'_, PyAny>) -> PyResult<()> {
+        if future.call_method0("cancelled")?.is_truthy()? {
+            self.0.cancel();
+        }
+        Ok(())
+    }
+}
+
+/// Runs `work` on a thread and returns a future of the running event loop
+/// that completes with its result, converted by `convert`
+pub(crate) fn spawn_future<T, W, C>(
+    py: Python<'_>,
+    cancellation: Option<CancellationToken>,
+    work: W,
+    convert: C,
+) -> PyResult<Py<PyAny>>
+where
+    T: Send + 'static,
+    W: FnOnce() -> Result<T> + Send + 'static,
+    C: FnOnce(Python<'_>, T) -> PyResult<Py<PyAny>> + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future = event_loop.call_method0("create_future")?;
+    if let Some(cancellation) = cancellation {
+        future.call_method1("add_done_callback", (CancelOnDone(cancellation),))?;
+    }
+
+    let event_loop = event_loop.unbind();
+    let thread_future = future.clone().unbind();
+    std::thread::Builder::new()
+        .name("aicred-async".to_string())
+        .spawn(move || {
+            let outcome = work();
+            Python::with_gil(|py| {
+                let completion = Completion {
+                    future: thread_future,
+                    outcome: Mutex::new(Some(match outcome {
+                        Ok(value) => convert(py, value),
+                        Err(e) => Err(core_error(&e)),
+                    })),
+                };
+                // Fails only once the loop is closed, when nobody awaits
+                let _ = event_loop.call_method1(py, "call_soon_threadsafe", (completion,));
+            });
+        })
+        .map_err(|e| core_error(&e.into()))?;
+
+    Ok(future.unbind())
+}
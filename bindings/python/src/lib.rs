@@ -3,7 +3,7 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::manual_range_contains)]
 
-use aicred_core::{scan as core_scan, ScanOptions};
+use aicred_core::{scan_with_progress, CancellationToken, ScanEvent, ScanOptions};
 // TODO: Core types will be mapped to Py* wrapper types when implementing full functionality
 // Currently only scan and ScanOptions are used directly
 
@@ -13,6 +13,25 @@ use pyo3::prelude::*;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+#[cfg(feature = "python")]
+mod aio;
+#[cfg(feature = "python")]
+mod options;
+#[cfg(feature = "python")]
+mod registry;
+#[cfg(feature = "python")]
+mod results;
+#[cfg(feature = "python")]
+pub use results::{as_dataclasses, ConfigInstance, DiscoveredKey, ScanResult};
+#[cfg(feature = "python")]
+mod scanners;
+#[cfg(feature = "python")]
+mod store;
+#[cfg(feature = "python")]
+mod streaming;
+#[cfg(feature = "python")]
+pub use streaming::ScanIterator;
+
 #[cfg(not(feature = "python"))]
 pub mod python_stub {
     pub type PyResult<T> = Result<T, String>;
@@ -304,6 +323,32 @@ impl PyModel {
     }
 }
 
+impl PyModel {
+    /// Copies the model, metadata included
+    fn duplicate(&self) -> Self {
+        let metadata = Python::with_gil(|py| {
+            self.metadata.as_ref().map(|meta| {
+                meta.iter()
+                    .map(|(k, v)| (k.clone(), v.clone_ref(py)))
+                    .collect::<HashMap<String, Py<PyAny>>>()
+            })
+        });
+
+        Self {
+            model_id: self.model_id.clone(),
+            provider_instance_id: self.provider_instance_id.clone(),
+            name: self.name.clone(),
+            quantization: self.quantization.clone(),
+            context_window: self.context_window,
+            capabilities: self.capabilities.clone(),
+            temperature: self.temperature,
+            tags: self.tags.clone(),
+            cost: self.cost.clone(),
+            metadata,
+        }
+    }
+}
+
 /// Provider instance configuration with enhanced metadata and model management.
 #[pyclass]
 #[derive(Debug)]
@@ -356,26 +401,7 @@ impl PyProviderInstance {
     }
 
     fn add_model(&mut self, model: &PyModel) {
-        let metadata = Python::with_gil(|py| {
-            model.metadata.as_ref().map(|meta| {
-                meta.iter()
-                    .map(|(k, v)| (k.clone(), v.clone_ref(py)))
-                    .collect::<HashMap<String, Py<PyAny>>>()
-            })
-        });
-
-        self.models.push(PyModel {
-            model_id: model.model_id.clone(),
-            provider_instance_id: model.provider_instance_id.clone(),
-            name: model.name.clone(),
-            quantization: model.quantization.clone(),
-            context_window: model.context_window,
-            capabilities: model.capabilities.clone(),
-            temperature: model.temperature,
-            tags: model.tags.clone(),
-            cost: model.cost.clone(),
-            metadata,
-        });
+        self.models.push(model.duplicate());
         self.updated_at = Some(chrono::Utc::now().to_rfc3339());
     }
 
@@ -443,6 +469,30 @@ impl PyProviderInstance {
     }
 }
 
+impl PyProviderInstance {
+    /// Copies the instance, keys and models included
+    fn duplicate(&self) -> Self {
+        let keys = Python::with_gil(|py| {
+            self.keys
+                .as_ref()
+                .map(|keys| keys.iter().map(|key| key.clone_ref(py)).collect())
+        });
+
+        Self {
+            id: self.id.clone(),
+            display_name: self.display_name.clone(),
+            provider_type: self.provider_type.clone(),
+            base_url: self.base_url.clone(),
+            keys,
+            models: self.models.iter().map(PyModel::duplicate).collect(),
+            metadata: self.metadata.clone(),
+            active: self.active,
+            created_at: self.created_at.clone(),
+            updated_at: self.updated_at.clone(),
+        }
+    }
+}
+
 /// Collection of provider instances with lookup and filtering capabilities.
 #[pyclass]
 #[derive(Debug)]
@@ -467,55 +517,18 @@ impl PyProviderInstances {
             )));
         }
 
-        self.instances.insert(
-            instance.id.clone(),
-            PyProviderInstance {
-                id: instance.id.clone(),
-                display_name: instance.display_name.clone(),
-                provider_type: instance.provider_type.clone(),
-                base_url: instance.base_url.clone(),
-                keys: None,         // Simplified - keys will be added separately
-                models: Vec::new(), // Will be added separately
-                metadata: instance.metadata.clone(),
-                active: instance.active,
-                created_at: instance.created_at.clone(),
-                updated_at: instance.updated_at.clone(),
-            },
-        );
+        self.instances
+            .insert(instance.id.clone(), instance.duplicate());
         Ok(())
     }
 
     fn add_or_replace_instance(&mut self, instance: &PyProviderInstance) {
-        self.instances.insert(
-            instance.id.clone(),
-            PyProviderInstance {
-                id: instance.id.clone(),
-                display_name: instance.display_name.clone(),
-                provider_type: instance.provider_type.clone(),
-                base_url: instance.base_url.clone(),
-                keys: None,         // Simplified - keys will be added separately
-                models: Vec::new(), // Will be added separately
-                metadata: instance.metadata.clone(),
-                active: instance.active,
-                created_at: instance.created_at.clone(),
-                updated_at: instance.updated_at.clone(),
-            },
-        );
+        self.instances
+            .insert(instance.id.clone(), instance.duplicate());
     }
 
     fn get_instance(&self, id: &str) -> Option<PyProviderInstance> {
-        self.instances.get(id).map(|instance| PyProviderInstance {
-            id: instance.id.clone(),
-            display_name: instance.display_name.clone(),
-            provider_type: instance.provider_type.clone(),
-            base_url: instance.base_url.clone(),
-            keys: None,         // Simplified - keys will be added separately
-            models: Vec::new(), // Simplified - models will be added separately
-            metadata: instance.metadata.clone(),
-            active: instance.active,
-            created_at: instance.created_at.clone(),
-            updated_at: instance.updated_at.clone(),
-        })
+        self.instances.get(id).map(PyProviderInstance::duplicate)
     }
 
     fn remove_instance(&mut self, id: &str) -> Option<PyProviderInstance> {
@@ -525,18 +538,7 @@ impl PyProviderInstances {
     fn all_instances(&self) -> Vec<PyProviderInstance> {
         self.instances
             .values()
-            .map(|instance| PyProviderInstance {
-                id: instance.id.clone(),
-                display_name: instance.display_name.clone(),
-                provider_type: instance.provider_type.clone(),
-                base_url: instance.base_url.clone(),
-                keys: None,         // Simplified - keys will be added separately
-                models: Vec::new(), // Simplified - models will be added separately
-                metadata: instance.metadata.clone(),
-                active: instance.active,
-                created_at: instance.created_at.clone(),
-                updated_at: instance.updated_at.clone(),
-            })
+            .map(PyProviderInstance::duplicate)
             .collect()
     }
 
@@ -544,18 +546,7 @@ impl PyProviderInstances {
         self.instances
             .values()
             .filter(|instance| instance.provider_type == provider_type)
-            .map(|instance| PyProviderInstance {
-                id: instance.id.clone(),
-                display_name: instance.display_name.clone(),
-                provider_type: instance.provider_type.clone(),
-                base_url: instance.base_url.clone(),
-                keys: None,         // Simplified - keys will be added separately
-                models: Vec::new(), // Simplified - models will be added separately
-                metadata: instance.metadata.clone(),
-                active: instance.active,
-                created_at: instance.created_at.clone(),
-                updated_at: instance.updated_at.clone(),
-            })
+            .map(PyProviderInstance::duplicate)
             .collect()
     }
 
@@ -563,18 +554,7 @@ impl PyProviderInstances {
         self.instances
             .values()
             .filter(|instance| instance.active)
-            .map(|instance| PyProviderInstance {
-                id: instance.id.clone(),
-                display_name: instance.display_name.clone(),
-                provider_type: instance.provider_type.clone(),
-                base_url: instance.base_url.clone(),
-                keys: None,         // Simplified - keys will be added separately
-                models: Vec::new(), // Simplified - models will be added separately
-                metadata: instance.metadata.clone(),
-                active: instance.active,
-                created_at: instance.created_at.clone(),
-                updated_at: instance.updated_at.clone(),
-            })
+            .map(PyProviderInstance::duplicate)
             .collect()
     }
 
@@ -582,18 +562,7 @@ impl PyProviderInstances {
         self.instances
             .values()
             .filter(|instance| instance.active && instance.provider_type == provider_type)
-            .map(|instance| PyProviderInstance {
-                id: instance.id.clone(),
-                display_name: instance.display_name.clone(),
-                provider_type: instance.provider_type.clone(),
-                base_url: instance.base_url.clone(),
-                keys: None,         // Simplified - keys will be added separately
-                models: Vec::new(), // Simplified - models will be added separately
-                metadata: instance.metadata.clone(),
-                active: instance.active,
-                created_at: instance.created_at.clone(),
-                updated_at: instance.updated_at.clone(),
-            })
+            .map(PyProviderInstance::duplicate)
             .collect()
     }
 
@@ -644,21 +613,7 @@ impl PyProviderInstances {
 
     fn merge(&mut self, other: &PyProviderInstances) {
         for (id, instance) in &other.instances {
-            self.instances.insert(
-                id.clone(),
-                PyProviderInstance {
-                    id: instance.id.clone(),
-                    display_name: instance.display_name.clone(),
-                    provider_type: instance.provider_type.clone(),
-                    base_url: instance.base_url.clone(),
-                    keys: None,         // Simplified - keys will be added separately
-                    models: Vec::new(), // Will be added separately
-                    metadata: instance.metadata.clone(),
-                    active: instance.active,
-                    created_at: instance.created_at.clone(),
-                    updated_at: instance.updated_at.clone(),
-                },
-            );
+            self.instances.insert(id.clone(), instance.duplicate());
         }
     }
 
@@ -671,45 +626,200 @@ impl PyProviderInstances {
     }
 }
 
-/// Wrapper function to provide 'scan' function with expected name
+/// Checks that a home directory to scan exists and can be read
+fn check_home_dir(home_dir: Option<&str>) -> PyResult<()> {
+    use std::io::{Error, ErrorKind};
+
+    if let Some(home_dir_str) = home_dir {
+        let path = PathBuf::from(home_dir_str);
+        let error = if !path.exists() {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Home directory does not exist: {}", home_dir_str),
+            )
+        } else if !path.is_dir() {
+            Error::new(
+                ErrorKind::NotADirectory,
+                format!("Home directory is not a directory: {}", home_dir_str),
+            )
+        } else {
+            match std::fs::read_dir(&path) {
+                Ok(_) => return Ok(()),
+                Err(e) => Error::new(
+                    e.kind(),
+                    format!("Cannot read home directory {}: {}", home_dir_str, e),
+                ),
+            }
+        };
+        return Err(core_error(&error.into()));
+    }
+    Ok(())
+}
+
+/// Scan for GenAI credentials and configurations
+///
+/// Args:
+///     options (ScanOptions, optional): Options of the scan. A string is taken
+///         as the home directory, as before ScanOptions existed.
+///     on_progress (callable, optional): Called as
+///         `on_progress(scanner, root, completed, total)` before each scanner
+///         runs; an exception it raises stops the scan and is raised again
+///
+/// The separate arguments `home_dir`, `include_full_values`, `max_file_size`,
+/// `only_providers`, `exclude_providers` and `redaction` still work in place
+/// of a ScanOptions; `include_full_values=True` means `redaction="none"`.
+///
+/// Returns:
+///     ScanResult: Keys and application configurations found, with
+///         `to_dict()` for the result as a dictionary
+///
+/// The GIL is released while the scan reads files.
+///
+/// Example:
+///     >>> import aicred
+///     >>> result = aicred.scan(aicred.ScanOptions(redaction="last4"))
+///     >>> print(f"Found {len(result.keys)} keys")
 #[pyfunction]
-#[pyo3(signature = (home_dir=None, include_full_values=false, max_file_size=1048576, only_providers=None, exclude_providers=None))]
+#[pyo3(signature = (options=None, include_full_values=None, max_file_size=None, only_providers=None, exclude_providers=None, redaction=None, on_progress=None, *, home_dir=None))]
 fn scan(
+    py: Python<'_>,
+    options: Option<&Bound<'_, PyAny>>,
+    include_full_values: Option<bool>,
+    max_file_size: Option<usize>,
+    only_providers: Option<Vec<String>>,
+    exclude_providers: Option<Vec<String>>,
+    redaction: Option<String>,
+    on_progress: Option<Py<PyAny>>,
+    home_dir: Option<String>,
+) -> PyResult<ScanResult> {
+    let options = options::resolve(
+        options,
+        options::Arguments {
+            home_dir,
+            include_full_values,
+            max_file_size,
+            only_providers,
+            exclude_providers,
+            redaction,
+        },
+    )?;
+    scan_py(py, options, on_progress)
+}
+
+/// Scan like `scan()`, yielding each key as it is found
+///
+/// Takes the same arguments as `scan()`. The scan runs on a thread of its own;
+/// `on_progress` is called on the iterating thread. Once the iterator is
+/// exhausted, its `result` holds the whole `ScanResult`. Dropping the iterator
+/// early stops the scan.
+///
+/// Example:
+///     >>> for key in aicred.scan_iter():
+///     ...     print(f"{key.provider}: {key.value}")
+#[pyfunction]
+#[pyo3(signature = (options=None, include_full_values=None, max_file_size=None, only_providers=None, exclude_providers=None, redaction=None, on_progress=None, *, home_dir=None))]
+fn scan_iter(
+    options: Option<&Bound<'_, PyAny>>,
+    include_full_values: Option<bool>,
+    max_file_size: Option<usize>,
+    only_providers: Option<Vec<String>>,
+    exclude_providers: Option<Vec<String>>,
+    redaction: Option<String>,
+    on_progress: Option<Py<PyAny>>,
     home_dir: Option<String>,
-    include_full_values: bool,
-    max_file_size: usize,
+) -> PyResult<ScanIterator> {
+    let options = options::resolve(
+        options,
+        options::Arguments {
+            home_dir,
+            include_full_values,
+            max_file_size,
+            only_providers,
+            exclude_providers,
+            redaction,
+        },
+    )?;
+    ScanIterator::start(options, on_progress)
+}
+
+/// Scan like `scan()` without blocking the event loop
+///
+/// Takes the same arguments as `scan()` but `on_progress`, and must be called
+/// with an event loop running. Cancelling the awaited future stops the scan.
+///
+/// Example:
+///     >>> result = await aicred.scan_async()
+#[pyfunction]
+#[pyo3(signature = (options=None, include_full_values=None, max_file_size=None, only_providers=None, exclude_providers=None, redaction=None, *, home_dir=None))]
+fn scan_async(
+    py: Python<'_>,
+    options: Option<&Bound<'_, PyAny>>,
+    include_full_values: Option<bool>,
+    max_file_size: Option<usize>,
     only_providers: Option<Vec<String>>,
     exclude_providers: Option<Vec<String>>,
+    redaction: Option<String>,
+    home_dir: Option<String>,
 ) -> PyResult<Py<PyAny>> {
-    // Validate home_dir if provided
-    if let Some(ref home_dir_str) = home_dir {
-        let path = PathBuf::from(home_dir_str);
-        if !path.exists() {
-            return Err(PyErr::new::<pyo3::exceptions::PyException, _>(format!(
-                "Home directory does not exist: {}",
-                home_dir_str
-            )));
-        }
-        if !path.is_dir() {
-            return Err(PyErr::new::<pyo3::exceptions::PyException, _>(format!(
-                "Home directory is not a directory: {}",
-                home_dir_str
-            )));
-        }
-        if std::fs::read_dir(&path).is_err() {
-            return Err(PyErr::new::<pyo3::exceptions::PyException, _>(format!(
-                "Cannot read home directory: {}",
-                home_dir_str
-            )));
-        }
-    }
+    let mut options = options::resolve(
+        options,
+        options::Arguments {
+            home_dir,
+            include_full_values,
+            max_file_size,
+            only_providers,
+            exclude_providers,
+            redaction,
+        },
+    )?;
+    let cancellation = CancellationToken::new();
+    options.cancellation = Some(cancellation.clone());
+    aio::spawn_future(
+        py,
+        Some(cancellation),
+        move || aicred_core::scan(&options),
+        |py, result| Ok(Py::new(py, ScanResult(result))?.into_any()),
+    )
+}
 
-    scan_py(
-        home_dir,
-        include_full_values,
-        max_file_size,
-        only_providers,
-        exclude_providers,
+/// Probe a provider for the models an API key can use, without blocking the
+/// event loop
+///
+/// Args:
+///     provider (str): Provider, such as "openai"
+///     api_key (str): API key to probe with
+///     base_url (str, optional): Endpoint replacing the provider's default
+///     timeout (float): Seconds to give up after. Default: 30
+///
+/// Returns:
+///     list[dict]: Metadata of the models found
+///
+/// Example:
+///     >>> models = await aicred.probe_models_async("openai", key)
+#[pyfunction]
+#[pyo3(signature = (provider, api_key, base_url=None, timeout=30.0))]
+fn probe_models_async(
+    py: Python<'_>,
+    provider: String,
+    api_key: String,
+    base_url: Option<String>,
+    timeout: f64,
+) -> PyResult<Py<PyAny>> {
+    let timeout = std::time::Duration::try_from_secs_f64(timeout)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    aio::spawn_future(
+        py,
+        None,
+        move || {
+            aicred_core::probe_models(
+                &provider,
+                &api_key,
+                base_url.as_deref(),
+                &aicred_core::http::NetworkOptions::default(),
+                timeout,
+            )
+        },
+        |py, models| results::to_python(py, &models),
     )
 }
 
@@ -990,6 +1100,16 @@ impl Model {
         self.0.tags = Some(tags);
     }
 
+    #[getter]
+    fn capabilities(&self) -> Option<Capabilities> {
+        self.0.capabilities.clone().map(Capabilities)
+    }
+
+    #[setter]
+    fn set_capabilities(&mut self, value: Option<PyRef<'_, Capabilities>>) {
+        self.0.capabilities = value.map(|c| c.0.clone());
+    }
+
     #[getter]
     fn cost(&self) -> Option<TokenCost> {
         self.0.cost.as_ref().map(|c| TokenCost(c.clone()))
@@ -1097,26 +1217,29 @@ impl ProviderInstance {
         self.0.active = active;
     }
 
+    #[getter]
+    fn metadata(&self) -> Option<HashMap<String, String>> {
+        self.0.metadata.clone()
+    }
+
+    #[setter]
+    fn set_metadata(&mut self, value: Option<HashMap<String, String>>) {
+        self.0.metadata = value;
+    }
+
+    #[getter]
+    fn created_at(&self) -> String {
+        self.0.created_at.clone()
+    }
+
+    #[getter]
+    fn updated_at(&self) -> Option<String> {
+        self.0.updated_at.clone()
+    }
+
     #[getter]
     fn models(&self) -> Vec<Model> {
-        self.0
-            .models
-            .iter()
-            .map(|m| {
-                Model(PyModel {
-                    model_id: m.model_id.clone(),
-                    provider_instance_id: m.provider_instance_id.clone(),
-                    name: m.name.clone(),
-                    quantization: m.quantization.clone(),
-                    context_window: m.context_window,
-                    capabilities: m.capabilities.clone(),
-                    temperature: m.temperature,
-                    tags: m.tags.clone(),
-                    cost: m.cost.clone(),
-                    metadata: None, // Simplified - metadata not cloneable
-                })
-            })
-            .collect()
+        self.0.models.iter().map(|m| Model(m.duplicate())).collect()
     }
 
     #[getter]
@@ -1177,6 +1300,20 @@ impl ProviderInstances {
         Self(PyProviderInstances::new())
     }
 
+    /// Load the instances of the configuration store, keys as stored
+    #[staticmethod]
+    #[pyo3(signature = (home_dir=None))]
+    fn load(home_dir: Option<&str>) -> PyResult<Self> {
+        store::load_instances(home_dir).map(Self)
+    }
+
+    /// Add the instances to the configuration store, replacing the stored
+    /// ones with their IDs; instances not in the collection are kept
+    #[pyo3(signature = (home_dir=None))]
+    fn save(&self, home_dir: Option<&str>) -> PyResult<()> {
+        store::save_instances(&self.0, home_dir)
+    }
+
     fn add_instance(&mut self, instance: &ProviderInstance) -> PyResult<()> {
         self.0.add_instance(&instance.0)
     }
@@ -1260,60 +1397,153 @@ impl ProviderInstances {
     fn __str__(&self) -> String {
         self.0.__repr__()
     }
-}
 
-/// Scan for GenAI credentials and configurations
-///
-/// Args:
-///     home_dir (str, optional): Home directory to scan. Defaults to user's home.
-///     include_full_values (bool): Include full secret values. Default: False
-///     max_file_size (int): Maximum file size to read in bytes. Default: 1048576
-///     only_providers (list[str], optional): Only scan these providers
-///     exclude_providers (list[str], optional): Exclude these providers
-///
-/// Returns:
-///     dict: Scan results with keys and config_instances
-///
-/// Example:
-///     >>> import aicred
-///     >>> result = aicred.scan()
-///     >>> print(f"Found {len(result['keys'])} keys")
-#[pyfunction]
-#[pyo3(signature = (home_dir=None, include_full_values=false, max_file_size=1048576, only_providers=None, exclude_providers=None))]
-fn scan_py(
-    home_dir: Option<String>,
-    include_full_values: bool,
-    max_file_size: usize,
-    only_providers: Option<Vec<String>>,
-    exclude_providers: Option<Vec<String>>,
-) -> PyResult<Py<PyAny>> {
-    let home_path = match home_dir {
-        Some(h) => Some(PathBuf::from(h)),
-        None => dirs_next::home_dir(),
-    };
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
 
-    let options = ScanOptions {
-        home_dir: home_path,
-        include_full_values,
-        max_file_size,
-        only_providers,
-        exclude_providers,
-        probe_models: false,
-        probe_timeout_secs: 30,
-    };
+    /// Iterates over the instances in order of their IDs
+    fn __iter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let mut instances = self.0.all_instances();
+        instances.sort_by(|a, b| a.id.cmp(&b.id));
+        results::iterate(py, instances.into_iter().map(ProviderInstance).collect())
+    }
 
-    let result = core_scan(&options)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+    fn __contains__(&self, id: &str) -> bool {
+        self.0.instances.contains_key(id)
+    }
+}
 
-    // Convert to JSON and then to Python dict
-    let json = serde_json::to_string(&result)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+pyo3::create_exception!(
+    aicred,
+    AicredError,
+    pyo3::exceptions::PyRuntimeError,
+    "Error of the core library; `code` holds its stable error code and `retryable` whether trying again may help."
+);
+pyo3::create_exception!(
+    aicred,
+    ScanError,
+    AicredError,
+    "Files could not be read or a scan did not complete, as when it was cancelled."
+);
+pyo3::create_exception!(
+    aicred,
+    ConfigError,
+    AicredError,
+    "The configuration or the store holding it is invalid or cannot be used."
+);
+pyo3::create_exception!(
+    aicred,
+    PluginError,
+    AicredError,
+    "A provider or scanner plugin failed or could not be registered."
+);
+pyo3::create_exception!(
+    aicred,
+    NetworkError,
+    AicredError,
+    "A request to a provider failed."
+);
+pyo3::create_exception!(
+    aicred,
+    IoDeniedError,
+    ScanError,
+    "A file could not be accessed for lack of permission."
+);
+pyo3::create_exception!(
+    aicred,
+    ParseFailedError,
+    ScanError,
+    "A file could not be parsed."
+);
+pyo3::create_exception!(
+    aicred,
+    PluginConflictError,
+    PluginError,
+    "A plugin name is already registered."
+);
+pyo3::create_exception!(
+    aicred,
+    StoreCorruptError,
+    ConfigError,
+    "A file of the configuration store is corrupt."
+);
+pyo3::create_exception!(
+    aicred,
+    NetworkTimeoutError,
+    NetworkError,
+    "A network request got no answer in time."
+);
+
+/// Converts a core error into the exception class of its variant, with the
+/// `code` and `retryable` attributes set
+fn core_error(e: &aicred_core::error::Error) -> PyErr {
+    use aicred_core::error::Error;
+
+    let message = e.to_string();
+    let err = match e {
+        Error::IoDenied(_) => IoDeniedError::new_err(message),
+        Error::ParseFailed { .. } => ParseFailedError::new_err(message),
+        Error::IoError(_) | Error::SerializationError(_) | Error::Cancelled => {
+            ScanError::new_err(message)
+        }
+        Error::PluginConflict(_) => PluginConflictError::new_err(message),
+        Error::PluginError(_) => PluginError::new_err(message),
+        Error::StoreCorrupt { .. } => StoreCorruptError::new_err(message),
+        Error::ConfigError(_) | Error::ValidationError(_) | Error::SecurityError(_) => {
+            ConfigError::new_err(message)
+        }
+        Error::NetworkTimeout(_) => NetworkTimeoutError::new_err(message),
+        Error::HttpError(_) | Error::ApiError(_) | Error::RateLimited { .. } => {
+            NetworkError::new_err(message)
+        }
+        Error::NotFound(_) => AicredError::new_err(message),
+    };
+    Python::with_gil(|py| {
+        let value = err.value(py);
+        let _ = value.setattr("code", e.code());
+        let _ = value.setattr("retryable", e.is_retryable());
+    });
+    err
+}
 
-    Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-        let json_module = py.import("json")?;
-        let loads = json_module.getattr("loads")?;
-        Ok(loads.call1((json,))?.extract::<Py<PyAny>>()?)
-    })
+/// Runs a scan, calling `on_progress` before each scanner
+fn scan_py(
+    py: Python<'_>,
+    mut options: ScanOptions,
+    on_progress: Option<Py<PyAny>>,
+) -> PyResult<ScanResult> {
+    let cancellation = CancellationToken::new();
+    options.cancellation = Some(cancellation.clone());
+
+    // A failing callback cancels the scan; its exception wins over the
+    // cancellation error the scan then returns
+    let mut callback_error = None;
+    let result = py.allow_threads(|| {
+        scan_with_progress(&options, |event| {
+            if let (
+                Some(on_progress),
+                ScanEvent::Scanner {
+                    scanner,
+                    root,
+                    completed,
+                    total,
+                },
+            ) = (&on_progress, event)
+            {
+                Python::with_gil(|py| {
+                    if let Err(err) = on_progress.call1(py, (scanner, root, completed, total)) {
+                        callback_error.get_or_insert(err);
+                        cancellation.cancel();
+                    }
+                });
+            }
+        })
+    });
+    if let Some(err) = callback_error {
+        return Err(err);
+    }
+    Ok(ScanResult(result.map_err(|e| core_error(&e))?))
 }
 
 /// Get library version
@@ -1335,10 +1565,14 @@ fn list_providers() -> Vec<&'static str> {
     ]
 }
 
-/// List available application scanners
+/// List available application scanners, those registered from Python last
 #[pyfunction]
-fn list_scanners() -> Vec<&'static str> {
-    vec!["roo-code", "claude-desktop", "ragit", "langchain", "gsh"]
+fn list_scanners() -> Vec<String> {
+    let mut names: Vec<String> = ["roo-code", "claude-desktop", "ragit", "langchain", "gsh"]
+        .map(String::from)
+        .into();
+    names.extend(scanners::registered_names());
+    names
 }
 
 /// AICred - Python bindings
@@ -1352,9 +1586,54 @@ fn aicred(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Model>()?;
     m.add_class::<ProviderInstance>()?;
     m.add_class::<ProviderInstances>()?;
+    m.add_class::<ScanResult>()?;
+    m.add_class::<DiscoveredKey>()?;
+    m.add_class::<ConfigInstance>()?;
+    m.add_class::<ScanIterator>()?;
+    m.add_class::<options::ScanOptions>()?;
+    m.add_class::<registry::ModelRegistry>()?;
+    m.add_class::<registry::ModelEntry>()?;
+    m.add_class::<registry::ModelPricing>()?;
+    m.add_class::<registry::ModelCost>()?;
+    m.add("models", registry::ModelRegistry::current())?;
+    results::add_dataclasses(m)?;
+
+    // Add the exception classes of the core library's errors
+    m.add("AicredError", m.py().get_type::<AicredError>())?;
+    m.add("ScanError", m.py().get_type::<ScanError>())?;
+    m.add("ConfigError", m.py().get_type::<ConfigError>())?;
+    m.add("PluginError", m.py().get_type::<PluginError>())?;
+    m.add("NetworkError", m.py().get_type::<NetworkError>())?;
+    m.add("IoDeniedError", m.py().get_type::<IoDeniedError>())?;
+    m.add("ParseFailedError", m.py().get_type::<ParseFailedError>())?;
+    m.add(
+        "PluginConflictError",
+        m.py().get_type::<PluginConflictError>(),
+    )?;
+    m.add("StoreCorruptError", m.py().get_type::<StoreCorruptError>())?;
+    m.add(
+        "NetworkTimeoutError",
+        m.py().get_type::<NetworkTimeoutError>(),
+    )?;
 
     // Add functions
     m.add_function(wrap_pyfunction!(scan, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_iter, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_async, m)?)?;
+    m.add_function(wrap_pyfunction!(probe_models_async, m)?)?;
+    m.add_function(wrap_pyfunction!(as_dataclasses, m)?)?;
+    m.add_function(wrap_pyfunction!(scanners::register_scanner, m)?)?;
+    m.add_function(wrap_pyfunction!(scanners::unregister_scanner, m)?)?;
+    m.add_function(wrap_pyfunction!(store::remove_instance, m)?)?;
+    m.add_function(wrap_pyfunction!(store::resolve_label, m)?)?;
+    m.add_function(wrap_pyfunction!(store::list_labels, m)?)?;
+    m.add_function(wrap_pyfunction!(store::set_label, m)?)?;
+    m.add_function(wrap_pyfunction!(store::unset_label, m)?)?;
+    m.add_function(wrap_pyfunction!(store::list_tags, m)?)?;
+    m.add_function(wrap_pyfunction!(store::add_tag, m)?)?;
+    m.add_function(wrap_pyfunction!(store::remove_tag, m)?)?;
+    m.add_function(wrap_pyfunction!(store::assign_tag, m)?)?;
+    m.add_function(wrap_pyfunction!(store::unassign_tag, m)?)?;
     m.add_function(wrap_pyfunction!(version, m)?)?;
     m.add_function(wrap_pyfunction!(list_providers, m)?)?;
     m.add_function(wrap_pyfunction!(list_scanners, m)?)?;
@@ -0,0 +1,271 @@
+//! Options of a scan, for `scan()`, `scan_iter()` and `scan_async()`
+//!
+//! [`ScanOptions`] mirrors the core struct for Python. The separate arguments
+//! the scan functions took before it existed still work and are turned into
+//! one by [`resolve`].
+
+use crate::{check_home_dir, scanners};
+use aicred_core::http::NetworkOptions;
+use aicred_core::models::RedactionMode;
+use aicred_core::ScanOptions as CoreScanOptions;
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+/// Largest file read by default, in bytes
+const DEFAULT_MAX_FILE_SIZE: usize = 1_048_576;
+
+/// Options of a scan.
+///
+/// Every argument is also an attribute that can be changed afterwards.
+#[pyclass(module = "aicred")]
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Home directory to scan, by default the user's
+    #[pyo3(get, set)]
+    home_dir: Option<String>,
+    /// Further directories scanned as if they were home directories
+    #[pyo3(get, set)]
+    extra_roots: Vec<String>,
+    /// How values are redacted: "none", "mask", "last4", "first8" or
+    /// "fingerprint"
+    #[pyo3(get, set)]
+    redaction: String,
+    /// Largest file read, in bytes
+    #[pyo3(get, set)]
+    max_file_size: usize,
+    /// Only scan for these providers
+    #[pyo3(get, set)]
+    only_providers: Option<Vec<String>>,
+    /// Do not scan for these providers
+    #[pyo3(get, set)]
+    exclude_providers: Option<Vec<String>>,
+    /// Only report values of these types, such as "ApiKey"
+    #[pyo3(get, set)]
+    only_value_types: Option<Vec<String>>,
+    /// Whether archives among the scan roots are extracted and scanned
+    #[pyo3(get, set)]
+    scan_archives: bool,
+    /// Whether the instances found are probed for their models
+    #[pyo3(get, set)]
+    probe_models: bool,
+    /// Seconds model probing gives up after
+    #[pyo3(get, set)]
+    probe_timeout_secs: u64,
+    /// Most model probes of one provider run at once, by default the core's
+    #[pyo3(get, set)]
+    parallelism: Option<usize>,
+    /// Proxy of model probing, overriding `HTTPS_PROXY` and `HTTP_PROXY`
+    #[pyo3(get, set)]
+    proxy: Option<String>,
+    /// PEM file of CA certificates model probing trusts besides the built-in
+    /// roots
+    #[pyo3(get, set)]
+    ca_bundle: Option<String>,
+    /// Whether the scan must not touch the network, skipping model probing
+    #[pyo3(get, set)]
+    offline: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            home_dir: None,
+            extra_roots: Vec::new(),
+            redaction: RedactionMode::default().to_string(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            only_providers: None,
+            exclude_providers: None,
+            only_value_types: None,
+            scan_archives: false,
+            probe_models: false,
+            probe_timeout_secs: 30,
+            parallelism: None,
+            proxy: None,
+            ca_bundle: None,
+            offline: false,
+        }
+    }
+}
+
+#[pymethods]
+impl ScanOptions {
+    /// Raises:
+    ///     ValueError: If the redaction, a value type or the parallelism is
+    ///         invalid
+    #[new]
+    #[pyo3(signature = (home_dir=None, *, extra_roots=Vec::new(), redaction=None, max_file_size=DEFAULT_MAX_FILE_SIZE, only_providers=None, exclude_providers=None, only_value_types=None, scan_archives=false, probe_models=false, probe_timeout_secs=30, parallelism=None, proxy=None, ca_bundle=None, offline=false))]
+    fn new(
+        home_dir: Option<String>,
+        extra_roots: Vec<String>,
+        redaction: Option<String>,
+        max_file_size: usize,
+        only_providers: Option<Vec<String>>,
+        exclude_providers: Option<Vec<String>>,
+        only_value_types: Option<Vec<String>>,
+        scan_archives: bool,
+        probe_models: bool,
+        probe_timeout_secs: u64,
+        parallelism: Option<usize>,
+        proxy: Option<String>,
+        ca_bundle: Option<String>,
+        offline: bool,
+    ) -> PyResult<Self> {
+        let options = Self {
+            home_dir,
+            extra_roots,
+            redaction: redaction.unwrap_or_else(|| RedactionMode::default().to_string()),
+            max_file_size,
+            only_providers,
+            exclude_providers,
+            only_value_types,
+            scan_archives,
+            probe_models,
+            probe_timeout_secs,
+            parallelism,
+            proxy,
+            ca_bundle,
+            offline,
+        };
+        // Reports invalid values now rather than at the scan
+        options.to_core()?;
+        Ok(options)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanOptions(home_dir={:?}, extra_roots={:?}, redaction='{}', probe_models={}, offline={})",
+            self.home_dir, self.extra_roots, self.redaction, self.probe_models, self.offline
+        )
+    }
+}
+
+fn value_error(message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(message)
+}
+
+impl ScanOptions {
+    /// The options of the core library, with the scanners registered from
+    /// Python
+    pub(crate) fn to_core(&self) -> PyResult<CoreScanOptions> {
+        let redaction = self.redaction.parse().map_err(value_error)?;
+        let only_value_types = self
+            .only_value_types
+            .as_ref()
+            .map(|types| {
+                types
+                    .iter()
+                    .map(|value_type| value_type.parse().map_err(value_error))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .transpose()?;
+
+        let mut network = NetworkOptions {
+            proxy: self.proxy.clone(),
+            ca_bundle: self.ca_bundle.as_ref().map(PathBuf::from),
+            ..NetworkOptions::default()
+        };
+        if let Some(parallelism) = self.parallelism {
+            if parallelism == 0 {
+                return Err(value_error("parallelism must be at least 1".to_string()));
+            }
+            network.max_per_provider = parallelism;
+        }
+
+        Ok(CoreScanOptions {
+            home_dir: self
+                .home_dir
+                .as_ref()
+                .map(PathBuf::from)
+                .or_else(dirs_next::home_dir),
+            redaction,
+            max_file_size: self.max_file_size,
+            only_providers: self.only_providers.clone(),
+            exclude_providers: self.exclude_providers.clone(),
+            only_value_types,
+            probe_models: self.probe_models,
+            probe_timeout_secs: self.probe_timeout_secs,
+            extra_roots: self.extra_roots.iter().map(PathBuf::from).collect(),
+            scan_archives: self.scan_archives,
+            detection_rules: Vec::new(),
+            fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+            scanner_settings: std::collections::HashMap::new(),
+            network,
+            offline: self.offline,
+            cancellation: None,
+            scanners: scanners::registry()?,
+        })
+    }
+}
+
+/// The separate arguments the scan functions take besides a [`ScanOptions`]
+#[derive(Debug, Default)]
+pub(crate) struct Arguments {
+    pub(crate) home_dir: Option<String>,
+    pub(crate) include_full_values: Option<bool>,
+    pub(crate) max_file_size: Option<usize>,
+    pub(crate) only_providers: Option<Vec<String>>,
+    pub(crate) exclude_providers: Option<Vec<String>>,
+    pub(crate) redaction: Option<String>,
+}
+
+impl Arguments {
+    fn is_empty(&self) -> bool {
+        self.home_dir.is_none()
+            && self.include_full_values.is_none()
+            && self.max_file_size.is_none()
+            && self.only_providers.is_none()
+            && self.exclude_providers.is_none()
+            && self.redaction.is_none()
+    }
+
+    fn into_options(self) -> ScanOptions {
+        let redaction = match (self.redaction, self.include_full_values) {
+            (Some(redaction), _) => redaction,
+            (None, Some(true)) => RedactionMode::None.to_string(),
+            (None, _) => RedactionMode::default().to_string(),
+        };
+        ScanOptions {
+            home_dir: self.home_dir,
+            redaction,
+            max_file_size: self.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE),
+            only_providers: self.only_providers,
+            exclude_providers: self.exclude_providers,
+            ..ScanOptions::default()
+        }
+    }
+}
+
+/// The core options of a scan given a [`ScanOptions`], or the home directory
+/// as the first argument, as before `ScanOptions` existed, and the separate
+/// arguments
+pub(crate) fn resolve(
+    options: Option<&Bound<'_, PyAny>>,
+    mut arguments: Arguments,
+) -> PyResult<CoreScanOptions> {
+    let options = match options {
+        Some(options) if options.is_instance_of::<ScanOptions>() => {
+            if !arguments.is_empty() {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                    "Pass either a ScanOptions or separate arguments, not both",
+                ));
+            }
+            options.extract::<ScanOptions>()?
+        }
+        Some(home_dir) => {
+            if arguments.home_dir.is_some() {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                    "Got the home directory twice",
+                ));
+            }
+            arguments.home_dir = Some(home_dir.extract()?);
+            arguments.into_options()
+        }
+        None => arguments.into_options(),
+    };
+
+    check_home_dir(options.home_dir.as_deref())?;
+    for root in &options.extra_roots {
+        check_home_dir(Some(root))?;
+    }
+    options.to_core()
+}
@@ -0,0 +1,338 @@
+//! The registry of well-known models with their capabilities and pricing,
+//! for cost tooling written in Python
+//!
+//! `aicred.models` is the registry of the current user, the builtin entries
+//! with the cached model data and the user's overrides; `ModelRegistry(home)`
+//! creates the one of another home directory.
+
+use crate::results::{iterate, to_python};
+use aicred_core::models::{ModelPricing as CorePricing, TokenCost};
+use aicred_core::{
+    ModelCapabilities, ModelEntry as CoreEntry, ModelQuery, ModelRegistry as CoreRegistry,
+    ModelRequirements, ModelStatus,
+};
+use pyo3::prelude::*;
+use std::path::Path;
+
+/// Price of a model per token.
+#[pyclass(module = "aicred", frozen)]
+#[derive(Debug, Clone)]
+pub struct ModelPricing(CorePricing);
+
+#[pymethods]
+impl ModelPricing {
+    /// Price of an input token
+    #[getter]
+    fn input_cost_per_token(&self) -> f64 {
+        self.0.input_cost_per_token
+    }
+
+    /// Price of an output token
+    #[getter]
+    fn output_cost_per_token(&self) -> f64 {
+        self.0.output_cost_per_token
+    }
+
+    /// Price of a million input tokens, as providers list it
+    #[getter]
+    fn input_cost_per_million(&self) -> f64 {
+        self.0.input_cost_per_token * 1_000_000.0
+    }
+
+    /// Price of a million output tokens, as providers list it
+    #[getter]
+    fn output_cost_per_million(&self) -> f64 {
+        self.0.output_cost_per_token * 1_000_000.0
+    }
+
+    /// Currency of the prices, such as "USD"
+    #[getter]
+    fn currency(&self) -> &str {
+        &self.0.currency
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ModelPricing(input_cost_per_million={}, output_cost_per_million={}, currency='{}')",
+            self.input_cost_per_million(),
+            self.output_cost_per_million(),
+            self.0.currency
+        )
+    }
+}
+
+/// Cost of a number of tokens, returned by `ModelEntry.cost()`.
+#[pyclass(module = "aicred", frozen)]
+#[derive(Debug, Clone)]
+pub struct ModelCost(TokenCost);
+
+#[pymethods]
+impl ModelCost {
+    /// Cost of the input and output tokens together
+    #[getter]
+    fn total_cost(&self) -> f64 {
+        self.0.total_cost
+    }
+
+    /// Cost of the input tokens
+    #[getter]
+    fn input_cost(&self) -> f64 {
+        self.0.input_cost
+    }
+
+    /// Cost of the output tokens
+    #[getter]
+    fn output_cost(&self) -> f64 {
+        self.0.output_cost
+    }
+
+    /// Currency of the costs, such as "USD"
+    #[getter]
+    fn currency(&self) -> &str {
+        &self.0.currency
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ModelCost(total_cost={}, currency='{}')",
+            self.0.total_cost, self.0.currency
+        )
+    }
+}
+
+/// A model known to the registry.
+#[pyclass(module = "aicred", frozen)]
+#[derive(Debug, Clone)]
+pub struct ModelEntry(CoreEntry);
+
+#[pymethods]
+impl ModelEntry {
+    /// Model ID as used in API requests, such as "gpt-4o"
+    #[getter]
+    fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    /// Provider type serving the model, such as "openai"
+    #[getter]
+    fn provider(&self) -> &str {
+        &self.0.provider
+    }
+
+    /// Human-readable name
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Names of the capabilities the model supports, such as "vision"
+    #[getter]
+    fn capabilities(&self) -> Vec<&'static str> {
+        ModelCapabilities::NAMES
+            .iter()
+            .copied()
+            .filter(|name| self.0.capabilities.supports(name))
+            .collect()
+    }
+
+    /// Context window in tokens, if known
+    #[getter]
+    fn context_window(&self) -> Option<u32> {
+        self.0.context_window
+    }
+
+    /// Pricing, if known
+    #[getter]
+    fn pricing(&self) -> Option<ModelPricing> {
+        self.0.pricing.clone().map(ModelPricing)
+    }
+
+    /// Lifecycle status today: "active", "deprecated" or "archived"
+    #[getter]
+    fn status(&self) -> &'static str {
+        match self.0.status_on(chrono::Utc::now().date_naive()) {
+            ModelStatus::Active => "active",
+            ModelStatus::Deprecated => "deprecated",
+            ModelStatus::Archived => "archived",
+        }
+    }
+
+    /// Day the pricing was last checked, in ISO 8601
+    #[getter]
+    fn pricing_updated_at(&self) -> Option<String> {
+        self.0.pricing_updated_at.map(|day| day.to_string())
+    }
+
+    /// Day the provider deprecated the model, in ISO 8601
+    #[getter]
+    fn deprecated_at(&self) -> Option<String> {
+        self.0.deprecated_at.map(|day| day.to_string())
+    }
+
+    /// Day the provider stops serving the model, in ISO 8601
+    #[getter]
+    fn sunset_at(&self) -> Option<String> {
+        self.0.sunset_at.map(|day| day.to_string())
+    }
+
+    /// Other IDs tools write for the model
+    #[getter]
+    fn aliases(&self) -> Vec<String> {
+        self.0.aliases.clone()
+    }
+
+    /// Whether the model supports a capability, by name or alias such as
+    /// "tools"
+    fn supports(&self, capability: &str) -> bool {
+        self.0.capabilities.supports(capability)
+    }
+
+    /// Cost of a number of input and output tokens, if the pricing is known
+    #[pyo3(signature = (input_tokens, output_tokens=0))]
+    fn cost(&self, input_tokens: u64, output_tokens: u64) -> Option<ModelCost> {
+        self.0.cost(input_tokens, output_tokens).map(ModelCost)
+    }
+
+    /// The entry as a dictionary, as override files write it
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ModelEntry(id='{}', provider='{}', name='{}')",
+            self.0.id, self.0.provider, self.0.name
+        )
+    }
+}
+
+fn check_capability(capability: &str) -> PyResult<()> {
+    if ModelCapabilities::is_known(capability) {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown capability '{}', expected one of {}",
+            capability,
+            ModelCapabilities::NAMES.join(", ")
+        )))
+    }
+}
+
+fn entries<'a>(entries: impl IntoIterator<Item = &'a CoreEntry>) -> Vec<ModelEntry> {
+    entries.into_iter().cloned().map(ModelEntry).collect()
+}
+
+/// Registry of well-known models, such as `aicred.models`.
+#[pyclass(module = "aicred", frozen)]
+#[derive(Debug)]
+pub struct ModelRegistry(CoreRegistry);
+
+impl ModelRegistry {
+    /// The registry of the current user, for `aicred.models`
+    pub(crate) fn current() -> Self {
+        Self(CoreRegistry::new())
+    }
+}
+
+#[pymethods]
+impl ModelRegistry {
+    /// The registry of the user with the home directory, by default the
+    /// current user, or of the builtin entries alone with `builtin=True`
+    #[new]
+    #[pyo3(signature = (home_dir=None, *, builtin=false))]
+    fn new(home_dir: Option<&str>, builtin: bool) -> Self {
+        Self(match home_dir {
+            _ if builtin => CoreRegistry::builtin(),
+            Some(home) => CoreRegistry::for_home(Path::new(home)),
+            None => CoreRegistry::new(),
+        })
+    }
+
+    /// Look up a model by ID, or an alias or variant of it such as
+    /// "openai/gpt-4o"
+    fn get(&self, model_id: &str) -> Option<ModelEntry> {
+        self.0.resolve(model_id).cloned().map(ModelEntry)
+    }
+
+    /// The registry ID of a model ID as some tool writes it, or the ID itself
+    fn canonical_id(&self, model_id: &str) -> String {
+        self.0.canonical_id(model_id)
+    }
+
+    /// Every entry, in registry order
+    fn all(&self) -> Vec<ModelEntry> {
+        entries(self.0.entries())
+    }
+
+    /// The models served by a provider type, such as "anthropic"
+    fn by_provider(&self, provider: &str) -> Vec<ModelEntry> {
+        entries(self.0.query(&ModelQuery {
+            provider: Some(provider.to_string()),
+            ..ModelQuery::default()
+        }))
+    }
+
+    /// The models supporting a capability, such as "vision" or "tools"
+    ///
+    /// Raises:
+    ///     ValueError: If the capability is unknown
+    fn by_capability(&self, capability: &str) -> PyResult<Vec<ModelEntry>> {
+        check_capability(capability)?;
+        Ok(entries(self.0.query(&ModelQuery {
+            capabilities: vec![capability.to_string()],
+            ..ModelQuery::default()
+        })))
+    }
+
+    /// The models whose ID or name contains the text, ignoring case,
+    /// optionally of one provider type only
+    #[pyo3(signature = (text, provider=None))]
+    fn search(&self, text: &str, provider: Option<String>) -> Vec<ModelEntry> {
+        entries(self.0.query(&ModelQuery {
+            provider,
+            search: Some(text.to_string()),
+            ..ModelQuery::default()
+        }))
+    }
+
+    /// The active models meeting the requirements, cheapest first; prices are
+    /// per million tokens
+    ///
+    /// Raises:
+    ///     ValueError: If a capability is unknown
+    #[pyo3(signature = (capabilities=Vec::new(), max_input_cost=None, max_output_cost=None, min_context_window=None))]
+    fn recommend(
+        &self,
+        capabilities: Vec<String>,
+        max_input_cost: Option<f64>,
+        max_output_cost: Option<f64>,
+        min_context_window: Option<u32>,
+    ) -> PyResult<Vec<ModelEntry>> {
+        for capability in &capabilities {
+            check_capability(capability)?;
+        }
+        Ok(entries(self.0.recommend(&ModelRequirements {
+            capabilities,
+            max_input_cost,
+            max_output_cost,
+            min_context_window,
+        })))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.entries().len()
+    }
+
+    /// Iterates over the entries in registry order
+    fn __iter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        iterate(py, self.all())
+    }
+
+    fn __contains__(&self, model_id: &str) -> bool {
+        self.0.resolve(model_id).is_some()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ModelRegistry(models={})", self.0.entries().len())
+    }
+}
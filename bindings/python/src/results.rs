@@ -0,0 +1,430 @@
+//! Typed scan results returned by `scan()`
+
+use aicred_core::models::{
+    ConfigInstance as CoreConfigInstance, DiscoveredCredential, RedactionMode,
+    ScanResult as CoreScanResult,
+};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Dataclasses of `as_dataclasses()`, each with the fields of the getters of
+/// the class it converts
+const DATACLASSES: [(&str, &[&str]); 3] = [
+    (
+        "DiscoveredKeyData",
+        &[
+            "provider",
+            "value",
+            "value_type",
+            "confidence",
+            "risk",
+            "hash",
+            "source_file",
+            "source_line",
+            "scanner",
+            "discovered_at",
+        ],
+    ),
+    (
+        "ConfigInstanceData",
+        &[
+            "instance_id",
+            "app_name",
+            "config_path",
+            "app_version",
+            "keys",
+            "metadata",
+        ],
+    ),
+    (
+        "ScanResultData",
+        &[
+            "keys",
+            "instances",
+            "summary",
+            "home_directory",
+            "providers_scanned",
+            "scan_started_at",
+            "scan_completed_at",
+        ],
+    ),
+];
+
+/// Converts a serializable value into the Python object of its JSON
+pub(crate) fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    Ok(py
+        .import("json")?
+        .getattr("loads")?
+        .call1((json,))?
+        .unbind())
+}
+
+/// A list as an iterator, for `__iter__`
+pub(crate) fn iterate<T>(py: Python<'_>, items: Vec<T>) -> PyResult<Py<PyAny>>
+where
+    T: pyo3::PyClass + Into<pyo3::PyClassInitializer<T>>,
+{
+    let items = items
+        .into_iter()
+        .map(|item| Py::new(py, item))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, items)?.try_iter()?.into_any().unbind())
+}
+
+/// Creates the dataclasses of `as_dataclasses()` in the module
+pub(crate) fn add_dataclasses(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    let make_dataclass = py.import("dataclasses")?.getattr("make_dataclass")?;
+    let options = PyDict::new(py);
+    options.set_item("frozen", true)?;
+    for (name, fields) in DATACLASSES {
+        let class = make_dataclass.call((name, fields.to_vec()), Some(&options))?;
+        class.setattr("__module__", "aicred")?;
+        m.add(name, class)?;
+    }
+    Ok(())
+}
+
+/// Builds a dataclass from the getters of a result, converting nested ones
+fn dataclass<'py>(
+    value: &Bound<'py, PyAny>,
+    (name, fields): (&str, &[&str]),
+) -> PyResult<Bound<'py, PyAny>> {
+    let arguments = PyDict::new(value.py());
+    for field in fields {
+        let field_value = value.getattr(*field)?;
+        let field_value = if matches!(*field, "keys" | "instances") {
+            as_dataclasses(&field_value)?
+        } else {
+            field_value
+        };
+        arguments.set_item(*field, field_value)?;
+    }
+    value
+        .py()
+        .import("aicred")?
+        .getattr(name)?
+        .call((), Some(&arguments))
+}
+
+/// Convert results into frozen dataclasses
+///
+/// Takes a ScanResult, ConfigInstance or DiscoveredKey, or a list of them,
+/// and returns a ScanResultData, ConfigInstanceData or DiscoveredKeyData with
+/// the same fields, or a list of them, for code and tools that expect plain
+/// dataclasses, such as `dataclasses.asdict()`.
+#[pyfunction]
+pub fn as_dataclasses<'py>(value: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let [key, instance, result] = DATACLASSES;
+    if value.is_instance_of::<DiscoveredKey>() {
+        dataclass(value, key)
+    } else if value.is_instance_of::<ConfigInstance>() {
+        dataclass(value, instance)
+    } else if value.is_instance_of::<ScanResult>() {
+        dataclass(value, result)
+    } else if let Ok(items) = value.try_iter() {
+        let items = items
+            .map(|item| as_dataclasses(&item?))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyList::new(value.py(), items)?.into_any())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "Cannot convert {} into a dataclass",
+            value.get_type().name()?
+        )))
+    }
+}
+
+/// A credential found by a scan.
+#[pyclass(module = "aicred")]
+#[derive(Debug, Clone)]
+pub struct DiscoveredKey(pub(crate) DiscoveredCredential);
+
+#[pymethods]
+impl DiscoveredKey {
+    /// Provider the key belongs to, such as "openai"
+    #[getter]
+    fn provider(&self) -> &str {
+        &self.0.provider
+    }
+
+    /// The value, redacted as the scan asked
+    #[getter]
+    fn value(&self) -> String {
+        self.0.display_value(RedactionMode::None)
+    }
+
+    /// Type of the value, such as "API Key"
+    #[getter]
+    fn value_type(&self) -> String {
+        self.0.value_type.to_string()
+    }
+
+    /// Confidence of the match: "Low", "Medium", "High" or "Very High"
+    #[getter]
+    fn confidence(&self) -> String {
+        self.0.confidence.to_string()
+    }
+
+    /// Risk level: "Low", "Medium", "High" or "Critical"
+    #[getter]
+    fn risk(&self) -> String {
+        self.0.risk.to_string()
+    }
+
+    /// Fingerprint of the value
+    #[getter]
+    fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    /// File the key was found in
+    #[getter]
+    fn source_file(&self) -> &str {
+        &self.0.source_file
+    }
+
+    /// Line of the file the key was found on, if known
+    #[getter]
+    fn source_line(&self) -> Option<usize> {
+        self.0.source_line
+    }
+
+    /// Scanner that found the key, such as "gsh"
+    #[getter]
+    fn scanner(&self) -> Option<&str> {
+        self.0.scanner.as_deref()
+    }
+
+    /// When the key was found, in RFC 3339
+    #[getter]
+    fn discovered_at(&self) -> String {
+        self.0.discovered_at.to_rfc3339()
+    }
+
+    /// The key as the dictionary `scan()` used to return
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DiscoveredKey(provider='{}', value_type='{}', value='{}', source_file='{}')",
+            self.0.provider,
+            self.0.value_type,
+            self.value(),
+            self.0.source_file
+        )
+    }
+}
+
+/// An application configuration found by a scan.
+#[pyclass(module = "aicred")]
+#[derive(Debug, Clone)]
+pub struct ConfigInstance(pub(crate) CoreConfigInstance);
+
+#[pymethods]
+impl ConfigInstance {
+    /// Unique identifier of the instance
+    #[getter]
+    fn instance_id(&self) -> &str {
+        &self.0.instance_id
+    }
+
+    /// Application, such as "roo-code"
+    #[getter]
+    fn app_name(&self) -> &str {
+        &self.0.app_name
+    }
+
+    /// Path of the configuration
+    #[getter]
+    fn config_path(&self) -> String {
+        self.0.config_path.to_string_lossy().into_owned()
+    }
+
+    /// Version of the application, if detected
+    #[getter]
+    fn app_version(&self) -> Option<&str> {
+        self.0.app_version.as_deref()
+    }
+
+    /// Keys found in the configuration
+    #[getter]
+    fn keys(&self) -> Vec<DiscoveredKey> {
+        self.0.keys.iter().cloned().map(DiscoveredKey).collect()
+    }
+
+    /// Metadata of the configuration, such as its settings
+    #[getter]
+    fn metadata(&self) -> HashMap<String, String> {
+        self.0.metadata.clone()
+    }
+
+    /// The configuration as the dictionary `scan()` used to return
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.0)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.keys.len()
+    }
+
+    /// Iterates over the keys found in the configuration
+    fn __iter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        iterate(py, self.keys())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConfigInstance(app_name='{}', config_path='{}', keys={})",
+            self.0.app_name,
+            self.0.config_path.display(),
+            self.0.keys.len()
+        )
+    }
+}
+
+/// Result of `scan()`.
+///
+/// Indexing it like the dictionary `scan()` used to return, as in
+/// `result["keys"]`, still works for existing code.
+#[pyclass(module = "aicred")]
+#[derive(Debug, Clone)]
+pub struct ScanResult(pub(crate) CoreScanResult);
+
+#[pymethods]
+impl ScanResult {
+    /// Keys found
+    #[getter]
+    fn keys(&self) -> Vec<DiscoveredKey> {
+        self.0.keys.iter().cloned().map(DiscoveredKey).collect()
+    }
+
+    /// Application configurations found
+    #[getter]
+    fn instances(&self) -> Vec<ConfigInstance> {
+        self.0
+            .config_instances
+            .iter()
+            .cloned()
+            .map(ConfigInstance)
+            .collect()
+    }
+
+    /// Counts of the scan: keys by provider, type and confidence, files and
+    /// directories scanned and the duration in seconds
+    #[getter]
+    fn summary(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.0.summary())
+    }
+
+    /// Home directory scanned
+    #[getter]
+    fn home_directory(&self) -> &str {
+        &self.0.home_directory
+    }
+
+    /// Providers scanned for
+    #[getter]
+    fn providers_scanned(&self) -> Vec<String> {
+        self.0.providers_scanned.clone()
+    }
+
+    /// When the scan started, in RFC 3339
+    #[getter]
+    fn scan_started_at(&self) -> String {
+        self.0.scan_started_at.to_rfc3339()
+    }
+
+    /// When the scan completed, in RFC 3339
+    #[getter]
+    fn scan_completed_at(&self) -> String {
+        self.0.scan_completed_at.to_rfc3339()
+    }
+
+    /// The result as the dictionary `scan()` used to return
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.0)
+    }
+
+    /// The keys as a DataFrame of pandas or polars, one row per key of the
+    /// result and of its configurations, without their values
+    #[pyo3(signature = (library="pandas"))]
+    fn to_dataframe(&self, py: Python<'_>, library: &str) -> PyResult<Py<PyAny>> {
+        if !matches!(library, "pandas" | "polars") {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown DataFrame library '{}', expected 'pandas' or 'polars'",
+                library
+            )));
+        }
+        let module = py.import(library).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyImportError, _>(format!(
+                "to_dataframe() needs {0}; install it with `pip install aicred[{0}]`",
+                library
+            ))
+        })?;
+
+        let rows = self.0.keys.iter().map(|key| (key, None)).chain(
+            self.0
+                .config_instances
+                .iter()
+                .flat_map(|instance| instance.keys.iter().map(move |key| (key, Some(instance)))),
+        );
+        let (mut provider, mut value_type, mut confidence, mut risk) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (mut path, mut line, mut app_name, mut instance_id) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (key, instance) in rows {
+            provider.push(key.provider.clone());
+            value_type.push(key.value_type.to_string());
+            confidence.push(key.confidence.to_string());
+            risk.push(key.risk.to_string());
+            path.push(key.source_file.clone());
+            line.push(key.source_line);
+            app_name.push(instance.map(|instance| instance.app_name.clone()));
+            instance_id.push(instance.map(|instance| instance.instance_id.clone()));
+        }
+
+        let columns = PyDict::new(py);
+        columns.set_item("provider", provider)?;
+        columns.set_item("value_type", value_type)?;
+        columns.set_item("confidence", confidence)?;
+        columns.set_item("risk", risk)?;
+        columns.set_item("path", path)?;
+        columns.set_item("line", line)?;
+        columns.set_item("app_name", app_name)?;
+        columns.set_item("instance_id", instance_id)?;
+        Ok(module.getattr("DataFrame")?.call1((columns,))?.unbind())
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.keys.len()
+    }
+
+    /// Iterates over the keys found
+    fn __iter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        iterate(py, self.keys())
+    }
+
+    fn __getitem__(&self, py: Python<'_>, key: &str) -> PyResult<Py<PyAny>> {
+        Ok(self.to_dict(py)?.bind(py).get_item(key)?.unbind())
+    }
+
+    fn __contains__(&self, py: Python<'_>, key: &str) -> PyResult<bool> {
+        self.to_dict(py)?.bind(py).contains(key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanResult(keys={}, instances={}, home_directory='{}')",
+            self.0.keys.len(),
+            self.0.config_instances.len(),
+            self.0.home_directory
+        )
+    }
+}
@@ -0,0 +1,236 @@
+//! Application scanners written in Python, for `register_scanner()`
+//!
+//! A registered object is wrapped into a [`ScannerPlugin`] that takes the GIL
+//! for each call, and runs alongside the built-in scanners in every later
+//! scan.
+
+use crate::core_error;
+use aicred_core::discovery::{ScanResult as ScannerOutput, ScannerContext};
+use aicred_core::error::{Error, Result};
+use aicred_core::models::{Confidence, ConfigInstance, DiscoveredCredential, ValueType};
+use aicred_core::{register_builtin_scanners, ScannerPlugin, ScannerRegistry};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Scanners registered from Python, in the order they were registered
+static SCANNERS: Mutex<Vec<Arc<PythonScanner>>> = Mutex::new(Vec::new());
+
+/// Confidence of a key whose dictionary gives none
+const DEFAULT_CONFIDENCE: f32 = 0.8;
+
+/// A Python object scanning for the configuration of an application
+struct PythonScanner {
+    name: String,
+    app_name: String,
+    object: Py<PyAny>,
+}
+
+/// Reads an attribute that may also be a method taking no arguments
+fn string_attr(object: &Bound<'_, PyAny>, name: &str) -> PyResult<String> {
+    let value = object.getattr(name)?;
+    if value.is_callable() {
+        value.call0()?.extract()
+    } else {
+        value.extract()
+    }
+}
+
+/// Converts a key dictionary returned by `parse_config` into a credential
+fn credential(key: &Bound<'_, PyAny>, path: &Path) -> PyResult<DiscoveredCredential> {
+    let key = key.downcast::<PyDict>()?;
+    let required = |name: &str| -> PyResult<String> {
+        key.get_item(name)?
+            .ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!("Key has no '{}'", name))
+            })?
+            .extract()
+    };
+
+    let value_type = match key.get_item("value_type")? {
+        Some(value_type) => value_type
+            .extract::<String>()?
+            .parse()
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+        None => ValueType::ApiKey,
+    };
+    let confidence = match key.get_item("confidence")? {
+        Some(confidence) => confidence.extract::<f32>()?,
+        None => DEFAULT_CONFIDENCE,
+    };
+
+    let mut credential = DiscoveredCredential::new(
+        required("provider")?,
+        path.display().to_string(),
+        value_type,
+        Confidence::from(confidence),
+        required("value")?,
+    );
+    if let Some(line) = key.get_item("line")? {
+        credential.source_line = Some(line.extract()?);
+    }
+    Ok(credential)
+}
+
+impl PythonScanner {
+    /// Reports an exception of the scanner as a plugin error of the scan
+    fn plugin_error(&self, method: &str, err: &PyErr) -> Error {
+        Error::PluginError(format!(
+            "Scanner '{}' {} failed: {}",
+            self.name, method, err
+        ))
+    }
+}
+
+impl ScannerPlugin for PythonScanner {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
+        Python::with_gil(|py| {
+            let paths = self
+                .object
+                .call_method1(py, "scan_paths", (home_dir.display().to_string(),))
+                .and_then(|paths| paths.extract::<Vec<PathBuf>>(py));
+            // A failing scanner finds nothing rather than failing the scan
+            paths.unwrap_or_else(|err| {
+                err.print(py);
+                Vec::new()
+            })
+        })
+    }
+
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<ScannerOutput> {
+        Python::with_gil(|py| {
+            let keys = self
+                .object
+                .call_method1(py, "parse_config", (path.display().to_string(), content))
+                .map_err(|err| self.plugin_error("parse_config", &err))?;
+
+            let mut output = ScannerOutput::new();
+            for key in keys
+                .bind(py)
+                .try_iter()
+                .map_err(|err| self.plugin_error("parse_config", &err))?
+            {
+                let key = key
+                    .and_then(|key| credential(&key, path))
+                    .map_err(|err| self.plugin_error("parse_config", &err))?;
+                output.add_key(key);
+            }
+            Ok(output)
+        })
+    }
+
+    fn can_handle_file(&self, path: &Path) -> bool {
+        Python::with_gil(|py| {
+            let object = self.object.bind(py);
+            if !object.hasattr("can_handle_file").unwrap_or(false) {
+                return true;
+            }
+            object
+                .call_method1("can_handle_file", (path.display().to_string(),))
+                .and_then(|handles| handles.is_truthy())
+                .unwrap_or(false)
+        })
+    }
+
+    // Instances would need a second `parse_config` of every file
+    fn scan_instances(&self, _home_dir: &Path) -> Result<Vec<ConfigInstance>> {
+        Ok(Vec::new())
+    }
+}
+
+fn scanners() -> std::sync::MutexGuard<'static, Vec<Arc<PythonScanner>>> {
+    SCANNERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Names of the scanners registered from Python
+pub(crate) fn registered_names() -> Vec<String> {
+    scanners()
+        .iter()
+        .map(|scanner| scanner.name.clone())
+        .collect()
+}
+
+/// The built-in scanners and those registered from Python, or None to scan
+/// with the built-in ones alone
+pub(crate) fn registry() -> PyResult<Option<ScannerRegistry>> {
+    let scanners = scanners();
+    if scanners.is_empty() {
+        return Ok(None);
+    }
+
+    let registry = ScannerRegistry::new();
+    register_builtin_scanners(&registry).map_err(|e| core_error(&e))?;
+    for scanner in scanners.iter() {
+        registry
+            .register(Arc::clone(scanner) as Arc<dyn ScannerPlugin>)
+            .map_err(|e| core_error(&e))?;
+    }
+    Ok(Some(registry))
+}
+
+/// Register an application scanner written in Python
+///
+/// The object needs a `name`, `scan_paths(home_dir)` returning the paths to
+/// read, directories being walked, and `parse_config(path, content)` returning
+/// the keys found as dictionaries with `provider` and `value`, and optionally
+/// `value_type` (default "ApiKey"), `confidence` from 0 to 1 (default 0.8) and
+/// `line`. It may also have an `app_name` and `can_handle_file(path)`.
+///
+/// Raises:
+///     PluginConflictError: If a scanner already has the name
+#[pyfunction]
+pub(crate) fn register_scanner(scanner: &Bound<'_, PyAny>) -> PyResult<()> {
+    let name = string_attr(scanner, "name")?;
+    let app_name = if scanner.hasattr("app_name")? {
+        string_attr(scanner, "app_name")?
+    } else {
+        name.clone()
+    };
+    for method in ["scan_paths", "parse_config"] {
+        if !scanner.getattr(method)?.is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                "Scanner '{}' has no {}() method",
+                name, method
+            )));
+        }
+    }
+
+    let builtin = ScannerRegistry::new();
+    register_builtin_scanners(&builtin).map_err(|e| core_error(&e))?;
+    let mut scanners = scanners();
+    if builtin.get(&name).is_some() || scanners.iter().any(|s| s.name == name) {
+        return Err(core_error(&Error::PluginConflict(name)));
+    }
+    scanners.push(Arc::new(PythonScanner {
+        name,
+        app_name,
+        object: scanner.clone().unbind(),
+    }));
+    Ok(())
+}
+
+/// Unregister a scanner registered from Python
+///
+/// Returns:
+///     bool: Whether a scanner had the name
+#[pyfunction]
+pub(crate) fn unregister_scanner(name: &str) -> bool {
+    let mut scanners = scanners();
+    let count = scanners.len();
+    scanners.retain(|scanner| scanner.name != name);
+    scanners.len() != count
+}
@@ -0,0 +1,478 @@
+//! Managing instances, labels and tags of the configuration store
+//!
+//! These read and write the same configuration store as the CLI and the C
+//! API: the one in the config directory of `home_dir`, such as
+//! `<home_dir>/.config/aicred` on Linux, or of the current user if it is
+//! not given. Changes are made under the store's lock.
+
+use crate::results::to_python;
+use crate::{core_error, PyModel, PyProviderInstance, PyProviderInstances};
+use aicred_core::bulk::TAG_ASSIGNMENTS_FILE;
+use aicred_core::models::{Label, LabelAssignment, LabelTarget, ProviderInstance};
+use aicred_core::resolve::LABELS_FILE;
+use aicred_core::{ConfigStore, InstanceSelector, SecretBackends, StoreLock};
+use pyo3::prelude::*;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Store file holding the descriptions of labels
+const LABELS_METADATA_FILE: &str = "labels_metadata.yaml";
+
+/// Store file holding the tags
+const TAGS_FILE: &str = "tags.yaml";
+
+/// Opens the configuration store of `home_dir`, or of the current user
+fn config_store(home_dir: Option<&str>) -> PyResult<ConfigStore> {
+    match home_dir {
+        Some(home) => Ok(ConfigStore::for_home(Path::new(home))),
+        None => ConfigStore::default_location().map_err(|e| core_error(&e)),
+    }
+}
+
+fn value_error(message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(message)
+}
+
+/// Loads a list from a file of the store, empty if the file is missing
+fn load_list<T: DeserializeOwned>(store: &ConfigStore, file: &str) -> PyResult<Vec<T>> {
+    Ok(store
+        .load_yaml(file)
+        .map_err(|e| core_error(&e))?
+        .unwrap_or_default())
+}
+
+fn save_list<T: serde::Serialize>(lock: &StoreLock<'_>, file: &str, list: &[T]) -> PyResult<()> {
+    lock.save_yaml(file, &list).map_err(|e| core_error(&e))?;
+    Ok(())
+}
+
+fn target(instance_id: String, model_id: Option<String>) -> LabelTarget {
+    match model_id {
+        Some(model_id) => LabelTarget::ProviderModel {
+            instance_id,
+            model_id,
+        },
+        None => LabelTarget::ProviderInstance { instance_id },
+    }
+}
+
+fn new_label(name: &str, description: Option<String>) -> Label {
+    Label {
+        name: name.to_string(),
+        description,
+        created_at: chrono::Utc::now(),
+        metadata: HashMap::new(),
+    }
+}
+
+/// Converts a stored instance, its key as stored
+fn from_core(instance: ProviderInstance) -> PyProviderInstance {
+    let mut py_instance = PyProviderInstance::new(
+        instance.id.clone(),
+        instance.id.clone(),
+        instance.provider_type,
+        instance.base_url,
+    );
+    if !instance.api_key.is_empty() {
+        let key = Python::with_gil(|py| {
+            pyo3::types::PyString::new(py, &instance.api_key)
+                .into_any()
+                .unbind()
+        });
+        py_instance.keys = Some(vec![key]);
+    }
+    py_instance.models = instance
+        .models
+        .iter()
+        .map(|model| PyModel::new(model.clone(), instance.id.clone(), model.clone()))
+        .collect();
+    py_instance.metadata = (!instance.metadata.is_empty()).then_some(instance.metadata);
+    py_instance.active = instance.active;
+    py_instance
+}
+
+/// Applies an instance to the stored one with its ID, keeping what Python
+/// does not model, such as capabilities and provenance
+///
+/// A stored instance holds one key: the first key that is a string.
+fn to_core(instance: &PyProviderInstance, stored: Option<ProviderInstance>) -> ProviderInstance {
+    let api_key = Python::with_gil(|py| {
+        instance
+            .keys
+            .iter()
+            .flatten()
+            .find_map(|key| key.extract::<String>(py).ok())
+    });
+
+    let mut core = stored.unwrap_or_else(|| {
+        ProviderInstance::new_without_models(
+            instance.id.clone(),
+            instance.provider_type.clone(),
+            instance.base_url.clone(),
+            String::new(),
+        )
+    });
+    core.provider_type.clone_from(&instance.provider_type);
+    core.base_url.clone_from(&instance.base_url);
+    if let Some(api_key) = api_key {
+        core.api_key = api_key;
+    }
+    core.models = Vec::new();
+    for model in &instance.models {
+        core.add_model(model.model_id.clone());
+    }
+    core.active = instance.active;
+    core.metadata = instance.metadata.clone().unwrap_or_default();
+    core
+}
+
+/// Loads the instances of a store
+pub(crate) fn load_instances(home_dir: Option<&str>) -> PyResult<PyProviderInstances> {
+    let store = config_store(home_dir)?;
+    let collection = store.load_instances().map_err(|e| core_error(&e))?;
+    let mut instances = PyProviderInstances::new();
+    for instance in collection.all_instances() {
+        instances
+            .instances
+            .insert(instance.id.clone(), from_core(instance.clone()));
+    }
+    Ok(instances)
+}
+
+/// Adds the instances to a store, replacing the stored ones with their IDs
+pub(crate) fn save_instances(
+    instances: &PyProviderInstances,
+    home_dir: Option<&str>,
+) -> PyResult<()> {
+    let store = config_store(home_dir)?;
+    let mut updates = Vec::new();
+    for instance in instances.instances.values() {
+        let stored = store
+            .find_instance(&instance.id)
+            .map_err(|e| core_error(&e))?;
+        let file_name = stored.as_ref().and_then(|(path, _)| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        });
+        let core = to_core(instance, stored.map(|(_, stored)| stored));
+        core.validate()
+            .map_err(|e| value_error(format!("Instance '{}': {}", instance.id, e)))?;
+        updates.push((file_name, core));
+    }
+
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    for (file_name, instance) in &updates {
+        match file_name {
+            Some(file_name) => lock.save_instance_as(file_name, instance),
+            None => lock.save_instance(instance),
+        }
+        .map_err(|e| core_error(&e))?;
+    }
+    Ok(())
+}
+
+/// Remove a provider instance from the configuration store
+///
+/// Like `aicred instances remove`, this keeps the file as a backup, drops the
+/// label and tag assignments pointing at the instance and deletes the secret
+/// its key references if it can.
+///
+/// Returns:
+///     bool: Whether an instance had the ID
+#[pyfunction]
+#[pyo3(signature = (id, home_dir=None))]
+pub(crate) fn remove_instance(id: &str, home_dir: Option<&str>) -> PyResult<bool> {
+    let store = config_store(home_dir)?;
+    let removed = aicred_core::bulk::remove(&store, &InstanceSelector::default().with_id(id))
+        .map_err(|e| core_error(&e))?;
+
+    // A secret that can't be deleted is only left behind
+    let backends = SecretBackends::builtin();
+    for instance in &removed {
+        let _ = backends.delete(&instance.api_key);
+    }
+    Ok(!removed.is_empty())
+}
+
+/// Resolve a label to the instance and model it points at
+///
+/// Follows the label's targets and fallbacks like `aicred resolve`.
+///
+/// Returns:
+///     dict: The label, the instance with its API key revealed, the model and
+///         why any targets were passed over
+#[pyfunction]
+#[pyo3(signature = (name, home_dir=None))]
+pub(crate) fn resolve_label(
+    py: Python<'_>,
+    name: &str,
+    home_dir: Option<&str>,
+) -> PyResult<Py<PyAny>> {
+    let store = config_store(home_dir)?;
+    let resolved = aicred_core::resolve::resolve_label(&store, name).map_err(|e| core_error(&e))?;
+    to_python(py, &resolved)
+}
+
+/// List the label assignments of the configuration store
+///
+/// Returns:
+///     list[dict]: Each label with its target, fallbacks and description
+#[pyfunction]
+#[pyo3(signature = (home_dir=None))]
+pub(crate) fn list_labels(py: Python<'_>, home_dir: Option<&str>) -> PyResult<Py<PyAny>> {
+    let store = config_store(home_dir)?;
+    let assignments: Vec<LabelAssignment> = load_list(&store, LABELS_FILE)?;
+    let descriptions: HashMap<String, Option<String>> =
+        load_list::<Label>(&store, LABELS_METADATA_FILE)?
+            .into_iter()
+            .map(|label| (label.name, label.description))
+            .collect();
+
+    let mut labels = Vec::new();
+    for assignment in &assignments {
+        let mut entry = serde_json::to_value(assignment).map_err(|e| value_error(e.to_string()))?;
+        entry["description"] = descriptions
+            .get(&assignment.label_name)
+            .cloned()
+            .flatten()
+            .into();
+        labels.push(entry);
+    }
+    to_python(py, &labels)
+}
+
+/// Point a label at an instance, or at one of its models
+///
+/// Creates the label if needed; an existing label is moved, replacing its
+/// target and any fallbacks. A description replaces the stored one.
+///
+/// Returns:
+///     bool: Whether an existing label was moved
+#[pyfunction]
+#[pyo3(signature = (name, instance_id, model_id=None, description=None, home_dir=None))]
+pub(crate) fn set_label(
+    name: &str,
+    instance_id: String,
+    model_id: Option<String>,
+    description: Option<String>,
+    home_dir: Option<&str>,
+) -> PyResult<bool> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(value_error("Label name cannot be empty".to_string()));
+    }
+
+    let store = config_store(home_dir)?;
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    let mut assignments: Vec<LabelAssignment> = load_list(&store, LABELS_FILE)?;
+    let mut labels: Vec<Label> = load_list(&store, LABELS_METADATA_FILE)?;
+
+    let target = target(instance_id, model_id);
+    let moved = match assignments.iter_mut().find(|a| a.label_name == name) {
+        Some(assignment) => {
+            assignment.target = target;
+            assignment.fallbacks.clear();
+            assignment.requires.clear();
+            assignment.assigned_at = chrono::Utc::now();
+            true
+        }
+        None => {
+            assignments.push(LabelAssignment {
+                label_name: name.to_string(),
+                target,
+                assigned_at: chrono::Utc::now(),
+                assigned_by: None,
+                fallbacks: Vec::new(),
+                requires: Vec::new(),
+            });
+            false
+        }
+    };
+
+    match labels.iter_mut().find(|label| label.name == name) {
+        Some(label) => {
+            if description.is_some() {
+                label.description = description;
+            }
+        }
+        None => labels.push(new_label(name, description)),
+    }
+
+    save_list(&lock, LABELS_FILE, &assignments)?;
+    save_list(&lock, LABELS_METADATA_FILE, &labels)?;
+    Ok(moved)
+}
+
+/// Remove a label
+///
+/// Returns:
+///     bool: Whether the label existed
+#[pyfunction]
+#[pyo3(signature = (name, home_dir=None))]
+pub(crate) fn unset_label(name: &str, home_dir: Option<&str>) -> PyResult<bool> {
+    let store = config_store(home_dir)?;
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    let mut assignments: Vec<LabelAssignment> = load_list(&store, LABELS_FILE)?;
+    let count = assignments.len();
+    assignments.retain(|assignment| assignment.label_name != name);
+    if assignments.len() == count {
+        return Ok(false);
+    }
+
+    let mut labels: Vec<Label> = load_list(&store, LABELS_METADATA_FILE)?;
+    labels.retain(|label| label.name != name);
+    save_list(&lock, LABELS_FILE, &assignments)?;
+    save_list(&lock, LABELS_METADATA_FILE, &labels)?;
+    Ok(true)
+}
+
+/// List the tags of the configuration store
+///
+/// Returns:
+///     list[dict]: Each tag with its description and the targets it is
+///         assigned to
+#[pyfunction]
+#[pyo3(signature = (home_dir=None))]
+pub(crate) fn list_tags(py: Python<'_>, home_dir: Option<&str>) -> PyResult<Py<PyAny>> {
+    let store = config_store(home_dir)?;
+    let tags: Vec<Label> = load_list(&store, TAGS_FILE)?;
+    let assignments: Vec<LabelAssignment> = load_list(&store, TAG_ASSIGNMENTS_FILE)?;
+
+    let mut entries = Vec::new();
+    for tag in &tags {
+        let mut entry = serde_json::to_value(tag).map_err(|e| value_error(e.to_string()))?;
+        entry["assignments"] = serde_json::to_value(
+            assignments
+                .iter()
+                .filter(|assignment| assignment.label_name == tag.name)
+                .map(|assignment| &assignment.target)
+                .collect::<Vec<_>>(),
+        )
+        .map_err(|e| value_error(e.to_string()))?;
+        entries.push(entry);
+    }
+    to_python(py, &entries)
+}
+
+/// Create a tag
+///
+/// Raises:
+///     ValueError: If the name is empty or a tag already has it
+#[pyfunction]
+#[pyo3(signature = (name, description=None, home_dir=None))]
+pub(crate) fn add_tag(
+    name: &str,
+    description: Option<String>,
+    home_dir: Option<&str>,
+) -> PyResult<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(value_error("Tag name cannot be empty".to_string()));
+    }
+
+    let store = config_store(home_dir)?;
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    let mut tags: Vec<Label> = load_list(&store, TAGS_FILE)?;
+    if tags.iter().any(|tag| tag.name == name) {
+        return Err(value_error(format!(
+            "Tag with name '{}' already exists",
+            name
+        )));
+    }
+    tags.push(new_label(name, description));
+    save_list(&lock, TAGS_FILE, &tags)
+}
+
+/// Remove a tag and its assignments
+///
+/// Returns:
+///     bool: Whether the tag existed
+#[pyfunction]
+#[pyo3(signature = (name, home_dir=None))]
+pub(crate) fn remove_tag(name: &str, home_dir: Option<&str>) -> PyResult<bool> {
+    let store = config_store(home_dir)?;
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    let mut tags: Vec<Label> = load_list(&store, TAGS_FILE)?;
+    let count = tags.len();
+    tags.retain(|tag| tag.name != name);
+    if tags.len() == count {
+        return Ok(false);
+    }
+
+    let mut assignments: Vec<LabelAssignment> = load_list(&store, TAG_ASSIGNMENTS_FILE)?;
+    let assigned = assignments.len();
+    assignments.retain(|assignment| assignment.label_name != name);
+    if assignments.len() != assigned {
+        save_list(&lock, TAG_ASSIGNMENTS_FILE, &assignments)?;
+    }
+    save_list(&lock, TAGS_FILE, &tags)?;
+    Ok(true)
+}
+
+/// Assign a tag to an instance, or to one of its models
+///
+/// Raises:
+///     ValueError: If no tag has the name or it is already assigned there
+#[pyfunction]
+#[pyo3(signature = (name, instance_id, model_id=None, home_dir=None))]
+pub(crate) fn assign_tag(
+    name: &str,
+    instance_id: String,
+    model_id: Option<String>,
+    home_dir: Option<&str>,
+) -> PyResult<()> {
+    let store = config_store(home_dir)?;
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    let tags: Vec<Label> = load_list(&store, TAGS_FILE)?;
+    if !tags.iter().any(|tag| tag.name == name) {
+        return Err(value_error(format!("Tag with name '{}' not found", name)));
+    }
+
+    let mut assignments: Vec<LabelAssignment> = load_list(&store, TAG_ASSIGNMENTS_FILE)?;
+    let target = target(instance_id, model_id);
+    if assignments
+        .iter()
+        .any(|assignment| assignment.label_name == name && assignment.target == target)
+    {
+        return Err(value_error(format!(
+            "Tag '{}' is already assigned to the specified target",
+            name
+        )));
+    }
+    assignments.push(LabelAssignment {
+        label_name: name.to_string(),
+        target,
+        assigned_at: chrono::Utc::now(),
+        assigned_by: None,
+        fallbacks: Vec::new(),
+        requires: Vec::new(),
+    });
+    save_list(&lock, TAG_ASSIGNMENTS_FILE, &assignments)
+}
+
+/// Remove a tag from an instance, or from one of its models
+///
+/// Returns:
+///     bool: Whether the tag was assigned there
+#[pyfunction]
+#[pyo3(signature = (name, instance_id, model_id=None, home_dir=None))]
+pub(crate) fn unassign_tag(
+    name: &str,
+    instance_id: String,
+    model_id: Option<String>,
+    home_dir: Option<&str>,
+) -> PyResult<bool> {
+    let store = config_store(home_dir)?;
+    let lock = store.lock().map_err(|e| core_error(&e))?;
+    let mut assignments: Vec<LabelAssignment> = load_list(&store, TAG_ASSIGNMENTS_FILE)?;
+    let target = target(instance_id, model_id);
+    let count = assignments.len();
+    assignments
+        .retain(|assignment| !(assignment.label_name == name && assignment.target == target));
+    if assignments.len() == count {
+        return Ok(false);
+    }
+    save_list(&lock, TAG_ASSIGNMENTS_FILE, &assignments)?;
+    Ok(true)
+}
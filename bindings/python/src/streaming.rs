@@ -0,0 +1,131 @@
+//! Scans yielding their keys as they are found, for `scan_iter()`
+
+use crate::core_error;
+use crate::results::{DiscoveredKey, ScanResult};
+use aicred_core::models::{DiscoveredCredential, ScanResult as CoreScanResult};
+use aicred_core::{error::Result, CancellationToken, ScanEvent, ScanOptions};
+use pyo3::prelude::*;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, PoisonError};
+
+/// What the scan thread sends to the iterator
+enum Message {
+    Progress {
+        scanner: String,
+        root: String,
+        completed: usize,
+        total: usize,
+    },
+    Key(Box<DiscoveredCredential>),
+    Done(Box<Result<CoreScanResult>>),
+}
+
+/// Iterator over the keys of a running scan, returned by `scan_iter()`.
+#[pyclass(module = "aicred")]
+pub struct ScanIterator {
+    receiver: Mutex<Receiver<Message>>,
+    on_progress: Option<Py<PyAny>>,
+    cancellation: CancellationToken,
+    result: Option<ScanResult>,
+}
+
+impl ScanIterator {
+    /// Starts the scan on a thread of its own
+    pub(crate) fn start(
+        mut options: ScanOptions,
+        on_progress: Option<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        let cancellation = CancellationToken::new();
+        options.cancellation = Some(cancellation.clone());
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("aicred-scan".to_string())
+            .spawn(move || {
+                let result = aicred_core::scan_with_progress(&options, |event| {
+                    let message = match event {
+                        ScanEvent::Scanner {
+                            scanner,
+                            root,
+                            completed,
+                            total,
+                        } => Message::Progress {
+                            scanner: scanner.to_string(),
+                            root: root.to_string(),
+                            completed,
+                            total,
+                        },
+                        ScanEvent::Key(key) => Message::Key(Box::new(key.clone())),
+                    };
+                    let _ = sender.send(message);
+                });
+                let _ = sender.send(Message::Done(Box::new(result)));
+            })
+            .map_err(|e| core_error(&e.into()))?;
+
+        Ok(Self {
+            receiver: Mutex::new(receiver),
+            on_progress,
+            cancellation,
+            result: None,
+        })
+    }
+}
+
+#[pymethods]
+impl ScanIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<DiscoveredKey>> {
+        loop {
+            let receiver = &self.receiver;
+            let message = py.allow_threads(|| {
+                receiver
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .recv()
+            });
+            match message {
+                Ok(Message::Progress {
+                    scanner,
+                    root,
+                    completed,
+                    total,
+                }) => {
+                    if let Some(on_progress) = &self.on_progress {
+                        if let Err(err) = on_progress.call1(py, (scanner, root, completed, total)) {
+                            self.cancellation.cancel();
+                            return Err(err);
+                        }
+                    }
+                }
+                Ok(Message::Key(key)) => return Ok(Some(DiscoveredKey(*key))),
+                Ok(Message::Done(result)) => {
+                    self.result = Some(ScanResult((*result).map_err(|e| core_error(&e))?));
+                    return Ok(None);
+                }
+                // The scan has finished and its result was taken
+                Err(_) => return Ok(None),
+            }
+        }
+    }
+
+    /// The whole result, once the iterator is exhausted
+    #[getter]
+    fn result(&self) -> Option<ScanResult> {
+        self.result.clone()
+    }
+
+    /// Stop the scan; the iterator then raises its cancellation error
+    fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+}
+
+impl Drop for ScanIterator {
+    fn drop(&mut self) {
+        self.cancellation.cancel();
+    }
+}
@@ -1,5 +1,10 @@
+import ast
+import asyncio
+import dataclasses
 import pytest
 import aicred
+import os
+import sys
 import tempfile
 
 
@@ -30,7 +35,7 @@ def test_scan_basic():
     """Test basic scan functionality"""
     with tempfile.TemporaryDirectory() as tmpdir:
         result = aicred.scan(home_dir=tmpdir)
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
         assert "keys" in result
         assert "config_instances" in result
         assert "home_directory" in result
@@ -47,15 +52,53 @@ def test_scan_with_options():
             max_file_size=512000,
             only_providers=["openai", "anthropic"],
         )
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
         assert isinstance(result["keys"], list)
 
 
+def test_scan_options_class():
+    """Test scanning with a ScanOptions and its compatibility with the old arguments"""
+    with tempfile.TemporaryDirectory() as home, tempfile.TemporaryDirectory() as extra:
+        with open(f"{extra}/.env", "w") as env:
+            env.write("ANTHROPIC_API_KEY=sk-ant-REDACTED\n")
+
+        options = aicred.ScanOptions(home, redaction="last4", parallelism=2)
+        assert options.redaction == "last4"
+        assert options.extra_roots == []
+        assert aicred.scan(options).keys == []
+
+        options.extra_roots = [extra]
+        options.offline = True
+        result = aicred.scan(options)
+        key = next(key for key in result.keys if key.provider == "anthropic")
+        assert key.value.endswith("wxyz")
+        assert "ScanOptions(home_dir=" in repr(options)
+
+        options.only_value_types = ["Model ID"]
+        assert not any(k.provider == "anthropic" for k in aicred.scan(options).keys)
+
+        # The home directory as the first argument, as before
+        assert aicred.scan(extra).home_directory == extra
+        assert aicred.scan(home_dir=extra, redaction="last4").keys[0].value.endswith("wxyz")
+
+        with pytest.raises(TypeError):
+            aicred.scan(options, redaction="none")
+        with pytest.raises(TypeError):
+            aicred.scan(extra, home_dir=extra)
+        with pytest.raises(ValueError):
+            aicred.ScanOptions(redaction="shout")
+        with pytest.raises(ValueError):
+            aicred.ScanOptions(parallelism=0)
+        with pytest.raises(aicred.ScanError):
+            aicred.scan(aicred.ScanOptions(extra_roots=[f"{home}/missing"]))
+
+
+
 def test_scan_with_exclude():
     """Test scan with exclude_providers"""
     with tempfile.TemporaryDirectory() as tmpdir:
         result = aicred.scan(home_dir=tmpdir, exclude_providers=["ollama"])
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
 
 
 def test_scan_invalid_home():
@@ -64,6 +107,33 @@ def test_scan_invalid_home():
         aicred.scan(home_dir="/nonexistent/path/that/does/not/exist")
 
 
+def test_error_classes():
+    """Test that core errors map to a hierarchy of RuntimeError subclasses"""
+    assert issubclass(aicred.AicredError, RuntimeError)
+    for cls, parent in (
+        (aicred.ScanError, aicred.AicredError),
+        (aicred.ConfigError, aicred.AicredError),
+        (aicred.PluginError, aicred.AicredError),
+        (aicred.NetworkError, aicred.AicredError),
+        (aicred.IoDeniedError, aicred.ScanError),
+        (aicred.ParseFailedError, aicred.ScanError),
+        (aicred.PluginConflictError, aicred.PluginError),
+        (aicred.StoreCorruptError, aicred.ConfigError),
+        (aicred.NetworkTimeoutError, aicred.NetworkError),
+    ):
+        assert issubclass(cls, parent)
+
+    with pytest.raises(aicred.ScanError) as error:
+        aicred.scan(home_dir="/nonexistent/path/that/does/not/exist")
+    assert error.value.code == 100
+    with tempfile.TemporaryDirectory() as tmpdir:
+        os.makedirs(f"{tmpdir}/.config/aicred")
+        with open(f"{tmpdir}/.config/aicred/labels.yaml", "w") as labels:
+            labels.write("- [not, a, label\n")
+        with pytest.raises(aicred.ConfigError):
+            aicred.list_labels(home_dir=tmpdir)
+
+
 def test_result_structure():
     """Test that result has expected structure"""
     with tempfile.TemporaryDirectory() as tmpdir:
@@ -78,32 +148,302 @@ def test_result_structure():
         assert isinstance(result["providers_scanned"], list)
 
 
+def test_typed_scan_result():
+    """Test attribute access on the typed scan result"""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with open(f"{tmpdir}/.env", "w") as env:
+            env.write("ANTHROPIC_API_KEY=sk-ant-REDACTED\n")
+        result = aicred.scan(home_dir=tmpdir, redaction="last4")
+
+        assert result.home_directory == result["home_directory"]
+        assert result.summary["total_keys"] == len(result.keys)
+        key = next(key for key in result.keys if key.provider == "anthropic")
+        assert isinstance(key, aicred.DiscoveredKey)
+        assert key.value.endswith("wxyz")
+        assert "1234567890" not in key.value
+        assert key.source_file.endswith(".env")
+        assert key.to_dict()["provider"] == "anthropic"
+        assert "DiscoveredKey(provider='anthropic'" in repr(key)
+        assert all(isinstance(i, aicred.ConfigInstance) for i in result.instances)
+        assert result.to_dict()["keys"][0]["provider"] == result.keys[0].provider
+
+
+
+def test_result_collections_and_dataclasses():
+    """Test len() and iteration of results and their dataclass conversion"""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with open(f"{tmpdir}/.env", "w") as env:
+            env.write("ANTHROPIC_API_KEY=sk-ant-REDACTED\n")
+        result = aicred.scan(home_dir=tmpdir)
+
+        assert len(result) == len(result.keys) > 0
+        assert [key.hash for key in result] == [key.hash for key in result.keys]
+        for instance in result.instances:
+            assert len(instance) == len(instance.keys)
+            assert [key.hash for key in instance] == [key.hash for key in instance.keys]
+
+        data = aicred.as_dataclasses(result)
+        assert isinstance(data, aicred.ScanResultData)
+        assert dataclasses.is_dataclass(data)
+        assert data.home_directory == result.home_directory
+        assert all(isinstance(key, aicred.DiscoveredKeyData) for key in data.keys)
+        assert dataclasses.asdict(data)["keys"][0]["provider"] == result.keys[0].provider
+        with pytest.raises(dataclasses.FrozenInstanceError):
+            data.home_directory = "/"
+
+        keys = aicred.as_dataclasses(result.keys)
+        assert [key.hash for key in keys] == [key.hash for key in result.keys]
+        with pytest.raises(TypeError):
+            aicred.as_dataclasses(42)
+
+
+def test_to_dataframe():
+    """Test the DataFrame export, with a stand-in for pandas"""
+
+    class FakePandas:
+        @staticmethod
+        def DataFrame(columns):
+            return columns
+
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with open(f"{tmpdir}/.env", "w") as env:
+            env.write("ANTHROPIC_API_KEY=sk-ant-REDACTED\n")
+        result = aicred.scan(home_dir=tmpdir)
+
+        saved = sys.modules.get("pandas"), sys.modules.get("polars")
+        sys.modules["pandas"], sys.modules["polars"] = FakePandas, None
+        try:
+            columns = result.to_dataframe()
+            with pytest.raises(ImportError):
+                result.to_dataframe("polars")
+            with pytest.raises(ValueError):
+                result.to_dataframe("spark")
+        finally:
+            for name, module in zip(["pandas", "polars"], saved):
+                if module is None:
+                    sys.modules.pop(name, None)
+                else:
+                    sys.modules[name] = module
+
+        rows = len(result.keys) + sum(len(i) for i in result.instances)
+        assert list(columns) == [
+            "provider",
+            "value_type",
+            "confidence",
+            "risk",
+            "path",
+            "line",
+            "app_name",
+            "instance_id",
+        ]
+        assert all(len(column) == rows for column in columns.values())
+        assert "anthropic" in columns["provider"]
+        assert any(path.endswith(".env") for path in columns["path"])
+
+
+def test_stub_matches_module():
+    """Test that aicred.pyi declares what the module has, and nothing else"""
+    stub = os.path.join(os.path.dirname(__file__), "..", "aicred.pyi")
+    with open(stub) as f:
+        tree = ast.parse(f.read())
+
+    declared = {}
+    for node in tree.body:
+        if isinstance(node, ast.ClassDef):
+            declared[node.name] = {
+                item.name if not isinstance(item, ast.AnnAssign) else item.target.id
+                for item in node.body
+                if isinstance(item, (ast.FunctionDef, ast.AnnAssign))
+            }
+        elif isinstance(node, ast.FunctionDef):
+            declared[node.name] = None
+        elif isinstance(node, ast.AnnAssign):
+            declared[node.target.id] = None
+
+    public = {name for name in dir(aicred) if not name.startswith("_")}
+    assert set(declared) == public
+
+    for name, members in declared.items():
+        value = getattr(aicred, name)
+        if members is None or issubclass(value, BaseException):
+            continue
+        if dataclasses.is_dataclass(value):
+            actual = {field.name for field in dataclasses.fields(value)}
+        else:
+            actual = {member for member in vars(value) if not member.startswith("__")}
+        declared_members = {member for member in members if not member.startswith("__")}
+        assert declared_members == actual, name
+
+
+def test_model_registry():
+    """Test lookups in the model registry and its pricing"""
+    models = aicred.models
+    assert isinstance(models, aicred.ModelRegistry)
+    assert len(models) > 0 and "gpt-4o" in models
+
+    entry = models.get("gpt-4o")
+    assert isinstance(entry, aicred.ModelEntry)
+    assert entry.provider == "openai"
+    assert "vision" in entry.capabilities and entry.supports("tools")
+    assert entry.status == "active"
+    assert isinstance(entry.pricing, aicred.ModelPricing)
+    assert entry.pricing.currency == "USD"
+    assert abs(entry.pricing.input_cost_per_million - 2.5) < 1e-9
+    cost = entry.cost(1_000_000, 1_000_000)
+    assert isinstance(cost, aicred.ModelCost)
+    assert abs(cost.total_cost - 12.5) < 1e-9
+    assert entry.to_dict()["id"] == "gpt-4o"
+    assert models.get("openai/gpt-4o").id == "gpt-4o"
+    assert models.get("no-such-model") is None
+
+    assert all(e.supports("vision") for e in models.by_capability("vision"))
+    assert not any(e.id.startswith("text-embedding") for e in models.by_capability("vision"))
+    with pytest.raises(ValueError):
+        models.by_capability("telepathy")
+    claude = models.search("CLAUDE")
+    assert claude and all(e.provider == "anthropic" for e in claude)
+    assert models.search("claude", provider="openai") == []
+    assert {e.provider for e in models.by_provider("gemini")} == {"gemini"}
+    cheapest = models.recommend(["vision"], max_input_cost=1.0)
+    assert cheapest and all(e.pricing.input_cost_per_million <= 1.0 for e in cheapest)
+    assert [e.id for e in models] == [e.id for e in models.all()]
+
+    with tempfile.TemporaryDirectory() as tmpdir:
+        assert len(aicred.ModelRegistry(tmpdir)) == len(aicred.ModelRegistry(builtin=True))
+
+
+def test_scan_iter_yields_keys_and_progress():
+    """Test streaming a scan and reporting its progress"""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with open(f"{tmpdir}/.env", "w") as env:
+            env.write("ANTHROPIC_API_KEY=sk-ant-REDACTED\n")
+        progress = []
+        scan = aicred.scan_iter(
+            home_dir=tmpdir, on_progress=lambda *args: progress.append(args)
+        )
+        assert scan.result is None
+        keys = list(scan)
+        assert any(key.provider == "anthropic" for key in keys)
+        assert len(scan.result.keys) == len(keys)
+        assert progress
+        scanner, root, completed, total = progress[-1]
+        assert isinstance(scanner, str) and completed < total
+
+        calls = []
+        aicred.scan(home_dir=tmpdir, on_progress=lambda *args: calls.append(args))
+        assert len(calls) == len(progress)
+
+
+def test_on_progress_exception_stops_scan():
+    """Test that an exception of on_progress is raised by the scan"""
+
+    def fail(*_):
+        raise KeyboardInterrupt
+
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with pytest.raises(KeyboardInterrupt):
+            aicred.scan(home_dir=tmpdir, on_progress=fail)
+        with pytest.raises(KeyboardInterrupt):
+            list(aicred.scan_iter(home_dir=tmpdir, on_progress=fail))
+
+
+def test_scan_async():
+    """Test awaiting a scan and a model probe"""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with open(f"{tmpdir}/.env", "w") as env:
+            env.write("ANTHROPIC_API_KEY=sk-ant-REDACTED\n")
+
+        async def main():
+            result = await aicred.scan_async(home_dir=tmpdir)
+            assert any(key.provider == "anthropic" for key in result.keys)
+            with pytest.raises(aicred.AicredError) as error:
+                await aicred.probe_models_async("no-such-provider", "key")
+            assert error.value.code == 102
+
+        asyncio.run(main())
+
+    with pytest.raises(RuntimeError):
+        aicred.scan_async()
+
+
+def test_register_scanner():
+    """Test a scanner written in Python running in scans"""
+
+    class MyToolScanner:
+        name = "my-tool"
+
+        def scan_paths(self, home_dir):
+            return [f"{home_dir}/.mytool/config"]
+
+        def parse_config(self, path, content):
+            return [
+                {"provider": "openai", "value": value, "line": number}
+                for number, line in enumerate(content.splitlines(), 1)
+                for name, _, value in [line.partition("=")]
+                if name == "token"
+            ]
+
+    aicred.register_scanner(MyToolScanner())
+    try:
+        assert "my-tool" in aicred.list_scanners()
+        with pytest.raises(aicred.PluginConflictError):
+            aicred.register_scanner(MyToolScanner())
+
+        with tempfile.TemporaryDirectory() as tmpdir:
+            os.makedirs(f"{tmpdir}/.mytool")
+            with open(f"{tmpdir}/.mytool/config", "w") as config:
+                config.write("user=me\ntoken=sk-mytool-1234567890abcdef\n")
+            result = aicred.scan(home_dir=tmpdir, include_full_values=True)
+            [key] = [key for key in result.keys if key.scanner == "my-tool"]
+            assert key.provider == "openai"
+            assert key.value == "sk-mytool-1234567890abcdef"
+            assert key.source_line == 2
+    finally:
+        assert aicred.unregister_scanner("my-tool")
+    assert "my-tool" not in aicred.list_scanners()
+
+    class GshScanner(MyToolScanner):
+        name = "gsh"
+
+    with pytest.raises(aicred.PluginConflictError):
+        aicred.register_scanner(GshScanner())
+
+
 def test_scan_with_full_values():
     """Test scanning with full values enabled."""
     with tempfile.TemporaryDirectory() as tmpdir:
         result = aicred.scan(home_dir=tmpdir, include_full_values=True)
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
+
+
+def test_scan_with_redaction_mode():
+    """Test scanning with a redaction mode, rejecting unknown modes."""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        result = aicred.scan(home_dir=tmpdir, redaction="fingerprint")
+        assert isinstance(result, aicred.ScanResult)
+        with pytest.raises(ValueError):
+            aicred.scan(home_dir=tmpdir, redaction="partial")
 
 
 def test_scan_with_max_file_size():
     """Test scanning with custom max file size."""
     with tempfile.TemporaryDirectory() as tmpdir:
         result = aicred.scan(home_dir=tmpdir, max_file_size=512000)
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
 
 
 def test_scan_with_provider_filter():
     """Test scanning with provider filtering."""
     with tempfile.TemporaryDirectory() as tmpdir:
         result = aicred.scan(home_dir=tmpdir, only_providers=["openai", "anthropic"])
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
 
 
 def test_scan_with_exclude_filter():
     """Test scanning with provider exclusion."""
     with tempfile.TemporaryDirectory() as tmpdir:
         result = aicred.scan(home_dir=tmpdir, exclude_providers=["ollama"])
-        assert isinstance(result, dict)
+        assert isinstance(result, aicred.ScanResult)
 
 
 # New tests for enhanced model and provider instance structures
@@ -250,6 +590,10 @@ def test_provider_instances_collection():
     assert instances.is_empty() is False
     assert "openai-1" in instances.instance_ids()
     assert "anthropic-1" in instances.instance_ids()
+    assert len(instances) == 2
+    assert "openai-1" in instances
+    assert "missing" not in instances
+    assert [instance.id for instance in instances] == ["anthropic-1", "openai-1"]
 
 
 def test_provider_instances_filtering():
@@ -298,6 +642,84 @@ def test_provider_instances_filtering():
     assert len(active_openai) == 1
 
 
+def test_instances_keep_keys_and_models():
+    """Test that copies out of ProviderInstances keep keys and models"""
+    instance = aicred.ProviderInstance(
+        id="openai-1",
+        display_name="OpenAI",
+        provider_type="openai",
+        base_url="https://api.openai.com",
+    )
+    instance.add_key("sk-test-1234567890abcdef")
+    instance.add_model(
+        aicred.Model(model_id="gpt-4o", provider_instance_id="openai-1", name="GPT-4o")
+    )
+    instances = aicred.ProviderInstances()
+    instances.add_instance(instance)
+
+    copy = instances.get_instance("openai-1")
+    assert copy.keys == ["sk-test-1234567890abcdef"]
+    assert [model.model_id for model in copy.models] == ["gpt-4o"]
+
+
+def test_store_instances_labels_and_tags():
+    """Test managing the configuration store of a home directory"""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        assert aicred.ProviderInstances.load(home_dir=tmpdir).is_empty()
+
+        instance = aicred.ProviderInstance(
+            id="openai-work",
+            display_name="OpenAI",
+            provider_type="openai",
+            base_url="https://api.openai.com/v1",
+        )
+        instance.add_key("sk-test-1234567890abcdef")
+        instance.add_model(
+            aicred.Model(
+                model_id="gpt-4o", provider_instance_id="openai-work", name="GPT-4o"
+            )
+        )
+        instances = aicred.ProviderInstances()
+        instances.add_instance(instance)
+        instances.save(home_dir=tmpdir)
+
+        loaded = aicred.ProviderInstances.load(home_dir=tmpdir)
+        stored = loaded.get_instance("openai-work")
+        assert stored.keys == ["sk-test-1234567890abcdef"]
+        assert [model.model_id for model in stored.models] == ["gpt-4o"]
+
+        assert aicred.set_label("smart", "openai-work", home_dir=tmpdir) is False
+        assert aicred.set_label(
+            "smart", "openai-work", "gpt-4o", description="Best", home_dir=tmpdir
+        )
+        [label] = aicred.list_labels(home_dir=tmpdir)
+        assert label["label_name"] == "smart"
+        assert label["description"] == "Best"
+        resolved = aicred.resolve_label("smart", home_dir=tmpdir)
+        assert resolved["instance"]["id"] == "openai-work"
+        assert resolved["model"]["id"] == "gpt-4o"
+        assert aicred.unset_label("smart", home_dir=tmpdir)
+        assert not aicred.unset_label("smart", home_dir=tmpdir)
+        with pytest.raises(aicred.AicredError):
+            aicred.resolve_label("smart", home_dir=tmpdir)
+
+        aicred.add_tag("prod", description="Production", home_dir=tmpdir)
+        with pytest.raises(ValueError):
+            aicred.add_tag("prod", home_dir=tmpdir)
+        aicred.assign_tag("prod", "openai-work", home_dir=tmpdir)
+        [tag] = aicred.list_tags(home_dir=tmpdir)
+        assert tag["assignments"] == [
+            {"type": "provider_instance", "instance_id": "openai-work"}
+        ]
+        assert aicred.unassign_tag("prod", "openai-work", home_dir=tmpdir)
+        assert aicred.remove_tag("prod", home_dir=tmpdir)
+        assert aicred.list_tags(home_dir=tmpdir) == []
+
+        assert aicred.remove_instance("openai-work", home_dir=tmpdir)
+        assert not aicred.remove_instance("openai-work", home_dir=tmpdir)
+        assert aicred.ProviderInstances.load(home_dir=tmpdir).is_empty()
+
+
 def test_model_metadata_getter():
     """Test Model metadata getter functionality"""
     model = aicred.Model(
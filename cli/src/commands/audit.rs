@@ -0,0 +1,27 @@
+//! Audit log commands for the aicred CLI.
+
+use aicred_core::audit::AuditLog;
+use anyhow::Result;
+use colored::*;
+
+/// Handle the audit verify command
+pub fn handle_verify_audit_log(file: String) -> Result<()> {
+    let log = AuditLog::new(&file);
+    let records = log.read()?.len();
+    let verified = log.verify()?;
+    if verified == 0 {
+        anyhow::bail!(
+            "{} has no hash-chained records (write them with 'aicred scan --audit-log {} --audit-chain')",
+            file,
+            file
+        );
+    }
+    println!(
+        "{} {} of {} record(s) in {} have an intact hash chain.",
+        "✓".green(),
+        verified,
+        records,
+        file
+    );
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! Budget commands and the budget checks of commands handing out keys.
+
+use crate::commands::cost::format_cost;
+use crate::commands::usage::usage_ledger;
+use crate::utils::provider_loader::config_store;
+use aicred_core::budget::{Budget, BudgetScope, BudgetStatus, Budgets, BUDGETS_FILE};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use colored::*;
+use std::path::Path;
+
+pub use aicred_app::budgets::load_budgets;
+
+/// The scope and name given by --instance or --label.
+fn scope_of(instance: Option<String>, label: Option<String>) -> Result<(BudgetScope, String)> {
+    match (instance, label) {
+        (Some(instance), None) => Ok((BudgetScope::Instance, instance)),
+        (None, Some(label)) => Ok((BudgetScope::Label, label)),
+        _ => Err(anyhow!("Give either --instance or --label")),
+    }
+}
+
+/// Warns about the spent budgets of a label and the instance it resolved to,
+/// or fails if one of them refuses to hand out keys once spent.
+pub fn enforce_budgets(
+    label: Option<&str>,
+    instance_id: Option<&str>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let budgets = load_budgets(home)?;
+    if budgets.is_empty() {
+        return Ok(());
+    }
+    let records = usage_ledger(home)?.load()?;
+    let now = Utc::now();
+    let spent = [
+        label.and_then(|label| budgets.status(BudgetScope::Label, label, &records, now)),
+        instance_id.and_then(|id| budgets.status(BudgetScope::Instance, id, &records, now)),
+    ];
+    for status in spent.iter().flatten().filter(|status| status.exceeded()) {
+        if status.refuse {
+            return Err(anyhow!(
+                "Budget exceeded: {}. Raise it with 'aicred budget set' or wait for next month.",
+                status
+            ));
+        }
+        eprintln!("{} budget exceeded: {}", "Warning:".yellow(), status);
+    }
+    Ok(())
+}
+
+/// Formats how much of a budget is spent, such as "$45.00 of $50.00 (90%)".
+pub fn format_utilization(status: &BudgetStatus) -> String {
+    format!(
+        "{} of {} ({:.0}%)",
+        format_cost(status.spent),
+        format_cost(status.monthly),
+        status.utilization() * 100.0
+    )
+}
+
+/// Handle the budget set command
+pub fn handle_set_budget(
+    instance: Option<String>,
+    label: Option<String>,
+    monthly: f64,
+    refuse: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let (scope, name) = scope_of(instance, label)?;
+    if !monthly.is_finite() || monthly < 0.0 {
+        return Err(anyhow!("Invalid budget, expected a non-negative amount"));
+    }
+    let store = config_store(home)?;
+    let lock = store.lock()?;
+    let mut budgets: Budgets = lock.store().load_yaml(BUDGETS_FILE)?.unwrap_or_default();
+    budgets.set(scope, name.clone(), Budget { monthly, refuse });
+    lock.save_yaml(BUDGETS_FILE, &budgets)?;
+
+    println!(
+        "{} Monthly budget of {} '{}' set to {}{}",
+        "✓".green(),
+        scope,
+        name,
+        format_cost(monthly),
+        if refuse {
+            ", refusing keys once spent"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Handle the budget remove command
+pub fn handle_remove_budget(
+    instance: Option<String>,
+    label: Option<String>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let (scope, name) = scope_of(instance, label)?;
+    let store = config_store(home)?;
+    let lock = store.lock()?;
+    let mut budgets: Budgets = lock.store().load_yaml(BUDGETS_FILE)?.unwrap_or_default();
+    if !budgets.remove(scope, &name) {
+        return Err(anyhow!("No budget is set for {} '{}'", scope, name));
+    }
+    lock.save_yaml(BUDGETS_FILE, &budgets)?;
+    println!("{} Removed the budget of {} '{}'", "✓".green(), scope, name);
+    Ok(())
+}
+
+/// Handle the budget list command
+pub fn handle_list_budgets(format: String, home: Option<&Path>) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    let statuses = load_budgets(home)?.statuses(&usage_ledger(home)?.load()?, Utc::now());
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+    if statuses.is_empty() {
+        println!("{}", "No budgets set.".yellow());
+        println!(
+            "{}",
+            "Use 'aicred budget set --label smart --monthly 50' to set one.".dimmed()
+        );
+        return Ok(());
+    }
+
+    println!("\n{}", "Budgets this month:".green().bold());
+    println!(
+        "{:<10} {:<24} {:<30} {}",
+        "Scope".bold(),
+        "Name".bold(),
+        "Spent".bold(),
+        "When spent".bold()
+    );
+    println!("{}", "-".repeat(78));
+    for status in &statuses {
+        let spent = format_utilization(status);
+        let spent = if status.exceeded() {
+            spent.red()
+        } else {
+            spent.normal()
+        };
+        println!(
+            "{:<10} {:<24} {:<30} {}",
+            status.scope.as_str(),
+            status.name.cyan(),
+            spent,
+            if status.refuse { "refuse" } else { "warn" }
+        );
+    }
+    Ok(())
+}
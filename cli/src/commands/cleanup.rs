@@ -0,0 +1,233 @@
+//! Cleanup command for the aicred CLI.
+
+use crate::commands::scan::get_default_base_url;
+use crate::plan::{print_plan, ChangeAction, ChangeKind, ChangePlan, PlanFormat};
+use crate::utils::provider_loader::{base_config_store, config_store, load_provider_instances};
+use aicred_core::cleanup::{self, Rewrite};
+use aicred_core::discovery::ids::key_instance_id;
+use aicred_core::fingerprint::{Fingerprinter, HashAlgorithm};
+use aicred_core::models::{DiscoveredCredential, Provenance, ProviderInstance};
+use aicred_core::{ScanOptions, SecretBackends};
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::Path;
+
+/// Handle the cleanup command
+#[allow(clippy::too_many_arguments)]
+pub fn handle_cleanup(
+    hashes: Vec<String>,
+    provider: Option<String>,
+    all: bool,
+    store: String,
+    env_var: Option<String>,
+    no_rewrite: bool,
+    dry_run: Option<PlanFormat>,
+    yes: bool,
+    hash_algorithm: HashAlgorithm,
+    home: Option<&Path>,
+) -> Result<()> {
+    if hashes.is_empty() && provider.is_none() && !all {
+        return Err(anyhow!(
+            "Select findings with --hash or --provider, or pass --all"
+        ));
+    }
+    let backends = SecretBackends::builtin();
+    if backends.get(&store).is_none() {
+        return Err(anyhow!(
+            "Unknown secret store '{}'. Supported: {}",
+            store,
+            backends.names().join(", ")
+        ));
+    }
+
+    let config = config_store(home)?;
+    let mut options =
+        ScanOptions::new()
+            .with_full_values(true)
+            .with_fingerprint(Fingerprinter::load(
+                &base_config_store(home)?,
+                hash_algorithm,
+            )?);
+    if let Some(home) = home {
+        options = options.with_home_dir(home.to_path_buf());
+    }
+    let result = aicred_core::scan(&options)?;
+
+    let prefixes: Vec<String> = hashes.iter().map(|h| h.to_ascii_lowercase()).collect();
+    let selected: Vec<&DiscoveredCredential> = result
+        .keys
+        .iter()
+        .filter(|key| prefixes.is_empty() || prefixes.iter().any(|p| key.hash.starts_with(p)))
+        .filter(|key| {
+            provider
+                .as_ref()
+                .is_none_or(|p| key.provider.eq_ignore_ascii_case(p))
+        })
+        .filter(|key| !Path::new(&key.source_file).starts_with(config.dir()))
+        .collect();
+
+    if selected.is_empty() {
+        println!("{}", "No findings match the selection.".yellow());
+        return Ok(());
+    }
+
+    let mut plan = dry_run.map(|_| ChangePlan::new("cleanup"));
+
+    let mut cleaned = 0;
+    for key in selected {
+        let Some(secret) = key.full_value() else {
+            println!(
+                "{} Skipping {}: the key's value is not available",
+                "-".yellow(),
+                &key.hash[..12]
+            );
+            continue;
+        };
+        let var = env_var
+            .clone()
+            .unwrap_or_else(|| cleanup::default_env_var(&key.provider));
+
+        let mut files = vec![key.source_file.clone()];
+        for source in &key.sources {
+            if !files.contains(source) {
+                files.push(source.clone());
+            }
+        }
+        let rewrites = if no_rewrite {
+            Vec::new()
+        } else {
+            let mut rewrites = Vec::new();
+            for file in &files {
+                if let Some(rewrite) =
+                    Rewrite::plan(Path::new(file), secret, &cleanup::env_reference(&var))?
+                {
+                    rewrites.push(rewrite);
+                }
+            }
+            rewrites
+        };
+
+        let existing = find_instance_with_key(secret, home)?;
+        if let Some(plan) = plan.as_mut() {
+            if existing.is_none() {
+                let id = key_instance_id(key);
+                plan.add(ChangeAction::Create, ChangeKind::Instance, &id)
+                    .detail(format!("{} key {}", key.provider, &key.hash[..12]));
+                if store != "file" {
+                    plan.add(ChangeAction::Create, ChangeKind::Secret, &id)
+                        .detail(format!("stored in {}", store));
+                }
+            }
+            for rewrite in &rewrites {
+                let change = plan
+                    .add(
+                        ChangeAction::Update,
+                        ChangeKind::File,
+                        rewrite.path().display().to_string(),
+                    )
+                    .at(rewrite.path());
+                for line in rewrite.changes() {
+                    change
+                        .detail(format!("{:>4} - {}", line.line, line.before))
+                        .detail(format!("{:>4} + {}", line.line, line.after));
+                }
+                plan.add(
+                    ChangeAction::Create,
+                    ChangeKind::File,
+                    rewrite.backup_path().display().to_string(),
+                )
+                .at(&rewrite.backup_path())
+                .detail(format!("backup of {}", rewrite.path().display()));
+            }
+            continue;
+        }
+
+        println!(
+            "\n{} key {} in {}",
+            key.provider.cyan().bold(),
+            key.hash[..12].cyan(),
+            files.join(", ")
+        );
+        match &existing {
+            Some(id) => println!("  Already stored as instance '{}'", id),
+            None => println!("  Store as instance '{}' ({})", key_instance_id(key), store),
+        }
+        for rewrite in &rewrites {
+            println!("  Rewrite {}:", rewrite.path().display());
+            for change in rewrite.changes() {
+                println!("    {:>4} - {}", change.line, change.before.red());
+                println!("    {:>4} + {}", change.line, change.after.green());
+            }
+            println!("  Back up to {}", rewrite.backup_path().display());
+        }
+
+        if !yes && !confirm("Clean up this key?")? {
+            println!("{}", "  Skipped.".dimmed());
+            continue;
+        }
+
+        if existing.is_none() {
+            let id = key_instance_id(key);
+            let mut instance = ProviderInstance::new(
+                id.clone(),
+                key.provider.clone(),
+                get_default_base_url(&key.provider),
+                String::new(),
+                Vec::new(),
+            )
+            .with_provenance(Provenance::from_key(key, key.discovered_at));
+            instance.set_api_key(backends.store(&store, &id, secret)?);
+            config.lock()?.save_instance(&instance)?;
+            println!("  {} Stored as instance '{}'", "✓".green(), id);
+        }
+        for rewrite in &rewrites {
+            let backup = rewrite.apply()?;
+            println!(
+                "  {} Rewrote {} (backup: {})",
+                "✓".green(),
+                rewrite.path().display(),
+                backup.display()
+            );
+        }
+        if !rewrites.is_empty() {
+            println!(
+                "  Set {} when running tools, e.g. `aicred wrap -- <command>`",
+                var.cyan()
+            );
+        }
+        cleaned += 1;
+    }
+
+    if let (Some(plan), Some(format)) = (plan, dry_run) {
+        return print_plan(&plan, format);
+    }
+    println!("\n{} Cleaned up {} key(s).", "✓".green(), cleaned);
+    if cleaned > 0 && !no_rewrite {
+        println!(
+            "{}",
+            "Backups still contain the keys; delete them once the rewritten files work.".yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Find an instance already holding a key in plain text
+fn find_instance_with_key(secret: &str, home: Option<&Path>) -> Result<Option<String>> {
+    let instances = load_provider_instances(home)?;
+    Ok(instances
+        .all_instances()
+        .into_iter()
+        .find(|instance| instance.api_key == secret)
+        .map(|instance| instance.id.clone()))
+}
+
+/// Ask a yes/no question, defaulting to no
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{self, Write};
+    print!("{} (y/N): ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
@@ -0,0 +1,118 @@
+//! Shell completion for the aicred CLI.
+//!
+//! Completions are dynamic: the script printed by `aicred completions <shell>`
+//! calls back into aicred with `COMPLETE=<shell>` set, so instance IDs, tag
+//! names and label names are completed from the config store at the time of
+//! completion rather than when the script was generated. The `--home` and
+//! `--profile` flags on the command line are honoured.
+
+use crate::commands::labels::load_label_assignments_with_home;
+use crate::commands::profile;
+use crate::commands::tags::load_tags;
+use crate::utils::provider_loader::{load_provider_instances, set_profile_override};
+use anyhow::{anyhow, Result};
+use clap_complete::env::Shells;
+use clap_complete::CompletionCandidate;
+use std::path::PathBuf;
+
+/// Environment variable that switches aicred into completion mode
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Value of a flag such as `--home` on the command line being completed
+fn completion_arg(flag: &str) -> Option<String> {
+    let mut args = std::env::args_os().skip_while(|arg| arg != "--");
+    while let Some(arg) = args.next() {
+        let arg = arg.to_string_lossy().into_owned();
+        if arg == flag {
+            return args
+                .next()
+                .map(|value| value.to_string_lossy().into_owned());
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Home directory given with `--home` on the command line being completed,
+/// selecting the profile given with `--profile` as well
+fn completion_home() -> Option<PathBuf> {
+    if let Some(name) = completion_arg("--profile") {
+        set_profile_override(name);
+    }
+    completion_arg("--home").map(PathBuf::from)
+}
+
+/// Completes instance IDs, with the provider type as help
+pub fn instance_ids() -> Vec<CompletionCandidate> {
+    let Ok(collection) = load_provider_instances(completion_home().as_deref()) else {
+        return Vec::new();
+    };
+    let mut instances = collection.all_instances();
+    instances.sort_by(|a, b| a.id.cmp(&b.id));
+    instances
+        .into_iter()
+        .map(|instance| {
+            CompletionCandidate::new(&instance.id).help(Some(instance.provider_type.clone().into()))
+        })
+        .collect()
+}
+
+/// Completes tag names, with the tag description as help
+pub fn tag_names() -> Vec<CompletionCandidate> {
+    let mut tags = load_tags(completion_home().as_deref()).unwrap_or_default();
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    tags.into_iter()
+        .map(|tag| CompletionCandidate::new(tag.name).help(tag.description.map(Into::into)))
+        .collect()
+}
+
+/// Completes assigned label names
+pub fn label_names() -> Vec<CompletionCandidate> {
+    let mut names: Vec<String> = load_label_assignments_with_home(completion_home().as_deref())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|assignment| assignment.label_name)
+        .collect();
+    names.sort();
+    names.dedup();
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Completes profile names
+pub fn profile_names() -> Vec<CompletionCandidate> {
+    profile::profile_names(completion_home().as_deref())
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Handle the completions command - print the registration script for a shell
+pub fn handle_completions(shell: String) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells.completer(&shell).ok_or_else(|| {
+        anyhow!(
+            "Unsupported shell: {}. Supported shells: {}",
+            shell,
+            shells.names().collect::<Vec<_>>().join(", ")
+        )
+    })?;
+    // The script calls back into this binary, so record where it lives
+    let bin = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.to_str().map(str::to_string))
+        .unwrap_or_else(|| "aicred".to_string());
+    completer.write_registration(
+        COMPLETE_VAR,
+        "aicred",
+        "aicred",
+        &bin,
+        &mut std::io::stdout(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! Config store maintenance commands for the aicred CLI.
+
+use crate::commands::export::write_output;
+use crate::utils::provider_loader::config_store;
+use aicred_core::bulk;
+use aicred_core::bundle::{Bundle, ConflictStrategy};
+use aicred_core::crypto::{self, KeySource, SecretCipher, PASSPHRASE_ENV};
+use aicred_core::store::SCHEMA_VERSION;
+use aicred_core::{ConfigStore, SecretBackends};
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::{Path, PathBuf};
+
+/// Handle the config migrate command
+pub fn handle_migrate(dry_run: bool, home: Option<&Path>) -> Result<()> {
+    let store = config_store(home)?;
+    let reports = store.migrate(dry_run)?;
+    let renames = bulk::migrate_ids(&store, &SecretBackends::builtin(), dry_run)?;
+
+    if reports.is_empty() && renames.is_empty() {
+        println!(
+            "{}",
+            format!("All configuration files are at schema version {SCHEMA_VERSION}.").green()
+        );
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would migrate" } else { "Migrated" };
+    for (path, report) in &reports {
+        println!(
+            "{} {} (v{} -> v{})",
+            verb.cyan().bold(),
+            path.display(),
+            report.from_version,
+            report.to_version
+        );
+        for change in &report.changes {
+            println!("  - {}", change);
+        }
+    }
+    let verb = if dry_run { "Would rename" } else { "Renamed" };
+    for (old_id, new_id) in &renames {
+        println!("{} instance {} -> {}", verb.cyan().bold(), old_id, new_id);
+    }
+
+    if !dry_run {
+        println!(
+            "{}",
+            "Previous versions were kept alongside as .bak files.".dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Resolve the key source for encrypt/decrypt from the command flags
+fn key_source(
+    store: &ConfigStore,
+    identity: Option<PathBuf>,
+    passphrase: bool,
+    create_identity: bool,
+) -> Result<KeySource> {
+    if passphrase {
+        let value = std::env::var(PASSPHRASE_ENV).unwrap_or_default();
+        if value.is_empty() {
+            return Err(anyhow!(
+                "--passphrase reads the passphrase from the {} environment variable, which is not set",
+                PASSPHRASE_ENV
+            ));
+        }
+        return Ok(KeySource::Passphrase(value));
+    }
+
+    let identity = identity.unwrap_or_else(|| store.identity_path());
+    if !identity.exists() {
+        if !create_identity {
+            return Err(anyhow!("Identity file not found: {}", identity.display()));
+        }
+        crypto::generate_identity(&identity)?;
+        println!(
+            "{} {}",
+            "Generated identity file:".green(),
+            identity.display()
+        );
+        println!(
+            "{}",
+            "Back it up: encrypted keys cannot be recovered without it.".yellow()
+        );
+    }
+    Ok(KeySource::IdentityFile(identity))
+}
+
+/// Handle the config encrypt command
+pub fn handle_encrypt(
+    identity: Option<PathBuf>,
+    passphrase: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let store = config_store(home)?;
+    let cipher = SecretCipher::new(key_source(&store, identity, passphrase, true)?)?;
+
+    let changed = store.update_instances(|instance| {
+        if instance.api_key.is_empty() || crypto::is_encrypted(&instance.api_key) {
+            return Ok(false);
+        }
+        instance.api_key = cipher.encrypt(&instance.api_key)?;
+        Ok(true)
+    })?;
+
+    println!(
+        "{}",
+        format!("Encrypted {} instance key(s).", changed).green()
+    );
+    Ok(())
+}
+
+/// Handle the config decrypt command
+pub fn handle_decrypt(
+    identity: Option<PathBuf>,
+    passphrase: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let store = config_store(home)?;
+    let cipher = SecretCipher::new(key_source(&store, identity, passphrase, false)?)?;
+
+    let changed = store.update_instances(|instance| {
+        if !crypto::is_encrypted(&instance.api_key) {
+            return Ok(false);
+        }
+        instance.api_key = cipher.decrypt(&instance.api_key)?;
+        Ok(true)
+    })?;
+
+    println!(
+        "{}",
+        format!("Decrypted {} instance key(s).", changed).green()
+    );
+    Ok(())
+}
+
+/// Handle the config export-bundle command
+pub fn handle_export_bundle(
+    output: Option<String>,
+    include_secrets: bool,
+    identity: Option<PathBuf>,
+    passphrase: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let store = config_store(home)?;
+    let cipher = SecretCipher::new(key_source(&store, identity, passphrase, false)?)?;
+    let bundle = Bundle::from_store(&store, include_secrets)?;
+    write_output(&bundle.seal(&cipher)?, output.as_deref())?;
+
+    eprintln!(
+        "Bundled {} instance(s), {} tag(s), {} label(s) and {} rule(s){}",
+        bundle.instances.len(),
+        bundle.tags.len(),
+        bundle.label_assignments.len(),
+        bundle.rules.rules.len(),
+        if include_secrets {
+            ", including API keys"
+        } else {
+            "; API keys were left out (use --include-secrets to add them)"
+        }
+    );
+    Ok(())
+}
+
+/// Handle the config import-bundle command
+pub fn handle_import_bundle(
+    file: String,
+    on_conflict: String,
+    dry_run: bool,
+    identity: Option<PathBuf>,
+    passphrase: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let strategy: ConflictStrategy = on_conflict.parse().map_err(|e: String| anyhow!(e))?;
+    let content = if file == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(&file)?
+    };
+    let store = config_store(home)?;
+    let cipher = SecretCipher::new(key_source(&store, identity, passphrase, false)?)?;
+    let bundle = Bundle::open(&content, &cipher)?;
+    let report = bundle.import_into(&store, strategy, dry_run)?;
+
+    let verb = |done: &'static str, planned: &'static str| if dry_run { planned } else { done };
+    for item in &report.added {
+        println!("{} {}", verb("Added", "Would add").green(), item);
+    }
+    for (from, to) in &report.renamed {
+        println!(
+            "{} instance {} as {}",
+            verb("Imported", "Would import").green(),
+            from,
+            to.cyan()
+        );
+    }
+    for item in &report.overwritten {
+        println!("{} {}", verb("Overwrote", "Would overwrite").yellow(), item);
+    }
+    for item in &report.skipped {
+        println!(
+            "{} {} (differs locally; use --on-conflict overwrite or rename)",
+            verb("Kept local", "Would keep local").yellow(),
+            item
+        );
+    }
+    if report.unchanged > 0 {
+        println!(
+            "{}",
+            format!("{} item(s) already up to date.", report.unchanged).dimmed()
+        );
+    }
+    if !report.has_changes() {
+        println!("{}", "Nothing to import.".green());
+    } else if !bundle.includes_secrets && !report.added.is_empty() {
+        println!(
+            "{} the bundle has no API keys; set them with 'aicred instances update'",
+            "Note:".yellow()
+        );
+    }
+    Ok(())
+}
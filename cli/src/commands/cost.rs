@@ -0,0 +1,309 @@
+//! Cost estimation from the pricing in the model registry.
+
+use crate::utils::provider_loader::{load_provider_instances, model_registry};
+use aicred_core::{ModelEntry, ModelRegistry, ModelStatus};
+use anyhow::{anyhow, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// One record of a usage file
+#[derive(Debug, Deserialize)]
+struct UsageRecord {
+    model: String,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+/// Estimated cost of a number of tokens on one model
+#[derive(Debug, Clone, Serialize)]
+struct CostRow {
+    model: String,
+    provider: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    input_cost: f64,
+    output_cost: f64,
+    total_cost: f64,
+    currency: String,
+}
+
+/// Totals of a usage file, per model and per provider
+#[derive(Debug, Serialize)]
+struct UsageReport {
+    models: Vec<CostRow>,
+    providers: Vec<CostRow>,
+    total_cost: f64,
+    unpriced: Vec<String>,
+}
+
+/// Parse a token count such as 1500, 200k, 1.5M or 2B
+pub fn parse_tokens(value: &str) -> Result<u64> {
+    let cleaned = value.trim().replace(['_', ','], "");
+    let (number, multiplier) = match cleaned.chars().last() {
+        Some('k' | 'K') => (&cleaned[..cleaned.len() - 1], 1e3),
+        Some('m' | 'M') => (&cleaned[..cleaned.len() - 1], 1e6),
+        Some('b' | 'B') => (&cleaned[..cleaned.len() - 1], 1e9),
+        _ => (cleaned.as_str(), 1.0),
+    };
+    let number: f64 = number.parse().map_err(|_| {
+        anyhow!(
+            "Invalid token count '{}', expected e.g. 1500, 200k or 1M",
+            value
+        )
+    })?;
+    if !number.is_finite() || number < 0.0 {
+        return Err(anyhow!("Invalid token count '{}'", value));
+    }
+    Ok((number * multiplier).round() as u64)
+}
+
+fn cost_row(entry: &ModelEntry, input_tokens: u64, output_tokens: u64) -> Option<CostRow> {
+    entry.cost(input_tokens, output_tokens).map(|cost| CostRow {
+        model: entry.id.clone(),
+        provider: entry.provider.clone(),
+        input_tokens,
+        output_tokens,
+        input_cost: cost.input_cost,
+        output_cost: cost.output_cost,
+        total_cost: cost.total_cost,
+        currency: cost.currency,
+    })
+}
+
+/// Models to compare when none are named: those offered by the configured
+/// instances, or every active registry model if none of them are known
+fn default_models(registry: &ModelRegistry, home: Option<&Path>) -> Vec<String> {
+    let mut models: Vec<String> = load_provider_instances(home)
+        .map(|instances| {
+            instances
+                .all_instances()
+                .iter()
+                .flat_map(|instance| &instance.models)
+                .filter_map(|model| registry.resolve(model).map(|entry| entry.id.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    models.sort();
+    models.dedup();
+    if models.is_empty() {
+        models = registry
+            .entries()
+            .iter()
+            .filter(|entry| entry.status == ModelStatus::Active && !entry.capabilities.embedding)
+            .map(|entry| entry.id.clone())
+            .collect();
+    }
+    models
+}
+
+pub(crate) fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        format!("${:.4}", cost)
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
+/// Print cost rows, labelled by model or, with `by_provider`, by provider
+fn print_rows(title: &str, rows: &[CostRow], by_provider: bool) {
+    println!("\n{}", title.green().bold());
+    if by_provider {
+        println!(
+            "{:<15} {:>14} {:>14} {:>12}",
+            "Provider".bold(),
+            "Input".bold(),
+            "Output".bold(),
+            "Total".bold()
+        );
+    } else {
+        println!(
+            "{:<30} {:<12} {:>14} {:>14} {:>12}",
+            "Model".bold(),
+            "Provider".bold(),
+            "Input".bold(),
+            "Output".bold(),
+            "Total".bold()
+        );
+    }
+    println!("{}", "-".repeat(if by_provider { 58 } else { 86 }));
+    for row in rows {
+        let costs = format!(
+            "{:>14} {:>14} {:>12}",
+            format_cost(row.input_cost),
+            format_cost(row.output_cost),
+            format_cost(row.total_cost)
+        );
+        if by_provider {
+            println!("{:<15} {}", row.provider.yellow(), costs);
+        } else {
+            println!(
+                "{:<30} {:<12} {}",
+                row.model.cyan(),
+                row.provider.yellow(),
+                costs
+            );
+        }
+    }
+}
+
+/// Handle the cost command
+pub fn handle_cost(
+    models: Vec<String>,
+    input_tokens: Option<String>,
+    output_tokens: Option<String>,
+    usage: Option<String>,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    let registry = model_registry(home);
+    if let Some(usage) = usage {
+        return handle_usage(&registry, &usage, &format);
+    }
+
+    let input_tokens = parse_tokens(input_tokens.as_deref().unwrap_or("0"))?;
+    let output_tokens = parse_tokens(output_tokens.as_deref().unwrap_or("0"))?;
+    if input_tokens == 0 && output_tokens == 0 {
+        return Err(anyhow!(
+            "Give --input-tokens and/or --output-tokens, or --usage with a usage file"
+        ));
+    }
+    let models = if models.is_empty() {
+        default_models(&registry, home)
+    } else {
+        models
+    };
+
+    let mut rows = Vec::new();
+    for model in &models {
+        let entry = registry
+            .resolve(model)
+            .ok_or_else(|| anyhow!("No pricing known for model '{}'", model))?;
+        rows.extend(cost_row(entry, input_tokens, output_tokens));
+    }
+    rows.sort_by(|a, b| a.total_cost.total_cmp(&b.total_cost));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+    let title = format!(
+        "Estimated cost of {} input and {} output tokens:",
+        input_tokens, output_tokens
+    );
+    print_rows(&title, &rows, false);
+    if let (Some(cheapest), Some(priciest)) = (rows.first(), rows.last()) {
+        if rows.len() > 1 && cheapest.total_cost > 0.0 {
+            println!(
+                "\n{}",
+                format!(
+                    "{} is {:.1}x cheaper than {}",
+                    cheapest.model,
+                    priciest.total_cost / cheapest.total_cost,
+                    priciest.model
+                )
+                .dimmed()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Price a usage file ('-' for stdin) holding a JSON array of
+/// `{"model", "input_tokens", "output_tokens"}` records
+fn handle_usage(registry: &ModelRegistry, file: &str, format: &str) -> Result<()> {
+    let content = if file == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?
+    };
+    let records: Vec<UsageRecord> =
+        serde_json::from_str(&content).with_context(|| format!("Invalid usage file {}", file))?;
+
+    let mut per_model: BTreeMap<String, CostRow> = BTreeMap::new();
+    let mut unpriced = Vec::new();
+    for record in &records {
+        let Some(row) = registry
+            .resolve(&record.model)
+            .and_then(|entry| cost_row(entry, record.input_tokens, record.output_tokens))
+        else {
+            if !unpriced.contains(&record.model) {
+                unpriced.push(record.model.clone());
+            }
+            continue;
+        };
+        match per_model.get_mut(&row.model) {
+            Some(total) => add_row(total, &row),
+            None => {
+                per_model.insert(row.model.clone(), row);
+            }
+        }
+    }
+
+    let mut per_provider: BTreeMap<String, CostRow> = BTreeMap::new();
+    for row in per_model.values() {
+        match per_provider.get_mut(&row.provider) {
+            Some(total) => add_row(total, row),
+            None => {
+                per_provider.insert(row.provider.clone(), row.clone());
+            }
+        }
+    }
+
+    let by_cost = |rows: BTreeMap<String, CostRow>| {
+        let mut rows: Vec<CostRow> = rows.into_values().collect();
+        rows.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost));
+        rows
+    };
+    let models = by_cost(per_model);
+    let report = UsageReport {
+        total_cost: models.iter().map(|row| row.total_cost).sum(),
+        models,
+        providers: by_cost(per_provider),
+        unpriced,
+    };
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    print_rows("Cost by model:", &report.models, false);
+    print_rows("Cost by provider:", &report.providers, true);
+    println!(
+        "\n{}",
+        format!(
+            "Total: {} over {} records",
+            format_cost(report.total_cost),
+            records.len()
+        )
+        .cyan()
+    );
+    if !report.unpriced.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "No pricing known for: {} (not included in the total)",
+                report.unpriced.join(", ")
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+fn add_row(total: &mut CostRow, row: &CostRow) {
+    total.input_tokens += row.input_tokens;
+    total.output_tokens += row.output_tokens;
+    total.input_cost += row.input_cost;
+    total.output_cost += row.output_cost;
+    total.total_cost += row.total_cost;
+}
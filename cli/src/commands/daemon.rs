@@ -0,0 +1,330 @@
+//! Daemon mode for the aicred CLI, serving JSON-RPC over a Unix socket.
+//!
+//! Requests and responses are JSON-RPC 2.0 objects, one per line. Methods:
+//!
+//! - `ping`: daemon version
+//! - `scan`: scans the home directory (`only`/`exclude` provider lists); values are never returned
+//! - `instances.list`: stored provider instances (`include_values`, `active_only`)
+//! - `labels.resolve`: the instance and model a label points at (`label`, `include_values`)
+//! - `watch`: subscribes the connection to `event` notifications for scans and store changes
+//! - `shutdown`: stops the daemon
+
+use crate::commands::labels::load_label_assignments_with_home;
+use crate::utils::provider_loader::{config_store, load_provider_instances, resolve_secret};
+use aicred_core::models::ProviderInstance;
+use aicred_core::notifications::Event;
+use aicred_core::resolve::resolve_in;
+use aicred_core::rules::DetectionRules;
+use aicred_core::{scan, ScanOptions, ScanResult};
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Socket file created in the config directory by default.
+pub const SOCKET_FILE: &str = "daemon.sock";
+
+/// JSON-RPC error codes.
+pub mod codes {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    pub const NOT_FOUND: i64 = -32001;
+}
+
+/// A JSON-RPC error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn internal(error: impl std::fmt::Display) -> Self {
+        Self::new(codes::INTERNAL_ERROR, error.to_string())
+    }
+}
+
+/// Runs a JSON-RPC method against a home directory.
+///
+/// `watch` and `shutdown` need a connection and are handled by the daemon itself.
+pub fn dispatch(method: &str, params: &Value, home: &Path) -> Result<Value, RpcError> {
+    match method {
+        "ping" => Ok(json!({ "version": env!("CARGO_PKG_VERSION") })),
+        "scan" => {
+            let result = run_scan(params, home)?;
+            serde_json::to_value(&result).map_err(RpcError::internal)
+        }
+        "instances.list" => {
+            let include_values = bool_param(params, "include_values");
+            let active_only = bool_param(params, "active_only");
+            let instances = load_provider_instances(Some(home)).map_err(RpcError::internal)?;
+            let mut listed = Vec::new();
+            for instance in instances.all_instances() {
+                if active_only && !instance.active {
+                    continue;
+                }
+                listed.push(instance_json(instance, include_values)?);
+            }
+            Ok(Value::Array(listed))
+        }
+        "labels.resolve" => {
+            let label = params
+                .get("label")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::new(codes::INVALID_PARAMS, "'label' is required"))?;
+            let assignments =
+                load_label_assignments_with_home(Some(home)).map_err(RpcError::internal)?;
+            let instances = load_provider_instances(Some(home)).map_err(RpcError::internal)?;
+            let resolved = resolve_in(&assignments, &instances, label).map_err(|e| match e {
+                aicred_core::Error::NotFound(message) => RpcError::new(codes::NOT_FOUND, message),
+                e => RpcError::internal(e),
+            })?;
+            Ok(json!({
+                "label": label,
+                "model": resolved.model.id,
+                "instance": instance_json(&resolved.instance, bool_param(params, "include_values"))?,
+                "skipped": resolved.skipped,
+            }))
+        }
+        _ => Err(RpcError::new(
+            codes::METHOD_NOT_FOUND,
+            format!("Unknown method: {method}"),
+        )),
+    }
+}
+
+/// Scans a home directory with the provider filters given in the params
+pub fn run_scan(params: &Value, home: &Path) -> Result<ScanResult, RpcError> {
+    let providers = |name: &str| -> Result<Option<Vec<String>>, RpcError> {
+        match params.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|_| {
+                    RpcError::new(
+                        codes::INVALID_PARAMS,
+                        format!("'{name}' must be a list of provider names"),
+                    )
+                }),
+        }
+    };
+    let store = config_store(Some(home)).map_err(RpcError::internal)?;
+    let options = ScanOptions {
+        home_dir: Some(home.to_path_buf()),
+        only_providers: providers("only")?,
+        exclude_providers: providers("exclude")?,
+        probe_models: false,
+        detection_rules: DetectionRules::load_from_store(&store)
+            .map_err(RpcError::internal)?
+            .rules,
+        ..ScanOptions::default()
+    };
+    scan(&options).map_err(RpcError::internal)
+}
+
+fn bool_param(params: &Value, name: &str) -> bool {
+    params.get(name).and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Serializes an instance, masking its key unless values were requested
+fn instance_json(instance: &ProviderInstance, include_values: bool) -> Result<Value, RpcError> {
+    let mut instance = instance.clone();
+    if include_values {
+        instance.api_key = resolve_secret(&instance.api_key).map_err(RpcError::internal)?;
+    } else if !instance.api_key.is_empty() {
+        instance.api_key = "********".to_string();
+    }
+    serde_json::to_value(&instance).map_err(RpcError::internal)
+}
+
+/// Builds a JSON-RPC response
+pub(crate) fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+/// Handle the daemon command
+pub fn handle_daemon(socket: Option<String>, home: Option<&Path>) -> Result<()> {
+    let home_dir = match home {
+        Some(h) => h.to_path_buf(),
+        None => dirs_next::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?,
+    };
+    let socket = match socket {
+        Some(path) => PathBuf::from(path),
+        None => config_store(Some(&home_dir))?.dir().join(SOCKET_FILE),
+    };
+    serve(&socket, home_dir)
+}
+
+#[cfg(unix)]
+fn serve(socket: &Path, home_dir: PathBuf) -> Result<()> {
+    use colored::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::{Arc, Mutex};
+
+    type Writer = Arc<Mutex<UnixStream>>;
+
+    fn send(writer: &Writer, message: &Value) -> std::io::Result<()> {
+        let mut stream = writer.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(stream, "{message}")
+    }
+
+    fn broadcast(watchers: &Mutex<Vec<Writer>>, event: &Value) {
+        let message = json!({ "jsonrpc": "2.0", "method": "event", "params": event });
+        let mut watchers = watchers.lock().unwrap_or_else(|e| e.into_inner());
+        watchers.retain(|writer| send(writer, &message).is_ok());
+    }
+
+    /// Snapshot of the store's files, to notice changes made by other tools
+    fn store_state(dir: &Path) -> Vec<(PathBuf, Option<std::time::SystemTime>, u64)> {
+        let mut state = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    pending.push(path);
+                } else if path.file_name().is_some_and(|name| name != SOCKET_FILE) {
+                    state.push((path, metadata.modified().ok(), metadata.len()));
+                }
+            }
+        }
+        state.sort();
+        state
+    }
+
+    if let Some(parent) = socket.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            anyhow::bail!("A daemon is already listening on {}", socket.display());
+        }
+        std::fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    println!(
+        "{} aicred daemon listening on {}",
+        "✓".green(),
+        socket.display()
+    );
+
+    let home_dir = Arc::new(home_dir);
+    let watchers: Arc<Mutex<Vec<Writer>>> = Arc::default();
+
+    // Tell watchers when instances, labels or settings change on disk
+    {
+        let watchers = Arc::clone(&watchers);
+        let store_dir = config_store(Some(&home_dir))?.dir().to_path_buf();
+        std::thread::spawn(move || {
+            let mut last = store_state(&store_dir);
+            loop {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+                let current = store_state(&store_dir);
+                if current != last {
+                    broadcast(&watchers, &json!({ "event": "store_changed" }));
+                    last = current;
+                }
+            }
+        });
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to accept daemon connection: {}", e);
+                continue;
+            }
+        };
+        let home_dir = Arc::clone(&home_dir);
+        let watchers = Arc::clone(&watchers);
+        let socket = socket.to_path_buf();
+        std::thread::spawn(move || {
+            let Ok(reader) = stream.try_clone() else {
+                return;
+            };
+            let writer: Writer = Arc::new(Mutex::new(stream));
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let request: Value = match serde_json::from_str(&line) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        let error = RpcError::new(codes::PARSE_ERROR, e.to_string());
+                        let _ = send(&writer, &response(Value::Null, Err(error)));
+                        continue;
+                    }
+                };
+                let id = request.get("id").cloned().unwrap_or(Value::Null);
+                let Some(method) = request.get("method").and_then(Value::as_str) else {
+                    let error = RpcError::new(codes::INVALID_REQUEST, "'method' is required");
+                    let _ = send(&writer, &response(id, Err(error)));
+                    continue;
+                };
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+                let result = match method {
+                    "watch" => {
+                        watchers
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .push(Arc::clone(&writer));
+                        Ok(json!({ "subscribed": true }))
+                    }
+                    "shutdown" => {
+                        let _ = send(&writer, &response(id, Ok(json!({ "stopping": true }))));
+                        let _ = std::fs::remove_file(&socket);
+                        std::process::exit(0);
+                    }
+                    "scan" => run_scan(&params, &home_dir).and_then(|result| {
+                        if let Ok(event) = serde_json::to_value(Event::scan_completed(&result)) {
+                            broadcast(&watchers, &event);
+                        }
+                        serde_json::to_value(&result).map_err(RpcError::internal)
+                    }),
+                    _ => dispatch(method, &params, &home_dir),
+                };
+                // Requests without an ID are notifications and get no response
+                if request.get("id").is_some() && send(&writer, &response(id, result)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve(_socket: &Path, _home_dir: PathBuf) -> Result<()> {
+    anyhow::bail!("Daemon mode requires Unix domain sockets and is only supported on Unix")
+}
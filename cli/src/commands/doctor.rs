@@ -0,0 +1,573 @@
+//! Environment diagnostics for the aicred CLI.
+//!
+//! Each check reports what it found and, for problems, a command or step
+//! that fixes them. Nothing is changed on disk.
+
+use crate::output::responses::{print_json, wants_json, CheckEntry, DoctorResponse};
+use crate::utils::parallel::map_parallel;
+use crate::utils::provider_loader::{config_store, load_provider_instances, model_registry};
+use aicred_core::cleanup::default_env_var;
+use aicred_core::crypto;
+use aicred_core::models::ProviderInstance;
+use aicred_core::secrets::KeyringBackend;
+use aicred_core::store::{schema_version, SCHEMA_VERSION};
+use aicred_core::{ConfigStore, SecretBackends};
+use anyhow::Result;
+use colored::*;
+use std::collections::BTreeMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Age after which a leftover temporary file is considered stale
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Ok,
+    Skipped,
+    Warning,
+    Failed,
+}
+
+impl Status {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Skipped => "skipped",
+            Self::Warning => "warning",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug)]
+struct Check {
+    status: Status,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            status: Status::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn skipped(message: impl Into<String>) -> Self {
+        Self {
+            status: Status::Skipped,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn failed(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Failed,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Files directly in the store directory and its instances directory
+fn store_files(store: &ConfigStore) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in [store.dir().to_path_buf(), store.instances_dir()] {
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            files.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_file()),
+            );
+        }
+    }
+    files.sort();
+    files
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml")
+}
+
+/// Checks that the store directory and files are private to the user
+#[cfg(unix)]
+fn check_permissions(store: &ConfigStore) -> Vec<Check> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = |path: &Path| {
+        std::fs::metadata(path)
+            .map(|meta| meta.permissions().mode() & 0o777)
+            .ok()
+    };
+    let mut checks = Vec::new();
+    for dir in [store.dir().to_path_buf(), store.instances_dir()] {
+        match mode(&dir) {
+            Some(mode) if mode & 0o077 != 0 => checks.push(Check::warning(
+                format!(
+                    "{} is accessible by other users (mode {mode:o})",
+                    dir.display()
+                ),
+                format!("chmod 700 {}", dir.display()),
+            )),
+            _ => {}
+        }
+    }
+    // Hidden files are the store lock and in-flight writes
+    for file in store_files(store)
+        .into_iter()
+        .filter(|path| !is_hidden(path))
+    {
+        match mode(&file) {
+            Some(mode) if mode & 0o077 != 0 => checks.push(Check::warning(
+                format!(
+                    "{} is readable by other users (mode {mode:o})",
+                    file.display()
+                ),
+                format!("chmod 600 {}", file.display()),
+            )),
+            _ => {}
+        }
+    }
+    if checks.is_empty() {
+        checks.push(Check::ok("Config directory and files are private"));
+    }
+    checks
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_store: &ConfigStore) -> Vec<Check> {
+    vec![Check::skipped(
+        "Permissions are not checked on this platform",
+    )]
+}
+
+/// Checks that every stored file is at the current schema version
+fn check_schema(store: &ConfigStore) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut current = 0;
+    for file in store_files(store)
+        .into_iter()
+        .filter(|path| is_yaml(path) && !is_hidden(path))
+    {
+        let Ok(content) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        match schema_version(&content) {
+            Some(SCHEMA_VERSION) => current += 1,
+            Some(version) if version > SCHEMA_VERSION => checks.push(Check::failed(
+                format!(
+                    "{} was written by a newer aicred (schema version {version}, this build reads {SCHEMA_VERSION})",
+                    file.display()
+                ),
+                "Upgrade aicred to read this file",
+            )),
+            Some(version) => checks.push(Check::warning(
+                format!(
+                    "{} uses schema version {version}, older than {SCHEMA_VERSION}",
+                    file.display()
+                ),
+                "aicred config migrate",
+            )),
+            None => checks.push(Check::warning(
+                format!("{} has no schema version header", file.display()),
+                "aicred config migrate",
+            )),
+        }
+    }
+    if checks.is_empty() {
+        checks.push(Check::ok(format!(
+            "{current} file(s) at schema version {SCHEMA_VERSION}"
+        )));
+    }
+    checks
+}
+
+/// Host and port a base URL connects to
+fn url_authority(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let default_port = match scheme.to_ascii_lowercase().as_str() {
+        "https" => 443,
+        "http" => 80,
+        _ => return None,
+    };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        // IPv6 literal, e.g. [::1]:8080
+        let (host, after) = rest.split_once(']')?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+    Some((host.to_string(), port))
+}
+
+/// Opens a TCP connection to a base URL's host
+fn reach(url: &str, timeout: Duration) -> std::result::Result<(), String> {
+    let (host, port) = url_authority(url).ok_or_else(|| "not a valid http(s) URL".to_string())?;
+    let addrs: Vec<_> = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("cannot resolve {host}: {e}"))?
+        .collect();
+    let mut last_error = format!("{host} has no addresses");
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = format!("cannot connect to {host}:{port}: {e}"),
+        }
+    }
+    Err(last_error)
+}
+
+/// Checks that each active instance's base URL accepts connections
+fn check_reachability(
+    instances: &[&ProviderInstance],
+    timeout: Duration,
+    parallelism: Option<usize>,
+) -> Vec<Check> {
+    let targets: Vec<(String, String)> = instances
+        .iter()
+        .filter(|instance| instance.active && !instance.base_url.is_empty())
+        .map(|instance| (instance.id.clone(), instance.base_url.clone()))
+        .collect();
+    if targets.is_empty() {
+        return vec![Check::skipped("No active instances with a base URL")];
+    }
+
+    // Probe in parallel so one slow host doesn't hold up the rest
+    map_parallel(&targets, parallelism, |(id, url)| {
+        (id.clone(), url.clone(), reach(url, timeout))
+    })
+    .into_iter()
+        .map(|(id, url, result)| match result {
+            Ok(()) => Check::ok(format!("{id}: {url} is reachable")),
+            Err(error) => Check::warning(
+                format!("{id}: {url} is unreachable ({error})"),
+                format!(
+                    "Check your network or proxy, or fix the URL with 'aicred instances update -i {id} --base-url <url>'"
+                ),
+            ),
+        })
+        .collect()
+}
+
+/// Checks that the OS keyring works when instance keys are stored in it
+fn check_keyring(instances: &[&ProviderInstance]) -> Vec<Check> {
+    let backends = SecretBackends::builtin();
+    let stored = instances
+        .iter()
+        .filter(|instance| {
+            backends
+                .owner(&instance.api_key)
+                .is_some_and(|backend| backend.name() == "keyring")
+        })
+        .count();
+    let fix = if cfg!(target_os = "macos") {
+        "The `security` tool ships with macOS; check that /usr/bin is on PATH"
+    } else if cfg!(windows) {
+        "Make sure Windows PowerShell (powershell.exe) is on PATH"
+    } else {
+        "Install secret-tool (libsecret-tools) and run aicred inside a desktop session, or store keys with --store file"
+    };
+    match (KeyringBackend::check_available(), stored) {
+        (Ok(()), 0) => vec![Check::ok("OS keyring is available")],
+        (Ok(()), n) => vec![Check::ok(format!(
+            "OS keyring is available and holds {n} instance key(s)"
+        ))],
+        (Err(e), 0) => vec![Check::skipped(format!(
+            "OS keyring is unavailable ({e}); only needed for --store keyring"
+        ))],
+        (Err(e), n) => vec![Check::failed(
+            format!(
+                "{n} instance key(s) are stored in the OS keyring, but it is unavailable ({e})"
+            ),
+            fix,
+        )],
+    }
+}
+
+/// Checks that provider variables set in the shell agree with the configuration
+fn check_env_conflicts(instances: &[&ProviderInstance]) -> Vec<Check> {
+    let backends = SecretBackends::builtin();
+    let mut by_var: BTreeMap<String, Vec<&ProviderInstance>> = BTreeMap::new();
+    for instance in instances.iter().filter(|instance| instance.active) {
+        by_var
+            .entry(default_env_var(&instance.provider_type))
+            .or_default()
+            .push(instance);
+    }
+
+    let mut checks = Vec::new();
+    for (var, configured) in &by_var {
+        let ids = configured
+            .iter()
+            .map(|instance| instance.id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(value) = std::env::var(var).ok().filter(|v| !v.is_empty()) {
+            // Keys held in a backend or still encrypted can't be compared
+            let plain: Vec<&&ProviderInstance> = configured
+                .iter()
+                .filter(|instance| {
+                    instance.has_non_empty_api_key()
+                        && !backends.is_reference(&instance.api_key)
+                        && !crypto::is_encrypted(&instance.api_key)
+                })
+                .collect();
+            if let Some(instance) = plain.iter().find(|instance| instance.api_key == value) {
+                checks.push(Check::ok(format!(
+                    "{var} matches instance '{}'",
+                    instance.id
+                )));
+            } else if plain.is_empty() {
+                checks.push(Check::skipped(format!(
+                    "{var} is set; the keys of {ids} are not stored in plain text and were not compared"
+                )));
+            } else {
+                checks.push(Check::warning(
+                    format!("{var} is set in the shell but matches none of the configured keys ({ids}); tools reading it use a different key"),
+                    format!(
+                        "unset {var}, or set it from the configuration with eval \"$(aicred setenv --instance {})\"",
+                        configured[0].id
+                    ),
+                ));
+            }
+        }
+
+        let url_var = format!("{}_BASE_URL", var.trim_end_matches("_API_KEY"));
+        if let Some(value) = std::env::var(&url_var).ok().filter(|v| !v.is_empty()) {
+            let matches = configured.iter().any(|instance| {
+                instance.base_url.trim_end_matches('/') == value.trim_end_matches('/')
+            });
+            if !matches {
+                checks.push(Check::warning(
+                    format!("{url_var}={value} is set in the shell but no configured instance uses it ({ids})"),
+                    format!("unset {url_var}, or update the instance's base URL"),
+                ));
+            }
+        }
+    }
+    if checks.is_empty() {
+        checks.push(Check::ok(
+            "No provider variables in the shell conflict with the configuration",
+        ));
+    }
+    checks
+}
+
+/// Checks for temporary files left behind by interrupted writes
+fn check_stale_files(store: &ConfigStore) -> Vec<Check> {
+    let now = SystemTime::now();
+    let stale: Vec<PathBuf> = store_files(store)
+        .into_iter()
+        .filter(|path| is_hidden(path) && path.extension().is_some_and(|ext| ext == "tmp"))
+        .filter(|path| {
+            std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > STALE_AFTER)
+        })
+        .collect();
+    if stale.is_empty() {
+        return vec![Check::ok("No stale temporary files")];
+    }
+    stale
+        .into_iter()
+        .map(|path| {
+            Check::warning(
+                format!("{} was left behind by an interrupted write", path.display()),
+                format!("rm {}", path.display()),
+            )
+        })
+        .collect()
+}
+
+/// Checks for instances offering models that are deprecated or no longer served
+fn check_model_lifecycle(instances: &[&ProviderInstance], home: Option<&Path>) -> Vec<Check> {
+    let warnings = model_registry(home)
+        .lifecycle_warnings(instances.iter().copied(), chrono::Local::now().date_naive());
+    if warnings.is_empty() {
+        return vec![Check::ok("No configured model is deprecated")];
+    }
+    warnings
+        .into_iter()
+        .map(|warning| {
+            let fix = format!(
+                "Move to a current model with 'aicred instances update -i {} --models <models>'; see 'aicred models registry --provider {}'",
+                warning.instance_id,
+                instances
+                    .iter()
+                    .find(|instance| instance.id == warning.instance_id)
+                    .map_or("<provider>", |instance| instance.provider_type.as_str())
+            );
+            Check::warning(warning.to_string(), fix)
+        })
+        .collect()
+}
+
+/// Handle the doctor command
+pub fn handle_doctor(
+    offline: bool,
+    timeout: u64,
+    parallelism: Option<usize>,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    let json = wants_json(&format)?;
+    let store = config_store(home)?;
+    let collection = load_provider_instances(home)?;
+    let mut instances = collection.all_instances();
+    instances.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut sections = vec![
+        ("Config directory", check_permissions(&store)),
+        ("Schema", check_schema(&store)),
+    ];
+    sections.push((
+        "Base URLs",
+        if offline {
+            vec![Check::skipped("Skipped (--offline)")]
+        } else {
+            check_reachability(&instances, Duration::from_secs(timeout), parallelism)
+        },
+    ));
+    sections.push(("Keyring", check_keyring(&instances)));
+    sections.push(("Environment", check_env_conflicts(&instances)));
+    sections.push(("Models", check_model_lifecycle(&instances, home)));
+    sections.push(("Stale files", check_stale_files(&store)));
+
+    if json {
+        let checks: Vec<CheckEntry> = sections
+            .iter()
+            .flat_map(|(name, checks)| {
+                checks.iter().map(|check| CheckEntry {
+                    section: name.to_string(),
+                    status: check.status.as_str().to_string(),
+                    message: check.message.clone(),
+                    fix: check.fix.clone(),
+                })
+            })
+            .collect();
+        let count = |status: Status| {
+            sections
+                .iter()
+                .flat_map(|(_, checks)| checks)
+                .filter(|check| check.status == status)
+                .count()
+        };
+        let failures = count(Status::Failed);
+        print_json(&DoctorResponse {
+            config_dir: store.dir().display().to_string(),
+            checks,
+            warnings: count(Status::Warning),
+            failures,
+        })?;
+        if failures > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        "aicred doctor:".bold(),
+        store.dir().display().to_string().dimmed()
+    );
+    let (mut warnings, mut failures) = (0, 0);
+    for (name, checks) in &sections {
+        println!("\n{}", name.bold());
+        for check in checks {
+            let marker = match check.status {
+                Status::Ok => "✓".green(),
+                Status::Skipped => "-".dimmed(),
+                Status::Warning => {
+                    warnings += 1;
+                    "!".yellow()
+                }
+                Status::Failed => {
+                    failures += 1;
+                    "✗".red()
+                }
+            };
+            println!("  {} {}", marker, check.message);
+            if let Some(ref fix) = check.fix {
+                println!("    {} {}", "Fix:".cyan(), fix);
+            }
+        }
+    }
+
+    println!();
+    if warnings == 0 && failures == 0 {
+        println!("{} No problems found", "✓".green());
+        return Ok(());
+    }
+    println!(
+        "{} {} warning(s), {} error(s)",
+        if failures > 0 {
+            "✗".red()
+        } else {
+            "!".yellow()
+        },
+        warnings,
+        failures
+    );
+    if failures > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_authority() {
+        assert_eq!(
+            url_authority("https://api.openai.com/v1"),
+            Some(("api.openai.com".to_string(), 443))
+        );
+        assert_eq!(
+            url_authority("http://localhost:11434"),
+            Some(("localhost".to_string(), 11434))
+        );
+        assert_eq!(
+            url_authority("http://user:pw@[::1]:8080/v1?x=1"),
+            Some(("::1".to_string(), 8080))
+        );
+        assert_eq!(url_authority("api.openai.com"), None);
+        assert_eq!(url_authority("ftp://example.com"), None);
+    }
+}
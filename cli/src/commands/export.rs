@@ -0,0 +1,479 @@
+//! Export of provider instances to other tools' configuration formats.
+
+use crate::commands::budget::enforce_budgets;
+use crate::commands::labels::load_label_assignments_with_home;
+use crate::commands::tags::get_tags_for_target;
+use crate::utils::provider_loader::{load_provider_instances, resolve_secret};
+use aicred_core::cleanup::default_env_var;
+use aicred_core::models::{LabelAssignment, ProviderCollection, ProviderInstance};
+use aicred_core::resolve::resolve_in;
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A `model_list` entry of a litellm proxy config
+#[derive(Debug, Serialize)]
+struct LitellmModel {
+    model_name: String,
+    litellm_params: LitellmParams,
+}
+
+#[derive(Debug, Serialize)]
+struct LitellmParams {
+    model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct LitellmConfig {
+    model_list: Vec<LitellmModel>,
+}
+
+/// litellm model prefix for a provider type; unknown providers are treated as OpenAI-compatible
+fn litellm_prefix(provider_type: &str) -> &str {
+    match provider_type {
+        "anthropic" => "anthropic",
+        "groq" => "groq",
+        "openrouter" => "openrouter",
+        "huggingface" => "huggingface",
+        "ollama" => "ollama",
+        "mistral" => "mistral",
+        "cohere" => "cohere",
+        "deepseek" => "deepseek",
+        "together" => "together_ai",
+        "fireworks" => "fireworks_ai",
+        "perplexity" => "perplexity",
+        "google" | "gemini" => "gemini",
+        "xai" => "xai",
+        "litellm" => "litellm_proxy",
+        _ => "openai",
+    }
+}
+
+/// Renders instances and label aliases as a litellm proxy config
+fn render_litellm(
+    instances: &[&ProviderInstance],
+    labels: &[(String, String, Option<String>)],
+    env_refs: bool,
+) -> Result<String> {
+    let params = |instance: &ProviderInstance, model: &str| -> Result<LitellmParams> {
+        let api_key = if !instance.has_non_empty_api_key() {
+            None
+        } else if env_refs {
+            Some(format!(
+                "os.environ/{}",
+                default_env_var(&instance.provider_type)
+            ))
+        } else {
+            Some(resolve_secret(&instance.api_key)?)
+        };
+        Ok(LitellmParams {
+            model: format!("{}/{}", litellm_prefix(&instance.provider_type), model),
+            api_base: Some(instance.base_url.clone()).filter(|url| !url.is_empty()),
+            api_key,
+        })
+    };
+
+    let mut model_list = Vec::new();
+    for instance in instances {
+        if instance.models.is_empty() {
+            // Route any model of the provider through the instance
+            let wildcard = format!("{}/*", litellm_prefix(&instance.provider_type));
+            model_list.push(LitellmModel {
+                model_name: wildcard,
+                litellm_params: params(instance, "*")?,
+            });
+        }
+        for model in &instance.models {
+            model_list.push(LitellmModel {
+                model_name: model.clone(),
+                litellm_params: params(instance, model)?,
+            });
+        }
+    }
+    // Labels become model aliases, e.g. `fast`
+    for (label, instance_id, model) in labels {
+        let Some(instance) = instances.iter().find(|i| i.id == *instance_id) else {
+            continue;
+        };
+        let Some(model) = model.as_ref().or_else(|| instance.models.first()) else {
+            continue;
+        };
+        model_list.push(LitellmModel {
+            model_name: label.clone(),
+            litellm_params: params(instance, model)?,
+        });
+    }
+
+    Ok(serde_yaml::to_string(&LitellmConfig { model_list })?)
+}
+
+/// Environment variables holding each provider's key, resolved from the secret backends
+fn key_env_vars(instances: &[&ProviderInstance]) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for instance in instances {
+        if !instance.has_non_empty_api_key() {
+            continue;
+        }
+        let var = default_env_var(&instance.provider_type);
+        if vars.contains_key(&var) {
+            eprintln!(
+                "{} {} is already set from another instance; skipping '{}'",
+                "Note:".yellow(),
+                var,
+                instance.id
+            );
+            continue;
+        }
+        vars.insert(var, resolve_secret(&instance.api_key)?);
+    }
+    Ok(vars)
+}
+
+/// Renders an `env` block, or fills the env blocks of an MCP client config's servers.
+///
+/// Named servers get every key; without names, each server only gets the
+/// variables its env block already declares.
+fn render_mcp_env(
+    vars: &BTreeMap<String, String>,
+    merge: Option<&str>,
+    servers: &[String],
+) -> Result<String> {
+    let Some(path) = merge else {
+        return Ok(serde_json::to_string_pretty(&json!({ "env": vars }))? + "\n");
+    };
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read MCP client config '{}': {}", path, e))?;
+    let mut config: Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid MCP client config '{}': {}", path, e))?;
+    let definitions = config
+        .get_mut("mcpServers")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow::anyhow!("'{}' has no mcpServers section", path))?;
+    for name in servers {
+        if !definitions.contains_key(name) {
+            anyhow::bail!("MCP server '{}' is not defined in {}", name, path);
+        }
+    }
+
+    for (name, definition) in definitions.iter_mut() {
+        if !servers.is_empty() && !servers.contains(name) {
+            continue;
+        }
+        let Some(definition) = definition.as_object_mut() else {
+            continue;
+        };
+        let env = definition
+            .entry("env")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("MCP server '{}' has an invalid env block", name))?;
+        for (var, value) in vars {
+            if !servers.is_empty() || env.contains_key(var) {
+                env.insert(var.clone(), Value::String(value.clone()));
+            }
+        }
+    }
+    Ok(serde_json::to_string_pretty(&config)? + "\n")
+}
+
+/// Writes exported content to a file readable only by the user, or to stdout
+pub(crate) fn write_output(content: &str, output: Option<&str>) -> Result<()> {
+    let Some(path) = output else {
+        print!("{content}");
+        return Ok(());
+    };
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    std::io::Write::write_all(&mut options.open(path)?, content.as_bytes())?;
+    eprintln!("{} Wrote {}", "✓".green(), path);
+    Ok(())
+}
+
+/// Prefix of a provider's environment variables, e.g. `OPENAI`
+fn env_prefix(provider_type: &str) -> String {
+    let var = default_env_var(provider_type);
+    var.trim_end_matches("_API_KEY").to_string()
+}
+
+/// Quotes a `.env` value if it contains characters dotenv parsers treat specially
+fn env_value(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:@+=,".contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// A selected instance and the (name, value) variables exported for it
+pub(crate) type InstanceEnvVars<'s, 'a> = (&'s Selected<'a>, Vec<(String, String)>);
+
+/// Provider-standard variables of each selected instance, e.g. `OPENAI_API_KEY`,
+/// skipping instances whose provider is already covered by an earlier one
+pub(crate) fn provider_env_vars<'s, 'a>(
+    selected: &'s [Selected<'a>],
+    no_values: bool,
+) -> Result<Vec<InstanceEnvVars<'s, 'a>>> {
+    let mut sets = Vec::new();
+    let mut seen = Vec::new();
+    for selection in selected {
+        let instance = selection.instance;
+        let prefix = env_prefix(&instance.provider_type);
+        if seen.contains(&prefix) {
+            eprintln!(
+                "{} {}_* variables are already set from another instance; skipping '{}'",
+                "Note:".yellow(),
+                prefix,
+                instance.id
+            );
+            continue;
+        }
+
+        let mut vars = Vec::new();
+        if instance.has_non_empty_api_key() {
+            let key = if no_values {
+                format!(
+                    "<{}>",
+                    default_env_var(&instance.provider_type)
+                        .to_lowercase()
+                        .replace('_', "-")
+                )
+            } else {
+                resolve_secret(&instance.api_key)?
+            };
+            vars.push((format!("{prefix}_API_KEY"), key));
+        }
+        if !instance.base_url.is_empty() {
+            vars.push((format!("{prefix}_BASE_URL"), instance.base_url.clone()));
+        }
+        if let Some(ref model) = selection.model {
+            vars.push((format!("{prefix}_MODEL"), model.clone()));
+        }
+        sets.push((selection, vars));
+        seen.push(prefix);
+    }
+    Ok(sets)
+}
+
+/// Renders `.env` lines for each selected instance, one set of variables per provider
+pub(crate) fn render_env(selected: &[Selected], no_values: bool) -> Result<String> {
+    let mut content = String::from("# Generated by aicred\n");
+    for (selection, vars) in provider_env_vars(selected, no_values)? {
+        content.push_str(&format!("\n# {}", selection.instance.id));
+        if let Some(ref label) = selection.label {
+            content.push_str(&format!(" (label '{label}')"));
+        }
+        content.push('\n');
+        for (name, value) in vars {
+            content.push_str(&format!("{name}={}\n", env_value(&value)));
+        }
+    }
+    Ok(content)
+}
+
+/// Renders a script creating a Docker secret per provider key, e.g. `openai_api_key`
+fn render_docker_secrets(selected: &[Selected]) -> Result<String> {
+    let mut script = String::from(
+        "#!/bin/sh\n# Generated by aicred: creates Docker secrets for AI provider keys\nset -eu\n",
+    );
+    let mut names = Vec::new();
+    for selection in selected {
+        let instance = selection.instance;
+        if !instance.has_non_empty_api_key() {
+            continue;
+        }
+        let name = default_env_var(&instance.provider_type).to_lowercase();
+        if names.contains(&name) {
+            eprintln!(
+                "{} Secret {} is already created from another instance; skipping '{}'",
+                "Note:".yellow(),
+                name,
+                instance.id
+            );
+            continue;
+        }
+        let key = resolve_secret(&instance.api_key)?;
+        script.push_str(&format!(
+            "\n# {}\nprintf '%s' '{}' | docker secret create {} -\n",
+            instance.id,
+            key.replace('\'', "'\\''"),
+            name
+        ));
+        names.push(name);
+    }
+    if names.is_empty() {
+        anyhow::bail!("None of the selected instances has a key");
+    }
+
+    script.push_str("\n# Reference the secrets from docker-compose.yml:\n#   secrets:\n");
+    for name in &names {
+        script.push_str(&format!("#     {name}:\n#       external: true\n"));
+    }
+    Ok(script)
+}
+
+/// An instance chosen for export, with the model and label it was chosen through
+pub(crate) struct Selected<'a> {
+    pub(crate) instance: &'a ProviderInstance,
+    pub(crate) model: Option<String>,
+    pub(crate) label: Option<String>,
+}
+
+/// Labels as (label, instance ID, model) tuples, sorted by label, each
+/// resolved to the first of its targets that is available
+pub(crate) fn label_targets(
+    collection: &ProviderCollection,
+    assignments: &[LabelAssignment],
+) -> Vec<(String, String, Option<String>)> {
+    let mut labels: Vec<(String, String, Option<String>)> = assignments
+        .iter()
+        .filter_map(|assignment| resolve_in(assignments, collection, &assignment.label_name).ok())
+        .map(|resolved| {
+            (
+                resolved.label,
+                resolved.instance.id,
+                Some(resolved.model.id),
+            )
+        })
+        .collect();
+    labels.sort();
+    labels
+}
+
+/// Picks the instance a label resolves to, the named instances, or else all
+/// active instances sorted by ID
+pub(crate) fn select_instances<'a>(
+    collection: &'a ProviderCollection,
+    assignments: &[LabelAssignment],
+    label: Option<&str>,
+    instance_ids: &[String],
+    home: Option<&Path>,
+) -> Result<Vec<Selected<'a>>> {
+    Ok(if let Some(label) = label {
+        let resolved = resolve_in(assignments, collection, label)?;
+        for reason in &resolved.skipped {
+            eprintln!("{} skipped {}", "Note:".yellow(), reason);
+        }
+        enforce_budgets(Some(label), Some(&resolved.instance.id), home)?;
+        let instance = collection
+            .get_instance(&resolved.instance.id)
+            .ok_or_else(|| anyhow::anyhow!("Instance '{}' not found", resolved.instance.id))?;
+        vec![Selected {
+            instance,
+            model: Some(resolved.model.id),
+            label: Some(label.to_string()),
+        }]
+    } else if !instance_ids.is_empty() {
+        instance_ids
+            .iter()
+            .map(|id| {
+                let instance = collection
+                    .get_instance(id)
+                    .ok_or_else(|| anyhow::anyhow!("Instance '{}' not found", id))?;
+                Ok(Selected {
+                    instance,
+                    model: instance.models.first().cloned(),
+                    label: None,
+                })
+            })
+            .collect::<Result<_>>()?
+    } else {
+        let mut active: Vec<&ProviderInstance> = collection
+            .all_instances()
+            .into_iter()
+            .filter(|instance| instance.active)
+            .collect();
+        active.sort_by(|a, b| a.id.cmp(&b.id));
+        active
+            .into_iter()
+            .map(|instance| Selected {
+                instance,
+                model: instance.models.first().cloned(),
+                label: None,
+            })
+            .collect()
+    })
+}
+
+/// Handle the export command
+#[allow(clippy::too_many_arguments)]
+pub fn handle_export(
+    format: String,
+    output: Option<String>,
+    env_refs: bool,
+    merge: Option<String>,
+    servers: Vec<String>,
+    label: Option<String>,
+    instance_ids: Vec<String>,
+    tag: Option<String>,
+    no_values: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    if format != "mcp-env" && (merge.is_some() || !servers.is_empty()) {
+        anyhow::bail!("--merge and --server are only supported with --format mcp-env");
+    }
+    if format != "litellm" && env_refs {
+        anyhow::bail!("--env-refs is only supported with --format litellm");
+    }
+    if !matches!(format.as_str(), "env" | "docker-env") && no_values {
+        anyhow::bail!("--no-values is only supported with the env and docker-env formats");
+    }
+
+    let collection = load_provider_instances(home)?;
+    let assignments = load_label_assignments_with_home(home)?;
+    let labels = label_targets(&collection, &assignments);
+    let selected = select_instances(
+        &collection,
+        &assignments,
+        label.as_deref(),
+        &instance_ids,
+        home,
+    )?;
+    // Tags name the services an instance's keys are meant for
+    let selected = match tag {
+        Some(ref tag) => {
+            let mut tagged = Vec::new();
+            for selection in selected {
+                if get_tags_for_target(&selection.instance.id, None, home)?
+                    .iter()
+                    .any(|t| t.name == *tag)
+                {
+                    tagged.push(selection);
+                }
+            }
+            tagged
+        }
+        None => selected,
+    };
+    if selected.is_empty() {
+        anyhow::bail!("No active provider instances to export");
+    }
+    let instances: Vec<&ProviderInstance> = selected.iter().map(|s| s.instance).collect();
+
+    let content = match format.as_str() {
+        "litellm" => render_litellm(&instances, &labels, env_refs)?,
+        "mcp-env" => render_mcp_env(&key_env_vars(&instances)?, merge.as_deref(), &servers)?,
+        // Compose env_files use the same KEY=value syntax as .env files
+        "env" | "docker-env" => render_env(&selected, no_values)?,
+        "docker-secrets" => render_docker_secrets(&selected)?,
+        _ => anyhow::bail!(
+            "Unknown export format: {} (expected litellm, mcp-env, env, docker-env or docker-secrets)",
+            format
+        ),
+    };
+    write_output(&content, output.as_deref())
+}
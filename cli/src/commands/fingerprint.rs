@@ -0,0 +1,34 @@
+//! Fingerprint command for the aicred CLI.
+//!
+//! Prints the hash `aicred scan` reports for a key, so a key known on one
+//! machine can be matched against the findings of another without sending the
+//! key itself. With `hmac-sha256` both machines need the same salt file.
+
+use crate::utils::provider_loader::base_config_store;
+use aicred_core::fingerprint::{Fingerprinter, HashAlgorithm};
+use anyhow::{anyhow, Result};
+use std::io::Read;
+use std::path::Path;
+
+/// Handle the fingerprint command, reading the key from stdin if it is `-` or omitted
+pub fn handle_fingerprint(
+    key: Option<String>,
+    algorithm: HashAlgorithm,
+    home: Option<&Path>,
+) -> Result<()> {
+    let key = match key.filter(|key| key != "-") {
+        Some(key) => key,
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    if key.is_empty() {
+        return Err(anyhow!("No key given to fingerprint"));
+    }
+
+    let fingerprinter = Fingerprinter::load(&base_config_store(home)?, algorithm)?;
+    println!("{}", fingerprinter.fingerprint(&key));
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Scan history command for the aicred CLI.
+
+use aicred_core::history::{self, ScanHistory};
+use anyhow::Result;
+use colored::*;
+use std::path::Path;
+
+/// Handle the history command
+pub fn handle_history(
+    keys: bool,
+    limit: Option<usize>,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    let history = match home {
+        Some(home) => ScanHistory::for_home(home),
+        None => ScanHistory::default_location()?,
+    };
+    let records = history.load()?;
+
+    if records.is_empty() && format == "table" {
+        println!("{}", "No scans recorded yet.".yellow());
+        println!(
+            "{}",
+            "Each 'aicred scan' is recorded unless --no-history is given.".dimmed()
+        );
+        return Ok(());
+    }
+
+    if keys {
+        let mut timelines = history::key_timelines(&records);
+        if let Some(limit) = limit {
+            timelines.truncate(limit);
+        }
+        if format == "json" {
+            println!("{}", serde_json::to_string_pretty(&timelines)?);
+            return Ok(());
+        }
+
+        println!("\n{}", "Key History:".green().bold());
+        println!(
+            "{:<14} {:<15} {:<18} {:<18} {:<8}",
+            "Hash".bold(),
+            "Provider".bold(),
+            "First Seen".bold(),
+            "Last Seen".bold(),
+            "Status".bold()
+        );
+        println!("{}", "-".repeat(80));
+        for timeline in &timelines {
+            let status = match timeline.disappeared_at {
+                Some(at) => format!("gone since {}", at.format("%Y-%m-%d")).dimmed(),
+                None => "present".green(),
+            };
+            println!(
+                "{:<14} {:<15} {:<18} {:<18} {}",
+                timeline.hash[..12].cyan(),
+                timeline.provider.yellow(),
+                timeline.first_seen.format("%Y-%m-%d %H:%M"),
+                timeline.last_seen.format("%Y-%m-%d %H:%M"),
+                status
+            );
+        }
+        println!(
+            "\n{}",
+            format!("Total keys seen: {}", timelines.len()).cyan()
+        );
+        return Ok(());
+    }
+
+    let mut trends = history::trends(&records);
+    if let Some(limit) = limit {
+        trends.drain(..trends.len().saturating_sub(limit));
+    }
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&trends)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "Scan History:".green().bold());
+    println!(
+        "{:<20} {:<8} {:<8} {:<8}",
+        "Scanned".bold(),
+        "Keys".bold(),
+        "New".bold(),
+        "Gone".bold()
+    );
+    println!("{}", "-".repeat(46));
+    for trend in &trends {
+        let added = if trend.added > 0 {
+            format!("+{}", trend.added).red()
+        } else {
+            "-".dimmed()
+        };
+        let removed = if trend.removed > 0 {
+            format!("-{}", trend.removed).green()
+        } else {
+            "-".dimmed()
+        };
+        println!(
+            "{:<20} {:<8} {:<8} {:<8}",
+            trend.scanned_at.format("%Y-%m-%d %H:%M"),
+            trend.total,
+            added,
+            removed
+        );
+    }
+    println!(
+        "\n{}",
+        format!(
+            "{} scan(s) recorded in {}",
+            records.len(),
+            history.path().display()
+        )
+        .cyan()
+    );
+    Ok(())
+}
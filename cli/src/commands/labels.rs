@@ -1,222 +1,73 @@
 //! Label management commands for the aicred CLI.
 
-use crate::utils::provider_loader::load_provider_instances;
+use crate::output::responses::{print_json, wants_json, LabelEntry, LabelsResponse};
+use crate::utils::provider_loader::{base_config_store, load_provider_instances};
+use aicred_app::labels::{
+    self as app_labels, load_labels as load_labels_with_home, save_labels as save_labels_with_home,
+};
 use aicred_core::env_resolver::LabelWithTarget;
-use aicred_core::models::{Label, LabelAssignment, LabelTarget, ProviderCollection};
+use aicred_core::models::{Label, LabelAssignment, LabelTarget};
+use aicred_core::resolve::resolve_in;
 use aicred_core::utils::ProviderModelTuple;
 use anyhow::Result;
 use colored::*;
-use std::io::Write;
 use std::path::Path;
-use tempfile::NamedTempFile;
-use tracing::{debug, error, info};
+
+pub use aicred_app::labels::{
+    assign_label, assign_label_with_fallbacks,
+    load_label_assignments as load_label_assignments_with_home,
+    save_label_assignments as save_label_assignments_with_home,
+};
 
 /// Load labels with their target assignments for use with EnvResolver
+///
+/// Each label is resolved to the first of its targets that is available.
 pub fn load_labels_with_targets(home: Option<&Path>) -> Result<Vec<LabelWithTarget>> {
     let assignments = load_label_assignments_with_home(home)?;
     let provider_instances = load_provider_instances(home)?;
 
-    // Convert LabelAssignments to LabelWithTarget format
     let mut labels_with_targets = Vec::new();
-    for assignment in assignments {
-        // Convert LabelTarget to ProviderModelTuple
-        let tuple = match &assignment.target {
-            LabelTarget::ProviderInstance { instance_id } => {
-                // Find provider instance
-                if let Some(instance) = provider_instances.get_instance(instance_id) {
-                    // Use first model if available
-                    if let Some(model_id) = instance.models.first() {
-                        ProviderModelTuple::new(instance.provider_type.clone(), model_id.clone())
-                    } else {
-                        ProviderModelTuple::new(instance.provider_type.clone(), String::new())
-                    }
-                } else {
-                    continue;
-                }
-            }
-            LabelTarget::ProviderModel {
-                instance_id,
-                model_id,
-            } => {
-                // Find provider instance
-                if let Some(instance) = provider_instances.get_instance(instance_id) {
-                    ProviderModelTuple::new(instance.provider_type.clone(), model_id.clone())
-                } else {
-                    continue;
-                }
-            }
+    for assignment in &assignments {
+        let Ok(resolved) = resolve_in(&assignments, &provider_instances, &assignment.label_name)
+        else {
+            continue;
         };
-
-        labels_with_targets.push(LabelWithTarget::new(assignment.label_name, tuple));
+        let tuple = ProviderModelTuple::new(resolved.instance.provider_type, resolved.model.id);
+        labels_with_targets.push(LabelWithTarget::new(resolved.label, tuple));
     }
 
     Ok(labels_with_targets)
 }
 
-/// Load all label assignments from the configuration directory
-pub fn load_label_assignments_with_home(home: Option<&Path>) -> Result<Vec<LabelAssignment>> {
-    let config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => {
-            // Check HOME environment variable first (for test compatibility)
-            if let Ok(home_env) = std::env::var("HOME") {
-                std::path::PathBuf::from(home_env)
-            } else {
-                dirs_next::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            }
-        }
-    }
-    .join(".config")
-    .join("aicred");
-
-    let labels_file = config_dir.join("labels.yaml");
-
-    if !labels_file.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content = std::fs::read_to_string(&labels_file)?;
-    let labels: Vec<LabelAssignment> = serde_yaml::from_str(&content)?;
-    Ok(labels)
-}
-
 /// Load all label assignments from the configuration directory
 pub fn load_label_assignments() -> Result<Vec<LabelAssignment>> {
-    load_label_assignments_with_home(None)
-}
-
-/// Save label assignments to the configuration directory
-pub fn save_label_assignments_with_home(
-    labels: &[LabelAssignment],
-    home: Option<&Path>,
-) -> Result<()> {
-    let config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => {
-            // Check HOME environment variable first (for test compatibility)
-            if let Ok(home_env) = std::env::var("HOME") {
-                std::path::PathBuf::from(home_env)
-            } else {
-                dirs_next::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            }
-        }
-    }
-    .join(".config")
-    .join("aicred");
-
-    std::fs::create_dir_all(&config_dir)?;
-
-    let labels_file = config_dir.join("labels.yaml");
-    let content = serde_yaml::to_string(labels)?;
-
-    // Atomic file write pattern: create temp file, write, flush, sync, then rename
-    debug!("Starting atomic write to labels.yaml");
-
-    // Create a temporary file in the same directory as the target file
-    let temp_file = NamedTempFile::new_in(&config_dir)?;
-    let temp_path = temp_file.path().to_path_buf();
-
-    // Write the content to the temporary file
-    {
-        let mut file = temp_file.as_file();
-        file.write_all(content.as_bytes())?;
-        file.flush()?;
-
-        // Ensure data is written to disk
-        if let Err(e) = file.sync_all() {
-            error!("Failed to sync temporary file to disk: {}", e);
-            return Err(e.into());
-        }
-    }
-
-    debug!("Temporary file written and synced successfully");
-
-    // Atomically rename the temporary file to the target file
-    if let Err(e) = std::fs::rename(&temp_path, &labels_file) {
-        error!("Failed to rename temporary file to labels.yaml: {}", e);
-        // Clean up the temporary file on error
-        let _ = std::fs::remove_file(&temp_path);
-        return Err(e.into());
-    }
-
-    info!(
-        "Successfully saved label assignments atomically to {:?}",
-        labels_file
-    );
-
-    Ok(())
+    Ok(load_label_assignments_with_home(None)?)
 }
 
 /// Save label assignments to the configuration directory
 pub fn save_label_assignments(labels: &[LabelAssignment]) -> Result<()> {
-    save_label_assignments_with_home(labels, None)
-}
-
-/// Load labels (metadata) from a separate file
-fn load_labels_with_home(home: Option<&Path>) -> Result<std::collections::HashMap<String, Label>> {
-    let config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => {
-            // Check HOME environment variable first (for test compatibility)
-            if let Ok(home_env) = std::env::var("HOME") {
-                std::path::PathBuf::from(home_env)
-            } else {
-                dirs_next::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            }
-        }
-    }
-    .join(".config")
-    .join("aicred");
-
-    let labels_metadata_file = config_dir.join("labels_metadata.yaml");
-
-    if !labels_metadata_file.exists() {
-        return Ok(std::collections::HashMap::new());
-    }
-
-    let content = std::fs::read_to_string(&labels_metadata_file)?;
-    let labels: Vec<Label> = serde_yaml::from_str(&content)?;
-    Ok(labels.into_iter().map(|l| (l.name.clone(), l)).collect())
-}
-
-/// Save labels (metadata) to a separate file
-fn save_labels_with_home(
-    labels: &std::collections::HashMap<String, Label>,
-    home: Option<&Path>,
-) -> Result<()> {
-    let config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => {
-            // Check HOME environment variable first (for test compatibility)
-            if let Ok(home_env) = std::env::var("HOME") {
-                std::path::PathBuf::from(home_env)
-            } else {
-                dirs_next::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            }
-        }
-    }
-    .join(".config")
-    .join("aicred");
-
-    std::fs::create_dir_all(&config_dir)?;
-
-    let labels_metadata_file = config_dir.join("labels_metadata.yaml");
-    let labels_vec: Vec<Label> = labels.values().cloned().collect();
-    let content = serde_yaml::to_string(&labels_vec)?;
-    std::fs::write(&labels_metadata_file, content)?;
-
-    Ok(())
+    Ok(save_label_assignments_with_home(labels, None)?)
 }
 
 /// Handle the labels list command
-pub fn handle_list_labels() -> Result<()> {
+pub fn handle_list_labels(format: String) -> Result<()> {
     let assignments = load_label_assignments()?;
     let labels_metadata = load_labels_with_home(None)?;
 
+    if wants_json(&format)? {
+        return print_json(&LabelsResponse {
+            labels: assignments
+                .iter()
+                .map(|assignment| {
+                    let description = labels_metadata
+                        .get(&assignment.label_name)
+                        .and_then(|label| label.description.clone());
+                    LabelEntry::new(assignment, description)
+                })
+                .collect(),
+        });
+    }
+
     if assignments.is_empty() {
         println!("{}", "No labels configured.".yellow());
         println!(
@@ -237,6 +88,18 @@ pub fn handle_list_labels() -> Result<()> {
             assignment.label_name.cyan().bold(),
             assignment_target_to_string(&assignment.target).dimmed()
         );
+        for fallback in &assignment.fallbacks {
+            println!(
+                "    then {}",
+                assignment_target_to_string(fallback).dimmed()
+            );
+        }
+        if !assignment.requires.is_empty() {
+            println!(
+                "    then {}",
+                format!("any model with {}", assignment.requires.join(", ")).dimmed()
+            );
+        }
 
         if let Some(label) = label_metadata {
             if let Some(ref description) = label.description {
@@ -273,22 +136,8 @@ fn assignment_target_to_string(target: &LabelTarget) -> String {
 /// Find the labels directory using runtime path resolution
 /// First checks user config directory, then falls back to distributed application files
 fn find_labels_directory(home: Option<&Path>) -> Result<std::path::PathBuf> {
-    // First try user config directory: ~/.config/aicred/patterns/
-    let user_config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => {
-            // Check HOME environment variable first (for test compatibility)
-            if let Ok(home_env) = std::env::var("HOME") {
-                std::path::PathBuf::from(home_env)
-            } else {
-                dirs_next::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            }
-        }
-    }
-    .join(".config")
-    .join("aicred")
-    .join("patterns");
+    // First try user config directory, such as ~/.config/aicred/patterns/
+    let user_config_dir = base_config_store(home)?.dir().join("patterns");
 
     if user_config_dir.exists() {
         return Ok(user_config_dir);
@@ -587,6 +436,8 @@ pub fn handle_label_scan(dry_run: bool, verbose: bool, home: Option<&Path>) -> R
                 },
                 assigned_at: chrono::Utc::now(),
                 assigned_by: None,
+                fallbacks: Vec::new(),
+                requires: Vec::new(),
             };
 
             // Check if assignment already exists and update it
@@ -735,50 +586,13 @@ pub fn handle_label_scan(dry_run: bool, verbose: bool, home: Option<&Path>) -> R
     Ok(())
 }
 
-/// Save provider instances to configuration directory
-fn save_provider_instances(instances: &ProviderCollection, home: Option<&Path>) -> Result<()> {
-    let config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => {
-            // Check HOME environment variable first (for test compatibility)
-            if let Ok(home_env) = std::env::var("HOME") {
-                std::path::PathBuf::from(home_env)
-            } else {
-                dirs_next::home_dir()
-                    .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            }
-        }
-    }
-    .join(".config")
-    .join("aicred")
-    .join("inference_services");
-
-    std::fs::create_dir_all(&config_dir)?;
-
-    // Save each instance to its own file
-    for instance in instances.all_instances() {
-        // Use provider name and first 4 chars of instance ID (hash)
-        let file_name = format!(
-            "{}-{}.yaml",
-            instance.provider_type,
-            &instance.id[..4.min(instance.id.len())]
-        );
-        let file_path = config_dir.join(&file_name);
-
-        // Serialize into a ProviderInstance YAML
-        let yaml_content = serde_yaml::to_string(instance)?;
-        std::fs::write(&file_path, yaml_content)?;
-    }
-
-    Ok(())
-}
-
 /// Handle the labels set command (create or update label assignment)
 pub fn handle_set_label(
     label_name: String,
     tuple_str: String,
     _color: Option<String>, // Color not supported in new Label
     description: Option<String>,
+    requires: Vec<String>,
     home: Option<&Path>,
 ) -> Result<()> {
     // Trim and validate label name
@@ -787,84 +601,16 @@ pub fn handle_set_label(
         return Err(anyhow::anyhow!("Label name cannot be empty"));
     }
 
-    let mut assignments = load_label_assignments_with_home(home)?;
-    let mut labels_metadata = load_labels_with_home(home)?;
-
-    // Parse the provider:model tuple
-    let tuple = ProviderModelTuple::parse(&tuple_str)
-        .map_err(|e| anyhow::anyhow!("Invalid provider:model tuple '{}': {}", tuple_str, e))?;
-
-    // Load provider instances to find the instance_id
-    let mut provider_instances = load_provider_instances(home)?;
-
-    // Find the matching provider instance
-    let provider_instances_list = provider_instances.all_instances();
-    let provider_instance = match provider_instances_list
-        .iter()
-        .find(|inst| inst.provider_type == tuple.provider())
-    {
-        Some(instance) => *instance,
-        None => {
-            // Auto-create a provider instance if none exists for this provider type
-            let instance_id = format!("auto-{}", tuple.provider());
-            let base_url = match tuple.provider().to_lowercase().as_str() {
-                "openai" => "https://api.openai.com/v1",
-                "anthropic" => "https://api.anthropic.com/v1",
-                "groq" => "https://api.groq.com/openai/v1",
-                "openrouter" => "https://openrouter.ai/api/v1",
-                "huggingface" => "https://huggingface.co/api",
-                "ollama" => "http://localhost:11434",
-                "litellm" => "http://localhost:4000",
-                _ => "https://api.example.com",
-            };
-
-            let new_instance = aicred_core::models::ProviderInstance::new(
-                instance_id.clone(),
-                format!("{} Instance", tuple.provider()),
-                tuple.provider().to_lowercase(),
-                base_url.to_string(),
-                Vec::new(),
-            );
-
-            // Add the new instance to the collection
-            provider_instances
-                .add_instance(new_instance.clone())
-                .map_err(|e| anyhow::anyhow!("Failed to create provider instance: {}", e))?;
-
-            // Save the updated instances
-            save_provider_instances(&provider_instances, home)?;
-
-            // Get reference to the newly added instance
-            provider_instances.get_instance(&instance_id).unwrap()
-        }
-    };
-
-    // Create label assignment target
-    let target = if let Some(model) = provider_instance.models.iter().find(|m: &&String| {
-        let basename = m.rsplit('/').next().unwrap_or(m);
-        basename == tuple.model()
-    }) {
-        LabelTarget::ProviderModel {
-            instance_id: provider_instance.id.clone(),
-            model_id: model.clone(),
-        }
-    } else {
-        // Model not found, create instance-level assignment
-        LabelTarget::ProviderInstance {
-            instance_id: provider_instance.id.clone(),
-        }
-    };
-
-    // Check if this label already exists and update it, or create new one
-    let existing_assignment_index = assignments
-        .iter()
-        .position(|assignment| assignment.label_name == label_name);
-
-    if let Some(index) = existing_assignment_index {
-        // Update existing assignment
-        assignments[index].target = target;
-        assignments[index].assigned_at = chrono::Utc::now();
+    // A comma separated list is a fallback chain, tried in order
+    let instances = load_provider_instances(home)?;
+    let mut targets = Vec::new();
+    for tuple in tuple_str.split(',').map(str::trim) {
+        targets.push(app_labels::label_target(tuple, &instances)?);
+    }
+    let target = targets.remove(0);
+    app_labels::check_capabilities(&requires)?;
 
+    if assign_label_with_fallbacks(&label_name, target, targets, requires, description, home)? {
         println!(
             "{} Label '{}' updated successfully.",
             "✓".green(),
@@ -872,57 +618,18 @@ pub fn handle_set_label(
         );
         println!("  Now assigned to: {}", tuple_str.cyan());
     } else {
-        // Create new assignment
-        let assignment = LabelAssignment {
-            label_name: label_name.clone(),
-            target,
-            assigned_at: chrono::Utc::now(),
-            assigned_by: None,
-        };
-
-        assignments.push(assignment);
         println!("{} Label '{}' set successfully.", "✓".green(), label_name);
         println!("  Assigned to: {}", tuple_str.cyan());
     }
 
-    // Update label metadata
-    if description.is_some() || !labels_metadata.contains_key(&label_name) {
-        let label = labels_metadata
-            .entry(label_name.clone())
-            .or_insert_with(|| Label {
-                name: label_name.clone(),
-                description: None,
-                created_at: chrono::Utc::now(),
-                metadata: std::collections::HashMap::new(),
-            });
-
-        label.description = description;
-    }
-
-    // Save to disk
-    save_label_assignments_with_home(&assignments, home)?;
-    save_labels_with_home(&labels_metadata, home)?;
-
     Ok(())
 }
 
 /// Handle the labels unset command (remove label assignment entirely)
 pub fn handle_unset_label(name: String, force: bool, home: Option<&Path>) -> Result<()> {
-    let mut assignments = load_label_assignments_with_home(home)?;
-    let mut labels_metadata = load_labels_with_home(home)?;
-
-    // Find the assignment by name
-    let assignment_index = assignments
-        .iter()
-        .position(|assignment| assignment.label_name == name);
-
-    if assignment_index.is_none() {
-        return Err(anyhow::anyhow!("Label '{}' not found", name));
-    }
-
-    let assignment = assignments[assignment_index.unwrap()].clone();
-
     if !force {
+        let assignment = app_labels::find_label(&name, home)?
+            .ok_or_else(|| anyhow::anyhow!("Label '{}' not found", name))?;
         println!(
             "{}",
             "Warning: This will permanently remove the label assignment."
@@ -938,16 +645,7 @@ pub fn handle_unset_label(name: String, force: bool, home: Option<&Path>) -> Res
         return Ok(());
     }
 
-    // Remove the assignment
-    assignments.remove(assignment_index.unwrap());
-    save_label_assignments_with_home(&assignments, home)?;
-
-    // Also remove label metadata if no more assignments reference it
-    if !assignments.iter().any(|a| a.label_name == name) {
-        labels_metadata.remove(&name);
-        save_labels_with_home(&labels_metadata, home)?;
-    }
-
+    app_labels::unset_label(&name, home)?;
     println!("{} Label '{}' unset successfully.", "✓".green(), name);
 
     Ok(())
@@ -1035,6 +733,8 @@ mod tests {
             },
             assigned_at: chrono::Utc::now(),
             assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
 
         let label = Label {
@@ -1066,6 +766,8 @@ mod tests {
             },
             assigned_at: chrono::Utc::now(),
             assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
         let label = Label {
             name: "thinking".to_string(),
@@ -1095,6 +797,8 @@ mod tests {
             },
             assigned_at: chrono::Utc::now(),
             assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
         let label = Label {
             name: "thinking".to_string(),
@@ -1124,6 +828,8 @@ mod tests {
             },
             assigned_at: chrono::Utc::now(),
             assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
         let label = Label {
             name: "thinking".to_string(),
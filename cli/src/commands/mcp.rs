@@ -0,0 +1,185 @@
+//! Model Context Protocol server for the aicred CLI.
+//!
+//! Speaks JSON-RPC over stdio, one message per line, and exposes read-only
+//! tools through which agents can see which providers and models are
+//! available. Tools never return key values.
+
+use crate::commands::daemon::{codes, dispatch, response, run_scan, RpcError};
+use aicred_core::models::RiskLevel;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// MCP protocol revision implemented by the server.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Names of the tools the server exposes.
+const TOOL_NAMES: &[&str] = &["list_instances", "resolve_label", "scan_summary"];
+
+/// Tool descriptions returned by `tools/list`
+fn tools() -> Value {
+    json!([
+        {
+            "name": "list_instances",
+            "description": "List configured AI provider instances with their base URLs and models. API keys are masked.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "active_only": { "type": "boolean", "description": "Only list active instances" }
+                }
+            }
+        },
+        {
+            "name": "resolve_label",
+            "description": "Resolve a semantic label such as 'fast' or 'smart' to the provider instance and model it points at. API keys are masked.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "label": { "type": "string", "description": "Label name" }
+                },
+                "required": ["label"]
+            }
+        },
+        {
+            "name": "scan_summary",
+            "description": "Scan the home directory for AI credentials and summarize what was found per provider and file, without key values.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "only": { "type": "array", "items": { "type": "string" }, "description": "Only scan these providers" },
+                    "exclude": { "type": "array", "items": { "type": "string" }, "description": "Skip these providers" }
+                }
+            }
+        }
+    ])
+}
+
+/// Runs a tool, returning its JSON output
+fn call_tool(name: &str, arguments: &Value, home: &Path) -> Result<Value, RpcError> {
+    match name {
+        "list_instances" => dispatch(
+            "instances.list",
+            &json!({ "active_only": arguments.get("active_only").and_then(Value::as_bool).unwrap_or(false) }),
+            home,
+        ),
+        "resolve_label" => dispatch(
+            "labels.resolve",
+            &json!({ "label": arguments.get("label").cloned().unwrap_or(Value::Null) }),
+            home,
+        ),
+        "scan_summary" => {
+            let result = run_scan(arguments, home)?;
+            let mut by_provider: BTreeMap<&str, usize> = BTreeMap::new();
+            for key in &result.keys {
+                *by_provider.entry(key.provider.as_str()).or_default() += 1;
+            }
+            Ok(json!({
+                "home_dir": result.home_directory,
+                "keys": result.keys.len(),
+                "high_risk": result.keys.iter().filter(|k| k.risk >= RiskLevel::High).count(),
+                "config_instances": result.config_instances.len(),
+                "by_provider": by_provider,
+                "findings": result.keys.iter().map(|key| json!({
+                    "provider": key.provider,
+                    "source_file": key.source_file,
+                    "line": key.source_line,
+                    "risk": key.risk,
+                })).collect::<Vec<_>>(),
+            }))
+        }
+        _ => Err(RpcError::new(
+            codes::INVALID_PARAMS,
+            format!("Unknown tool: {name}"),
+        )),
+    }
+}
+
+/// Handles one MCP request
+fn handle_request(method: &str, params: &Value, home: &Path) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "aicred", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => {
+            let name = params
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::new(codes::INVALID_PARAMS, "'name' is required"))?;
+            if !TOOL_NAMES.contains(&name) {
+                return Err(RpcError::new(
+                    codes::INVALID_PARAMS,
+                    format!("Unknown tool: {name}"),
+                ));
+            }
+            let arguments = params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            // Failures of a known tool are reported to the agent as tool output
+            match call_tool(name, &arguments, home) {
+                Ok(output) => Ok(json!({
+                    "content": [{ "type": "text", "text": serde_json::to_string_pretty(&output).map_err(RpcError::internal)? }],
+                })),
+                Err(error) => Ok(json!({
+                    "content": [{ "type": "text", "text": error.message }],
+                    "isError": true,
+                })),
+            }
+        }
+        _ => Err(RpcError::new(
+            codes::METHOD_NOT_FOUND,
+            format!("Unknown method: {method}"),
+        )),
+    }
+}
+
+/// Handle the mcp command
+pub fn handle_mcp(home: Option<&Path>) -> Result<()> {
+    let home_dir = match home {
+        Some(h) => h.to_path_buf(),
+        None => dirs_next::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?,
+    };
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError::new(codes::PARSE_ERROR, e.to_string());
+                writeln!(stdout, "{}", response(Value::Null, Err(error)))?;
+                stdout.flush()?;
+                continue;
+            }
+        };
+        // Notifications such as notifications/initialized need no response
+        let Some(id) = request.get("id").cloned() else {
+            continue;
+        };
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) => handle_request(
+                method,
+                request.get("params").unwrap_or(&Value::Null),
+                &home_dir,
+            ),
+            None => Err(RpcError::new(
+                codes::INVALID_REQUEST,
+                "'method' is required",
+            )),
+        };
+        writeln!(stdout, "{}", response(id, result))?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
@@ -1,7 +1,29 @@
+pub mod audit;
+pub mod budget;
+pub mod cleanup;
+pub mod completions;
+pub mod config;
+pub mod cost;
+pub mod daemon;
+pub mod doctor;
+pub mod export;
+pub mod fingerprint;
+pub mod history;
 pub mod labels;
+pub mod mcp;
+pub mod models;
+pub mod profile;
 pub mod providers;
+pub mod resolve;
+pub mod revoke;
+pub mod rotate;
+pub mod rules;
 pub mod scan;
+pub mod setenv;
 pub mod tags;
+pub mod tui;
+pub mod usage;
+pub mod wizard;
 pub mod wrap;
 
 // Re-export helper functions for use in output modules
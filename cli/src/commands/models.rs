@@ -0,0 +1,391 @@
+//! Model comparison and recommendation from the model registry.
+
+use crate::utils::provider_loader::{
+    cache_dir, load_provider_instances, model_registry, resolve_secret,
+};
+use aicred_core::http::NetworkOptions;
+use aicred_core::models::{RefreshOptions, RefreshSource};
+use aicred_core::{ModelCapabilities, ModelEntry, ModelQuery, ModelRegistry, ModelRequirements};
+use anyhow::{anyhow, Result};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A registry entry with the configured instances offering it
+#[derive(Debug, Serialize)]
+struct AvailableModel<'a> {
+    #[serde(flatten)]
+    entry: &'a ModelEntry,
+    instances: Vec<String>,
+}
+
+/// IDs of the active instances offering each model, by registry ID
+fn instances_by_model(
+    registry: &ModelRegistry,
+    home: Option<&Path>,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut by_model: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for instance in load_provider_instances(home)?.active_instances() {
+        for model in &instance.models {
+            by_model
+                .entry(registry.canonical_id(model))
+                .or_default()
+                .push(instance.id.clone());
+        }
+    }
+    Ok(by_model)
+}
+
+fn per_million(cost_per_token: f64) -> String {
+    format!("${:.2}", cost_per_token * 1_000_000.0)
+}
+
+fn yes_no(supported: bool) -> String {
+    if supported { "yes" } else { "no" }.to_string()
+}
+
+/// Handle the models compare command
+pub fn handle_compare_models(
+    models: Vec<String>,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    let registry = model_registry(home);
+    let by_model = instances_by_model(&registry, home)?;
+    let mut compared = Vec::new();
+    for model in &models {
+        let entry = registry
+            .resolve(model)
+            .ok_or_else(|| anyhow!("Model '{}' is not in the model registry", model))?;
+        compared.push(AvailableModel {
+            entry,
+            instances: by_model.get(&entry.id).cloned().unwrap_or_default(),
+        });
+    }
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&compared)?);
+        return Ok(());
+    }
+
+    let row = |name: &str, values: Vec<String>| {
+        let cells: Vec<String> = values.iter().map(|v| format!("{:<28}", v)).collect();
+        println!("{:<16} {}", name.bold(), cells.join(" ").trim_end());
+    };
+    println!("\n{}", "Model Comparison:".green().bold());
+    row(
+        "Model",
+        compared.iter().map(|m| m.entry.id.clone()).collect(),
+    );
+    println!("{}", "-".repeat(17 + 29 * compared.len()));
+    row(
+        "Provider",
+        compared.iter().map(|m| m.entry.provider.clone()).collect(),
+    );
+    row(
+        "Status",
+        compared
+            .iter()
+            .map(|m| format!("{:?}", m.entry.status).to_lowercase())
+            .collect(),
+    );
+    row(
+        "Context",
+        compared
+            .iter()
+            .map(|m| {
+                m.entry
+                    .context_window
+                    .map_or_else(|| "-".to_string(), |window| format!("{} tokens", window))
+            })
+            .collect(),
+    );
+    row(
+        "Input / 1M",
+        compared
+            .iter()
+            .map(|m| {
+                m.entry
+                    .pricing
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), |p| per_million(p.input_cost_per_token))
+            })
+            .collect(),
+    );
+    row(
+        "Output / 1M",
+        compared
+            .iter()
+            .map(|m| {
+                m.entry
+                    .pricing
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), |p| per_million(p.output_cost_per_token))
+            })
+            .collect(),
+    );
+    for capability in ModelCapabilities::NAMES {
+        row(
+            capability,
+            compared
+                .iter()
+                .map(|m| yes_no(m.entry.capabilities.supports(capability)))
+                .collect(),
+        );
+    }
+    row(
+        "Instances",
+        compared
+            .iter()
+            .map(|m| {
+                if m.instances.is_empty() {
+                    "none".to_string()
+                } else {
+                    m.instances.join(", ")
+                }
+            })
+            .collect(),
+    );
+    Ok(())
+}
+
+/// Handle the models registry command
+pub fn handle_registry_models(
+    provider: Option<String>,
+    capabilities: Vec<String>,
+    search: Option<String>,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    if let Some(unknown) = capabilities
+        .iter()
+        .find(|capability| !ModelCapabilities::is_known(capability))
+    {
+        return Err(anyhow!(
+            "Unknown capability '{}'. Known capabilities: {}",
+            unknown,
+            ModelCapabilities::NAMES.join(", ")
+        ));
+    }
+
+    let registry = model_registry(home);
+    let entries = registry.query(&ModelQuery {
+        provider,
+        capabilities,
+        search,
+    });
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("{}", "No model in the registry matches.".yellow());
+        return Ok(());
+    }
+
+    println!("\n{}", "Model Registry:".green().bold());
+    println!(
+        "{:<32} {:<12} {:>12} {:>12} {:>10}  {}",
+        "Model".bold(),
+        "Provider".bold(),
+        "Input / 1M".bold(),
+        "Output / 1M".bold(),
+        "Context".bold(),
+        "Status".bold()
+    );
+    println!("{}", "-".repeat(96));
+    for entry in &entries {
+        let (input, output) = entry.pricing.as_ref().map_or_else(
+            || ("-".to_string(), "-".to_string()),
+            |p| {
+                (
+                    per_million(p.input_cost_per_token),
+                    per_million(p.output_cost_per_token),
+                )
+            },
+        );
+        println!(
+            "{:<32} {:<12} {:>12} {:>12} {:>10}  {}",
+            entry.id.cyan(),
+            entry.provider.yellow(),
+            input,
+            output,
+            entry
+                .context_window
+                .map_or_else(|| "-".to_string(), |window| window.to_string()),
+            format!("{:?}", entry.status).to_lowercase()
+        );
+    }
+    println!("\n{} models", entries.len());
+    Ok(())
+}
+
+/// Handle the models recommend command
+#[allow(clippy::too_many_arguments)]
+pub fn handle_recommend_models(
+    needs: Vec<String>,
+    max_input_cost: Option<f64>,
+    max_output_cost: Option<f64>,
+    min_context: Option<u32>,
+    all: bool,
+    limit: usize,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    if let Some(unknown) = needs.iter().find(|need| !ModelCapabilities::is_known(need)) {
+        return Err(anyhow!(
+            "Unknown capability '{}'. Known capabilities: {}",
+            unknown,
+            ModelCapabilities::NAMES.join(", ")
+        ));
+    }
+
+    let registry = model_registry(home);
+    let requirements = ModelRequirements {
+        capabilities: needs,
+        max_input_cost,
+        max_output_cost,
+        min_context_window: min_context,
+    };
+    let instances = load_provider_instances(home)?;
+    let mut by_model: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for found in instances.find_model(&registry, &requirements) {
+        let offering = by_model.entry(found.entry.id.as_str()).or_default();
+        if !offering.contains(&found.instance.id) {
+            offering.push(found.instance.id.clone());
+        }
+    }
+    let recommended: Vec<AvailableModel> = registry
+        .recommend(&requirements)
+        .into_iter()
+        .map(|entry| AvailableModel {
+            entry,
+            instances: by_model.remove(entry.id.as_str()).unwrap_or_default(),
+        })
+        .filter(|model| all || !model.instances.is_empty())
+        .take(limit)
+        .collect();
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&recommended)?);
+        return Ok(());
+    }
+    if recommended.is_empty() {
+        println!("{}", "No configured model meets the requirements.".yellow());
+        if !all {
+            println!(
+                "{}",
+                "Use --all to include models none of your instances offer.".dimmed()
+            );
+        }
+        return Ok(());
+    }
+
+    println!("\n{}", "Recommended Models:".green().bold());
+    println!(
+        "{:<4} {:<28} {:<12} {:>12} {:>12} {:>10}  {}",
+        "#".bold(),
+        "Model".bold(),
+        "Provider".bold(),
+        "Input / 1M".bold(),
+        "Output / 1M".bold(),
+        "Context".bold(),
+        "Instances".bold()
+    );
+    println!("{}", "-".repeat(100));
+    for (rank, model) in recommended.iter().enumerate() {
+        let (input, output) = model.entry.pricing.as_ref().map_or_else(
+            || ("-".to_string(), "-".to_string()),
+            |p| {
+                (
+                    per_million(p.input_cost_per_token),
+                    per_million(p.output_cost_per_token),
+                )
+            },
+        );
+        let instances = if model.instances.is_empty() {
+            "none".dimmed()
+        } else {
+            model.instances.join(", ").normal()
+        };
+        println!(
+            "{:<4} {:<28} {:<12} {:>12} {:>12} {:>10}  {}",
+            rank + 1,
+            model.entry.id.cyan(),
+            model.entry.provider.yellow(),
+            input,
+            output,
+            model
+                .entry
+                .context_window
+                .map_or_else(|| "-".to_string(), |window| window.to_string()),
+            instances
+        );
+    }
+    Ok(())
+}
+
+/// Handle the models refresh command
+pub fn handle_refresh_models(
+    force: bool,
+    offline: bool,
+    format: String,
+    network: &NetworkOptions,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    // Instances let the refresh add models only their providers list; one
+    // whose key cannot be resolved is left out
+    let mut instances = Vec::new();
+    if !offline {
+        for instance in load_provider_instances(home)?.active_instances() {
+            if let Ok(api_key) = resolve_secret(&instance.api_key) {
+                instances.push(aicred_core::models::ProviderInstance {
+                    api_key,
+                    ..instance.clone()
+                });
+            }
+        }
+    }
+    let options = RefreshOptions {
+        force,
+        offline,
+        instances,
+        network: network.clone(),
+        ..RefreshOptions::new(&cache_dir(home)?)
+    };
+    let report = ModelRegistry::builtin().refresh_from_remote(&options)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let source = match report.source {
+        RefreshSource::Remote => "fetched just now".green(),
+        RefreshSource::Cache => "from the cache".normal(),
+        RefreshSource::StaleCache => "from an expired cache".yellow(),
+    };
+    println!(
+        "{} model entries {}, fetched {}",
+        report.entries,
+        source,
+        report.fetched_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    for warning in &report.warnings {
+        println!("{} {}", "Warning:".yellow(), warning);
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! Profile management - separate sets of instances, tags and labels.
+//!
+//! The default profile is the base store under `~/.config/aicred`; named
+//! profiles live in `~/.config/aicred/profiles/<name>/` and are complete stores
+//! of their own. Commands use the profile given with `--profile`, then
+//! `AICRED_PROFILE`, then the one chosen with `aicred profile use`.
+
+use crate::utils::provider_loader::{
+    active_profile, base_config_store, profile_store, ACTIVE_PROFILE_FILE, DEFAULT_PROFILE,
+};
+use aicred_core::crypto;
+use aicred_core::ConfigStore;
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::Path;
+
+/// Store files copied by `aicred profile create --from`, besides instances
+const PROFILE_FILES: &[&str] = &[
+    "tags.yaml",
+    "tag_assignments.yaml",
+    "labels.yaml",
+    "labels_metadata.yaml",
+];
+
+/// Names of all profiles, default first
+pub fn profile_names(home: Option<&Path>) -> Result<Vec<String>> {
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    names.extend(base_config_store(home)?.profiles()?);
+    Ok(names)
+}
+
+/// Store of a profile that must already exist
+fn existing_profile_store(home: Option<&Path>, name: &str) -> Result<ConfigStore> {
+    let store = profile_store(home, name)?;
+    if name != DEFAULT_PROFILE && !store.dir().is_dir() {
+        return Err(anyhow!("Profile '{}' not found", name));
+    }
+    Ok(store)
+}
+
+/// Number of parseable instance files in a store
+fn instance_count(store: &ConfigStore) -> usize {
+    store.instance_files().map_or(0, |files| {
+        files
+            .iter()
+            .filter(|(_, content)| store.parse_instance(content).is_ok())
+            .count()
+    })
+}
+
+/// Copy the instances, tags and labels of one store into another
+fn copy_profile(source: &ConfigStore, target: &ConfigStore) -> Result<()> {
+    let lock = target.lock()?;
+    // Copies are upgraded to the current schema, since they get its header
+    for (path, content) in source.instance_files()? {
+        let (Some(file), Ok((instance, _))) = (
+            path.file_name().and_then(|file| file.to_str()),
+            source.parse_instance(&content),
+        ) else {
+            continue;
+        };
+        lock.save_instance_as(file, &instance)?;
+    }
+    for name in PROFILE_FILES {
+        if let Some(value) = source.load_yaml::<serde_yaml::Value>(name)? {
+            lock.save_yaml(name, &value)?;
+        }
+    }
+    // Encrypted keys stay readable with the identity they were sealed with
+    let identity = source.dir().join(crypto::IDENTITY_FILE);
+    if identity.exists() {
+        std::fs::copy(&identity, target.dir().join(crypto::IDENTITY_FILE))?;
+    }
+    Ok(())
+}
+
+/// Handle the profile list command
+pub fn handle_list_profiles(home: Option<&Path>) -> Result<()> {
+    let active = active_profile(home)?;
+    println!("\n{}", "Profiles:".green().bold());
+    for name in profile_names(home)? {
+        let store = profile_store(home, &name)?;
+        let count = instance_count(&store);
+        let marker = if name == active { "*" } else { " " };
+        let line = format!("{} {} ({} instances)", marker, name, count);
+        if name == active {
+            println!("{}", line.cyan().bold());
+        } else {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Handle the profile create command
+pub fn handle_create_profile(
+    name: String,
+    from: Option<String>,
+    home: Option<&Path>,
+) -> Result<()> {
+    if name == DEFAULT_PROFILE {
+        return Err(anyhow!("The '{}' profile always exists", DEFAULT_PROFILE));
+    }
+    let store = profile_store(home, &name)?;
+    if store.dir().is_dir() {
+        return Err(anyhow!("Profile '{}' already exists", name));
+    }
+
+    match &from {
+        Some(source) => copy_profile(&existing_profile_store(home, source)?, &store)?,
+        None => drop(store.lock()?),
+    }
+
+    println!("{} Created profile '{}'", "✓".green(), name.cyan());
+    if let Some(source) = from {
+        println!(
+            "  Copied {} instances, tags and labels from '{}'",
+            instance_count(&store),
+            source
+        );
+    }
+    println!("  Switch to it with: aicred profile use {}", name);
+    Ok(())
+}
+
+/// Handle the profile use command
+pub fn handle_use_profile(name: String, home: Option<&Path>) -> Result<()> {
+    existing_profile_store(home, &name)?;
+    base_config_store(home)?
+        .lock()?
+        .save_yaml(ACTIVE_PROFILE_FILE, &name)?;
+    println!("{} Switched to profile '{}'", "✓".green(), name.cyan());
+    Ok(())
+}
+
+/// Handle the profile show command
+pub fn handle_show_profile(home: Option<&Path>) -> Result<()> {
+    let name = active_profile(home)?;
+    let store = existing_profile_store(home, &name)?;
+    println!("{} {}", "Active profile:".green().bold(), name.cyan());
+    println!("  Directory: {}", store.dir().display());
+    println!("  Instances: {}", instance_count(&store));
+    Ok(())
+}
+
+/// Handle the profile delete command
+pub fn handle_delete_profile(name: String, force: bool, home: Option<&Path>) -> Result<()> {
+    if name == DEFAULT_PROFILE {
+        return Err(anyhow!(
+            "The '{}' profile cannot be deleted",
+            DEFAULT_PROFILE
+        ));
+    }
+    let store = existing_profile_store(home, &name)?;
+    let stored: Option<String> = base_config_store(home)?.load_yaml(ACTIVE_PROFILE_FILE)?;
+    if active_profile(home)? == name || stored.as_deref() == Some(name.as_str()) {
+        return Err(anyhow!(
+            "Profile '{}' is active. Switch to another profile first with 'aicred profile use'",
+            name
+        ));
+    }
+
+    let count = instance_count(&store);
+    if count > 0 && !force {
+        println!(
+            "{}",
+            "Warning: This profile still has provider instances."
+                .yellow()
+                .bold()
+        );
+        println!("Profile: {} ({} instances)", name.cyan(), count);
+        print!("Are you sure you want to delete it? (y/N): ");
+
+        use std::io::{self, Write};
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{}", "Deletion cancelled.".dimmed());
+            return Ok(());
+        }
+    }
+
+    std::fs::remove_dir_all(store.dir())?;
+    println!("{} Deleted profile '{}'", "✓".green(), name.cyan());
+    Ok(())
+}
@@ -1,8 +1,23 @@
-use crate::utils::provider_loader::load_provider_instances;
+use crate::output::responses::{
+    print_json, wants_json, InstanceEntry, InstancesResponse, ModelEntry, ModelsResponse,
+    ProviderEntry, ProvidersResponse,
+};
+use crate::plan::{print_plan, ChangeAction, ChangeKind, ChangePlan, PlanFormat};
+use crate::utils::provider_loader::{
+    config_store, load_provider_instances, model_registry, resolve_secret,
+};
+use aicred_app::instances as app_instances;
+use aicred_core::bulk::{self, InstanceSelector};
+use aicred_core::health::{self, HealthCheck, HealthStatus};
+use aicred_core::http::NetworkOptions;
 use aicred_core::models::{ProviderCollection, ProviderInstance};
+use aicred_core::store::instance_file_name;
+use aicred_core::SecretBackends;
 use anyhow::Result;
 use colored::*;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Truncate a string to a maximum length, adding "..." if truncated
 fn truncate_string(s: &str, max_len: usize) -> String {
@@ -15,25 +30,7 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 
 /// Save provider instances to configuration directory
 fn save_provider_instances(instances: &ProviderCollection) -> Result<()> {
-    let config_dir = dirs_next::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-        .join(".config")
-        .join("aicred")
-        .join("inference_services");
-
-    std::fs::create_dir_all(&config_dir)?;
-
-    // Save each instance to its own file
-    for instance in instances.all_instances() {
-        // Use provider name and first 4 chars of instance ID (hash)
-        let file_name = format!("{}-{}.yaml", instance.provider_type, &instance.id[..4]);
-        let file_path = config_dir.join(&file_name);
-
-        // Serialize into a ProviderInstance YAML
-        let yaml_content = serde_yaml::to_string(instance)?;
-        std::fs::write(&file_path, yaml_content)?;
-    }
-
+    config_store(None)?.save_instances(instances)?;
     Ok(())
 }
 
@@ -45,10 +42,17 @@ pub fn handle_list_instances(
     active_only: bool,
     tag: Option<String>,
     label: Option<String>,
+    format: String,
 ) -> Result<()> {
+    if !matches!(
+        format.as_str(),
+        "table" | "json" | "csv" | "markdown" | "md"
+    ) {
+        anyhow::bail!("Unknown format: {}", format);
+    }
     let instances = load_provider_instances(home.as_deref())?;
 
-    if instances.is_empty() {
+    if instances.is_empty() && format == "table" {
         println!("{}", "No provider instances configured.".yellow());
         println!(
             "{}",
@@ -57,9 +61,8 @@ pub fn handle_list_instances(
         return Ok(());
     }
 
-    println!("\n{}", "Configured Provider Instances:".green().bold());
-
-    let all_instances = instances.all_instances();
+    let mut all_instances = instances.all_instances();
+    all_instances.sort_by(|a, b| a.id.cmp(&b.id));
     let filtered_instances: Vec<&ProviderInstance> = all_instances
         .into_iter()
         .filter(|instance| {
@@ -97,6 +100,24 @@ pub fn handle_list_instances(
         })
         .collect();
 
+    match format.as_str() {
+        "json" => {
+            return print_json(&InstancesResponse {
+                instances: filtered_instances
+                    .iter()
+                    .map(|instance| InstanceEntry::from(*instance))
+                    .collect(),
+            })
+        }
+        "csv" => return crate::output::csv::output_instances_csv(&filtered_instances),
+        "markdown" | "md" => {
+            return crate::output::markdown::output_instances_markdown(&filtered_instances)
+        }
+        _ => {}
+    }
+
+    println!("\n{}", "Configured Provider Instances:".green().bold());
+
     if filtered_instances.is_empty() {
         println!("{}", "No instances match the specified criteria.".yellow());
         return Ok(());
@@ -134,8 +155,32 @@ pub fn handle_list_instances(
                 println!("  Available Models: {}", model_names.join(", "));
             }
 
-            if !instance.metadata.is_empty() {
-                for (key, value) in &instance.metadata {
+            if let Some(status) = instance.metadata.get(health::STATUS_METADATA) {
+                let latency = instance
+                    .metadata
+                    .get(health::LATENCY_METADATA)
+                    .map(|ms| format!(", {} ms", ms))
+                    .unwrap_or_default();
+                let checked = instance
+                    .metadata
+                    .get(health::LAST_CHECKED_METADATA)
+                    .map(String::as_str)
+                    .unwrap_or("unknown");
+                let status = if status == HealthStatus::Ok.as_str() {
+                    status.green()
+                } else {
+                    status.red()
+                };
+                println!("  Health: {}{} (checked {})", status, latency, checked);
+            }
+
+            let health_keys = [
+                health::STATUS_METADATA,
+                health::LATENCY_METADATA,
+                health::LAST_CHECKED_METADATA,
+            ];
+            for (key, value) in &instance.metadata {
+                if !health_keys.contains(&key.as_str()) {
                     println!("  {}: {}", key, value);
                 }
             }
@@ -146,17 +191,18 @@ pub fn handle_list_instances(
         }
     } else {
         // Table mode: show instances in a nicely formatted table
+        let width = id_width(filtered_instances.iter().map(|instance| &instance.id));
         println!(
-            "{:<20} {:<15} {:<15}",
+            "{:<width$} {:<15} {:<15}",
             "ID".bold(),
             "Provider".bold(),
             "Num of Models".bold()
         );
-        println!("{}", "-".repeat(55));
+        println!("{}", "-".repeat(width + 35));
 
         for instance in filtered_instances {
             println!(
-                "{:<20} {:<15} {:<15}",
+                "{:<width$} {:<15} {:<15}",
                 instance.id.cyan(),
                 instance.provider_type.yellow(),
                 instance.model_count()
@@ -169,7 +215,13 @@ pub fn handle_list_instances(
     Ok(())
 }
 
+/// Width of an ID column: the longest ID, and at least 20
+fn id_width<'a>(ids: impl Iterator<Item = &'a String>) -> usize {
+    ids.map(String::len).max().unwrap_or_default().max(20)
+}
+
 /// Handle the add-instance command
+#[allow(clippy::too_many_arguments)]
 pub fn handle_add_instance(
     id: String,
     _name: String, // display_name not supported in new ProviderInstance
@@ -178,7 +230,18 @@ pub fn handle_add_instance(
     api_key: Option<String>,
     models: Option<String>,
     active: bool,
+    store: String,
+    dry_run: Option<PlanFormat>,
 ) -> Result<()> {
+    let backends = SecretBackends::builtin();
+    if backends.get(&store).is_none() {
+        return Err(anyhow::anyhow!(
+            "Unknown secret store '{}'. Supported: {}",
+            store,
+            backends.names().join(", ")
+        ));
+    }
+
     let mut instances = load_provider_instances(None)?;
 
     // Check if instance with this ID already exists
@@ -198,9 +261,9 @@ pub fn handle_add_instance(
     );
     instance.active = active;
 
-    // Add API key if provided
-    if let Some(key_value) = api_key {
-        instance.set_api_key(key_value);
+    // Validate the instance
+    if let Err(e) = instance.validate() {
+        return Err(anyhow::anyhow!("Invalid instance configuration: {}", e));
     }
 
     // Add models if provided
@@ -213,9 +276,15 @@ pub fn handle_add_instance(
         }
     }
 
-    // Validate the instance
-    if let Err(e) = instance.validate() {
-        return Err(anyhow::anyhow!("Invalid instance configuration: {}", e));
+    if let Some(format) = dry_run {
+        let secret_backend = api_key.as_ref().map(|_| store.as_str());
+        let plan = app_instances::plan_add(&config_store(None)?, &instance, secret_backend);
+        return print_plan(&plan, format);
+    }
+
+    // Add API key if provided, keeping only a reference when stored elsewhere
+    if let Some(key_value) = api_key {
+        instance.set_api_key(backends.store(&store, &id, &key_value)?);
     }
 
     // Add to collection
@@ -246,20 +315,41 @@ pub fn handle_add_instance(
     Ok(())
 }
 
-/// Handle the remove-instance command
-pub fn handle_remove_instance(id: String, force: bool) -> Result<()> {
-    let mut instances = load_provider_instances(None)?;
-
-    // Check if instance exists
-    if instances.get_instance(&id).is_none() {
-        return Err(anyhow::anyhow!(
-            "Provider instance with ID '{}' not found",
-            id
-        ));
+/// Selector for the instances named by ID or carrying all the given tags
+fn instance_selector(ids: Vec<String>, tags: Vec<String>) -> InstanceSelector {
+    InstanceSelector {
+        tags,
+        ids,
+        provider_type: None,
     }
+}
 
-    // Get instance for confirmation
-    let instance = instances.get_instance(&id).unwrap();
+/// Handle the remove-instance command
+pub fn handle_remove_instance(
+    id: Option<String>,
+    tags: Vec<String>,
+    force: bool,
+    dry_run: Option<PlanFormat>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let store = config_store(home)?;
+    let selector = instance_selector(id.clone().into_iter().collect(), tags);
+    let selected = bulk::select(&store, &selector)?;
+    if let (Some(format), false) = (dry_run, selected.is_empty()) {
+        return print_plan(&app_instances::plan_remove(&store, &selector)?, format);
+    }
+    if selected.is_empty() {
+        return match id {
+            Some(id) => Err(anyhow::anyhow!(
+                "Provider instance with ID '{}' not found",
+                id
+            )),
+            None => {
+                println!("{}", "No provider instances match the given tags.".yellow());
+                Ok(())
+            }
+        };
+    }
 
     if !force {
         println!(
@@ -268,7 +358,9 @@ pub fn handle_remove_instance(id: String, force: bool) -> Result<()> {
                 .yellow()
                 .bold()
         );
-        println!("Instance: {}", instance.id.cyan());
+        for instance in &selected {
+            println!("Instance: {}", instance.id.cyan());
+        }
         print!("Are you sure? (y/N): ");
 
         use std::io::{self, Write};
@@ -283,19 +375,81 @@ pub fn handle_remove_instance(id: String, force: bool) -> Result<()> {
         }
     }
 
-    // Remove the instance
-    instances.remove_instance(&id);
+    // Remove the instances, their assignments and any secret they reference
+    let backends = SecretBackends::builtin();
+    for instance in bulk::remove(&store, &selector)? {
+        if let Err(e) = backends.delete(&instance.api_key) {
+            eprintln!(
+                "{} {}",
+                "Warning: failed to delete stored secret:".yellow(),
+                e
+            );
+        }
+        println!(
+            "{} Provider instance '{}' removed successfully.",
+            "✓".green(),
+            instance.id.cyan()
+        );
+    }
 
-    // Save to disk - create a copy to avoid borrow issues
-    let instances_copy = instances.clone();
-    save_provider_instances(&instances_copy)?;
+    Ok(())
+}
 
-    println!(
-        "{} Provider instance '{}' removed successfully.",
-        "✓".green(),
-        id.cyan()
-    );
+/// Handle the set-active command
+pub fn handle_set_active(
+    ids: Vec<String>,
+    tags: Vec<String>,
+    active: bool,
+    dry_run: Option<PlanFormat>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let store = config_store(home)?;
+    let selector = instance_selector(ids, tags);
+    let selected = bulk::select(&store, &selector)?;
+    if let Some(missing) = selector
+        .ids
+        .iter()
+        .find(|id| !selected.iter().any(|instance| instance.id == **id))
+    {
+        return Err(anyhow::anyhow!(
+            "Provider instance with ID '{}' not found",
+            missing
+        ));
+    }
+    if selected.is_empty() {
+        println!("{}", "No provider instances match the given tags.".yellow());
+        return Ok(());
+    }
 
+    if let Some(format) = dry_run {
+        let mut plan = ChangePlan::new("instances set-active");
+        for (path, instance) in bulk::select_files(&store, &selector)? {
+            if instance.active != active {
+                plan.add(ChangeAction::Update, ChangeKind::Instance, &instance.id)
+                    .at(&path)
+                    .detail(format!("active: {} -> {}", instance.active, active));
+            }
+        }
+        return print_plan(&plan, format);
+    }
+
+    let changed = bulk::set_active(&store, &selector, active)?;
+    let state = if active { "activated" } else { "deactivated" };
+    for id in &changed {
+        println!(
+            "{} Provider instance '{}' {}.",
+            "✓".green(),
+            id.cyan(),
+            state
+        );
+    }
+    let unchanged = selected.len() - changed.len();
+    if unchanged > 0 {
+        println!(
+            "{}",
+            format!("{} matching instance(s) were already {}.", unchanged, state).dimmed()
+        );
+    }
     Ok(())
 }
 
@@ -307,8 +461,10 @@ pub fn handle_update_instance(
     api_key: Option<String>,
     models: Option<String>,
     active: Option<bool>,
+    dry_run: Option<PlanFormat>,
 ) -> Result<()> {
     let mut instances = load_provider_instances(None)?;
+    let before = instances.get_instance(&id).cloned();
 
     // Get mutable reference to the instance
     let instance = instances
@@ -327,8 +483,22 @@ pub fn handle_update_instance(
         instance.active = new_active;
     }
 
-    // Update API key if provided
-    if let Some(new_key_value) = api_key {
+    // Update API key if provided, in the backend already holding it unless
+    // the new value is itself a reference
+    let mut secret_backend = None;
+    if let Some(new_key_value) = api_key.clone() {
+        let backends = SecretBackends::builtin();
+        let new_key_value = match backends.owner(&instance.api_key) {
+            Some(backend) if !backends.is_reference(&new_key_value) => {
+                secret_backend = Some(backend.name());
+                if dry_run.is_some() {
+                    instance.api_key.clone()
+                } else {
+                    backend.store(&instance_id, &new_key_value)?
+                }
+            }
+            _ => new_key_value,
+        };
         instance.set_api_key(new_key_value);
     }
 
@@ -351,6 +521,15 @@ pub fn handle_update_instance(
     // Get the final active status before saving
     let final_active_status = instance.active;
 
+    if let (Some(format), Some(before)) = (dry_run, before) {
+        let path = config_store(None)?
+            .instances_dir()
+            .join(instance_file_name(&before));
+        let plan =
+            app_instances::plan_update(&path, &before, instance, api_key.is_some(), secret_backend);
+        return print_plan(&plan, format);
+    }
+
     // Save to disk
     save_provider_instances(&instances)?;
 
@@ -372,7 +551,13 @@ pub fn handle_update_instance(
 }
 
 /// Handle the get-instance command
-pub fn handle_get_instance(home: Option<PathBuf>, id: String, include_values: bool) -> Result<()> {
+pub fn handle_get_instance(
+    home: Option<PathBuf>,
+    id: String,
+    include_values: bool,
+    format: String,
+) -> Result<()> {
+    let json = wants_json(&format)?;
     let instances = load_provider_instances(home.as_deref())?;
 
     // Add debug logging to validate the home parameter issue
@@ -389,6 +574,17 @@ pub fn handle_get_instance(home: Option<PathBuf>, id: String, include_values: bo
         .get_instance(&id)
         .ok_or_else(|| anyhow::anyhow!("Provider instance with ID '{}' not found", id))?;
 
+    if json {
+        let mut entry = InstanceEntry::from(instance);
+        if include_values {
+            entry.api_key = instance
+                .get_api_key()
+                .map(|key| resolve_secret(key))
+                .transpose()?;
+        }
+        return print_json(&entry);
+    }
+
     println!("\n{}", instance.id.cyan().bold());
     println!("{}", "─".repeat(50).dimmed());
 
@@ -410,7 +606,7 @@ pub fn handle_get_instance(home: Option<PathBuf>, id: String, include_values: bo
     println!("\n{}", "API Keys:".green().bold());
     if let Some(api_key) = instance.get_api_key() {
         if include_values {
-            println!("  Value: {}", api_key.red());
+            println!("  Value: {}", resolve_secret(api_key)?.red());
         } else {
             println!("  Value: {}", "********".dimmed());
         }
@@ -442,6 +638,21 @@ pub fn handle_get_instance(home: Option<PathBuf>, id: String, include_values: bo
         }
     }
 
+    // Show where the key was discovered
+    if let Some(provenance) = &instance.provenance {
+        println!("{}", "Provenance:".green().bold());
+        if let Some(scanner) = &provenance.scanner {
+            println!("  Scanner: {}", scanner.cyan());
+        }
+        for source in &provenance.source_files {
+            println!("  Source: {}", source);
+        }
+        println!(
+            "  Scanned: {}",
+            provenance.scanned_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
+
     Ok(())
 }
 
@@ -510,10 +721,144 @@ pub fn handle_validate_instances(id: Option<String>, all_errors: bool) -> Result
     Ok(())
 }
 
-/// Original handle_providers function for backward compatibility
-pub fn handle_providers(verbose: bool) -> Result<()> {
-    println!("\n{}", "Available Providers:".green().bold());
+/// Handle the check-instances command - probe each instance live and record the result
+pub fn handle_check_instances(
+    id: Option<String>,
+    tags: Vec<String>,
+    timeout: u64,
+    parallelism: Option<usize>,
+    network: &NetworkOptions,
+    home: Option<&Path>,
+) -> Result<()> {
+    let collection = load_provider_instances(home)?;
+    let mut instances: Vec<ProviderInstance> = match &id {
+        Some(instance_id) => vec![collection
+            .get_instance(instance_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Provider instance with ID '{}' not found", instance_id)
+            })?
+            .clone()],
+        None if !tags.is_empty() => {
+            bulk::select(&config_store(home)?, &instance_selector(Vec::new(), tags))?
+                .into_iter()
+                .filter(|instance| instance.active)
+                .collect()
+        }
+        None => collection
+            .all_instances()
+            .into_iter()
+            .filter(|instance| instance.active)
+            .cloned()
+            .collect(),
+    };
+    if instances.is_empty() {
+        println!("{}", "No active provider instances configured.".yellow());
+        return Ok(());
+    }
+    instances.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // A key that cannot be resolved fails its own check only
+    let mut checks: Vec<HealthCheck> = Vec::new();
+    let mut resolved: Vec<ProviderInstance> = Vec::new();
+    for instance in &instances {
+        match resolve_secret(&instance.api_key) {
+            Ok(api_key) => {
+                let mut instance = instance.clone();
+                instance.api_key = api_key;
+                resolved.push(instance);
+            }
+            Err(e) => checks.push(HealthCheck {
+                instance_id: instance.id.clone(),
+                status: HealthStatus::Error,
+                latency_ms: None,
+                models: Vec::new(),
+                message: Some(format!("could not resolve the API key: {}", e)),
+                checked_at: chrono::Utc::now(),
+            }),
+        }
+    }
+
+    // Probe in parallel, a few requests per provider at a time
+    checks.extend(health::check_instances(
+        &resolved,
+        Duration::from_secs(timeout),
+        network,
+        parallelism,
+    ));
+    checks.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
 
+    println!(
+        "
+{}",
+        "Instance Health:".green().bold()
+    );
+    let width = id_width(checks.iter().map(|check| &check.instance_id));
+    for check in &checks {
+        if check.status == HealthStatus::RateLimited {
+            println!(
+                "  {} {:<width$} {:<12} {}",
+                "…".yellow(),
+                check.instance_id.cyan(),
+                check.status.to_string().yellow(),
+                check.message.as_deref().unwrap_or_default()
+            );
+        } else if check.status == HealthStatus::Ok {
+            println!(
+                "  {} {:<width$} {:<12} {:>6} ms  {} models",
+                "✓".green(),
+                check.instance_id.cyan(),
+                check.status.to_string().green(),
+                check.latency_ms.unwrap_or_default(),
+                check.models.len()
+            );
+        } else {
+            println!(
+                "  {} {:<width$} {:<12} {}",
+                "✗".red(),
+                check.instance_id.cyan(),
+                check.status.to_string().red(),
+                check.message.as_deref().unwrap_or_default()
+            );
+        }
+    }
+
+    // Rate-limited instances weren't checked, so their last result stands
+    config_store(home)?.update_instances(|instance| {
+        let Some(check) = checks.iter().find(|check| {
+            check.instance_id == instance.id && check.status != HealthStatus::RateLimited
+        }) else {
+            return Ok(false);
+        };
+        check.record(instance);
+        Ok(true)
+    })?;
+
+    let rate_limited = checks
+        .iter()
+        .filter(|check| check.status == HealthStatus::RateLimited)
+        .count();
+    if rate_limited > 0 {
+        println!(
+            "\n{}",
+            format!(
+                "{} of {} instances were rate limited and not checked; try again later.",
+                rate_limited,
+                checks.len()
+            )
+            .yellow()
+        );
+    }
+    if checks
+        .iter()
+        .any(|check| check.status != HealthStatus::Ok && check.status != HealthStatus::RateLimited)
+    {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Original handle_providers function for backward compatibility
+pub fn handle_providers(verbose: bool, format: String) -> Result<()> {
     let providers = vec![
         ("openai", "OpenAI API keys"),
         ("anthropic", "Anthropic (Claude) API keys"),
@@ -522,6 +867,30 @@ pub fn handle_providers(verbose: bool) -> Result<()> {
         ("litellm", "LiteLLM configurations"),
         ("groq", "Groq API keys"),
     ];
+    let scanners = vec![
+        ("roo-code", "Roo Code VSCode extension"),
+        ("claude-desktop", "Claude Desktop application"),
+        ("ragit", "Ragit configurations"),
+        ("langchain", "LangChain application configs"),
+        ("gsh", "GSH configurations"),
+    ];
+
+    if wants_json(&format)? {
+        let entries = |list: &[(&str, &str)]| {
+            list.iter()
+                .map(|(name, description)| ProviderEntry {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                })
+                .collect()
+        };
+        return print_json(&ProvidersResponse {
+            providers: entries(&providers),
+            scanners: entries(&scanners),
+        });
+    }
+
+    println!("\n{}", "Available Providers:".green().bold());
 
     for (name, desc) in providers {
         if verbose {
@@ -533,14 +902,6 @@ pub fn handle_providers(verbose: bool) -> Result<()> {
 
     println!("\n{}", "Available Application Scanners:".green().bold());
 
-    let scanners = vec![
-        ("roo-code", "Roo Code VSCode extension"),
-        ("claude-desktop", "Claude Desktop application"),
-        ("ragit", "Ragit configurations"),
-        ("langchain", "LangChain application configs"),
-        ("gsh", "GSH configurations"),
-    ];
-
     for (name, desc) in scanners {
         if verbose {
             println!("  {} - {}", name.cyan(), desc);
@@ -562,10 +923,12 @@ pub fn handle_list_models(
     provider_type: Option<String>,
     tag: Option<String>,
     label: Option<String>,
+    format: String,
 ) -> Result<()> {
+    let json = wants_json(&format)?;
     let instances = load_provider_instances(home.as_deref())?;
 
-    if instances.is_empty() {
+    if instances.is_empty() && !json {
         println!("{}", "No provider instances configured.".yellow());
         println!(
             "{}",
@@ -574,8 +937,6 @@ pub fn handle_list_models(
         return Ok(());
     }
 
-    println!("\n{}", "Configured Models:".green().bold());
-
     // Collect all models from all instances
     let mut all_models: Vec<(&ProviderInstance, &String)> = Vec::new();
     for instance in instances.all_instances() {
@@ -584,7 +945,10 @@ pub fn handle_list_models(
         }
     }
 
-    if all_models.is_empty() {
+    if !json {
+        println!("\n{}", "Configured Models:".green().bold());
+    }
+    if all_models.is_empty() && !json {
         println!("{}", "No models configured.".yellow());
         return Ok(());
     }
@@ -626,6 +990,52 @@ pub fn handle_list_models(
         })
         .collect();
 
+    // Deprecated and archived models, keyed by instance and model
+    let lifecycle: HashMap<(String, String), String> = model_registry(home.as_deref())
+        .lifecycle_warnings(instances.all_instances(), chrono::Local::now().date_naive())
+        .into_iter()
+        .map(|warning| {
+            let message = warning.to_string();
+            ((warning.instance_id, warning.model_id), message)
+        })
+        .collect();
+    let warning_for = |instance: &ProviderInstance, model_id: &str| {
+        lifecycle
+            .get(&(instance.id.clone(), model_id.to_string()))
+            .cloned()
+    };
+
+    if json {
+        filtered_models.sort_by(|(inst_a, model_a), (inst_b, model_b)| {
+            (&inst_a.id, model_a).cmp(&(&inst_b.id, model_b))
+        });
+        let names = |result: Result<Vec<aicred_core::models::Label>>| {
+            result
+                .map(|items| items.into_iter().map(|item| item.name).collect())
+                .unwrap_or_default()
+        };
+        let models = filtered_models
+            .into_iter()
+            .map(|(instance, model_id)| ModelEntry {
+                model_id: model_id.clone(),
+                instance_id: instance.id.clone(),
+                provider_type: instance.provider_type.clone(),
+                tags: names(crate::commands::tags::get_tags_for_target(
+                    &instance.id,
+                    Some(model_id.as_str()),
+                    home.as_deref(),
+                )),
+                labels: names(crate::commands::labels::get_labels_for_target(
+                    &instance.id,
+                    Some(model_id.as_str()),
+                    home.as_deref(),
+                )),
+                warning: warning_for(instance, model_id),
+            })
+            .collect();
+        return print_json(&ModelsResponse { models });
+    }
+
     if filtered_models.is_empty() {
         println!("{}", "No models match the specified criteria.".yellow());
         return Ok(());
@@ -639,6 +1049,9 @@ pub fn handle_list_models(
         for (instance, model_id) in filtered_models {
             println!("{} ({})", model_id.cyan(), instance.provider_type);
             println!("  Instance: {} ({})", instance.id, instance.id);
+            if let Some(warning) = warning_for(instance, model_id) {
+                println!("  {} {}", "Warning:".yellow(), warning);
+            }
 
             // Show tags
             if let Ok(tags) = crate::commands::tags::get_tags_for_target(
@@ -690,6 +1103,10 @@ pub fn handle_list_models(
         );
         println!("{}", "-".repeat(105));
 
+        let warnings: Vec<String> = filtered_models
+            .iter()
+            .filter_map(|(instance, model_id)| warning_for(instance, model_id))
+            .collect();
         for (instance, model_id) in filtered_models {
             // Extract basename from model_id (everything after the last slash)
             let basename = if let Some(last_slash_pos) = model_id.rfind('/') {
@@ -742,6 +1159,12 @@ pub fn handle_list_models(
                 }
             );
         }
+        if !warnings.is_empty() {
+            println!();
+            for warning in warnings {
+                println!("{} {}", "Warning:".yellow(), warning);
+            }
+        }
     }
 
     Ok(())
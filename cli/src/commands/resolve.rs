@@ -0,0 +1,33 @@
+//! Label resolution for scripts and other tools.
+
+use crate::commands::budget::enforce_budgets;
+use crate::commands::export::{render_env, Selected};
+use crate::utils::provider_loader::config_store;
+use aicred_core::resolve::resolve_label;
+use anyhow::Result;
+use colored::*;
+use std::path::Path;
+
+/// Handle the resolve command
+pub fn handle_resolve(label: String, format: String, home: Option<&Path>) -> Result<()> {
+    if !matches!(format.as_str(), "json" | "env") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    let resolved = resolve_label(&config_store(home)?, &label)?;
+    for reason in &resolved.skipped {
+        eprintln!("{} skipped {}", "Note:".yellow(), reason);
+    }
+    enforce_budgets(Some(&resolved.label), Some(&resolved.instance.id), home)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&resolved)?);
+        return Ok(());
+    }
+    let selected = [Selected {
+        instance: &resolved.instance,
+        model: Some(resolved.model.id.clone()),
+        label: Some(resolved.label.clone()),
+    }];
+    print!("{}", render_env(&selected, false)?);
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Key revocation command for the aicred CLI.
+
+use crate::utils::provider_loader::{
+    base_config_store, config_store, load_provider_instances, resolve_secret,
+};
+use aicred_core::fingerprint::{Fingerprinter, HashAlgorithm};
+use aicred_core::revocation::{self, RevocationTarget};
+use aicred_core::rotation::KEY_ID_METADATA;
+use aicred_core::{get_provider, register_builtin_providers, ScanOptions, SecretBackends};
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::Path;
+
+/// Shortest hash prefix accepted to identify a key
+const MIN_HASH_PREFIX: usize = 8;
+
+/// Handle the revoke command
+pub fn handle_revoke(
+    target: String,
+    admin_key: Option<String>,
+    force: bool,
+    hash_algorithm: HashAlgorithm,
+    home: Option<&Path>,
+) -> Result<()> {
+    let fingerprint = Fingerprinter::load(&base_config_store(home)?, hash_algorithm)?;
+    let revocation = find_target(&target, &fingerprint, home)?;
+
+    let registry = register_builtin_providers();
+    let plugin = get_provider(&registry, revocation.provider())
+        .ok_or_else(|| anyhow!("Unknown provider type '{}'", revocation.provider()))?;
+    let admin_key = match admin_key {
+        Some(key) => key,
+        None => {
+            let env = plugin.admin_key_env().ok_or_else(|| {
+                anyhow!(
+                    "{} does not offer a revocation API; revoke the key in its console",
+                    revocation.provider()
+                )
+            })?;
+            std::env::var(env)
+                .map_err(|_| anyhow!("Set {} or pass --admin-key to revoke keys", env))?
+        }
+    };
+    let admin_key = resolve_secret(&admin_key)?;
+
+    if !force {
+        println!(
+            "{}",
+            "Warning: This will permanently revoke the key at the provider."
+                .yellow()
+                .bold()
+        );
+        println!(
+            "Key: {} ({})",
+            &revocation.hash()[..12].cyan(),
+            revocation.provider()
+        );
+        print!("Are you sure? (y/N): ");
+
+        use std::io::{self, Write};
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{}", "Revocation cancelled.".dimmed());
+            return Ok(());
+        }
+    }
+
+    let store = config_store(home)?;
+    let revoked = revocation::revoke(&store, plugin, &admin_key, &revocation)?;
+
+    println!(
+        "{} Revoked {} key {}",
+        "✓".green(),
+        revoked.provider,
+        revoked.key_id.cyan()
+    );
+    if let Some(instance_id) = &revoked.instance_id {
+        println!(
+            "  Instance '{}' marked as revoked and deactivated",
+            instance_id
+        );
+    }
+    println!("  Recorded in {}", revocation::REVOKED_KEYS_FILE);
+    Ok(())
+}
+
+/// Resolve an instance ID or key hash (prefix) to the key to revoke
+fn find_target(
+    target: &str,
+    fingerprint: &Fingerprinter,
+    home: Option<&Path>,
+) -> Result<RevocationTarget> {
+    let instances = load_provider_instances(home)?;
+    if let Some(instance) = instances.get_instance(target) {
+        let mut revocation =
+            RevocationTarget::new(&instance.provider_type, resolve_secret(&instance.api_key)?)
+                .with_instance(&instance.id);
+        if let Some(key_id) = instance.metadata.get(KEY_ID_METADATA) {
+            revocation = revocation.with_key_id(key_id);
+        }
+        return Ok(revocation);
+    }
+
+    let prefix = target.to_ascii_lowercase();
+    if prefix.len() < MIN_HASH_PREFIX || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!(
+            "'{}' is neither an instance ID nor a key hash (at least {} hex characters)",
+            target,
+            MIN_HASH_PREFIX
+        ));
+    }
+
+    // Keys held by instances, skipping references to avoid unlocking every backend
+    let backends = SecretBackends::builtin();
+    for instance in instances.all_instances() {
+        if instance.api_key.is_empty() || backends.is_reference(&instance.api_key) {
+            continue;
+        }
+        if fingerprint
+            .fingerprint(&instance.api_key)
+            .starts_with(&prefix)
+        {
+            return Ok(
+                RevocationTarget::new(&instance.provider_type, &instance.api_key)
+                    .with_instance(&instance.id),
+            );
+        }
+    }
+
+    // Keys found by a scan, as reported by `aicred scan`
+    let mut options = ScanOptions::new()
+        .with_full_values(true)
+        .with_fingerprint(fingerprint.clone());
+    if let Some(home) = home {
+        options = options.with_home_dir(home.to_path_buf());
+    }
+    let result = aicred_core::scan(&options)?;
+    let key = result
+        .keys
+        .iter()
+        .find(|key| key.hash.starts_with(&prefix))
+        .ok_or_else(|| anyhow!("No instance or discovered key matches '{}'", target))?;
+    let value = key
+        .full_value()
+        .ok_or_else(|| anyhow!("The discovered key's value is not available"))?;
+    Ok(RevocationTarget::new(&key.provider, value))
+}
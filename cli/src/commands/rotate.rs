@@ -0,0 +1,95 @@
+//! Key rotation command for the aicred CLI.
+
+use crate::plan::{print_plan, ChangeAction, ChangeKind, ChangePlan, PlanFormat};
+use crate::utils::provider_loader::{config_store, load_provider_instances, resolve_secret};
+use aicred_core::rotation::{KeyRotator, RotateOptions, RotationStep};
+use aicred_core::store::instance_file_name;
+use aicred_core::{get_provider, register_builtin_providers};
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::Path;
+
+/// Handle the rotate command
+pub fn handle_rotate(
+    instance_id: String,
+    revoke_old: bool,
+    admin_key: Option<String>,
+    key_name: Option<String>,
+    dry_run: Option<PlanFormat>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let instances = load_provider_instances(home)?;
+    let instance = instances
+        .get_instance(&instance_id)
+        .ok_or_else(|| anyhow!("Provider instance with ID '{}' not found", instance_id))?;
+
+    let registry = register_builtin_providers();
+    let plugin = get_provider(&registry, &instance.provider_type)
+        .ok_or_else(|| anyhow!("Unknown provider type '{}'", instance.provider_type))?;
+
+    if !plugin.supports_key_creation() {
+        return Err(anyhow!(
+            "{} does not offer a key management API for creating keys; rotate the key in its console and run `aicred instances update --id {} --api-key <new key>`",
+            instance.provider_type,
+            instance_id
+        ));
+    }
+
+    if let Some(format) = dry_run {
+        let mut plan = ChangePlan::new("rotate");
+        let change = plan.add(ChangeAction::Create, ChangeKind::ProviderKey, &instance_id);
+        change.detail(format!("new {} key", instance.provider_type));
+        if let Some(name) = &key_name {
+            change.detail(format!("named {}", name));
+        }
+        plan.add(ChangeAction::Update, ChangeKind::Instance, &instance_id)
+            .at(&config_store(home)?
+                .instances_dir()
+                .join(instance_file_name(instance)))
+            .detail("API key replaced with the new key");
+        if revoke_old {
+            plan.add(ChangeAction::Delete, ChangeKind::ProviderKey, &instance_id)
+                .detail("old key revoked once the new one is verified");
+        }
+        return print_plan(&plan, format);
+    }
+
+    let admin_key = match (admin_key, plugin.admin_key_env()) {
+        (Some(key), _) => key,
+        (None, Some(env)) => std::env::var(env)
+            .map_err(|_| anyhow!("Set {} or pass --admin-key to rotate keys", env))?,
+        (None, None) => return Err(anyhow!("Pass --admin-key to rotate keys")),
+    };
+    let admin_key = resolve_secret(&admin_key)?;
+
+    let mut options = RotateOptions::new().with_revoke_old(revoke_old);
+    if let Some(name) = key_name {
+        options = options.with_key_name(name);
+    }
+
+    println!("Rotating key for {}...", instance_id.cyan());
+    let store = config_store(home)?;
+    KeyRotator::new(&store, plugin).rotate(
+        &instance_id,
+        &admin_key,
+        &options,
+        |step| match step {
+            RotationStep::Created { key_id } => {
+                println!("{} Created new key {}", "✓".green(), key_id.cyan())
+            }
+            RotationStep::Stored { path } => {
+                println!("{} Stored new key in {}", "✓".green(), path.display())
+            }
+            RotationStep::Verified => println!("{} Verified new key", "✓".green()),
+            RotationStep::Revoked { key_id } => {
+                println!("{} Revoked old key {}", "✓".green(), key_id.cyan())
+            }
+            RotationStep::RevokeSkipped { reason } => {
+                println!("{} Old key left active: {}", "-".yellow(), reason)
+            }
+        },
+    )?;
+
+    println!("{}", "Rotation complete.".green().bold());
+    Ok(())
+}
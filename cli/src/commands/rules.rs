@@ -0,0 +1,117 @@
+//! Custom detection rule commands for the aicred CLI.
+
+use crate::utils::provider_loader::config_store;
+use aicred_core::rules::gitleaks::{self, ImportOptions};
+use aicred_core::rules::{DetectionRules, DETECTION_RULES_FILE};
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::Path;
+
+/// Handle the rules import command
+pub fn handle_import_rules(
+    file: String,
+    all_rules: bool,
+    dry_run: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let content = std::fs::read_to_string(&file)
+        .map_err(|e| anyhow!("Failed to read rule file '{}': {}", file, e))?;
+    let report = gitleaks::import(&content, ImportOptions::new().with_all_rules(all_rules))?;
+
+    for rule in &report.rules {
+        println!(
+            "{} {} ({})",
+            "+".green(),
+            rule.id.cyan(),
+            rule.provider.yellow()
+        );
+    }
+    for (id, reason) in &report.skipped {
+        println!("{} {}: {}", "-".dimmed(), id.dimmed(), reason.dimmed());
+    }
+
+    if report.rules.is_empty() {
+        println!("{}", "No rules to import.".yellow());
+        return Ok(());
+    }
+    if dry_run {
+        println!(
+            "\n{}",
+            format!(
+                "Would import {} rule(s), skipping {}.",
+                report.rules.len(),
+                report.skipped.len()
+            )
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    let store = config_store(home)?;
+    let lock = store.lock()?;
+    let mut rules = DetectionRules::load_from_store(&store)?;
+    let imported = report.rules.len();
+    let replaced = rules.merge(report.rules);
+    lock.save_yaml(DETECTION_RULES_FILE, &rules)?;
+
+    println!(
+        "\n{} Imported {} rule(s) ({} replaced), skipped {}. Scans now run {} custom rule(s).",
+        "✓".green(),
+        imported,
+        replaced,
+        report.skipped.len(),
+        rules.rules.len()
+    );
+    Ok(())
+}
+
+/// Handle the rules list command
+pub fn handle_list_rules(home: Option<&Path>) -> Result<()> {
+    let store = config_store(home)?;
+    let rules = DetectionRules::load_from_store(&store)?;
+
+    if rules.rules.is_empty() {
+        println!("{}", "No custom detection rules configured.".yellow());
+        println!(
+            "{}",
+            "Use 'aicred rules import <gitleaks.toml>' to import rules.".dimmed()
+        );
+        return Ok(());
+    }
+
+    println!("\n{}", "Custom Detection Rules:".green().bold());
+    println!(
+        "{:<32} {:<15} {}",
+        "ID".bold(),
+        "Provider".bold(),
+        "Description".bold()
+    );
+    println!("{}", "-".repeat(80));
+    for rule in &rules.rules {
+        println!(
+            "{:<32} {:<15} {}",
+            rule.id.cyan(),
+            rule.provider.yellow(),
+            rule.description.as_deref().unwrap_or("-")
+        );
+    }
+    println!("\n{}", format!("Total rules: {}", rules.rules.len()).cyan());
+    Ok(())
+}
+
+/// Handle the rules remove command
+pub fn handle_remove_rule(id: String, home: Option<&Path>) -> Result<()> {
+    let store = config_store(home)?;
+    let lock = store.lock()?;
+    let mut rules = DetectionRules::load_from_store(&store)?;
+
+    let before = rules.rules.len();
+    rules.rules.retain(|rule| rule.id != id);
+    if rules.rules.len() == before {
+        return Err(anyhow!("Detection rule '{}' not found", id));
+    }
+    lock.save_yaml(DETECTION_RULES_FILE, &rules)?;
+
+    println!("{} Detection rule '{}' removed.", "✓".green(), id.cyan());
+    Ok(())
+}
@@ -1,12 +1,20 @@
-use aicred_core::models::{Model, ProviderInstance};
-use aicred_core::{scan, DiscoveredCredential, ScanOptions};
+use crate::utils::provider_loader::{base_config_store, config_store};
+use aicred_core::audit::{AuditLog, AuditRecord};
+use aicred_core::discovery::ids::{key_instance_id, unique_instance_id};
+use aicred_core::fingerprint::{Fingerprinter, HashAlgorithm};
+use aicred_core::history::{ScanHistory, ScanRecord};
+use aicred_core::http::NetworkOptions;
+use aicred_core::models::{Model, Provenance, ProviderInstance, ValueType};
+use aicred_core::notifications::{Event, Notifier};
+use aicred_core::policy::{Policy, PolicyAction, PolicyReport};
+use aicred_core::rules::DetectionRules;
+use aicred_core::{
+    scan, Baseline, DiscoveredCredential, RedactionMode, RiskLevel, ScanOptions, ScannerContext,
+    SecretBackends,
+};
 use anyhow::Result;
 use colored::*;
-use sha2::{Digest, Sha256};
-use std::fs::OpenOptions;
-use std::io::Write;
-#[cfg(unix)]
-use std::os::unix::fs::OpenOptionsExt;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Sanitizes a provider name to prevent path traversal and OS issues
@@ -52,21 +60,99 @@ fn sanitize_provider_name(name: &str) -> String {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn handle_scan(
-    home: Option<String>,
-    format: String,
-    include_values: bool,
-    only: Option<String>,
-    exclude: Option<String>,
-    max_bytes_per_file: usize,
-    dry_run: bool,
-    audit_log: Option<String>,
-    verbose: bool,
-    update: bool,
-    probe_models: bool,
-    probe_timeout: Option<u64>,
-) -> Result<()> {
+/// Options of the scan command
+#[derive(Debug, Clone)]
+pub struct ScanArgs {
+    /// Home directory to scan, by default the user's
+    pub home: Option<String>,
+    /// Output format (json, ndjson, table, summary, csv, markdown)
+    pub format: String,
+    /// How key values are redacted
+    pub redaction: RedactionMode,
+    /// How the hash of each finding is computed
+    pub hash_algorithm: HashAlgorithm,
+    /// Only scan these providers (comma-separated)
+    pub only: Option<String>,
+    /// Skip these providers (comma-separated)
+    pub exclude: Option<String>,
+    /// Only report these value types (comma-separated)
+    pub only_types: Option<String>,
+    /// Settings of each scanner from `config.yaml`
+    pub scanner_settings: HashMap<String, ScannerContext>,
+    /// Network settings of requests to provider APIs
+    pub network: NetworkOptions,
+    /// Maximum file size to read (in bytes)
+    pub max_bytes_per_file: usize,
+    /// Show what would be scanned without reading any file
+    pub dry_run: bool,
+    /// Append a JSON Lines audit record of the scan to this file
+    pub audit_log: Option<String>,
+    /// Hash chain the audit records
+    pub audit_chain: bool,
+    /// Show the discovered keys
+    pub verbose: bool,
+    /// Write the discovered keys to the instance store
+    pub update: bool,
+    /// Probe discovered instances for their models
+    pub probe_models: bool,
+    /// Timeout for model probing in seconds (default: 30)
+    pub probe_timeout: Option<u64>,
+    /// Record the hashes of all findings to this baseline file
+    pub write_baseline: Option<String>,
+    /// Only report findings not in this baseline file
+    pub baseline: Option<String>,
+    /// Evaluate findings against this YAML policy file
+    pub policy: Option<String>,
+    /// Only report findings at or above this risk level
+    pub min_risk: Option<String>,
+    /// Sort findings by field (risk, confidence, provider)
+    pub sort: Option<String>,
+    /// Only report keys not rotated in at least this many days
+    pub stale_days: Option<u32>,
+    /// Additional directories to scan alongside the home directory
+    pub roots: Vec<String>,
+    /// Also scan .zip/.tar/.tar.gz backups
+    pub scan_archives: bool,
+    /// Don't record the scan in the history
+    pub no_history: bool,
+    /// Post the scan to the notification sinks
+    pub notify: bool,
+    /// Make no network calls
+    pub offline: bool,
+}
+
+pub fn handle_scan(args: ScanArgs) -> Result<()> {
+    let ScanArgs {
+        home,
+        format,
+        redaction,
+        hash_algorithm,
+        only,
+        exclude,
+        only_types,
+        scanner_settings,
+        network,
+        max_bytes_per_file,
+        dry_run,
+        audit_log,
+        audit_chain,
+        verbose,
+        update,
+        probe_models,
+        probe_timeout,
+        write_baseline,
+        baseline,
+        policy,
+        min_risk,
+        sort,
+        stale_days,
+        roots,
+        scan_archives,
+        no_history,
+        notify,
+        offline,
+    } = args;
+
     // Determine home directory
     let home_dir = match home {
         Some(h) => PathBuf::from(h),
@@ -77,32 +163,156 @@ pub fn handle_scan(
     // Parse provider filters
     let only_providers = only.map(|s| s.split(',').map(String::from).collect());
     let exclude_providers = exclude.map(|s| s.split(',').map(String::from).collect());
+    let only_value_types = only_types
+        .map(|s| {
+            s.split(',')
+                .map(|name| name.parse::<ValueType>().map_err(|e| anyhow::anyhow!(e)))
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
 
     // When --update is specified, we MUST include full values to write them to config files
     // Otherwise config files will contain redacted placeholders
-    let include_full_values = include_values || update;
+    let redaction = if update {
+        RedactionMode::None
+    } else {
+        redaction
+    };
 
     // Create scan options
-    let options = ScanOptions {
+    let mut options = ScanOptions {
         home_dir: Some(home_dir.clone()),
-        include_full_values,
+        redaction,
         max_file_size: max_bytes_per_file,
         only_providers,
         exclude_providers,
+        only_value_types,
+        scanner_settings,
+        network,
+        offline,
         probe_models,
         probe_timeout_secs: probe_timeout.unwrap_or(30),
+        extra_roots: roots.iter().map(PathBuf::from).collect(),
+        scan_archives,
+        detection_rules: DetectionRules::load_from_store(&config_store(Some(&home_dir))?)?.rules,
+        ..ScanOptions::default()
     };
 
+    for root in &options.extra_roots {
+        let is_archive = scan_archives
+            && root.is_file()
+            && aicred_core::archive::ArchiveKind::from_path(root).is_some();
+        if !root.is_dir() && !is_archive {
+            anyhow::bail!("Scan root is not a directory: {}", root.display());
+        }
+    }
+
     if dry_run {
         println!("{}", "DRY RUN MODE - No files will be read".yellow().bold());
         // Show what would be scanned
         println!("Would scan directory: {}", home_dir.display());
+        for root in &options.extra_roots {
+            println!("Would scan directory: {}", root.display());
+        }
         return Ok(());
     }
 
-    // Perform scan
-    println!("{}", "Scanning for GenAI credentials...".cyan().bold());
-    let result = scan(&options)?;
+    // Loaded after the dry run, which must not create the salt file
+    options.fingerprint =
+        Fingerprinter::load(&base_config_store(Some(&home_dir))?, hash_algorithm)?;
+
+    // Load the baseline up front so a bad path fails before scanning
+    let baseline = baseline
+        .map(|path| Baseline::load(std::path::Path::new(&path)))
+        .transpose()?;
+    let policy = policy
+        .map(|path| Policy::load(std::path::Path::new(&path)))
+        .transpose()?;
+    let min_risk = min_risk
+        .map(|level| level.parse::<RiskLevel>().map_err(|e| anyhow::anyhow!(e)))
+        .transpose()?;
+    if let Some(ref field) = sort {
+        if !matches!(field.as_str(), "risk" | "confidence" | "provider") {
+            anyhow::bail!(
+                "Unknown sort field: {} (expected risk, confidence or provider)",
+                field
+            );
+        }
+    }
+
+    // Perform scan, keeping machine-readable and report formats free of
+    // progress output
+    if format == "table" {
+        println!("{}", "Scanning for GenAI credentials...".cyan().bold());
+    }
+    let mut result = scan(&options)?;
+
+    // Record unfiltered scans so keys only look gone when they really are
+    let filtered = options.only_providers.is_some()
+        || options.exclude_providers.is_some()
+        || options.only_value_types.is_some();
+    if !no_history && !filtered {
+        let history = ScanHistory::for_home(&home_dir);
+        if let Err(e) = history.append(&ScanRecord::from_scan_result(&result)) {
+            tracing::warn!("Failed to record scan history: {}", e);
+        }
+    }
+
+    // Record the baseline from the full, unfiltered result
+    if let Some(path) = write_baseline {
+        let new_baseline = Baseline::from_scan_result(&result);
+        new_baseline.save(std::path::Path::new(&path))?;
+        println!(
+            "{}",
+            format!(
+                "Wrote baseline with {} finding(s) to {}",
+                new_baseline.len(),
+                path
+            )
+            .green()
+        );
+    }
+
+    // Suppress findings already recorded in the baseline
+    if let Some(ref baseline) = baseline {
+        let suppressed = baseline.filter_scan_result(&mut result);
+        result
+            .metadata
+            .get_or_insert_with(Default::default)
+            .insert("baseline_suppressed".to_string(), suppressed.into());
+    }
+
+    // Flag findings that were revoked through `aicred revoke`
+    let revoked = aicred_core::revocation::load_revoked_keys(&config_store(Some(&home_dir))?)?;
+    for key in &mut result.keys {
+        if aicred_core::revocation::is_revoked(&revoked, &key.hash) {
+            let metadata = key.metadata.get_or_insert_with(|| serde_json::json!({}));
+            if let Some(map) = metadata.as_object_mut() {
+                map.insert("revoked".to_string(), true.into());
+            }
+        }
+    }
+
+    // Filter and order findings by risk and age before reporting
+    if let Some(min_risk) = min_risk {
+        retain_findings(&mut result, |key| key.risk >= min_risk);
+    }
+    if let Some(days) = stale_days {
+        let now = chrono::Utc::now();
+        retain_findings(&mut result, |key| key.is_stale(days, now));
+    }
+    if let Some(ref field) = sort {
+        sort_findings(&mut result, field);
+    }
+
+    // Evaluate policy rules against the (baseline-filtered) findings
+    let policy_report = policy.map(|policy| policy.evaluate(&result, &home_dir));
+    if let Some(ref report) = policy_report {
+        result.metadata.get_or_insert_with(Default::default).insert(
+            "policy_violations".to_string(),
+            serde_json::to_value(&report.violations)?,
+        );
+    }
 
     // Output results based on format
     match format.as_str() {
@@ -110,27 +320,171 @@ pub fn handle_scan(
         "ndjson" => crate::output::ndjson::output_ndjson(&result, verbose)?,
         "table" => crate::output::table::output_table(&result, verbose)?,
         "summary" => crate::output::summary::output_summary(&result, verbose)?,
+        "csv" => crate::output::csv::output_csv(&result, verbose)?,
+        "markdown" | "md" => crate::output::markdown::output_markdown(&result, verbose)?,
         _ => anyhow::bail!("Unknown format: {}", format),
     }
 
-    // Write audit log if requested
+    // Append an audit record if requested
     if let Some(log_path) = audit_log {
-        write_audit_log(&log_path, &result)?;
+        let record =
+            AuditRecord::for_scan(&result, &options).with_option("format", format.as_str());
+        AuditLog::new(log_path)
+            .with_hash_chain(audit_chain)
+            .append(record)?;
     }
 
-    // Exit code: 0 if keys found, 1 if none found
-    if result.keys.is_empty() && result.config_instances.is_empty() {
-        std::process::exit(1);
+    if let Some(ref report) = policy_report {
+        print_policy_report(report);
     }
 
+    if notify {
+        send_notifications(&home_dir, &result, policy_report.as_ref());
+    }
+
+    let nothing_found = result.keys.is_empty() && result.config_instances.is_empty();
+
     // Update YAML configuration file if requested
-    if update {
+    if update && !nothing_found {
         update_yaml_config(&result, &home_dir)?;
     }
 
+    // Exit code: with --policy, 1 if any fail rule matched; with --baseline, 1 if any new
+    // keys were found; otherwise 0 if keys found, 1 if none found
+    let failed = match (&policy_report, &baseline) {
+        (Some(report), _) => report.has_failures(),
+        (None, Some(_)) => !result.keys.is_empty(),
+        (None, None) => nothing_found,
+    };
+    if failed {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
 
+/// Keeps only keys matching `keep`, along with the provider instances they back
+fn retain_findings<F>(result: &mut aicred_core::ScanResult, keep: F)
+where
+    F: Fn(&DiscoveredCredential) -> bool,
+{
+    result.keys.retain(|key| keep(key));
+
+    let mut config_instances = std::mem::take(&mut result.config_instances);
+    for instance in &mut config_instances {
+        let source = instance.config_path_string();
+        let unbacked: Vec<String> = instance
+            .provider_instances()
+            .into_iter()
+            .filter(|pi| {
+                result
+                    .keys_for_source(&pi.provider_type, &source)
+                    .next()
+                    .is_none()
+            })
+            .map(|pi| pi.id.clone())
+            .collect();
+        for id in unbacked {
+            instance.remove_provider_instance(&id);
+        }
+    }
+    config_instances.retain(|instance| !instance.provider_instances.is_empty());
+    result.config_instances = config_instances;
+}
+
+/// Orders keys and config instances by the given field
+fn sort_findings(result: &mut aicred_core::ScanResult, field: &str) {
+    match field {
+        "risk" => {
+            result.sort_by_risk();
+            let mut config_instances = std::mem::take(&mut result.config_instances);
+            config_instances.sort_by_cached_key(|instance| {
+                let source = instance.config_path_string();
+                std::cmp::Reverse(
+                    instance
+                        .provider_instances()
+                        .iter()
+                        .filter_map(|pi| result.risk_for_source(&pi.provider_type, &source))
+                        .max(),
+                )
+            });
+            result.config_instances = config_instances;
+        }
+        "confidence" => result
+            .keys
+            .sort_by_key(|key| std::cmp::Reverse(key.confidence)),
+        "provider" => {
+            result.keys.sort_by(|a, b| a.provider.cmp(&b.provider));
+            result
+                .config_instances
+                .sort_by(|a, b| a.app_name.cmp(&b.app_name));
+        }
+        _ => {}
+    }
+}
+
+/// Posts the scan and any policy violations to the configured notification sinks
+fn send_notifications(
+    home_dir: &std::path::Path,
+    result: &aicred_core::ScanResult,
+    policy_report: Option<&PolicyReport>,
+) {
+    let notifier = match config_store(Some(home_dir))
+        .and_then(|store| Ok(Notifier::load_from_store(&store)?))
+    {
+        Ok(notifier) => notifier,
+        Err(e) => {
+            eprintln!("{} {}", "Failed to load notification settings:".yellow(), e);
+            return;
+        }
+    };
+    if notifier.is_empty() {
+        return;
+    }
+
+    let mut events = vec![Event::scan_completed(result)];
+    if let Some(report) = policy_report.filter(|r| !r.violations.is_empty()) {
+        events.push(Event::policy_violations(result, &report.violations));
+    }
+    for event in &events {
+        for (sink, e) in notifier.notify(event) {
+            eprintln!("{} {}: {}", "Failed to notify".yellow(), sink, e);
+        }
+    }
+}
+
+/// Prints policy violations to stderr so structured stdout output stays parseable
+fn print_policy_report(report: &PolicyReport) {
+    if report.violations.is_empty() {
+        eprintln!("{}", "Policy check passed".green());
+        return;
+    }
+
+    for (action, label) in [(PolicyAction::Fail, "FAIL"), (PolicyAction::Warn, "WARN")] {
+        for violation in report.by_action(action) {
+            let label = if action == PolicyAction::Fail {
+                label.red().bold()
+            } else {
+                label.yellow().bold()
+            };
+            eprintln!(
+                "{} [{}] {} key in {}",
+                label, violation.rule, violation.provider, violation.source_file
+            );
+            if let Some(description) = &violation.description {
+                eprintln!("       {}", description.dimmed());
+            }
+        }
+    }
+
+    let failures = report.by_action(PolicyAction::Fail).len();
+    let warnings = report.by_action(PolicyAction::Warn).len();
+    eprintln!(
+        "Policy check: {} failure(s), {} warning(s)",
+        failures, warnings
+    );
+}
+
 /// Helper function to create a full Model struct with capabilities based on model ID
 fn create_full_model(model_id: &str) -> Model {
     let capabilities = aicred_core::models::ModelCapabilities {
@@ -185,7 +539,7 @@ fn load_model_config(model_path: &std::path::Path) -> Result<Model> {
 }
 
 /// Helper function to get default base URLs for providers
-fn get_default_base_url(provider_name: &str) -> String {
+pub(crate) fn get_default_base_url(provider_name: &str) -> String {
     match provider_name.to_lowercase().as_str() {
         "openai" => "https://api.openai.com/v1".to_string(),
         "anthropic" => "https://api.anthropic.com".to_string(),
@@ -206,23 +560,38 @@ fn hash_value(value: &str) -> String {
     hex::encode(hasher.finalize())
 }
 
+/// Whether a stored instance belongs to another key than `key`, so its ID
+/// can't be reused for it
+fn held_by_other_key(stored: &ProviderInstance, key: &DiscoveredCredential) -> bool {
+    if !stored.provider_type.eq_ignore_ascii_case(&key.provider) {
+        return true;
+    }
+    let stored_key = stored.api_key.as_str();
+    let plain = !stored_key.is_empty()
+        && !stored_key.starts_with("REDACTED_")
+        && !aicred_core::crypto::is_encrypted(stored_key)
+        && !SecretBackends::builtin().is_reference(stored_key);
+    plain && key.full_value().is_some_and(|value| value != stored_key)
+}
+
 /// Updates or creates the YAML configuration files with discovered providers and keys
 /// NEW APPROACH: One instance per API key, using API key as instance ID
 fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Path) -> Result<()> {
-    let config_dir = home_dir
-        .join(".config")
-        .join("aicred")
-        .join("inference_services");
+    let store = config_store(Some(home_dir))?;
+    let config_dir = store.ensure_dir(aicred_core::store::INSTANCES_DIR)?;
 
-    // Create directory if it doesn't exist
-    std::fs::create_dir_all(&config_dir)?;
+    // Hold the store lock for the whole update so concurrent runs don't interleave
+    let lock = store.lock()?;
+    let stored = store.load_instances()?;
 
     // Debug: Print the actual directory being used
     tracing::info!("Using config directory: {}", config_dir.display());
 
+    // Model IDs are stored as the registry knows them, so lists dedupe
+    let registry = aicred_core::ModelRegistry::for_home(home_dir);
+
     // Create models directory
-    let models_dir = config_dir.parent().unwrap().join("models");
-    std::fs::create_dir_all(&models_dir)?;
+    let models_dir = store.ensure_dir("models")?;
 
     let _now = chrono::Utc::now();
 
@@ -348,16 +717,27 @@ fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Pa
             };
 
             for primary_key in primary_keys {
-                // Generate SHA-256 hash of the primary key content for consistent instance ID
-                let mut hasher = Sha256::new();
-                hasher.update(primary_key.hash.as_bytes());
-                let hash_result = hasher.finalize();
-                let full_hash = format!("{:x}", hash_result);
-                let instance_id = full_hash[..4].to_string();
-
-                // Create a filename using provider name and first 4 chars of hash
-                let sanitized_provider = sanitize_provider_name(&provider_name);
-                let filename = format!("{}-{}.yaml", sanitized_provider, &instance_id[..4]);
+                // Reuse the key's ID unless a stored instance of another key holds it
+                let instance_id = unique_instance_id(&key_instance_id(primary_key), |id| {
+                    stored
+                        .get_instance(id)
+                        .is_some_and(|instance| held_by_other_key(instance, primary_key))
+                });
+
+                // IDs of the scheme are safe file names: a provider slug and hex digits
+                let filename = format!("{instance_id}.yaml");
+
+                // Keep where the key was first found, adding any new files
+                let provenance = match stored
+                    .get_instance(&instance_id)
+                    .and_then(|instance| instance.provenance.clone())
+                {
+                    Some(mut provenance) => {
+                        provenance.add_sources(primary_key);
+                        provenance
+                    }
+                    None => Provenance::from_key(primary_key, result.scan_started_at),
+                };
                 let instance_file_path = config_dir.join(&filename);
 
                 tracing::debug!(
@@ -386,7 +766,8 @@ fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Pa
                         get_default_base_url(&provider_name),
                         String::new(), // Empty API key initially
                         Vec::new(),    // Empty models initially
-                    );
+                    )
+                    .with_provenance(provenance.clone());
 
                     // Check if we have probed models for this source
                     let source_key = (provider_name.clone(), primary_key.source_file.clone());
@@ -595,12 +976,8 @@ fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Pa
                     tracing::debug!("Instance models before save: {:?}", instance.models);
 
                     // Save the instance configuration
-                    let yaml_content = serde_yaml::to_string(&instance)?;
-                    tracing::debug!(
-                        "Generated YAML content length: {} chars",
-                        yaml_content.len()
-                    );
-                    std::fs::write(&instance_file_path, yaml_content)?;
+                    instance.canonicalize_models(&registry);
+                    lock.save_instance_as(&filename, &instance)?;
 
                     tracing::debug!("Saved instance config to: {}", instance_file_path.display());
                 } else {
@@ -622,7 +999,8 @@ fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Pa
                             get_default_base_url(&provider_name),
                             String::new(), // Empty API key initially
                             Vec::new(),    // Empty models initially
-                        );
+                        )
+                        .with_provenance(provenance);
 
                         // Check if we have probed models for this provider and instance
                         let source_key = (provider_name.clone(), primary_key.source_file.clone());
@@ -705,8 +1083,8 @@ fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Pa
                     }
 
                     // Save the instance configuration
-                    let yaml_content = serde_yaml::to_string(&instance)?;
-                    std::fs::write(&instance_file_path, yaml_content)?;
+                    instance.canonicalize_models(&registry);
+                    lock.save_instance_as(&filename, &instance)?;
 
                     tracing::debug!("Saved instance config to: {}", instance_file_path.display());
                 }
@@ -723,60 +1101,3 @@ fn update_yaml_config(result: &aicred_core::ScanResult, home_dir: &std::path::Pa
 
     Ok(())
 }
-
-fn write_audit_log(log_path: &str, result: &aicred_core::ScanResult) -> Result<()> {
-    #[cfg(unix)]
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .mode(0o600)
-        .open(log_path)?;
-    #[cfg(not(unix))]
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(log_path)?;
-    writeln!(file, "AICred Audit Log")?;
-    writeln!(file, "==================")?;
-    writeln!(file, "Scan Date: {}", result.scan_completed_at)?;
-    writeln!(file, "Home Directory: {}", result.home_directory)?;
-    writeln!(
-        file,
-        "Providers Scanned: {}",
-        result.providers_scanned.join(", ")
-    )?;
-    writeln!(file, "Total Keys Found: {}", result.keys.len())?;
-    writeln!(
-        file,
-        "Total Config Instances: {}",
-        result.config_instances.len()
-    )?;
-    writeln!(file)?;
-
-    if !result.keys.is_empty() {
-        writeln!(file, "Discovered Keys:")?;
-        for key in &result.keys {
-            writeln!(
-                file,
-                "  - {}: {} ({} - confidence: {})",
-                key.provider, key.value_type, key.source_file, key.confidence
-            )?;
-        }
-    }
-
-    if !result.config_instances.is_empty() {
-        writeln!(file, "\nConfig Instances:")?;
-        for instance in &result.config_instances {
-            writeln!(
-                file,
-                "  - {}: {}",
-                instance.app_name,
-                instance.config_path.display()
-            )?;
-        }
-    }
-
-    Ok(())
-}
@@ -0,0 +1,142 @@
+//! Shell integration - prints statements that set provider environment
+//! variables in the calling shell, e.g. `eval "$(aicred setenv)"`.
+
+use crate::commands::export::{provider_env_vars, select_instances};
+use crate::commands::labels::load_label_assignments_with_home;
+use crate::commands::wrap::resolve_scanner_env;
+use crate::utils::provider_loader::load_provider_instances;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Shells `setenv` can generate statements for
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+/// Shell to generate for when none is given, from the basename of `$SHELL`
+pub fn default_shell() -> &'static str {
+    let shell = std::env::var("SHELL").unwrap_or_default();
+    match Path::new(&shell).file_name().and_then(|name| name.to_str()) {
+        Some("zsh") => "zsh",
+        Some("fish") => "fish",
+        Some("pwsh" | "powershell" | "pwsh.exe" | "powershell.exe") => "powershell",
+        Some(_) => "bash",
+        None if cfg!(windows) => "powershell",
+        None => "bash",
+    }
+}
+
+/// Quotes a value so the shell reads it back verbatim
+fn quote(value: &str, shell: &str) -> String {
+    match shell {
+        // Nothing is special inside POSIX single quotes except the quote itself
+        "bash" | "zsh" => format!("'{}'", value.replace('\'', "'\\''")),
+        // Fish single quotes honour \' and \\
+        "fish" => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        // PowerShell also treats typographic single quotes as quote characters
+        "powershell" => {
+            let mut quoted = String::from("'");
+            for c in value.chars() {
+                if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+                    quoted.push(c);
+                }
+                quoted.push(c);
+            }
+            quoted.push('\'');
+            quoted
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Renders statements setting each variable in the given shell, sorted by name
+pub fn render_shell_exports(vars: &BTreeMap<String, String>, shell: &str) -> Result<String> {
+    let mut script = String::new();
+    for (name, value) in vars {
+        let line = match shell {
+            "bash" | "zsh" => format!("export {}={}", name, quote(value, shell)),
+            "fish" => format!("set -gx {} {}", name, quote(value, shell)),
+            "powershell" => format!("$env:{} = {}", name, quote(value, shell)),
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported shell: {}. Supported shells: {}",
+                    shell,
+                    SHELLS.join(", ")
+                ))
+            }
+        };
+        script.push_str(&line);
+        script.push('\n');
+    }
+    Ok(script)
+}
+
+/// Prints shell statements for the variables, or a masked listing on a dry run
+pub fn print_shell_exports(
+    vars: &BTreeMap<String, String>,
+    shell: &str,
+    dry_run: bool,
+) -> Result<()> {
+    // Handle dry run mode
+    if dry_run {
+        println!("Environment variables that would be exported:");
+        for (key, value) in vars {
+            // Mask sensitive values (API keys)
+            let display_value = if key.contains("API_KEY") && !value.is_empty() {
+                // Only show first 4 and last 4 chars if value is long enough
+                if value.len() >= 8 {
+                    format!("{}...{}", &value[..4], &value[value.len() - 4..])
+                } else {
+                    // For short values, mask completely for security
+                    "****".to_string()
+                }
+            } else {
+                value.clone()
+            };
+            println!("  {}={}", key, display_value);
+        }
+        return Ok(());
+    }
+
+    print!("{}", render_shell_exports(vars, shell)?);
+    Ok(())
+}
+
+/// Handle the setenv command
+pub fn handle_setenv(
+    shell: Option<String>,
+    label: Option<String>,
+    instance_ids: Vec<String>,
+    scanner_names: Option<Vec<String>>,
+    dry_run: bool,
+    home: Option<&Path>,
+) -> Result<()> {
+    let shell = shell.unwrap_or_else(|| default_shell().to_string());
+    if !SHELLS.contains(&shell.as_str()) {
+        return Err(anyhow!(
+            "Unsupported shell: {}. Supported shells: {}",
+            shell,
+            SHELLS.join(", ")
+        ));
+    }
+
+    // Without an explicit selection, set what the scanner's tool expects
+    if label.is_none() && instance_ids.is_empty() {
+        let resolution = resolve_scanner_env(scanner_names, dry_run, home)?;
+        let vars = resolution.variables.into_iter().collect();
+        return print_shell_exports(&vars, &shell, dry_run);
+    }
+    let collection = load_provider_instances(home)?;
+    let assignments = load_label_assignments_with_home(home)?;
+    let selected = select_instances(
+        &collection,
+        &assignments,
+        label.as_deref(),
+        &instance_ids,
+        home,
+    )?;
+    let vars: BTreeMap<String, String> = provider_env_vars(&selected, false)?
+        .into_iter()
+        .flat_map(|(_, vars)| vars)
+        .collect();
+    print_shell_exports(&vars, &shell, dry_run)
+}
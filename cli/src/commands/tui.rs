@@ -0,0 +1,683 @@
+//! Interactive terminal dashboard for the aicred CLI.
+//!
+//! Shows provider instances, their models, the tags and labels pointing at
+//! them, and the findings of the latest scan. Instances can be activated or
+//! deactivated, labels assigned and rescans triggered without leaving it.
+
+use crate::commands::daemon::run_scan;
+use crate::commands::labels::{assign_label, load_label_assignments_with_home};
+use crate::commands::tags::load_tag_assignments;
+use crate::utils::provider_loader::{config_store, load_provider_instances};
+use aicred_core::history::{ScanHistory, ScanRecord};
+use aicred_core::models::{LabelAssignment, LabelTarget, ProviderInstance, RiskLevel};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// Panes that take the keyboard focus, in Tab order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Instances,
+    Models,
+    Findings,
+}
+
+impl Pane {
+    const fn next(self) -> Self {
+        match self {
+            Self::Instances => Self::Models,
+            Self::Models => Self::Findings,
+            Self::Findings => Self::Instances,
+        }
+    }
+
+    const fn previous(self) -> Self {
+        match self {
+            Self::Instances => Self::Findings,
+            Self::Models => Self::Instances,
+            Self::Findings => Self::Models,
+        }
+    }
+}
+
+/// Dashboard state
+struct App {
+    home: Option<PathBuf>,
+    home_dir: PathBuf,
+    instances: Vec<ProviderInstance>,
+    labels: Vec<LabelAssignment>,
+    tags: Vec<LabelAssignment>,
+    findings: Option<ScanRecord>,
+    focus: Pane,
+    instance_state: ListState,
+    model_state: ListState,
+    finding_state: TableState,
+    /// Label name being typed, while assigning a label
+    label_input: Option<String>,
+    status: String,
+    /// Pending background rescan
+    scan: Option<Receiver<Result<ScanRecord, String>>>,
+    quit: bool,
+}
+
+impl App {
+    fn load(home: Option<&Path>) -> Result<Self> {
+        let home_dir = match home {
+            Some(h) => h.to_path_buf(),
+            None => dirs_next::home_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?,
+        };
+        let history = match home {
+            Some(home) => ScanHistory::for_home(home),
+            None => ScanHistory::default_location()?,
+        };
+        let mut app = Self {
+            home: home.map(Path::to_path_buf),
+            home_dir,
+            instances: Vec::new(),
+            labels: Vec::new(),
+            tags: Vec::new(),
+            findings: history.load()?.pop(),
+            focus: Pane::Instances,
+            instance_state: ListState::default(),
+            model_state: ListState::default(),
+            finding_state: TableState::default(),
+            label_input: None,
+            status: String::new(),
+            scan: None,
+            quit: false,
+        };
+        app.reload()?;
+        if app.findings.is_none() {
+            app.status = "No scan recorded yet; press r to scan".to_string();
+        }
+        Ok(app)
+    }
+
+    /// Re-reads instances, labels and tags from the config store
+    fn reload(&mut self) -> Result<()> {
+        let home = self.home.as_deref();
+        let mut instances: Vec<ProviderInstance> = load_provider_instances(home)?
+            .all_instances()
+            .into_iter()
+            .cloned()
+            .collect();
+        instances.sort_by(|a, b| a.id.cmp(&b.id));
+        self.instances = instances;
+        self.labels = load_label_assignments_with_home(home)?;
+        self.tags = load_tag_assignments(home)?;
+
+        if self.instances.is_empty() {
+            self.instance_state.select(None);
+        } else {
+            let selected = self.instance_state.selected().unwrap_or(0);
+            self.instance_state
+                .select(Some(selected.min(self.instances.len() - 1)));
+        }
+        self.clamp_model();
+        Ok(())
+    }
+
+    fn selected_instance(&self) -> Option<&ProviderInstance> {
+        self.instance_state
+            .selected()
+            .and_then(|index| self.instances.get(index))
+    }
+
+    fn selected_model(&self) -> Option<&String> {
+        let instance = self.selected_instance()?;
+        self.model_state
+            .selected()
+            .and_then(|index| instance.models.get(index))
+    }
+
+    fn clamp_model(&mut self) {
+        let count = self.selected_instance().map_or(0, |i| i.models.len());
+        if count == 0 {
+            self.model_state.select(None);
+        } else {
+            let selected = self.model_state.selected().unwrap_or(0);
+            self.model_state.select(Some(selected.min(count - 1)));
+        }
+    }
+
+    /// Moves the selection of the focused pane by `delta` rows
+    fn move_selection(&mut self, delta: isize) {
+        let (count, selected) = match self.focus {
+            Pane::Instances => (self.instances.len(), self.instance_state.selected()),
+            Pane::Models => (
+                self.selected_instance().map_or(0, |i| i.models.len()),
+                self.model_state.selected(),
+            ),
+            Pane::Findings => (
+                self.findings.as_ref().map_or(0, |f| f.keys.len()),
+                self.finding_state.selected(),
+            ),
+        };
+        if count == 0 {
+            return;
+        }
+        let index = selected
+            .unwrap_or(0)
+            .saturating_add_signed(delta)
+            .min(count - 1);
+        match self.focus {
+            Pane::Instances => {
+                self.instance_state.select(Some(index));
+                self.model_state.select(Some(0));
+                self.clamp_model();
+            }
+            Pane::Models => self.model_state.select(Some(index)),
+            Pane::Findings => self.finding_state.select(Some(index)),
+        }
+    }
+
+    /// Activates or deactivates the selected instance and saves it
+    fn toggle_active(&mut self) -> Result<()> {
+        let Some(index) = self.instance_state.selected() else {
+            return Ok(());
+        };
+        let instance = &mut self.instances[index];
+        instance.active = !instance.active;
+        config_store(self.home.as_deref())?
+            .lock()?
+            .save_instance(instance)?;
+        self.status = format!(
+            "{} '{}'",
+            if instance.active {
+                "Activated"
+            } else {
+                "Deactivated"
+            },
+            instance.id
+        );
+        Ok(())
+    }
+
+    /// Target a new label would point at: the selected model when the
+    /// models pane has focus, otherwise the selected instance
+    fn label_target(&self) -> Option<LabelTarget> {
+        let instance = self.selected_instance()?;
+        match self.selected_model() {
+            Some(model) if self.focus == Pane::Models => Some(LabelTarget::ProviderModel {
+                instance_id: instance.id.clone(),
+                model_id: model.clone(),
+            }),
+            _ => Some(LabelTarget::ProviderInstance {
+                instance_id: instance.id.clone(),
+            }),
+        }
+    }
+
+    fn finish_label(&mut self, name: &str) -> Result<()> {
+        let name = name.trim();
+        let Some(target) = self.label_target() else {
+            return Ok(());
+        };
+        if name.is_empty() {
+            self.status = "Label name cannot be empty".to_string();
+            return Ok(());
+        }
+        let description = target_description(&target);
+        let moved = assign_label(name, target, None, self.home.as_deref())?;
+        self.labels = load_label_assignments_with_home(self.home.as_deref())?;
+        self.status = format!(
+            "Label '{name}' {} {description}",
+            if moved { "moved to" } else { "assigned to" }
+        );
+        Ok(())
+    }
+
+    /// Starts a scan on a background thread
+    fn start_scan(&mut self) {
+        if self.scan.is_some() {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let home_dir = self.home_dir.clone();
+        std::thread::spawn(move || {
+            let result = run_scan(&json!({}), &home_dir)
+                .map(|result| ScanRecord::from_scan_result(&result))
+                .map_err(|error| error.message);
+            let _ = sender.send(result);
+        });
+        self.scan = Some(receiver);
+        self.status = "Scanning...".to_string();
+    }
+
+    /// Picks up the result of a finished background scan
+    fn poll_scan(&mut self) -> Result<()> {
+        let Some(ref receiver) = self.scan else {
+            return Ok(());
+        };
+        let Ok(result) = receiver.try_recv() else {
+            return Ok(());
+        };
+        self.scan = None;
+        match result {
+            Ok(record) => {
+                let history = match self.home.as_deref() {
+                    Some(home) => ScanHistory::for_home(home),
+                    None => ScanHistory::default_location()?,
+                };
+                history.append(&record)?;
+                self.status = format!("Scan found {} key(s)", record.keys.len());
+                self.finding_state
+                    .select((!record.keys.is_empty()).then_some(0));
+                self.findings = Some(record);
+            }
+            Err(message) => self.status = format!("Scan failed: {message}"),
+        }
+        Ok(())
+    }
+
+    fn on_key(&mut self, key: KeyEvent) -> Result<()> {
+        if let Some(ref mut input) = self.label_input {
+            match key.code {
+                KeyCode::Enter => {
+                    let name = std::mem::take(input);
+                    self.label_input = None;
+                    self.finish_label(&name)?;
+                }
+                KeyCode::Esc => self.label_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.quit = true;
+            }
+            KeyCode::Tab => self.focus = self.focus.next(),
+            KeyCode::BackTab => self.focus = self.focus.previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Char('a') => self.toggle_active()?,
+            KeyCode::Char('l') if self.selected_instance().is_some() => {
+                self.label_input = Some(String::new());
+            }
+            KeyCode::Char('r') => self.start_scan(),
+            KeyCode::Char('R') => {
+                self.reload()?;
+                self.status = "Reloaded configuration".to_string();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.render(frame))?;
+            if event::poll(Duration::from_millis(250))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        // Keep the dashboard up when an action fails
+                        if let Err(error) = self.on_key(key) {
+                            self.status = format!("Error: {error}");
+                        }
+                    }
+                }
+            }
+            if let Err(error) = self.poll_scan() {
+                self.status = format!("Error: {error}");
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&self, title: &str, pane: Option<Pane>) -> Block<'static> {
+        let block = Block::bordered().title(format!(" {title} "));
+        if pane == Some(self.focus) {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [header, body, findings, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(8),
+            Constraint::Percentage(35),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [instances, details] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+        let [models, labels] =
+            Layout::vertical([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(details);
+
+        let active = self.instances.iter().filter(|i| i.active).count();
+        frame.render_widget(
+            Line::from(vec![
+                " aicred ".bold().reversed(),
+                format!(
+                    "  {} instance(s), {} active, {} label(s)",
+                    self.instances.len(),
+                    active,
+                    self.labels.len()
+                )
+                .into(),
+                format!("   {}", self.status).cyan(),
+            ]),
+            header,
+        );
+        self.render_instances(frame, instances);
+        self.render_models(frame, models);
+        self.render_labels(frame, labels);
+        self.render_findings(frame, findings);
+        self.render_footer(frame, footer);
+    }
+
+    fn render_instances(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .instances
+            .iter()
+            .map(|instance| {
+                let (marker, style) = if instance.active {
+                    ("● ", Style::new().fg(Color::Green))
+                } else {
+                    ("○ ", Style::new().add_modifier(Modifier::DIM))
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, style),
+                    Span::styled(instance.id.clone(), style),
+                    format!(" ({})", instance.provider_type).dim(),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(self.block("Instances", Some(Pane::Instances)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.instance_state);
+    }
+
+    fn render_models(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = match self.selected_instance() {
+            Some(instance) => instance
+                .models
+                .iter()
+                .map(|model| {
+                    let mut spans = vec![Span::raw(model.clone())];
+                    for name in names_for_model(&self.labels, &instance.id, model) {
+                        spans.push(format!(" [{name}]").yellow());
+                    }
+                    for name in names_for_model(&self.tags, &instance.id, model) {
+                        spans.push(format!(" #{name}").magenta());
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let list = List::new(items)
+            .block(self.block("Models", Some(Pane::Models)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.model_state);
+    }
+
+    fn render_labels(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = Vec::new();
+        if let Some(instance) = self.selected_instance() {
+            lines.push(Line::from("Labels".bold()));
+            lines.extend(
+                assignments_for(&self.labels, &instance.id).map(|(name, model)| {
+                    Line::from(vec![
+                        format!("  {name}").yellow(),
+                        format!(" → {}", model.unwrap_or("(instance)")).into(),
+                    ])
+                }),
+            );
+            lines.push(Line::from("Tags".bold()));
+            lines.extend(
+                assignments_for(&self.tags, &instance.id).map(|(name, model)| {
+                    let mut spans = vec![format!("  #{name}").magenta()];
+                    if let Some(model) = model {
+                        spans.push(format!(" on {model}").into());
+                    }
+                    Line::from(spans)
+                }),
+            );
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(self.block("Tags & Labels", None)),
+            area,
+        );
+    }
+
+    fn render_findings(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.findings {
+            Some(ref record) => format!(
+                "Findings (scanned {})",
+                record.scanned_at.format("%Y-%m-%d %H:%M")
+            ),
+            None => "Findings".to_string(),
+        };
+        let rows: Vec<Row> = self
+            .findings
+            .iter()
+            .flat_map(|record| &record.keys)
+            .map(|key| {
+                let color = match key.risk {
+                    RiskLevel::Critical => Color::Red,
+                    RiskLevel::High => Color::LightRed,
+                    RiskLevel::Medium => Color::Yellow,
+                    RiskLevel::Low => Color::Green,
+                };
+                Row::new(vec![
+                    Span::styled(key.risk.to_string(), Style::new().fg(color)),
+                    Span::raw(key.provider.clone()),
+                    Span::raw(key.hash.chars().take(12).collect::<String>()).dim(),
+                    Span::raw(key.sources.join(", ")),
+                ])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(9),
+                Constraint::Length(16),
+                Constraint::Length(13),
+                Constraint::Min(10),
+            ],
+        )
+        .header(Row::new(["Risk", "Provider", "Hash", "Sources"]).bold())
+        .block(self.block(&title, Some(Pane::Findings)))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.finding_state);
+    }
+
+    fn render_footer(&self, frame: &mut Frame, area: Rect) {
+        let line = match self.label_input {
+            Some(ref input) => {
+                let target = self
+                    .label_target()
+                    .map(|target| target_description(&target))
+                    .unwrap_or_default();
+                Line::from(vec![
+                    format!(" Label for {target}: ").bold(),
+                    Span::raw(format!("{input}_")),
+                    "  (Enter to assign, Esc to cancel)".dim(),
+                ])
+            }
+            None => Line::from(vec![
+                " q".bold(),
+                " quit  ".into(),
+                "tab".bold(),
+                " pane  ".into(),
+                "↑↓".bold(),
+                " move  ".into(),
+                "a".bold(),
+                " (de)activate  ".into(),
+                "l".bold(),
+                " label  ".into(),
+                "r".bold(),
+                " rescan  ".into(),
+                "R".bold(),
+                " reload".into(),
+            ]),
+        };
+        frame.render_widget(line, area);
+    }
+}
+
+/// Describes a label target as `instance` or `instance/model`
+fn target_description(target: &LabelTarget) -> String {
+    match target {
+        LabelTarget::ProviderInstance { instance_id } => format!("'{instance_id}'"),
+        LabelTarget::ProviderModel {
+            instance_id,
+            model_id,
+        } => format!("'{instance_id}/{model_id}'"),
+    }
+}
+
+/// Names of the assignments pointing at an instance, with the model if any
+fn assignments_for<'a>(
+    assignments: &'a [LabelAssignment],
+    instance: &'a str,
+) -> impl Iterator<Item = (&'a str, Option<&'a str>)> {
+    assignments
+        .iter()
+        .filter_map(move |assignment| match assignment.target {
+            LabelTarget::ProviderInstance { ref instance_id } if instance_id == instance => {
+                Some((assignment.label_name.as_str(), None))
+            }
+            LabelTarget::ProviderModel {
+                ref instance_id,
+                ref model_id,
+            } if instance_id == instance => {
+                Some((assignment.label_name.as_str(), Some(model_id.as_str())))
+            }
+            _ => None,
+        })
+}
+
+/// Names of the assignments pointing at one model of an instance
+fn names_for_model<'a>(
+    assignments: &'a [LabelAssignment],
+    instance: &'a str,
+    model: &'a str,
+) -> impl Iterator<Item = &'a str> {
+    assignments_for(assignments, instance)
+        .filter(move |(_, m)| *m == Some(model))
+        .map(|(name, _)| name)
+}
+
+/// Handle the tui command
+pub fn handle_tui(home: Option<&Path>) -> Result<()> {
+    let mut app = App::load(home)?;
+    ratatui::run(|terminal| app.run(terminal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_home() -> TempDir {
+        let temp_home = TempDir::new().unwrap();
+        let providers_dir = temp_home
+            .path()
+            .join(".config")
+            .join("aicred")
+            .join("inference_services");
+        fs::create_dir_all(&providers_dir).unwrap();
+        fs::write(
+            providers_dir.join("groq-groq.yaml"),
+            "id: groq-fast\nprovider_type: groq\nbase_url: https://api.groq.com/openai/v1\n\
+             api_key: gsk-tui\nmodels: [llama-3.1-8b-instant, llama-3.3-70b]\n",
+        )
+        .unwrap();
+        temp_home
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.on_key(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+    }
+
+    #[test]
+    fn test_toggle_active_and_assign_label() {
+        let temp_home = setup_home();
+        let home = Some(temp_home.path());
+        let mut app = App::load(home).unwrap();
+
+        press(&mut app, KeyCode::Char('a'));
+        let stored = load_provider_instances(home).unwrap();
+        assert!(!stored.get_instance("groq-fast").unwrap().active);
+
+        // Label the second model from the models pane
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('l'));
+        for c in "fast".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        let labels = load_label_assignments_with_home(home).unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].label_name, "fast");
+        assert_eq!(
+            labels[0].target,
+            LabelTarget::ProviderModel {
+                instance_id: "groq-fast".to_string(),
+                model_id: "llama-3.3-70b".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_shows_instances_and_labels() {
+        let temp_home = setup_home();
+        let mut app = App::load(Some(temp_home.path())).unwrap();
+        assign_label(
+            "smart",
+            LabelTarget::ProviderInstance {
+                instance_id: "groq-fast".to_string(),
+            },
+            None,
+            Some(temp_home.path()),
+        )
+        .unwrap();
+        app.reload().unwrap();
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("groq-fast (groq)"));
+        assert!(screen.contains("llama-3.3-70b"));
+        assert!(screen.contains("smart → (instance)"));
+        assert!(screen.contains("press r to scan"));
+        assert!(!screen.contains("gsk-tui"));
+    }
+}
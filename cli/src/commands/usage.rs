@@ -0,0 +1,183 @@
+//! Usage ledger commands: recording what tools used and reporting spend.
+
+use crate::commands::budget::{format_utilization, load_budgets};
+use crate::commands::cost::{format_cost, parse_tokens};
+use crate::commands::labels::load_label_assignments_with_home;
+use crate::utils::provider_loader::{load_provider_instances, model_registry};
+use aicred_core::budget::BudgetScope;
+use aicred_core::resolve::resolve_in;
+use aicred_core::usage::{self, UsageGrouping, UsageLedger, UsageRecord};
+use anyhow::{anyhow, Context, Result};
+use chrono::{NaiveDate, TimeZone, Utc};
+use colored::*;
+use std::path::Path;
+
+/// Opens the usage ledger of a home directory, or of the current user.
+pub fn usage_ledger(home: Option<&Path>) -> Result<UsageLedger> {
+    Ok(match home {
+        Some(home) => UsageLedger::for_home(home),
+        None => UsageLedger::default_location()?,
+    })
+}
+
+/// Adds the records a wrapped command wrote to the ledger, returning how many
+/// there were.
+pub fn import_usage(path: &Path, home: Option<&Path>) -> Result<usize> {
+    let records = usage_ledger(home)?.import(path, &model_registry(home))?;
+    Ok(records.len())
+}
+
+/// Handle the usage record command
+#[allow(clippy::too_many_arguments)]
+pub fn handle_record_usage(
+    model: String,
+    input_tokens: Option<String>,
+    output_tokens: Option<String>,
+    instance: Option<String>,
+    label: Option<String>,
+    cost: Option<f64>,
+    source: Option<String>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let mut record = UsageRecord::new(
+        model,
+        parse_tokens(input_tokens.as_deref().unwrap_or("0"))?,
+        parse_tokens(output_tokens.as_deref().unwrap_or("0"))?,
+    );
+    if cost.is_some_and(|cost| !cost.is_finite() || cost < 0.0) {
+        return Err(anyhow!("Invalid cost, expected a non-negative amount"));
+    }
+    // Usage recorded against a label counts for the instance it points at
+    record.instance_id = match (instance, &label) {
+        (Some(instance), _) => Some(instance),
+        (None, Some(label)) => resolve_in(
+            &load_label_assignments_with_home(home)?,
+            &load_provider_instances(home)?,
+            label,
+        )
+        .ok()
+        .map(|resolved| resolved.instance.id),
+        (None, None) => None,
+    };
+    record.label = label;
+    record.cost = cost;
+    record.source = source;
+    record.price(&model_registry(home));
+
+    usage_ledger(home)?.record([&record])?;
+    println!(
+        "{} Recorded {} input and {} output tokens of {} ({})",
+        "✓".green(),
+        record.input_tokens,
+        record.output_tokens,
+        record.model,
+        record
+            .cost
+            .map_or_else(|| "unpriced".to_string(), format_cost)
+    );
+    Ok(())
+}
+
+/// Handle the usage report command
+pub fn handle_usage_report(
+    by: UsageGrouping,
+    since: Option<String>,
+    format: String,
+    home: Option<&Path>,
+) -> Result<()> {
+    if !matches!(format.as_str(), "table" | "json") {
+        anyhow::bail!("Unknown format: {}", format);
+    }
+    let since = since
+        .map(|since| {
+            NaiveDate::parse_from_str(&since, "%Y-%m-%d")
+                .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", since))
+                .map(|day| Utc.from_utc_datetime(&day.and_time(chrono::NaiveTime::MIN)))
+        })
+        .transpose()?;
+    let records = usage_ledger(home)?.load()?;
+    let mut totals = usage::totals(&records, by, since);
+    let scope = match by {
+        UsageGrouping::Instance => Some(BudgetScope::Instance),
+        UsageGrouping::Label => Some(BudgetScope::Label),
+        UsageGrouping::Day => None,
+    };
+    if let Some(scope) = scope {
+        let budgets = load_budgets(home)?;
+        let now = Utc::now();
+        for total in &mut totals {
+            total.budget = budgets.status(scope, &total.key, &records, now);
+        }
+    }
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&totals)?);
+        return Ok(());
+    }
+    if totals.is_empty() {
+        println!("{}", "No usage recorded yet.".yellow());
+        println!(
+            "{}",
+            "Tools report usage with 'aicred usage record' or, under 'aicred wrap', to $AICRED_USAGE_FILE."
+                .dimmed()
+        );
+        return Ok(());
+    }
+
+    let heading = match by {
+        UsageGrouping::Instance => "Instance",
+        UsageGrouping::Label => "Label",
+        UsageGrouping::Day => "Day",
+    };
+    println!("\n{}", format!("Usage by {}:", by).green().bold());
+    println!(
+        "{:<24} {:>10} {:>14} {:>14} {:>12}  {}",
+        heading.bold(),
+        "Requests".bold(),
+        "Input".bold(),
+        "Output".bold(),
+        "Cost".bold(),
+        if scope.is_some() {
+            "Budget this month"
+        } else {
+            ""
+        }
+        .bold()
+    );
+    println!("{}", "-".repeat(if scope.is_some() { 100 } else { 78 }));
+    for total in &totals {
+        let cost = if total.unpriced > 0 {
+            format!("{} ({} unpriced)", format_cost(total.cost), total.unpriced)
+        } else {
+            format_cost(total.cost)
+        };
+        let budget = total.budget.as_ref().map_or_else(
+            || "".normal(),
+            |status| {
+                if status.exceeded() {
+                    format_utilization(status).red()
+                } else {
+                    format_utilization(status).normal()
+                }
+            },
+        );
+        println!(
+            "{:<24} {:>10} {:>14} {:>14} {:>12}  {}",
+            total.key.cyan(),
+            total.requests,
+            total.input_tokens,
+            total.output_tokens,
+            cost,
+            budget
+        );
+    }
+    println!(
+        "\n{}",
+        format!(
+            "Total: {}",
+            format_cost(totals.iter().map(|total| total.cost).sum())
+        )
+        .cyan()
+    );
+    Ok(())
+}
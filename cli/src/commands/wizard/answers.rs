@@ -0,0 +1,101 @@
+//! Answers file for running the wizard without prompts.
+
+use super::Candidate;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// What to do when the store already holds instances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingConfig {
+    /// Keep the existing instances, updating those whose keys were found again
+    Merge,
+    /// Remove the existing instances and import the new ones
+    Replace,
+    /// Leave the configuration alone and stop
+    Cancel,
+}
+
+/// Options for the scan phase
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanAnswers {
+    /// Only scan these providers
+    #[serde(default)]
+    pub only: Option<Vec<String>>,
+    /// Skip these providers
+    #[serde(default)]
+    pub exclude: Option<Vec<String>>,
+    /// Extra directories to scan alongside the home directory
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Probe the discovered keys for their models
+    #[serde(default)]
+    pub probe_models: bool,
+}
+
+/// A credential to import, picked by provider and optionally by source file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportAnswer {
+    /// Provider of the credential
+    pub provider: String,
+    /// Part of the path of the file the credential was found in
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Instance ID to give it (default: derived from the key, as `scan --update` does)
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Whether the instance starts active
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+const fn default_active() -> bool {
+    true
+}
+
+impl ImportAnswer {
+    /// Whether a discovered credential is the one this answer picks
+    pub fn matches(&self, candidate: &Candidate) -> bool {
+        let credential = &candidate.credential;
+        credential.provider.eq_ignore_ascii_case(&self.provider)
+            && self.source.as_ref().is_none_or(|source| {
+                credential
+                    .sources
+                    .iter()
+                    .chain(std::iter::once(&credential.source_file))
+                    .any(|file| file.contains(source.as_str()))
+            })
+    }
+}
+
+/// Choices for every wizard phase, read from a YAML file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WizardAnswers {
+    /// What to do with an existing configuration (required if there is one)
+    #[serde(default)]
+    pub existing: Option<ExistingConfig>,
+    /// Scan options
+    #[serde(default)]
+    pub scan: ScanAnswers,
+    /// Credentials to import (default: every one found)
+    #[serde(default)]
+    pub import: Option<Vec<ImportAnswer>>,
+    /// Labels to assign, as label name to `instance:model` (or a comma separated fallback chain)
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl WizardAnswers {
+    /// Load an answers file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read answers file {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid answers file {}", path.display()))
+    }
+}
@@ -0,0 +1,99 @@
+//! Guided setup: scan for credentials, pick which to import, name the
+//! instances and assign labels.
+//!
+//! Each step is a phase in [`phases`]. With `--answers` the phases take their
+//! choices from a [`WizardAnswers`] file instead of prompting, so new machines
+//! can be provisioned by script. With `--dry-run` the wizard stops before
+//! saving and prints the plan of what it would change.
+
+mod answers;
+mod phases;
+mod review;
+
+pub use answers::WizardAnswers;
+
+use crate::plan::{print_plan, PlanFormat};
+use crate::utils::provider_loader::config_store;
+use aicred_core::models::ProviderInstance;
+use aicred_core::DiscoveredCredential;
+use anyhow::Result;
+use colored::*;
+use phases::ExistingChoice;
+use std::path::Path;
+
+/// A discovered key that can become a provider instance
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// The key itself, with its full value
+    pub credential: DiscoveredCredential,
+    /// Base URL found next to the key
+    pub base_url: Option<String>,
+    /// Models found next to the key or probed with it
+    pub models: Vec<String>,
+    /// Instance ID `scan --update` would give the key
+    pub default_id: String,
+    /// Configured instance holding the same key, when merging
+    pub updates: Option<ProviderInstance>,
+}
+
+/// Handle the wizard command
+pub fn handle_wizard(
+    answers: Option<String>,
+    dry_run: Option<PlanFormat>,
+    home: Option<&Path>,
+) -> Result<()> {
+    let answers = answers
+        .map(|path| WizardAnswers::load(Path::new(&path)))
+        .transpose()?;
+    let scan_home = match home {
+        Some(home) => home.to_path_buf(),
+        None => dirs_next::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?,
+    };
+    let store = config_store(Some(&scan_home))?;
+
+    let existing = phases::handle_existing_config(&store, answers.as_ref())?;
+    if matches!(existing, ExistingChoice::Cancel) {
+        println!(
+            "{}",
+            "Setup cancelled; configuration left unchanged.".dimmed()
+        );
+        return Ok(());
+    }
+
+    let candidates = phases::run_scan_phase(&store, &scan_home, answers.as_ref())?;
+    if candidates.is_empty() {
+        println!("{}", "No credentials found to import.".yellow());
+        return Ok(());
+    }
+    let (candidates, unchanged) = phases::run_merge_phase(&store, &existing, candidates);
+    if !unchanged.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "{} discovered credential(s) already configured as they are.",
+                unchanged.len()
+            )
+            .dimmed()
+        );
+    }
+    if candidates.is_empty() {
+        println!("{}", "Nothing new to import.".green());
+        return Ok(());
+    }
+    let selections = phases::run_review_phase(candidates, &unchanged, answers.as_ref())?;
+    let instances = phases::run_configure_phase(selections, &existing, answers.is_none())?;
+    if let Some(format) = dry_run {
+        let plan = phases::plan_changes(&store, &existing, &instances, answers.as_ref())?;
+        return print_plan(&plan, format);
+    }
+    phases::run_save_phase(&store, &existing, &instances)?;
+    phases::run_labels_phase(answers.as_ref(), &instances, Some(&scan_home))?;
+
+    println!(
+        "\n{} Imported {} instance(s). Run 'aicred instances' to review them.",
+        "✓".green(),
+        instances.len()
+    );
+    Ok(())
+}
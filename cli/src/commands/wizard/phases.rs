@@ -0,0 +1,731 @@
+//! The wizard's phases, each prompting or reading its answers.
+
+use super::answers::{ExistingConfig, ImportAnswer, WizardAnswers};
+use super::review::{self, Filter, ReviewCommand};
+use super::Candidate;
+use crate::commands::labels::handle_set_label;
+use crate::commands::scan::get_default_base_url;
+use crate::plan::{ChangeAction, ChangeKind, ChangePlan};
+use crate::utils::provider_loader::config_store;
+use aicred_app::labels::label_target;
+use aicred_core::bulk::{self, InstanceSelector};
+use aicred_core::discovery::ids::key_instance_id;
+use aicred_core::models::{LabelAssignment, Provenance, ProviderInstance};
+use aicred_core::resolve::{reveal_api_key, LABELS_FILE};
+use aicred_core::rules::DetectionRules;
+use aicred_core::store::instance_file_name;
+use aicred_core::{
+    scan, ConfigStore, DiscoveredCredential, ModelRegistry, RedactionMode, ScanOptions, ScanResult,
+    SecretBackends, ValueType,
+};
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// How to treat instances already in the store
+#[derive(Debug)]
+pub enum ExistingChoice {
+    /// The store holds no instances
+    Fresh,
+    /// Keep these instances, updating those whose keys were found again
+    Merge(Vec<ProviderInstance>),
+    /// Remove these instances before saving the new ones
+    Replace(Vec<String>),
+    /// Stop without changing anything
+    Cancel,
+}
+
+impl ExistingChoice {
+    /// Instances that stay configured alongside the imported ones
+    fn kept(&self) -> &[ProviderInstance] {
+        match self {
+            Self::Merge(instances) => instances,
+            _ => &[],
+        }
+    }
+}
+
+/// A candidate picked for import
+#[derive(Debug)]
+pub struct Selection {
+    pub candidate: Candidate,
+    pub id: Option<String>,
+    pub active: bool,
+}
+
+/// Ask a question on stdout, returning the trimmed answer or the default on an
+/// empty line or end of input
+fn prompt(question: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    Ok(if input.is_empty() { default } else { input }.to_string())
+}
+
+/// Decide what happens to the instances already configured
+pub fn handle_existing_config(
+    store: &ConfigStore,
+    answers: Option<&WizardAnswers>,
+) -> Result<ExistingChoice> {
+    let existing = store.load_instances()?;
+    if existing.is_empty() {
+        return Ok(ExistingChoice::Fresh);
+    }
+    let mut instances: Vec<ProviderInstance> =
+        existing.all_instances().into_iter().cloned().collect();
+    instances.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let choice = match answers {
+        Some(answers) => answers.existing.ok_or_else(|| {
+            anyhow!(
+                "The configuration already has {} instance(s); set 'existing: merge', 'existing: replace' or 'existing: cancel' in the answers file",
+                instances.len()
+            )
+        })?,
+        None => {
+            println!(
+                "{}",
+                format!(
+                    "The configuration already has {} instance(s).",
+                    instances.len()
+                )
+                .yellow()
+            );
+            match prompt("[m]erge into them, [r]eplace them or [c]ancel?", "m")?
+                .to_lowercase()
+                .as_str()
+            {
+                "m" | "merge" => ExistingConfig::Merge,
+                "r" | "replace" => ExistingConfig::Replace,
+                _ => ExistingConfig::Cancel,
+            }
+        }
+    };
+    Ok(match choice {
+        ExistingConfig::Merge => ExistingChoice::Merge(instances),
+        ExistingConfig::Replace => {
+            ExistingChoice::Replace(instances.into_iter().map(|instance| instance.id).collect())
+        }
+        ExistingConfig::Cancel => ExistingChoice::Cancel,
+    })
+}
+
+/// Note instances whose models could not be listed; setup continues with the
+/// models that were found
+fn report_probe_failures(result: &ScanResult) {
+    let count = |key: &str| {
+        result
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(key))
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or_default()
+    };
+    let (failures, rate_limited) = (count("probe_failures"), count("probe_rate_limited"));
+    if failures == 0 {
+        return;
+    }
+    let mut note = format!(
+        "Models of {} of {} instances could not be listed",
+        failures,
+        count("probe_total_instances")
+    );
+    if rate_limited > 0 {
+        note = format!("{note} ({rate_limited} rate limited)");
+    }
+    println!(
+        "{}",
+        format!("{note}; continuing with the models found.").yellow()
+    );
+}
+
+/// Scan for keys, pairing each with the base URL and models found beside it
+pub fn run_scan_phase(
+    store: &ConfigStore,
+    home: &Path,
+    answers: Option<&WizardAnswers>,
+) -> Result<Vec<Candidate>> {
+    let scan_answers = answers.map(|answers| &answers.scan);
+    let options = ScanOptions {
+        home_dir: Some(home.to_path_buf()),
+        redaction: RedactionMode::None,
+        only_providers: scan_answers.and_then(|scan| scan.only.clone()),
+        exclude_providers: scan_answers.and_then(|scan| scan.exclude.clone()),
+        probe_models: scan_answers.is_some_and(|scan| scan.probe_models),
+        extra_roots: scan_answers
+            .map(|scan| scan.roots.iter().map(PathBuf::from).collect())
+            .unwrap_or_default(),
+        detection_rules: DetectionRules::load_from_store(store)?.rules,
+        ..ScanOptions::default()
+    };
+    println!("{}", "Scanning for GenAI credentials...".cyan().bold());
+    let result = scan(&options)?;
+    report_probe_failures(&result);
+
+    let mut probed: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for config in &result.config_instances {
+        let source = config.config_path.to_string_lossy().to_string();
+        for instance in config.provider_instances.all_instances() {
+            if !instance.models.is_empty() {
+                probed.insert(
+                    (instance.provider_type.clone(), source.clone()),
+                    instance.models.clone(),
+                );
+            }
+        }
+    }
+
+    let credentials: Vec<&DiscoveredCredential> = result
+        .keys
+        .iter()
+        .chain(
+            result
+                .config_instances
+                .iter()
+                .flat_map(|config| &config.keys),
+        )
+        .collect();
+    let registry = ModelRegistry::for_home(home);
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for credential in &credentials {
+        if !is_primary_key(&credential.value_type)
+            || credential.full_value().is_none()
+            || candidates
+                .iter()
+                .any(|candidate| candidate.credential.hash == credential.hash)
+        {
+            continue;
+        }
+        let mut candidate = Candidate {
+            credential: (*credential).clone(),
+            base_url: None,
+            models: probed
+                .get(&(credential.provider.clone(), credential.source_file.clone()))
+                .cloned()
+                .unwrap_or_default(),
+            default_id: key_instance_id(credential),
+            updates: None,
+        };
+        for neighbour in credentials.iter().filter(|other| {
+            other.provider == credential.provider && other.source_file == credential.source_file
+        }) {
+            let Some(value) = neighbour.full_value() else {
+                continue;
+            };
+            match &neighbour.value_type {
+                ValueType::BaseUrl => candidate.base_url = Some(value.to_string()),
+                ValueType::Custom(kind) if kind.eq_ignore_ascii_case("baseurl") => {
+                    candidate.base_url = Some(value.to_string());
+                }
+                ValueType::ModelId => push_unique(&mut candidate.models, value),
+                ValueType::Custom(kind) if kind.eq_ignore_ascii_case("modelid") => {
+                    push_unique(&mut candidate.models, value);
+                }
+                _ => {}
+            }
+        }
+        // Tools write the same model differently; keep one ID per model
+        candidate.models = registry.canonical_ids(candidate.models.iter().map(String::as_str));
+        candidates.push(candidate);
+    }
+    Ok(candidates)
+}
+
+fn is_primary_key(value_type: &ValueType) -> bool {
+    matches!(
+        value_type,
+        ValueType::ApiKey | ValueType::AccessToken | ValueType::SecretKey | ValueType::BearerToken
+    )
+}
+
+fn push_unique(models: &mut Vec<String>, model: &str) {
+    if !models.iter().any(|existing| existing == model) {
+        models.push(model.to_string());
+    }
+}
+
+/// When merging, match the candidates to the configured instances holding the
+/// same keys. Returns the candidates that are new or would change an instance,
+/// and apart from them those already configured as they are.
+pub fn run_merge_phase(
+    store: &ConfigStore,
+    existing: &ExistingChoice,
+    candidates: Vec<Candidate>,
+) -> (Vec<Candidate>, Vec<Candidate>) {
+    let ExistingChoice::Merge(configured) = existing else {
+        return (candidates, Vec::new());
+    };
+    // A key that cannot be revealed is compared as stored
+    let cipher = store.cipher().ok().flatten();
+    let backends = SecretBackends::builtin();
+    let keys: Vec<(&ProviderInstance, String)> = configured
+        .iter()
+        .map(|instance| {
+            let mut revealed = instance.clone();
+            let _ = reveal_api_key(&mut revealed, cipher.as_ref(), &backends);
+            (instance, revealed.api_key)
+        })
+        .collect();
+
+    let mut remaining = Vec::new();
+    let mut unchanged = Vec::new();
+    for mut candidate in candidates {
+        let value = candidate.credential.full_value();
+        candidate.updates = keys
+            .iter()
+            .find(|(_, key)| Some(key.as_str()) == value)
+            .map(|(instance, _)| (*instance).clone());
+        match &candidate.updates {
+            Some(instance) if describe_changes(instance, &candidate).is_none() => {
+                unchanged.push(candidate);
+            }
+            _ => remaining.push(candidate),
+        }
+    }
+    (remaining, unchanged)
+}
+
+/// What importing a candidate would change on the instance it updates, if anything
+fn describe_changes(instance: &ProviderInstance, candidate: &Candidate) -> Option<String> {
+    let mut changes = Vec::new();
+    if let Some(base_url) = candidate
+        .base_url
+        .as_ref()
+        .filter(|base_url| **base_url != instance.base_url)
+    {
+        changes.push(format!("base URL {} -> {}", instance.base_url, base_url));
+    }
+    let new_models = candidate
+        .models
+        .iter()
+        .filter(|model| !instance.models.contains(model))
+        .count();
+    if new_models > 0 {
+        changes.push(format!("+{} model(s)", new_models));
+    }
+    (!changes.is_empty()).then(|| changes.join(", "))
+}
+
+/// Pick the credentials to import; `unchanged` are those left out because they
+/// are already configured
+pub fn run_review_phase(
+    candidates: Vec<Candidate>,
+    unchanged: &[Candidate],
+    answers: Option<&WizardAnswers>,
+) -> Result<Vec<Selection>> {
+    match answers.and_then(|answers| answers.import.as_ref()) {
+        Some(imports) => select_answered(candidates, unchanged, imports),
+        None if answers.is_some() => Ok(candidates
+            .into_iter()
+            .map(|candidate| Selection {
+                candidate,
+                id: None,
+                active: true,
+            })
+            .collect()),
+        None => select_interactively(candidates),
+    }
+}
+
+fn select_answered(
+    candidates: Vec<Candidate>,
+    unchanged: &[Candidate],
+    imports: &[ImportAnswer],
+) -> Result<Vec<Selection>> {
+    let mut picked: Vec<Option<&ImportAnswer>> = vec![None; candidates.len()];
+    for import in imports {
+        let matching: Vec<usize> = (0..candidates.len())
+            .filter(|&index| picked[index].is_none() && import.matches(&candidates[index]))
+            .collect();
+        let describe = || {
+            import.source.as_ref().map_or_else(
+                || format!("provider '{}'", import.provider),
+                |source| format!("provider '{}' in '{}'", import.provider, source),
+            )
+        };
+        match matching.len() {
+            0 if unchanged.iter().any(|candidate| import.matches(candidate)) => {}
+            0 => return Err(anyhow!("No discovered credential matches {}", describe())),
+            1 => {}
+            count if import.id.is_some() => {
+                return Err(anyhow!(
+                    "{} credentials match {}; add a 'source' to pick one for id '{}'",
+                    count,
+                    describe(),
+                    import.id.as_deref().unwrap_or_default()
+                ))
+            }
+            _ => {}
+        }
+        for index in matching {
+            picked[index] = Some(import);
+        }
+    }
+    Ok(candidates
+        .into_iter()
+        .zip(picked)
+        .filter_map(|(candidate, import)| {
+            import.map(|import| Selection {
+                candidate,
+                id: import.id.clone(),
+                active: import.active,
+            })
+        })
+        .collect())
+}
+
+/// List the candidates with their provenance and let the user sort, filter
+/// and pick from the list
+fn select_interactively(candidates: Vec<Candidate>) -> Result<Vec<Selection>> {
+    let all: Vec<usize> = (0..candidates.len()).collect();
+    let mut visible = all.clone();
+    let mut sort_field = None;
+    let mut filters: Vec<Filter> = Vec::new();
+    loop {
+        println!("\n{}", "Discovered credentials:".green().bold());
+        for (number, &index) in visible.iter().enumerate() {
+            let candidate = &candidates[index];
+            let status = candidate.updates.as_ref().map_or_else(
+                || "new".green(),
+                |instance| {
+                    format!(
+                        "updates '{}': {}",
+                        instance.id,
+                        describe_changes(instance, candidate).unwrap_or_default()
+                    )
+                    .yellow()
+                },
+            );
+            review::print_candidate(number + 1, candidate, status);
+        }
+        if visible.len() < candidates.len() {
+            println!(
+                "{}",
+                format!(
+                    "  ({} of {} shown; 'clear' shows all)",
+                    visible.len(),
+                    candidates.len()
+                )
+                .dimmed()
+            );
+        }
+        println!("{}", review::REVIEW_HELP.dimmed());
+
+        let answer = prompt(
+            "Import which credentials? (e.g. 1,3, 'all' or 'none')",
+            "all",
+        )?;
+        if let Some(command) = review::parse_command(&answer) {
+            match command {
+                Ok(ReviewCommand::Sort(field)) => sort_field = Some(field),
+                Ok(ReviewCommand::Filter(filter)) => filters.push(filter),
+                Ok(ReviewCommand::Clear) => filters.clear(),
+                Err(e) => println!("{}", e.to_string().red()),
+            }
+            visible = all
+                .iter()
+                .copied()
+                .filter(|&index| {
+                    filters
+                        .iter()
+                        .all(|filter| filter.matches(&candidates[index]))
+                })
+                .collect();
+            if let Some(field) = sort_field {
+                review::sort(&candidates, &mut visible, field);
+            }
+            continue;
+        }
+
+        let chosen: Result<Vec<usize>> = match answer.to_lowercase().as_str() {
+            "all" => Ok(visible.clone()),
+            "none" => Ok(Vec::new()),
+            list => list
+                .split(',')
+                .map(|number| {
+                    number
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&number| (1..=visible.len()).contains(&number))
+                        .map(|number| visible[number - 1])
+                        .ok_or_else(|| anyhow!("'{}' is not one of the listed numbers", number))
+                })
+                .collect(),
+        };
+        match chosen {
+            Ok(chosen) => {
+                return Ok(candidates
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, _)| chosen.contains(index))
+                    .map(|(_, candidate)| Selection {
+                        candidate,
+                        id: None,
+                        active: true,
+                    })
+                    .collect())
+            }
+            Err(e) => println!("{}", e.to_string().red()),
+        }
+    }
+}
+
+/// Turn the picked credentials into named instances. A credential updating a
+/// configured instance keeps its ID, key, metadata, provenance and state and
+/// adds what was found, so its tags and labels still apply.
+pub fn run_configure_phase(
+    selections: Vec<Selection>,
+    existing: &ExistingChoice,
+    interactive: bool,
+) -> Result<Vec<ProviderInstance>> {
+    let kept = existing.kept();
+    let mut instances: Vec<ProviderInstance> = Vec::new();
+    for selection in selections {
+        let candidate = selection.candidate;
+        if let Some(mut instance) = candidate.updates {
+            if let Some(base_url) = candidate.base_url {
+                instance.base_url = base_url;
+            }
+            for model in &candidate.models {
+                push_unique(&mut instance.models, model);
+            }
+            if let Some(provenance) = &mut instance.provenance {
+                provenance.add_sources(&candidate.credential);
+            }
+            instances.push(instance);
+            continue;
+        }
+
+        let taken = |id: &str| {
+            kept.iter()
+                .chain(&instances)
+                .any(|instance| instance.id == id)
+        };
+        let mut id = selection.id.unwrap_or(candidate.default_id);
+        if interactive {
+            loop {
+                id = prompt(
+                    &format!(
+                        "Instance ID for the {} key in {}",
+                        candidate.credential.provider, candidate.credential.source_file
+                    ),
+                    &id,
+                )?;
+                if !taken(&id) {
+                    break;
+                }
+                println!(
+                    "{}",
+                    format!("Instance ID '{}' is already in use.", id).red()
+                );
+            }
+        } else if kept.iter().any(|instance| instance.id == id) {
+            return Err(anyhow!(
+                "Instance ID '{}' is already configured with a different key",
+                id
+            ));
+        }
+        if taken(&id) {
+            return Err(anyhow!("Instance ID '{}' is used more than once", id));
+        }
+
+        let mut instance = ProviderInstance::new(
+            id,
+            candidate.credential.provider.to_lowercase(),
+            candidate
+                .base_url
+                .unwrap_or_else(|| get_default_base_url(&candidate.credential.provider)),
+            candidate
+                .credential
+                .full_value()
+                .unwrap_or_default()
+                .to_string(),
+            candidate.models,
+        )
+        .with_provenance(Provenance::from_key(
+            &candidate.credential,
+            candidate.credential.discovered_at,
+        ));
+        instance.active = selection.active;
+        instances.push(instance);
+    }
+    Ok(instances)
+}
+
+/// Write the instances, replacing or updating the configured ones as chosen
+pub fn run_save_phase(
+    store: &ConfigStore,
+    existing: &ExistingChoice,
+    instances: &[ProviderInstance],
+) -> Result<()> {
+    if let ExistingChoice::Replace(ids) = existing {
+        let selector = InstanceSelector {
+            ids: ids.clone(),
+            ..InstanceSelector::new()
+        };
+        bulk::remove(store, &selector)?;
+    }
+    let kept = existing.kept();
+    let (updated, new): (Vec<&ProviderInstance>, Vec<&ProviderInstance>) = instances
+        .iter()
+        .partition(|instance| kept.iter().any(|kept| kept.id == instance.id));
+
+    // Updated instances stay in their own files
+    store.update_instances(|stored| {
+        let Some(instance) = updated.iter().find(|instance| instance.id == stored.id) else {
+            return Ok(false);
+        };
+        *stored = (*instance).clone();
+        Ok(true)
+    })?;
+    for instance in &updated {
+        println!(
+            "{} Updated instance '{}' ({}, {} models)",
+            "✓".green(),
+            instance.id.cyan(),
+            instance.provider_type,
+            instance.models.len()
+        );
+    }
+
+    let lock = store.lock()?;
+    for instance in new {
+        lock.save_instance_as(&new_file_name(store, instance), instance)?;
+        println!(
+            "{} Saved instance '{}' ({}, {} models)",
+            "✓".green(),
+            instance.id.cyan(),
+            instance.provider_type,
+            instance.models.len()
+        );
+    }
+    Ok(())
+}
+
+/// File name for a new instance; the default one only holds part of the ID,
+/// so it can be taken
+fn new_file_name(store: &ConfigStore, instance: &ProviderInstance) -> String {
+    let file_name = instance_file_name(instance);
+    if store.instances_dir().join(&file_name).exists() {
+        format!("{}-{}.yaml", instance.provider_type, instance.id)
+    } else {
+        file_name
+    }
+}
+
+/// Plan what the save and label phases would change
+pub fn plan_changes(
+    store: &ConfigStore,
+    existing: &ExistingChoice,
+    instances: &[ProviderInstance],
+    answers: Option<&WizardAnswers>,
+) -> Result<ChangePlan> {
+    let mut plan = ChangePlan::new("wizard");
+    if let ExistingChoice::Replace(ids) = existing {
+        let selector = InstanceSelector {
+            ids: ids.clone(),
+            ..InstanceSelector::new()
+        };
+        for (path, instance) in bulk::select_files(store, &selector)? {
+            plan.add(ChangeAction::Delete, ChangeKind::Instance, &instance.id)
+                .at(&path);
+        }
+    }
+
+    let kept = existing.kept();
+    let stored = bulk::select_files(store, &InstanceSelector::new())?;
+    for instance in instances {
+        let describe = format!(
+            "{}, {} models",
+            instance.provider_type,
+            instance.models.len()
+        );
+        if kept.iter().any(|kept| kept.id == instance.id) {
+            let change = plan.add(ChangeAction::Update, ChangeKind::Instance, &instance.id);
+            if let Some((path, _)) = stored.iter().find(|(_, stored)| stored.id == instance.id) {
+                change.at(path);
+            }
+            change.detail(describe);
+        } else {
+            plan.add(ChangeAction::Create, ChangeKind::Instance, &instance.id)
+                .at(&store.instances_dir().join(new_file_name(store, instance)))
+                .detail(describe);
+        }
+    }
+
+    let labels: Vec<LabelAssignment> = store.load_yaml(LABELS_FILE)?.unwrap_or_default();
+    for (label, target) in answers.map(|answers| &answers.labels).into_iter().flatten() {
+        let action = if labels
+            .iter()
+            .any(|assignment| assignment.label_name == *label)
+        {
+            ChangeAction::Update
+        } else {
+            ChangeAction::Create
+        };
+        plan.add(action, ChangeKind::Assignment, label)
+            .at(&store.dir().join(LABELS_FILE))
+            .detail(format!("targets {}", target));
+    }
+    Ok(plan)
+}
+
+/// Assign labels to the new instances
+pub fn run_labels_phase(
+    answers: Option<&WizardAnswers>,
+    instances: &[ProviderInstance],
+    home: Option<&Path>,
+) -> Result<()> {
+    if let Some(answers) = answers {
+        // Every target must name a saved instance and one of its models, so
+        // no label is set when one of them is wrong
+        let saved = config_store(home)?.load_instances()?;
+        for (label, targets) in &answers.labels {
+            for target in targets.split(',').map(str::trim) {
+                label_target(target, &saved)
+                    .map_err(|e| anyhow!("Cannot set label '{}': {}", label, e))?;
+            }
+        }
+        for (label, target) in &answers.labels {
+            handle_set_label(label.clone(), target.clone(), None, None, Vec::new(), home)?;
+        }
+        return Ok(());
+    }
+    if instances.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        "Assign labels such as fast=<instance>:<model> (empty line to finish):".bold()
+    );
+    loop {
+        let answer = prompt("Label", "")?;
+        if answer.is_empty() {
+            return Ok(());
+        }
+        let Some((label, target)) = answer.split_once('=') else {
+            println!("{}", "Expected <label>=<instance>:<model>".red());
+            continue;
+        };
+        if let Err(e) = handle_set_label(
+            label.trim().to_string(),
+            target.trim().to_string(),
+            None,
+            None,
+            Vec::new(),
+            home,
+        ) {
+            println!("{}", e.to_string().red());
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Provenance display, sorting and filtering of the credentials under review.
+
+use super::Candidate;
+use aicred_core::{Confidence, RiskLevel};
+use anyhow::{anyhow, Result};
+use colored::*;
+
+/// Field to sort the review list by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    /// Highest risk first
+    Risk,
+    /// Highest confidence first
+    Confidence,
+    /// Provider name
+    Provider,
+    /// Source file, then line
+    Source,
+    /// Most widely readable files first
+    Permissions,
+}
+
+/// Condition a credential must meet to stay in the review list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Risk at least this level
+    MinRisk(RiskLevel),
+    /// Confidence at least this level
+    MinConfidence(Confidence),
+    /// Provider is this one
+    Provider(String),
+    /// Source file path contains this text
+    Source(String),
+}
+
+/// A command typed at the review prompt instead of a selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewCommand {
+    Sort(SortField),
+    Filter(Filter),
+    Clear,
+}
+
+/// Usage shown with the review list
+pub const REVIEW_HELP: &str = "Or type 'sort risk|confidence|provider|source|permissions', \
+     'filter risk=<level>|confidence=<level>|provider=<name>|source=<text>' or 'clear'";
+
+/// Parse a review command, or `None` if the input is a selection
+pub fn parse_command(input: &str) -> Option<Result<ReviewCommand>> {
+    let (command, argument) = input
+        .trim()
+        .split_once(char::is_whitespace)
+        .map_or((input.trim(), ""), |(command, argument)| {
+            (command, argument.trim())
+        });
+    match command.to_lowercase().as_str() {
+        "clear" => Some(Ok(ReviewCommand::Clear)),
+        "sort" => Some(parse_sort(argument).map(ReviewCommand::Sort)),
+        "filter" => Some(parse_filter(argument).map(ReviewCommand::Filter)),
+        _ => None,
+    }
+}
+
+fn parse_sort(field: &str) -> Result<SortField> {
+    match field.to_lowercase().as_str() {
+        "risk" => Ok(SortField::Risk),
+        "confidence" => Ok(SortField::Confidence),
+        "provider" => Ok(SortField::Provider),
+        "source" => Ok(SortField::Source),
+        "permissions" | "perms" => Ok(SortField::Permissions),
+        _ => Err(anyhow!(
+            "Unknown sort field '{}' (expected risk, confidence, provider, source or permissions)",
+            field
+        )),
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<Filter> {
+    let (field, value) = filter
+        .split_once('=')
+        .map(|(field, value)| (field.trim(), value.trim()))
+        .ok_or_else(|| anyhow!("Expected a filter such as risk=high or provider=openai"))?;
+    match field.to_lowercase().as_str() {
+        "risk" => value
+            .parse::<RiskLevel>()
+            .map(Filter::MinRisk)
+            .map_err(|e| anyhow!(e)),
+        "confidence" => parse_confidence(value).map(Filter::MinConfidence),
+        "provider" => Ok(Filter::Provider(value.to_string())),
+        "source" => Ok(Filter::Source(value.to_string())),
+        _ => Err(anyhow!(
+            "Unknown filter field '{}' (expected risk, confidence, provider or source)",
+            field
+        )),
+    }
+}
+
+fn parse_confidence(value: &str) -> Result<Confidence> {
+    match value.to_lowercase().replace(['-', '_'], "").as_str() {
+        "low" => Ok(Confidence::Low),
+        "medium" => Ok(Confidence::Medium),
+        "high" => Ok(Confidence::High),
+        "veryhigh" => Ok(Confidence::VeryHigh),
+        _ => Err(anyhow!(
+            "Unknown confidence '{}' (expected low, medium, high or very-high)",
+            value
+        )),
+    }
+}
+
+impl Filter {
+    /// Whether a candidate meets the condition
+    pub fn matches(&self, candidate: &Candidate) -> bool {
+        let credential = &candidate.credential;
+        match self {
+            Self::MinRisk(level) => credential.risk >= *level,
+            Self::MinConfidence(level) => credential.confidence >= *level,
+            Self::Provider(provider) => credential.provider.eq_ignore_ascii_case(provider),
+            Self::Source(source) => credential.source_file.contains(source.as_str()),
+        }
+    }
+}
+
+/// Order candidate indices by a field
+pub fn sort(candidates: &[Candidate], indices: &mut [usize], field: SortField) {
+    let key = |index: &usize| &candidates[*index].credential;
+    match field {
+        SortField::Risk => indices.sort_by_key(|index| std::cmp::Reverse(key(index).risk)),
+        SortField::Confidence => {
+            indices.sort_by_key(|index| std::cmp::Reverse(key(index).confidence));
+        }
+        SortField::Provider => indices.sort_by(|a, b| key(a).provider.cmp(&key(b).provider)),
+        SortField::Source => indices.sort_by(|a, b| {
+            (&key(a).source_file, key(a).source_line)
+                .cmp(&(&key(b).source_file, key(b).source_line))
+        }),
+        SortField::Permissions => indices.sort_by_key(|index| {
+            std::cmp::Reverse(file_mode(&key(index).source_file).map(|mode| mode & 0o077))
+        }),
+    }
+}
+
+/// Unix permission bits of a file
+#[cfg(unix)]
+pub fn file_mode(path: &str) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .ok()
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.permissions().mode() & 0o777)
+}
+
+/// Unix permission bits of a file
+#[cfg(not(unix))]
+pub fn file_mode(_path: &str) -> Option<u32> {
+    None
+}
+
+/// Describe file permissions, flagging files others can read
+fn describe_mode(mode: Option<u32>) -> ColoredString {
+    match mode {
+        Some(mode) if mode & 0o004 != 0 => format!("{:04o} world-readable", mode).red(),
+        Some(mode) if mode & 0o040 != 0 => format!("{:04o} group-readable", mode).yellow(),
+        Some(mode) => format!("{:04o}", mode).green(),
+        None => "not a file".dimmed(),
+    }
+}
+
+fn describe_risk(risk: RiskLevel) -> ColoredString {
+    let text = format!("risk {}", risk);
+    match risk {
+        RiskLevel::Critical => text.red().bold(),
+        RiskLevel::High => text.red(),
+        RiskLevel::Medium => text.yellow(),
+        RiskLevel::Low => text.green(),
+    }
+}
+
+/// Print a candidate with where it was found and how far to trust it
+pub fn print_candidate(number: usize, candidate: &Candidate, status: ColoredString) {
+    let credential = &candidate.credential;
+    println!(
+        "  [{}] {:<12} {:<20} {}",
+        number,
+        credential.provider.cyan(),
+        credential.redacted_value(),
+        status
+    );
+    let location = credential.source_line.map_or_else(
+        || credential.source_file.clone(),
+        |line| format!("{}:{}", credential.source_file, line),
+    );
+    println!(
+        "      {}  ({})",
+        location.dimmed(),
+        describe_mode(file_mode(&credential.source_file))
+    );
+    println!(
+        "      confidence {}, {}",
+        credential.confidence,
+        describe_risk(credential.risk)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_recognizes_sorts_filters_and_selections() {
+        assert_eq!(
+            parse_command("sort risk").unwrap().unwrap(),
+            ReviewCommand::Sort(SortField::Risk)
+        );
+        assert_eq!(
+            parse_command("filter confidence=very-high")
+                .unwrap()
+                .unwrap(),
+            ReviewCommand::Filter(Filter::MinConfidence(Confidence::VeryHigh))
+        );
+        assert_eq!(
+            parse_command("filter provider = openai").unwrap().unwrap(),
+            ReviewCommand::Filter(Filter::Provider("openai".to_string()))
+        );
+        assert_eq!(
+            parse_command("clear").unwrap().unwrap(),
+            ReviewCommand::Clear
+        );
+        assert!(parse_command("sort size").unwrap().is_err());
+        assert!(parse_command("filter risk").unwrap().is_err());
+        assert!(parse_command("1,3").is_none());
+        assert!(parse_command("all").is_none());
+    }
+}
@@ -1,10 +1,16 @@
 //! Wrap command implementation - executes commands with LLM environment variables
 
-use crate::commands::labels::load_labels_with_targets;
-use crate::utils::provider_loader::load_provider_instances;
+use crate::commands::budget::enforce_budgets;
+use crate::commands::labels::{load_label_assignments_with_home, load_labels_with_targets};
+use crate::commands::setenv::print_shell_exports;
+use crate::commands::usage::import_usage;
+use crate::utils::provider_loader::{load_provider_instances, resolve_instance_secrets};
+use aicred_core::resolve::resolve_in;
 use aicred_core::scanners::ScannerRegistry;
+use aicred_core::usage::USAGE_FILE_ENV;
+use aicred_core::EnvResolutionResult;
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Handle the wrap command - execute a command with LLM environment variables or generate shell exports
@@ -21,8 +27,98 @@ pub fn handle_wrap(
         return Err(anyhow!("No command specified to wrap"));
     }
 
+    let resolution_result = resolve_scanner_env(scanner_names, dry_run, home_dir.as_deref())?;
+    if !dry_run {
+        enforce_label_budgets(&resolution_result.resolved_labels, home_dir.as_deref())?;
+    }
+
+    // 1. Handle --setenv mode: generate shell export statements
+    if setenv {
+        let vars = resolution_result.variables.into_iter().collect();
+        return print_shell_exports(&vars, format.as_deref().unwrap_or("bash"), dry_run);
+    }
+
+    // 2. Handle dry run for wrap mode
+    if dry_run {
+        println!("Environment variables that would be set:");
+        for (key, value) in &resolution_result.variables {
+            // Mask sensitive values (API keys)
+            let display_value = if key.contains("API_KEY") && !value.is_empty() {
+                if value.len() > 8 {
+                    format!("{}***{}", &value[..4], &value[value.len() - 4..])
+                } else {
+                    "****".to_string()
+                }
+            } else {
+                value.clone()
+            };
+            println!("  {}={}", key, display_value);
+        }
+
+        if !resolution_result.missing_required.is_empty() {
+            println!("\nMissing required variables:");
+            for var in &resolution_result.missing_required {
+                println!("  {}", var);
+            }
+        }
+        return Ok(());
+    }
+
+    // 3. Check for missing required variables in normal mode
+    if !resolution_result.is_successful() {
+        return Err(anyhow!(
+            "Environment variable resolution failed. Missing required variables: {}",
+            resolution_result.missing_required.join(", ")
+        ));
+    }
+
+    // 4. Execute command with resolved environment variables, giving it a
+    // file to report its token usage in
+    let (cmd, args) = command_args.split_first().unwrap();
+    let usage_file = tempfile::Builder::new()
+        .prefix("aicred-usage-")
+        .suffix(".jsonl")
+        .tempfile()?;
+
+    let status = Command::new(cmd)
+        .args(args)
+        .envs(resolution_result.variables)
+        .env(USAGE_FILE_ENV, usage_file.path())
+        .status()?;
+
+    if let Err(e) = import_usage(usage_file.path(), home_dir.as_deref()) {
+        eprintln!("Warning: failed to record the command's usage: {}", e);
+    }
+    drop(usage_file);
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Checks the budgets of the labels whose keys a command is handed and of
+/// the instances they resolve to.
+fn enforce_label_budgets(labels: &[String], home_dir: Option<&Path>) -> Result<()> {
+    if labels.is_empty() {
+        return Ok(());
+    }
+    let assignments = load_label_assignments_with_home(home_dir)?;
+    let instances = load_provider_instances(home_dir)?;
+    for label in labels {
+        let instance_id = resolve_in(&assignments, &instances, label)
+            .ok()
+            .map(|resolved| resolved.instance.id);
+        enforce_budgets(Some(label), instance_id.as_deref(), home_dir)?;
+    }
+    Ok(())
+}
+
+/// Resolves the environment variables a scanner's tool expects from the
+/// configured labels and provider instances
+pub(crate) fn resolve_scanner_env(
+    scanner_names: Option<Vec<String>>,
+    dry_run: bool,
+    home_dir: Option<&Path>,
+) -> Result<EnvResolutionResult> {
     // Set home directory if provided
-    if let Some(home) = &home_dir {
+    if let Some(home) = home_dir {
         std::env::set_var("HOME", home);
     }
 
@@ -64,15 +160,16 @@ pub fn handle_wrap(
     let label_mappings = scanner.get_label_mappings();
 
     // 4. Load labels and provider instances from user configuration
-    let labels = load_labels_with_targets(home_dir.as_deref())?;
+    let labels = load_labels_with_targets(home_dir)?;
 
     // 5. Load provider instances from disk/config and convert to new API
-    let provider_instances_collection = load_provider_instances(home_dir.as_deref())?;
-    let provider_instances: Vec<aicred_core::ProviderInstance> = provider_instances_collection
+    let provider_instances_collection = load_provider_instances(home_dir)?;
+    let mut provider_instances: Vec<aicred_core::ProviderInstance> = provider_instances_collection
         .list()
         .into_iter()
         .cloned()
         .collect();
+    resolve_instance_secrets(&mut provider_instances)?;
 
     // 6. Use EnvResolver to properly resolve environment variables
     let env_resolver = aicred_core::EnvResolverBuilder::new()
@@ -82,133 +179,5 @@ pub fn handle_wrap(
         .with_label_mappings(label_mappings)
         .build();
 
-    let resolution_result = env_resolver.resolve(dry_run)?;
-
-    // 7. Handle --setenv mode: generate shell export statements
-    if setenv {
-        return generate_shell_exports(resolution_result.variables, format, dry_run);
-    }
-
-    // 8. Handle dry run for wrap mode
-    if dry_run {
-        println!("Environment variables that would be set:");
-        for (key, value) in &resolution_result.variables {
-            // Mask sensitive values (API keys)
-            let display_value = if key.contains("API_KEY") && !value.is_empty() {
-                if value.len() > 8 {
-                    format!("{}***{}", &value[..4], &value[value.len() - 4..])
-                } else {
-                    "****".to_string()
-                }
-            } else {
-                value.clone()
-            };
-            println!("  {}={}", key, display_value);
-        }
-
-        if !resolution_result.missing_required.is_empty() {
-            println!("\nMissing required variables:");
-            for var in &resolution_result.missing_required {
-                println!("  {}", var);
-            }
-        }
-        return Ok(());
-    }
-
-    // 9. Check for missing required variables in normal mode
-    if !resolution_result.is_successful() {
-        return Err(anyhow!(
-            "Environment variable resolution failed. Missing required variables: {}",
-            resolution_result.missing_required.join(", ")
-        ));
-    }
-
-    // 10. Execute command with resolved environment variables
-    let (cmd, args) = command_args.split_first().unwrap();
-
-    let status = Command::new(cmd)
-        .args(args)
-        .envs(resolution_result.variables)
-        .status()?;
-
-    std::process::exit(status.code().unwrap_or(1));
-}
-
-/// Generate shell export statements for environment variables
-fn generate_shell_exports(
-    env_vars: std::collections::HashMap<String, String>,
-    format: Option<String>,
-    dry_run: bool,
-) -> Result<()> {
-    // Handle dry run mode
-    if dry_run {
-        println!("Environment variables that would be exported:");
-        for (key, value) in &env_vars {
-            // Mask sensitive values (API keys)
-            let display_value = if key.contains("API_KEY") && !value.is_empty() {
-                // Only show first 4 and last 4 chars if value is long enough
-                if value.len() >= 8 {
-                    format!("{}...{}", &value[..4], &value[value.len() - 4..])
-                } else {
-                    // For short values, mask completely for security
-                    "****".to_string()
-                }
-            } else {
-                value.clone()
-            };
-            println!("  {}={}", key, display_value);
-        }
-        return Ok(());
-    }
-
-    // Generate export statements based on format
-    let format_str = format.as_deref().unwrap_or("bash");
-    match format_str {
-        "bash" | "zsh" => {
-            for (key, value) in env_vars {
-                println!("export {}='{}'", key, escape_shell_value(&value, "bash"));
-            }
-        }
-        "fish" => {
-            for (key, value) in env_vars {
-                println!("set -gx {} '{}'", key, escape_shell_value(&value, "fish"));
-            }
-        }
-        "powershell" => {
-            for (key, value) in env_vars {
-                println!(
-                    "$env:{} = '{}'",
-                    key,
-                    escape_shell_value(&value, "powershell")
-                );
-            }
-        }
-        _ => {
-            return Err(anyhow!(
-                "Unsupported format: {}. Supported formats: bash, fish, powershell",
-                format_str
-            ))
-        }
-    }
-
-    Ok(())
-}
-
-/// Escape shell value based on the shell format
-fn escape_shell_value(value: &str, shell_type: &str) -> String {
-    match shell_type {
-        "bash" | "zsh" => {
-            // Escape single quotes in bash/zsh
-            value.replace("'", "'\\''")
-        }
-        "fish" => {
-            // Escape single quotes in fish
-            value.replace("'", "\\'")
-        }
-        "powershell" => {
-            // Escape single quotes in PowerShell
-            value.replace("'", "''")
-        }
-        _ => value.to_string(),
-    }
+    Ok(env_resolver.resolve(dry_run)?)
 }
@@ -0,0 +1,4 @@
+//! Defaults for command-line options, read from `config.yaml` in the config
+//! directory when the CLI starts; see [`aicred_app::settings`].
+
+pub use aicred_app::settings::Settings as CliConfig;
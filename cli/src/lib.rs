@@ -4,7 +4,9 @@
 //! including tag and label management commands.
 
 pub mod commands;
+pub mod config;
 pub mod output;
+pub mod plan;
 pub mod utils;
 
 pub use commands::{labels, tags};
@@ -13,28 +13,64 @@
 #![allow(unused_imports)]
 #![allow(unused_comparisons)]
 
+use aicred_core::fingerprint::HashAlgorithm;
+use aicred_core::usage::UsageGrouping;
+use aicred_core::RedactionMode;
 use anyhow::{anyhow, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv};
 use colored::*;
 use std::path::PathBuf;
 use tracing_subscriber::EnvFilter;
 
 mod commands;
+mod config;
 mod output;
+mod plan;
 mod utils;
 
 use commands::{
+    audit::handle_verify_audit_log,
+    budget::{handle_list_budgets, handle_remove_budget, handle_set_budget},
+    cleanup::handle_cleanup,
+    completions::{self, handle_completions},
+    config::{
+        handle_decrypt, handle_encrypt, handle_export_bundle, handle_import_bundle, handle_migrate,
+    },
+    cost::handle_cost,
+    daemon::handle_daemon,
+    doctor::handle_doctor,
+    export::handle_export,
+    fingerprint::handle_fingerprint,
+    history::handle_history,
     labels::{handle_label_scan, handle_list_labels, handle_set_label, handle_unset_label},
+    mcp::handle_mcp,
+    models::{
+        handle_compare_models, handle_recommend_models, handle_refresh_models,
+        handle_registry_models,
+    },
+    profile::{
+        handle_create_profile, handle_delete_profile, handle_list_profiles, handle_show_profile,
+        handle_use_profile,
+    },
     providers::{
-        handle_add_instance, handle_get_instance, handle_list_instances, handle_list_models,
-        handle_providers, handle_remove_instance, handle_update_instance,
-        handle_validate_instances,
+        handle_add_instance, handle_check_instances, handle_get_instance, handle_list_instances,
+        handle_list_models, handle_providers, handle_remove_instance, handle_set_active,
+        handle_update_instance, handle_validate_instances,
     },
-    scan::handle_scan,
+    resolve::handle_resolve,
+    revoke::handle_revoke,
+    rotate::handle_rotate,
+    rules::{handle_import_rules, handle_list_rules, handle_remove_rule},
+    scan::{handle_scan, ScanArgs},
+    setenv::handle_setenv,
     tags::{
         handle_add_tag, handle_assign_tag, handle_list_tags, handle_remove_tag,
         handle_unassign_tag, handle_update_tag,
     },
+    tui::handle_tui,
+    usage::{handle_record_usage, handle_usage_report},
+    wizard::handle_wizard,
     wrap::handle_wrap,
 };
 
@@ -47,6 +83,18 @@ struct Cli {
     #[arg(long, global = true)]
     home: Option<String>,
 
+    /// Profile to use instead of the active one (see 'aicred profile')
+    #[arg(long, global = true, add = ArgValueCandidates::new(completions::profile_names))]
+    profile: Option<String>,
+
+    /// Show what would change without changing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// How --dry-run prints its plan of changes (text, json)
+    #[arg(long, global = true, default_value = "text")]
+    plan_format: String,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -59,14 +107,22 @@ enum Commands {
         #[arg(long)]
         home: Option<String>,
 
-        /// Output format (json, ndjson, table, summary)
-        #[arg(long, short = 'f', default_value = "table")]
-        format: String,
+        /// Output format (json, ndjson, table, summary, csv, markdown)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
 
         /// Include full secret values (DANGEROUS - use with caution)
         #[arg(long)]
         include_values: bool,
 
+        /// How key values are redacted (none, mask, last4, first8, fingerprint)
+        #[arg(long, conflicts_with = "include_values")]
+        redaction: Option<RedactionMode>,
+
+        /// How the hash of each finding is computed (sha256, hmac-sha256)
+        #[arg(long)]
+        hash_algorithm: Option<HashAlgorithm>,
+
         /// Only scan specific providers (comma-separated)
         #[arg(long)]
         only: Option<String>,
@@ -75,18 +131,22 @@ enum Commands {
         #[arg(long)]
         exclude: Option<String>,
 
+        /// Only report specific value types, e.g. api_key,access_token (comma-separated)
+        #[arg(long)]
+        only_types: Option<String>,
+
         /// Maximum file size to read (in bytes)
         #[arg(long, default_value = "1048576")]
         max_bytes_per_file: usize,
 
-        /// Dry run - show files that would be scanned without reading them
-        #[arg(long)]
-        dry_run: bool,
-
-        /// Write audit log to file
+        /// Append a JSON Lines audit record of the scan to this file
         #[arg(long)]
         audit_log: Option<String>,
 
+        /// Hash chain audit records so tampering can be detected with 'aicred audit verify'
+        #[arg(long, requires = "audit_log")]
+        audit_chain: bool,
+
         /// Verbose output - show actual discovered keys
         #[arg(long, short = 'v')]
         verbose: bool,
@@ -102,6 +162,65 @@ enum Commands {
         /// Timeout for model probing in seconds (default: 30)
         #[arg(long)]
         probe_timeout: Option<u64>,
+
+        /// Record the hashes of all current findings to a baseline file
+        #[arg(long, value_name = "FILE")]
+        write_baseline: Option<String>,
+
+        /// Only report findings not present in this baseline file (exits 1 if new keys are found)
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<String>,
+
+        /// Evaluate findings against a YAML policy file (exits 1 if any fail rule matches)
+        #[arg(long, value_name = "FILE")]
+        policy: Option<String>,
+
+        /// Only report findings at or above this risk level (low, medium, high, critical)
+        #[arg(long, value_name = "LEVEL")]
+        min_risk: Option<String>,
+
+        /// Sort findings by field (risk, confidence, provider)
+        #[arg(long, value_name = "FIELD")]
+        sort: Option<String>,
+
+        /// Only report credentials that haven't been rotated in at least N days
+        #[arg(long, value_name = "N")]
+        stale_days: Option<u32>,
+
+        /// Additional directory to scan alongside the home directory (repeatable)
+        #[arg(long = "root", value_name = "DIR")]
+        roots: Vec<String>,
+
+        /// Also scan .zip/.tar/.tar.gz backups given as roots or found directly inside one
+        #[arg(long)]
+        scan_archives: bool,
+
+        /// Don't record this scan in the scan history
+        #[arg(long)]
+        no_history: bool,
+
+        /// Don't post this scan to the notification sinks
+        #[arg(long)]
+        no_notify: bool,
+
+        /// Make no network calls: skip model probing and notifications
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Show when keys appeared and disappeared across recorded scans
+    History {
+        /// Show per-key first and last sightings instead of counts per scan
+        #[arg(long)]
+        keys: bool,
+
+        /// Only show the most recent N entries
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
     },
 
     /// Show available providers and scanners
@@ -109,18 +228,26 @@ enum Commands {
         /// Show detailed information
         #[arg(long, short = 'v')]
         verbose: bool,
+
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
     },
 
     /// Provider instance management commands
     Instances {
         /// Instance ID to get (positional argument - shorthand for 'instances get <id>')
-        #[arg(value_name = "ID")]
+        #[arg(value_name = "ID", add = ArgValueCandidates::new(completions::instance_ids))]
         id: Option<String>,
 
         /// Include full secret values when using direct ID lookup (DANGEROUS - use with caution)
         #[arg(long)]
         include_values: bool,
 
+        /// Output format of the direct ID lookup or default list (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
+
         #[command(subcommand)]
         command: Option<InstanceCommands>,
     },
@@ -143,6 +270,210 @@ enum Commands {
         command: Option<ModelCommands>,
     },
 
+    /// Print the instance and model a label points at, for scripts
+    Resolve {
+        /// Label to resolve
+        #[arg(add = ArgValueCandidates::new(completions::label_names))]
+        label: String,
+
+        /// Output format (json, env)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+
+    /// Estimate token costs from the model registry's pricing
+    Cost {
+        /// Model to price (repeatable to compare; defaults to the configured models)
+        #[arg(long = "model", short = 'm')]
+        models: Vec<String>,
+
+        /// Number of input tokens (e.g., 1500, 200k, 1M)
+        #[arg(long)]
+        input_tokens: Option<String>,
+
+        /// Number of output tokens (e.g., 1500, 200k, 1M)
+        #[arg(long)]
+        output_tokens: Option<String>,
+
+        /// Price a JSON usage file instead ('-' for stdin)
+        #[arg(long, conflicts_with_all = ["models", "input_tokens", "output_tokens"])]
+        usage: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Record token usage and report local spend
+    Usage {
+        #[command(subcommand)]
+        command: Option<UsageCommands>,
+    },
+
+    /// Monthly spending budgets of instances and labels
+    Budget {
+        #[command(subcommand)]
+        command: Option<BudgetCommands>,
+    },
+
+    /// Profile management commands (separate instance, tag and label sets)
+    Profile {
+        #[command(subcommand)]
+        command: Option<ProfileCommands>,
+    },
+
+    /// Configuration store maintenance commands
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Export provider instances to another tool's configuration format
+    Export {
+        /// Export format (litellm, mcp-env, env, docker-env, docker-secrets)
+        #[arg(value_name = "FORMAT", required_unless_present = "format")]
+        target: Option<String>,
+
+        /// Export format, as an alternative to the positional FORMAT
+        #[arg(long, short = 'f', conflicts_with = "target")]
+        format: Option<String>,
+
+        /// Only export the instance and model a label points at
+        #[arg(long, conflicts_with = "instances", add = ArgValueCandidates::new(completions::label_names))]
+        label: Option<String>,
+
+        /// Only export these instances (repeatable)
+        #[arg(long = "instance", add = ArgValueCandidates::new(completions::instance_ids))]
+        instances: Vec<String>,
+
+        /// Only export instances with this tag, e.g. the service that uses them
+        #[arg(long, add = ArgValueCandidates::new(completions::tag_names))]
+        tag: Option<String>,
+
+        /// Write placeholders instead of key values (env format)
+        #[arg(long)]
+        no_values: bool,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<String>,
+
+        /// Reference keys through environment variables instead of writing their values
+        #[arg(long)]
+        env_refs: bool,
+
+        /// MCP client config (e.g. claude_desktop_config.json) whose server env blocks to fill
+        #[arg(long)]
+        merge: Option<String>,
+
+        /// MCP server to give every key to (repeatable; default: only fill declared variables)
+        #[arg(long = "server")]
+        servers: Vec<String>,
+    },
+
+    /// Serve scans, instances and labels over a JSON-RPC Unix socket
+    Daemon {
+        /// Socket path (default: ~/.config/aicred/daemon.sock)
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Run a Model Context Protocol server on stdio for AI agents
+    Mcp,
+
+    /// Audit log commands
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+
+    /// Custom detection rule commands
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommands,
+    },
+
+    /// Guided setup: scan for credentials and import the ones you pick
+    Wizard {
+        /// Take every choice from this YAML answers file instead of prompting
+        #[arg(long)]
+        answers: Option<String>,
+    },
+
+    /// Rotate an instance's key through the provider's key management API
+    Rotate {
+        /// Instance ID whose key to rotate
+        #[arg(add = ArgValueCandidates::new(completions::instance_ids))]
+        instance_id: String,
+
+        /// Revoke the old key once the new one is verified
+        #[arg(long)]
+        revoke_old: bool,
+
+        /// Admin or provisioning key for the provider (defaults to the provider's env var)
+        #[arg(long)]
+        admin_key: Option<String>,
+
+        /// Name given to the new key at the provider
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Revoke a leaked key through the provider's admin API
+    Revoke {
+        /// Instance ID, or hash (prefix) of a key reported by scan
+        target: String,
+
+        /// Admin key for the provider (defaults to the provider's env var)
+        #[arg(long)]
+        admin_key: Option<String>,
+
+        /// Revoke without confirmation
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Move discovered keys into the aicred store and out of the files they were found in
+    Cleanup {
+        /// Hash (prefix) of a key reported by scan; repeat to select several
+        #[arg(long = "hash")]
+        hashes: Vec<String>,
+
+        /// Select all keys of a provider
+        #[arg(long)]
+        provider: Option<String>,
+
+        /// Select every discovered key
+        #[arg(long)]
+        all: bool,
+
+        /// Secret store for the key (file, keyring, vault; default: file)
+        #[arg(long)]
+        store: Option<String>,
+
+        /// Environment variable referenced in place of the key (defaults to <PROVIDER>_API_KEY)
+        #[arg(long)]
+        env_var: Option<String>,
+
+        /// Store the key without rewriting the source files
+        #[arg(long)]
+        no_rewrite: bool,
+
+        /// Clean up without confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Print the hash scan reports for a key, to match findings without sharing the key
+    Fingerprint {
+        /// Key to fingerprint; read from stdin if omitted or '-'
+        key: Option<String>,
+
+        /// Hash algorithm (sha256, hmac-sha256; default: from config.yaml, else sha256)
+        #[arg(long)]
+        algorithm: Option<HashAlgorithm>,
+    },
+
     /// Show version information
     Version,
 
@@ -156,18 +487,61 @@ enum Commands {
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         command: Vec<String>,
 
-        /// Dry run - show environment variables without executing command
-        #[arg(long)]
-        dry_run: bool,
-
         /// Generate shell export statements instead of executing command
         #[arg(long)]
         setenv: bool,
 
-        /// Output format for shell exports (bash, fish, powershell) - only used with --setenv
+        /// Output format for shell exports (bash, zsh, fish, powershell) - only used with --setenv
         #[arg(long)]
         format: Option<String>,
     },
+
+    /// Print statements setting provider environment variables, e.g. eval "$(aicred setenv)"
+    Setenv {
+        /// Shell to generate for (bash, zsh, fish, powershell); defaults to $SHELL
+        #[arg(long)]
+        shell: Option<String>,
+
+        /// Set the variables of the instance and model a label points at
+        #[arg(long, conflicts_with = "instances", add = ArgValueCandidates::new(completions::label_names))]
+        label: Option<String>,
+
+        /// Set the variables of these instances (repeatable)
+        #[arg(long = "instance", add = ArgValueCandidates::new(completions::instance_ids))]
+        instances: Vec<String>,
+
+        /// Scanner whose tool's variables to set (e.g., gsh, roo-code)
+        #[arg(long, short = 's', conflicts_with_all = ["label", "instances"])]
+        scanner_names: Option<Vec<String>>,
+    },
+
+    /// Interactive dashboard of instances, models, labels and scan findings
+    Tui,
+
+    /// Check the configuration and environment for problems and suggest fixes
+    Doctor {
+        /// Skip checks that need the network, such as base URL reachability
+        #[arg(long)]
+        offline: bool,
+
+        /// Connection timeout for reachability checks in seconds
+        #[arg(long, default_value = "5")]
+        timeout: u64,
+
+        /// Most instances probed at once (default: all)
+        #[arg(long)]
+        parallelism: Option<usize>,
+
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
+    },
+
+    /// Print a completion script, e.g. source <(aicred completions bash)
+    Completions {
+        /// Shell to complete in (bash, zsh, fish, powershell, elvish)
+        shell: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -187,12 +561,16 @@ enum InstanceCommands {
         active_only: bool,
 
         /// Filter by tag name
-        #[arg(long)]
+        #[arg(long, add = ArgValueCandidates::new(completions::tag_names))]
         tag: Option<String>,
 
         /// Filter by label name
-        #[arg(long)]
+        #[arg(long, add = ArgValueCandidates::new(completions::label_names))]
         label: Option<String>,
+
+        /// Output format (table, json, csv, markdown)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
     },
 
     /// Add a new provider instance
@@ -224,13 +602,21 @@ enum InstanceCommands {
         /// Set instance as active
         #[arg(long, default_value = "true")]
         active: bool,
+
+        /// Where to store the API key: file (inline, the default), keyring (OS keyring) or vault (HashiCorp Vault)
+        #[arg(long)]
+        store: Option<String>,
     },
 
-    /// Remove a provider instance by ID
+    /// Remove a provider instance by ID, or every instance with the given tags
     Remove {
         /// Instance ID to remove
-        #[arg(short = 'i', long)]
-        id: String,
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids), required_unless_present = "tag")]
+        id: Option<String>,
+
+        /// Remove the instances carrying this tag (repeatable; all must match)
+        #[arg(long, add = ArgValueCandidates::new(completions::tag_names))]
+        tag: Vec<String>,
 
         /// Force removal without confirmation
         #[arg(long)]
@@ -240,7 +626,7 @@ enum InstanceCommands {
     /// Update an existing provider instance
     Update {
         /// Instance ID to update
-        #[arg(short = 'i', long)]
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids))]
         id: String,
 
         /// New display name
@@ -267,29 +653,72 @@ enum InstanceCommands {
     /// Show detailed information for a specific instance
     Get {
         /// Instance ID to show (positional argument)
+        #[arg(add = ArgValueCandidates::new(completions::instance_ids))]
         id: String,
 
         /// Include full secret values (DANGEROUS - use with caution)
         #[arg(long)]
         include_values: bool,
+
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
     },
 
     /// Validate provider instance configurations
     Validate {
         /// Validate specific instance by ID
-        #[arg(short = 'i', long)]
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids))]
         id: Option<String>,
 
         /// Show all validation errors, not just the first
         #[arg(long)]
         all_errors: bool,
     },
+
+    /// Activate or deactivate instances by ID or tag
+    SetActive {
+        /// Instance ID to change (repeatable)
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids), required_unless_present = "tag")]
+        id: Vec<String>,
+
+        /// Change the instances carrying this tag (repeatable; all must match)
+        #[arg(long, add = ArgValueCandidates::new(completions::tag_names))]
+        tag: Vec<String>,
+
+        /// Whether the instances should be active
+        #[arg(long, action = clap::ArgAction::Set)]
+        active: bool,
+    },
+
+    /// Probe instances live (key, model list, latency) and record the result
+    Check {
+        /// Check a specific instance by ID (default: all active instances)
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids), conflicts_with = "tag")]
+        id: Option<String>,
+
+        /// Check the active instances carrying this tag (repeatable; all must match)
+        #[arg(long, add = ArgValueCandidates::new(completions::tag_names))]
+        tag: Vec<String>,
+
+        /// Request timeout in seconds
+        #[arg(long, default_value = "10")]
+        timeout: u64,
+
+        /// Most instances probed at once (default: all)
+        #[arg(long)]
+        parallelism: Option<usize>,
+    },
 }
 
 #[derive(Subcommand)]
 enum TagCommands {
     /// List all tags
-    List,
+    List {
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
+    },
 
     /// Add a new tag
     Add {
@@ -309,7 +738,7 @@ enum TagCommands {
     /// Remove a tag
     Remove {
         /// Tag name to remove
-        #[arg(short = 'n', long)]
+        #[arg(short = 'n', long, add = ArgValueCandidates::new(completions::tag_names))]
         name: String,
 
         /// Force removal without confirmation
@@ -320,7 +749,7 @@ enum TagCommands {
     /// Update a tag
     Update {
         /// Tag name to update
-        #[arg(short = 'n', long)]
+        #[arg(short = 'n', long, add = ArgValueCandidates::new(completions::tag_names))]
         name: String,
 
         /// New tag color
@@ -335,11 +764,11 @@ enum TagCommands {
     /// Assign a tag to an instance or model
     Assign {
         /// Tag name to assign
-        #[arg(short = 'n', long)]
+        #[arg(short = 'n', long, add = ArgValueCandidates::new(completions::tag_names))]
         name: String,
 
         /// Instance ID
-        #[arg(short = 'i', long)]
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids))]
         instance: Option<String>,
 
         /// Model ID (requires instance ID)
@@ -350,11 +779,11 @@ enum TagCommands {
     /// Unassign a tag from an instance or model
     Unassign {
         /// Tag name to unassign
-        #[arg(short = 'n', long)]
+        #[arg(short = 'n', long, add = ArgValueCandidates::new(completions::tag_names))]
         name: String,
 
         /// Instance ID
-        #[arg(short = 'i', long)]
+        #[arg(short = 'i', long, add = ArgValueCandidates::new(completions::instance_ids))]
         instance: Option<String>,
 
         /// Model ID (requires instance ID)
@@ -366,11 +795,16 @@ enum TagCommands {
 #[derive(Subcommand)]
 enum LabelCommands {
     /// List all label assignments
-    List,
+    List {
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
+    },
 
     /// Set (create or update) a label assignment
     Set {
-        /// Label assignment in format: label=provider:model
+        /// Label assignment in format: label=provider:model, with fallbacks
+        /// tried in order after commas (e.g., smart=openrouter:claude-3.5,openai:gpt-4o)
         #[arg(index = 1, required = true)]
         assignment: String,
 
@@ -381,12 +815,17 @@ enum LabelCommands {
         /// Label description
         #[arg(short = 'd', long)]
         description: Option<String>,
+
+        /// Capabilities of the cheapest configured model to use when no
+        /// target is available (e.g., vision,function_calling)
+        #[arg(long, value_delimiter = ',')]
+        requires: Vec<String>,
     },
 
     /// Unset (remove) a label assignment
     Unset {
         /// Label name to remove
-        #[arg(index = 1, required = true)]
+        #[arg(index = 1, required = true, add = ArgValueCandidates::new(completions::label_names))]
         name: String,
 
         /// Force removal without confirmation
@@ -396,16 +835,236 @@ enum LabelCommands {
 
     /// Scan for label assignments based on regex patterns in conf/labels/*.scan files
     Scan {
-        /// Dry run - show what would be assigned without making changes
-        #[arg(long)]
-        dry_run: bool,
-
         /// Verbose output - show detailed matching information
         #[arg(long, short = 'v')]
         verbose: bool,
     },
 }
 
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Check the hash chain of an audit log written with --audit-chain
+    Verify {
+        /// Audit log file
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesCommands {
+    /// Import rules from a gitleaks TOML configuration
+    Import {
+        /// gitleaks configuration file (e.g. gitleaks.toml)
+        file: String,
+
+        /// Import rules for every provider, not only AI providers
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// List custom detection rules
+    List,
+
+    /// Remove a custom detection rule
+    Remove {
+        /// Rule ID
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Upgrade stored configuration files to the current schema version and
+    /// rename instances with IDs created before the 12-hex scheme
+    Migrate,
+
+    /// Encrypt stored instance API keys
+    Encrypt {
+        /// Identity file holding the key (default: ~/.config/aicred/identity.key, created if missing)
+        #[arg(long, value_name = "FILE", conflicts_with = "passphrase")]
+        identity: Option<PathBuf>,
+
+        /// Use a passphrase from the AICRED_PASSPHRASE environment variable instead
+        #[arg(long)]
+        passphrase: bool,
+    },
+
+    /// Decrypt stored instance API keys back to plain text
+    Decrypt {
+        /// Identity file holding the key (default: ~/.config/aicred/identity.key)
+        #[arg(long, value_name = "FILE", conflicts_with = "passphrase")]
+        identity: Option<PathBuf>,
+
+        /// Use a passphrase from the AICRED_PASSPHRASE environment variable instead
+        #[arg(long)]
+        passphrase: bool,
+    },
+
+    /// Write instances, tags, labels and custom rules to an encrypted bundle
+    ExportBundle {
+        /// Bundle file to write (default: stdout)
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<String>,
+
+        /// Include instance API keys (resolved from the keyring or encryption)
+        #[arg(long)]
+        include_secrets: bool,
+
+        /// Identity file to encrypt the bundle with (default: ~/.config/aicred/identity.key)
+        #[arg(long, value_name = "FILE", conflicts_with = "passphrase")]
+        identity: Option<PathBuf>,
+
+        /// Encrypt with a passphrase from the AICRED_PASSPHRASE environment variable instead
+        #[arg(long)]
+        passphrase: bool,
+    },
+
+    /// Merge an encrypted bundle into the configuration
+    ImportBundle {
+        /// Bundle file to read ('-' for stdin)
+        #[arg(value_name = "FILE")]
+        file: String,
+
+        /// What to do with items that differ locally (skip, overwrite, rename)
+        #[arg(long, default_value = "skip")]
+        on_conflict: String,
+
+        /// Identity file the bundle was encrypted with (default: ~/.config/aicred/identity.key)
+        #[arg(long, value_name = "FILE", conflicts_with = "passphrase")]
+        identity: Option<PathBuf>,
+
+        /// Decrypt with a passphrase from the AICRED_PASSPHRASE environment variable instead
+        #[arg(long)]
+        passphrase: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum UsageCommands {
+    /// Record tokens a tool used
+    Record {
+        /// Model used (any ID the registry resolves, e.g., openai/gpt-4o)
+        #[arg(long, short = 'm')]
+        model: String,
+
+        /// Number of input tokens (e.g., 1500, 200k, 1M)
+        #[arg(long)]
+        input_tokens: Option<String>,
+
+        /// Number of output tokens (e.g., 1500, 200k, 1M)
+        #[arg(long)]
+        output_tokens: Option<String>,
+
+        /// Instance the requests went through
+        #[arg(long, add = ArgValueCandidates::new(completions::instance_ids))]
+        instance: Option<String>,
+
+        /// Label the tool resolved; also attributes the usage to its instance
+        #[arg(long, add = ArgValueCandidates::new(completions::label_names))]
+        label: Option<String>,
+
+        /// Cost the provider reported (default: priced from the model registry)
+        #[arg(long)]
+        cost: Option<f64>,
+
+        /// Tool reporting the usage
+        #[arg(long)]
+        source: Option<String>,
+    },
+
+    /// Total the recorded usage and its cost
+    Report {
+        /// Group by instance, label or day
+        #[arg(long, default_value = "instance")]
+        by: UsageGrouping,
+
+        /// Only count usage from this day on (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BudgetCommands {
+    /// List budgets and how much of them is spent this month
+    List {
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Set the monthly budget of an instance or label
+    Set {
+        /// Instance to budget
+        #[arg(long, conflicts_with = "label", required_unless_present = "label", add = ArgValueCandidates::new(completions::instance_ids))]
+        instance: Option<String>,
+
+        /// Label to budget
+        #[arg(long, add = ArgValueCandidates::new(completions::label_names))]
+        label: Option<String>,
+
+        /// Most to spend per calendar month (e.g., 50 for $50)
+        #[arg(long)]
+        monthly: f64,
+
+        /// Refuse to hand out keys once the budget is spent, instead of warning
+        #[arg(long)]
+        refuse: bool,
+    },
+
+    /// Remove the budget of an instance or label
+    Remove {
+        /// Instance whose budget to remove
+        #[arg(long, conflicts_with = "label", required_unless_present = "label", add = ArgValueCandidates::new(completions::instance_ids))]
+        instance: Option<String>,
+
+        /// Label whose budget to remove
+        #[arg(long, add = ArgValueCandidates::new(completions::label_names))]
+        label: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// List all profiles, marking the active one
+    List,
+
+    /// Create a new profile
+    Create {
+        /// Profile name (letters, digits, '-' and '_')
+        name: String,
+
+        /// Copy instances, tags and labels from this profile
+        #[arg(long, add = ArgValueCandidates::new(completions::profile_names))]
+        from: Option<String>,
+    },
+
+    /// Make a profile the active one
+    Use {
+        /// Profile name ('default' for the base configuration)
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        name: String,
+    },
+
+    /// Show the active profile
+    Show,
+
+    /// Delete a profile and everything stored in it
+    Delete {
+        /// Profile name
+        #[arg(add = ArgValueCandidates::new(completions::profile_names))]
+        name: String,
+
+        /// Delete without confirmation
+        #[arg(long)]
+        force: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum ModelCommands {
     /// List all models with their configurations
@@ -419,15 +1078,104 @@ enum ModelCommands {
         provider_type: Option<String>,
 
         /// Filter by tag name
-        #[arg(long)]
+        #[arg(long, add = ArgValueCandidates::new(completions::tag_names))]
         tag: Option<String>,
 
         /// Filter by label name
-        #[arg(long)]
+        #[arg(long, add = ArgValueCandidates::new(completions::label_names))]
         label: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(long, short = 'f')]
+        format: Option<String>,
+    },
+
+    /// Compare the capabilities and pricing of models side by side
+    Compare {
+        /// Model IDs to compare
+        #[arg(required = true, num_args = 1..)]
+        models: Vec<String>,
+
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Query the model registry, including models no instance offers
+    Registry {
+        /// Only models of this provider type (e.g., openai, anthropic)
+        #[arg(long)]
+        provider: Option<String>,
+
+        /// Required capabilities, repeatable or comma separated (e.g., vision)
+        #[arg(long, value_delimiter = ',')]
+        capability: Vec<String>,
+
+        /// Text the model ID or name must contain
+        #[arg(long)]
+        search: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Rank the configured models meeting requirements, cheapest first
+    Recommend {
+        /// Required capabilities, comma separated (e.g., vision,function_calling)
+        #[arg(long, value_delimiter = ',')]
+        needs: Vec<String>,
+
+        /// Highest price per million input tokens (USD)
+        #[arg(long)]
+        max_input_cost: Option<f64>,
+
+        /// Highest price per million output tokens (USD)
+        #[arg(long)]
+        max_output_cost: Option<f64>,
+
+        /// Smallest context window in tokens
+        #[arg(long)]
+        min_context: Option<u32>,
+
+        /// Include models none of the configured instances offer
+        #[arg(long)]
+        all: bool,
+
+        /// Number of models to show
+        #[arg(long, default_value = "10")]
+        limit: usize,
+
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Fetch current model pricing and context windows from OpenRouter and
+    /// the configured instances' providers
+    Refresh {
+        /// Fetch even if the cached model data is still fresh
+        #[arg(long)]
+        force: bool,
+
+        /// Use the cached model data, however old, without network calls
+        #[arg(long, conflicts_with = "force")]
+        offline: bool,
+
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
     },
 }
 
+/// Formats of `scan`, for the configured default format
+const SCAN_FORMATS: &[&str] = &[
+    "json", "ndjson", "table", "summary", "csv", "markdown", "md",
+];
+
+/// Formats of `instances list`, for the configured default format
+const INSTANCE_LIST_FORMATS: &[&str] = &["table", "json", "csv", "markdown", "md"];
+
 fn main() -> Result<()> {
     // Initialize tracing with environment filter
     tracing_subscriber::fmt()
@@ -435,46 +1183,105 @@ fn main() -> Result<()> {
         .with_target(false)
         .init();
 
+    // Answer completion requests from the script printed by 'aicred completions'
+    CompleteEnv::with_factory(Cli::command)
+        .var(completions::COMPLETE_VAR)
+        .complete();
+
     let cli = Cli::parse();
+    if let Some(profile) = cli.profile.clone() {
+        utils::provider_loader::set_profile_override(profile);
+    }
+    let settings = config::CliConfig::load(cli.home.as_deref().map(std::path::Path::new))?;
+    let table_or_json =
+        |format: Option<String>| settings.format(format, &["table", "json"], "table");
+    let dry_run = cli.dry_run;
+    let plan = if dry_run {
+        Some(plan::PlanFormat::parse(&cli.plan_format)?)
+    } else {
+        None
+    };
 
     match cli.command {
         Commands::Scan {
             home: scan_home,
             format,
             include_values,
+            redaction,
+            hash_algorithm,
             only,
             exclude,
+            only_types,
             max_bytes_per_file,
-            dry_run,
             audit_log,
+            audit_chain,
             verbose,
             update,
             no_probe,
             probe_timeout,
-        } => handle_scan(
-            scan_home.or(cli.home),
-            format,
-            include_values,
+            write_baseline,
+            baseline,
+            policy,
+            min_risk,
+            sort,
+            stale_days,
+            roots,
+            scan_archives,
+            no_history,
+            no_notify,
+            offline,
+        } => handle_scan(ScanArgs {
+            home: scan_home.or(cli.home),
+            format: settings.format(format, SCAN_FORMATS, "table"),
+            redaction: settings.redaction(redaction, include_values),
+            hash_algorithm: settings.hash_algorithm(hash_algorithm),
             only,
-            exclude,
+            exclude: settings.exclude(exclude),
+            only_types,
+            scanner_settings: settings.scanners.clone(),
+            network: settings.network.clone(),
             max_bytes_per_file,
             dry_run,
             audit_log,
+            audit_chain,
             verbose,
             update,
-            !no_probe, // Invert: probing is enabled by default unless --no-probe is specified
+            // Invert: probing is enabled by default unless --no-probe is specified
+            probe_models: !no_probe,
             probe_timeout,
+            write_baseline,
+            baseline,
+            policy,
+            min_risk,
+            sort,
+            stale_days,
+            roots,
+            scan_archives,
+            no_history,
+            notify: settings.notify(no_notify || offline),
+            offline,
+        }),
+        Commands::History {
+            keys,
+            limit,
+            format,
+        } => handle_history(
+            keys,
+            limit,
+            table_or_json(format),
+            cli.home.map(PathBuf::from).as_deref(),
         ),
-        Commands::Providers { verbose } => {
+        Commands::Providers { verbose, format } => {
             // Set home directory if provided
             if let Some(home) = &cli.home {
                 std::env::set_var("HOME", home);
             }
-            handle_providers(verbose)
+            handle_providers(verbose, table_or_json(format))
         }
         Commands::Instances {
             id,
             include_values,
+            format,
             command,
         } => match (id.as_ref(), command) {
             (Some(instance_id), None) => {
@@ -483,11 +1290,20 @@ fn main() -> Result<()> {
                     cli.home.map(PathBuf::from),
                     instance_id.clone(),
                     include_values,
+                    table_or_json(format),
                 )
             }
             (None, None) => {
                 // Default to list when no subcommand and no ID is provided
-                handle_list_instances(cli.home.map(PathBuf::from), false, None, false, None, None)
+                handle_list_instances(
+                    cli.home.map(PathBuf::from),
+                    false,
+                    None,
+                    false,
+                    None,
+                    None,
+                    table_or_json(format),
+                )
             }
             (
                 _,
@@ -497,6 +1313,7 @@ fn main() -> Result<()> {
                     active_only,
                     tag,
                     label,
+                    format,
                 }),
             ) => handle_list_instances(
                 cli.home.map(PathBuf::from),
@@ -505,6 +1322,7 @@ fn main() -> Result<()> {
                 active_only,
                 tag,
                 label,
+                settings.format(format, INSTANCE_LIST_FORMATS, "table"),
             ),
             (
                 _,
@@ -516,9 +1334,29 @@ fn main() -> Result<()> {
                     api_key,
                     models,
                     active,
+                    store,
                 }),
-            ) => handle_add_instance(id, name, provider_type, base_url, api_key, models, active),
-            (_, Some(InstanceCommands::Remove { id, force })) => handle_remove_instance(id, force),
+            ) => handle_add_instance(
+                id,
+                name,
+                provider_type,
+                base_url,
+                api_key,
+                models,
+                active,
+                settings.secret_backend(store),
+                plan,
+            ),
+            (_, Some(InstanceCommands::Remove { id, tag, force })) => {
+                handle_remove_instance(id, tag, force, plan, cli.home.map(PathBuf::from).as_deref())
+            }
+            (_, Some(InstanceCommands::SetActive { id, tag, active })) => handle_set_active(
+                id,
+                tag,
+                active,
+                plan,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
             (
                 _,
                 Some(InstanceCommands::Update {
@@ -529,16 +1367,45 @@ fn main() -> Result<()> {
                     models,
                     active,
                 }),
-            ) => handle_update_instance(id, name, base_url, api_key, models, active),
-            (_, Some(InstanceCommands::Get { id, include_values })) => {
-                handle_get_instance(cli.home.map(PathBuf::from), id, include_values)
-            }
+            ) => handle_update_instance(id, name, base_url, api_key, models, active, plan),
+            (
+                _,
+                Some(InstanceCommands::Get {
+                    id,
+                    include_values,
+                    format,
+                }),
+            ) => handle_get_instance(
+                cli.home.map(PathBuf::from),
+                id,
+                include_values,
+                table_or_json(format),
+            ),
             (_, Some(InstanceCommands::Validate { id, all_errors })) => {
                 handle_validate_instances(id, all_errors)
             }
+            (
+                _,
+                Some(InstanceCommands::Check {
+                    id,
+                    tag,
+                    timeout,
+                    parallelism,
+                }),
+            ) => handle_check_instances(
+                id,
+                tag,
+                timeout,
+                parallelism.or(settings.parallelism),
+                &settings.network,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
         },
         Commands::Tags { command } => match command {
-            Some(TagCommands::List) => handle_list_tags(cli.home.map(PathBuf::from).as_deref()),
+            Some(TagCommands::List { format }) => handle_list_tags(
+                table_or_json(format),
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
             Some(TagCommands::Add {
                 name,
                 color,
@@ -582,14 +1449,15 @@ fn main() -> Result<()> {
                 model,
                 cli.home.map(PathBuf::from).as_deref(),
             ),
-            None => handle_list_tags(cli.home.map(PathBuf::from).as_deref()),
+            None => handle_list_tags("table".to_string(), cli.home.map(PathBuf::from).as_deref()),
         },
         Commands::Labels { command } => match command {
-            Some(LabelCommands::List) => handle_list_labels(),
+            Some(LabelCommands::List { format }) => handle_list_labels(table_or_json(format)),
             Some(LabelCommands::Set {
                 assignment,
                 color,
                 description,
+                requires,
             }) => {
                 // Parse assignment format: label=provider:model
                 let parts: Vec<&str> = assignment.split('=').collect();
@@ -605,16 +1473,17 @@ fn main() -> Result<()> {
                     tuple_str,
                     color,
                     description,
+                    requires,
                     cli.home.map(PathBuf::from).as_deref(),
                 )
             }
             Some(LabelCommands::Unset { name, force }) => {
                 handle_unset_label(name, force, cli.home.map(PathBuf::from).as_deref())
             }
-            Some(LabelCommands::Scan { dry_run, verbose }) => {
+            Some(LabelCommands::Scan { verbose }) => {
                 handle_label_scan(dry_run, verbose, cli.home.map(PathBuf::from).as_deref())
             }
-            None => handle_list_labels(),
+            None => handle_list_labels("table".to_string()),
         },
         Commands::Models { command } => match command {
             Some(ModelCommands::List {
@@ -622,20 +1491,282 @@ fn main() -> Result<()> {
                 provider_type,
                 tag,
                 label,
+                format,
             }) => handle_list_models(
                 cli.home.map(PathBuf::from),
                 verbose,
                 provider_type,
                 tag,
                 label,
+                table_or_json(format),
             ),
-            None => handle_list_models(cli.home.map(PathBuf::from), false, None, None, None),
+            Some(ModelCommands::Compare { models, format }) => {
+                handle_compare_models(models, format, cli.home.map(PathBuf::from).as_deref())
+            }
+            Some(ModelCommands::Registry {
+                provider,
+                capability,
+                search,
+                format,
+            }) => handle_registry_models(
+                provider,
+                capability,
+                search,
+                format,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
+            Some(ModelCommands::Recommend {
+                needs,
+                max_input_cost,
+                max_output_cost,
+                min_context,
+                all,
+                limit,
+                format,
+            }) => handle_recommend_models(
+                needs,
+                max_input_cost,
+                max_output_cost,
+                min_context,
+                all,
+                limit,
+                format,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
+            Some(ModelCommands::Refresh {
+                force,
+                offline,
+                format,
+            }) => handle_refresh_models(
+                force,
+                offline,
+                format,
+                &settings.network,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
+            None => handle_list_models(
+                cli.home.map(PathBuf::from),
+                false,
+                None,
+                None,
+                None,
+                "table".to_string(),
+            ),
+        },
+        Commands::Resolve { label, format } => {
+            handle_resolve(label, format, cli.home.map(PathBuf::from).as_deref())
+        }
+        Commands::Cost {
+            models,
+            input_tokens,
+            output_tokens,
+            usage,
+            format,
+        } => handle_cost(
+            models,
+            input_tokens,
+            output_tokens,
+            usage,
+            format,
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Usage { command } => {
+            let home = cli.home.map(PathBuf::from);
+            match command {
+                Some(UsageCommands::Record {
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    instance,
+                    label,
+                    cost,
+                    source,
+                }) => handle_record_usage(
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    instance,
+                    label,
+                    cost,
+                    source,
+                    home.as_deref(),
+                ),
+                Some(UsageCommands::Report { by, since, format }) => {
+                    handle_usage_report(by, since, format, home.as_deref())
+                }
+                None => handle_usage_report(
+                    UsageGrouping::Instance,
+                    None,
+                    "table".to_string(),
+                    home.as_deref(),
+                ),
+            }
+        }
+        Commands::Budget { command } => {
+            let home = cli.home.map(PathBuf::from);
+            match command {
+                Some(BudgetCommands::List { format }) => {
+                    handle_list_budgets(format, home.as_deref())
+                }
+                Some(BudgetCommands::Set {
+                    instance,
+                    label,
+                    monthly,
+                    refuse,
+                }) => handle_set_budget(instance, label, monthly, refuse, home.as_deref()),
+                Some(BudgetCommands::Remove { instance, label }) => {
+                    handle_remove_budget(instance, label, home.as_deref())
+                }
+                None => handle_list_budgets("table".to_string(), home.as_deref()),
+            }
+        }
+        Commands::Profile { command } => {
+            let home = cli.home.map(PathBuf::from);
+            match command {
+                Some(ProfileCommands::List) | None => handle_list_profiles(home.as_deref()),
+                Some(ProfileCommands::Create { name, from }) => {
+                    handle_create_profile(name, from, home.as_deref())
+                }
+                Some(ProfileCommands::Use { name }) => handle_use_profile(name, home.as_deref()),
+                Some(ProfileCommands::Show) => handle_show_profile(home.as_deref()),
+                Some(ProfileCommands::Delete { name, force }) => {
+                    handle_delete_profile(name, force, home.as_deref())
+                }
+            }
+        }
+        Commands::Config { command } => match command {
+            ConfigCommands::Migrate => {
+                handle_migrate(dry_run, cli.home.map(PathBuf::from).as_deref())
+            }
+            ConfigCommands::Encrypt {
+                identity,
+                passphrase,
+            } => handle_encrypt(identity, passphrase, cli.home.map(PathBuf::from).as_deref()),
+            ConfigCommands::Decrypt {
+                identity,
+                passphrase,
+            } => handle_decrypt(identity, passphrase, cli.home.map(PathBuf::from).as_deref()),
+            ConfigCommands::ExportBundle {
+                output,
+                include_secrets,
+                identity,
+                passphrase,
+            } => handle_export_bundle(
+                output,
+                include_secrets,
+                identity,
+                passphrase,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
+            ConfigCommands::ImportBundle {
+                file,
+                on_conflict,
+                identity,
+                passphrase,
+            } => handle_import_bundle(
+                file,
+                on_conflict,
+                dry_run,
+                identity,
+                passphrase,
+                cli.home.map(PathBuf::from).as_deref(),
+            ),
+        },
+        Commands::Export {
+            target,
+            format,
+            output,
+            env_refs,
+            merge,
+            servers,
+            label,
+            instances,
+            tag,
+            no_values,
+        } => handle_export(
+            target.or(format).unwrap_or_default(),
+            output,
+            env_refs,
+            merge,
+            servers,
+            label,
+            instances,
+            tag,
+            no_values,
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Daemon { socket } => {
+            handle_daemon(socket, cli.home.map(PathBuf::from).as_deref())
+        }
+        Commands::Mcp => handle_mcp(cli.home.map(PathBuf::from).as_deref()),
+        Commands::Audit { command } => match command {
+            AuditCommands::Verify { file } => handle_verify_audit_log(file),
+        },
+        Commands::Rules { command } => match command {
+            RulesCommands::Import { file, all } => {
+                handle_import_rules(file, all, dry_run, cli.home.map(PathBuf::from).as_deref())
+            }
+            RulesCommands::List => handle_list_rules(cli.home.map(PathBuf::from).as_deref()),
+            RulesCommands::Remove { id } => {
+                handle_remove_rule(id, cli.home.map(PathBuf::from).as_deref())
+            }
         },
+        Commands::Wizard { answers } => {
+            handle_wizard(answers, plan, cli.home.map(PathBuf::from).as_deref())
+        }
+        Commands::Rotate {
+            instance_id,
+            revoke_old,
+            admin_key,
+            name,
+        } => handle_rotate(
+            instance_id,
+            revoke_old,
+            admin_key,
+            name,
+            plan,
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Revoke {
+            target,
+            admin_key,
+            force,
+        } => handle_revoke(
+            target,
+            admin_key,
+            force,
+            settings.hash_algorithm(None),
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Cleanup {
+            hashes,
+            provider,
+            all,
+            store,
+            env_var,
+            no_rewrite,
+            yes,
+        } => handle_cleanup(
+            hashes,
+            provider,
+            all,
+            settings.secret_backend(store),
+            env_var,
+            no_rewrite,
+            plan,
+            yes,
+            settings.hash_algorithm(None),
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Fingerprint { key, algorithm } => handle_fingerprint(
+            key,
+            settings.hash_algorithm(algorithm),
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
         Commands::Version => handle_version(),
         Commands::Wrap {
             scanner_names,
             command,
-            dry_run,
             setenv,
             format,
         } => handle_wrap(
@@ -646,6 +1777,33 @@ fn main() -> Result<()> {
             setenv,
             format,
         ),
+        Commands::Setenv {
+            shell,
+            label,
+            instances,
+            scanner_names,
+        } => handle_setenv(
+            shell,
+            label,
+            instances,
+            scanner_names,
+            dry_run,
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Tui => handle_tui(cli.home.map(PathBuf::from).as_deref()),
+        Commands::Doctor {
+            offline,
+            timeout,
+            parallelism,
+            format,
+        } => handle_doctor(
+            offline,
+            timeout,
+            parallelism.or(settings.parallelism),
+            table_or_json(format),
+            cli.home.map(PathBuf::from).as_deref(),
+        ),
+        Commands::Completions { shell } => handle_completions(shell),
     }
 }
 
@@ -0,0 +1,37 @@
+use crate::output::rows::{finding_rows, instance_rows, FINDING_HEADERS, INSTANCE_HEADERS};
+use aicred_core::models::ProviderInstance;
+use aicred_core::ScanResult;
+use anyhow::Result;
+
+pub fn output_csv(result: &ScanResult, _verbose: bool) -> Result<()> {
+    print!("{}", render(&FINDING_HEADERS, &finding_rows(result)));
+    Ok(())
+}
+
+pub fn output_instances_csv(instances: &[&ProviderInstance]) -> Result<()> {
+    print!("{}", render(&INSTANCE_HEADERS, &instance_rows(instances)));
+    Ok(())
+}
+
+/// Render a header line and rows as RFC 4180 CSV
+fn render(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let header: Vec<String> = headers.iter().map(|h| escape(h)).collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| escape(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quote a field if it contains a delimiter, quote or line break
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,48 @@
+use crate::output::rows::{finding_rows, instance_rows, FINDING_HEADERS, INSTANCE_HEADERS};
+use aicred_core::models::ProviderInstance;
+use aicred_core::ScanResult;
+use anyhow::Result;
+
+pub fn output_markdown(result: &ScanResult, _verbose: bool) -> Result<()> {
+    println!("## Discovered Credentials\n");
+    print!("{}", render(&FINDING_HEADERS, &finding_rows(result)));
+    println!(
+        "\n{} findings in {} application configurations",
+        result.keys.len(),
+        result.config_instances.len()
+    );
+    Ok(())
+}
+
+pub fn output_instances_markdown(instances: &[&ProviderInstance]) -> Result<()> {
+    println!("## Provider Instances\n");
+    print!("{}", render(&INSTANCE_HEADERS, &instance_rows(instances)));
+    Ok(())
+}
+
+/// Render a header line and rows as a GitHub-flavored Markdown table
+fn render(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let header: Vec<String> = headers.iter().map(|h| escape(h)).collect();
+    out.push_str(&format!("| {} |\n", header.join(" | ")));
+    out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| {
+                if cell.is_empty() {
+                    "-".to_string()
+                } else {
+                    escape(cell)
+                }
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// Escape pipes and flatten line breaks so a value stays in its cell
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
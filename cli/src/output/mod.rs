@@ -1,4 +1,8 @@
+pub mod csv;
 pub mod json;
+pub mod markdown;
 pub mod ndjson;
+pub mod responses;
+mod rows;
 pub mod summary;
 pub mod table;
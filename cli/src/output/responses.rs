@@ -0,0 +1,168 @@
+//! Responses of the listing commands for `--format json`.
+//!
+//! These structs are the stable shape of the JSON output: fields are only ever
+//! added, never renamed or removed, so wrappers and the GUI can deserialize
+//! them instead of parsing the human tables.
+
+use aicred_core::models::{LabelAssignment, LabelTarget};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub use aicred_app::instances::InstanceEntry;
+
+/// Check a `--format` value of a command printing a table or JSON, returning
+/// whether JSON was asked for
+pub fn wants_json(format: &str) -> Result<bool> {
+    match format {
+        "json" => Ok(true),
+        "table" => Ok(false),
+        _ => bail!("Unknown format: {} (expected table or json)", format),
+    }
+}
+
+/// Print a response as pretty JSON
+pub fn print_json<T: Serialize>(response: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(response)?);
+    Ok(())
+}
+
+/// A provider or application scanner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderEntry {
+    pub name: String,
+    pub description: String,
+}
+
+/// Response of `aicred providers`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvidersResponse {
+    pub providers: Vec<ProviderEntry>,
+    pub scanners: Vec<ProviderEntry>,
+}
+
+/// Response of `aicred instances list`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstancesResponse {
+    pub instances: Vec<InstanceEntry>,
+}
+
+/// A model of a provider instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelEntry {
+    pub model_id: String,
+    pub instance_id: String,
+    pub provider_type: String,
+    pub tags: Vec<String>,
+    pub labels: Vec<String>,
+    /// Why the model should be replaced, if it is deprecated or archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Response of `aicred models list`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelsResponse {
+    pub models: Vec<ModelEntry>,
+}
+
+/// A tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response of `aicred tags list`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagsResponse {
+    pub tags: Vec<TagEntry>,
+}
+
+/// The instance, and optionally the model, a label points at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetEntry {
+    pub instance_id: String,
+    #[serde(default)]
+    pub model_id: Option<String>,
+}
+
+impl From<&LabelTarget> for TargetEntry {
+    fn from(target: &LabelTarget) -> Self {
+        Self {
+            instance_id: target.instance_id().to_string(),
+            model_id: target.model_id().map(str::to_string),
+        }
+    }
+}
+
+/// A label assignment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelEntry {
+    pub name: String,
+    pub target: TargetEntry,
+    /// Targets tried in order when the main one is unhealthy
+    #[serde(default)]
+    pub fallbacks: Vec<TargetEntry>,
+    /// Capabilities of the configured model used when no target is available
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub assigned_at: DateTime<Utc>,
+}
+
+impl LabelEntry {
+    pub fn new(assignment: &LabelAssignment, description: Option<String>) -> Self {
+        Self {
+            name: assignment.label_name.clone(),
+            target: TargetEntry::from(&assignment.target),
+            fallbacks: assignment.fallbacks.iter().map(TargetEntry::from).collect(),
+            requires: assignment.requires.clone(),
+            description,
+            assigned_at: assignment.assigned_at,
+        }
+    }
+}
+
+/// Response of `aicred labels list`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelsResponse {
+    pub labels: Vec<LabelEntry>,
+}
+
+/// The outcome of one doctor check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckEntry {
+    /// Section the check belongs to, e.g. `Keyring`
+    pub section: String,
+    /// `ok`, `skipped`, `warning` or `failed`
+    pub status: String,
+    pub message: String,
+    /// Command or step fixing the problem
+    #[serde(default)]
+    pub fix: Option<String>,
+}
+
+/// Response of `aicred doctor`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoctorResponse {
+    pub config_dir: String,
+    pub checks: Vec<CheckEntry>,
+    pub warnings: usize,
+    pub failures: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wants_json_checks_the_format() {
+        assert!(wants_json("json").unwrap());
+        assert!(!wants_json("table").unwrap());
+        assert!(wants_json("yaml").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Flat rows of scan findings and instances for the tabular report formats.
+
+use aicred_core::models::ProviderInstance;
+use aicred_core::ScanResult;
+
+/// Column headers of scan finding rows
+pub const FINDING_HEADERS: [&str; 8] = [
+    "Provider",
+    "Value Type",
+    "Confidence",
+    "Risk",
+    "Source",
+    "Line",
+    "Value",
+    "Hash",
+];
+
+/// Column headers of instance rows
+pub const INSTANCE_HEADERS: [&str; 5] = ["ID", "Provider", "Base URL", "Active", "Models"];
+
+/// One row per discovered value, shown as the scan's redaction mode left it
+pub fn finding_rows(result: &ScanResult) -> Vec<Vec<String>> {
+    result
+        .keys
+        .iter()
+        .map(|key| {
+            vec![
+                key.provider.clone(),
+                key.value_type.to_string(),
+                key.confidence.to_string(),
+                key.risk.to_string(),
+                key.source_file.clone(),
+                key.source_line.map(|l| l.to_string()).unwrap_or_default(),
+                key.full_value()
+                    .map_or_else(|| key.redacted_value(), str::to_string),
+                key.hash.chars().take(12).collect(),
+            ]
+        })
+        .collect()
+}
+
+/// One row per provider instance
+pub fn instance_rows(instances: &[&ProviderInstance]) -> Vec<Vec<String>> {
+    instances
+        .iter()
+        .map(|instance| {
+            vec![
+                instance.id.clone(),
+                instance.provider_type.clone(),
+                instance.base_url.clone(),
+                instance.active.to_string(),
+                instance.models.join(", "),
+            ]
+        })
+        .collect()
+}
@@ -11,6 +11,15 @@ pub fn output_summary(result: &ScanResult, verbose: bool) -> Result<(), anyhow::
 
     println!("\n{}", "Scan Summary".green().bold());
     println!("  Home Directory: {}", result.home_directory);
+    if let Some(roots) = result
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("scan_roots"))
+        .and_then(|roots| roots.as_array())
+    {
+        let roots: Vec<&str> = roots.iter().filter_map(|root| root.as_str()).collect();
+        println!("  Scan Roots: {}", roots.join(", "));
+    }
     println!("  Scan Time: {}", result.scan_completed_at);
     println!(
         "  Providers Scanned: {}",
@@ -126,7 +135,7 @@ pub fn output_summary(result: &ScanResult, verbose: bool) -> Result<(), anyhow::
         for instance in &result.config_instances {
             println!(
                 "  - {}: {}",
-                instance.app_name.cyan(),
+                instance.describe().cyan(),
                 instance.config_path.display()
             );
 
@@ -1,4 +1,4 @@
-use aicred_core::{models::Label, ScanResult};
+use aicred_core::{models::Label, RiskLevel, ScanResult};
 use colored::*;
 use tracing::debug;
 
@@ -17,15 +17,17 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
         if verbose {
             // Verbose mode: show settings and tags/labels columns
             println!(
-                "{:<15} {:<40} {:<25} {:<20} {:<15} {:<20}",
+                "{:<15} {:<10} {:<8} {:<40} {:<25} {:<20} {:<15} {:<20}",
                 "Provider".bold(),
+                "Risk".bold(),
+                "Age".bold(),
                 "Source".bold(),
                 "Models".bold(),
                 "Tags".bold(),
                 "Labels".bold(),
                 "Settings".bold()
             );
-            println!("{}", "-".repeat(140));
+            println!("{}", "-".repeat(160));
 
             for instance in &result.config_instances {
                 for provider_instance in instance.provider_instances() {
@@ -67,8 +69,13 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
                     };
 
                     println!(
-                        "{:<15} {:<40} {:<25} {:<20} {:<15} {:<20}",
+                        "{:<15} {:<10} {:<8} {:<40} {:<25} {:<20} {:<15} {:<20}",
                         provider_instance.provider_type.cyan(),
+                        risk_display(result.risk_for_source(
+                            &provider_instance.provider_type,
+                            &instance.config_path_string()
+                        )),
+                        age_display(result, &provider_instance.provider_type, instance),
                         truncate_path(&instance.config_path.display().to_string(), 40),
                         models_display,
                         tags_display,
@@ -76,11 +83,9 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
                         settings_display
                     );
 
-                    // Show API key if verbose and available
+                    // Show API key if verbose and available, as the scan redacted it
                     if let Some(api_key) = provider_instance.get_api_key() {
-                        if !api_key.is_empty() {
-                            println!("  API Key: {}", "********".yellow());
-                        }
+                        println!("  API Key: {}", api_key.yellow());
                     }
 
                     // Show tags and labels details if verbose
@@ -109,14 +114,16 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
         } else {
             // Normal mode: show tags and labels columns
             println!(
-                "{:<15} {:<40} {:<25} {:<20} {:<15}",
+                "{:<15} {:<10} {:<8} {:<40} {:<25} {:<20} {:<15}",
                 "Provider".bold(),
+                "Risk".bold(),
+                "Age".bold(),
                 "Source".bold(),
                 "Models".bold(),
                 "Tags".bold(),
                 "Labels".bold()
             );
-            println!("{}", "-".repeat(120));
+            println!("{}", "-".repeat(140));
 
             for instance in &result.config_instances {
                 for provider_instance in instance.provider_instances() {
@@ -146,8 +153,13 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
                     };
 
                     println!(
-                        "{:<15} {:<40} {:<25} {:<20} {:<15}",
+                        "{:<15} {:<10} {:<8} {:<40} {:<25} {:<20} {:<15}",
                         provider_instance.provider_type.cyan(),
+                        risk_display(result.risk_for_source(
+                            &provider_instance.provider_type,
+                            &instance.config_path_string()
+                        )),
+                        age_display(result, &provider_instance.provider_type, instance),
                         truncate_path(&instance.config_path.display().to_string(), 40),
                         models_display,
                         tags_display,
@@ -196,6 +208,10 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
                 truncate_path(&instance.config_path.display().to_string(), 48)
             );
 
+            if verbose && (instance.app_version.is_some() || !instance.settings.is_empty()) {
+                println!("  {}", instance.describe());
+            }
+
             // Show provider instances if verbose
             if verbose && !instance.provider_instances.is_empty() {
                 println!("  Providers configured:");
@@ -231,6 +247,31 @@ pub fn output_table(result: &ScanResult, verbose: bool) -> Result<(), anyhow::Er
     Ok(())
 }
 
+fn risk_display(risk: Option<RiskLevel>) -> ColoredString {
+    match risk {
+        Some(RiskLevel::Critical) => "Critical".red().bold(),
+        Some(RiskLevel::High) => "High".red(),
+        Some(RiskLevel::Medium) => "Medium".yellow(),
+        Some(RiskLevel::Low) => "Low".green(),
+        None => "-".dimmed(),
+    }
+}
+
+/// Shows the age of the oldest key backing a provider instance, in days
+fn age_display(
+    result: &ScanResult,
+    provider_type: &str,
+    instance: &aicred_core::ConfigInstance,
+) -> String {
+    let now = chrono::Utc::now();
+    let source = instance.config_path_string();
+    result
+        .keys_for_source(provider_type, &source)
+        .filter_map(|key| key.age_days(now))
+        .max()
+        .map_or_else(|| "-".dimmed().to_string(), |days| format!("{}d", days))
+}
+
 fn truncate_path(path: &str, max_len: usize) -> String {
     if path.chars().count() <= max_len {
         return path.to_string();
@@ -0,0 +1,72 @@
+//! Plans of the changes a mutating command would make.
+//!
+//! With the global `--dry-run`, mutating commands build a [`ChangePlan`]
+//! instead of writing anything and print it as text or, with
+//! `--plan-format json`, as JSON for wrappers and the GUI to render.
+
+pub use aicred_app::plan::{ChangeAction, ChangeKind, ChangePlan};
+use anyhow::{anyhow, Result};
+use colored::*;
+
+/// How a dry run prints its plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    Text,
+    Json,
+}
+
+impl PlanFormat {
+    /// Parse a `--plan-format` value
+    pub fn parse(format: &str) -> Result<Self> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow!(
+                "Unknown plan format: {} (expected text or json)",
+                format
+            )),
+        }
+    }
+}
+
+/// Print a plan
+pub fn print_plan(plan: &ChangePlan, format: PlanFormat) -> Result<()> {
+    if format == PlanFormat::Json {
+        println!("{}", serde_json::to_string_pretty(plan)?);
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!("Dry run - planned changes for 'aicred {}':", plan.command)
+            .yellow()
+            .bold()
+    );
+    if plan.changes.is_empty() {
+        println!("  {}", "Nothing would change.".dimmed());
+    }
+    for change in &plan.changes {
+        let action = match change.action {
+            ChangeAction::Create => "create".green(),
+            ChangeAction::Update => "update".yellow(),
+            ChangeAction::Delete => "delete".red(),
+        };
+        let kind = match change.kind {
+            ChangeKind::File => "file",
+            ChangeKind::Instance => "instance",
+            ChangeKind::Secret => "secret",
+            ChangeKind::ProviderKey => "provider key",
+            ChangeKind::Assignment => "assignment",
+        };
+        print!("  {} {} {}", action, kind, change.target.cyan());
+        match &change.path {
+            Some(path) => println!(" ({})", path.dimmed()),
+            None => println!(),
+        }
+        for detail in &change.details {
+            println!("      {}", detail);
+        }
+    }
+    println!("{}", "No changes were made.".dimmed());
+    Ok(())
+}
@@ -1,3 +1,4 @@
 //! Utility modules for the aicred CLI.
 
+pub mod parallel;
 pub mod provider_loader;
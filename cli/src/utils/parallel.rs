@@ -0,0 +1,24 @@
+//! Running work on several threads at once, optionally capped.
+
+/// Map items on scoped threads, at most `limit` at a time (all at once when
+/// `None`), keeping the order of the items
+pub fn map_parallel<T, R, F>(items: &[T], limit: Option<usize>, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let chunk_size = limit.unwrap_or(items.len()).max(1);
+    let f = &f;
+    let mut results = Vec::with_capacity(items.len());
+    for chunk in items.chunks(chunk_size) {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|item| scope.spawn(move || f(item)))
+                .collect();
+            results.extend(handles.into_iter().filter_map(|handle| handle.join().ok()));
+        });
+    }
+    results
+}
@@ -1,155 +1,206 @@
 //! Provider instance loading utilities.
 
+use aicred_core::crypto;
 use aicred_core::models::{ProviderCollection, ProviderInstance};
+use aicred_core::paths::AppDirs;
+use aicred_core::store::{schema_version, SCHEMA_VERSION};
+use aicred_core::{ConfigStore, ModelRegistry, SecretBackends};
 use anyhow::Result;
 use colored::Colorize;
 use std::path::Path;
 
-/// Load provider instances from configuration directory
-pub fn load_provider_instances(home: Option<&Path>) -> Result<ProviderCollection> {
-    let config_dir = match home {
-        Some(h) => h.to_path_buf(),
-        None => dirs_next::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?,
+pub use aicred_app::profiles::{
+    base_config_store, set_profile_override, ACTIVE_PROFILE_FILE, DEFAULT_PROFILE, PROFILE_ENV_VAR,
+};
+
+/// Get the cache directory for a home directory, or for the current user
+pub fn cache_dir(home: Option<&Path>) -> Result<std::path::PathBuf> {
+    let dirs = match home {
+        Some(h) => AppDirs::for_home(h),
+        None => AppDirs::current()?,
+    };
+    Ok(dirs.cache_dir().to_path_buf())
+}
+
+/// Get the model registry of a home directory, or of the current user, with
+/// the model data last fetched by 'aicred models refresh' and the user's
+/// overrides merged in
+pub fn model_registry(home: Option<&Path>) -> ModelRegistry {
+    home.map_or_else(ModelRegistry::new, ModelRegistry::for_home)
+}
+
+pub use aicred_app::profiles::{active_profile, config_store, profile_store};
+
+/// Resolve a stored key to the secret, fetching it from its backend if it is a reference
+pub fn resolve_secret(value: &str) -> Result<String> {
+    Ok(SecretBackends::builtin().resolve(value)?)
+}
+
+/// Resolve keyring and other secret references in place
+pub fn resolve_instance_secrets(instances: &mut [ProviderInstance]) -> Result<()> {
+    let backends = SecretBackends::builtin();
+    for instance in instances {
+        if backends.is_reference(&instance.api_key) {
+            instance.api_key = backends.resolve(&instance.api_key)?;
+        }
     }
-    .join(".config")
-    .join("aicred");
+    Ok(())
+}
 
-    let instances_dir = config_dir.join("inference_services");
+/// Load provider instances from configuration directory
+pub fn load_provider_instances(home: Option<&Path>) -> Result<ProviderCollection> {
+    let store = config_store(home)?;
 
-    // Create instances directory if it doesn't exist
-    if !instances_dir.exists() {
-        std::fs::create_dir_all(&instances_dir)?;
+    // Loading never creates the store; a missing directory holds no instances
+    if !store.instances_dir().exists() {
         return Ok(ProviderCollection::new());
     }
 
     // Load all instance files
     let mut instances = ProviderCollection::new();
 
-    let entries = std::fs::read_dir(&instances_dir)?;
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.extension().is_some_and(|ext| ext == "yaml") {
-            if let Ok(content) = std::fs::read_to_string(&path) {
-                // Parse as ProviderInstance (modern format)
-                if let Ok(new_instance) = serde_yaml::from_str::<ProviderInstance>(&content) {
-                    let id = new_instance.id.clone();
-                    instances.add(id, new_instance);
-                    continue;
-                }
+    for (path, content) in store.instance_files()? {
+        // Never guess at files written by a newer aicred
+        if schema_version(&content).is_some_and(|version| version > SCHEMA_VERSION) {
+            eprintln!(
+                "{} {}: written by a newer schema version, skipping",
+                "Error parsing instance file:".red(),
+                path.display()
+            );
+            continue;
+        }
 
-                // Fallback: try a permissive parse for ad-hoc YAML fixtures
-                #[allow(clippy::collapsible_match)]
-                if let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
-                    if let serde_yaml::Value::Mapping(map) = value {
-                        // Helper to extract string fields
-                        let get_str = |k: &str| -> Option<String> {
-                            map.get(serde_yaml::Value::String(k.to_string()))
-                                .and_then(|v| v.as_str().map(|s| s.to_string()))
-                        };
-
-                        let id = get_str("id").unwrap_or_else(|| {
-                            path.file_stem()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("unknown")
-                                .to_string()
-                        });
-
-                        let provider_type =
-                            get_str("provider_type").unwrap_or_else(|| "unknown".to_string());
-                        let base_url = get_str("base_url")
-                            .unwrap_or_else(|| "https://api.example.com".to_string());
-
-                        let mut instance = ProviderInstance {
-                            id: id.clone(),
-                            provider_type: provider_type.clone(),
-                            base_url,
-                            api_key: String::new(),
-                            models: Vec::new(),
-                            capabilities: Default::default(),
-                            active: true,
-                            metadata: std::collections::HashMap::new(),
-                        };
-
-                        // Extract direct api_key field if present
-                        if let Some(api_key_val) =
-                            map.get(serde_yaml::Value::String("api_key".to_string()))
-                        {
-                            if let Some(k) = api_key_val.as_str() {
-                                instance.api_key = k.to_string();
-                            }
-                        }
+        // Parse as ProviderInstance, migrating older layouts
+        if let Ok((new_instance, _)) = store.parse_instance(&content) {
+            let id = new_instance.id.clone();
+            instances.add(id, new_instance);
+            continue;
+        }
 
-                        // Active flag
-                        if let Some(active_val) =
-                            map.get(serde_yaml::Value::String("active".to_string()))
-                        {
-                            if let Some(b) = active_val.as_bool() {
-                                instance.active = b;
-                            }
-                        }
+        // Fallback: try a permissive parse for ad-hoc YAML fixtures
+        #[allow(clippy::collapsible_match)]
+        if let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+            if let serde_yaml::Value::Mapping(map) = value {
+                // Helper to extract string fields
+                let get_str = |k: &str| -> Option<String> {
+                    map.get(serde_yaml::Value::String(k.to_string()))
+                        .and_then(|v| v.as_str().map(|s| s.to_string()))
+                };
+
+                let id = get_str("id").unwrap_or_else(|| {
+                    path.file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("unknown")
+                        .to_string()
+                });
+
+                let provider_type =
+                    get_str("provider_type").unwrap_or_else(|| "unknown".to_string());
+                let base_url =
+                    get_str("base_url").unwrap_or_else(|| "https://api.example.com".to_string());
+
+                let mut instance = ProviderInstance {
+                    id: id.clone(),
+                    provider_type: provider_type.clone(),
+                    base_url,
+                    api_key: String::new(),
+                    models: Vec::new(),
+                    capabilities: Default::default(),
+                    active: true,
+                    metadata: std::collections::HashMap::new(),
+                    provenance: None,
+                };
+
+                // Extract direct api_key field if present
+                if let Some(api_key_val) = map.get(serde_yaml::Value::String("api_key".to_string()))
+                {
+                    if let Some(k) = api_key_val.as_str() {
+                        instance.api_key = k.to_string();
+                    }
+                }
 
-                        // Extract API key from legacy `keys` sequence if present
-                        if let Some(keys_val) =
-                            map.get(serde_yaml::Value::String("keys".to_string()))
-                        {
-                            if let Some(seq) = keys_val.as_sequence() {
-                                if !seq.is_empty() {
-                                    if let Some(first_key) = seq[0].as_mapping() {
-                                        let api_key = first_key
-                                            .get(serde_yaml::Value::String("api_key".to_string()))
-                                            .or_else(|| {
-                                                first_key.get(serde_yaml::Value::String(
-                                                    "value".to_string(),
-                                                ))
-                                            })
-                                            .and_then(|v| v.as_str().map(|s| s.to_string()));
-                                        if let Some(k) = api_key {
-                                            instance.api_key = k;
-                                        }
-                                    } else if let Some(s) = seq[0].as_str() {
-                                        instance.api_key = s.to_string();
-                                    }
+                // Active flag
+                if let Some(active_val) = map.get(serde_yaml::Value::String("active".to_string())) {
+                    if let Some(b) = active_val.as_bool() {
+                        instance.active = b;
+                    }
+                }
+
+                // Extract API key from legacy `keys` sequence if present
+                if let Some(keys_val) = map.get(serde_yaml::Value::String("keys".to_string())) {
+                    if let Some(seq) = keys_val.as_sequence() {
+                        if !seq.is_empty() {
+                            if let Some(first_key) = seq[0].as_mapping() {
+                                let api_key = first_key
+                                    .get(serde_yaml::Value::String("api_key".to_string()))
+                                    .or_else(|| {
+                                        first_key
+                                            .get(serde_yaml::Value::String("value".to_string()))
+                                    })
+                                    .and_then(|v| v.as_str().map(|s| s.to_string()));
+                                if let Some(k) = api_key {
+                                    instance.api_key = k;
                                 }
+                            } else if let Some(s) = seq[0].as_str() {
+                                instance.api_key = s.to_string();
                             }
                         }
+                    }
+                }
 
-                        // Extract models: either sequence of strings or sequence of maps with model_id
-                        if let Some(models_val) =
-                            map.get(serde_yaml::Value::String("models".to_string()))
-                        {
-                            if let Some(seq) = models_val.as_sequence() {
-                                for item in seq {
-                                    if let Some(s) = item.as_str() {
-                                        instance.add_model(s.to_string());
-                                    } else if let Some(m) = item.as_mapping() {
-                                        if let Some(model_id_val) =
-                                            m.get(serde_yaml::Value::String("model_id".to_string()))
-                                        {
-                                            if let Some(model_id) = model_id_val.as_str() {
-                                                instance.add_model(model_id.to_string());
-                                            }
-                                        }
+                // Extract models: either sequence of strings or sequence of maps with model_id
+                if let Some(models_val) = map.get(serde_yaml::Value::String("models".to_string())) {
+                    if let Some(seq) = models_val.as_sequence() {
+                        for item in seq {
+                            if let Some(s) = item.as_str() {
+                                instance.add_model(s.to_string());
+                            } else if let Some(m) = item.as_mapping() {
+                                if let Some(model_id_val) =
+                                    m.get(serde_yaml::Value::String("model_id".to_string()))
+                                {
+                                    if let Some(model_id) = model_id_val.as_str() {
+                                        instance.add_model(model_id.to_string());
                                     }
                                 }
                             }
                         }
-
-                        instances.add(id.clone(), instance);
-                        continue;
                     }
                 }
 
-                eprintln!(
-                    "{} {}: failed to parse as ProviderInstance",
-                    "Error parsing instance file:".red(),
-                    path.display()
-                );
+                instances.add(id.clone(), instance);
+                continue;
             }
         }
+
+        eprintln!(
+            "{} {}: failed to parse as ProviderInstance",
+            "Error parsing instance file:".red(),
+            path.display()
+        );
     }
 
+    decrypt_instances(&store, &mut instances);
+
     Ok(instances)
 }
+
+/// Decrypt encrypted instance keys when a passphrase or identity is available
+fn decrypt_instances(store: &ConfigStore, instances: &mut ProviderCollection) {
+    if !instances
+        .all_instances()
+        .iter()
+        .any(|instance| crypto::is_encrypted(&instance.api_key))
+    {
+        return;
+    }
+
+    match store.cipher() {
+        Ok(Some(cipher)) => {
+            if let Err(e) = cipher.decrypt_instances(instances) {
+                eprintln!("{} {}", "Failed to decrypt instance keys:".red(), e);
+            }
+        }
+        Ok(None) => tracing::debug!("Instance keys are encrypted and no identity is available"),
+        Err(e) => eprintln!("{} {}", "Failed to decrypt instance keys:".red(), e),
+    }
+}
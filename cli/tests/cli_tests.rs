@@ -22,6 +22,50 @@ fn home_path_str(home: &std::path::Path) -> &str {
     home.to_str().unwrap()
 }
 
+/// Helper function to configure the instances the label tests point labels at
+fn write_label_instances(home: &std::path::Path) {
+    let providers_dir = home
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    for (id, provider_type, base_url, models) in [
+        (
+            "openai-main",
+            "openai",
+            "https://api.openai.com/v1",
+            "[gpt-4]",
+        ),
+        (
+            "anthropic-main",
+            "anthropic",
+            "https://api.anthropic.com/v1",
+            "[claude-3-opus, claude-3-haiku]",
+        ),
+        (
+            "groq-main",
+            "groq",
+            "https://api.groq.com/openai/v1",
+            "[llama3-8b]",
+        ),
+        (
+            "openrouter-main",
+            "openrouter",
+            "https://openrouter.ai/api/v1",
+            "[deepseek/deepseek-v3.2-exp]",
+        ),
+    ] {
+        fs::write(
+            providers_dir.join(format!("{}.yaml", id)),
+            format!(
+                "id: {}\nprovider_type: {}\nbase_url: {}\napi_key: sk-test\nmodels: {}\n",
+                id, provider_type, base_url, models
+            ),
+        )
+        .unwrap();
+    }
+}
+
 #[test]
 fn test_version_command() {
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -65,13 +109,163 @@ fn test_scan_with_format() {
 
 #[test]
 fn test_all_output_formats() {
-    for format in ["json", "ndjson", "table", "summary"].iter() {
+    for format in ["json", "ndjson", "table", "summary", "csv", "markdown"].iter() {
         let mut cmd = Command::cargo_bin("aicred").unwrap();
         cmd.args(&["scan", "--format", format, "--dry-run"]);
         cmd.assert().success();
     }
 }
 
+#[test]
+fn test_scan_csv_and_markdown_columns() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--format", "csv", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Provider,Value Type,Confidence,Risk,Source,Line,Value,Hash\r\n",
+        ))
+        .stdout(predicate::str::contains("anthropic,API Key,"))
+        .stdout(predicate::str::contains(",sk-ant-1****,"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--format", "csv", "--redaction", "last4", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(",****wxyz,"))
+        .stdout(predicate::str::contains("sk-ant-1").not());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--format", "markdown", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "| Provider | Value Type | Confidence | Risk | Source | Line | Value | Hash |",
+        ))
+        .stdout(predicate::str::contains("| anthropic | API Key |"));
+}
+
+#[test]
+fn test_scan_only_types() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n\
+         OPENAI_BASE_URL=https://api.openai.com/v1\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "scan",
+        "--format",
+        "csv",
+        "--only-types",
+        "api_key",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(",API Key,"))
+        .stdout(predicate::str::contains("Base URL").not());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "scan",
+        "--format",
+        "csv",
+        "--only-types",
+        "base-url",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(",Base URL,"))
+        .stdout(predicate::str::contains(",API Key,").not());
+}
+
+#[test]
+fn test_fingerprint_matches_scan_hashes() {
+    let temp_home = TempDir::new().unwrap();
+    let key = "sk-ant-REDACTED";
+    fs::write(
+        temp_home.path().join(".env"),
+        format!("ANTHROPIC_API_KEY={}\n", key),
+    )
+    .unwrap();
+
+    let fingerprint = |algorithm: &str, stdin: bool| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(&["--home"]).arg(temp_home.path()).args(&[
+            "fingerprint",
+            "--algorithm",
+            algorithm,
+        ]);
+        if stdin {
+            cmd.write_stdin(format!("{}\n", key));
+        } else {
+            cmd.arg(key);
+        }
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap().trim().to_string()
+    };
+    let scan_hash = |algorithm: &str| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(&[
+            "scan",
+            "--format",
+            "json",
+            "--no-probe",
+            "--no-history",
+            "--hash-algorithm",
+            algorithm,
+            "--home",
+        ])
+        .arg(temp_home.path());
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let output = String::from_utf8(output).unwrap();
+        // Skip the progress line printed before the JSON
+        let json: serde_json::Value =
+            serde_json::from_str(&output[output.find('{').unwrap()..]).unwrap();
+        json["keys"][0]["hash"].as_str().unwrap().to_string()
+    };
+
+    let sha256 = fingerprint("sha256", true);
+    assert_eq!(sha256.len(), 64);
+    assert_eq!(scan_hash("sha256"), sha256);
+    assert!(!temp_home
+        .path()
+        .join(".config/aicred/fingerprint.salt")
+        .exists());
+
+    let hmac = scan_hash("hmac-sha256");
+    assert_ne!(hmac, sha256);
+    assert!(temp_home
+        .path()
+        .join(".config/aicred/fingerprint.salt")
+        .exists());
+    assert_eq!(fingerprint("hmac-sha256", false), hmac);
+}
+
 #[test]
 fn test_provider_filtering() {
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -121,9 +315,52 @@ fn test_audit_logging() {
     // Audit log should be written regardless of whether any keys were found.
     assert!(log_path.exists(), "Audit log file should be created");
 
-    // Basic content sanity check
+    // One structured JSON record per scan
     let contents = std::fs::read_to_string(&log_path).expect("read audit log");
-    assert!(contents.contains("AICred Audit Log"));
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(record["action"], "scan");
+    assert_eq!(record["home_dir"], home.to_str().unwrap());
+    assert_eq!(record["options"]["format"], "json");
+    assert!(record["counts"]["keys"].is_number());
+}
+
+#[test]
+fn test_audit_chain_verify_detects_tampering() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+    let log_path = temp_home.path().join("audit.jsonl");
+
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(&["scan", "--no-history", "--format", "json", "--home"])
+            .arg(temp_home.path())
+            .arg("--audit-log")
+            .arg(&log_path)
+            .arg("--audit-chain");
+        cmd.assert().success();
+    }
+    let contents = fs::read_to_string(&log_path).unwrap();
+    assert!(!contents.contains("abcdefghijklmnopqrstuvwxyz"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.args(&["audit", "verify"]).arg(&log_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("2 of 2 record(s)"));
+
+    fs::write(&log_path, contents.replacen("\"keys\":1", "\"keys\":0", 1)).unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.args(&["audit", "verify"]).arg(&log_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("record 1 was modified"));
 }
 
 #[test]
@@ -152,6 +389,47 @@ fn test_version_contains_pkg_version() {
         .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")));
 }
 
+#[test]
+fn test_json_output_parses() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    write_label_instances(temp_home.path());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--format", "json", "--no-history", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let scan: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(scan.is_object());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "list", "--format", "json", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let listed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let ids: Vec<&str> = listed["instances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|instance| instance["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "anthropic-main",
+            "groq-main",
+            "openai-main",
+            "openrouter-main"
+        ]
+    );
+}
+
 #[test]
 fn test_instances_list_command() {
     // Create a temporary home directory with test configuration
@@ -185,6 +463,75 @@ updated_at: "2023-01-01T00:00:00Z"
         .stdout(predicate::str::contains("openrouter-instance"));
 }
 
+#[test]
+fn test_instances_list_sizes_the_id_column() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    for id in ["huggingface-0123456789ab", "groq-main"] {
+        fs::write(
+            providers_dir.join(format!("{}.yaml", id)),
+            format!(
+                "id: {}\nprovider_type: groq\nbase_url: https://api.groq.com/openai/v1\nmodels: []\n",
+                id
+            ),
+        )
+        .unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "list", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let columns: Vec<usize> = output
+        .lines()
+        .filter(|line| line.starts_with("ID") || line.contains(" groq "))
+        .map(|line| {
+            line.find(" groq ")
+                .map(|space| space + 1)
+                .or_else(|| line.find("Provider"))
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(columns.len(), 3);
+    assert!(columns.iter().all(|column| *column == columns[0]));
+}
+
+#[test]
+fn test_instances_list_csv_format() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    let test_config = r#"---
+id: "openrouter-instance"
+provider_type: "openrouter"
+base_url: "https://openrouter.ai/api/v1"
+active: true
+api_key: "sk-or-test-key"
+models: ["gpt-4o", "claude-3"]
+"#;
+    fs::write(providers_dir.join("openrouter-open.yaml"), test_config).unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "list", "--format", "csv", "--home"])
+        .arg(temp_home.path());
+    cmd.assert().success().stdout(
+        "ID,Provider,Base URL,Active,Models\r\n\
+         openrouter-instance,openrouter,https://openrouter.ai/api/v1,true,\"gpt-4o, claude-3\"\r\n",
+    );
+}
+
 #[test]
 fn test_instances_default_behavior() {
     // Create a temporary home directory with test configuration
@@ -351,6 +698,45 @@ chain:
     }
 }
 
+#[cfg(unix)]
+#[test]
+fn test_store_directories_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_dir = temp_dir.path().join(".config").join("aicred");
+    let mode = |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+    // Listing reads the store without creating it
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(["instances", "list", "--home"])
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+    assert!(!config_dir.join("inference_services").exists());
+
+    fs::write(
+        temp_dir.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(["scan", "--update", "--home"])
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    for dir in [
+        config_dir.clone(),
+        config_dir.join("inference_services"),
+        config_dir.join("models"),
+    ] {
+        assert_eq!(mode(&dir), 0o700, "{}", dir.display());
+    }
+}
+
 #[test]
 fn test_multiple_scans_dont_create_duplicates() {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -1545,6 +1931,7 @@ fn test_labels_add_and_assign_positional_syntax() {
     let temp_home = TempDir::new().unwrap();
     let config_dir = temp_home.path().join(".config").join("aicred");
     fs::create_dir_all(&config_dir).unwrap();
+    write_label_instances(temp_home.path());
 
     // Set a label using the new simplified syntax
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -1572,6 +1959,7 @@ fn test_labels_assign_with_tuple_flag() {
     let temp_home = TempDir::new().unwrap();
     let config_dir = temp_home.path().join(".config").join("aicred");
     fs::create_dir_all(&config_dir).unwrap();
+    write_label_instances(temp_home.path());
 
     // Set a label using the new simplified syntax
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -1600,6 +1988,7 @@ fn test_labels_assign_with_name_flag() {
     let config_dir = temp_home.path().join(".config").join("aicred");
     let labels_dir = config_dir.join("labels");
     fs::create_dir_all(&labels_dir).unwrap();
+    write_label_instances(temp_home.path());
 
     // Set a label using the new simplified syntax
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -1628,6 +2017,7 @@ fn test_labels_list_command() {
     let config_dir = temp_home.path().join(".config").join("aicred");
     let labels_dir = config_dir.join("labels");
     fs::create_dir_all(&labels_dir).unwrap();
+    write_label_instances(temp_home.path());
 
     // Set multiple labels (implicitly creates them)
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -1680,6 +2070,7 @@ fn test_labels_assign_multiple_to_same_tuple() {
     let config_dir = temp_home.path().join(".config").join("aicred");
     let labels_dir = config_dir.join("labels");
     fs::create_dir_all(&labels_dir).unwrap();
+    write_label_instances(temp_home.path());
 
     // Set multiple labels to the same tuple (implicitly creates them)
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -1733,6 +2124,32 @@ fn test_labels_assign_error_handling() {
     ]);
     cmd.assert().failure();
 
+    // Models the instance does not list are not silently dropped
+    write_label_instances(temp_home.path());
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "labels",
+        "set",
+        "test-label=openai:gpt-5",
+        "--home",
+        home_path_str(temp_home.path()),
+    ]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "No provider instance of type 'openai' has model 'gpt-5'",
+    ));
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "labels",
+        "set",
+        "test-label=unconfigured:model",
+        "--home",
+        home_path_str(temp_home.path()),
+    ]);
+    cmd.assert().failure();
+    assert!(!config_dir.join("labels.yaml").exists());
+
     // Try to set a label without any arguments
     let mut cmd = Command::cargo_bin("aicred").unwrap();
     set_test_home_envs(&mut cmd, temp_home.path());
@@ -1758,6 +2175,7 @@ fn test_labels_remove_command() {
     let config_dir = temp_home.path().join(".config").join("aicred");
     let labels_dir = config_dir.join("labels");
     fs::create_dir_all(&labels_dir).unwrap();
+    write_label_instances(temp_home.path());
 
     // Set the label (implicitly creates it)
     let mut cmd = Command::cargo_bin("aicred").unwrap();
@@ -1813,3 +2231,2314 @@ fn test_labels_help_commands() {
         "Unset (remove) a label assignment",
     ));
 }
+
+#[test]
+fn test_scan_baseline_suppresses_known_findings() {
+    let temp_dir = TempDir::new().unwrap();
+    let baseline_path = temp_dir.path().join("baseline.json");
+    let env_file = temp_dir.path().join(".env");
+    fs::write(
+        &env_file,
+        "GROQ_API_KEY=gsk_1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+
+    // Record the current findings
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(&["scan", "--no-probe", "--home"])
+        .arg(temp_dir.path())
+        .arg("--write-baseline")
+        .arg(&baseline_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote baseline"));
+
+    let baseline = fs::read_to_string(&baseline_path).unwrap();
+    assert!(!baseline.contains("gsk_1234567890"));
+
+    // Nothing new since the baseline was written
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(&["scan", "--no-probe", "--format", "json", "--home"])
+        .arg(temp_dir.path())
+        .arg("--baseline")
+        .arg(&baseline_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"baseline_suppressed\""));
+
+    // A newly introduced key is reported and fails the run
+    fs::write(
+        &env_file,
+        "GROQ_API_KEY=gsk_1234567890abcdefghijklmnopqrstuvwxyz\nOPENAI_API_KEY=sk-newkey1234567890abcdefghijklmnop\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(&["scan", "--no-probe", "--format", "json", "--home"])
+        .arg(temp_dir.path())
+        .arg("--baseline")
+        .arg(&baseline_path);
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("openai"));
+}
+
+#[test]
+fn test_scan_policy_sets_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join(".env"),
+        "OPENAI_API_KEY=sk-policy1234567890abcdefghijklmnop\n",
+    )
+    .unwrap();
+
+    let warn_policy = temp_dir.path().join("warn.yaml");
+    fs::write(
+        &warn_policy,
+        "rules:\n  - name: any-openai\n    action: warn\n    providers: [openai]\n",
+    )
+    .unwrap();
+    let fail_policy = temp_dir.path().join("fail.yaml");
+    fs::write(
+        &fail_policy,
+        "rules:\n  - name: no-dotenv\n    action: fail\n    paths: [\"~/.env\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(&["scan", "--no-probe", "--home"])
+        .arg(temp_dir.path())
+        .arg("--policy")
+        .arg(&warn_policy);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("WARN [any-openai]"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_dir.path());
+    cmd.args(&["scan", "--no-probe", "--home"])
+        .arg(temp_dir.path())
+        .arg("--policy")
+        .arg(&fail_policy);
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("FAIL [no-dotenv]"));
+}
+
+#[test]
+fn test_scan_extra_roots() {
+    let home = TempDir::new().unwrap();
+    let backup = TempDir::new().unwrap();
+    fs::write(
+        backup.path().join(".env"),
+        "OPENAI_API_KEY=sk-backup1234567890abcdefghijklmnop\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, home.path());
+    cmd.args(&["scan", "--no-probe", "--format", "json", "--home"])
+        .arg(home.path())
+        .arg("--root")
+        .arg(backup.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "\"scan_root\": \"{}\"",
+            backup.path().display()
+        )));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, home.path());
+    cmd.args(&["scan", "--no-probe", "--home"])
+        .arg(home.path())
+        .arg("--root")
+        .arg(backup.path().join("missing"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Scan root is not a directory"));
+}
+
+#[test]
+fn test_config_migrate_upgrades_legacy_instances() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    let legacy = "id: legacy-openai\nprovider_type: openai\nbase_url: https://api.openai.com/v1\nkeys:\n  - api_key: sk-legacy1234567890\n";
+    fs::write(providers_dir.join("openai-lega.yaml"), legacy).unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["config", "migrate", "--dry-run", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Would migrate"))
+        .stdout(predicate::str::contains("`keys`"));
+    assert_eq!(
+        fs::read_to_string(providers_dir.join("openai-lega.yaml")).unwrap(),
+        legacy
+    );
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["config", "migrate", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Migrated"));
+
+    let migrated = fs::read_to_string(providers_dir.join("openai-lega.yaml")).unwrap();
+    assert!(migrated.starts_with("# aicred-schema-version: 1\n"));
+    assert!(migrated.contains("api_key: sk-legacy1234567890"));
+    assert!(providers_dir.join("openai-lega.yaml.bak").exists());
+}
+
+#[test]
+fn test_config_encrypt_and_transparent_decrypt() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    let instance_file = providers_dir.join("openai-enc1.yaml");
+    fs::write(
+        &instance_file,
+        r#"---
+id: "enc-openai"
+provider_type: "openai"
+base_url: "https://api.openai.com/v1"
+api_key: "sk-encrypt-me-1234567890"
+models: []
+capabilities:
+  chat: true
+  completion: false
+  embedding: false
+  image_generation: false
+  function_calling: false
+  streaming: false
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["config", "encrypt", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Encrypted 1 instance key(s)"));
+    assert!(config_dir.join("identity.key").exists());
+    let encrypted = fs::read_to_string(&instance_file).unwrap();
+    assert!(!encrypted.contains("sk-encrypt-me"));
+    assert!(encrypted.contains("aicred-enc:v1:"));
+
+    // The default identity is picked up when reading instances
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "enc-openai", "--include-values", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("sk-encrypt-me-1234567890"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["config", "decrypt", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Decrypted 1 instance key(s)"));
+    assert!(fs::read_to_string(&instance_file)
+        .unwrap()
+        .contains("sk-encrypt-me-1234567890"));
+}
+
+#[test]
+fn test_instances_add_rejects_unknown_store() {
+    let temp_home = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "instances",
+        "add",
+        "--id",
+        "store-test",
+        "--name",
+        "Store Test",
+        "--provider-type",
+        "openai",
+        "--base-url",
+        "https://api.openai.com/v1",
+        "--api-key",
+        "sk-store-test-1234567890",
+        "--store",
+        "floppy",
+    ]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown secret store 'floppy'"));
+    let instances_dir = temp_home.path().join(".config/aicred/inference_services");
+    assert!(fs::read_dir(&instances_dir).map_or(true, |mut dir| dir.next().is_none()));
+}
+
+#[test]
+fn test_rotate_reports_unsupported_provider() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-rot1.yaml"),
+        r#"---
+id: "rotate-openai"
+provider_type: "openai"
+base_url: "https://api.openai.com/v1"
+api_key: "sk-rotate-me-1234567890"
+models: []
+capabilities:
+  chat: true
+  completion: false
+  embedding: false
+  image_generation: false
+  function_calling: false
+  streaming: false
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["rotate", "rotate-openai", "--home"])
+        .arg(temp_home.path());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "does not offer a key management API",
+    ));
+}
+
+#[test]
+fn test_revoke_rejects_unknown_target() {
+    let temp_home = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["revoke", "no-such-instance", "--force", "--home"])
+        .arg(temp_home.path());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "neither an instance ID nor a key hash",
+    ));
+}
+
+#[test]
+fn test_cleanup_dry_run_leaves_files_unchanged() {
+    let temp_home = TempDir::new().unwrap();
+    let env_file = temp_home.path().join(".env");
+    let content = "ANTHROPIC_API_KEY=sk-ant-REDACTED\n";
+    fs::write(&env_file, content).unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["cleanup", "--all", "--dry-run", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "ANTHROPIC_API_KEY=${ANTHROPIC_API_KEY}",
+        ))
+        .stdout(predicate::str::contains("sk-ant-1234567890").not());
+
+    assert_eq!(fs::read_to_string(&env_file).unwrap(), content);
+    assert!(!temp_home.path().join(".env.aicred-bak").exists());
+}
+
+#[test]
+fn test_cleanup_moves_key_into_store() {
+    let temp_home = TempDir::new().unwrap();
+    let env_file = temp_home.path().join(".env");
+    fs::write(
+        &env_file,
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["cleanup", "--provider", "anthropic", "--yes", "--home"])
+        .arg(temp_home.path());
+    cmd.assert().success();
+
+    assert_eq!(
+        fs::read_to_string(&env_file).unwrap(),
+        "ANTHROPIC_API_KEY=${ANTHROPIC_API_KEY}\n"
+    );
+    assert!(temp_home.path().join(".env.aicred-bak").exists());
+    let instances_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    let stored: Vec<String> = fs::read_dir(&instances_dir)
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(stored
+        .iter()
+        .any(|yaml| yaml.contains("sk-ant-REDACTED")));
+}
+
+#[test]
+fn test_imported_gitleaks_rules_are_used_by_scan() {
+    let temp_home = TempDir::new().unwrap();
+    let rules_file = temp_home.path().join("gitleaks.toml");
+    fs::write(
+        &rules_file,
+        r#"
+[[rules]]
+id = "openai-project-key"
+regex = '''\b(sk-proj-[A-Za-z0-9]{24})\b'''
+keywords = ["sk-proj-"]
+
+[[rules]]
+id = "github-pat"
+regex = '''ghp_[0-9a-zA-Z]{36}'''
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["rules", "import"])
+        .arg(&rules_file)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 1 rule(s)"))
+        .stdout(predicate::str::contains(
+            "github-pat: not an AI provider rule",
+        ));
+
+    let project = temp_home.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("settings.toml"),
+        "llm_token = \"sk-proj-Ab3dEf6hIj9kLm2nOp4qRs5t\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--format", "csv", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("openai,API Key,Medium"))
+        .stdout(predicate::str::contains("settings.toml,1,"));
+}
+
+#[test]
+fn test_history_records_scans() {
+    let temp_home = TempDir::new().unwrap();
+    let env_file = temp_home.path().join(".env");
+    fs::write(
+        &env_file,
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    let scan = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(&["scan", "--format", "json", "--home"])
+            .arg(temp_home.path())
+            .args(extra);
+        cmd.assert().success();
+    };
+    scan(&[]);
+    // Opted-out and provider-filtered scans are not recorded
+    scan(&["--no-history"]);
+    scan(&["--only", "anthropic"]);
+    fs::write(&env_file, "# key removed\n").unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--home"]).arg(temp_home.path());
+    cmd.assert();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["history", "--keys", "--format", "json", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let timelines: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let timelines = timelines.as_array().unwrap();
+    assert_eq!(timelines.len(), 1);
+    assert_eq!(timelines[0]["provider"], "anthropic");
+    assert_eq!(timelines[0]["sightings"], 1);
+    assert!(!timelines[0]["disappeared_at"].is_null());
+}
+
+#[test]
+fn test_scan_posts_webhook_notification() {
+    use std::io::{Read, Write};
+
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.yaml"),
+        format!(
+            "notifications:\n  - type: webhook\n    url: http://{}/hook\n",
+            listener.local_addr().unwrap()
+        ),
+    )
+    .unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !String::from_utf8_lossy(&request).ends_with('}') {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        String::from_utf8_lossy(&request).into_owned()
+    });
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["scan", "--no-history", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Failed to notify").not());
+
+    let request = server.join().unwrap();
+    let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+    let event: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(event["event"], "scan_completed");
+    assert_eq!(event["keys"], 1);
+    assert!(!body.contains("abcdefghijklmnopqrstuvwxyz"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_serves_json_rpc() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    fs::create_dir_all(config_dir.join("inference_services")).unwrap();
+    fs::write(
+        config_dir
+            .join("inference_services")
+            .join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-daemon-test-key\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_instance\n    instance_id: openai-work\n  \
+         assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+    let socket = temp_home.path().join("aicred.sock");
+
+    let mut daemon = std::process::Command::new(assert_cmd::cargo::cargo_bin("aicred"))
+        .env("HOME", temp_home.path())
+        .args(["daemon", "--socket"])
+        .arg(&socket)
+        .arg("--home")
+        .arg(temp_home.path())
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let stream = (0..100)
+        .find_map(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            UnixStream::connect(&socket).ok()
+        })
+        .expect("daemon did not start");
+
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let mut call = |request: serde_json::Value| {
+        writeln!(writer, "{request}").unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str::<serde_json::Value>(&line).unwrap()
+    };
+
+    let pong = call(serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}));
+    assert_eq!(pong["result"]["version"], env!("CARGO_PKG_VERSION"));
+
+    let list = call(serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "instances.list"}));
+    assert_eq!(list["result"][0]["id"], "openai-work");
+    assert_eq!(list["result"][0]["api_key"], "********");
+
+    let resolved = call(serde_json::json!({
+        "jsonrpc": "2.0", "id": 3, "method": "labels.resolve",
+        "params": {"label": "fast", "include_values": true}
+    }));
+    assert_eq!(resolved["result"]["model"], "gpt-4o");
+    assert_eq!(
+        resolved["result"]["instance"]["api_key"],
+        "sk-daemon-test-key"
+    );
+
+    let unknown = call(serde_json::json!({"jsonrpc": "2.0", "id": 4, "method": "nope"}));
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let stopping = call(serde_json::json!({"jsonrpc": "2.0", "id": 5, "method": "shutdown"}));
+    assert_eq!(stopping["result"]["stopping"], true);
+    assert!(daemon.wait().unwrap().success());
+    assert!(!socket.exists());
+}
+
+#[test]
+fn test_mcp_server_lists_tools_and_masks_keys() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-mcp-test-key\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"list_instances","arguments":{}}}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"resolve_label","arguments":{"label":"missing"}}}"#,
+    ];
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["mcp", "--home"])
+        .arg(temp_home.path())
+        .write_stdin(requests.join("\n") + "\n");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("sk-mcp-test-key"));
+    let responses: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // The initialized notification gets no response
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "aicred");
+    let tools: Vec<&str> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(tools, ["list_instances", "resolve_label", "scan_summary"]);
+    let text = responses[2]["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("openai-work"));
+    assert_eq!(responses[3]["result"]["isError"], true);
+}
+
+#[test]
+fn test_export_litellm_config() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("anthropic-work.yaml"),
+        "id: anthropic-work\nprovider_type: anthropic\nbase_url: https://api.anthropic.com\n\
+         api_key: sk-ant-export-key\nmodels: [claude-sonnet-4]\n",
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("groq-idle.yaml"),
+        "id: groq-idle\nprovider_type: groq\nbase_url: https://api.groq.com/openai/v1\n\
+         api_key: gsk-idle\nmodels: []\nactive: false\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: smart\n  target:\n    type: provider_model\n    instance_id: anthropic-work\n    \
+         model_id: claude-sonnet-4\n  assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "--format", "litellm", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let config: serde_yaml::Value = serde_yaml::from_slice(&output.stdout).unwrap();
+    let models = config["model_list"].as_sequence().unwrap();
+    // Inactive instances are skipped; labels become aliases
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0]["model_name"], "claude-sonnet-4");
+    assert_eq!(
+        models[0]["litellm_params"]["model"],
+        "anthropic/claude-sonnet-4"
+    );
+    assert_eq!(
+        models[0]["litellm_params"]["api_base"],
+        "https://api.anthropic.com"
+    );
+    assert_eq!(models[0]["litellm_params"]["api_key"], "sk-ant-export-key");
+    assert_eq!(models[1]["model_name"], "smart");
+
+    let out_file = temp_home.path().join("litellm.yaml");
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "-f", "litellm", "--env-refs", "-o"])
+        .arg(&out_file)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert().success();
+    let written = fs::read_to_string(&out_file).unwrap();
+    assert!(written.contains("api_key: os.environ/ANTHROPIC_API_KEY"));
+    assert!(!written.contains("sk-ant-export-key"));
+}
+
+#[test]
+fn test_export_mcp_env_merges_client_config() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-mcp-env-key\nmodels: []\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "--format", "mcp-env", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let block: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(block["env"]["OPENAI_API_KEY"], "sk-mcp-env-key");
+
+    let client_config = temp_home.path().join("claude_desktop_config.json");
+    fs::write(
+        &client_config,
+        r#"{"mcpServers": {
+            "search": {"command": "search-mcp", "env": {"OPENAI_API_KEY": "", "OTHER": "x"}},
+            "files": {"command": "files-mcp"}
+        }}"#,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "--format", "mcp-env", "--merge"])
+        .arg(&client_config)
+        .arg("--home")
+        .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let merged: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    // Only declared variables are filled unless servers are named
+    assert_eq!(
+        merged["mcpServers"]["search"]["env"]["OPENAI_API_KEY"],
+        "sk-mcp-env-key"
+    );
+    assert_eq!(merged["mcpServers"]["search"]["env"]["OTHER"], "x");
+    assert!(merged["mcpServers"]["files"]["env"]
+        .as_object()
+        .unwrap()
+        .is_empty());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "export", "--format", "mcp-env", "--server", "files", "--merge",
+    ])
+    .arg(&client_config)
+    .arg("--home")
+    .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    let merged: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        merged["mcpServers"]["files"]["env"]["OPENAI_API_KEY"],
+        "sk-mcp-env-key"
+    );
+    assert_eq!(merged["mcpServers"]["search"]["env"]["OPENAI_API_KEY"], "");
+}
+
+#[test]
+fn test_export_env_for_label() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("groq-fast.yaml"),
+        "id: groq-fast\nprovider_type: groq\nbase_url: https://api.groq.com/openai/v1\n\
+         api_key: gsk-env-export-key\nmodels: [llama-3.1-8b-instant, llama-3.3-70b]\n",
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-other\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_model\n    instance_id: groq-fast\n    \
+         model_id: llama-3.3-70b\n  assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    let env_file = temp_home.path().join(".env");
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "env", "--label", "fast", "--output"])
+        .arg(&env_file)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert().success();
+    let written = fs::read_to_string(&env_file).unwrap();
+    assert!(written.contains("GROQ_API_KEY=gsk-env-export-key\n"));
+    assert!(written.contains("GROQ_BASE_URL=https://api.groq.com/openai/v1\n"));
+    assert!(written.contains("GROQ_MODEL=llama-3.3-70b\n"));
+    assert!(!written.contains("OPENAI_"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "env", "--no-values", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("GROQ_API_KEY=\"<groq-api-key>\""))
+        .stdout(predicate::str::contains("OPENAI_MODEL=gpt-4o"))
+        .stdout(predicate::str::contains("sk-other").not());
+}
+
+#[test]
+fn test_export_docker_formats_filter_by_tag() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-api.yaml"),
+        "id: openai-api\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-it's-docker\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("anthropic-batch.yaml"),
+        "id: anthropic-batch\nprovider_type: anthropic\nbase_url: https://api.anthropic.com\n\
+         api_key: sk-ant-batch\nmodels: []\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("tags.yaml"),
+        "- name: web\n  description: null\n  created_at: 2026-01-01T00:00:00Z\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("tag_assignments.yaml"),
+        "- label_name: web\n  target:\n    type: provider_instance\n    instance_id: openai-api\n  \
+         assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "docker-env", "--tag", "web", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("OPENAI_API_KEY="))
+        .stdout(predicate::str::contains("ANTHROPIC").not());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["export", "docker-secrets", "--tag", "web", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let script = String::from_utf8(output.stdout).unwrap();
+    assert!(script.starts_with("#!/bin/sh"));
+    assert!(
+        script.contains("printf '%s' 'sk-it'\\''s-docker' | docker secret create openai_api_key -")
+    );
+    assert!(script.contains("#     openai_api_key:\n#       external: true"));
+    assert!(!script.contains("anthropic"));
+}
+
+#[test]
+fn test_completions_script() {
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.args(&["completions", "bash"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("COMPLETE=\"bash\""))
+        .stdout(predicate::str::contains("-F _clap_complete_aicred aicred"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.args(&["completions", "tcsh"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported shell: tcsh"));
+}
+
+#[test]
+fn test_completions_complete_store_values() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    for id in ["groq-fast", "openai-work"] {
+        fs::write(
+            providers_dir.join(format!("{id}.yaml")),
+            format!("id: {id}\nprovider_type: openai\nbase_url: https://api.example.com/v1\n"),
+        )
+        .unwrap();
+    }
+    fs::write(
+        config_dir.join("tags.yaml"),
+        "- name: prod\n  description: Production\n  created_at: 2026-01-01T00:00:00Z\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_instance\n    instance_id: groq-fast\n  \
+         assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    // What the bash script runs when completing the last word
+    let complete = |words: &[&str]| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.env("COMPLETE", "bash")
+            .env("_CLAP_COMPLETE_INDEX", (words.len() - 1).to_string())
+            .env("_CLAP_COMPLETE_COMP_TYPE", "9")
+            .env("_CLAP_COMPLETE_SPACE", "true")
+            .env("_CLAP_IFS", "\n")
+            .arg("--")
+            .args(words);
+        String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+    };
+
+    assert_eq!(complete(&["aicred", "instances", "get", "gr"]), "groq-fast");
+    assert_eq!(
+        complete(&["aicred", "tags", "assign", "--name", ""]),
+        "prod"
+    );
+    assert_eq!(complete(&["aicred", "setenv", "--label", "f"]), "fast");
+
+    // --home on the command line being completed selects the store
+    let empty_home = TempDir::new().unwrap();
+    let words = [
+        "aicred",
+        "instances",
+        "get",
+        "--home",
+        empty_home.path().to_str().unwrap(),
+        "gr",
+    ];
+    assert_eq!(complete(&words), "");
+}
+
+#[test]
+fn test_doctor_reports_problems_with_fixes() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+
+    // One base URL that accepts connections and one that doesn't
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let open_port = listener.local_addr().unwrap().port();
+    let closed_port = {
+        let probe = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        probe.local_addr().unwrap().port()
+    };
+    fs::write(
+        providers_dir.join("groq-groq.yaml"),
+        format!(
+            "id: groq-fast\nprovider_type: groq\nbase_url: http://127.0.0.1:{open_port}/v1\n\
+             api_key: gsk-configured\nmodels: [llama-3.3-70b]\n"
+        ),
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("openai-open.yaml"),
+        format!(
+            "id: openai-local\nprovider_type: openai\nbase_url: http://127.0.0.1:{closed_port}/v1\n\
+             api_key: sk-configured\nmodels: [gpt-4o, gpt-3.5-turbo]\n"
+        ),
+    )
+    .unwrap();
+    let stale = config_dir.join(".labels.yaml.4242.tmp");
+    fs::write(&stale, "partial").unwrap();
+    let two_hours_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(7200);
+    fs::File::options()
+        .write(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(two_hours_ago)
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.env("GROQ_API_KEY", "gsk-configured")
+        .env("OPENAI_API_KEY", "sk-from-shell")
+        .env_remove("GROQ_BASE_URL")
+        .env_remove("OPENAI_BASE_URL")
+        .args(&["doctor", "--timeout", "2", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("has no schema version header"))
+        .stdout(predicate::str::contains("Fix: aicred config migrate"))
+        .stdout(predicate::str::contains(format!(
+            "groq-fast: http://127.0.0.1:{open_port}/v1 is reachable"
+        )))
+        .stdout(
+            predicate::str::contains("openai-local: ")
+                .and(predicate::str::contains("is unreachable")),
+        )
+        .stdout(predicate::str::contains(
+            "GROQ_API_KEY matches instance 'groq-fast'",
+        ))
+        .stdout(predicate::str::contains(
+            "OPENAI_API_KEY is set in the shell but matches none of the configured keys",
+        ))
+        .stdout(predicate::str::contains(
+            "was left behind by an interrupted write",
+        ))
+        .stdout(predicate::str::contains(
+            "openai-local uses gpt-3.5-turbo, which is deprecated",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "Fix: rm {}",
+            stale.display()
+        )))
+        .stdout(predicate::str::contains("sk-configured").not());
+    drop(listener);
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["doctor", "--offline", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped (--offline)"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["models", "list", "--format", "json", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let models: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let warnings: Vec<&str> = models["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|model| model["warning"].as_str())
+        .collect();
+    assert_eq!(
+        warnings,
+        ["openai-local uses gpt-3.5-turbo, which is deprecated"]
+    );
+}
+
+#[test]
+fn test_profiles_keep_separate_instances() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-pers.yaml"),
+        "id: openai-personal\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-personal\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    let aicred = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.env_remove("AICRED_PROFILE")
+            .args(args)
+            .arg("--home")
+            .arg(temp_home.path());
+        cmd
+    };
+
+    aicred(&["profile", "create", "work"]).assert().success();
+    aicred(&["profile", "create", "copy", "--from", "default"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Copied 1 instances"));
+    aicred(&["--profile", "work", "instances", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No provider instances configured"));
+    aicred(&["--profile", "copy", "instances", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("openai-personal"));
+    aicred(&["--profile", "missing", "instances", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Profile 'missing' does not exist"));
+
+    // 'profile use' persists the choice, and the environment overrides it
+    aicred(&["profile", "use", "work"]).assert().success();
+    aicred(&["instances", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("openai-personal").not());
+    aicred(&["profile", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("* work (0 instances)"))
+        .stdout(predicate::str::contains("  default (1 instances)"));
+    aicred(&["profile", "show"])
+        .env("AICRED_PROFILE", "default")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Active profile: default"));
+
+    aicred(&["profile", "delete", "work"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Profile 'work' is active"));
+    aicred(&["profile", "use", "default"]).assert().success();
+    aicred(&["profile", "delete", "copy", "--force"])
+        .assert()
+        .success();
+    assert!(!temp_home
+        .path()
+        .join(".config/aicred/profiles/copy")
+        .exists());
+}
+
+#[test]
+fn test_config_bundle_moves_setup_between_homes() {
+    let source_home = TempDir::new().unwrap();
+    let config_dir = source_home.path().join(".config").join("aicred");
+    fs::create_dir_all(config_dir.join("inference_services")).unwrap();
+    fs::write(
+        config_dir
+            .join("inference_services")
+            .join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work-secret\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_instance\n    instance_id: openai-work\n  \
+         assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+    let bundle = source_home.path().join("setup.bundle");
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, source_home.path());
+    cmd.env("AICRED_PASSPHRASE", "moving day")
+        .args(&[
+            "config",
+            "export-bundle",
+            "--passphrase",
+            "--include-secrets",
+            "-o",
+        ])
+        .arg(&bundle)
+        .arg("--home")
+        .arg(source_home.path());
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Bundled 1 instance(s)"));
+    let sealed = fs::read_to_string(&bundle).unwrap();
+    assert!(sealed.starts_with("aicred-bundle v1\n"));
+    assert!(!sealed.contains("sk-work-secret"));
+
+    let target_home = TempDir::new().unwrap();
+    let import = |passphrase: &str, extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, target_home.path());
+        cmd.env("AICRED_PASSPHRASE", passphrase)
+            .args(&["config", "import-bundle", "--passphrase"])
+            .arg(&bundle)
+            .args(extra)
+            .arg("--home")
+            .arg(target_home.path());
+        cmd
+    };
+
+    import("wrong", &[])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Decryption failed"));
+    import("moving day", &["--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would add instance openai-work"));
+    import("moving day", &[])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added instance openai-work"))
+        .stdout(predicate::str::contains("Added label assignment fast"));
+    import("moving day", &[])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to import."));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, target_home.path());
+    cmd.args(&[
+        "instances",
+        "get",
+        "openai-work",
+        "--include-values",
+        "--home",
+    ])
+    .arg(target_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("sk-work-secret"));
+}
+
+#[test]
+fn test_instances_check_records_health() {
+    use std::io::{Read, Write};
+
+    // A provider answering one model list request
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4096];
+        let read = stream.read(&mut request).unwrap();
+        let request = String::from_utf8_lossy(&request[..read]).into_owned();
+        let body = r#"{"data":[{"id":"gpt-4o"},{"id":"gpt-4o-mini"}]}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        request
+    });
+    let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-live.yaml"),
+        format!(
+            "id: live\nprovider_type: openai\nbase_url: http://127.0.0.1:{port}/v1\n\
+             api_key: sk-live\nmodels: [gpt-4o]\n"
+        ),
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("groq-down.yaml"),
+        format!(
+            "id: down\nprovider_type: groq\nbase_url: http://127.0.0.1:{closed_port}/v1\n\
+             api_key: gsk-down\nmodels: [llama-3.3-70b]\n"
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "check", "--timeout", "5", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_match(r"✓ live\s+ok\s+\d+ ms  2 models").unwrap())
+        .stdout(predicate::str::is_match(r"✗ down\s+unreachable").unwrap());
+    assert!(server
+        .join()
+        .unwrap()
+        .contains("authorization: Bearer sk-live"));
+
+    let stored = fs::read_to_string(providers_dir.join("openai-live.yaml")).unwrap();
+    assert!(stored.contains("status: ok"));
+    assert!(stored.contains("latency_ms:"));
+    assert!(stored.contains("last_checked:"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "list", "--verbose", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Health: ok, \d+ ms \(checked ").unwrap())
+        .stdout(predicate::str::contains("Health: unreachable (checked "));
+}
+
+#[test]
+fn test_cost_compares_models_and_prices_usage_files() {
+    let temp_home = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "cost",
+        "--model",
+        "gpt-4o",
+        "--model",
+        "gpt-4o-mini",
+        "--input-tokens",
+        "1M",
+        "--output-tokens",
+        "200k",
+        "--format",
+        "json",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let rows: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(rows[0]["model"], "gpt-4o-mini");
+    assert_eq!(rows[1]["model"], "gpt-4o");
+    assert!((rows[1]["total_cost"].as_f64().unwrap() - 4.5).abs() < 1e-9);
+
+    let usage = temp_home.path().join("usage.json");
+    fs::write(
+        &usage,
+        r#"[{"model":"gpt-4o","input_tokens":1000000,"output_tokens":0},
+            {"model":"gpt-4o","input_tokens":0,"output_tokens":100000},
+            {"model":"claude-3-haiku-20240307","input_tokens":4000000},
+            {"model":"in-house-model","input_tokens":5}]"#,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["cost", "--usage"])
+        .arg(&usage)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"gpt-4o\s+openai\s+\$2\.50\s+\$1\.00\s+\$3\.50").unwrap())
+        .stdout(predicate::str::is_match(r"anthropic\s+\$1\.00\s+\$0\.00\s+\$1\.00").unwrap())
+        .stdout(predicate::str::contains("Total: $4.50 over 4 records"))
+        .stdout(predicate::str::contains(
+            "No pricing known for: in-house-model",
+        ));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "cost",
+        "--model",
+        "gpt-4o",
+        "--input-tokens",
+        "lots",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid token count 'lots'"));
+}
+
+#[test]
+fn test_models_compare_and_recommend_use_configured_instances() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work\nmodels: [gpt-4o, gpt-4o-mini, gpt-3.5-turbo]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["models", "compare", "gpt-4o", "gpt-3.5-turbo", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Status\s+active\s+deprecated").unwrap())
+        .stdout(predicate::str::is_match(r"vision\s+yes\s+no").unwrap())
+        .stdout(predicate::str::is_match(r"Instances\s+work\s+work").unwrap());
+
+    // Only the configured models are ranked without --all
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "models",
+        "recommend",
+        "--needs",
+        "vision,function_calling",
+        "--max-input-cost",
+        "1.0",
+        "--format",
+        "json",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let models: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let ids: Vec<&str> = models
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|model| model["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["gpt-4o-mini"]);
+    assert_eq!(models[0]["instances"][0], "work");
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["models", "recommend", "--needs", "telepathy", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown capability 'telepathy'"));
+}
+
+#[test]
+fn test_resolve_prints_label_target_as_json_and_env() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("anthropic-work.yaml"),
+        "id: anthropic-work\nprovider_type: anthropic\nbase_url: https://api.anthropic.com/v1\n\
+         api_key: sk-ant-work\nmodels: [claude-3-5-haiku-20241022, claude-sonnet-4-20250514]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: smart\n  target:\n    type: provider_model\n    instance_id: anthropic-work\n    \
+         model_id: claude-sonnet-4-20250514\n  assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["resolve", "smart", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let resolved: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(resolved["label"], "smart");
+    assert_eq!(resolved["instance"]["id"], "anthropic-work");
+    assert_eq!(resolved["instance"]["api_key"], "sk-ant-work");
+    assert_eq!(resolved["model"]["id"], "claude-sonnet-4-20250514");
+    assert_eq!(resolved["model"]["context_window"], 200000);
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["resolve", "smart", "--format", "env", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("ANTHROPIC_API_KEY=sk-ant-work"))
+        .stdout(predicate::str::contains(
+            "ANTHROPIC_MODEL=claude-sonnet-4-20250514",
+        ));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["resolve", "fast", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Label 'fast' is not assigned"));
+}
+
+#[test]
+fn test_label_fallback_chain_skips_unhealthy_instances() {
+    let temp_home = TempDir::new().unwrap();
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openrouter-main.yaml"),
+        "id: openrouter-main\nprovider_type: openrouter\nbase_url: https://openrouter.ai/api/v1\n\
+         api_key: sk-or-main\nmodels: [anthropic/claude-3.5-sonnet]\n",
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "labels",
+        "set",
+        "smart=openrouter:claude-3.5-sonnet,openai:gpt-4o",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["resolve", "smart", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let resolved: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(resolved["instance"]["id"], "openrouter-main");
+    assert_eq!(resolved["model"]["id"], "anthropic/claude-3.5-sonnet");
+
+    // A failed health check moves the label on to the next entry
+    fs::write(
+        providers_dir.join("openrouter-main.yaml"),
+        "id: openrouter-main\nprovider_type: openrouter\nbase_url: https://openrouter.ai/api/v1\n\
+         api_key: sk-or-main\nmodels: [anthropic/claude-3.5-sonnet]\nmetadata:\n  status: unreachable\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["resolve", "smart", "--format", "env", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("OPENAI_API_KEY=sk-work"))
+        .stdout(predicate::str::contains("OPENAI_MODEL=gpt-4o"))
+        .stderr(predicate::str::contains(
+            "skipped instance 'openrouter-main' failed its last health check (unreachable)",
+        ));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["setenv", "--label", "smart", "--shell", "bash", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("export OPENAI_API_KEY='sk-work'"));
+
+    // Required capabilities stand in for the whole chain
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "labels",
+        "set",
+        "smart=openrouter:claude-3.5-sonnet",
+        "--requires",
+        "vision,function_calling",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["resolve", "smart", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let resolved: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(resolved["instance"]["id"], "openai-work");
+    assert_eq!(resolved["model"]["id"], "gpt-4o");
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "labels",
+        "set",
+        "smart=openai:gpt-4o",
+        "--requires",
+        "telepathy",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown capability 'telepathy'"));
+}
+
+#[test]
+fn test_instances_bulk_set_active_and_remove_by_tag() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    for (id, provider_type) in [
+        ("stage-a", "openai"),
+        ("stage-b", "groq"),
+        ("prod", "openai"),
+    ] {
+        fs::write(
+            providers_dir.join(format!("{}-{}.yaml", provider_type, id)),
+            format!(
+                "id: {}\nprovider_type: {}\nbase_url: https://api.example.com/v1\n\
+                 api_key: sk-{}\nmodels: [gpt-4o-mini]\n",
+                id, provider_type, id
+            ),
+        )
+        .unwrap();
+    }
+    let assignment = |name: &str, id: &str| {
+        format!(
+            "- label_name: {}\n  target:\n    type: provider_instance\n    instance_id: {}\n  \
+             assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+            name, id
+        )
+    };
+    fs::write(
+        config_dir.join("tag_assignments.yaml"),
+        assignment("staging", "stage-a") + &assignment("staging", "stage-b"),
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        assignment("fast", "stage-a") + &assignment("smart", "prod"),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "instances",
+        "set-active",
+        "--tag",
+        "staging",
+        "--active",
+        "false",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("'stage-a' deactivated"))
+        .stdout(predicate::str::contains("'stage-b' deactivated"))
+        .stdout(predicate::str::contains("prod").not());
+    let stage_a = fs::read_to_string(providers_dir.join("openai-stage-a.yaml")).unwrap();
+    assert!(stage_a.contains("active: false"));
+    let prod = fs::read_to_string(providers_dir.join("openai-prod.yaml")).unwrap();
+    assert!(!prod.contains("active: false"));
+
+    // A dry run reports the plan and leaves everything in place
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "instances",
+        "remove",
+        "--tag",
+        "staging",
+        "--dry-run",
+        "--plan-format",
+        "json",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(plan["command"], "instances remove");
+    let changes = plan["changes"].as_array().unwrap();
+    assert!(changes.iter().any(|change| change["action"] == "delete"
+        && change["kind"] == "instance"
+        && change["target"] == "stage-a"));
+    assert!(changes
+        .iter()
+        .any(|change| change["kind"] == "assignment" && change["target"] == "fast"));
+    assert!(providers_dir.join("openai-stage-a.yaml").exists());
+    assert!(providers_dir.join("groq-stage-b.yaml").exists());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&[
+        "instances",
+        "remove",
+        "--tag",
+        "staging",
+        "--force",
+        "--home",
+    ])
+    .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("'stage-a' removed"))
+        .stdout(predicate::str::contains("'stage-b' removed"));
+    assert!(!providers_dir.join("openai-stage-a.yaml").exists());
+    assert!(!providers_dir.join("groq-stage-b.yaml").exists());
+    assert!(providers_dir.join("openai-prod.yaml").exists());
+    let labels = fs::read_to_string(config_dir.join("labels.yaml")).unwrap();
+    assert!(!labels.contains("fast"));
+    assert!(labels.contains("smart"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "remove", "--force", "--home"])
+        .arg(temp_home.path());
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_wizard_imports_credentials_from_an_answers_file() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n\
+         OPENAI_MODEL=gpt-4o-mini\n\
+         GROQ_API_KEY=gsk_1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    let answers = temp_home.path().join("wizard.yaml");
+    fs::write(
+        &answers,
+        "import:\n  - provider: openai\n    source: .env\n    id: openai-work\n\
+         labels:\n  fast: openai-work:gpt-4o-mini\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--dry-run", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("create instance openai-work"))
+        .stdout(predicate::str::contains("create assignment fast"))
+        .stdout(predicate::str::contains("No changes were made."));
+    assert!(!temp_home
+        .path()
+        .join(".config/aicred/inference_services/openai-open.yaml")
+        .exists());
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Saved instance 'openai-work'"))
+        .stdout(predicate::str::contains("Imported 1 instance(s)"));
+
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let instance = fs::read_to_string(
+        config_dir
+            .join("inference_services")
+            .join("openai-open.yaml"),
+    )
+    .unwrap();
+    assert!(instance.contains("id: openai-work"));
+    assert!(instance.contains("sk-1234567890abcdefghijklmnopqrstuvwxyz"));
+    assert!(instance.contains("gpt-4o-mini"));
+    let labels = fs::read_to_string(config_dir.join("labels.yaml")).unwrap();
+    assert!(labels.contains("label_name: fast"));
+    assert!(labels.contains("instance_id: openai-work"));
+    assert!(labels.contains("model_id: gpt-4o-mini"));
+
+    // A second run must say what to do with the existing configuration
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "set 'existing: merge', 'existing: replace' or 'existing: cancel'",
+    ));
+
+    fs::write(&answers, "existing: replace\nimport:\n  - provider: groq\n").unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert().success();
+    assert!(!config_dir
+        .join("inference_services")
+        .join("openai-open.yaml")
+        .exists());
+    let labels = fs::read_to_string(config_dir.join("labels.yaml")).unwrap();
+    assert!(!labels.contains("openai-work"));
+}
+
+#[test]
+fn test_wizard_fails_on_a_label_naming_an_unknown_model() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    let answers = temp_home.path().join("wizard.yaml");
+    fs::write(
+        &answers,
+        "import:\n  - provider: openai\n    source: .env\n    id: openai-work\n\
+         labels:\n  fast: openai-work:gpt-4o-mini\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Cannot set label 'fast': Provider instance 'openai-work' has no model 'gpt-4o-mini'",
+    ));
+    assert!(!temp_home.path().join(".config/aicred/labels.yaml").exists());
+}
+
+#[test]
+fn test_wizard_merge_keeps_configured_instances() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n\
+         OPENAI_MODEL=gpt-4o-mini\n\
+         GROQ_API_KEY=gsk_1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    // Configured by hand: the OpenAI key without its model, with user metadata and a tag
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-1234567890abcdefghijklmnopqrstuvwxyz\nmodels: []\nactive: false\n\
+         metadata:\n  owner: platform-team\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("tag_assignments.yaml"),
+        "- label_name: team\n  target:\n    type: provider_instance\n    instance_id: work\n  \
+         assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+    let answers = temp_home.path().join("wizard.yaml");
+    fs::write(&answers, "existing: merge\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Updated instance 'work'"))
+        .stdout(predicate::str::contains("Saved instance"));
+
+    let work = fs::read_to_string(providers_dir.join("openai-work.yaml")).unwrap();
+    assert!(work.contains("gpt-4o-mini"));
+    assert!(work.contains("owner: platform-team"));
+    assert!(work.contains("active: false"));
+    let tags = fs::read_to_string(config_dir.join("tag_assignments.yaml")).unwrap();
+    assert!(tags.contains("instance_id: work"));
+    let groq_files = fs::read_dir(&providers_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("groq-"))
+        .count();
+    assert_eq!(groq_files, 1);
+
+    // Nothing changed since, so a second merge has nothing to do
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--answers"])
+        .arg(&answers)
+        .arg("--home")
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing new to import"));
+}
+
+#[test]
+fn test_wizard_review_shows_provenance_and_filters_the_list() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n\
+         GROQ_API_KEY=gsk_1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(
+            temp_home.path().join(".env"),
+            fs::Permissions::from_mode(0o644),
+        )
+        .unwrap();
+    }
+
+    // Filter down to the Groq key, import everything shown, keep the default ID, no labels
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["wizard", "--home"])
+        .arg(temp_home.path())
+        .write_stdin("sort provider\nfilter provider=groq\nall\n\n\n");
+    let assert = cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".env:"))
+        .stdout(predicate::str::contains("confidence"))
+        .stdout(predicate::str::contains(
+            "(1 of 2 shown; 'clear' shows all)",
+        ))
+        .stdout(predicate::str::contains("Imported 1 instance(s)"));
+    #[cfg(unix)]
+    assert.stdout(predicate::str::contains("0644 world-readable"));
+    #[cfg(not(unix))]
+    let _ = assert;
+
+    let providers_dir = temp_home
+        .path()
+        .join(".config")
+        .join("aicred")
+        .join("inference_services");
+    let files: Vec<String> = fs::read_dir(&providers_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".yaml"))
+        .collect();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].starts_with("groq-"));
+}
+
+#[test]
+fn test_listing_commands_print_json() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work-secret\nmodels: [gpt-4o-mini]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_model\n    instance_id: work\n    \
+         model_id: gpt-4o-mini\n  assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    let json = |args: &[&str]| -> serde_json::Value {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(args)
+            .args(["--format", "json", "--home"])
+            .arg(temp_home.path());
+        let output = cmd.output().unwrap();
+        assert!(output.status.success(), "{:?} failed", args);
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let instances = json(&["instances", "list"]);
+    assert_eq!(instances["instances"][0]["id"], "work");
+    assert_eq!(instances["instances"][0]["has_api_key"], true);
+    assert!(instances["instances"][0].get("api_key").is_none());
+    assert_eq!(json(&["instances", "work"])["models"][0], "gpt-4o-mini");
+
+    let models = json(&["models", "list"]);
+    assert_eq!(models["models"][0]["model_id"], "gpt-4o-mini");
+    assert!(models["models"][0]["labels"].is_array());
+
+    let labels = json(&["labels", "list"]);
+    assert_eq!(labels["labels"][0]["name"], "fast");
+    assert_eq!(labels["labels"][0]["target"]["model_id"], "gpt-4o-mini");
+
+    assert_eq!(json(&["tags", "list"])["tags"], serde_json::json!([]));
+    assert!(json(&["providers"])["providers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|provider| provider["name"] == "openai"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["tags", "list", "--format", "yaml"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown format"));
+}
+
+#[test]
+fn test_config_file_sets_default_options() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.yaml"),
+        "format: json\nsecret_backend: nowhere\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["tags", "list", "--home"]).arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"tags\": []"));
+
+    // The environment overrides the file, and flags override both
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.env("AICRED_FORMAT", "table")
+        .args(["tags", "list", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No tags configured"));
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["tags", "list", "--format", "table", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No tags configured"));
+
+    let add = |store: Option<&str>| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args([
+            "instances",
+            "add",
+            "--id",
+            "work",
+            "--name",
+            "Work",
+            "--provider-type",
+            "openai",
+            "--base-url",
+            "https://api.openai.com/v1",
+            "--api-key",
+            "sk-work",
+        ]);
+        if let Some(store) = store {
+            cmd.args(["--store", store]);
+        }
+        cmd.arg("--home").arg(temp_home.path());
+        cmd.assert()
+    };
+    add(None)
+        .failure()
+        .stderr(predicate::str::contains("Unknown secret store 'nowhere'"));
+    add(Some("file")).success();
+
+    fs::write(config_dir.join("config.yaml"), "parallelism: many\n").unwrap();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["tags", "list", "--home"]).arg(temp_home.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid settings"));
+}
+
+#[test]
+fn test_config_file_sets_scanner_settings() {
+    let temp_home = TempDir::new().unwrap();
+    let install = temp_home.path().join("Apps").join("Claude");
+    fs::create_dir_all(&install).unwrap();
+    fs::write(
+        install.join("claude.json"),
+        r#"{"userID": "sk-ant-REDACTED"}"#,
+    )
+    .unwrap();
+
+    let scan = || {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.arg("--home")
+            .arg(temp_home.path())
+            .args(["scan", "--format", "csv"]);
+        cmd.assert()
+    };
+    // Nothing found exits 1
+    scan()
+        .failure()
+        .stdout(predicate::str::contains("anthropic,").not());
+
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.yaml"),
+        "scanners:\n  claude-desktop:\n    extra_paths: [~/Apps/Claude/claude.json]\n",
+    )
+    .unwrap();
+    scan()
+        .success()
+        .stdout(predicate::str::contains("anthropic,API Key,"));
+}
+
+#[test]
+fn test_scan_update_records_provenance() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-1234567890abcdefghijklmnopqrstuvwxyz\n",
+    )
+    .unwrap();
+    let scan_update = || {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(&["scan", "--include-values", "--update", "--home"])
+            .arg(temp_home.path());
+        cmd.assert().success();
+    };
+    scan_update();
+
+    let providers_dir = temp_home.path().join(".config/aicred/inference_services");
+    let (path, instance) = fs::read_dir(&providers_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+        .map(|path| {
+            let content = fs::read_to_string(&path).unwrap();
+            (
+                path,
+                serde_yaml::from_str::<serde_yaml::Value>(&content).unwrap(),
+            )
+        })
+        .find(|(_, instance)| instance["provider_type"].as_str() == Some("openai"))
+        .expect("scan --update should store the OpenAI key");
+    let id = instance["id"].as_str().unwrap().to_string();
+    let provenance = &instance["provenance"];
+    assert!(provenance["scanner"].as_str().is_some());
+    assert!(provenance["source_files"][0]
+        .as_str()
+        .unwrap()
+        .ends_with(".env"));
+    let scanned_at = provenance["scanned_at"].clone();
+
+    // A later scan keeps when and by whom the key was first found
+    scan_update();
+    let instance: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(instance["provenance"]["scanned_at"], scanned_at);
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(&["instances", "get", &id, "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Provenance:"))
+        .stdout(predicate::str::contains("Scanner:"))
+        .stdout(predicate::str::contains(".env"));
+}
+
+#[test]
+fn test_usage_record_and_report() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work\nmodels: [gpt-4o-mini]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_instance\n    instance_id: openai-work\n  \
+         assigned_at: 2026-03-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+
+    for args in [
+        vec![
+            "--model",
+            "gpt-4o-mini",
+            "--input-tokens",
+            "1M",
+            "--label",
+            "fast",
+        ],
+        vec![
+            "--model",
+            "in-house",
+            "--output-tokens",
+            "500",
+            "--cost",
+            "0.25",
+        ],
+        vec!["--model", "mystery-model", "--input-tokens", "10"],
+    ] {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(["usage", "record"])
+            .args(&args)
+            .arg("--home")
+            .arg(temp_home.path());
+        cmd.assert().success();
+    }
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["usage", "report", "--format", "json", "--home"])
+        .arg(temp_home.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let totals: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(totals[0]["key"], "unattributed");
+    assert_eq!(totals[0]["requests"], 2);
+    assert_eq!(totals[0]["unpriced"], 1);
+    assert_eq!(totals[1]["key"], "openai-work");
+    assert!((totals[1]["cost"].as_f64().unwrap() - 0.15).abs() < 1e-9);
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["usage", "report", "--by", "label", "--home"])
+        .arg(temp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("fast"))
+        .stdout(predicate::str::contains("1 unpriced"));
+
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    set_test_home_envs(&mut cmd, temp_home.path());
+    cmd.args(["usage", "report", "--by", "model", "--home"])
+        .arg(temp_home.path());
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_budget_warns_then_refuses_resolving_labels() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: smart\n  target:\n    type: provider_instance\n    instance_id: openai-work\n  \
+         assigned_at: 2026-03-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("aicred").unwrap();
+        set_test_home_envs(&mut cmd, temp_home.path());
+        cmd.args(args).arg("--home").arg(temp_home.path());
+        cmd.assert()
+    };
+
+    run(&["budget", "set", "--label", "smart", "--monthly", "5"]).success();
+    run(&[
+        "usage", "record", "-m", "gpt-4o", "--label", "smart", "--cost", "6",
+    ])
+    .success();
+
+    run(&["resolve", "smart"])
+        .success()
+        .stderr(predicate::str::contains(
+            "label 'smart' has spent 6.00 of its monthly budget of 5.00 (120%)",
+        ));
+    run(&["usage", "report", "--by", "label"])
+        .success()
+        .stdout(predicate::str::contains("$6.00 of $5.00 (120%)"));
+
+    run(&[
+        "budget",
+        "set",
+        "--instance",
+        "openai-work",
+        "--monthly",
+        "4",
+        "--refuse",
+    ])
+    .success();
+    run(&["resolve", "smart"])
+        .failure()
+        .stderr(predicate::str::contains(
+            "Budget exceeded: instance 'openai-work'",
+        ));
+    run(&["setenv", "--label", "smart", "--shell", "bash"]).failure();
+
+    let output = run(&["budget", "list", "--format", "json"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let budgets: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(budgets[0]["scope"], "instance");
+    assert_eq!(budgets[0]["refuse"], true);
+    assert_eq!(budgets[1]["name"], "smart");
+
+    run(&["budget", "remove", "--instance", "openai-work"]).success();
+    run(&["resolve", "smart"]).success();
+    run(&["budget", "remove", "--instance", "openai-work"]).failure();
+}
@@ -0,0 +1,174 @@
+//! Round-trip tests for `aicred setenv`.
+//!
+//! Each test evaluates the generated output in a real shell and checks the
+//! variables read back verbatim. Shells that are not installed are skipped.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A key with characters every shell treats specially somewhere
+const TRICKY_KEY: &str = "gsk-a'b\"c\\d$e `x` f!‘g’";
+
+/// Creates a home with two instances and a label pointing at one of them
+fn setup_home() -> TempDir {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = temp_home.path().join(".config").join("aicred");
+    let providers_dir = config_dir.join("inference_services");
+    fs::create_dir_all(&providers_dir).unwrap();
+    fs::write(
+        providers_dir.join("groq-fast.yaml"),
+        format!(
+            "id: groq-fast\nprovider_type: groq\nbase_url: https://api.groq.com/openai/v1\n\
+             api_key: '{}'\nmodels: [llama-3.1-8b-instant, llama-3.3-70b]\n",
+            TRICKY_KEY.replace('\'', "''")
+        ),
+    )
+    .unwrap();
+    fs::write(
+        providers_dir.join("openai-work.yaml"),
+        "id: openai-work\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+         api_key: sk-work\nmodels: [gpt-4o]\n",
+    )
+    .unwrap();
+    fs::write(
+        config_dir.join("labels.yaml"),
+        "- label_name: fast\n  target:\n    type: provider_model\n    instance_id: groq-fast\n    \
+         model_id: llama-3.3-70b\n  assigned_at: 2026-01-01T00:00:00Z\n  assigned_by: null\n",
+    )
+    .unwrap();
+    temp_home
+}
+
+fn shell_available(shell: &str) -> bool {
+    std::process::Command::new(shell)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Runs `script` in `shell` with `AICRED` and `AICRED_HOME` set, returning stdout
+fn run_in_shell(shell: &str, args: &[&str], script: &str, home: &Path) -> Option<String> {
+    if !shell_available(shell) {
+        eprintln!("{shell} is not installed; skipping");
+        return None;
+    }
+    let output = std::process::Command::new(shell)
+        .args(args)
+        .arg(script)
+        .env("AICRED", assert_cmd::cargo::cargo_bin("aicred"))
+        .env("AICRED_HOME", home)
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{shell} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Some(String::from_utf8(output.stdout).unwrap())
+}
+
+fn expected_label_values() -> String {
+    format!("{TRICKY_KEY}\nhttps://api.groq.com/openai/v1\nllama-3.3-70b\n")
+}
+
+fn assert_posix_round_trip(shell: &str) {
+    let home = setup_home();
+    let script = format!(
+        "eval \"$(\"$AICRED\" setenv --shell {shell} --label fast --home \"$AICRED_HOME\")\" && \
+         printf '%s\\n' \"$GROQ_API_KEY\" \"$GROQ_BASE_URL\" \"$GROQ_MODEL\""
+    );
+    if let Some(stdout) = run_in_shell(shell, &["-c"], &script, home.path()) {
+        assert_eq!(stdout, expected_label_values());
+    }
+}
+
+#[test]
+fn test_setenv_bash_round_trip() {
+    assert_posix_round_trip("bash");
+}
+
+#[test]
+fn test_setenv_zsh_round_trip() {
+    assert_posix_round_trip("zsh");
+}
+
+#[test]
+fn test_setenv_fish_round_trip() {
+    let home = setup_home();
+    let script = "$AICRED setenv --shell fish --label fast --home $AICRED_HOME | source; \
+                  and printf '%s\\n' $GROQ_API_KEY $GROQ_BASE_URL $GROQ_MODEL";
+    if let Some(stdout) = run_in_shell("fish", &["-c"], script, home.path()) {
+        assert_eq!(stdout, expected_label_values());
+    }
+}
+
+#[test]
+fn test_setenv_powershell_round_trip() {
+    let home = setup_home();
+    let script = "$ErrorActionPreference = 'Stop'; \
+                  Invoke-Expression (& $env:AICRED setenv --shell powershell --label fast --home $env:AICRED_HOME | Out-String); \
+                  [Console]::Out.Write(\"$env:GROQ_API_KEY`n$env:GROQ_BASE_URL`n$env:GROQ_MODEL`n\")";
+    if let Some(stdout) = run_in_shell(
+        "pwsh",
+        &["-NoProfile", "-NonInteractive", "-Command"],
+        script,
+        home.path(),
+    ) {
+        assert_eq!(stdout, expected_label_values());
+    }
+}
+
+#[test]
+fn test_setenv_instances_round_trip() {
+    let home = setup_home();
+    let script = "eval \"$(\"$AICRED\" setenv --shell bash --instance openai-work \
+                  --instance groq-fast --home \"$AICRED_HOME\")\" && \
+                  printf '%s\\n' \"$OPENAI_API_KEY\" \"$OPENAI_MODEL\" \"$GROQ_MODEL\"";
+    if let Some(stdout) = run_in_shell("bash", &["-c"], script, home.path()) {
+        // Instances without a label use their first model
+        assert_eq!(stdout, "sk-work\ngpt-4o\nllama-3.1-8b-instant\n");
+    }
+}
+
+#[test]
+fn test_setenv_shell_defaults_to_login_shell() {
+    let home = setup_home();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.env("HOME", home.path())
+        .env("SHELL", "/usr/local/bin/fish")
+        .args(["setenv", "--instance", "openai-work", "--home"])
+        .arg(home.path());
+    cmd.assert().success().stdout(predicate::str::contains(
+        "set -gx OPENAI_API_KEY 'sk-work'\n",
+    ));
+}
+
+#[test]
+fn test_setenv_rejects_unknown_shell() {
+    let home = setup_home();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.env("HOME", home.path())
+        .args(["setenv", "--shell", "tcsh", "--label", "fast", "--home"])
+        .arg(home.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported shell: tcsh"));
+}
+
+#[test]
+fn test_setenv_dry_run_masks_keys() {
+    let home = setup_home();
+    let mut cmd = Command::cargo_bin("aicred").unwrap();
+    cmd.env("HOME", home.path())
+        .args(["setenv", "--instance", "openai-work", "--dry-run", "--home"])
+        .arg(home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("OPENAI_API_KEY=****"))
+        .stdout(predicate::str::contains("sk-work").not());
+}
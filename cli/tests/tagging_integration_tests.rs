@@ -22,6 +22,21 @@ mod cli_integration_tests {
         (cmd, temp_dir)
     }
 
+    /// Configures an OpenAI instance with `gpt-4` for the labels to point at
+    fn write_openai_instance(home: &std::path::Path) {
+        let providers_dir = home
+            .join(".config")
+            .join("aicred")
+            .join("inference_services");
+        fs::create_dir_all(&providers_dir).unwrap();
+        fs::write(
+            providers_dir.join("openai-main.yaml"),
+            "id: openai-main\nprovider_type: openai\nbase_url: https://api.openai.com/v1\n\
+             api_key: sk-test\nmodels: [gpt-4]\n",
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_cli_tag_lifecycle() {
         let (mut cmd, _temp_dir) = setup_test_cli();
@@ -91,6 +106,7 @@ mod cli_integration_tests {
     #[test]
     fn test_cli_label_lifecycle() {
         let (mut cmd, _temp_dir) = setup_test_cli();
+        write_openai_instance(_temp_dir.path());
 
         // Test label creation
         cmd.arg("labels")
@@ -220,6 +236,7 @@ mod cli_integration_tests {
     #[test]
     fn test_cli_label_assignment_workflow() {
         let (mut cmd, _temp_dir) = setup_test_cli();
+        write_openai_instance(_temp_dir.path());
 
         // Create a label
         cmd.arg("labels")
@@ -285,6 +302,7 @@ mod cli_integration_tests {
     #[test]
     fn test_cli_label_uniqueness_constraint() {
         let (mut cmd, _temp_dir) = setup_test_cli();
+        write_openai_instance(_temp_dir.path());
 
         // Create a label
         cmd.arg("labels")
@@ -319,6 +337,7 @@ mod cli_integration_tests {
     #[test]
     fn test_cli_file_persistence() {
         let (mut cmd, _temp_dir) = setup_test_cli();
+        write_openai_instance(_temp_dir.path());
 
         // Create tags and labels
         cmd.arg("tags")
@@ -0,0 +1,74 @@
+//! Benchmarks env-file key extraction with cached pattern sets against compiling
+//! a regex per pattern per file.
+//!
+//! Run with `cargo bench -p aicred-core --bench env_patterns`.
+
+use aicred_core::discovery::extract_env_keys;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const PATTERNS: &[(&str, &str)] = &[
+    ("OPENAI_API_KEY", "openai"),
+    ("ANTHROPIC_API_KEY", "anthropic"),
+    ("GROQ_API_KEY", "groq"),
+    ("HUGGINGFACE_API_KEY", "huggingface"),
+    ("HF_TOKEN", "huggingface"),
+    ("COHERE_API_KEY", "cohere"),
+    ("MISTRAL_API_KEY", "mistral"),
+    ("GOOGLE_API_KEY", "google"),
+];
+
+/// Extraction as it worked before pattern caching: one regex compiled per pattern per call.
+fn extract_uncached(content: &str) -> usize {
+    PATTERNS
+        .iter()
+        .map(|(env_var, _)| {
+            let pattern = format!(
+                r"(?i){}\s*=\s*([a-zA-Z0-9_-]{{15,}})",
+                regex::escape(env_var)
+            );
+            regex::Regex::new(&pattern)
+                .unwrap()
+                .captures_iter(content)
+                .count()
+        })
+        .sum()
+}
+
+fn large_env_file(lines: usize) -> String {
+    let mut content = String::with_capacity(lines * 32);
+    for i in 0..lines {
+        content.push_str(&format!("APP_SETTING_{i}=value-{i}\n"));
+    }
+    content.push_str("OPENAI_API_KEY=sk-abcdefghijklmnopqrstuvwxyz012345\n");
+    content
+}
+
+fn time(iterations: u32, mut f: impl FnMut() -> usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    start.elapsed() / iterations
+}
+
+fn main() {
+    for (files, lines) in [(200, 20), (20, 20_000)] {
+        let content = large_env_file(lines);
+        let uncached = time(5, || {
+            (0..files)
+                .map(|_| extract_uncached(black_box(&content)))
+                .sum()
+        });
+        let cached = time(5, || {
+            (0..files)
+                .map(|_| extract_env_keys(black_box(&content), PATTERNS).len())
+                .sum()
+        });
+
+        println!(
+            "{files} files x {lines} lines: uncached {uncached:?}, cached {cached:?} ({:.1}x)",
+            uncached.as_secs_f64() / cached.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,609 @@
+#![allow(clippy::missing_errors_doc)]
+//! Extraction of `.zip`, `.tar` and `.tar.gz` backups for scanning.
+//!
+//! Archives are extracted into a private temporary directory that is scanned like a
+//! home directory and removed when the [`ExtractedArchive`] is dropped. Entry paths
+//! are sanitized so nothing is written outside that directory, and extraction stops
+//! at configurable size and entry limits.
+
+use crate::error::{Error, Result};
+use flate2::read::{DeflateDecoder, GzDecoder};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default limit on the total bytes extracted from one archive (64MB).
+pub const DEFAULT_MAX_ARCHIVE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Default limit on the number of files extracted from one archive.
+pub const DEFAULT_MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// Supported archive formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// ZIP archive (stored or deflated entries)
+    Zip,
+    /// Uncompressed tar archive
+    Tar,
+    /// Gzip-compressed tar archive
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Detects the archive format from a file name
+    #[must_use]
+    #[allow(clippy::case_sensitive_file_extension_comparisons)] // name is lowercased
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Limits applied while extracting an archive.
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveLimits {
+    /// Entries larger than this are skipped
+    pub max_entry_size: u64,
+    /// Extraction stops once this many bytes have been written
+    pub max_total_size: u64,
+    /// Extraction stops once this many files have been written
+    pub max_entries: usize,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entry_size: crate::DEFAULT_MAX_FILE_SIZE as u64,
+            max_total_size: DEFAULT_MAX_ARCHIVE_BYTES,
+            max_entries: DEFAULT_MAX_ARCHIVE_ENTRIES,
+        }
+    }
+}
+
+/// Finds archives to scan for a root: the root itself if it is an archive, or the
+/// archives directly inside it.
+#[must_use]
+pub fn find_archives(root: &Path) -> Vec<PathBuf> {
+    if root.is_file() {
+        return ArchiveKind::from_path(root)
+            .map(|_| vec![root.to_path_buf()])
+            .unwrap_or_default();
+    }
+
+    let mut archives: Vec<PathBuf> = std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && ArchiveKind::from_path(path).is_some())
+                .collect()
+        })
+        .unwrap_or_default();
+    archives.sort();
+    archives
+}
+
+/// An archive extracted into a temporary directory, removed on drop.
+#[derive(Debug)]
+pub struct ExtractedArchive {
+    source: PathBuf,
+    dir: PathBuf,
+}
+
+impl ExtractedArchive {
+    /// Extracts an archive into a new private temporary directory
+    pub fn extract(source: &Path, limits: &ArchiveLimits) -> Result<Self> {
+        let kind = ArchiveKind::from_path(source).ok_or_else(|| {
+            Error::ValidationError(format!("Not a supported archive: {}", source.display()))
+        })?;
+
+        let extracted = Self {
+            source: source.to_path_buf(),
+            dir: create_private_temp_dir()?,
+        };
+        let mut writer = EntryWriter::new(&extracted.dir, limits);
+        let file = File::open(source)?;
+        match kind {
+            ArchiveKind::Zip => extract_zip(file, &mut writer),
+            ArchiveKind::Tar => extract_tar(file, &mut writer),
+            ArchiveKind::TarGz => extract_tar(GzDecoder::new(file), &mut writer),
+        }
+        .map_err(|e| Error::ParseFailed {
+            path: source.to_path_buf(),
+            format: "archive".to_string(),
+            message: format!("Invalid archive: {e}"),
+        })?;
+
+        tracing::debug!(
+            "Extracted {} files from {} into {}",
+            writer.entries,
+            source.display(),
+            extracted.dir.display()
+        );
+        Ok(extracted)
+    }
+
+    /// Gets the archive the files were extracted from
+    #[must_use]
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Gets the temporary directory holding the extracted files
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Maps a path inside the extraction directory to `archive!/inner/path`.
+    ///
+    /// Paths outside the extraction directory are returned unchanged.
+    #[must_use]
+    pub fn display_path(&self, extracted: &str) -> String {
+        Path::new(extracted).strip_prefix(&self.dir).map_or_else(
+            |_| extracted.to_string(),
+            |inner| format!("{}!/{}", self.source.display(), inner.display()),
+        )
+    }
+}
+
+impl Drop for ExtractedArchive {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+            tracing::warn!(
+                "Failed to remove extracted archive {}: {}",
+                self.dir.display(),
+                e
+            );
+        }
+    }
+}
+
+fn create_private_temp_dir() -> Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let dir = std::env::temp_dir().join(format!(
+        "aicred-archive-{}-{}-{nanos}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+/// Writes sanitized entries into the extraction directory while enforcing limits.
+struct EntryWriter<'a> {
+    dir: &'a Path,
+    limits: &'a ArchiveLimits,
+    entries: usize,
+    total: u64,
+}
+
+impl<'a> EntryWriter<'a> {
+    const fn new(dir: &'a Path, limits: &'a ArchiveLimits) -> Self {
+        Self {
+            dir,
+            limits,
+            entries: 0,
+            total: 0,
+        }
+    }
+
+    /// Checks whether an entry of this size should be extracted
+    const fn accepts(&self, size: u64) -> bool {
+        size <= self.limits.max_entry_size
+            && self.total + size <= self.limits.max_total_size
+            && self.entries < self.limits.max_entries
+    }
+
+    /// Writes an entry, returning `false` if its name is unsafe or limits were hit.
+    /// A later entry with the same name replaces the earlier one, as `tar`
+    /// does with appended entries
+    fn write(&mut self, name: &str, size: u64, reader: &mut impl Read) -> std::io::Result<bool> {
+        let Some(relative) = sanitize_entry_name(name) else {
+            tracing::debug!("Skipping unsafe archive entry: {}", name);
+            return Ok(false);
+        };
+        if !self.accepts(size) {
+            tracing::debug!(
+                "Skipping archive entry over limits: {} ({} bytes)",
+                name,
+                size
+            );
+            return Ok(false);
+        }
+
+        let target = self.dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match std::fs::remove_file(&target) {
+            Ok(()) => tracing::debug!("Replacing duplicate archive entry: {}", name),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&target)?;
+        // Never trust the declared size: copy at most the limit
+        let written = std::io::copy(&mut reader.take(self.limits.max_entry_size), &mut file)?;
+
+        self.entries += 1;
+        self.total += written;
+        Ok(true)
+    }
+}
+
+/// Converts an archive entry name to a relative path, rejecting absolute paths and `..`
+fn sanitize_entry_name(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        None
+    } else {
+        Some(relative)
+    }
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+/// Extracts a ZIP archive using its central directory.
+fn extract_zip(mut file: File, writer: &mut EntryWriter<'_>) -> std::io::Result<()> {
+    const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+    const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+    const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+
+    // The end of central directory record is in the last 22 + 65535 bytes
+    let len = file.metadata()?.len();
+    let tail_len = len.min(22 + 65_535);
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let eocd = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| u32_at(&tail, i) == EOCD_SIGNATURE)
+        .ok_or_else(|| invalid("missing end of central directory"))?;
+
+    let entry_count = usize::from(u16_at(&tail, eocd + 10));
+    let directory_size = u32_at(&tail, eocd + 12);
+    let directory_offset = u32_at(&tail, eocd + 16);
+    if directory_offset == u32::MAX || directory_size == u32::MAX {
+        return Err(invalid("ZIP64 archives are not supported"));
+    }
+
+    file.seek(SeekFrom::Start(u64::from(directory_offset)))?;
+    let mut directory = vec![
+        0u8;
+        usize::try_from(directory_size)
+            .map_err(|_| invalid("central directory too large"))?
+    ];
+    file.read_exact(&mut directory)?;
+
+    let mut offset = 0;
+    for _ in 0..entry_count {
+        if offset + 46 > directory.len() || u32_at(&directory, offset) != CENTRAL_SIGNATURE {
+            return Err(invalid("corrupt central directory"));
+        }
+        let flags = u16_at(&directory, offset + 8);
+        let method = u16_at(&directory, offset + 10);
+        let compressed_size = u64::from(u32_at(&directory, offset + 20));
+        let size = u64::from(u32_at(&directory, offset + 24));
+        let name_len = usize::from(u16_at(&directory, offset + 28));
+        let extra_len = usize::from(u16_at(&directory, offset + 30));
+        let comment_len = usize::from(u16_at(&directory, offset + 32));
+        let local_offset = u64::from(u32_at(&directory, offset + 42));
+        let name_end = offset + 46 + name_len;
+        if name_end > directory.len() {
+            return Err(invalid("corrupt central directory"));
+        }
+        let name = String::from_utf8_lossy(&directory[offset + 46..name_end]).into_owned();
+        offset = name_end + extra_len + comment_len;
+
+        // Skip directories and encrypted entries
+        if name.ends_with('/') || flags & 1 != 0 {
+            continue;
+        }
+
+        let mut local = [0u8; 30];
+        file.seek(SeekFrom::Start(local_offset))?;
+        file.read_exact(&mut local)?;
+        if u32_at(&local, 0) != LOCAL_SIGNATURE {
+            return Err(invalid("corrupt local file header"));
+        }
+        let data_offset =
+            local_offset + 30 + u64::from(u16_at(&local, 26)) + u64::from(u16_at(&local, 28));
+        file.seek(SeekFrom::Start(data_offset))?;
+        let mut data = Read::by_ref(&mut file).take(compressed_size);
+
+        match method {
+            0 => writer.write(&name, size, &mut data)?,
+            8 => writer.write(&name, size, &mut DeflateDecoder::new(data))?,
+            _ => {
+                tracing::debug!(
+                    "Skipping ZIP entry with unsupported method {}: {}",
+                    method,
+                    name
+                );
+                false
+            }
+        };
+    }
+
+    Ok(())
+}
+
+/// Extracts a ustar/GNU tar stream.
+fn extract_tar(mut reader: impl Read, writer: &mut EntryWriter<'_>) -> std::io::Result<()> {
+    let mut long_name: Option<String> = None;
+    let mut header = [0u8; 512];
+
+    loop {
+        if !read_block(&mut reader, &mut header)? || header.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+
+        let size = parse_octal(&header[124..136]).ok_or_else(|| invalid("invalid tar size"))?;
+        let padded = size.div_ceil(512) * 512;
+        let type_flag = header[156];
+
+        let name = long_name.take().unwrap_or_else(|| {
+            let name = c_string(&header[0..100]);
+            let prefix = c_string(&header[345..500]);
+            if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                format!("{prefix}/{name}")
+            } else {
+                name
+            }
+        });
+
+        let mut data = (&mut reader).take(padded);
+        match type_flag {
+            b'0' | 0 => {
+                writer.write(&name, size, &mut (&mut data).take(size))?;
+            }
+            // GNU long name: the entry's data is the next entry's name
+            b'L' => {
+                let mut buf = Vec::new();
+                (&mut data).take(size).read_to_end(&mut buf)?;
+                long_name = Some(c_string(&buf));
+            }
+            _ => {}
+        }
+        // Skip whatever remains of the entry, including padding
+        std::io::copy(&mut data, &mut std::io::sink())?;
+    }
+}
+
+/// Reads a full 512-byte block, returning `false` at a clean end of stream
+fn read_block(reader: &mut impl Read, block: &mut [u8; 512]) -> std::io::Result<bool> {
+    let mut filled = 0;
+    while filled < block.len() {
+        let read = reader.read(&mut block[filled..])?;
+        if read == 0 {
+            return if filled == 0 {
+                Ok(false)
+            } else {
+                Err(invalid("truncated tar header"))
+            };
+        }
+        filled += read;
+    }
+    Ok(true)
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn parse_octal(bytes: &[u8]) -> Option<u64> {
+    let text = c_string(bytes);
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn tar_entry(name: &str, content: &[u8]) -> Vec<u8> {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", content.len()).as_bytes());
+        header[156] = b'0';
+        header[257..262].copy_from_slice(b"ustar");
+        // Checksum is computed with the checksum field set to spaces
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+        header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+
+        let mut entry = header.to_vec();
+        entry.extend_from_slice(content);
+        entry.resize(512 + content.len().div_ceil(512) * 512, 0);
+        entry
+    }
+
+    fn stored_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut central = Vec::new();
+        for (name, content) in entries {
+            let offset = u32::try_from(zip.len()).unwrap();
+            let size = u32::try_from(content.len()).unwrap();
+            let name_len = u16::try_from(name.len()).unwrap();
+
+            zip.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+            zip.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            zip.extend_from_slice(&size.to_le_bytes());
+            zip.extend_from_slice(&size.to_le_bytes());
+            zip.extend_from_slice(&name_len.to_le_bytes());
+            zip.extend_from_slice(&0u16.to_le_bytes());
+            zip.extend_from_slice(name.as_bytes());
+            zip.extend_from_slice(content);
+
+            central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend_from_slice(&size.to_le_bytes());
+            central.extend_from_slice(&size.to_le_bytes());
+            central.extend_from_slice(&name_len.to_le_bytes());
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let central_offset = u32::try_from(zip.len()).unwrap();
+        let count = u16::try_from(entries.len()).unwrap();
+        zip.extend_from_slice(&central);
+        zip.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        zip.extend_from_slice(&[0, 0, 0, 0]);
+        zip.extend_from_slice(&count.to_le_bytes());
+        zip.extend_from_slice(&count.to_le_bytes());
+        zip.extend_from_slice(&u32::try_from(central.len()).unwrap().to_le_bytes());
+        zip.extend_from_slice(&central_offset.to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip
+    }
+
+    #[test]
+    fn test_extract_tar_gz() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive = temp_dir.path().join("backup.tar.gz");
+
+        let mut tar = tar_entry(".config/app/.env", b"OPENAI_API_KEY=sk-test\n");
+        tar.extend(tar_entry("../escape.env", b"SHOULD_NOT=exist\n"));
+        tar.extend([0u8; 1024]);
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar).unwrap();
+        std::fs::write(&archive, encoder.finish().unwrap()).unwrap();
+
+        let extracted = ExtractedArchive::extract(&archive, &ArchiveLimits::default()).unwrap();
+        let env_file = extracted.path().join(".config/app/.env");
+        assert_eq!(
+            std::fs::read_to_string(&env_file).unwrap(),
+            "OPENAI_API_KEY=sk-test\n"
+        );
+        assert!(!extracted.path().join("../escape.env").exists());
+        assert_eq!(
+            extracted.display_path(&env_file.display().to_string()),
+            format!("{}!/.config/app/.env", archive.display())
+        );
+
+        let dir = extracted.path().to_path_buf();
+        drop(extracted);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_extract_zip_respects_limits() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive = temp_dir.path().join("export.zip");
+        std::fs::write(
+            &archive,
+            stored_zip(&[
+                ("settings.json", b"{\"apiKey\": \"sk-test\"}"),
+                ("big.log", &[b'x'; 64]),
+            ]),
+        )
+        .unwrap();
+
+        let limits = ArchiveLimits {
+            max_entry_size: 32,
+            ..ArchiveLimits::default()
+        };
+        let extracted = ExtractedArchive::extract(&archive, &limits).unwrap();
+        assert!(extracted.path().join("settings.json").is_file());
+        assert!(!extracted.path().join("big.log").exists());
+    }
+
+    #[test]
+    fn test_duplicate_entries_keep_the_last() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let tar_archive = temp_dir.path().join("backup.tar");
+        let mut tar = tar_entry(".env", b"OPENAI_API_KEY=sk-old\n");
+        tar.extend(tar_entry(".env", b"OPENAI_API_KEY=sk-new\n"));
+        tar.extend([0u8; 1024]);
+        std::fs::write(&tar_archive, tar).unwrap();
+        let zip_archive = temp_dir.path().join("export.zip");
+        std::fs::write(
+            &zip_archive,
+            stored_zip(&[
+                (".env", b"OPENAI_API_KEY=sk-old\n"),
+                (".env", b"OPENAI_API_KEY=sk-new\n"),
+            ]),
+        )
+        .unwrap();
+
+        for archive in [tar_archive, zip_archive] {
+            let extracted = ExtractedArchive::extract(&archive, &ArchiveLimits::default()).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(extracted.path().join(".env")).unwrap(),
+                "OPENAI_API_KEY=sk-new\n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_archives() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("a.zip"), b"").unwrap();
+        std::fs::write(temp_dir.path().join("b.tgz"), b"").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), b"").unwrap();
+
+        let archives = find_archives(temp_dir.path());
+        assert_eq!(
+            archives,
+            vec![temp_dir.path().join("a.zip"), temp_dir.path().join("b.tgz")]
+        );
+        assert_eq!(find_archives(&temp_dir.path().join("a.zip")).len(), 1);
+        assert!(ArchiveKind::from_path(Path::new("notes.txt")).is_none());
+    }
+}
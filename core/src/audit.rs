@@ -0,0 +1,401 @@
+#![allow(clippy::missing_errors_doc)]
+//! Structured audit log of scans.
+//!
+//! Each scan appends one [`AuditRecord`] as a JSON line: who ran it, when, what
+//! was scanned with which options, and what was found, identified by key hash
+//! and never by value. With hash chaining enabled, every record also stores the
+//! SHA-256 of the previous record and of itself, so [`AuditLog::verify`] can
+//! detect edited, reordered or deleted records.
+
+use crate::error::{Error, Result};
+use crate::models::{RiskLevel, ScanResult};
+use crate::ScanOptions;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the audit record format.
+pub const AUDIT_FORMAT_VERSION: u32 = 1;
+
+/// Previous hash of the first chained record.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Who ran the audited action.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditActor {
+    /// Login name of the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Host the action ran on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+impl AuditActor {
+    /// Identifies the current user and host from the environment.
+    #[must_use]
+    pub fn current() -> Self {
+        let env = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| std::env::var(name).ok())
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            user: env(&["USER", "USERNAME", "LOGNAME"]),
+            hostname: env(&["HOSTNAME", "COMPUTERNAME"]).or_else(|| {
+                std::fs::read_to_string("/etc/hostname")
+                    .ok()
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+            }),
+        }
+    }
+}
+
+/// Counts of what an audited scan found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditCounts {
+    /// Keys found
+    pub keys: usize,
+    /// Keys assessed as high or critical risk
+    pub high_risk: usize,
+    /// Application configurations found
+    pub config_instances: usize,
+    /// Files read
+    pub files_scanned: u32,
+}
+
+/// A key found by an audited scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditFinding {
+    /// Provider the key belongs to
+    pub provider: String,
+    /// Type of the value
+    pub value_type: String,
+    /// File the key was found in
+    pub source_file: String,
+    /// SHA-256 hash of the key
+    pub hash: String,
+}
+
+/// One audited action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Record format version
+    pub version: u32,
+    /// When the action finished
+    pub timestamp: DateTime<Utc>,
+    /// Action that was audited, such as `scan`
+    pub action: String,
+    /// Who ran the action
+    pub actor: AuditActor,
+    /// Home directory the action ran against
+    pub home_dir: String,
+    /// Additional directories that were scanned
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<String>,
+    /// Options the action ran with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, serde_json::Value>,
+    /// Providers that were scanned
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers_scanned: Vec<String>,
+    /// What was found
+    pub counts: AuditCounts,
+    /// Keys found
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<AuditFinding>,
+    /// Hash of the previous record, when chained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// Hash of this record, when chained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl AuditRecord {
+    /// Records a finished scan and the options it ran with.
+    #[must_use]
+    pub fn for_scan(result: &ScanResult, options: &ScanOptions) -> Self {
+        let mut recorded = BTreeMap::new();
+        recorded.insert(
+            "include_full_values".to_string(),
+            options.includes_full_values().into(),
+        );
+        recorded.insert("redaction".to_string(), options.redaction.as_str().into());
+        recorded.insert(
+            "hash_algorithm".to_string(),
+            options.fingerprint.algorithm().as_str().into(),
+        );
+        recorded.insert("max_file_size".to_string(), options.max_file_size.into());
+        if let Some(ref only) = options.only_providers {
+            recorded.insert("only_providers".to_string(), only.clone().into());
+        }
+        if let Some(ref exclude) = options.exclude_providers {
+            recorded.insert("exclude_providers".to_string(), exclude.clone().into());
+        }
+        recorded.insert("probe_models".to_string(), options.probe_models.into());
+        recorded.insert("scan_archives".to_string(), options.scan_archives.into());
+        recorded.insert(
+            "detection_rules".to_string(),
+            options.detection_rules.len().into(),
+        );
+
+        Self {
+            version: AUDIT_FORMAT_VERSION,
+            timestamp: result.scan_completed_at,
+            action: "scan".to_string(),
+            actor: AuditActor::current(),
+            home_dir: result.home_directory.clone(),
+            roots: options
+                .extra_roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect(),
+            options: recorded,
+            providers_scanned: result.providers_scanned.clone(),
+            counts: AuditCounts {
+                keys: result.keys.len(),
+                high_risk: result
+                    .keys
+                    .iter()
+                    .filter(|k| k.risk >= RiskLevel::High)
+                    .count(),
+                config_instances: result.config_instances.len(),
+                files_scanned: result.files_scanned,
+            },
+            findings: result
+                .keys
+                .iter()
+                .map(|key| AuditFinding {
+                    provider: key.provider.clone(),
+                    value_type: key.value_type.to_string(),
+                    source_file: key.source_file.clone(),
+                    hash: key.hash.clone(),
+                })
+                .collect(),
+            prev_hash: None,
+            hash: None,
+        }
+    }
+
+    /// Adds an option the action ran with.
+    #[must_use]
+    pub fn with_option(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.options.insert(name.into(), value.into());
+        self
+    }
+
+    /// SHA-256 of the record with its own hash left out.
+    pub fn compute_hash(&self) -> Result<String> {
+        let unhashed = Self {
+            hash: None,
+            ..self.clone()
+        };
+        let content =
+            serde_json::to_vec(&unhashed).map_err(|e| Error::SerializationError(e.to_string()))?;
+        Ok(hex::encode(Sha256::digest(content)))
+    }
+}
+
+/// An append-only JSON Lines audit log.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    hash_chain: bool,
+}
+
+impl AuditLog {
+    /// Opens the audit log at a path.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            hash_chain: false,
+        }
+    }
+
+    /// Sets whether appended records are hash chained.
+    #[must_use]
+    pub const fn with_hash_chain(mut self, hash_chain: bool) -> Self {
+        self.hash_chain = hash_chain;
+        self
+    }
+
+    /// Path of the log file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a record, chaining it to the last record when enabled.
+    pub fn append(&self, mut record: AuditRecord) -> Result<AuditRecord> {
+        if self.hash_chain {
+            let prev_hash = self
+                .read()?
+                .last()
+                .and_then(|last| last.hash.clone())
+                .unwrap_or_else(|| GENESIS_HASH.to_string());
+            record.prev_hash = Some(prev_hash);
+            record.hash = None;
+            record.hash = Some(record.compute_hash()?);
+        }
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        let line =
+            serde_json::to_string(&record).map_err(|e| Error::SerializationError(e.to_string()))?;
+        writeln!(file, "{line}")?;
+        Ok(record)
+    }
+
+    /// Reads every record in file order.
+    pub fn read(&self) -> Result<Vec<AuditRecord>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut records = Vec::new();
+        for (index, line) in std::io::BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|e| Error::ParseFailed {
+                path: self.path.clone(),
+                format: "jsonl".to_string(),
+                message: format!("line {}: {e}", index + 1),
+            })?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Checks the hash chain, returning how many chained records were verified.
+    ///
+    /// Records written before chaining was enabled are skipped; once a chained
+    /// record appears, every later record must be chained to its predecessor.
+    pub fn verify(&self) -> Result<usize> {
+        let mut previous: Option<String> = None;
+        let mut verified = 0;
+        for (index, record) in self.read()?.iter().enumerate() {
+            let number = index + 1;
+            let Some(ref hash) = record.hash else {
+                if previous.is_some() {
+                    return Err(Error::ValidationError(format!(
+                        "audit record {number} is not chained"
+                    )));
+                }
+                continue;
+            };
+            let expected_prev = previous.as_deref().unwrap_or(GENESIS_HASH);
+            if record.prev_hash.as_deref() != Some(expected_prev) {
+                return Err(Error::ValidationError(format!(
+                    "audit record {number} does not follow the previous record"
+                )));
+            }
+            if record.compute_hash()? != *hash {
+                return Err(Error::ValidationError(format!(
+                    "audit record {number} was modified"
+                )));
+            }
+            previous = Some(hash.clone());
+            verified += 1;
+        }
+        Ok(verified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Confidence, DiscoveredCredential, ValueType};
+    use tempfile::TempDir;
+
+    fn record() -> AuditRecord {
+        let mut result = ScanResult::new(
+            "/home/u".to_string(),
+            vec!["openai".to_string()],
+            Utc::now(),
+        );
+        result.add_key(DiscoveredCredential::new(
+            "openai".to_string(),
+            "/home/u/.env".to_string(),
+            ValueType::ApiKey,
+            Confidence::High,
+            "sk-proj-auditsecret1234567890".to_string(),
+        ));
+        AuditRecord::for_scan(&result, &ScanOptions::default()).with_option("format", "json")
+    }
+
+    #[test]
+    fn test_record_has_counts_but_no_secrets() {
+        let record = record();
+        assert_eq!(record.counts.keys, 1);
+        assert_eq!(record.findings[0].provider, "openai");
+        assert_eq!(record.options["format"], "json");
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(!line.contains("auditsecret"));
+    }
+
+    #[test]
+    fn test_chained_log_verifies() {
+        let temp = TempDir::new().unwrap();
+        let log = AuditLog::new(temp.path().join("audit.jsonl"));
+        // Records from before chaining was enabled are skipped
+        log.append(record()).unwrap();
+        let chained = log.clone().with_hash_chain(true);
+        let first = chained.append(record()).unwrap();
+        let second = chained.append(record()).unwrap();
+
+        assert_eq!(first.prev_hash.as_deref(), Some(GENESIS_HASH));
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(log.read().unwrap().len(), 3);
+        assert_eq!(log.verify().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_tampering_breaks_chain() {
+        let temp = TempDir::new().unwrap();
+        let log = AuditLog::new(temp.path().join("audit.jsonl")).with_hash_chain(true);
+        for _ in 0..3 {
+            log.append(record()).unwrap();
+        }
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        let edited = content.replacen("\"keys\":1", "\"keys\":0", 1);
+        std::fs::write(log.path(), edited).unwrap();
+        assert!(log
+            .verify()
+            .unwrap_err()
+            .to_string()
+            .contains("record 1 was modified"));
+
+        let mut lines: Vec<&str> = content.lines().collect();
+        lines.remove(1);
+        std::fs::write(log.path(), lines.join("\n")).unwrap();
+        assert!(log
+            .verify()
+            .unwrap_err()
+            .to_string()
+            .contains("record 2 does not follow"));
+    }
+}
@@ -0,0 +1,209 @@
+#![allow(clippy::missing_errors_doc)]
+//! Baseline (allowlist) support for suppressing known findings.
+//!
+//! A baseline records the hashes of credentials that are intentionally present so
+//! that later scans only report new findings. Baselines never store secret values.
+
+use crate::error::{Error, Result};
+use crate::models::{DiscoveredCredential, ScanResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Current baseline file format version.
+pub const BASELINE_VERSION: u32 = 1;
+
+/// A single known finding recorded in a baseline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// SHA-256 hash of the credential value
+    pub hash: String,
+    /// Provider the credential belongs to
+    pub provider: String,
+    /// Source file where the credential was found (informational only)
+    pub source_file: String,
+}
+
+/// A set of known findings that should not be reported again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Baseline file format version
+    pub version: u32,
+    /// When the baseline was created
+    pub created_at: DateTime<Utc>,
+    /// Recorded findings
+    pub entries: Vec<BaselineEntry>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Baseline {
+    /// Creates an empty baseline
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            version: BASELINE_VERSION,
+            created_at: Utc::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Creates a baseline from all keys in a scan result
+    #[must_use]
+    pub fn from_scan_result(result: &ScanResult) -> Self {
+        let mut baseline = Self::new();
+        for key in &result.keys {
+            baseline.add(key);
+        }
+        baseline
+    }
+
+    /// Records a credential in the baseline, ignoring duplicates
+    pub fn add(&mut self, key: &DiscoveredCredential) {
+        if !self.contains(key) {
+            self.entries.push(BaselineEntry {
+                hash: key.hash.clone(),
+                provider: key.provider.clone(),
+                source_file: key.source_file.clone(),
+            });
+        }
+    }
+
+    /// Checks whether a credential is already recorded in the baseline
+    #[must_use]
+    pub fn contains(&self, key: &DiscoveredCredential) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.hash == key.hash && entry.provider == key.provider)
+    }
+
+    /// Gets the number of recorded findings
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the baseline has no recorded findings
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes keys already present in the baseline from a scan result.
+    ///
+    /// Returns the number of keys that were suppressed.
+    pub fn filter_scan_result(&self, result: &mut ScanResult) -> usize {
+        let known: HashSet<(&str, &str)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.hash.as_str(), entry.provider.as_str()))
+            .collect();
+
+        let before = result.keys.len();
+        result
+            .keys
+            .retain(|key| !known.contains(&(key.hash.as_str(), key.provider.as_str())));
+        before - result.keys.len()
+    }
+
+    /// Loads a baseline from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let baseline: Self = serde_json::from_str(&content).map_err(|e| Error::ParseFailed {
+            path: path.to_path_buf(),
+            format: "json".to_string(),
+            message: format!("Invalid baseline file: {e}"),
+        })?;
+
+        if baseline.version > BASELINE_VERSION {
+            return Err(Error::ValidationError(format!(
+                "Unsupported baseline version {} (expected <= {BASELINE_VERSION})",
+                baseline.version
+            )));
+        }
+
+        Ok(baseline)
+    }
+
+    /// Saves the baseline to a JSON file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Confidence, ValueType};
+
+    fn key(provider: &str, value: &str) -> DiscoveredCredential {
+        DiscoveredCredential::new_redacted(
+            provider.to_string(),
+            "/home/user/.env".to_string(),
+            ValueType::ApiKey,
+            Confidence::High,
+            value,
+        )
+    }
+
+    #[test]
+    fn test_filter_suppresses_known_keys() {
+        let mut known = ScanResult::new("/home/user".to_string(), vec![], Utc::now());
+        known.add_key(key("openai", "sk-known-value-1234"));
+        let baseline = Baseline::from_scan_result(&known);
+
+        let mut result = ScanResult::new("/home/user".to_string(), vec![], Utc::now());
+        result.add_key(key("openai", "sk-known-value-1234"));
+        result.add_key(key("openai", "sk-brand-new-value-5678"));
+        result.add_key(key("groq", "sk-known-value-1234"));
+
+        let suppressed = baseline.filter_scan_result(&mut result);
+        assert_eq!(suppressed, 1);
+        assert_eq!(result.keys.len(), 2);
+        assert!(result.keys.iter().all(|k| !baseline.contains(k)));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("baseline.json");
+
+        let mut baseline = Baseline::new();
+        baseline.add(&key("anthropic", "sk-ant-roundtrip-value"));
+        baseline.add(&key("anthropic", "sk-ant-roundtrip-value"));
+        baseline.save(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("sk-ant-roundtrip-value"));
+
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.entries, baseline.entries);
+    }
+
+    #[test]
+    fn test_load_rejects_future_version() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("baseline.json");
+        std::fs::write(
+            &path,
+            r#"{"version": 99, "created_at": "2024-01-01T00:00:00Z", "entries": []}"#,
+        )
+        .unwrap();
+
+        assert!(Baseline::load(&path).is_err());
+    }
+}
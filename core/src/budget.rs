@@ -0,0 +1,270 @@
+//! Monthly spending budgets of instances and labels.
+//!
+//! Budgets are kept in `budgets.yaml` of a
+//! [`ConfigStore`](crate::store::ConfigStore) and measured against the
+//! [`usage ledger`](crate::usage) for the current calendar month in UTC. A
+//! budget only warns once spending reaches it, unless it is set to refuse, in
+//! which case `resolve` and the commands handing out keys stop doing so until
+//! the month is over.
+
+use crate::usage::UsageRecord;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Store file holding the budgets.
+pub const BUDGETS_FILE: &str = "budgets.yaml";
+
+/// Spending limit of one instance or label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    /// Most to spend in a calendar month, in the currency of the pricing
+    pub monthly: f64,
+    /// Whether keys stop being handed out once the budget is spent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub refuse: bool,
+}
+
+/// What a budget limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetScope {
+    /// Usage through a provider instance
+    Instance,
+    /// Usage of a label
+    Label,
+}
+
+impl BudgetScope {
+    /// Name of the scope
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Instance => "instance",
+            Self::Label => "label",
+        }
+    }
+
+    fn matches(self, record: &UsageRecord, name: &str) -> bool {
+        match self {
+            Self::Instance => record.instance_id.as_deref() == Some(name),
+            Self::Label => record.label.as_deref() == Some(name),
+        }
+    }
+}
+
+impl std::fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Spending of this month against a budget.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetStatus {
+    /// What the budget limits
+    pub scope: BudgetScope,
+    /// Instance ID or label
+    pub name: String,
+    /// Monthly budget
+    pub monthly: f64,
+    /// Spent this month
+    pub spent: f64,
+    /// Whether keys stop being handed out once the budget is spent
+    pub refuse: bool,
+}
+
+impl BudgetStatus {
+    /// Share of the budget spent, 1.0 when fully spent.
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        if self.monthly > 0.0 {
+            self.spent / self.monthly
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// Checks whether the budget is spent.
+    #[must_use]
+    pub fn exceeded(&self) -> bool {
+        self.spent >= self.monthly
+    }
+}
+
+impl std::fmt::Display for BudgetStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} '{}' has spent {:.2} of its monthly budget of {:.2} ({:.0}%)",
+            self.scope,
+            self.name,
+            self.spent,
+            self.monthly,
+            self.utilization() * 100.0
+        )
+    }
+}
+
+/// Budgets of instances and labels.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Budgets {
+    /// Budgets by instance ID
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub instances: BTreeMap<String, Budget>,
+    /// Budgets by label
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, Budget>,
+}
+
+impl Budgets {
+    const fn scope(&self, scope: BudgetScope) -> &BTreeMap<String, Budget> {
+        match scope {
+            BudgetScope::Instance => &self.instances,
+            BudgetScope::Label => &self.labels,
+        }
+    }
+
+    const fn scope_mut(&mut self, scope: BudgetScope) -> &mut BTreeMap<String, Budget> {
+        match scope {
+            BudgetScope::Instance => &mut self.instances,
+            BudgetScope::Label => &mut self.labels,
+        }
+    }
+
+    /// Gets the budget of an instance or label.
+    #[must_use]
+    pub fn get(&self, scope: BudgetScope, name: &str) -> Option<&Budget> {
+        self.scope(scope).get(name)
+    }
+
+    /// Sets the budget of an instance or label.
+    pub fn set(&mut self, scope: BudgetScope, name: impl Into<String>, budget: Budget) {
+        self.scope_mut(scope).insert(name.into(), budget);
+    }
+
+    /// Removes the budget of an instance or label, returning whether it had one.
+    pub fn remove(&mut self, scope: BudgetScope, name: &str) -> bool {
+        self.scope_mut(scope).remove(name).is_some()
+    }
+
+    /// Checks whether no budget is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty() && self.labels.is_empty()
+    }
+
+    /// Measures the budget of an instance or label against the usage of
+    /// the month of `now`.
+    #[must_use]
+    pub fn status(
+        &self,
+        scope: BudgetScope,
+        name: &str,
+        records: &[UsageRecord],
+        now: DateTime<Utc>,
+    ) -> Option<BudgetStatus> {
+        let budget = self.get(scope, name)?;
+        let since = month_start(now);
+        Some(BudgetStatus {
+            scope,
+            name: name.to_string(),
+            monthly: budget.monthly,
+            spent: records
+                .iter()
+                .filter(|record| record.recorded_at >= since && scope.matches(record, name))
+                .filter_map(|record| record.cost)
+                .sum(),
+            refuse: budget.refuse,
+        })
+    }
+
+    /// Measures every budget, instances first.
+    #[must_use]
+    pub fn statuses(&self, records: &[UsageRecord], now: DateTime<Utc>) -> Vec<BudgetStatus> {
+        [BudgetScope::Instance, BudgetScope::Label]
+            .into_iter()
+            .flat_map(|scope| {
+                self.scope(scope)
+                    .keys()
+                    .filter_map(move |name| self.status(scope, name, records, now))
+            })
+            .collect()
+    }
+}
+
+/// Start of the calendar month of `now`, in UTC.
+#[must_use]
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spend(day: u32, label: Option<&str>, instance_id: &str, cost: f64) -> UsageRecord {
+        UsageRecord {
+            recorded_at: Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+            label: label.map(str::to_string),
+            instance_id: Some(instance_id.to_string()),
+            cost: Some(cost),
+            ..UsageRecord::new("gpt-4o", 0, 0)
+        }
+    }
+
+    #[test]
+    fn test_status_counts_this_months_spending() {
+        let mut budgets = Budgets::default();
+        budgets.set(
+            BudgetScope::Label,
+            "smart",
+            Budget {
+                monthly: 50.0,
+                refuse: true,
+            },
+        );
+        budgets.set(
+            BudgetScope::Instance,
+            "work",
+            Budget {
+                monthly: 100.0,
+                refuse: false,
+            },
+        );
+        let mut last_month = spend(1, Some("smart"), "work", 500.0);
+        last_month.recorded_at = Utc.with_ymd_and_hms(2026, 2, 28, 23, 0, 0).unwrap();
+        let records = [
+            last_month,
+            spend(2, Some("smart"), "work", 30.0),
+            spend(5, Some("smart"), "home", 25.0),
+            spend(6, None, "work", 10.0),
+        ];
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 0, 0, 0).unwrap();
+
+        let smart = budgets
+            .status(BudgetScope::Label, "smart", &records, now)
+            .unwrap();
+        assert!((smart.spent - 55.0).abs() < f64::EPSILON);
+        assert!(smart.exceeded());
+        assert_eq!(
+            smart.to_string(),
+            "label 'smart' has spent 55.00 of its monthly budget of 50.00 (110%)"
+        );
+
+        let statuses = budgets.statuses(&records, now);
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].name, "work");
+        assert!((statuses[0].utilization() - 0.4).abs() < 1e-9);
+        assert!(!statuses[0].exceeded());
+
+        assert!(budgets.remove(BudgetScope::Label, "smart"));
+        assert!(!budgets.remove(BudgetScope::Label, "smart"));
+        assert!(budgets
+            .status(BudgetScope::Label, "smart", &records, now)
+            .is_none());
+    }
+}
@@ -0,0 +1,538 @@
+#![allow(clippy::missing_errors_doc)]
+//! Bulk operations on the provider instances of a [`ConfigStore`].
+//!
+//! An [`InstanceSelector`] picks instances by ID, provider type and tags, where
+//! an instance carries a tag assigned to it or to one of its models in
+//! `tag_assignments.yaml`. [`set_active`] and [`remove`] apply to every
+//! selected instance under a single store lock. [`migrate_ids`] renames
+//! instances with IDs created before the 12-hex scheme.
+
+use crate::bundle::retarget;
+use crate::discovery::ids;
+use crate::error::{Error, Result};
+use crate::models::{DiscoveredCredential, LabelAssignment, ProviderInstance};
+use crate::resolve::{reveal_api_key, LABELS_FILE};
+use crate::secrets::SecretBackends;
+use crate::store::{ConfigStore, INSTANCES_DIR};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Store file holding the tag assignments.
+pub const TAG_ASSIGNMENTS_FILE: &str = "tag_assignments.yaml";
+
+/// Criteria picking provider instances; an instance must meet all of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceSelector {
+    /// Tags the instance must all carry
+    pub tags: Vec<String>,
+    /// IDs of which the instance must have one, if any are given
+    pub ids: Vec<String>,
+    /// Provider type the instance must have
+    pub provider_type: Option<String>,
+}
+
+impl InstanceSelector {
+    /// Creates a selector matching every instance.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            tags: Vec::new(),
+            ids: Vec::new(),
+            provider_type: None,
+        }
+    }
+
+    /// Requires a tag.
+    #[must_use]
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Adds an instance ID to pick.
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.ids.push(id.into());
+        self
+    }
+
+    /// Requires a provider type.
+    #[must_use]
+    pub fn with_provider_type(mut self, provider_type: impl Into<String>) -> Self {
+        self.provider_type = Some(provider_type.into());
+        self
+    }
+
+    /// Checks whether the selector has no criteria, matching every instance.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.ids.is_empty() && self.provider_type.is_none()
+    }
+
+    /// Checks whether an instance meets the criteria.
+    #[must_use]
+    pub fn matches(
+        &self,
+        instance: &ProviderInstance,
+        tag_assignments: &[LabelAssignment],
+    ) -> bool {
+        (self.ids.is_empty() || self.ids.contains(&instance.id))
+            && self
+                .provider_type
+                .as_ref()
+                .is_none_or(|provider_type| *provider_type == instance.provider_type)
+            && self.tags.iter().all(|tag| {
+                tag_assignments.iter().any(|assignment| {
+                    assignment.label_name == *tag && assignment.target.instance_id() == instance.id
+                })
+            })
+    }
+}
+
+/// Gets the instances a selector picks, sorted by ID.
+pub fn select(store: &ConfigStore, selector: &InstanceSelector) -> Result<Vec<ProviderInstance>> {
+    let tag_assignments = load_assignments(store, TAG_ASSIGNMENTS_FILE)?;
+    let mut selected: Vec<ProviderInstance> = store
+        .load_instances()?
+        .all_instances()
+        .into_iter()
+        .filter(|instance| selector.matches(instance, &tag_assignments))
+        .cloned()
+        .collect();
+    selected.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(selected)
+}
+
+/// Gets the instances a selector picks with the files holding them, sorted by
+/// ID.
+pub fn select_files(
+    store: &ConfigStore,
+    selector: &InstanceSelector,
+) -> Result<Vec<(PathBuf, ProviderInstance)>> {
+    let tag_assignments = load_assignments(store, TAG_ASSIGNMENTS_FILE)?;
+    let mut selected = Vec::new();
+    for (path, content) in store.instance_files()? {
+        let Ok((instance, _)) = store.parse_instance(&content) else {
+            continue;
+        };
+        if selector.matches(&instance, &tag_assignments) {
+            selected.push((path, instance));
+        }
+    }
+    selected.sort_by(|a, b| a.1.id.cmp(&b.1.id));
+    Ok(selected)
+}
+
+/// Activates or deactivates the selected instances, returning the IDs of those
+/// that changed.
+pub fn set_active(
+    store: &ConfigStore,
+    selector: &InstanceSelector,
+    active: bool,
+) -> Result<Vec<String>> {
+    let tag_assignments = load_assignments(store, TAG_ASSIGNMENTS_FILE)?;
+    let mut changed = Vec::new();
+    store.update_instances(|instance| {
+        if instance.active == active || !selector.matches(instance, &tag_assignments) {
+            return Ok(false);
+        }
+        instance.active = active;
+        changed.push(instance.id.clone());
+        Ok(true)
+    })?;
+    changed.sort();
+    Ok(changed)
+}
+
+/// An assignment a removal changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignmentChange {
+    /// Store file holding the assignment
+    pub file: &'static str,
+    /// The assignment as it is now
+    pub assignment: LabelAssignment,
+    /// The assignment after the removal, or `None` if it is dropped
+    pub after: Option<LabelAssignment>,
+}
+
+/// What removing the selected instances would change.
+#[derive(Debug, Clone, Default)]
+pub struct RemovalPlan {
+    /// Instances to remove with their files, sorted by ID
+    pub instances: Vec<(PathBuf, ProviderInstance)>,
+    /// Label and tag assignments pointing at them
+    pub assignments: Vec<AssignmentChange>,
+}
+
+/// Works out what [`remove`] would change, without changing anything.
+pub fn plan_remove(store: &ConfigStore, selector: &InstanceSelector) -> Result<RemovalPlan> {
+    let mut plan = RemovalPlan {
+        instances: select_files(store, selector)?,
+        assignments: Vec::new(),
+    };
+
+    let ids: Vec<&str> = plan
+        .instances
+        .iter()
+        .map(|(_, instance)| instance.id.as_str())
+        .collect();
+    for file in [LABELS_FILE, TAG_ASSIGNMENTS_FILE] {
+        for assignment in load_assignments(store, file)? {
+            if assignment
+                .targets()
+                .any(|target| ids.contains(&target.instance_id()))
+            {
+                let after = drop_targets(vec![assignment.clone()], &ids).pop();
+                plan.assignments.push(AssignmentChange {
+                    file,
+                    assignment,
+                    after,
+                });
+            }
+        }
+    }
+    Ok(plan)
+}
+
+/// Removes the selected instances, returning them sorted by ID.
+///
+/// Their files are kept as backups. Label and tag assignments pointing at them
+/// are dropped; a label whose main target is removed falls back to its next
+/// remaining target. An empty selector is refused rather than removing every
+/// instance.
+pub fn remove(store: &ConfigStore, selector: &InstanceSelector) -> Result<Vec<ProviderInstance>> {
+    if selector.is_empty() {
+        return Err(Error::ValidationError(
+            "Refusing to remove instances without an ID, tag or provider type to select them"
+                .to_string(),
+        ));
+    }
+
+    let lock = store.lock()?;
+    let plan = plan_remove(store, selector)?;
+    let mut removed = Vec::new();
+    for (path, instance) in plan.instances {
+        if let Some(file) = path.file_name() {
+            lock.remove(Path::new(INSTANCES_DIR).join(file))?;
+            removed.push(instance);
+        }
+    }
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+
+    let ids: Vec<&str> = removed
+        .iter()
+        .map(|instance| instance.id.as_str())
+        .collect();
+    for file in [LABELS_FILE, TAG_ASSIGNMENTS_FILE] {
+        if plan.assignments.iter().any(|change| change.file == file) {
+            let assignments = load_assignments(store, file)?;
+            lock.save_yaml(file, &drop_targets(assignments, &ids))?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Renames the instances with IDs created before the 12-hex scheme to the
+/// [`ids`] scheme, returning `(old, new)` pairs sorted by old ID.
+///
+/// The new ID is derived from the instance's key, revealed through `backends`
+/// if it is encrypted or held in a secret backend, so it matches the ID the
+/// next `scan --update` gives the key. An instance whose key can't be read is
+/// named after its old ID instead. Label and tag assignments follow the
+/// renames, and the old files are kept as backups. With `dry_run`, nothing is
+/// written.
+pub fn migrate_ids(
+    store: &ConfigStore,
+    backends: &SecretBackends,
+    dry_run: bool,
+) -> Result<Vec<(String, String)>> {
+    let lock = if dry_run { None } else { Some(store.lock()?) };
+    let instances: Vec<(PathBuf, ProviderInstance)> = store
+        .instance_files()?
+        .into_iter()
+        .filter_map(|(path, content)| {
+            let (instance, _) = store.parse_instance(&content).ok()?;
+            Some((path, instance))
+        })
+        .collect();
+    let mut taken: HashSet<String> = instances
+        .iter()
+        .map(|(_, instance)| instance.id.clone())
+        .collect();
+    let cipher = store.cipher()?;
+
+    let mut renames = Vec::new();
+    for (path, instance) in &instances {
+        if !ids::is_legacy_instance_id(&instance.id) {
+            continue;
+        }
+        let mut revealed = instance.clone();
+        let fingerprint = match reveal_api_key(&mut revealed, cipher.as_ref(), backends) {
+            Ok(())
+                if !revealed.api_key.is_empty() && !revealed.api_key.starts_with("REDACTED_") =>
+            {
+                DiscoveredCredential::hash_value(&revealed.api_key)
+            }
+            _ => instance.id.clone(),
+        };
+        let new_id = ids::unique_instance_id(
+            &ids::instance_id(&instance.provider_type, &fingerprint),
+            |id| taken.contains(id),
+        );
+        taken.insert(new_id.clone());
+
+        if let Some(lock) = &lock {
+            let mut moved = instance.clone();
+            moved.id.clone_from(&new_id);
+            lock.save_instance(&moved)?;
+            if let Some(file) = path.file_name() {
+                lock.remove(Path::new(INSTANCES_DIR).join(file))?;
+            }
+        }
+        renames.push((instance.id.clone(), new_id));
+    }
+
+    if let Some(lock) = &lock {
+        let map: HashMap<String, String> = renames.iter().cloned().collect();
+        for file in [LABELS_FILE, TAG_ASSIGNMENTS_FILE] {
+            let assignments = load_assignments(store, file)?;
+            if assignments.iter().any(|assignment| {
+                assignment
+                    .targets()
+                    .any(|target| map.contains_key(target.instance_id()))
+            }) {
+                let assignments: Vec<LabelAssignment> = assignments
+                    .iter()
+                    .map(|assignment| retarget(assignment, &map))
+                    .collect();
+                lock.save_yaml(file, &assignments)?;
+            }
+        }
+    }
+    renames.sort();
+    Ok(renames)
+}
+
+fn load_assignments(store: &ConfigStore, file: &str) -> Result<Vec<LabelAssignment>> {
+    Ok(store.load_yaml(file)?.unwrap_or_default())
+}
+
+/// Drops the targets at removed instances, promoting the first remaining
+/// fallback of an assignment whose main target is gone.
+fn drop_targets(assignments: Vec<LabelAssignment>, ids: &[&str]) -> Vec<LabelAssignment> {
+    assignments
+        .into_iter()
+        .filter_map(|mut assignment| {
+            assignment
+                .fallbacks
+                .retain(|target| !ids.contains(&target.instance_id()));
+            if ids.contains(&assignment.target.instance_id()) {
+                if assignment.fallbacks.is_empty() {
+                    return None;
+                }
+                assignment.target = assignment.fallbacks.remove(0);
+            }
+            Some(assignment)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LabelTarget;
+    use chrono::Utc;
+
+    fn assignment(name: &str, instance_id: &str) -> LabelAssignment {
+        LabelAssignment {
+            label_name: name.to_string(),
+            target: LabelTarget::ProviderInstance {
+                instance_id: instance_id.to_string(),
+            },
+            assigned_at: Utc::now(),
+            assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
+        }
+    }
+
+    fn store_with_instances(home: &Path) -> ConfigStore {
+        let store = ConfigStore::for_home(home);
+        let lock = store.lock().unwrap();
+        for (id, provider_type) in [
+            ("stage-a", "openai"),
+            ("stage-b", "groq"),
+            ("prod", "openai"),
+        ] {
+            lock.save_instance(&ProviderInstance::new(
+                id.to_string(),
+                provider_type.to_string(),
+                "https://api.example.com/v1".to_string(),
+                format!("sk-{id}"),
+                vec!["gpt-4o-mini".to_string()],
+            ))
+            .unwrap();
+        }
+        lock.save_yaml(
+            TAG_ASSIGNMENTS_FILE,
+            &vec![
+                assignment("staging", "stage-a"),
+                assignment("staging", "stage-b"),
+                assignment("critical", "prod"),
+            ],
+        )
+        .unwrap();
+        drop(lock);
+        store
+    }
+
+    #[test]
+    fn test_selector_combines_criteria() {
+        let home = tempfile::tempdir().unwrap();
+        let store = store_with_instances(home.path());
+        let ids = |selector: &InstanceSelector| -> Vec<String> {
+            select(&store, selector)
+                .unwrap()
+                .into_iter()
+                .map(|instance| instance.id)
+                .collect()
+        };
+
+        assert_eq!(ids(&InstanceSelector::new()).len(), 3);
+        assert_eq!(
+            ids(&InstanceSelector::new().with_tag("staging")),
+            ["stage-a", "stage-b"]
+        );
+        assert_eq!(
+            ids(&InstanceSelector::new()
+                .with_tag("staging")
+                .with_provider_type("openai")),
+            ["stage-a"]
+        );
+        assert!(ids(&InstanceSelector::new()
+            .with_tag("staging")
+            .with_tag("critical"))
+        .is_empty());
+        assert_eq!(
+            ids(&InstanceSelector::new().with_id("prod").with_id("stage-b")),
+            ["prod", "stage-b"]
+        );
+    }
+
+    #[test]
+    fn test_set_active_reports_changed_instances() {
+        let home = tempfile::tempdir().unwrap();
+        let store = store_with_instances(home.path());
+        let staging = InstanceSelector::new().with_tag("staging");
+
+        assert_eq!(
+            set_active(&store, &staging, false).unwrap(),
+            ["stage-a", "stage-b"]
+        );
+        assert!(set_active(&store, &staging, false).unwrap().is_empty());
+        let instances = store.load_instances().unwrap();
+        assert!(!instances.get_instance("stage-a").unwrap().active);
+        assert!(instances.get_instance("prod").unwrap().active);
+    }
+
+    #[test]
+    fn test_remove_drops_instances_and_their_assignments() {
+        let home = tempfile::tempdir().unwrap();
+        let store = store_with_instances(home.path());
+        let mut fast = assignment("fast", "stage-a");
+        fast.fallbacks = vec![LabelTarget::ProviderInstance {
+            instance_id: "prod".to_string(),
+        }];
+        store
+            .lock()
+            .unwrap()
+            .save_yaml(LABELS_FILE, &vec![fast, assignment("cheap", "stage-b")])
+            .unwrap();
+
+        let plan = plan_remove(&store, &InstanceSelector::new().with_tag("staging")).unwrap();
+        assert_eq!(plan.instances.len(), 2);
+        assert_eq!(plan.assignments.len(), 4);
+        let fast = plan
+            .assignments
+            .iter()
+            .find(|change| change.assignment.label_name == "fast")
+            .unwrap();
+        assert_eq!(fast.after.as_ref().unwrap().target.instance_id(), "prod");
+        assert_eq!(store.load_instances().unwrap().len(), 3);
+
+        assert!(matches!(
+            remove(&store, &InstanceSelector::new()),
+            Err(Error::ValidationError(_))
+        ));
+        let removed = remove(&store, &InstanceSelector::new().with_tag("staging")).unwrap();
+        assert_eq!(removed.len(), 2);
+
+        let instances = store.load_instances().unwrap();
+        assert_eq!(instances.len(), 1);
+        let labels = load_assignments(&store, LABELS_FILE).unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].label_name, "fast");
+        assert_eq!(labels[0].target.instance_id(), "prod");
+        assert!(labels[0].fallbacks.is_empty());
+        let tags = load_assignments(&store, TAG_ASSIGNMENTS_FILE).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert!(home
+            .path()
+            .join(".config/aicred")
+            .join(INSTANCES_DIR)
+            .read_dir()
+            .unwrap()
+            .any(|entry| entry.unwrap().path().to_string_lossy().ends_with(".bak")));
+    }
+
+    #[test]
+    fn test_migrate_ids_renames_legacy_instances() {
+        let home = tempfile::tempdir().unwrap();
+        let store = store_with_instances(home.path());
+        let lock = store.lock().unwrap();
+        for (id, key) in [("7484", "sk-test1234567890abcdef"), ("12ac", "")] {
+            lock.save_instance(&ProviderInstance::new(
+                id.to_string(),
+                "openai".to_string(),
+                "https://api.openai.com/v1".to_string(),
+                key.to_string(),
+                Vec::new(),
+            ))
+            .unwrap();
+        }
+        lock.save_yaml(LABELS_FILE, &vec![assignment("fast", "7484")])
+            .unwrap();
+        drop(lock);
+
+        let backends = SecretBackends::new();
+        let planned = migrate_ids(&store, &backends, true).unwrap();
+        let fallback = ids::instance_id("openai", "12ac");
+        assert_eq!(
+            planned,
+            [
+                ("12ac".to_string(), fallback.clone()),
+                ("7484".to_string(), "openai-7484bce3a9d8".to_string()),
+            ]
+        );
+        assert!(store
+            .load_instances()
+            .unwrap()
+            .get_instance("7484")
+            .is_some());
+
+        assert_eq!(migrate_ids(&store, &backends, false).unwrap(), planned);
+        let instances = store.load_instances().unwrap();
+        assert_eq!(instances.len(), 5);
+        assert!(instances.get_instance("7484").is_none());
+        assert!(instances.get_instance(&fallback).is_some());
+        let labels = load_assignments(&store, LABELS_FILE).unwrap();
+        assert_eq!(labels[0].target.instance_id(), "openai-7484bce3a9d8");
+        assert!(store
+            .instances_dir()
+            .join("openai-7484bce3a9d8.yaml")
+            .exists());
+        assert!(migrate_ids(&store, &backends, false).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,598 @@
+#![allow(clippy::missing_errors_doc)]
+//! Encrypted bundles for moving a configuration between machines.
+//!
+//! A [`Bundle`] holds the instances, tags, labels and custom detection rules of
+//! a [`ConfigStore`]. Secrets are left out unless asked for; when included,
+//! encrypted and keyring-backed keys are resolved to plain values so they work
+//! on the target machine. [`Bundle::seal`] encrypts the whole bundle with a
+//! [`SecretCipher`], and [`Bundle::import_into`] merges it into another store,
+//! settling conflicting items with a [`ConflictStrategy`].
+
+use crate::bulk::TAG_ASSIGNMENTS_FILE;
+use crate::crypto::{self, SecretCipher};
+use crate::error::{Error, Result};
+use crate::models::{Label, LabelAssignment, LabelTarget, ProviderInstance};
+use crate::resolve::{reveal_api_key, LABELS_FILE};
+use crate::rules::{DetectionRules, DETECTION_RULES_FILE};
+use crate::secrets::SecretBackends;
+use crate::store::{instance_file_name, ConfigStore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// First line of a sealed bundle, identifying the format.
+pub const BUNDLE_HEADER: &str = "aicred-bundle v1";
+
+const TAGS_FILE: &str = "tags.yaml";
+const LABELS_METADATA_FILE: &str = "labels_metadata.yaml";
+
+/// An instance to write, with its file name in the instances directory.
+type InstanceWrite = (String, ProviderInstance);
+
+/// How to settle an item that exists in both the bundle and the store with
+/// different contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Keep the local item
+    #[default]
+    Skip,
+    /// Replace the local item with the bundled one
+    Overwrite,
+    /// Import conflicting instances under a new ID; other items keep the local version
+    Rename,
+}
+
+impl std::str::FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename" => Ok(Self::Rename),
+            _ => Err(format!(
+                "Invalid conflict strategy '{s}' (expected skip, overwrite or rename)"
+            )),
+        }
+    }
+}
+
+/// A portable snapshot of a configuration store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    /// When the bundle was created
+    pub created_at: DateTime<Utc>,
+    /// Whether instance API keys are included
+    pub includes_secrets: bool,
+    /// Provider instances
+    #[serde(default)]
+    pub instances: Vec<ProviderInstance>,
+    /// Tag definitions
+    #[serde(default)]
+    pub tags: Vec<Label>,
+    /// Tag assignments
+    #[serde(default)]
+    pub tag_assignments: Vec<LabelAssignment>,
+    /// Label definitions
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// Label assignments
+    #[serde(default)]
+    pub label_assignments: Vec<LabelAssignment>,
+    /// Custom detection rules
+    #[serde(default)]
+    pub rules: DetectionRules,
+}
+
+/// What importing a bundle did, or would do on a dry run. Items are described
+/// as `<kind> <name>`, e.g. `instance openai-prod`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Items that did not exist locally
+    pub added: Vec<String>,
+    /// Conflicting items replaced by the bundled version
+    pub overwritten: Vec<String>,
+    /// Conflicting items where the local version was kept
+    pub skipped: Vec<String>,
+    /// Conflicting instances imported under a new ID, as (bundled ID, new ID)
+    pub renamed: Vec<(String, String)>,
+    /// Items identical to the local version
+    pub unchanged: usize,
+}
+
+impl ImportReport {
+    /// Checks whether the import changes the store.
+    #[must_use]
+    pub const fn has_changes(&self) -> bool {
+        !self.added.is_empty() || !self.overwritten.is_empty() || !self.renamed.is_empty()
+    }
+}
+
+impl Bundle {
+    /// Collects the contents of a store. Without `include_secrets`, instance keys
+    /// are left empty.
+    pub fn from_store(store: &ConfigStore, include_secrets: bool) -> Result<Self> {
+        let mut instances: Vec<ProviderInstance> = store
+            .load_instances()?
+            .all_instances()
+            .into_iter()
+            .cloned()
+            .collect();
+        instances.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let cipher = store.cipher()?;
+        let backends = SecretBackends::builtin();
+        for instance in &mut instances {
+            if include_secrets {
+                reveal_api_key(instance, cipher.as_ref(), &backends)?;
+            } else {
+                instance.api_key.clear();
+            }
+        }
+
+        Ok(Self {
+            created_at: Utc::now(),
+            includes_secrets: include_secrets,
+            instances,
+            tags: store.load_yaml(TAGS_FILE)?.unwrap_or_default(),
+            tag_assignments: store.load_yaml(TAG_ASSIGNMENTS_FILE)?.unwrap_or_default(),
+            labels: store.load_yaml(LABELS_METADATA_FILE)?.unwrap_or_default(),
+            label_assignments: store.load_yaml(LABELS_FILE)?.unwrap_or_default(),
+            rules: DetectionRules::load_from_store(store)?,
+        })
+    }
+
+    /// Encrypts the bundle into its portable text form.
+    pub fn seal(&self, cipher: &SecretCipher) -> Result<String> {
+        let json =
+            serde_json::to_string(self).map_err(|e| Error::SerializationError(e.to_string()))?;
+        Ok(format!("{BUNDLE_HEADER}\n{}\n", cipher.encrypt(&json)?))
+    }
+
+    /// Decrypts a bundle produced by [`Bundle::seal`].
+    pub fn open(content: &str, cipher: &SecretCipher) -> Result<Self> {
+        let mut lines = content.lines();
+        if lines.next().map(str::trim) != Some(BUNDLE_HEADER) {
+            return Err(Error::ValidationError(
+                "Not an aicred bundle (missing header)".to_string(),
+            ));
+        }
+        let sealed = lines.next().map(str::trim).unwrap_or_default();
+        if !crypto::is_encrypted(sealed) {
+            return Err(Error::ValidationError(
+                "Malformed aicred bundle".to_string(),
+            ));
+        }
+        serde_json::from_str(&cipher.decrypt(sealed)?)
+            .map_err(|e| Error::SerializationError(e.to_string()))
+    }
+
+    /// Merges the bundle into a store under one lock. With `dry_run`, nothing
+    /// is written.
+    pub fn import_into(
+        &self,
+        store: &ConfigStore,
+        strategy: ConflictStrategy,
+        dry_run: bool,
+    ) -> Result<ImportReport> {
+        let lock = store.lock()?;
+        let mut report = ImportReport::default();
+
+        let (instance_writes, renames) = self.merge_instances(store, strategy, &mut report)?;
+
+        let tags = merge_named(
+            store.load_yaml(TAGS_FILE)?.unwrap_or_default(),
+            &self.tags,
+            "tag",
+            strategy,
+            &mut report,
+        )?;
+        let labels = merge_named(
+            store.load_yaml(LABELS_METADATA_FILE)?.unwrap_or_default(),
+            &self.labels,
+            "label",
+            strategy,
+            &mut report,
+        )?;
+        let tag_assignments = merge_tag_assignments(
+            store.load_yaml(TAG_ASSIGNMENTS_FILE)?.unwrap_or_default(),
+            &self.tag_assignments,
+            &renames,
+            &mut report,
+        );
+        let label_assignments = merge_label_assignments(
+            store.load_yaml(LABELS_FILE)?.unwrap_or_default(),
+            &self.label_assignments,
+            &renames,
+            strategy,
+            &mut report,
+        );
+        let mut rules = DetectionRules::load_from_store(store)?;
+        let rules_changed = merge_rules(&mut rules, &self.rules, strategy, &mut report);
+
+        if dry_run {
+            return Ok(report);
+        }
+        for (file, instance) in &instance_writes {
+            lock.save_instance_as(file, instance)?;
+        }
+        for (file, changed, value) in [
+            (TAGS_FILE, tags.1, to_value(&tags.0)?),
+            (LABELS_METADATA_FILE, labels.1, to_value(&labels.0)?),
+            (
+                TAG_ASSIGNMENTS_FILE,
+                tag_assignments.1,
+                to_value(&tag_assignments.0)?,
+            ),
+            (
+                LABELS_FILE,
+                label_assignments.1,
+                to_value(&label_assignments.0)?,
+            ),
+            (DETECTION_RULES_FILE, rules_changed, to_value(&rules)?),
+        ] {
+            if changed {
+                lock.save_yaml(file, &value)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Merges the bundled instances with those in a store, returning the
+    /// instances to write by file name and the IDs of renamed instances.
+    fn merge_instances(
+        &self,
+        store: &ConfigStore,
+        strategy: ConflictStrategy,
+        report: &mut ImportReport,
+    ) -> Result<(Vec<InstanceWrite>, HashMap<String, String>)> {
+        let mut local = HashMap::new();
+        let mut files = HashSet::new();
+        for (path, content) in store.instance_files()? {
+            let Some(file) = path.file_name().map(|f| f.to_string_lossy().into_owned()) else {
+                continue;
+            };
+            if let Ok((instance, _)) = store.parse_instance(&content) {
+                local.insert(instance.id.clone(), (file.clone(), instance));
+            }
+            files.insert(file);
+        }
+
+        let mut renames = HashMap::new();
+        let mut writes = Vec::new();
+        for bundled in &self.instances {
+            let item = format!("instance {}", bundled.id);
+            let Some((file, existing)) = local.get(&bundled.id) else {
+                report.added.push(item);
+                writes.push((free_file_name(bundled, &mut files), bundled.clone()));
+                continue;
+            };
+            let mut incoming = bundled.clone();
+            if !self.includes_secrets {
+                incoming.api_key.clone_from(&existing.api_key);
+            }
+            if same(existing, &incoming)? {
+                report.unchanged += 1;
+                continue;
+            }
+            match strategy {
+                ConflictStrategy::Skip => report.skipped.push(item),
+                ConflictStrategy::Overwrite => {
+                    report.overwritten.push(item);
+                    writes.push((file.clone(), incoming));
+                }
+                ConflictStrategy::Rename => {
+                    let new_id = free_id(&bundled.id, |id| {
+                        local.contains_key(id) || self.instances.iter().any(|other| other.id == id)
+                    });
+                    report.renamed.push((bundled.id.clone(), new_id.clone()));
+                    renames.insert(bundled.id.clone(), new_id.clone());
+                    let mut imported = bundled.clone();
+                    imported.id = new_id;
+                    writes.push((free_file_name(&imported, &mut files), imported));
+                }
+            }
+        }
+        Ok((writes, renames))
+    }
+}
+
+/// Default file name for an instance, numbered if another file already has it.
+fn free_file_name(instance: &ProviderInstance, taken: &mut HashSet<String>) -> String {
+    let default = instance_file_name(instance);
+    let stem = default.trim_end_matches(".yaml");
+    let mut candidate = default.clone();
+    let mut n = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{stem}-{n}.yaml");
+        n += 1;
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<serde_yaml::Value> {
+    serde_yaml::to_value(value).map_err(|e| Error::SerializationError(e.to_string()))
+}
+
+/// Compares two items by their serialized form.
+fn same<T: Serialize>(a: &T, b: &T) -> Result<bool> {
+    let a = serde_json::to_value(a).map_err(|e| Error::SerializationError(e.to_string()))?;
+    let b = serde_json::to_value(b).map_err(|e| Error::SerializationError(e.to_string()))?;
+    Ok(a == b)
+}
+
+/// First of `<id>-imported`, `<id>-imported-2`, ... that is not taken.
+fn free_id(id: &str, taken: impl Fn(&str) -> bool) -> String {
+    let base = format!("{id}-imported");
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = format!("{base}-{n}");
+        n += 1;
+    }
+    candidate
+}
+
+/// Points an assignment at the new ID of a renamed instance.
+pub(crate) fn retarget(
+    assignment: &LabelAssignment,
+    renames: &HashMap<String, String>,
+) -> LabelAssignment {
+    let mut assignment = assignment.clone();
+    for target in std::iter::once(&mut assignment.target).chain(&mut assignment.fallbacks) {
+        match target {
+            LabelTarget::ProviderInstance { instance_id }
+            | LabelTarget::ProviderModel { instance_id, .. } => {
+                if let Some(new_id) = renames.get(instance_id) {
+                    instance_id.clone_from(new_id);
+                }
+            }
+        }
+    }
+    assignment
+}
+
+/// Merges tag or label definitions by name; returns the result and whether it changed.
+fn merge_named(
+    mut local: Vec<Label>,
+    bundled: &[Label],
+    kind: &str,
+    strategy: ConflictStrategy,
+    report: &mut ImportReport,
+) -> Result<(Vec<Label>, bool)> {
+    let mut changed = false;
+    for label in bundled {
+        let item = format!("{kind} {}", label.name);
+        match local
+            .iter_mut()
+            .find(|existing| existing.name == label.name)
+        {
+            None => {
+                report.added.push(item);
+                local.push(label.clone());
+                changed = true;
+            }
+            Some(existing) if same(existing, label)? => report.unchanged += 1,
+            Some(existing) => {
+                if strategy == ConflictStrategy::Overwrite {
+                    report.overwritten.push(item);
+                    existing.clone_from(label);
+                    changed = true;
+                } else {
+                    report.skipped.push(item);
+                }
+            }
+        }
+    }
+    Ok((local, changed))
+}
+
+/// Adds tag assignments not present locally; a tag can be on many targets, so
+/// these never conflict.
+fn merge_tag_assignments(
+    mut local: Vec<LabelAssignment>,
+    bundled: &[LabelAssignment],
+    renames: &HashMap<String, String>,
+    report: &mut ImportReport,
+) -> (Vec<LabelAssignment>, bool) {
+    let mut changed = false;
+    for assignment in bundled {
+        let assignment = retarget(assignment, renames);
+        if local.iter().any(|existing| {
+            existing.label_name == assignment.label_name && existing.target == assignment.target
+        }) {
+            report.unchanged += 1;
+        } else {
+            report
+                .added
+                .push(format!("tag assignment {}", assignment.label_name));
+            local.push(assignment);
+            changed = true;
+        }
+    }
+    (local, changed)
+}
+
+/// Merges label assignments; a label points at one target, so a label
+/// assigned elsewhere locally is a conflict.
+fn merge_label_assignments(
+    mut local: Vec<LabelAssignment>,
+    bundled: &[LabelAssignment],
+    renames: &HashMap<String, String>,
+    strategy: ConflictStrategy,
+    report: &mut ImportReport,
+) -> (Vec<LabelAssignment>, bool) {
+    let mut changed = false;
+    for assignment in bundled {
+        let assignment = retarget(assignment, renames);
+        let item = format!("label assignment {}", assignment.label_name);
+        match local
+            .iter_mut()
+            .find(|existing| existing.label_name == assignment.label_name)
+        {
+            None => {
+                report.added.push(item);
+                local.push(assignment);
+                changed = true;
+            }
+            Some(existing) if existing.target == assignment.target => report.unchanged += 1,
+            Some(existing) => {
+                if strategy == ConflictStrategy::Overwrite {
+                    report.overwritten.push(item);
+                    *existing = assignment;
+                    changed = true;
+                } else {
+                    report.skipped.push(item);
+                }
+            }
+        }
+    }
+    (local, changed)
+}
+
+/// Merges detection rules by ID; returns whether they changed.
+fn merge_rules(
+    local: &mut DetectionRules,
+    bundled: &DetectionRules,
+    strategy: ConflictStrategy,
+    report: &mut ImportReport,
+) -> bool {
+    let mut changed = false;
+    for rule in &bundled.rules {
+        let item = format!("rule {}", rule.id);
+        match local
+            .rules
+            .iter_mut()
+            .find(|existing| existing.id == rule.id)
+        {
+            None => {
+                report.added.push(item);
+                local.rules.push(rule.clone());
+                changed = true;
+            }
+            Some(existing) if existing == rule => report.unchanged += 1,
+            Some(existing) => {
+                if strategy == ConflictStrategy::Overwrite {
+                    report.overwritten.push(item);
+                    existing.clone_from(rule);
+                    changed = true;
+                } else {
+                    report.skipped.push(item);
+                }
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeySource;
+    use crate::models::ProviderCollection;
+
+    fn instance(id: &str, key: &str, model: &str) -> ProviderInstance {
+        ProviderInstance::new(
+            id.to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            key.to_string(),
+            vec![model.to_string()],
+        )
+    }
+
+    fn store_with(instances: &[ProviderInstance]) -> (tempfile::TempDir, ConfigStore) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+        let mut collection = ProviderCollection::new();
+        for instance in instances {
+            collection.add(instance.id.clone(), instance.clone());
+        }
+        store.save_instances(&collection).unwrap();
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_seal_round_trip_needs_the_passphrase() {
+        let (_dir, store) = store_with(&[instance("prod", "sk-prod", "gpt-4o")]);
+        let bundle = Bundle::from_store(&store, true).unwrap();
+        let cipher = SecretCipher::new(KeySource::Passphrase("correct".to_string())).unwrap();
+        let sealed = bundle.seal(&cipher).unwrap();
+        assert!(sealed.starts_with(BUNDLE_HEADER));
+        assert!(!sealed.contains("sk-prod"));
+
+        let opened = Bundle::open(&sealed, &cipher).unwrap();
+        assert_eq!(opened.instances[0].api_key, "sk-prod");
+        let wrong = SecretCipher::new(KeySource::Passphrase("wrong".to_string())).unwrap();
+        assert!(Bundle::open(&sealed, &wrong).is_err());
+
+        let without = Bundle::from_store(&store, false).unwrap();
+        assert!(without.instances[0].api_key.is_empty());
+    }
+
+    #[test]
+    fn test_import_settles_conflicts() {
+        let (_source_dir, source) = store_with(&[
+            instance("prod", "sk-new", "gpt-4o"),
+            instance("dev", "sk-dev", "gpt-4o-mini"),
+        ]);
+        source
+            .save_yaml(
+                LABELS_FILE,
+                &vec![LabelAssignment {
+                    label_name: "main".to_string(),
+                    target: LabelTarget::ProviderInstance {
+                        instance_id: "prod".to_string(),
+                    },
+                    assigned_at: Utc::now(),
+                    assigned_by: None,
+                    fallbacks: Vec::new(),
+                    requires: Vec::new(),
+                }],
+            )
+            .unwrap();
+        let bundle = Bundle::from_store(&source, false).unwrap();
+
+        let (_target_dir, target) = store_with(&[
+            instance("prod", "sk-local", "gpt-4"),
+            instance("dev", "sk-dev", "gpt-4o-mini"),
+        ]);
+
+        // A dry run reports without writing
+        let report = bundle
+            .import_into(&target, ConflictStrategy::Rename, true)
+            .unwrap();
+        assert_eq!(
+            report.renamed,
+            vec![("prod".to_string(), "prod-imported".to_string())]
+        );
+        assert_eq!(report.unchanged, 1);
+        assert!(target.find_instance("prod-imported").unwrap().is_none());
+
+        let report = bundle
+            .import_into(&target, ConflictStrategy::Rename, false)
+            .unwrap();
+        assert!(report.has_changes());
+        let (_, renamed) = target.find_instance("prod-imported").unwrap().unwrap();
+        assert_eq!(renamed.models, vec!["gpt-4o"]);
+        let labels: Vec<LabelAssignment> = target.load_yaml(LABELS_FILE).unwrap().unwrap();
+        assert_eq!(labels[0].target.instance_id(), "prod-imported");
+
+        // Overwriting without secrets keeps the local key
+        let report = bundle
+            .import_into(&target, ConflictStrategy::Overwrite, false)
+            .unwrap();
+        assert_eq!(report.overwritten.len(), 2);
+        let (_, prod) = target.find_instance("prod").unwrap().unwrap();
+        assert_eq!(
+            (prod.api_key.as_str(), prod.models[0].as_str()),
+            ("sk-local", "gpt-4o")
+        );
+
+        let report = bundle
+            .import_into(&target, ConflictStrategy::Skip, false)
+            .unwrap();
+        assert!(!report.has_changes());
+    }
+}
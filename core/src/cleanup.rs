@@ -0,0 +1,232 @@
+#![allow(clippy::missing_errors_doc)]
+//! Removal of keys from the files they were discovered in.
+//!
+//! A [`Rewrite`] replaces every occurrence of a key in a source file with a
+//! reference to an environment variable. It is planned first so callers can
+//! preview it, and applying it backs the original file up next to it before the
+//! rewritten content is swapped in atomically with the same permissions.
+
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Suffix of backups made before a file is rewritten.
+pub const BACKUP_SUFFIX: &str = ".aicred-bak";
+
+/// Default environment variable for a provider's key, e.g. `OPENAI_API_KEY`.
+#[must_use]
+pub fn default_env_var(provider: &str) -> String {
+    let prefix: String = provider
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{prefix}_API_KEY")
+}
+
+/// Reference to an environment variable written in place of a key.
+#[must_use]
+pub fn env_reference(var: &str) -> String {
+    format!("${{{var}}}")
+}
+
+/// A changed line of a planned rewrite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineChange {
+    /// 1-based line number
+    pub line: usize,
+    /// The original line with the key redacted
+    pub before: String,
+    /// The rewritten line
+    pub after: String,
+}
+
+/// A planned rewrite of one source file.
+#[derive(Clone)]
+pub struct Rewrite {
+    path: PathBuf,
+    original: String,
+    rewritten: String,
+    changes: Vec<LineChange>,
+}
+
+impl std::fmt::Debug for Rewrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rewrite")
+            .field("path", &self.path)
+            .field("changes", &self.changes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Rewrite {
+    /// Plans replacing every occurrence of `secret` in a file with `replacement`.
+    ///
+    /// Returns `None` if the file does not contain the secret.
+    pub fn plan(path: &Path, secret: &str, replacement: &str) -> Result<Option<Self>> {
+        if secret.is_empty() {
+            return Err(Error::ValidationError("secret cannot be empty".to_string()));
+        }
+        let original = std::fs::read_to_string(path)?;
+        if !original.contains(secret) {
+            return Ok(None);
+        }
+
+        let redacted = redact(secret);
+        let changes = original
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.contains(secret))
+            .map(|(index, line)| LineChange {
+                line: index + 1,
+                before: line.replace(secret, &redacted),
+                after: line.replace(secret, replacement),
+            })
+            .collect();
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            rewritten: original.replace(secret, replacement),
+            original,
+            changes,
+        }))
+    }
+
+    /// File the rewrite applies to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Lines the rewrite changes.
+    #[must_use]
+    pub fn changes(&self) -> &[LineChange] {
+        &self.changes
+    }
+
+    /// Path the original file is backed up to, avoiding existing backups.
+    #[must_use]
+    pub fn backup_path(&self) -> PathBuf {
+        let base = format!("{}{BACKUP_SUFFIX}", self.path.display());
+        let mut candidate = PathBuf::from(&base);
+        let mut n = 1;
+        while candidate.exists() {
+            candidate = PathBuf::from(format!("{base}.{n}"));
+            n += 1;
+        }
+        candidate
+    }
+
+    /// Backs up the file and writes the rewritten content, returning the backup path.
+    ///
+    /// Fails without changes if the file was modified since it was planned.
+    pub fn apply(&self) -> Result<PathBuf> {
+        if std::fs::read_to_string(&self.path)? != self.original {
+            return Err(Error::ConfigError(format!(
+                "{} changed since the cleanup was planned",
+                self.path.display()
+            )));
+        }
+
+        let backup = self.backup_path();
+        std::fs::copy(&self.path, &backup)?;
+
+        let file_name = self
+            .path
+            .file_name()
+            .ok_or_else(|| Error::ConfigError(format!("{} is not a file", self.path.display())))?;
+        let temp = self.path.with_file_name(format!(
+            ".{}.aicred-{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id()
+        ));
+        std::fs::write(&temp, &self.rewritten)?;
+        let result = std::fs::metadata(&self.path)
+            .and_then(|meta| std::fs::set_permissions(&temp, meta.permissions()))
+            .and_then(|()| std::fs::rename(&temp, &self.path));
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(backup)
+    }
+}
+
+/// Redacts a secret for previews, keeping its last four characters.
+fn redact(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SECRET: &str = "sk-cleanup-1234567890abcdef";
+
+    #[test]
+    fn test_plan_and_apply_rewrite() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".env");
+        let content = format!("# keys\nOPENAI_API_KEY={SECRET}\nOTHER=1\n");
+        std::fs::write(&path, &content).unwrap();
+
+        let rewrite = Rewrite::plan(&path, SECRET, &env_reference("OPENAI_API_KEY"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rewrite.changes(),
+            &[LineChange {
+                line: 2,
+                before: "OPENAI_API_KEY=****cdef".to_string(),
+                after: "OPENAI_API_KEY=${OPENAI_API_KEY}".to_string(),
+            }]
+        );
+        // Planning leaves the file alone
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+
+        let backup = rewrite.apply().unwrap();
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), content);
+        let rewritten = std::fs::read_to_string(&path).unwrap();
+        assert!(!rewritten.contains(SECRET));
+        assert!(rewritten.contains("OTHER=1"));
+
+        // A second backup does not overwrite the first
+        assert_ne!(rewrite.backup_path(), backup);
+    }
+
+    #[test]
+    fn test_file_without_secret_is_not_planned() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.json");
+        std::fs::write(&path, "{}").unwrap();
+        assert!(Rewrite::plan(&path, SECRET, "${X}").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_changed_file_is_not_rewritten() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".env");
+        std::fs::write(&path, format!("KEY={SECRET}\n")).unwrap();
+        let rewrite = Rewrite::plan(&path, SECRET, "${KEY}").unwrap().unwrap();
+
+        std::fs::write(&path, format!("KEY={SECRET}\nNEW=1\n")).unwrap();
+        assert!(rewrite.apply().is_err());
+        assert!(std::fs::read_to_string(&path).unwrap().contains(SECRET));
+    }
+
+    #[test]
+    fn test_default_env_var() {
+        assert_eq!(default_env_var("openai"), "OPENAI_API_KEY");
+        assert_eq!(default_env_var("hugging-face"), "HUGGING_FACE_API_KEY");
+    }
+}
@@ -0,0 +1,356 @@
+#![allow(clippy::missing_errors_doc)]
+//! Encryption of stored secrets at rest.
+//!
+//! Secrets are sealed with ChaCha20-Poly1305 under a 256-bit key that comes
+//! either from an identity file or from a passphrase stretched with
+//! PBKDF2-HMAC-SHA256. Encrypted values are self-describing strings starting with
+//! [`ENCRYPTED_PREFIX`], so they can sit in any string field of a stored file and
+//! plain values keep working alongside them.
+
+use crate::error::{Error, Result};
+use crate::models::ProviderCollection;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Prefix of every encrypted value.
+pub const ENCRYPTED_PREFIX: &str = "aicred-enc:v1:";
+
+/// Environment variable holding the passphrase used to decrypt secrets.
+pub const PASSPHRASE_ENV: &str = "AICRED_PASSPHRASE";
+
+/// Environment variable pointing at the identity file used to decrypt secrets.
+pub const IDENTITY_ENV: &str = "AICRED_IDENTITY";
+
+/// Default identity file name within the config store.
+pub const IDENTITY_FILE: &str = "identity.key";
+
+/// PBKDF2 iterations for passphrase-derived keys.
+const PBKDF2_ITERATIONS: u32 = 210_000;
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+
+/// Checks whether a value was produced by [`SecretCipher::encrypt`].
+#[must_use]
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Where the encryption key comes from.
+#[derive(Clone)]
+pub enum KeySource {
+    /// A passphrase stretched with PBKDF2
+    Passphrase(String),
+    /// A file holding a hex-encoded 256-bit key
+    IdentityFile(PathBuf),
+}
+
+impl std::fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passphrase(_) => f.write_str("Passphrase(<redacted>)"),
+            Self::IdentityFile(path) => f.debug_tuple("IdentityFile").field(path).finish(),
+        }
+    }
+}
+
+impl KeySource {
+    /// Picks the key source from the environment: [`PASSPHRASE_ENV`], then
+    /// [`IDENTITY_ENV`], then `default_identity` if that file exists.
+    #[must_use]
+    pub fn from_env(default_identity: &Path) -> Option<Self> {
+        if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+            if !passphrase.is_empty() {
+                return Some(Self::Passphrase(passphrase));
+            }
+        }
+        if let Some(path) = std::env::var_os(IDENTITY_ENV) {
+            return Some(Self::IdentityFile(PathBuf::from(path)));
+        }
+        default_identity
+            .is_file()
+            .then(|| Self::IdentityFile(default_identity.to_path_buf()))
+    }
+}
+
+/// Generates a new identity file with a random key, readable only by the owner.
+///
+/// Fails if the file already exists.
+pub fn generate_identity(path: &Path) -> Result<()> {
+    let mut key = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| Error::SecurityError("Failed to generate key".to_string()))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    std::io::Write::write_all(&mut file, format!("{}\n", hex::encode(key)).as_bytes())?;
+    Ok(())
+}
+
+/// Encrypts and decrypts secret values with one key source.
+pub struct SecretCipher {
+    source: KeySource,
+    rng: SystemRandom,
+    /// Keys derived so far, by salt (empty salt for identity files)
+    keys: Mutex<HashMap<Vec<u8>, [u8; KEY_LEN]>>,
+    /// Salt used for new passphrase encryptions
+    salt: [u8; SALT_LEN],
+}
+
+impl std::fmt::Debug for SecretCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretCipher")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretCipher {
+    /// Creates a cipher for a key source.
+    pub fn new(source: KeySource) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill(&mut salt)
+            .map_err(|_| Error::SecurityError("Failed to generate salt".to_string()))?;
+        Ok(Self {
+            source,
+            rng,
+            keys: Mutex::new(HashMap::new()),
+            salt,
+        })
+    }
+
+    /// Gets the tag recorded in values encrypted by this cipher.
+    const fn kind(&self) -> &'static str {
+        match self.source {
+            KeySource::Passphrase(_) => "pbkdf2",
+            KeySource::IdentityFile(_) => "key",
+        }
+    }
+
+    fn key(&self, salt: &[u8]) -> Result<LessSafeKey> {
+        let mut keys = self
+            .keys
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let key = if let Some(key) = keys.get(salt) {
+            *key
+        } else {
+            let key = match &self.source {
+                KeySource::Passphrase(passphrase) => {
+                    let mut key = [0u8; KEY_LEN];
+                    ring::pbkdf2::derive(
+                        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+                        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap_or(NonZeroU32::MIN),
+                        salt,
+                        passphrase.as_bytes(),
+                        &mut key,
+                    );
+                    key
+                }
+                KeySource::IdentityFile(path) => read_identity(path)?,
+            };
+            keys.insert(salt.to_vec(), key);
+            key
+        };
+        drop(keys);
+
+        UnboundKey::new(&CHACHA20_POLY1305, &key)
+            .map(LessSafeKey::new)
+            .map_err(|_| Error::SecurityError("Invalid encryption key".to_string()))
+    }
+
+    /// Encrypts a value; already encrypted values are returned unchanged.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        if is_encrypted(plaintext) {
+            return Ok(plaintext.to_string());
+        }
+
+        let kind = self.kind();
+        let salt: &[u8] = match self.source {
+            KeySource::Passphrase(_) => &self.salt,
+            KeySource::IdentityFile(_) => &[],
+        };
+        let key = self.key(salt)?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| Error::SecurityError("Failed to generate nonce".to_string()))?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(kind.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| Error::SecurityError("Encryption failed".to_string()))?;
+
+        Ok(format!(
+            "{ENCRYPTED_PREFIX}{kind}:{}:{}{}",
+            hex::encode(salt),
+            hex::encode(nonce),
+            hex::encode(sealed)
+        ))
+    }
+
+    /// Decrypts a value; plain values are returned unchanged.
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some(rest) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let malformed = || Error::SecurityError("Malformed encrypted value".to_string());
+
+        let mut parts = rest.splitn(3, ':');
+        let (Some(kind), Some(salt), Some(payload)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(malformed());
+        };
+        if kind != self.kind() {
+            return Err(Error::SecurityError(format!(
+                "Value was encrypted with a {}, not a {}",
+                kind_label(kind),
+                kind_label(self.kind())
+            )));
+        }
+
+        let salt = hex::decode(salt).map_err(|_| malformed())?;
+        let payload = hex::decode(payload).map_err(|_| malformed())?;
+        if payload.len() < NONCE_LEN {
+            return Err(malformed());
+        }
+        let (nonce, sealed) = payload.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| malformed())?;
+
+        let mut sealed = sealed.to_vec();
+        let plaintext = self
+            .key(&salt)?
+            .open_in_place(nonce, Aad::from(kind.as_bytes()), &mut sealed)
+            .map_err(|_| {
+                Error::SecurityError("Decryption failed: wrong key or corrupted value".to_string())
+            })?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| malformed())
+    }
+
+    /// Encrypts every instance API key in place, returning how many changed.
+    pub fn encrypt_instances(&self, instances: &mut ProviderCollection) -> Result<usize> {
+        let mut changed = 0;
+        for instance in instances.instances.values_mut() {
+            if !instance.api_key.is_empty() && !is_encrypted(&instance.api_key) {
+                instance.api_key = self.encrypt(&instance.api_key)?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Decrypts every instance API key in place, returning how many changed.
+    pub fn decrypt_instances(&self, instances: &mut ProviderCollection) -> Result<usize> {
+        let mut changed = 0;
+        for instance in instances.instances.values_mut() {
+            if is_encrypted(&instance.api_key) {
+                instance.api_key = self.decrypt(&instance.api_key)?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Describes the key source behind a value's tag.
+fn kind_label(kind: &str) -> &'static str {
+    if kind == "key" {
+        "key file"
+    } else {
+        "passphrase"
+    }
+}
+
+/// Reads a hex-encoded key from an identity file.
+fn read_identity(path: &Path) -> Result<[u8; KEY_LEN]> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        Error::SecurityError(format!(
+            "Failed to read identity file {}: {e}",
+            path.display()
+        ))
+    })?;
+    hex::decode(content.trim())
+        .ok()
+        .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes).ok())
+        .ok_or_else(|| Error::SecurityError(format!("Invalid identity file: {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProviderInstance;
+
+    #[test]
+    fn test_identity_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let identity = temp_dir.path().join(IDENTITY_FILE);
+        generate_identity(&identity).unwrap();
+        assert!(generate_identity(&identity).is_err());
+
+        let cipher = SecretCipher::new(KeySource::IdentityFile(identity)).unwrap();
+        let sealed = cipher.encrypt("sk-test1234567890").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("sk-test"));
+        assert_eq!(cipher.encrypt(&sealed).unwrap(), sealed);
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "sk-test1234567890");
+        assert_eq!(cipher.decrypt("plain").unwrap(), "plain");
+    }
+
+    #[test]
+    fn test_passphrase_round_trip_and_wrong_key() {
+        let cipher = SecretCipher::new(KeySource::Passphrase("correct horse".to_string())).unwrap();
+        let sealed = cipher.encrypt("sk-ant-secret").unwrap();
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "sk-ant-secret");
+
+        // A different cipher with the same passphrase derives the key from the stored salt
+        let other = SecretCipher::new(KeySource::Passphrase("correct horse".to_string())).unwrap();
+        assert_eq!(other.decrypt(&sealed).unwrap(), "sk-ant-secret");
+
+        let wrong = SecretCipher::new(KeySource::Passphrase("battery staple".to_string())).unwrap();
+        assert!(wrong.decrypt(&sealed).is_err());
+
+        let last = if sealed.ends_with('0') { '1' } else { '0' };
+        let tampered = format!("{}{last}", &sealed[..sealed.len() - 1]);
+        assert!(cipher.decrypt(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_instances_round_trip() {
+        let cipher = SecretCipher::new(KeySource::Passphrase("pass".to_string())).unwrap();
+        let mut instances = ProviderCollection::new();
+        instances.add(
+            "abcd".to_string(),
+            ProviderInstance::new(
+                "abcd".to_string(),
+                "openai".to_string(),
+                "https://api.openai.com/v1".to_string(),
+                "sk-test1234567890".to_string(),
+                Vec::new(),
+            ),
+        );
+
+        assert_eq!(cipher.encrypt_instances(&mut instances).unwrap(), 1);
+        assert_eq!(cipher.encrypt_instances(&mut instances).unwrap(), 0);
+        assert!(is_encrypted(&instances.get("abcd").unwrap().api_key));
+        assert_eq!(cipher.decrypt_instances(&mut instances).unwrap(), 1);
+        assert_eq!(instances.get("abcd").unwrap().api_key, "sk-test1234567890");
+    }
+}
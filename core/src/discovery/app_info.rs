@@ -0,0 +1,246 @@
+//! Application versions and settings summaries of config instances.
+//!
+//! [`enrich`] runs on every config instance a scanner returns, so scan reports
+//! can say "claude-desktop 0.9.2 with 3 MCP servers, 2 containing credentials"
+//! without each scanner detecting them on its own. The version is taken from,
+//! in order:
+//!
+//! 1. the `version` metadata the scanner recorded,
+//! 2. a schema marker in the config itself, such as `claude_version`,
+//! 3. the `version` of a `package.json` next to the config or above it,
+//! 4. the `CFBundleShortVersionString` of an `Info.plist` above the config.
+
+use crate::models::{ConfigInstance, DiscoveredCredential, SettingsSummary};
+use serde_json::Value;
+use std::path::Path;
+
+/// Config keys holding the version of the application or of its config schema.
+pub const VERSION_MARKERS: &[&str] = &[
+    "version",
+    "app_version",
+    "appVersion",
+    "claude_version",
+    "ragit_version",
+    "schema_version",
+    "schemaVersion",
+];
+
+/// Config keys holding MCP server definitions.
+const MCP_SERVER_KEYS: &[&str] = &["mcpServers", "mcp_servers"];
+
+/// Parent directories searched for `package.json` and `Info.plist`.
+const MAX_ANCESTORS: usize = 3;
+
+/// Fills in the version and settings summary of an instance parsed from the
+/// file at `path`.
+pub fn enrich(
+    instance: &mut ConfigInstance,
+    path: &Path,
+    content: &str,
+    keys: &[DiscoveredCredential],
+) {
+    let config = parse_config(path, content);
+    if instance.app_version.is_none() {
+        instance.app_version = instance
+            .metadata
+            .get("version")
+            .cloned()
+            .or_else(|| config.as_ref().and_then(schema_version))
+            .or_else(|| package_json_version(path))
+            .or_else(|| plist_version(path));
+    }
+    if let Some(config) = &config {
+        let keys: Vec<&DiscoveredCredential> = keys.iter().chain(&instance.keys).collect();
+        let settings = summarize_settings(config, &keys);
+        if !settings.is_empty() {
+            instance.settings = settings;
+        }
+    }
+}
+
+/// Version from a schema marker at the top level of a config.
+#[must_use]
+pub fn schema_version(config: &Value) -> Option<String> {
+    VERSION_MARKERS
+        .iter()
+        .find_map(|marker| version_string(config.get(*marker)?))
+}
+
+/// Version of the nearest `package.json` in the directory of `path` or above.
+#[must_use]
+pub fn package_json_version(path: &Path) -> Option<String> {
+    path.ancestors()
+        .skip(1)
+        .take(MAX_ANCESTORS + 1)
+        .find_map(|dir| {
+            let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+            let package: Value = serde_json::from_str(&content).ok()?;
+            version_string(package.get("version")?)
+        })
+}
+
+/// Version of the nearest `Info.plist` of an app bundle above `path`.
+#[must_use]
+pub fn plist_version(path: &Path) -> Option<String> {
+    path.ancestors()
+        .skip(1)
+        .take(MAX_ANCESTORS + 1)
+        .flat_map(|dir| [dir.join("Info.plist"), dir.join("Contents/Info.plist")])
+        .find_map(|plist| {
+            let content = std::fs::read_to_string(plist).ok()?;
+            plist_string(&content, "CFBundleShortVersionString")
+                .or_else(|| plist_string(&content, "CFBundleVersion"))
+        })
+}
+
+/// Counts the MCP servers of a config and those holding a credential: a
+/// secret-looking environment variable or header, or one of `keys`.
+#[must_use]
+pub fn summarize_settings(config: &Value, keys: &[&DiscoveredCredential]) -> SettingsSummary {
+    let mut servers = Vec::new();
+    collect_mcp_servers(config, &mut servers);
+    SettingsSummary {
+        mcp_servers: servers.len(),
+        mcp_servers_with_credentials: servers
+            .iter()
+            .filter(|server| holds_credential(server, keys))
+            .count(),
+    }
+}
+
+/// Parses a JSON or YAML config, `None` for other formats.
+fn parse_config(path: &Path, content: &str) -> Option<Value> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(content).ok(),
+        _ => serde_json::from_str(content).ok(),
+    }
+    .filter(Value::is_object)
+}
+
+fn version_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(version) if !version.trim().is_empty() => Some(version.trim().to_string()),
+        Value::Number(version) => Some(version.to_string()),
+        _ => None,
+    }
+}
+
+/// String value of a key in an XML property list.
+fn plist_string(content: &str, key: &str) -> Option<String> {
+    let rest = &content[content.find(&format!("<key>{key}</key>"))?..];
+    let start = rest.find("<string>")? + "<string>".len();
+    let end = start + rest[start..].find("</string>")?;
+    let value = rest[start..end].trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Collects the server definitions of every MCP section, including those of
+/// per-project settings.
+fn collect_mcp_servers<'a>(value: &'a Value, servers: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                match child {
+                    Value::Object(definitions) if MCP_SERVER_KEYS.contains(&name.as_str()) => {
+                        servers.extend(definitions.values());
+                    }
+                    _ => collect_mcp_servers(child, servers),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_mcp_servers(item, servers);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn holds_credential(server: &Value, keys: &[&DiscoveredCredential]) -> bool {
+    let secret_setting = ["env", "headers"].iter().any(|section| {
+        server
+            .get(*section)
+            .and_then(Value::as_object)
+            .is_some_and(|settings| {
+                settings.iter().any(|(name, value)| {
+                    is_secret_name(name)
+                        && value
+                            .as_str()
+                            .is_some_and(|value| !value.is_empty() && !value.starts_with("${"))
+                })
+            })
+    });
+    secret_setting || {
+        let text = server.to_string();
+        keys.iter()
+            .filter_map(|key| key.full_value())
+            .any(|value| !value.is_empty() && text.contains(value))
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTHORIZATION"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_enrich_detects_version_and_mcp_servers() {
+        let temp = tempfile::tempdir().unwrap();
+        let contents = temp.path().join("Claude.app/Contents");
+        std::fs::create_dir_all(contents.join("Resources")).unwrap();
+        std::fs::write(
+            contents.join("Info.plist"),
+            "<plist><dict><key>CFBundleShortVersionString</key>\n<string>0.9.2</string></dict></plist>",
+        )
+        .unwrap();
+        let path = contents.join("Resources/claude_desktop_config.json");
+        let config = r#"{
+            "mcpServers": {
+                "github": {"command": "npx", "env": {"GITHUB_TOKEN": "ghp_abcdefghijklmnop"}},
+                "search": {"command": "npx", "args": ["--key", "sk-search-1234567890"]},
+                "files": {"command": "npx", "env": {"API_KEY": "${API_KEY}"}}
+            }
+        }"#;
+        let key = DiscoveredCredential::new(
+            "search".to_string(),
+            path.display().to_string(),
+            crate::models::ValueType::ApiKey,
+            crate::models::Confidence::High,
+            "sk-search-1234567890".to_string(),
+        );
+
+        let mut instance = ConfigInstance::new(
+            "claude_1".to_string(),
+            "claude-desktop".to_string(),
+            PathBuf::from(&path),
+        );
+        enrich(&mut instance, &path, config, &[key]);
+
+        assert_eq!(instance.app_version.as_deref(), Some("0.9.2"));
+        assert_eq!(
+            instance.settings,
+            SettingsSummary {
+                mcp_servers: 3,
+                mcp_servers_with_credentials: 2,
+            }
+        );
+
+        let mut versioned = ConfigInstance::default();
+        enrich(
+            &mut versioned,
+            Path::new("/nonexistent/config.json"),
+            r#"{"claude_version": "1.0.0"}"#,
+            &[],
+        );
+        assert_eq!(versioned.app_version.as_deref(), Some("1.0.0"));
+        assert!(versioned.settings.is_empty());
+    }
+}
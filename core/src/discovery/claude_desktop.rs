@@ -1,6 +1,8 @@
 //! `Claude Desktop` scanner for discovering API keys in `Claude Desktop` configuration files.
 
-use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerPlugin, ScannerPluginExt};
+use super::{
+    EnvVarDeclaration, LabelMapping, ScanResult, ScannerContext, ScannerPlugin, ScannerPluginExt,
+};
 use crate::error::Result;
 use crate::models::credentials::{Confidence, DiscoveredCredential, ValueType};
 use crate::models::ConfigInstance;
@@ -19,8 +21,18 @@ impl ScannerPlugin for ClaudeDesktopScanner {
         "Claude Desktop"
     }
 
+    fn description(&self) -> &'static str {
+        "Claude Desktop application"
+    }
+
     fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
-        vec![home_dir.join(".claude.json")]
+        vec![
+            home_dir.join(".claude.json"),
+            // ~/.config/Claude, ~/Library/Application Support/Claude or %APPDATA%\Claude
+            crate::paths::app_settings_dir(home_dir)
+                .join("Claude")
+                .join("claude_desktop_config.json"),
+        ]
     }
 
     fn can_handle_file(&self, path: &Path) -> bool {
@@ -33,7 +45,12 @@ impl ScannerPlugin for ClaudeDesktopScanner {
                 || path_str.contains(".claude"))
     }
 
-    fn parse_config(&self, path: &Path, content: &str) -> Result<ScanResult> {
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<ScanResult> {
         self.parse_config_with_registry(path, content, None)
     }
 
@@ -350,7 +367,7 @@ impl ClaudeDesktopScanner {
         }
 
         let mut instance = ConfigInstance::new(
-            Self::generate_instance_id(path),
+            super::ids::config_instance_id("claude", path),
             "claude-desktop".to_string(),
             path.to_path_buf(),
         );
@@ -358,17 +375,6 @@ impl ClaudeDesktopScanner {
         instance
     }
 
-    /// Generate a unique instance ID.
-    fn generate_instance_id(path: &Path) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(path.to_string_lossy().as_bytes());
-        format!("claude_{:x}", hasher.finalize())
-            .chars()
-            .take(16)
-            .collect()
-    }
-
     /// Check if a key is valid.
     fn is_valid_key(key: &str) -> bool {
         // For Anthropic API keys, require the sk-ant- prefix and reasonable length
@@ -440,9 +446,21 @@ mod tests {
         let home_dir = temp_dir.path();
         let paths = scanner.scan_paths(home_dir);
 
-        // Should only include ~/.claude.json
-        assert_eq!(paths.len(), 1);
+        assert_eq!(paths.len(), 2);
         assert!(paths[0].to_string_lossy().contains(".claude.json"));
+
+        #[cfg(windows)]
+        let settings_dir = home_dir.join("AppData").join("Roaming");
+        #[cfg(target_os = "macos")]
+        let settings_dir = home_dir.join("Library").join("Application Support");
+        #[cfg(all(unix, not(target_os = "macos")))]
+        let settings_dir = home_dir.join(".config");
+        assert_eq!(
+            paths[1],
+            settings_dir
+                .join("Claude")
+                .join("claude_desktop_config.json")
+        );
     }
 
     #[test]
@@ -474,7 +492,7 @@ mod tests {
         }"#;
 
         let result = scanner
-            .parse_config(Path::new("test.json"), config)
+            .parse_config(Path::new("test.json"), config, &ScannerContext::default())
             .unwrap();
         assert_eq!(result.keys.len(), 1);
         assert_eq!(result.instances.len(), 1);
@@ -533,7 +551,7 @@ mod tests {
         }"#;
 
         let result = scanner
-            .parse_config(Path::new("test.json"), config)
+            .parse_config(Path::new("test.json"), config, &ScannerContext::default())
             .unwrap();
 
         // Verify all configuration values are discovered (API key + model + temperature + max_tokens)
@@ -598,7 +616,7 @@ mod tests {
         }"#;
 
         let result = scanner
-            .parse_config(Path::new("test.json"), config)
+            .parse_config(Path::new("test.json"), config, &ScannerContext::default())
             .unwrap();
 
         // Should discover model as a key even without API key
@@ -0,0 +1,156 @@
+//! Per-scanner settings passed to [`ScannerPlugin::parse_config`](super::ScannerPlugin::parse_config).
+//!
+//! Settings come from `ScanOptions::scanner_settings`, keyed by scanner name,
+//! and let a scan cover applications installed in nonstandard locations or
+//! configured through unusual variable names without code changes.
+
+use super::{extract_env_keys_with_metadata, ScanResult};
+use crate::models::DiscoveredCredential;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Settings of one scanner.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScannerContext {
+    /// Further files or directories to scan, relative to the home directory
+    /// unless absolute.
+    pub extra_paths: Vec<PathBuf>,
+    /// Variable or config key names whose values are not reported, where `*`
+    /// matches any characters.
+    pub disabled_patterns: Vec<String>,
+    /// Further variable names holding API keys, mapped to their provider.
+    pub env_vars: BTreeMap<String, String>,
+}
+
+impl ScannerContext {
+    /// Checks if the context changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.extra_paths.is_empty() && self.disabled_patterns.is_empty() && self.env_vars.is_empty()
+    }
+
+    /// Appends the extra paths to the scanner's own paths.
+    #[must_use]
+    pub fn scan_paths(&self, mut paths: Vec<PathBuf>, home_dir: &Path) -> Vec<PathBuf> {
+        for path in &self.extra_paths {
+            let path = path
+                .strip_prefix("~")
+                .map_or_else(|_| home_dir.join(path), |rest| home_dir.join(rest));
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// Checks if a variable or config key name matches a disabled pattern.
+    #[must_use]
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.disabled_patterns
+            .iter()
+            .any(|pattern| wildcard_regex(pattern).is_some_and(|regex| regex.is_match(name)))
+    }
+
+    /// Extracts the keys of the custom variables from `content`.
+    #[must_use]
+    pub fn extract_env_keys(&self, content: &str) -> Vec<DiscoveredCredential> {
+        let patterns: Vec<(&str, &str)> = self
+            .env_vars
+            .iter()
+            .map(|(name, provider)| (name.as_str(), provider.as_str()))
+            .collect();
+        if patterns.is_empty() {
+            return Vec::new();
+        }
+        extract_env_keys_with_metadata(content, &patterns, &[])
+    }
+
+    /// Adds the keys of the custom variables to a scanner's result and drops
+    /// the findings whose name is disabled.
+    pub fn apply(&self, result: &mut ScanResult, content: &str) {
+        result.add_keys(self.extract_env_keys(content));
+        if self.disabled_patterns.is_empty() {
+            return;
+        }
+
+        let enabled = |key: &DiscoveredCredential| {
+            !key.full_value()
+                .and_then(|value| assigned_name(content, value))
+                .is_some_and(|name| self.is_disabled(name))
+        };
+        result.keys.retain(|key| enabled(key));
+        for instance in &mut result.instances {
+            instance.keys.retain(|key| enabled(key));
+        }
+    }
+}
+
+/// Name assigned a value on the first line holding it, as in `NAME=value`,
+/// `export NAME="value"` or `"name": "value"`.
+fn assigned_name<'a>(content: &'a str, value: &str) -> Option<&'a str> {
+    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let line = content.lines().find(|line| line.contains(value))?;
+    let before = line[..line.find(value)?]
+        .trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '=' | ':'));
+    let start = before
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !is_name_char(c))
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let name = &before[start..];
+    (!name.is_empty()).then_some(name)
+}
+
+/// Compiles a pattern where `*` matches any characters, ignoring case.
+fn wildcard_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    Regex::new(&format!("(?i)^{pattern}$")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ValueType;
+
+    #[test]
+    fn test_context_adds_custom_vars_and_drops_disabled_names() {
+        let context = ScannerContext {
+            extra_paths: vec![PathBuf::from("~/Apps/Claude/config.json")],
+            disabled_patterns: vec!["*_MODEL_ID".to_string()],
+            env_vars: BTreeMap::from([("CORP_LLM_TOKEN".to_string(), "openai".to_string())]),
+        };
+        let home = Path::new("/home/user");
+        assert_eq!(
+            context.scan_paths(vec![home.join(".claude.json")], home),
+            vec![
+                home.join(".claude.json"),
+                home.join("Apps/Claude/config.json")
+            ]
+        );
+        assert!(context.is_disabled("gsh_fast_model_id"));
+        assert!(!context.is_disabled("GSH_FAST_MODEL_API_KEY"));
+
+        let gshrc = "export CORP_LLM_TOKEN=sk-corp-1234567890\nGSH_FAST_MODEL_ID=llama3-70b\n";
+        let mut result = ScanResult::new();
+        result.add_key(DiscoveredCredential::new(
+            "groq".to_string(),
+            ".gshrc".to_string(),
+            ValueType::ModelId,
+            crate::models::Confidence::High,
+            "llama3-70b".to_string(),
+        ));
+        context.apply(&mut result, gshrc);
+
+        assert_eq!(result.keys.len(), 1);
+        assert_eq!(result.keys[0].provider, "openai");
+        assert_eq!(result.keys[0].full_value(), Some("sk-corp-1234567890"));
+        assert!(ScannerContext::default().is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 //! `GSH` scanner for discovering API keys in `GSH` configuration files.
 
-use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerPlugin, ScannerPluginExt};
+use super::{
+    EnvVarDeclaration, LabelMapping, ScanResult, ScannerContext, ScannerPlugin, ScannerPluginExt,
+};
 use crate::error::Result;
 use crate::models::credentials::DiscoveredCredential;
 use crate::models::credentials::{Confidence, ValueType};
@@ -20,6 +22,10 @@ impl ScannerPlugin for GshScanner {
         "GSH"
     }
 
+    fn description(&self) -> &'static str {
+        "GSH configurations"
+    }
+
     fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
         vec![home_dir.join(".gshrc")]
     }
@@ -30,7 +36,12 @@ impl ScannerPlugin for GshScanner {
         file_name == ".gshrc" || file_name.ends_with("gshrc")
     }
 
-    fn parse_config(&self, path: &Path, content: &str) -> Result<ScanResult> {
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<ScanResult> {
         self.parse_config_with_registry(path, content, None)
     }
 
@@ -462,22 +473,22 @@ impl GshScanner {
             ),
         ];
 
-        for (pattern, provider) in patterns {
-            let regex = regex::Regex::new(pattern).unwrap();
-            for cap in regex.captures_iter(content) {
-                if let Some(key_match) = cap.get(1) {
-                    let key_value = key_match.as_str();
+        let compiled: Vec<String> = patterns.iter().map(|(p, _)| (*p).to_string()).collect();
+        let set = super::PatternSet::cached(&compiled);
 
-                    let discovered_key = DiscoveredCredential::new(
-                        provider.to_string(),
-                        path.display().to_string(),
-                        ValueType::ApiKey,
-                        Self::get_confidence(key_value),
-                        key_value.to_string(),
-                    );
+        for (index, cap) in set.captures_iter(content) {
+            if let Some(key_match) = cap.get(1) {
+                let key_value = key_match.as_str();
 
-                    keys.push(discovered_key);
-                }
+                let discovered_key = DiscoveredCredential::new(
+                    patterns[index].1.to_string(),
+                    path.display().to_string(),
+                    ValueType::ApiKey,
+                    Self::get_confidence(key_value),
+                    key_value.to_string(),
+                );
+
+                keys.push(discovered_key);
             }
         }
 
@@ -506,7 +517,7 @@ impl GshScanner {
         metadata.insert("format".to_string(), "KEY=value".to_string());
 
         let mut instance = ConfigInstance::new(
-            Self::generate_instance_id(path),
+            super::ids::config_instance_id("gsh", path),
             "gsh".to_string(),
             path.to_path_buf(),
         );
@@ -514,17 +525,6 @@ impl GshScanner {
         instance
     }
 
-    /// Generate a unique instance ID.
-    fn generate_instance_id(path: &Path) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(path.to_string_lossy().as_bytes());
-        format!("gsh_{:x}", hasher.finalize())
-            .chars()
-            .take(16)
-            .collect()
-    }
-
     /// Get confidence score for a key.
     fn get_confidence(key: &str) -> Confidence {
         if key.starts_with("sk-") || key.starts_with("sk-ant-") || key.starts_with("hf_") {
@@ -580,7 +580,9 @@ export ANTHROPIC_API_KEY="sk-ant-REDACTED"
 GOOGLE_API_KEY="AIzaSyTest1234567890abcdef"
 "#;
 
-        let result = scanner.parse_config(Path::new(".gshrc"), config).unwrap();
+        let result = scanner
+            .parse_config(Path::new(".gshrc"), config, &ScannerContext::default())
+            .unwrap();
         assert_eq!(result.keys.len(), 3);
         assert_eq!(result.instances.len(), 1);
 
@@ -745,7 +747,9 @@ export GSH_SLOW_MODEL_API_KEY="sk-or-v1_test1234567890abcdef"
 export OPENAI_API_KEY="sk-test1234567890abcdef"
 "#;
 
-        let result = scanner.parse_config(Path::new(".gshrc"), config).unwrap();
+        let result = scanner
+            .parse_config(Path::new(".gshrc"), config, &ScannerContext::default())
+            .unwrap();
         // Should have keys from both GSH parsing and shell script parsing
         assert!(result.keys.len() >= 3);
 
@@ -768,7 +772,9 @@ export ANTHROPIC_API_KEY="sk-ant-REDACTED"
 export GSH_SLOW_MODEL_API_KEY="sk-ant-REDACTED"
 "#;
 
-        let result = scanner.parse_config(Path::new(".gshrc"), config).unwrap();
+        let result = scanner
+            .parse_config(Path::new(".gshrc"), config, &ScannerContext::default())
+            .unwrap();
 
         // Count unique key hashes to verify no duplicates
         let mut unique_hashes = std::collections::HashSet::new();
@@ -810,7 +816,9 @@ export GROQ_API_KEY="gsk_test1234567890abcdef1234567890abcdef"
 export COHERE_API_KEY="cohere_test1234567890abcdef1234567890abcdef"
 "#;
 
-        let result = scanner.parse_config(Path::new(".gshrc"), config).unwrap();
+        let result = scanner
+            .parse_config(Path::new(".gshrc"), config, &ScannerContext::default())
+            .unwrap();
 
         println!("Found {} keys:", result.keys.len());
         for (i, key) in result.keys.iter().enumerate() {
@@ -869,7 +877,9 @@ export HUGGING_FACE_HUB_TOKEN="hf_duplicate1234567890abcdef"
 export HF_TOKEN="hf_duplicate1234567890abcdef"
 "#;
 
-        let result = scanner.parse_config(Path::new(".gshrc"), config).unwrap();
+        let result = scanner
+            .parse_config(Path::new(".gshrc"), config, &ScannerContext::default())
+            .unwrap();
 
         println!("=== Testing actual duplicate detection ===");
         println!("Found {} keys:", result.keys.len());
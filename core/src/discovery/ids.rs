@@ -0,0 +1,149 @@
+//! Instance IDs derived from discovered config files and keys.
+//!
+//! Stored configs refer to instances by these IDs, so the derivations must not
+//! change: every scanner and command uses the functions here instead of
+//! hashing on its own.
+//!
+//! A provider instance ID has the form `<provider>-<fingerprint>`, such as
+//! `openai-7484d0b5f1a9`:
+//!
+//! - `<provider>` is the provider type in lowercase, with every character other
+//!   than a letter or digit replaced by `-`.
+//! - `<fingerprint>` is the first [`INSTANCE_FINGERPRINT_LEN`] hex characters
+//!   of the SHA-256 of the instance's source fingerprint. For an instance built
+//!   from a key, that is the plain SHA-256 of the key's value, so discovery,
+//!   `scan --update` and the setup wizard agree on the ID of a key wherever it
+//!   is found.
+//!
+//! When the ID is held by a different instance in the store, `-2`, `-3`, ...
+//! is appended (see [`unique_instance_id`]). IDs created before this 12-hex
+//! scheme were the first four hex characters of the fingerprint alone;
+//! [`is_legacy_instance_id`] recognizes those, and `aicred config migrate`
+//! renames them.
+
+use crate::models::DiscoveredCredential;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Length of a config instance ID, including its scanner prefix.
+const CONFIG_INSTANCE_ID_LEN: usize = 16;
+
+/// Length of the fingerprint part of a provider instance ID in hex characters.
+pub const INSTANCE_FINGERPRINT_LEN: usize = 12;
+
+/// Length of a provider instance ID created before the 12-hex scheme in hex
+/// characters.
+const LEGACY_INSTANCE_ID_LEN: usize = 4;
+
+/// ID of the config instance of an application config file, such as
+/// `gsh_1a2b3c4d5e6f` for a `prefix` of `gsh`.
+#[must_use]
+pub fn config_instance_id(prefix: &str, path: &Path) -> String {
+    let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+    format!("{prefix}_{digest:x}")
+        .chars()
+        .take(CONFIG_INSTANCE_ID_LEN)
+        .collect()
+}
+
+/// ID of a provider instance from its provider type and source fingerprint.
+#[must_use]
+pub fn instance_id(provider_type: &str, source_fingerprint: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(source_fingerprint.as_bytes()));
+    format!(
+        "{}-{}",
+        provider_slug(provider_type),
+        &digest[..INSTANCE_FINGERPRINT_LEN]
+    )
+}
+
+/// ID of the provider instance of a key, as built by discovery,
+/// `scan --update` and the setup wizard.
+///
+/// Derived from the plain SHA-256 of the key's value, so it doesn't change when
+/// findings are hashed with HMAC.
+#[must_use]
+pub fn key_instance_id(key: &DiscoveredCredential) -> String {
+    instance_id(&key.provider, &key_fingerprint(key))
+}
+
+/// Provider instance ID `scan --update` and the setup wizard gave a key before
+/// the 12-hex scheme.
+#[must_use]
+pub fn legacy_key_instance_id(key: &DiscoveredCredential) -> String {
+    let digest = format!("{:x}", Sha256::digest(key_fingerprint(key).as_bytes()));
+    digest[..LEGACY_INSTANCE_ID_LEN].to_string()
+}
+
+/// Provider instance ID discovery gave a provider's keys in a config file
+/// before the 12-hex scheme.
+#[must_use]
+pub fn legacy_provider_instance_id(provider: &str, source_path: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(format!("{provider}:{source_path}")));
+    digest[..LEGACY_INSTANCE_ID_LEN].to_string()
+}
+
+/// Checks if an ID has the form of a provider instance ID created before the
+/// 12-hex scheme.
+#[must_use]
+pub fn is_legacy_instance_id(id: &str) -> bool {
+    id.len() == LEGACY_INSTANCE_ID_LEN && is_lower_hex(id)
+}
+
+/// Checks if an ID has the form `<provider>-<fingerprint>`, optionally with
+/// a collision suffix.
+#[must_use]
+pub fn is_instance_id(id: &str) -> bool {
+    let id = match id.rsplit_once('-') {
+        Some((rest, suffix))
+            if suffix.len() < INSTANCE_FINGERPRINT_LEN
+                && suffix.parse::<u32>().is_ok_and(|n| n >= 2) =>
+        {
+            rest
+        }
+        _ => id,
+    };
+    id.rsplit_once('-').is_some_and(|(slug, fingerprint)| {
+        !slug.is_empty()
+            && fingerprint.len() == INSTANCE_FINGERPRINT_LEN
+            && is_lower_hex(fingerprint)
+    })
+}
+
+/// First of `id`, `id-2`, `id-3`, ... that is not taken by another instance.
+#[must_use]
+pub fn unique_instance_id(id: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut candidate = id.to_string();
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = format!("{id}-{n}");
+        n += 1;
+    }
+    candidate
+}
+
+/// Provider part of an instance ID.
+fn provider_slug(provider_type: &str) -> String {
+    let slug: String = provider_type
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "instance".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Source fingerprint of a key: the plain SHA-256 of its value, else its hash.
+fn key_fingerprint(key: &DiscoveredCredential) -> String {
+    key.full_value()
+        .map_or_else(|| key.hash.clone(), DiscoveredCredential::hash_value)
+}
+
+fn is_lower_hex(s: &str) -> bool {
+    s.chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, 'a'..='f'))
+}
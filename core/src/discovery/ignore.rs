@@ -0,0 +1,296 @@
+#![allow(clippy::missing_errors_doc)]
+//! Gitignore-style ignore rules for excluding paths from all scanners.
+//!
+//! Rules come from a global `ignore` file in the config directory (see
+//! [`crate::paths`]), such as `~/.config/aicred/ignore`, and from `.aicredignore`
+//! files in any ancestor directory of a scanned path. Later rules win, and rules in
+//! deeper directories take precedence over shallower ones, as with `.gitignore`.
+
+use crate::error::{Error, Result};
+use crate::paths::AppDirs;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Name of the global ignore file in the config directory.
+pub const GLOBAL_IGNORE_FILE: &str = "ignore";
+
+/// Name of per-directory ignore files.
+pub const IGNORE_FILE_NAME: &str = ".aicredignore";
+
+/// A single compiled ignore pattern.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory the pattern is relative to
+    base: PathBuf,
+    /// Compiled pattern, matched against `/`-separated relative paths
+    regex: Regex,
+    /// Whether the pattern re-includes previously ignored paths
+    negated: bool,
+    /// Whether the pattern only matches directories
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    /// Checks the rule against a path, returning whether it matched
+    fn matches(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let components: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        // A rule matching any parent directory also excludes everything beneath it
+        (1..=components.len()).any(|len| {
+            let is_dir = len < components.len() || path.is_dir();
+            (!self.dir_only || is_dir) && self.regex.is_match(&components[..len].join("/"))
+        })
+    }
+}
+
+/// Set of ignore rules consulted before a scanner reads a file.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+    /// Whether to also honor `.aicredignore` files in ancestor directories
+    pub per_directory: bool,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IgnoreRules {
+    /// Creates an empty rule set that honors per-directory ignore files
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            per_directory: true,
+        }
+    }
+
+    /// Loads the global ignore file from the given home directory, if it exists
+    #[must_use]
+    pub fn load(home_dir: &Path) -> Self {
+        let mut rules = Self::new();
+        let global = AppDirs::for_home(home_dir)
+            .config_dir()
+            .join(GLOBAL_IGNORE_FILE);
+        if global.is_file() {
+            if let Err(e) = rules.add_file_with_base(&global, home_dir) {
+                tracing::warn!("Ignoring invalid ignore file {}: {}", global.display(), e);
+            }
+        }
+        rules
+    }
+
+    /// Adds a single gitignore-style pattern relative to `base`
+    pub fn add_pattern(&mut self, base: &Path, pattern: &str) -> Result<()> {
+        if let Some(rule) = compile_pattern(base, pattern)? {
+            self.rules.push(rule);
+        }
+        Ok(())
+    }
+
+    /// Adds every pattern in gitignore-formatted content relative to `base`
+    pub fn add_patterns(&mut self, base: &Path, content: &str) -> Result<()> {
+        for line in content.lines() {
+            self.add_pattern(base, line)?;
+        }
+        Ok(())
+    }
+
+    /// Adds the patterns from an ignore file, relative to the file's directory
+    pub fn add_file(&mut self, path: &Path) -> Result<()> {
+        let base = path.parent().unwrap_or_else(|| Path::new("/"));
+        self.add_file_with_base(path, base)
+    }
+
+    fn add_file_with_base(&mut self, path: &Path, base: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path)?;
+        self.add_patterns(base, &content)
+    }
+
+    /// Gets the number of loaded rules (not counting per-directory files)
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Checks if no rules are loaded (not counting per-directory files)
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Checks whether a path is excluded by the loaded rules or any
+    /// `.aicredignore` file in its ancestor directories
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let mut ignored = apply_rules(&self.rules, path, false);
+
+        if self.per_directory {
+            // Walk from the shallowest ancestor to the deepest so deeper files win
+            let mut ancestors: Vec<&Path> = path.ancestors().skip(1).collect();
+            ancestors.reverse();
+            for dir in ancestors {
+                let ignore_file = dir.join(IGNORE_FILE_NAME);
+                if !ignore_file.is_file() {
+                    continue;
+                }
+                let mut local = Self::new();
+                match local.add_file_with_base(&ignore_file, dir) {
+                    Ok(()) => ignored = apply_rules(&local.rules, path, ignored),
+                    Err(e) => tracing::warn!(
+                        "Ignoring invalid ignore file {}: {}",
+                        ignore_file.display(),
+                        e
+                    ),
+                }
+            }
+        }
+
+        ignored
+    }
+}
+
+/// Applies rules in order on top of the current state; the last match wins
+fn apply_rules(rules: &[IgnoreRule], path: &Path, mut ignored: bool) -> bool {
+    for rule in rules {
+        if rule.matches(path) {
+            ignored = !rule.negated;
+        }
+    }
+    ignored
+}
+
+/// Compiles a gitignore-style pattern, returning `None` for blank lines and comments
+fn compile_pattern(base: &Path, pattern: &str) -> Result<Option<IgnoreRule>> {
+    let pattern = pattern.trim_end();
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return Ok(None);
+    }
+
+    let (negated, pattern) = pattern
+        .strip_prefix('!')
+        .map_or((false, pattern), |rest| (true, rest));
+    let pattern = pattern.strip_prefix('\\').unwrap_or(pattern);
+    let (dir_only, pattern) = pattern
+        .strip_suffix('/')
+        .map_or((false, pattern), |rest| (true, rest));
+
+    // Patterns containing a slash are anchored to the ignore file's directory
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                if let Some(end) = chars[i..].iter().position(|&c| c == ']') {
+                    let class: String = chars[i + 1..i + end].iter().collect();
+                    let class = class
+                        .strip_prefix('!')
+                        .map_or_else(|| class.clone(), |rest| format!("^{rest}"));
+                    regex.push('[');
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                    i += end + 1;
+                    continue;
+                }
+                regex.push_str(r"\[");
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    regex.push('$');
+
+    let regex = Regex::new(&regex)
+        .map_err(|e| Error::ConfigError(format!("Invalid ignore pattern '{pattern}': {e}")))?;
+
+    Ok(Some(IgnoreRule {
+        base: base.to_path_buf(),
+        regex,
+        negated,
+        dir_only,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_basename_and_anchored_patterns() {
+        let base = Path::new("/home/user");
+        let mut rules = IgnoreRules::new();
+        rules.per_directory = false;
+        rules
+            .add_patterns(base, "# comment\n*.bak\n/project/.env\nfixtures/\n")
+            .unwrap();
+
+        assert!(rules.is_ignored(Path::new("/home/user/deep/dir/config.bak")));
+        assert!(rules.is_ignored(Path::new("/home/user/project/.env")));
+        assert!(!rules.is_ignored(Path::new("/home/user/other/project/.env")));
+        assert!(rules.is_ignored(Path::new("/home/user/a/fixtures/.env")));
+        assert!(!rules.is_ignored(Path::new("/home/user/.env")));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/config.bak")));
+    }
+
+    #[test]
+    fn test_negation_and_double_star() {
+        let base = Path::new("/home/user");
+        let mut rules = IgnoreRules::new();
+        rules.per_directory = false;
+        rules
+            .add_patterns(base, "work/**/.env\n!work/keep/.env\n")
+            .unwrap();
+
+        assert!(rules.is_ignored(Path::new("/home/user/work/.env")));
+        assert!(rules.is_ignored(Path::new("/home/user/work/a/b/.env")));
+        assert!(!rules.is_ignored(Path::new("/home/user/work/keep/.env")));
+    }
+
+    #[test]
+    fn test_global_and_per_directory_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path();
+        let config_dir = AppDirs::for_home(home).config_dir().to_path_buf();
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join(GLOBAL_IGNORE_FILE), "scratch/\n").unwrap();
+
+        let project = home.join("project");
+        fs::create_dir_all(project.join("scratch")).unwrap();
+        fs::write(project.join(IGNORE_FILE_NAME), ".env.local\n").unwrap();
+
+        let rules = IgnoreRules::load(home);
+        assert_eq!(rules.len(), 1);
+        assert!(rules.is_ignored(&project.join("scratch/.env")));
+        assert!(rules.is_ignored(&project.join(".env.local")));
+        assert!(!rules.is_ignored(&project.join(".env")));
+        assert!(!rules.is_ignored(&home.join(".env.local")));
+    }
+}
@@ -5,7 +5,9 @@
 #![allow(clippy::module_name_repetitions)]
 //! `LangChain` scanner for discovering API keys in `LangChain` configuration files.
 
-use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerPlugin, ScannerPluginExt};
+use super::{
+    EnvVarDeclaration, LabelMapping, ScanResult, ScannerContext, ScannerPlugin, ScannerPluginExt,
+};
 use crate::error::{Error, Result};
 use crate::models::credentials::DiscoveredCredential;
 use crate::models::credentials::{Confidence, ValueType};
@@ -25,6 +27,10 @@ impl ScannerPlugin for LangChainScanner {
         "LangChain"
     }
 
+    fn description(&self) -> &'static str {
+        "LangChain application configs"
+    }
+
     fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
         vec![
             // Global config
@@ -54,7 +60,12 @@ impl ScannerPlugin for LangChainScanner {
             || path_str.contains("langchain")
     }
 
-    fn parse_config(&self, path: &Path, content: &str) -> Result<ScanResult> {
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<ScanResult> {
         let mut result = ScanResult::new();
 
         // Determine file type and parse accordingly
@@ -144,7 +155,7 @@ impl ScannerPlugin for LangChainScanner {
             }
         } else if file_name == ".env" || file_name.ends_with(".env") {
             // Parse environment file
-            let mut env_result = Self::parse_env_file(content);
+            let mut env_result = Self::parse_env_file(path, content);
 
             // Build provider instances from discovered keys
             if !env_result.keys.is_empty() {
@@ -156,7 +167,7 @@ impl ScannerPlugin for LangChainScanner {
                     Ok(provider_instances) => {
                         // Create a config instance for the .env file with the provider instances
                         let mut instance = ConfigInstance::new(
-                            Self::generate_instance_id(path),
+                            super::ids::config_instance_id("langchain", path),
                             "langchain".to_string(),
                             path.to_path_buf(),
                         );
@@ -330,8 +341,7 @@ impl LangChainScanner {
     fn is_valid_langchain_config_yaml(yaml_value: &serde_yaml::Value) -> bool {
         // Convert to JSON for consistent checking
         serde_json::to_value(yaml_value)
-            .map(|json_value| Self::is_valid_langchain_config(&json_value))
-            .unwrap_or(false)
+            .is_ok_and(|json_value| Self::is_valid_langchain_config(&json_value))
     }
 
     /// Create a config instance from `LangChain` configuration.
@@ -366,7 +376,7 @@ impl LangChainScanner {
         }
 
         let mut instance = ConfigInstance::new(
-            Self::generate_instance_id(path),
+            super::ids::config_instance_id("langchain", path),
             "langchain".to_string(),
             path.to_path_buf(),
         );
@@ -390,17 +400,6 @@ impl LangChainScanner {
         )
     }
 
-    /// Generate a unique instance ID.
-    fn generate_instance_id(path: &Path) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(path.to_string_lossy().as_bytes());
-        format!("langchain_{:x}", hasher.finalize())
-            .chars()
-            .take(16)
-            .collect()
-    }
-
     /// Check if a key is valid.
     fn is_valid_key(key: &str) -> bool {
         key.len() >= 15 && key.chars().any(char::is_alphanumeric)
@@ -434,7 +433,7 @@ impl LangChainScanner {
     }
 
     /// Parse .env file format.
-    fn parse_env_file(content: &str) -> ScanResult {
+    fn parse_env_file(path: &Path, content: &str) -> ScanResult {
         let mut result = ScanResult::new();
 
         // API key patterns
@@ -473,8 +472,12 @@ impl LangChainScanner {
             ("OPENROUTER_BASE_URL", "openrouter", "BaseUrl"),
         ];
 
+        let source = path.display().to_string();
         let keys =
-            super::extract_env_keys_with_metadata(content, &api_patterns, &metadata_patterns);
+            super::extract_env_keys_with_metadata(content, &api_patterns, &metadata_patterns)
+                .into_iter()
+                .map(|key| key.with_source_file(source.clone()))
+                .collect();
         result.add_keys(keys);
         result
     }
@@ -535,7 +538,7 @@ mod tests {
         }"#;
 
         let result = scanner
-            .parse_config(Path::new("config.json"), config)
+            .parse_config(Path::new("config.json"), config, &ScannerContext::default())
             .unwrap();
         assert_eq!(result.keys.len(), 2);
         assert_eq!(result.instances.len(), 1);
@@ -575,7 +578,7 @@ chain:
 "#;
 
         let result = scanner
-            .parse_config(Path::new("config.yaml"), config)
+            .parse_config(Path::new("config.yaml"), config, &ScannerContext::default())
             .unwrap();
         assert_eq!(result.keys.len(), 2);
         assert_eq!(result.instances.len(), 1);
@@ -1,4 +1,6 @@
 #![allow(clippy::too_many_lines)]
+// The built-in scanners still implement the deprecated `ScannerPlugin`
+#![allow(deprecated)]
 #![allow(clippy::match_wildcard_for_single_variants)]
 #![allow(clippy::match_same_arms)]
 #![allow(clippy::cast_precision_loss)]
@@ -20,8 +22,10 @@ pub const DEFAULT_MAX_FILE_SIZE: usize = 1024 * 1024;
 pub struct ScannerConfig {
     /// Maximum file size to scan in bytes.
     pub max_file_size: usize,
-    /// Whether to follow symbolic links.
+    /// Whether to follow symbolic links to directories during traversal.
     pub follow_symlinks: bool,
+    /// Maximum directory depth to descend below a scan path.
+    pub max_depth: usize,
     /// File extensions to include.
     pub include_extensions: Option<Vec<String>>,
     /// File extensions to exclude.
@@ -30,6 +34,8 @@ pub struct ScannerConfig {
     pub exclude_files: Option<Vec<String>>,
     /// Whether to scan hidden files/directories.
     pub scan_hidden: bool,
+    /// Ignore rules from `~/.config/aicred/ignore` and `.aicredignore` files.
+    pub ignore: IgnoreRules,
 }
 
 impl Default for ScannerConfig {
@@ -37,30 +43,119 @@ impl Default for ScannerConfig {
         Self {
             max_file_size: DEFAULT_MAX_FILE_SIZE,
             follow_symlinks: false,
+            max_depth: DEFAULT_MAX_DEPTH,
             include_extensions: None,
             exclude_extensions: Some(vec![".log".to_string(), ".tmp".to_string()]),
             exclude_files: Some(vec![".DS_Store".to_string(), "Thumbs.db".to_string()]),
             scan_hidden: true,
+            ignore: IgnoreRules::new(),
         }
     }
 }
 
+impl ScannerConfig {
+    /// Creates the default configuration with ignore files loaded from `home_dir`.
+    #[must_use]
+    pub fn for_home(home_dir: &Path) -> Self {
+        Self::default().with_ignore_rules(IgnoreRules::load(home_dir))
+    }
+
+    /// Sets the ignore rules.
+    #[must_use]
+    pub fn with_ignore_rules(mut self, ignore: IgnoreRules) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    /// Sets whether to follow symlinked directories.
+    #[must_use]
+    pub const fn with_follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Sets the maximum traversal depth.
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Checks whether a directory should be descended into during traversal.
+    #[must_use]
+    pub fn should_descend(&self, dir: &Path) -> bool {
+        let hidden = dir
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        (self.scan_hidden || !hidden) && !self.ignore.is_ignored(dir)
+    }
+
+    /// Checks whether a path should be read by scanners.
+    #[must_use]
+    pub fn should_scan(&self, path: &Path) -> bool {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()));
+
+        if !self.scan_hidden && file_name.starts_with('.') {
+            return false;
+        }
+        if let Some(excluded) = &self.exclude_files {
+            if excluded.iter().any(|name| *name == file_name) {
+                return false;
+            }
+        }
+        if let (Some(excluded), Some(ext)) = (&self.exclude_extensions, &extension) {
+            if excluded.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
+                return false;
+            }
+        }
+        if let Some(included) = &self.include_extensions {
+            if !extension
+                .as_ref()
+                .is_some_and(|ext| included.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+            {
+                return false;
+            }
+        }
+
+        !self.ignore.is_ignored(path)
+    }
+}
+
+pub mod app_info;
 mod claude_desktop;
+mod context;
 mod gsh;
+pub mod ids;
+mod ignore;
 mod langchain;
+pub mod patterns;
 mod ragit;
 mod roo_code;
+mod scanner;
+mod walk;
 
 pub use claude_desktop::ClaudeDesktopScanner;
+pub use context::ScannerContext;
 pub use gsh::GshScanner;
+pub use ignore::{IgnoreRules, GLOBAL_IGNORE_FILE, IGNORE_FILE_NAME};
 pub use langchain::LangChainScanner;
+pub use patterns::PatternSet;
 pub use ragit::RagitScanner;
 pub use roo_code::RooCodeScanner;
+pub use scanner::{
+    remediation_hint, Finding, FindingSource, LegacyScanner, ScanOutput, Scanner, Span,
+};
+pub use walk::{walk_files, DEFAULT_MAX_DEPTH};
 
 use crate::error::{Error, Result};
 use crate::models::credentials::{Confidence, DiscoveredCredential, ValueType};
-use crate::models::{ConfigInstance, ProviderInstance};
-use sha2::Digest;
+use crate::models::{ConfigInstance, Provenance, ProviderInstance};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -143,7 +238,15 @@ impl LabelMapping {
     }
 }
 
-/// Trait that all application scanner plugins must implement.
+/// Trait of the first version of the application scanner API.
+///
+/// Implement [`Scanner`] instead, which returns structured [`Finding`]s.
+/// Plugins implementing this trait still register through
+/// [`ScannerRegistry::register`], which runs them as a [`LegacyScanner`].
+#[deprecated(
+    since = "0.2.0",
+    note = "Implement `discovery::Scanner`; `ScannerRegistry::register` adapts old plugins"
+)]
 pub trait ScannerPlugin: Send + Sync {
     /// Returns the name of this scanner (e.g., "ragit", "claude-desktop").
     fn name(&self) -> &str;
@@ -151,13 +254,30 @@ pub trait ScannerPlugin: Send + Sync {
     /// Returns the application name (e.g., "Ragit", "Claude Desktop").
     fn app_name(&self) -> &str;
 
+    /// Returns a one-line description of what the scanner reads, for listings.
+    #[allow(clippy::unnecessary_literal_bound)] // Implementations may return their own data
+    fn description(&self) -> &str {
+        ""
+    }
+
     /// Returns the paths that this scanner should scan for configuration files.
+    ///
+    /// Directories are traversed recursively, bounded by `ScannerConfig::max_depth`.
     fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf>;
 
     /// Parses a configuration file and extracts discovered keys and config instances.
+    ///
+    /// `context` carries the settings configured for this scanner. The scan
+    /// already adds its custom variables and drops its disabled names, so most
+    /// scanners can ignore it.
     /// # Errors
     /// Returns an error if the configuration file cannot be parsed or is invalid.
-    fn parse_config(&self, path: &Path, content: &str) -> Result<ScanResult>;
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        context: &ScannerContext,
+    ) -> Result<ScanResult>;
 
     /// Validates that this scanner can handle the given file.
     fn can_handle_file(&self, path: &Path) -> bool;
@@ -186,7 +306,9 @@ pub trait ScannerPlugin: Send + Sync {
         for path in app_paths {
             if path.exists() {
                 if let Ok(content) = std::fs::read_to_string(&path) {
-                    if let Ok(result) = self.parse_config(&path, &content) {
+                    if let Ok(result) =
+                        self.parse_config(&path, &content, &ScannerContext::default())
+                    {
                         if !result.keys.is_empty() || !result.instances.is_empty() {
                             instances.extend(result.instances);
                         }
@@ -248,7 +370,7 @@ impl Default for ScanResult {
 /// Registry for managing scanner plugins.
 #[derive(Clone)]
 pub struct ScannerRegistry {
-    scanners: std::sync::Arc<std::sync::RwLock<HashMap<String, std::sync::Arc<dyn ScannerPlugin>>>>,
+    scanners: std::sync::Arc<std::sync::RwLock<HashMap<String, std::sync::Arc<dyn Scanner>>>>,
 }
 
 impl std::fmt::Debug for ScannerRegistry {
@@ -271,19 +393,24 @@ impl ScannerRegistry {
         }
     }
 
-    /// Registers a new scanner.
+    /// Registers a plugin written against the deprecated [`ScannerPlugin`] trait.
     /// # Errors
     /// Returns an error if the scanner cannot be registered (e.g., already exists).
     pub fn register(&self, scanner: std::sync::Arc<dyn ScannerPlugin>) -> Result<()> {
+        self.register_scanner(std::sync::Arc::new(LegacyScanner(scanner)))
+    }
+
+    /// Registers a new scanner.
+    /// # Errors
+    /// Returns an error if the scanner cannot be registered (e.g., already exists).
+    pub fn register_scanner(&self, scanner: std::sync::Arc<dyn Scanner>) -> Result<()> {
         let mut scanners = self.scanners.write().map_err(|_| {
             Error::PluginError("Failed to acquire write lock on scanners".to_string())
         })?;
 
         let name = scanner.name().to_string();
         if scanners.contains_key(&name) {
-            return Err(Error::PluginError(format!(
-                "Scanner '{name}' is already registered"
-            )));
+            return Err(Error::PluginConflict(name));
         }
 
         scanners.insert(name, scanner);
@@ -293,7 +420,7 @@ impl ScannerRegistry {
 
     /// Gets a scanner by name.
     #[must_use]
-    pub fn get(&self, name: &str) -> Option<std::sync::Arc<dyn ScannerPlugin>> {
+    pub fn get(&self, name: &str) -> Option<std::sync::Arc<dyn Scanner>> {
         self.scanners
             .read()
             .ok()
@@ -312,7 +439,7 @@ impl ScannerRegistry {
 
     /// Gets all scanners that can handle a specific file.
     #[must_use]
-    pub fn get_scanners_for_file(&self, path: &Path) -> Vec<std::sync::Arc<dyn ScannerPlugin>> {
+    pub fn get_scanners_for_file(&self, path: &Path) -> Vec<std::sync::Arc<dyn Scanner>> {
         self.scanners
             .read()
             .ok()
@@ -382,6 +509,73 @@ pub fn find_existing_configs(home_dir: &Path, relative_paths: &[&str]) -> Vec<Pa
         .collect()
 }
 
+/// Maximum length of a context snippet in characters.
+pub const MAX_SNIPPET_LEN: usize = 200;
+
+/// Finds the 1-based line and column of the first occurrence of `value` in `content`.
+#[must_use]
+pub fn locate_value(content: &str, value: &str) -> Option<(usize, u32)> {
+    if value.is_empty() {
+        return None;
+    }
+    content.lines().enumerate().find_map(|(index, line)| {
+        line.find(value).and_then(|byte_offset| {
+            let column = u32::try_from(line[..byte_offset].chars().count() + 1).ok()?;
+            Some((index + 1, column))
+        })
+    })
+}
+
+/// Builds the context snippet for a credential: its source line, trimmed, with the
+/// value redacted unless it is a non-sensitive type such as a model ID or base URL.
+#[must_use]
+pub fn context_snippet(line: &str, key: &DiscoveredCredential) -> String {
+    let line = line.trim();
+    let snippet = match (key.full_value(), &key.value_type) {
+        (_, ValueType::ModelId | ValueType::BaseUrl | ValueType::Temperature) | (None, _) => {
+            line.to_string()
+        }
+        (Some(value), _) => line.replace(value, &key.redacted_value()),
+    };
+    if snippet.chars().count() > MAX_SNIPPET_LEN {
+        let truncated: String = snippet.chars().take(MAX_SNIPPET_LEN).collect();
+        format!("{truncated}...")
+    } else {
+        snippet
+    }
+}
+
+/// Fills in missing line/column positions and context snippets for keys parsed
+/// from `content`.
+///
+/// `line_offset` is the number of lines that precede `content` in the source file,
+/// for content read in chunks. Keys whose value cannot be found are left unchanged.
+pub fn annotate_positions(keys: &mut [DiscoveredCredential], content: &str, line_offset: usize) {
+    for key in keys {
+        let Some(value) = key.full_value().map(str::to_string) else {
+            continue;
+        };
+
+        if key.source_line.is_none() {
+            if let Some((line, column)) = locate_value(content, &value) {
+                key.source_line = Some(line + line_offset);
+                key.column_number = Some(column);
+            }
+        }
+
+        if key.context_snippet.is_none() {
+            let line = key
+                .source_line
+                .and_then(|line| line.checked_sub(line_offset + 1))
+                .and_then(|index| content.lines().nth(index))
+                .filter(|line| line.contains(value.as_str()));
+            if let Some(line) = line {
+                key.context_snippet = Some(context_snippet(line, key));
+            }
+        }
+    }
+}
+
 /// Helper function to extract keys from environment variable format.
 /// # Errors
 /// Returns an error if regex pattern compilation fails.
@@ -392,27 +586,25 @@ pub fn find_existing_configs(home_dir: &Path, relative_paths: &[&str]) -> Vec<Pa
 pub fn extract_env_keys(content: &str, patterns: &[(&str, &str)]) -> Vec<DiscoveredCredential> {
     let mut keys = Vec::new();
 
-    for (env_var, provider) in patterns {
-        let pattern = format!(
-            r"(?i){}\s*=\s*([a-zA-Z0-9_-]{{15,}})",
-            regex::escape(env_var)
-        );
-        let regex = regex::Regex::new(&pattern).unwrap();
+    let compiled: Vec<String> = patterns
+        .iter()
+        .map(|(env_var, _)| patterns::env_key_pattern(env_var))
+        .collect();
+    let set = PatternSet::cached(&compiled);
 
-        for cap in regex.captures_iter(content) {
-            if let Some(key_match) = cap.get(1) {
-                let key_value = key_match.as_str();
+    for (index, cap) in set.captures_iter(content) {
+        if let Some(key_match) = cap.get(1) {
+            let key_value = key_match.as_str();
 
-                let discovered_key = DiscoveredCredential::new(
-                    (*provider).to_string(),
-                    "env_file".to_string(),
-                    ValueType::ApiKey,
-                    Confidence::High,
-                    key_value.to_string(),
-                );
+            let discovered_key = DiscoveredCredential::new(
+                patterns[index].1.to_string(),
+                "env_file".to_string(),
+                ValueType::ApiKey,
+                Confidence::High,
+                key_value.to_string(),
+            );
 
-                keys.push(discovered_key);
-            }
+            keys.push(discovered_key);
         }
     }
 
@@ -435,62 +627,66 @@ pub fn extract_env_keys_with_metadata(
     let mut keys = Vec::new();
 
     // First, extract API keys
-    for (env_var, provider) in api_patterns {
-        let pattern = format!(r"(?i){}\s*=\s*(.+)", regex::escape(env_var));
-        let regex = regex::Regex::new(&pattern).unwrap();
-
-        for cap in regex.captures_iter(content) {
-            if let Some(key_match) = cap.get(1) {
-                let key_value = key_match.as_str().trim_matches('"').trim();
-
-                // Only add if it's a reasonable API key length
-                if key_value.len() >= 8
-                    && key_value
-                        .chars()
-                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-                {
-                    let discovered_key = DiscoveredCredential::new(
-                        (*provider).to_string(),
-                        "env_file".to_string(),
-                        ValueType::ApiKey,
-                        Confidence::High,
-                        key_value.to_string(),
-                    );
-
-                    keys.push(discovered_key);
-                }
+    let compiled: Vec<String> = api_patterns
+        .iter()
+        .map(|(env_var, _)| patterns::env_value_pattern(env_var))
+        .collect();
+    let set = PatternSet::cached(&compiled);
+
+    for (index, cap) in set.captures_iter(content) {
+        let provider = api_patterns[index].1;
+        if let Some(key_match) = cap.get(1) {
+            let key_value = key_match.as_str().trim_matches('"').trim();
+
+            // Only add if it's a reasonable API key length
+            if key_value.len() >= 8
+                && key_value
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            {
+                let discovered_key = DiscoveredCredential::new(
+                    provider.to_string(),
+                    "env_file".to_string(),
+                    ValueType::ApiKey,
+                    Confidence::High,
+                    key_value.to_string(),
+                );
+
+                keys.push(discovered_key);
             }
         }
     }
 
     // Then, extract metadata
-    for (env_var, provider, custom_type) in metadata_patterns {
-        let pattern = format!(r"(?i){}\s*=\s*(.+)", regex::escape(env_var));
-        let regex = regex::Regex::new(&pattern).unwrap();
-
-        for cap in regex.captures_iter(content) {
-            if let Some(value_match) = cap.get(1) {
-                let value = value_match.as_str().trim_matches('"').trim();
-
-                if !value.is_empty() {
-                    // Map special custom types to their proper ValueType variants
-                    let value_type = match *custom_type {
-                        "ModelId" => ValueType::ModelId,
-                        "BaseUrl" => ValueType::BaseUrl,
-                        "Temperature" => ValueType::Temperature,
-                        _ => ValueType::Custom((*custom_type).to_string()),
-                    };
-
-                    let discovered_key = DiscoveredCredential::new(
-                        (*provider).to_string(),
-                        "env_file".to_string(),
-                        value_type,
-                        Confidence::High,
-                        value.to_string(),
-                    );
-
-                    keys.push(discovered_key);
-                }
+    let compiled: Vec<String> = metadata_patterns
+        .iter()
+        .map(|(env_var, _, _)| patterns::env_value_pattern(env_var))
+        .collect();
+    let set = PatternSet::cached(&compiled);
+
+    for (index, cap) in set.captures_iter(content) {
+        let (_, provider, custom_type) = metadata_patterns[index];
+        if let Some(value_match) = cap.get(1) {
+            let value = value_match.as_str().trim_matches('"').trim();
+
+            if !value.is_empty() {
+                // Map special custom types to their proper ValueType variants
+                let value_type = match custom_type {
+                    "ModelId" => ValueType::ModelId,
+                    "BaseUrl" => ValueType::BaseUrl,
+                    "Temperature" => ValueType::Temperature,
+                    _ => ValueType::Custom(custom_type.to_string()),
+                };
+
+                let discovered_key = DiscoveredCredential::new(
+                    provider.to_string(),
+                    "env_file".to_string(),
+                    value_type,
+                    Confidence::High,
+                    value.to_string(),
+                );
+
+                keys.push(discovered_key);
             }
         }
     }
@@ -665,13 +861,8 @@ pub trait ScannerPluginExt: ScannerPlugin {
                 default_url
             });
 
-            // Create instance ID using SHA-256 hash for consistency
-            let instance_id_source = format!("{provider_name}:{source_path}");
-            let mut hasher = sha2::Sha256::new();
-            hasher.update(instance_id_source.as_bytes());
-            let hash_result = hasher.finalize();
-            let full_hash = format!("{hash_result:x}");
-            let instance_id = full_hash[..4].to_string();
+            // Named after the first key, so `scan --update` stores it under the same ID
+            let instance_id = ids::key_instance_id(api_keys[0].0);
 
             // Create the provider instance
             let mut instance = ProviderInstance::new_without_models(
@@ -684,6 +875,10 @@ pub trait ScannerPluginExt: ScannerPlugin {
             // Set the API key from the first discovered key
             if let Some((discovered_key, key_value)) = api_keys.first() {
                 instance.set_api_key(key_value.clone());
+                let mut provenance =
+                    Provenance::from_key(discovered_key, discovered_key.discovered_at);
+                provenance.scanner = Some(self.name().to_string());
+                instance.provenance = Some(provenance);
                 tracing::debug!(
                     "Set API key for instance '{}' (confidence: {})",
                     instance_id,
@@ -860,7 +1055,12 @@ mod tests {
             vec![]
         }
 
-        fn parse_config(&self, _path: &Path, _content: &str) -> Result<ScanResult> {
+        fn parse_config(
+            &self,
+            _path: &Path,
+            _content: &str,
+            _context: &ScannerContext,
+        ) -> Result<ScanResult> {
             Ok(ScanResult::new())
         }
 
@@ -869,6 +1069,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_builtin_scanners_describe_themselves() {
+        let registry = ScannerRegistry::new();
+        register_builtin_scanners(&registry).unwrap();
+        for name in registry.list() {
+            let scanner = registry.get(&name).unwrap();
+            assert!(
+                !scanner.description().is_empty(),
+                "{name} has no description"
+            );
+        }
+    }
+
     #[test]
     fn test_group_keys_by_provider() {
         let scanner = MockScanner;
@@ -1197,4 +1410,62 @@ mod tests {
         // Line numbers from DiscoveredCredential are not automatically stored in instance metadata
         // unless the instance goes through ProviderConfig conversion
     }
+
+    #[test]
+    fn test_locate_value_reports_line_and_column() {
+        let content = "# comment\nexport OPENAI_API_KEY=\"sk-abcdefghijklmnop\"\n";
+        assert_eq!(locate_value(content, "sk-abcdefghijklmnop"), Some((2, 24)));
+        assert_eq!(locate_value(content, "missing"), None);
+        assert_eq!(locate_value(content, ""), None);
+    }
+
+    #[test]
+    fn test_annotate_positions_redacts_snippets() {
+        let content = "model: gpt-4\napi_key: sk-abcdefghijklmnop\n";
+        let mut keys = vec![
+            DiscoveredCredential::new(
+                "openai".to_string(),
+                "/test/config.yaml".to_string(),
+                ValueType::ApiKey,
+                Confidence::High,
+                "sk-abcdefghijklmnop".to_string(),
+            ),
+            DiscoveredCredential::new(
+                "openai".to_string(),
+                "/test/config.yaml".to_string(),
+                ValueType::ModelId,
+                Confidence::High,
+                "gpt-4".to_string(),
+            ),
+        ];
+
+        annotate_positions(&mut keys, content, 10);
+
+        assert_eq!(keys[0].source_line, Some(12));
+        assert_eq!(keys[0].column_number, Some(10));
+        let snippet = keys[0].context_snippet.as_deref().unwrap();
+        assert!(snippet.starts_with("api_key: "));
+        assert!(!snippet.contains("sk-abcdefghijklmnop"));
+
+        assert_eq!(keys[1].source_line, Some(11));
+        assert_eq!(keys[1].context_snippet.as_deref(), Some("model: gpt-4"));
+    }
+
+    #[test]
+    fn test_annotate_positions_keeps_existing_position() {
+        let content = "api_key: sk-abcdefghijklmnop\n";
+        let mut keys = vec![DiscoveredCredential::new(
+            "openai".to_string(),
+            "/test/config.yaml".to_string(),
+            ValueType::ApiKey,
+            Confidence::High,
+            "sk-abcdefghijklmnop".to_string(),
+        )
+        .with_position(1, 3)];
+
+        annotate_positions(&mut keys, content, 0);
+
+        assert_eq!(keys[0].column_number, Some(3));
+        assert!(keys[0].context_snippet.is_some());
+    }
 }
@@ -0,0 +1,143 @@
+//! Precompiled, cached pattern sets shared by scanners.
+//!
+//! Compiling a regex per environment variable per file dominates scan time on
+//! large env files. Pattern lists are compiled once into a [`RegexSet`] plus the
+//! individual regexes needed for captures, cached for the life of the process,
+//! and only patterns the set reports as matching are run for captures.
+
+use regex::{Captures, Regex, RegexSet};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// A compiled list of patterns with a combined prefilter.
+#[derive(Debug)]
+pub struct PatternSet {
+    set: RegexSet,
+    regexes: Vec<Regex>,
+}
+
+impl PatternSet {
+    /// Gets the cached pattern set for these patterns, compiling it on first use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any pattern is not a valid regex.
+    #[must_use]
+    pub fn cached(patterns: &[String]) -> Arc<Self> {
+        static CACHE: OnceLock<Mutex<HashMap<Vec<String>, Arc<PatternSet>>>> = OnceLock::new();
+
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut cache = cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(set) = cache.get(patterns) {
+            return Arc::clone(set);
+        }
+
+        let set = Arc::new(Self::new(patterns).expect("scanner patterns must be valid regexes"));
+        cache.insert(patterns.to_vec(), Arc::clone(&set));
+        set
+    }
+
+    /// Compiles a pattern set without caching it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any pattern is not a valid regex.
+    pub fn new(patterns: &[String]) -> Result<Self, regex::Error> {
+        Ok(Self {
+            set: RegexSet::new(patterns)?,
+            regexes: patterns
+                .iter()
+                .map(|p| Regex::new(p))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Gets the number of patterns in the set.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.regexes.len()
+    }
+
+    /// Checks if the set has no patterns.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.regexes.is_empty()
+    }
+
+    /// Iterates over `(pattern index, captures)` for every match in `content`,
+    /// in pattern order.
+    pub fn captures_iter<'a>(
+        &'a self,
+        content: &'a str,
+    ) -> impl Iterator<Item = (usize, Captures<'a>)> + 'a {
+        self.set
+            .matches(content)
+            .into_iter()
+            .flat_map(move |index| {
+                self.regexes[index]
+                    .captures_iter(content)
+                    .map(move |cap| (index, cap))
+            })
+    }
+}
+
+/// Builds the pattern matching `ENV_VAR=<key>` where the key is at least 15
+/// token characters.
+#[must_use]
+pub fn env_key_pattern(env_var: &str) -> String {
+    format!(
+        r"(?i){}\s*=\s*([a-zA-Z0-9_-]{{15,}})",
+        regex::escape(env_var)
+    )
+}
+
+/// Builds the pattern matching `ENV_VAR=<value>` for any non-empty value.
+#[must_use]
+pub fn env_value_pattern(env_var: &str) -> String {
+    format!(r"(?i){}\s*=\s*(.+)", regex::escape(env_var))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_sets_are_shared() {
+        let patterns = vec![env_key_pattern("CACHE_TEST_KEY")];
+        let first = PatternSet::cached(&patterns);
+        let second = PatternSet::cached(&patterns);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.len(), 1);
+    }
+
+    #[test]
+    fn test_captures_only_run_for_matching_patterns() {
+        let patterns = vec![
+            env_key_pattern("OPENAI_API_KEY"),
+            env_key_pattern("GROQ_API_KEY"),
+            env_value_pattern("OPENAI_BASE_URL"),
+        ];
+        let set = PatternSet::new(&patterns).unwrap();
+        let content =
+            "OPENAI_API_KEY=sk-abcdefghijklmnop\nOPENAI_BASE_URL=https://example.com\nOTHER=1\n";
+
+        let matches: Vec<(usize, String)> = set
+            .captures_iter(content)
+            .map(|(index, cap)| (index, cap[1].to_string()))
+            .collect();
+        assert_eq!(
+            matches,
+            vec![
+                (0, "sk-abcdefghijklmnop".to_string()),
+                (2, "https://example.com".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        assert!(PatternSet::new(&["(unclosed".to_string()]).is_err());
+    }
+}
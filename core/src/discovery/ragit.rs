@@ -1,6 +1,6 @@
 //! Ragit scanner for discovering API keys in Ragit configuration files.
 
-use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerPlugin};
+use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerContext, ScannerPlugin};
 use crate::error::Result;
 use crate::models::credentials::DiscoveredCredential;
 use crate::models::credentials::{Confidence, ValueType};
@@ -20,6 +20,10 @@ impl ScannerPlugin for RagitScanner {
         "Ragit"
     }
 
+    fn description(&self) -> &'static str {
+        "Ragit configurations"
+    }
+
     fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
         vec![
             // Global config
@@ -37,7 +41,12 @@ impl ScannerPlugin for RagitScanner {
                 || path.parent().is_some_and(|p| p.ends_with(".ragit")))
     }
 
-    fn parse_config(&self, path: &Path, content: &str) -> Result<ScanResult> {
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<ScanResult> {
         let mut result = ScanResult::new();
 
         // Try to parse as JSON first
@@ -45,7 +54,7 @@ impl ScannerPlugin for RagitScanner {
             // If JSON parsing fails, try to extract from .env format
             if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
                 if filename == ".env" || filename == ".env.local" {
-                    return Ok(Self::parse_env_file(content));
+                    return Ok(Self::parse_env_file(path, content));
                 }
             }
             return Ok(result);
@@ -206,7 +215,7 @@ impl RagitScanner {
         }
 
         let mut instance = ConfigInstance::new(
-            Self::generate_instance_id(path),
+            super::ids::config_instance_id("ragit", path),
             "ragit".to_string(),
             path.to_path_buf(),
         );
@@ -214,17 +223,6 @@ impl RagitScanner {
         instance
     }
 
-    /// Generate a unique instance ID.
-    fn generate_instance_id(path: &Path) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(path.to_string_lossy().as_bytes());
-        format!("ragit_{:x}", hasher.finalize())
-            .chars()
-            .take(16)
-            .collect()
-    }
-
     /// Check if a key is valid.
     fn is_valid_key(key: &str) -> bool {
         key.len() >= 15 && key.chars().any(char::is_alphanumeric)
@@ -258,7 +256,7 @@ impl RagitScanner {
     }
 
     /// Parse .env file format.
-    fn parse_env_file(content: &str) -> ScanResult {
+    fn parse_env_file(path: &Path, content: &str) -> ScanResult {
         let mut result = ScanResult::new();
 
         // API key patterns
@@ -284,8 +282,12 @@ impl RagitScanner {
             ("HUGGINGFACE_TEMPERATURE", "huggingface", "Temperature"),
         ];
 
+        let source = path.display().to_string();
         let keys =
-            super::extract_env_keys_with_metadata(content, &api_patterns, &metadata_patterns);
+            super::extract_env_keys_with_metadata(content, &api_patterns, &metadata_patterns)
+                .into_iter()
+                .map(|key| key.with_source_file(source.clone()))
+                .collect();
         result.add_keys(keys);
         result
     }
@@ -385,7 +387,7 @@ mod tests {
         }"#;
 
         let result = scanner
-            .parse_config(Path::new("test.json"), config)
+            .parse_config(Path::new("test.json"), config, &ScannerContext::default())
             .unwrap();
         assert_eq!(result.keys.len(), 2);
         assert_eq!(result.instances.len(), 1);
@@ -406,7 +408,7 @@ RAGIT_API_KEY=sk-test-FAKE-12345-ragit
 OPENAI_API_KEY=sk-test-FAKE-12345-openai
 ";
 
-        let result = RagitScanner::parse_env_file(env_content);
+        let result = RagitScanner::parse_env_file(Path::new(".env"), env_content);
         assert_eq!(result.keys.len(), 2);
     }
 
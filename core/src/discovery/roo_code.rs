@@ -5,7 +5,9 @@
 #![allow(clippy::module_name_repetitions)]
 //! Roo Code scanner for discovering API keys in `VSCode` extension configurations.
 
-use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerPlugin, ScannerPluginExt};
+use super::{
+    EnvVarDeclaration, LabelMapping, ScanResult, ScannerContext, ScannerPlugin, ScannerPluginExt,
+};
 use crate::error::Result;
 use crate::models::credentials::DiscoveredCredential;
 use crate::models::credentials::{Confidence, ValueType};
@@ -25,41 +27,29 @@ impl ScannerPlugin for RooCodeScanner {
         "Roo Code"
     }
 
+    fn description(&self) -> &'static str {
+        "Roo Code VSCode extension"
+    }
+
     fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
         let mut paths = Vec::new();
 
-        // VSCode globalStorage directories (where Roo Code actually stores configs)
-        #[cfg(target_os = "macos")]
-        {
-            // macOS: ~/Library/Application Support/Code/User/globalStorage/rooveterinaryinc.roo-cline
-            if let Some(app_support) = dirs_next::data_dir() {
-                let app_support_path = app_support.join("Code").join("User").join("globalStorage");
-                paths.push(app_support_path.join("rooveterinaryinc.roo-cline"));
-                paths.push(
-                    app_support_path
-                        .join("rooveterinaryinc.roo-cline")
-                        .join("tasks"),
-                );
-            }
-
-            // Also check for VSCode Insiders
-            if let Some(app_support) = dirs_next::data_dir() {
-                let app_support_path = app_support
-                    .join("Code - Insiders")
-                    .join("User")
-                    .join("globalStorage");
-                paths.push(app_support_path.join("rooveterinaryinc.roo-cline"));
-                paths.push(
-                    app_support_path
-                        .join("rooveterinaryinc.roo-cline")
-                        .join("tasks"),
-                );
-            }
+        // VSCode globalStorage directories (where Roo Code actually stores configs):
+        // ~/.config/Code, ~/Library/Application Support/Code or %APPDATA%\Code
+        let settings_dir = crate::paths::app_settings_dir(home_dir);
+        for editor in ["Code", "Code - Insiders"] {
+            let storage = settings_dir
+                .join(editor)
+                .join("User")
+                .join("globalStorage")
+                .join("rooveterinaryinc.roo-cline");
+            paths.push(storage.join("tasks"));
+            paths.push(storage);
         }
 
         #[cfg(target_os = "linux")]
         {
-            // Linux: ~/.vscode-server/data/User/globalStorage/rooveterinaryinc.roo-cline
+            // VS Code Remote: ~/.vscode-server/data/User/globalStorage/rooveterinaryinc.roo-cline
             paths.push(
                 home_dir
                     .join(".vscode-server")
@@ -79,44 +69,6 @@ impl ScannerPlugin for RooCodeScanner {
             );
         }
 
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(app_data) = std::env::var_os("APPDATA") {
-                let app_data_path = PathBuf::from(app_data);
-                // Windows: %APPDATA%\Code\User\globalStorage\rooveterinaryinc.roo-cline
-                paths.push(
-                    app_data_path
-                        .join("Code")
-                        .join("User")
-                        .join("globalStorage")
-                        .join("rooveterinaryinc.roo-cline"),
-                );
-                paths.push(
-                    app_data_path
-                        .join("Code")
-                        .join("User")
-                        .join("globalStorage")
-                        .join("rooveterinaryinc.roo-cline")
-                        .join("tasks"),
-                );
-                paths.push(
-                    app_data_path
-                        .join("Code - Insiders")
-                        .join("User")
-                        .join("globalStorage")
-                        .join("rooveterinaryinc.roo-cline"),
-                );
-                paths.push(
-                    app_data_path
-                        .join("Code - Insiders")
-                        .join("User")
-                        .join("globalStorage")
-                        .join("rooveterinaryinc.roo-cline")
-                        .join("tasks"),
-                );
-            }
-        }
-
         // VSCode extensions (available on all platforms)
         paths.push(
             home_dir
@@ -217,7 +169,12 @@ impl ScannerPlugin for RooCodeScanner {
         false
     }
 
-    fn parse_config(&self, path: &Path, content: &str) -> Result<ScanResult> {
+    fn parse_config(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<ScanResult> {
         self.parse_config_with_registry(path, content, None)
     }
 
@@ -280,7 +237,7 @@ impl RooCodeScanner {
                     .and_then(|name| name.to_str())
                     .unwrap_or_default();
                 if file_name.starts_with(".env") {
-                    return Ok(Self::parse_env_file(content));
+                    return Ok(Self::parse_env_file(path, content));
                 }
                 return Ok(result);
             }
@@ -647,7 +604,7 @@ impl RooCodeScanner {
         }
 
         let mut instance = ConfigInstance::new(
-            Self::generate_instance_id(extension_path),
+            super::ids::config_instance_id("roo", extension_path),
             "roo-code".to_string(),
             extension_path.to_path_buf(),
         );
@@ -675,7 +632,7 @@ impl RooCodeScanner {
         }
 
         let mut instance = ConfigInstance::new(
-            Self::generate_instance_id(path),
+            super::ids::config_instance_id("roo", path),
             "roo-code".to_string(),
             path.to_path_buf(),
         );
@@ -683,17 +640,6 @@ impl RooCodeScanner {
         instance
     }
 
-    /// Generate a unique instance ID.
-    fn generate_instance_id(path: &Path) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(path.to_string_lossy().as_bytes());
-        format!("roo_{:x}", hasher.finalize())
-            .chars()
-            .take(16)
-            .collect()
-    }
-
     /// Check if a key is valid.
     fn is_valid_key(key: &str) -> bool {
         key.len() >= 15 && key.chars().any(char::is_alphanumeric)
@@ -727,7 +673,7 @@ impl RooCodeScanner {
     }
 
     /// Parse .env file format.
-    fn parse_env_file(content: &str) -> ScanResult {
+    fn parse_env_file(path: &Path, content: &str) -> ScanResult {
         let mut result = ScanResult::new();
         let env_patterns = [
             ("ROO_CODE_API_KEY", "roo-code"),
@@ -735,7 +681,11 @@ impl RooCodeScanner {
             ("ANTHROPIC_API_KEY", "anthropic"),
         ];
 
-        let keys = super::extract_env_keys(content, &env_patterns);
+        let source = path.display().to_string();
+        let keys = super::extract_env_keys(content, &env_patterns)
+            .into_iter()
+            .map(|key| key.with_source_file(source.clone()))
+            .collect();
         result.add_keys(keys);
         result
     }
@@ -767,6 +717,22 @@ mod tests {
         assert!(normalized_paths
             .iter()
             .any(|p| p.contains(".vscode/extensions")));
+
+        #[cfg(windows)]
+        let settings_dir = home_dir.join("AppData").join("Roaming");
+        #[cfg(target_os = "macos")]
+        let settings_dir = home_dir.join("Library").join("Application Support");
+        #[cfg(all(unix, not(target_os = "macos")))]
+        let settings_dir = home_dir.join(".config");
+        for editor in ["Code", "Code - Insiders"] {
+            assert!(paths.contains(
+                &settings_dir
+                    .join(editor)
+                    .join("User")
+                    .join("globalStorage")
+                    .join("rooveterinaryinc.roo-cline")
+            ));
+        }
     }
 
     #[test]
@@ -796,7 +762,11 @@ mod tests {
         }"#;
 
         let result = scanner
-            .parse_config(Path::new("settings.json"), config)
+            .parse_config(
+                Path::new("settings.json"),
+                config,
+                &ScannerContext::default(),
+            )
             .unwrap();
         // Key extraction requires "roo", "api", and "key" in the property name
         assert!(!result.keys.is_empty()); // Should find the API key
@@ -864,7 +834,11 @@ mod tests {
         let scanner = RooCodeScanner;
         let content = "{ this is not valid json";
         let result = scanner
-            .parse_config(Path::new("package.json"), content)
+            .parse_config(
+                Path::new("package.json"),
+                content,
+                &ScannerContext::default(),
+            )
             .unwrap();
         assert_eq!(result.keys.len(), 0);
         assert_eq!(result.instances.len(), 0);
@@ -0,0 +1,329 @@
+//! Version 2 of the application scanner API.
+//!
+//! [`Scanner`] replaces [`ScannerPlugin`]: instead of bare keys a scanner
+//! returns [`Finding`]s carrying where in the file the key sits, which file and
+//! scanner it came from and how to fix it. Plugins written against the old
+//! trait keep working through [`LegacyScanner`], which
+//! [`ScannerRegistry::register`](super::ScannerRegistry::register) applies to
+//! them.
+#![allow(deprecated)]
+
+use super::{EnvVarDeclaration, LabelMapping, ScanResult, ScannerContext, ScannerPlugin};
+use crate::error::Result;
+use crate::models::credentials::ValueType;
+use crate::models::{ConfigInstance, DiscoveredCredential};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Where a key sits within its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column of the first character, starting at 1.
+    pub column: u32,
+    /// Length of the value in bytes.
+    pub len: usize,
+}
+
+/// File and scanner a finding came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FindingSource {
+    /// File holding the key.
+    pub path: PathBuf,
+    /// Name of the scanner that found it, e.g. `claude-desktop`.
+    pub scanner: String,
+    /// Application the file belongs to, e.g. `Claude Desktop`.
+    pub app_name: String,
+    /// When the file was last modified, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl FindingSource {
+    /// Creates the source of the findings of a scanner in a file.
+    #[must_use]
+    pub fn new(path: &Path, scanner: &(impl Scanner + ?Sized)) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            scanner: scanner.name().to_string(),
+            app_name: scanner.app_name().to_string(),
+            modified_at: std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::<Utc>::from),
+        }
+    }
+}
+
+/// A key found by a scanner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// The discovered key.
+    pub key: DiscoveredCredential,
+    /// Where the key sits within the file, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// File and scanner the key came from.
+    pub source: FindingSource,
+    /// How to fix the finding, if anything needs fixing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl Finding {
+    /// Creates a finding, taking the span from the key's line and column and
+    /// the default remediation hint for its value type.
+    #[must_use]
+    pub fn new(key: DiscoveredCredential, source: FindingSource) -> Self {
+        let span = key.source_line.map(|line| Span {
+            line,
+            column: key.column_number.unwrap_or(1),
+            len: key.full_value().map_or(0, str::len),
+        });
+        let remediation = remediation_hint(&key);
+        Self {
+            key,
+            span,
+            source,
+            remediation,
+        }
+    }
+
+    /// Sets where the key sits within the file.
+    #[must_use]
+    pub const fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Sets how to fix the finding.
+    #[must_use]
+    pub fn with_remediation(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+
+    /// Converts the finding into its key, recording the span on the key.
+    #[must_use]
+    pub fn into_key(self) -> DiscoveredCredential {
+        let mut key = self.key;
+        if let Some(span) = self.span {
+            key.source_line = Some(span.line);
+            key.column_number = Some(span.column);
+        }
+        key
+    }
+}
+
+/// Default remediation hint for a key, `None` for values that aren't secret.
+#[must_use]
+pub fn remediation_hint(key: &DiscoveredCredential) -> Option<String> {
+    match key.value_type {
+        ValueType::ApiKey | ValueType::AccessToken | ValueType::SecretKey | ValueType::BearerToken => {
+            Some(format!(
+                "Rotate this {} key if the file was shared, and move it to a secret store with `aicred cleanup`",
+                key.provider
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Findings and config instances of one file.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutput {
+    /// Keys found in the file.
+    pub findings: Vec<Finding>,
+    /// Configuration instances found in the file.
+    pub instances: Vec<ConfigInstance>,
+}
+
+impl ScanOutput {
+    /// Wraps the keys of a version 1 scan result into findings.
+    #[must_use]
+    pub fn from_scan_result(result: ScanResult, source: &FindingSource) -> Self {
+        Self {
+            findings: result
+                .keys
+                .into_iter()
+                .map(|key| Finding::new(key, source.clone()))
+                .collect(),
+            instances: result.instances,
+        }
+    }
+}
+
+impl From<ScanOutput> for ScanResult {
+    fn from(output: ScanOutput) -> Self {
+        let mut result = Self::new();
+        result.add_keys(output.findings.into_iter().map(Finding::into_key).collect());
+        result.add_instances(output.instances);
+        result
+    }
+}
+
+/// An application scanner.
+pub trait Scanner: Send + Sync {
+    /// Returns the name of this scanner (e.g., "ragit", "claude-desktop").
+    fn name(&self) -> &str;
+
+    /// Returns the application name (e.g., "Ragit", "Claude Desktop").
+    fn app_name(&self) -> &str;
+
+    /// Returns a one-line description of what the scanner reads, for listings.
+    #[allow(clippy::unnecessary_literal_bound)] // Implementations may return their own data
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Returns the paths that this scanner should scan for configuration files.
+    ///
+    /// Directories are traversed recursively, bounded by `ScannerConfig::max_depth`.
+    fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf>;
+
+    /// Validates that this scanner can handle the given file.
+    fn can_handle_file(&self, path: &Path) -> bool;
+
+    /// Scans the content of a configuration file for findings and config instances.
+    /// # Errors
+    /// Returns an error if the configuration file cannot be parsed or is invalid.
+    fn scan_file(&self, path: &Path, content: &str, context: &ScannerContext)
+        -> Result<ScanOutput>;
+
+    /// Returns the environment variable schema for this scanner.
+    fn get_env_var_schema(&self) -> Vec<EnvVarDeclaration> {
+        Vec::new()
+    }
+
+    /// Returns the label mappings for this scanner.
+    fn get_label_mappings(&self) -> Vec<LabelMapping> {
+        Vec::new()
+    }
+
+    /// Scans for the config instances of this application in its own paths.
+    /// # Errors
+    /// Returns an error if scanning fails or configuration files cannot be read.
+    fn scan_instances(&self, home_dir: &Path) -> Result<Vec<ConfigInstance>> {
+        let context = ScannerContext::default();
+        Ok(self
+            .scan_paths(home_dir)
+            .into_iter()
+            .filter_map(|path| {
+                let content = std::fs::read_to_string(&path).ok()?;
+                self.scan_file(&path, &content, &context).ok()
+            })
+            .flat_map(|output| output.instances)
+            .collect())
+    }
+}
+
+/// Runs a plugin written against the deprecated [`ScannerPlugin`] trait as a
+/// [`Scanner`].
+#[derive(Clone)]
+pub struct LegacyScanner(pub Arc<dyn ScannerPlugin>);
+
+impl std::fmt::Debug for LegacyScanner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LegacyScanner")
+            .field(&self.0.name())
+            .finish()
+    }
+}
+
+impl Scanner for LegacyScanner {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn app_name(&self) -> &str {
+        self.0.app_name()
+    }
+
+    fn description(&self) -> &str {
+        self.0.description()
+    }
+
+    fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
+        self.0.scan_paths(home_dir)
+    }
+
+    fn can_handle_file(&self, path: &Path) -> bool {
+        self.0.can_handle_file(path)
+    }
+
+    fn scan_file(
+        &self,
+        path: &Path,
+        content: &str,
+        settings: &ScannerContext,
+    ) -> Result<ScanOutput> {
+        let result = self.0.parse_config(path, content, settings)?;
+        Ok(ScanOutput::from_scan_result(
+            result,
+            &FindingSource::new(path, self),
+        ))
+    }
+
+    fn get_env_var_schema(&self) -> Vec<EnvVarDeclaration> {
+        self.0.get_env_var_schema()
+    }
+
+    fn get_label_mappings(&self) -> Vec<LabelMapping> {
+        self.0.get_label_mappings()
+    }
+
+    fn scan_instances(&self, home_dir: &Path) -> Result<Vec<ConfigInstance>> {
+        self.0.scan_instances(home_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::GshScanner;
+    use crate::models::Confidence;
+
+    #[test]
+    fn test_legacy_scanner_returns_findings() {
+        let scanner = LegacyScanner(Arc::new(GshScanner));
+        let output = scanner
+            .scan_file(
+                Path::new(".gshrc"),
+                "export OPENAI_API_KEY=\"sk-test1234567890abcdef\"\n",
+                &ScannerContext::default(),
+            )
+            .unwrap();
+
+        let finding = output
+            .findings
+            .iter()
+            .find(|finding| finding.key.provider == "openai")
+            .expect("the key should be found");
+        assert_eq!(finding.source.scanner, "gsh");
+        assert_eq!(finding.source.app_name, "GSH");
+        assert!(finding.remediation.as_deref().unwrap().contains("openai"));
+
+        let key = finding
+            .clone()
+            .with_span(Span {
+                line: 1,
+                column: 23,
+                len: 23,
+            })
+            .into_key();
+        assert_eq!(key.source_line, Some(1));
+        assert_eq!(key.column_number, Some(23));
+
+        let model = DiscoveredCredential::new(
+            "openai".to_string(),
+            ".gshrc".to_string(),
+            ValueType::ModelId,
+            Confidence::High,
+            "gpt-4o".to_string(),
+        );
+        assert!(remediation_hint(&model).is_none());
+    }
+}
@@ -0,0 +1,135 @@
+//! Bounded directory traversal shared by scanners.
+//!
+//! Traversal honors `ScannerConfig::max_depth`, only descends into symlinked
+//! directories when `follow_symlinks` is set, and tracks visited directories by
+//! file identity so symlink cycles cannot loop forever.
+
+use super::ScannerConfig;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Default maximum directory depth to descend below a scan path.
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// Identity of a directory, used to detect cycles.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> Option<DirId> {
+    std::fs::canonicalize(path).ok()
+}
+
+/// Collects the files beneath `root` that scanners should read, in sorted order.
+///
+/// Symlinked files are always returned; symlinked directories are only entered
+/// when `follow_symlinks` is enabled. Ignored and (optionally) hidden directories
+/// are pruned, and each returned file has passed `ScannerConfig::should_scan`.
+#[must_use]
+pub fn walk_files(root: &Path, config: &ScannerConfig) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut visited: HashSet<DirId> = HashSet::new();
+    let mut stack = vec![(root.to_path_buf(), 0usize)];
+
+    if let Some(id) = dir_id(root) {
+        visited.insert(id);
+    }
+
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            let is_dir = if file_type.is_symlink() {
+                path.is_dir()
+            } else {
+                file_type.is_dir()
+            };
+
+            if !is_dir {
+                if config.should_scan(&path) {
+                    files.push(path);
+                }
+                continue;
+            }
+
+            if file_type.is_symlink() && !config.follow_symlinks {
+                tracing::debug!("Not following symlinked directory: {}", path.display());
+                continue;
+            }
+            if depth + 1 > config.max_depth || !config.should_descend(&path) {
+                continue;
+            }
+            match dir_id(&path) {
+                Some(id) if visited.insert(id) => stack.push((path, depth + 1)),
+                Some(_) => {
+                    tracing::debug!("Skipping already visited directory: {}", path.display());
+                }
+                None => {}
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_walk_respects_max_depth() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("top.json"), "{}").unwrap();
+        fs::write(root.join("a/mid.json"), "{}").unwrap();
+        fs::write(root.join("a/b/deep.json"), "{}").unwrap();
+
+        let config = ScannerConfig {
+            max_depth: 1,
+            ..ScannerConfig::default()
+        };
+        let files = walk_files(root, &config);
+        assert_eq!(files, vec![root.join("a/mid.json"), root.join("top.json")]);
+
+        let files = walk_files(root, &ScannerConfig::default());
+        assert_eq!(files.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_terminates_on_symlink_cycle() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(root.join("config/settings.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(root, root.join("config/loop")).unwrap();
+
+        let not_following = walk_files(root, &ScannerConfig::default());
+        assert_eq!(not_following, vec![root.join("config/settings.json")]);
+
+        let config = ScannerConfig {
+            follow_symlinks: true,
+            ..ScannerConfig::default()
+        };
+        let following = walk_files(root, &config);
+        assert_eq!(following, vec![root.join("config/settings.json")]);
+    }
+}
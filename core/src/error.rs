@@ -1,20 +1,72 @@
 //! Error types for the aicred core library.
+//!
+//! Every variant has a stable numeric [`Error::code`], which the FFI layer
+//! and the language bindings expose so callers can handle failures without
+//! matching on messages. Codes are grouped by hundreds:
+//!
+//! | Code | Variant |
+//! |------|---------|
+//! | 100 | [`Error::IoError`] |
+//! | 101 | [`Error::IoDenied`] |
+//! | 102 | [`Error::NotFound`] |
+//! | 200 | [`Error::ParseFailed`] |
+//! | 201 | [`Error::SerializationError`] |
+//! | 300 | [`Error::PluginError`] |
+//! | 301 | [`Error::PluginConflict`] |
+//! | 400 | [`Error::ConfigError`] |
+//! | 401 | [`Error::StoreCorrupt`] |
+//! | 402 | [`Error::ValidationError`] |
+//! | 500 | [`Error::SecurityError`] |
+//! | 600 | [`Error::HttpError`] |
+//! | 601 | [`Error::NetworkTimeout`] |
+//! | 602 | [`Error::ApiError`] |
+//! | 603 | [`Error::RateLimited`] |
+//! | 700 | [`Error::Cancelled`] |
+//!
+//! Codes are never reused or renumbered; new variants take the next free code
+//! of their group. [`CODES`] lists them with a short description.
 
 use std::path::PathBuf;
 use thiserror::Error;
 
+/// Every error code with a short description, in code order.
+pub const CODES: &[(u32, &str)] = &[
+    (100, "IO error"),
+    (101, "Permission denied"),
+    (102, "Not found"),
+    (200, "Parse failed"),
+    (201, "Serialization error"),
+    (300, "Plugin error"),
+    (301, "Plugin conflict"),
+    (400, "Configuration error"),
+    (401, "Corrupt configuration store"),
+    (402, "Validation error"),
+    (500, "Security error"),
+    (600, "HTTP error"),
+    (601, "Network timeout"),
+    (602, "Provider API error"),
+    (603, "Rate limited"),
+    (700, "Operation cancelled"),
+];
+
 /// Main error type for the core library.
 #[derive(Error, Debug)]
 pub enum Error {
     /// IO-related errors during file operations.
     #[error("IO error: {0}")]
-    IoError(#[from] std::io::Error),
+    IoError(std::io::Error),
+
+    /// A file or directory could not be accessed for lack of permission.
+    #[error("Permission denied: {0}")]
+    IoDenied(std::io::Error),
 
     /// Errors when parsing configuration files.
-    #[error("Parse error in {path}: {message}")]
-    ParseError {
+    #[error("Parse error in {path} ({format}): {message}")]
+    ParseFailed {
         /// The path of the file that failed to parse
         path: PathBuf,
+        /// The format the file was parsed as, e.g. `json` or `yaml`
+        format: String,
         /// The error message describing what went wrong
         message: String,
     },
@@ -23,6 +75,10 @@ pub enum Error {
     #[error("Plugin error: {0}")]
     PluginError(String),
 
+    /// A plugin or scanner was registered under a name already taken.
+    #[error("Plugin conflict: '{0}' is already registered")]
+    PluginConflict(String),
+
     /// Security-related errors (e.g., invalid key formats).
     #[error("Security error: {0}")]
     SecurityError(String),
@@ -43,14 +99,143 @@ pub enum Error {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    /// A file of the configuration store could not be read back.
+    #[error("Corrupt config file {path}: {message}")]
+    StoreCorrupt {
+        /// The path of the corrupt file
+        path: PathBuf,
+        /// The error message describing what went wrong
+        message: String,
+    },
+
     /// API-related errors (e.g., authentication failures, rate limits).
     #[error("API error: {0}")]
     ApiError(String),
 
     /// HTTP request errors.
     #[error("HTTP error: {0}")]
-    HttpError(#[from] reqwest::Error),
+    HttpError(reqwest::Error),
+
+    /// A network request got no answer in time.
+    #[error("Network timeout: {0}")]
+    NetworkTimeout(String),
+
+    /// The provider answered 429 Too Many Requests.
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        /// Seconds the provider asked to wait, from its `Retry-After` header
+        retry_after_secs: Option<u64>,
+        /// The error message describing what went wrong
+        message: String,
+    },
+
+    /// The operation was cancelled through a
+    /// [`CancellationToken`](crate::cancel::CancellationToken).
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl Error {
+    /// Stable numeric code of the error, see the [module docs](self).
+    #[must_use]
+    pub const fn code(&self) -> u32 {
+        match self {
+            Self::IoError(_) => 100,
+            Self::IoDenied(_) => 101,
+            Self::NotFound(_) => 102,
+            Self::ParseFailed { .. } => 200,
+            Self::SerializationError(_) => 201,
+            Self::PluginError(_) => 300,
+            Self::PluginConflict(_) => 301,
+            Self::ConfigError(_) => 400,
+            Self::StoreCorrupt { .. } => 401,
+            Self::ValidationError(_) => 402,
+            Self::SecurityError(_) => 500,
+            Self::HttpError(_) => 600,
+            Self::NetworkTimeout(_) => 601,
+            Self::ApiError(_) => 602,
+            Self::RateLimited { .. } => 603,
+            Self::Cancelled => 700,
+        }
+    }
+
+    /// Short description of an error code, such as "Not found" for 102.
+    #[must_use]
+    pub fn describe_code(code: u32) -> Option<&'static str> {
+        CODES
+            .iter()
+            .find(|(known, _)| *known == code)
+            .map(|(_, description)| *description)
+    }
+
+    /// Checks if the operation may succeed when tried again unchanged.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::NetworkTimeout(_) | Self::RateLimited { .. } => true,
+            Self::HttpError(e) => e.is_timeout() || e.is_connect(),
+            Self::IoError(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::WouldBlock
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::PermissionDenied {
+            Self::IoDenied(e)
+        } else {
+            Self::IoError(e)
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::NetworkTimeout(e.to_string())
+        } else {
+            Self::HttpError(e)
+        }
+    }
 }
 
 /// Result type alias for the core library.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_errors_map_to_codes() {
+        let denied: Error = std::io::Error::from(std::io::ErrorKind::PermissionDenied).into();
+        assert!(matches!(denied, Error::IoDenied(_)));
+        assert_eq!(denied.code(), 101);
+        assert!(!denied.is_retryable());
+
+        let interrupted: Error = std::io::Error::from(std::io::ErrorKind::Interrupted).into();
+        assert_eq!(interrupted.code(), 100);
+        assert!(interrupted.is_retryable());
+
+        assert_eq!(Error::PluginConflict("openai".to_string()).code(), 301);
+        assert!(Error::NetworkTimeout("no answer".to_string()).is_retryable());
+        assert_eq!(Error::Cancelled.code(), 700);
+
+        assert_eq!(
+            Error::describe_code(denied.code()),
+            Some("Permission denied")
+        );
+        assert_eq!(
+            Error::describe_code(Error::Cancelled.code()),
+            Some("Operation cancelled")
+        );
+        assert_eq!(Error::describe_code(999), None);
+        assert!(CODES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
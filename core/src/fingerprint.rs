@@ -0,0 +1,224 @@
+#![allow(clippy::missing_errors_doc)]
+//! Fingerprints of discovered key values.
+//!
+//! The `hash` of a finding is a plain SHA-256 digest of the value by default.
+//! With [`HashAlgorithm::HmacSha256`] it is an HMAC keyed with a salt kept in
+//! the config directory, so fingerprints can't be matched against known keys by
+//! anyone without the salt. Copying [`SALT_FILE`] to other machines makes their
+//! fingerprints match, so teams can correlate findings without sharing keys.
+
+use crate::error::{Error, Result};
+use crate::store::ConfigStore;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Salt file for HMAC fingerprints within the config store.
+pub const SALT_FILE: &str = "fingerprint.salt";
+
+const SALT_LEN: usize = 32;
+
+/// Algorithm computing the fingerprint of a key value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HashAlgorithm {
+    /// Plain SHA-256 of the value
+    #[default]
+    Sha256,
+    /// HMAC-SHA256 keyed with the local salt
+    HmacSha256,
+}
+
+impl HashAlgorithm {
+    /// Name of the algorithm as accepted by [`FromStr`](std::str::FromStr)
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::HmacSha256 => "hmac-sha256",
+        }
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "sha256" | "sha-256" => Ok(Self::Sha256),
+            "hmac" | "hmac-sha256" => Ok(Self::HmacSha256),
+            _ => Err(format!(
+                "Invalid hash algorithm '{s}' (expected sha256 or hmac-sha256)"
+            )),
+        }
+    }
+}
+
+/// Computes the fingerprints of key values.
+#[derive(Clone, Default)]
+pub struct Fingerprinter {
+    /// HMAC key, or `None` for plain SHA-256
+    key: Option<hmac::Key>,
+}
+
+impl std::fmt::Debug for Fingerprinter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fingerprinter")
+            .field("algorithm", &self.algorithm())
+            .finish()
+    }
+}
+
+impl Fingerprinter {
+    /// Fingerprints with plain SHA-256.
+    #[must_use]
+    pub const fn sha256() -> Self {
+        Self { key: None }
+    }
+
+    /// Fingerprints with HMAC-SHA256 keyed with a salt.
+    #[must_use]
+    pub fn hmac(salt: &[u8]) -> Self {
+        Self {
+            key: Some(hmac::Key::new(hmac::HMAC_SHA256, salt)),
+        }
+    }
+
+    /// Fingerprints with an algorithm, reading the salt of the config store for
+    /// HMAC and generating it on first use.
+    pub fn load(store: &ConfigStore, algorithm: HashAlgorithm) -> Result<Self> {
+        match algorithm {
+            HashAlgorithm::Sha256 => Ok(Self::sha256()),
+            HashAlgorithm::HmacSha256 => Ok(Self::hmac(&load_or_generate_salt(
+                &store.dir().join(SALT_FILE),
+            )?)),
+        }
+    }
+
+    /// Algorithm the fingerprints are computed with.
+    #[must_use]
+    pub const fn algorithm(&self) -> HashAlgorithm {
+        if self.key.is_some() {
+            HashAlgorithm::HmacSha256
+        } else {
+            HashAlgorithm::Sha256
+        }
+    }
+
+    /// Hex fingerprint of a value.
+    #[must_use]
+    pub fn fingerprint(&self, value: &str) -> String {
+        self.key.as_ref().map_or_else(
+            || hex::encode(Sha256::digest(value.as_bytes())),
+            |key| hex::encode(hmac::sign(key, value.as_bytes())),
+        )
+    }
+}
+
+/// Reads the hex-encoded salt at `path`, generating a random one readable only
+/// by the owner if the file doesn't exist.
+pub fn load_or_generate_salt(path: &Path) -> Result<Vec<u8>> {
+    if !path.exists() {
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| Error::SecurityError("Failed to generate salt".to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        match options.open(path) {
+            Ok(mut file) => {
+                std::io::Write::write_all(
+                    &mut file,
+                    format!("{}\n", hex::encode(salt)).as_bytes(),
+                )?;
+                return Ok(salt.to_vec());
+            }
+            // Created by a concurrent scan, read it below
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    hex::decode(content.trim())
+        .ok()
+        .filter(|salt| !salt.is_empty())
+        .ok_or_else(|| Error::SecurityError(format!("Invalid salt file: {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscoveredCredential;
+
+    #[test]
+    fn test_fingerprints_by_algorithm() {
+        let sha256 = Fingerprinter::default();
+        assert_eq!(sha256.algorithm(), HashAlgorithm::Sha256);
+        assert_eq!(
+            sha256.fingerprint("sk-test"),
+            DiscoveredCredential::hash_value("sk-test")
+        );
+
+        // RFC 4231 test case 2
+        let hmac = Fingerprinter::hmac(b"Jefe");
+        assert_eq!(hmac.algorithm(), HashAlgorithm::HmacSha256);
+        assert_eq!(
+            hmac.fingerprint("what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_ne!(
+            Fingerprinter::hmac(b"other").fingerprint("sk-test"),
+            hmac.fingerprint("sk-test")
+        );
+
+        assert_eq!("hmac".parse(), Ok(HashAlgorithm::HmacSha256));
+        assert_eq!("SHA256".parse(), Ok(HashAlgorithm::Sha256));
+        assert!("md5".parse::<HashAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_salt_is_generated_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(dir.path().to_path_buf());
+        let first = Fingerprinter::load(&store, HashAlgorithm::HmacSha256).unwrap();
+        let second = Fingerprinter::load(&store, HashAlgorithm::HmacSha256).unwrap();
+        assert_eq!(first.fingerprint("sk-test"), second.fingerprint("sk-test"));
+        assert_ne!(
+            first.fingerprint("sk-test"),
+            DiscoveredCredential::hash_value("sk-test")
+        );
+
+        let salt = std::fs::read_to_string(dir.path().join(SALT_FILE)).unwrap();
+        assert_eq!(salt.trim().len(), SALT_LEN * 2);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path().join(SALT_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(dir.path().join(SALT_FILE), "not hex\n").unwrap();
+        assert!(Fingerprinter::load(&store, HashAlgorithm::HmacSha256).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! Live health checks of configured provider instances.
+//!
+//! A check lists the instance's models with its API key, which tests the key
+//! and the base URL in one request, and measures how long the provider took to
+//! answer. Results are recorded in the instance metadata under
+//! [`LAST_CHECKED_METADATA`], [`STATUS_METADATA`] and [`LATENCY_METADATA`].
+//!
+//! [`check_instances`] checks many instances through a
+//! [`ProbeCoordinator`], so providers see a few requests at a time and an
+//! instance that stays rate limited is reported on its own.
+
+use crate::error::{Error, Result};
+use crate::http::NetworkOptions;
+use crate::models::ProviderInstance;
+use crate::probe::ProbeCoordinator;
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Metadata key holding when the instance was last checked (RFC 3339).
+pub const LAST_CHECKED_METADATA: &str = "last_checked";
+
+/// Metadata key holding the [`HealthStatus`] of the last check.
+pub const STATUS_METADATA: &str = "status";
+
+/// Metadata key holding the latency of the last check in milliseconds.
+pub const LATENCY_METADATA: &str = "latency_ms";
+
+/// Outcome of a health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// The key was accepted and models were listed
+    Ok,
+    /// The provider rejected the key
+    Unauthorized,
+    /// The provider answered with another error
+    Error,
+    /// No answer, e.g. a connection failure or timeout
+    Unreachable,
+    /// The provider kept answering 429 Too Many Requests, so the instance
+    /// could not be checked
+    RateLimited,
+}
+
+impl HealthStatus {
+    /// Gets the name stored in [`STATUS_METADATA`].
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Unauthorized => "unauthorized",
+            Self::Error => "error",
+            Self::Unreachable => "unreachable",
+            Self::RateLimited => "rate_limited",
+        }
+    }
+}
+
+impl std::fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Result of checking one instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// Instance that was checked
+    pub instance_id: String,
+    /// Outcome
+    pub status: HealthStatus,
+    /// Time until the provider answered, if it did
+    pub latency_ms: Option<u64>,
+    /// Models the provider listed
+    pub models: Vec<String>,
+    /// Why the check failed, if it did
+    pub message: Option<String>,
+    /// When the check ran
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthCheck {
+    /// Records the result in the instance metadata.
+    pub fn record(&self, instance: &mut ProviderInstance) {
+        instance.metadata.insert(
+            LAST_CHECKED_METADATA.to_string(),
+            self.checked_at.to_rfc3339(),
+        );
+        instance
+            .metadata
+            .insert(STATUS_METADATA.to_string(), self.status.to_string());
+        match self.latency_ms {
+            Some(latency) => {
+                instance
+                    .metadata
+                    .insert(LATENCY_METADATA.to_string(), latency.to_string());
+            }
+            None => {
+                instance.metadata.remove(LATENCY_METADATA);
+            }
+        }
+    }
+}
+
+/// Checks an instance, whose API key must already be resolved to its value.
+#[must_use]
+pub fn check_instance(instance: &ProviderInstance, timeout: Duration) -> HealthCheck {
+    check_once(instance, timeout).0
+}
+
+/// Checks an instance, also returning the `Retry-After` of a 429 answer.
+fn check_once(instance: &ProviderInstance, timeout: Duration) -> (HealthCheck, Option<Duration>) {
+    let mut check = HealthCheck {
+        instance_id: instance.id.clone(),
+        status: HealthStatus::Unreachable,
+        latency_ms: None,
+        models: Vec::new(),
+        message: None,
+        checked_at: Utc::now(),
+    };
+    let client = match Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => {
+            check.message = Some(e.to_string());
+            return (check, None);
+        }
+    };
+
+    let started = Instant::now();
+    let response = models_request(&client, instance).send();
+    check.latency_ms = Some(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX));
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            check.latency_ms = None;
+            check.message = Some(if e.is_timeout() {
+                format!("no answer within {}s", timeout.as_secs())
+            } else {
+                e.to_string()
+            });
+            return (check, None);
+        }
+    };
+
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = crate::http::retry_after(response.headers());
+        check.status = HealthStatus::RateLimited;
+        check.message = Some(retry_after.map_or_else(
+            || "rate limited".to_string(),
+            |wait| format!("rate limited, retry after {}s", wait.as_secs()),
+        ));
+        return (check, retry_after);
+    }
+    if status.as_u16() == 401 || status.as_u16() == 403 {
+        check.status = HealthStatus::Unauthorized;
+        check.message = Some(format!("the provider rejected the API key (HTTP {status})"));
+        return (check, None);
+    }
+    if !status.is_success() {
+        check.status = HealthStatus::Error;
+        check.message = Some(format!("HTTP {status}"));
+        return (check, None);
+    }
+    match response.json::<Value>() {
+        Ok(body) => {
+            check.status = HealthStatus::Ok;
+            check.models = model_ids(&body);
+        }
+        Err(e) => {
+            check.status = HealthStatus::Error;
+            check.message = Some(format!("unexpected response: {e}"));
+        }
+    }
+    (check, None)
+}
+
+/// Lists the models of an instance, whose API key must already be resolved
+/// to its value.
+///
+/// # Errors
+/// Returns an error if the request fails or the provider doesn't list models.
+pub fn list_models(instance: &ProviderInstance, network: &NetworkOptions) -> Result<Vec<String>> {
+    let response = models_request(&network.blocking_client()?, instance).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::ApiError(format!(
+            "listing the models of {} failed with HTTP {status}",
+            instance.id
+        )));
+    }
+    let body: Value = response
+        .json()
+        .map_err(|e| Error::SerializationError(format!("unexpected response: {e}")))?;
+    Ok(model_ids(&body))
+}
+
+/// Checks instances, whose API keys must already be resolved to their values,
+/// running at most `parallelism` checks at once and at most
+/// [`NetworkOptions::max_per_provider`] per provider.
+///
+/// Rate-limited checks are run again after the provider's `Retry-After` as
+/// often as [`NetworkOptions::retries`] allows; checks that stay rate limited
+/// come back as [`HealthStatus::RateLimited`]. Results are in the order of
+/// `instances`.
+#[must_use]
+pub fn check_instances(
+    instances: &[ProviderInstance],
+    timeout: Duration,
+    network: &NetworkOptions,
+    parallelism: Option<usize>,
+) -> Vec<HealthCheck> {
+    let unchecked = |instance: &ProviderInstance, message: String| HealthCheck {
+        instance_id: instance.id.clone(),
+        status: HealthStatus::Error,
+        latency_ms: None,
+        models: Vec::new(),
+        message: Some(message),
+        checked_at: Utc::now(),
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return instances
+                .iter()
+                .map(|instance| unchecked(instance, e.to_string()))
+                .collect()
+        }
+    };
+    let mut coordinator = ProbeCoordinator::new(network);
+    if let Some(limit) = parallelism {
+        coordinator = coordinator.with_max_concurrent(limit);
+    }
+    let coordinator = std::sync::Arc::new(coordinator);
+
+    runtime.block_on(async {
+        let tasks: Vec<_> = instances
+            .iter()
+            .map(|instance| {
+                let coordinator = coordinator.clone();
+                let instance = instance.clone();
+                tokio::spawn(async move {
+                    coordinator
+                        .run(&instance.provider_type, || {
+                            let instance = instance.clone();
+                            async move {
+                                let (check, retry_after) = tokio::task::spawn_blocking(move || {
+                                    check_once(&instance, timeout)
+                                })
+                                .await
+                                .map_err(|e| Error::PluginError(e.to_string()))?;
+                                if check.status == HealthStatus::RateLimited {
+                                    return Err(Error::RateLimited {
+                                        retry_after_secs: retry_after.map(|wait| wait.as_secs()),
+                                        message: check.message.unwrap_or_default(),
+                                    });
+                                }
+                                Ok(check)
+                            }
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        let mut checks = Vec::with_capacity(instances.len());
+        for (instance, task) in instances.iter().zip(tasks) {
+            checks.push(match task.await {
+                Ok(Ok(check)) => check,
+                Ok(Err(Error::RateLimited { message, .. })) => HealthCheck {
+                    status: HealthStatus::RateLimited,
+                    ..unchecked(instance, message)
+                },
+                Ok(Err(e)) => unchecked(instance, e.to_string()),
+                Err(e) => unchecked(instance, format!("check failed: {e}")),
+            });
+        }
+        checks
+    })
+}
+
+/// Builds the model list request for an instance, authenticated the way its
+/// provider expects.
+fn models_request(client: &Client, instance: &ProviderInstance) -> RequestBuilder {
+    let base = instance.base_url.trim_end_matches('/');
+    let key = instance.api_key.as_str();
+    match instance.provider_type.as_str() {
+        "anthropic" => client
+            .get(format!("{base}/models"))
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        "gemini" | "google" => client
+            .get(format!("{base}/models"))
+            .header("x-goog-api-key", key),
+        "ollama" if !base.ends_with("/v1") => client.get(format!("{base}/api/tags")),
+        _ if key.is_empty() => client.get(format!("{base}/models")),
+        _ => client.get(format!("{base}/models")).bearer_auth(key),
+    }
+}
+
+/// Extracts model IDs from the `OpenAI`, Anthropic, Gemini and Ollama list formats.
+fn model_ids(body: &Value) -> Vec<String> {
+    let entries = body
+        .get("data")
+        .or_else(|| body.get("models"))
+        .and_then(Value::as_array);
+    entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .get("id")
+                .or_else(|| entry.get("name"))
+                .and_then(Value::as_str)
+        })
+        .map(|id| id.trim_start_matches("models/").to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves one HTTP response on a local port, returning the base URL.
+    fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request).unwrap();
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://127.0.0.1:{port}/v1")
+    }
+
+    fn instance(base_url: &str) -> ProviderInstance {
+        ProviderInstance::new(
+            "local".to_string(),
+            "openai".to_string(),
+            base_url.to_string(),
+            "sk-test".to_string(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_check_lists_models_and_records_result() {
+        let base = serve_once(
+            "200 OK",
+            r#"{"data":[{"id":"gpt-4o"},{"id":"gpt-4o-mini"}]}"#,
+        );
+        let mut instance = instance(&base);
+        let check = check_instance(&instance, Duration::from_secs(5));
+        assert_eq!(check.status, HealthStatus::Ok);
+        assert_eq!(check.models, vec!["gpt-4o", "gpt-4o-mini"]);
+        assert!(check.latency_ms.is_some());
+
+        check.record(&mut instance);
+        assert_eq!(instance.metadata[STATUS_METADATA], "ok");
+        assert!(instance.metadata.contains_key(LAST_CHECKED_METADATA));
+        assert!(instance.metadata.contains_key(LATENCY_METADATA));
+    }
+
+    #[test]
+    fn test_check_reports_rejected_keys_and_unreachable_hosts() {
+        let base = serve_once("401 Unauthorized", r#"{"error":"invalid key"}"#);
+        let check = check_instance(&instance(&base), Duration::from_secs(5));
+        assert_eq!(check.status, HealthStatus::Unauthorized);
+
+        // Nothing listens on a port freed right after binding it
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let check = check_instance(
+            &instance(&format!("http://127.0.0.1:{port}")),
+            Duration::from_secs(5),
+        );
+        assert_eq!(check.status, HealthStatus::Unreachable);
+        assert_eq!(check.latency_ms, None);
+    }
+
+    #[test]
+    fn test_check_instances_reports_rate_limited_instances() {
+        let limited = serve_once("429 Too Many Requests", r#"{"error":"slow down"}"#);
+        let healthy = serve_once("200 OK", r#"{"data":[{"id":"gpt-4o"}]}"#);
+        let mut second = instance(&healthy);
+        second.id = "other".to_string();
+        let network = NetworkOptions {
+            retries: 0,
+            ..NetworkOptions::default()
+        };
+
+        let checks = check_instances(
+            &[instance(&limited), second],
+            Duration::from_secs(5),
+            &network,
+            Some(1),
+        );
+        assert_eq!(checks[0].status, HealthStatus::RateLimited);
+        assert_eq!(checks[0].instance_id, "local");
+        assert_eq!(checks[1].status, HealthStatus::Ok);
+        assert_eq!(checks[1].models, vec!["gpt-4o"]);
+    }
+
+    #[test]
+    fn test_model_ids_reads_gemini_and_ollama_lists() {
+        let gemini = serde_json::json!({"models": [{"name": "models/gemini-2.0-flash"}]});
+        assert_eq!(model_ids(&gemini), vec!["gemini-2.0-flash"]);
+        let ollama = serde_json::json!({"models": [{"name": "llama3:8b"}]});
+        assert_eq!(model_ids(&ollama), vec!["llama3:8b"]);
+    }
+}
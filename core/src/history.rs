@@ -0,0 +1,287 @@
+#![allow(clippy::missing_errors_doc)]
+//! Persistent history of scan results.
+//!
+//! Each scan appends a [`ScanRecord`] to an append-only JSON Lines file in
+//! the data directory (see [`crate::paths`]), such as `~/.local/share/aicred`.
+//! Records hold key hashes, never values, so the history shows when a key
+//! first appeared and when it disappeared without keeping secrets.
+
+use crate::error::{Error, Result};
+use crate::models::{RiskLevel, ScanResult};
+use crate::paths::AppDirs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// History file name.
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// A key seen by a scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySighting {
+    /// SHA-256 hash of the key
+    pub hash: String,
+    /// Provider the key belongs to
+    pub provider: String,
+    /// Risk assessed for the key
+    pub risk: RiskLevel,
+    /// Files the key was found in
+    pub sources: Vec<String>,
+}
+
+/// Summary of one scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanRecord {
+    /// When the scan ran
+    pub scanned_at: DateTime<Utc>,
+    /// Home directory that was scanned
+    pub home_dir: String,
+    /// Number of application configurations found
+    pub config_count: usize,
+    /// Keys found
+    pub keys: Vec<KeySighting>,
+}
+
+impl ScanRecord {
+    /// Summarizes a scan result.
+    #[must_use]
+    pub fn from_scan_result(result: &ScanResult) -> Self {
+        Self {
+            scanned_at: result.scan_completed_at,
+            home_dir: result.home_directory.clone(),
+            config_count: result.config_instances.len(),
+            keys: result
+                .keys
+                .iter()
+                .map(|key| KeySighting {
+                    hash: key.hash.clone(),
+                    provider: key.provider.clone(),
+                    risk: key.risk,
+                    sources: key.sources.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// When a key was seen across the history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyTimeline {
+    /// SHA-256 hash of the key
+    pub hash: String,
+    /// Provider the key belongs to
+    pub provider: String,
+    /// First scan that found the key
+    pub first_seen: DateTime<Utc>,
+    /// Last scan that found the key
+    pub last_seen: DateTime<Utc>,
+    /// First scan after `last_seen`, if the key is gone
+    pub disappeared_at: Option<DateTime<Utc>>,
+    /// Number of scans that found the key
+    pub sightings: usize,
+}
+
+/// Number of keys in one scan, and how they changed from the previous scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanTrend {
+    /// When the scan ran
+    pub scanned_at: DateTime<Utc>,
+    /// Keys found
+    pub total: usize,
+    /// Keys not found by the previous scan
+    pub added: usize,
+    /// Keys found by the previous scan but not this one
+    pub removed: usize,
+}
+
+/// The scan history file.
+#[derive(Debug, Clone)]
+pub struct ScanHistory {
+    path: PathBuf,
+}
+
+impl ScanHistory {
+    /// Opens the history at a specific path.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Opens the history kept under a home directory.
+    #[must_use]
+    pub fn for_home(home_dir: &Path) -> Self {
+        Self::new(AppDirs::for_home(home_dir).data_dir().join(HISTORY_FILE))
+    }
+
+    /// Opens the current user's history.
+    pub fn default_location() -> Result<Self> {
+        Ok(Self::new(AppDirs::current()?.data_dir().join(HISTORY_FILE)))
+    }
+
+    /// Path of the history file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a scan to the history.
+    pub fn append(&self, record: &ScanRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path)?;
+        let line =
+            serde_json::to_string(record).map_err(|e| Error::SerializationError(e.to_string()))?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+
+    /// Loads every recorded scan, oldest first, skipping unreadable lines.
+    pub fn load(&self) -> Result<Vec<ScanRecord>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut records = Vec::new();
+        for line in std::io::BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => tracing::warn!("Skipping unreadable history entry: {}", e),
+            }
+        }
+        records.sort_by_key(|record: &ScanRecord| record.scanned_at);
+        Ok(records)
+    }
+}
+
+/// Builds the timeline of every key in the history, most recently first seen first.
+#[must_use]
+pub fn key_timelines(records: &[ScanRecord]) -> Vec<KeyTimeline> {
+    let mut timelines: HashMap<&str, KeyTimeline> = HashMap::new();
+    for record in records {
+        for key in &record.keys {
+            timelines
+                .entry(key.hash.as_str())
+                .and_modify(|timeline| {
+                    timeline.last_seen = record.scanned_at;
+                    timeline.disappeared_at = None;
+                    timeline.sightings += 1;
+                })
+                .or_insert_with(|| KeyTimeline {
+                    hash: key.hash.clone(),
+                    provider: key.provider.clone(),
+                    first_seen: record.scanned_at,
+                    last_seen: record.scanned_at,
+                    disappeared_at: None,
+                    sightings: 1,
+                });
+        }
+        for timeline in timelines.values_mut() {
+            if timeline.disappeared_at.is_none() && timeline.last_seen < record.scanned_at {
+                timeline.disappeared_at = Some(record.scanned_at);
+            }
+        }
+    }
+
+    let mut timelines: Vec<KeyTimeline> = timelines.into_values().collect();
+    timelines.sort_by(|a, b| b.first_seen.cmp(&a.first_seen).then(a.hash.cmp(&b.hash)));
+    timelines
+}
+
+/// Counts keys per scan along with the keys added and removed since the previous scan.
+#[must_use]
+pub fn trends(records: &[ScanRecord]) -> Vec<ScanTrend> {
+    let mut previous: Vec<&str> = Vec::new();
+    records
+        .iter()
+        .map(|record| {
+            let current: Vec<&str> = record.keys.iter().map(|k| k.hash.as_str()).collect();
+            let trend = ScanTrend {
+                scanned_at: record.scanned_at,
+                total: current.len(),
+                added: current.iter().filter(|h| !previous.contains(h)).count(),
+                removed: previous.iter().filter(|h| !current.contains(h)).count(),
+            };
+            previous = current;
+            trend
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn record(day: u32, hashes: &[&str]) -> ScanRecord {
+        ScanRecord {
+            scanned_at: Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+            home_dir: "/home/u".to_string(),
+            config_count: 0,
+            keys: hashes
+                .iter()
+                .map(|hash| KeySighting {
+                    hash: (*hash).to_string(),
+                    provider: "openai".to_string(),
+                    risk: RiskLevel::High,
+                    sources: vec!["/home/u/.env".to_string()],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let temp = TempDir::new().unwrap();
+        let history = ScanHistory::for_home(temp.path());
+        assert!(history.load().unwrap().is_empty());
+
+        history.append(&record(2, &["b"])).unwrap();
+        history.append(&record(1, &["a"])).unwrap();
+        let loaded = history.load().unwrap();
+        assert_eq!(loaded, vec![record(1, &["a"]), record(2, &["b"])]);
+    }
+
+    #[test]
+    fn test_key_timelines_track_appearance_and_disappearance() {
+        let records = [record(1, &["a"]), record(2, &["a", "b"]), record(3, &["b"])];
+        let timelines = key_timelines(&records);
+
+        let a = timelines.iter().find(|t| t.hash == "a").unwrap();
+        assert_eq!(a.first_seen, records[0].scanned_at);
+        assert_eq!(a.last_seen, records[1].scanned_at);
+        assert_eq!(a.disappeared_at, Some(records[2].scanned_at));
+        assert_eq!(a.sightings, 2);
+
+        let b = timelines.iter().find(|t| t.hash == "b").unwrap();
+        assert_eq!(b.disappeared_at, None);
+        // Most recently first seen first
+        assert_eq!(timelines[0].hash, "b");
+    }
+
+    #[test]
+    fn test_trends_count_added_and_removed() {
+        let records = [record(1, &["a"]), record(2, &["a", "b"]), record(3, &["b"])];
+        let trends = trends(&records);
+        let counts: Vec<(usize, usize, usize)> = trends
+            .iter()
+            .map(|t| (t.total, t.added, t.removed))
+            .collect();
+        assert_eq!(counts, [(1, 1, 0), (2, 1, 0), (1, 0, 1)]);
+    }
+}
@@ -0,0 +1,336 @@
+#![allow(clippy::missing_errors_doc)]
+//! Shared HTTP client for talking to provider APIs.
+//!
+//! Model probing builds its client from [`NetworkOptions`], which
+//! [`ScanOptions::network`](crate::ScanOptions::network) carries, so scans on
+//! corporate networks can go through a proxy and trust an inspecting CA.
+//!
+//! Without options, `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` are
+//! honoured as usual. [`NetworkOptions::proxy`] and [`NetworkOptions::no_proxy`]
+//! take precedence over them.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default timeout of a request in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Default number of retries of a failed request.
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// Default delay before the first retry in milliseconds.
+pub const DEFAULT_RETRY_BACKOFF_MS: u64 = 500;
+
+/// Default number of probes of one provider run at once.
+pub const DEFAULT_MAX_PER_PROVIDER: usize = 4;
+
+/// Default longest `Retry-After` waited for in seconds.
+pub const DEFAULT_MAX_RETRY_AFTER_SECS: u64 = 60;
+
+/// Network settings of requests to provider APIs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkOptions {
+    /// Proxy for every request, such as `http://proxy.corp:3128`, overriding
+    /// `HTTPS_PROXY` and `HTTP_PROXY`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Comma-separated hosts reached without the proxy, overriding `NO_PROXY`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM file of CA certificates to trust besides the built-in roots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+    /// Timeout of each request in seconds.
+    pub timeout_secs: u64,
+    /// How often a request is tried again after a timeout, a failed connection
+    /// or a 429 or 5xx response.
+    pub retries: u32,
+    /// Delay before the first retry in milliseconds, doubled for each further
+    /// one.
+    pub retry_backoff_ms: u64,
+    /// Most probes of one provider run at once, see
+    /// [`ProbeCoordinator`](crate::probe::ProbeCoordinator).
+    pub max_per_provider: usize,
+    /// Longest `Retry-After` of a 429 response waited for in seconds; longer
+    /// waits are cut to this.
+    pub max_retry_after_secs: u64,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            retries: DEFAULT_RETRIES,
+            retry_backoff_ms: DEFAULT_RETRY_BACKOFF_MS,
+            max_per_provider: DEFAULT_MAX_PER_PROVIDER,
+            max_retry_after_secs: DEFAULT_MAX_RETRY_AFTER_SECS,
+        }
+    }
+}
+
+impl NetworkOptions {
+    /// Builds an async client with these settings.
+    pub fn client(&self) -> Result<HttpClient> {
+        let mut builder = reqwest::Client::builder().timeout(self.timeout());
+        if let Some(proxy) = self.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in self.certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| Error::ConfigError(format!("Failed to create HTTP client: {e}")))?;
+        Ok(HttpClient {
+            client,
+            retries: self.retries,
+            retry_backoff_ms: self.retry_backoff_ms,
+            max_retry_after: self.max_retry_after(),
+        })
+    }
+
+    /// Builds a blocking client with these settings. Blocking requests are
+    /// not retried.
+    pub fn blocking_client(&self) -> Result<reqwest::blocking::Client> {
+        let mut builder = reqwest::blocking::Client::builder().timeout(self.timeout());
+        if let Some(proxy) = self.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in self.certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        builder
+            .build()
+            .map_err(|e| Error::ConfigError(format!("Failed to create HTTP client: {e}")))
+    }
+
+    /// Timeout of each request.
+    #[must_use]
+    pub const fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Longest `Retry-After` waited for.
+    #[must_use]
+    pub const fn max_retry_after(&self) -> Duration {
+        Duration::from_secs(self.max_retry_after_secs)
+    }
+
+    /// The configured proxy, `None` to use the proxy environment variables.
+    fn proxy(&self) -> Result<Option<reqwest::Proxy>> {
+        let Some(url) = self.proxy.as_deref().filter(|url| !url.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let proxy = reqwest::Proxy::all(url.trim())
+            .map_err(|e| Error::ConfigError(format!("Invalid proxy URL '{url}': {e}")))?;
+        let no_proxy = self
+            .no_proxy
+            .clone()
+            .or_else(|| std::env::var("NO_PROXY").ok())
+            .or_else(|| std::env::var("no_proxy").ok());
+        Ok(Some(proxy.no_proxy(
+            no_proxy.as_deref().and_then(reqwest::NoProxy::from_string),
+        )))
+    }
+
+    /// Certificates of the CA bundle, if one is configured.
+    fn certificates(&self) -> Result<Vec<reqwest::Certificate>> {
+        let Some(path) = &self.ca_bundle else {
+            return Ok(Vec::new());
+        };
+        let pem = std::fs::read_to_string(path).map_err(|e| {
+            Error::ConfigError(format!("Failed to read CA bundle {}: {e}", path.display()))
+        })?;
+        let certificates = pem_blocks(&pem)
+            .map(|block| {
+                reqwest::Certificate::from_pem(block.as_bytes()).map_err(|e| {
+                    Error::ConfigError(format!(
+                        "Invalid certificate in CA bundle {}: {e}",
+                        path.display()
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if certificates.is_empty() {
+            return Err(Error::ConfigError(format!(
+                "CA bundle {} contains no certificates",
+                path.display()
+            )));
+        }
+        Ok(certificates)
+    }
+}
+
+/// The `-----BEGIN CERTIFICATE-----` blocks of a PEM bundle.
+fn pem_blocks(pem: &str) -> impl Iterator<Item = &str> {
+    const END: &str = "-----END CERTIFICATE-----";
+    pem.split_inclusive(END)
+        .filter_map(|chunk| {
+            chunk
+                .find("-----BEGIN CERTIFICATE-----")
+                .map(|start| &chunk[start..])
+        })
+        .filter(|block| block.ends_with(END))
+}
+
+/// Async client that retries failed requests with exponential backoff.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    retries: u32,
+    retry_backoff_ms: u64,
+    max_retry_after: Duration,
+}
+
+impl HttpClient {
+    /// The underlying client, for building requests.
+    #[must_use]
+    pub const fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Starts a GET request to `url`.
+    pub fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.client.get(url)
+    }
+
+    /// Sends a request, trying it again after a timeout, a failed connection
+    /// or a 429 or 5xx response as often as configured. A 429 response is
+    /// retried no sooner than its `Retry-After`. Requests whose body cannot be
+    /// cloned are sent once.
+    pub async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let Some(this_attempt) = request.try_clone() else {
+                return Ok(request.send().await?);
+            };
+            let can_retry = attempt < self.retries;
+            let mut delay = self.backoff(attempt);
+            match this_attempt.send().await {
+                Ok(response) if can_retry && is_retryable_status(response.status()) => {
+                    tracing::debug!(
+                        "Retrying {} after status {}",
+                        response.url(),
+                        response.status()
+                    );
+                    if let Some(retry_after) = retry_after(response.headers()) {
+                        delay = delay.max(retry_after.min(self.max_retry_after));
+                    }
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if can_retry && (e.is_timeout() || e.is_connect()) => {
+                    tracing::debug!("Retrying request after error: {}", e);
+                }
+                Err(e) => return Err(e.into()),
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Delay before retry number `attempt`, starting at 0.
+    fn backoff(&self, attempt: u32) -> Duration {
+        Duration::from_millis(self.retry_backoff_ms.saturating_mul(1 << attempt.min(16)))
+    }
+}
+
+/// Wait a response asks for in its `Retry-After` header, given in seconds or
+/// as an HTTP date.
+#[must_use]
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+        .or(Some(Duration::ZERO))
+}
+
+/// Checks if a response status is worth retrying.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_send_retries_server_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("GET", "/models")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/models")
+            .with_status(200)
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        let client = NetworkOptions {
+            retry_backoff_ms: 1,
+            ..NetworkOptions::default()
+        }
+        .client()
+        .unwrap();
+        let response = client
+            .send(client.get(&format!("{}/models", server.url())))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        unavailable.assert_async().await;
+        ok.assert_async().await;
+        drop(server);
+    }
+
+    #[test]
+    fn test_rejects_invalid_settings_and_reads_retry_after() {
+        let temp = tempfile::tempdir().unwrap();
+        let bundle = temp.path().join("ca.pem");
+        std::fs::write(&bundle, "not a certificate").unwrap();
+
+        let bad_bundle = NetworkOptions {
+            ca_bundle: Some(bundle),
+            ..NetworkOptions::default()
+        };
+        assert!(matches!(bad_bundle.client(), Err(Error::ConfigError(_))));
+
+        let bad_proxy = NetworkOptions {
+            proxy: Some("http://[::1".to_string()),
+            ..NetworkOptions::default()
+        };
+        assert!(matches!(bad_proxy.client(), Err(Error::ConfigError(_))));
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        assert_eq!(
+            pem_blocks("x\n-----BEGIN CERTIFICATE-----\nAA\n-----END CERTIFICATE-----\ny").count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,213 @@
+#![allow(clippy::missing_errors_doc)]
+//! Bounded file reading shared by scanners.
+//!
+//! Files up to the size limit are read whole. Larger files are streamed as
+//! line-aligned chunks of at most the limit, so memory use stays bounded no
+//! matter how large a log-like config grows. Binary files are detected from
+//! their first bytes and skipped before anything else is read.
+
+use crate::error::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Default chunk size for streaming large files (64KB).
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of leading bytes inspected to decide whether a file is binary.
+pub const SNIFF_LEN: usize = 8 * 1024;
+
+/// Checks whether content looks binary: it contains a NUL byte, or more than 30%
+/// of the bytes are control characters other than common whitespace.
+#[must_use]
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return true;
+    }
+    let control = bytes
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0c | 0x1b))
+        .count();
+    control * 10 > bytes.len() * 3
+}
+
+/// Contents of a file read with [`read_bounded`].
+#[derive(Debug)]
+pub enum BoundedRead {
+    /// The whole file fit within the limit
+    Complete(String),
+    /// The file exceeded the limit and must be consumed chunk by chunk
+    Chunked(ChunkedReader<File>),
+    /// The file looks binary and was not read
+    Binary,
+}
+
+/// Reads a file whole if it is at most `max_bytes`, otherwise returns a chunked reader.
+///
+/// Files whose first [`SNIFF_LEN`] bytes look binary are reported as
+/// [`BoundedRead::Binary`] without reading further.
+pub fn read_bounded(path: &Path, max_bytes: usize) -> Result<BoundedRead> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+    if looks_binary(&head) {
+        tracing::debug!("Skipping binary file: {}", path.display());
+        return Ok(BoundedRead::Binary);
+    }
+
+    if usize::try_from(size).is_ok_and(|size| size <= max_bytes) {
+        file.read_to_end(&mut head)?;
+        let content = String::from_utf8(head)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        return Ok(BoundedRead::Complete(content));
+    }
+    file.seek(SeekFrom::Start(0))?;
+
+    tracing::debug!(
+        "Streaming {} ({} bytes) in chunks of {} bytes",
+        path.display(),
+        size,
+        max_bytes
+    );
+    Ok(BoundedRead::Chunked(ChunkedReader::new(file, max_bytes)))
+}
+
+/// Iterator over line-aligned chunks of a reader.
+///
+/// Each chunk holds whole lines up to `chunk_size` bytes; a single line longer than
+/// the chunk size is split. Invalid UTF-8 is replaced rather than rejected.
+#[derive(Debug)]
+pub struct ChunkedReader<R> {
+    reader: BufReader<R>,
+    chunk_size: usize,
+    /// Line that did not fit in the previous chunk
+    pending: Vec<u8>,
+}
+
+impl<R: Read> ChunkedReader<R> {
+    /// Creates a chunked reader; a zero chunk size uses [`DEFAULT_CHUNK_SIZE`]
+    pub fn new(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            chunk_size: if chunk_size == 0 {
+                DEFAULT_CHUNK_SIZE
+            } else {
+                chunk_size
+            },
+            pending: Vec::new(),
+        }
+    }
+
+    fn next_chunk(&mut self) -> std::io::Result<Option<String>> {
+        let mut chunk = std::mem::take(&mut self.pending);
+        let mut line = Vec::new();
+
+        while chunk.len() < self.chunk_size {
+            line.clear();
+            let limit = (self.chunk_size - chunk.len()) as u64;
+            let read = (&mut self.reader)
+                .take(limit)
+                .read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+
+            if line.ends_with(b"\n") || chunk.is_empty() {
+                chunk.extend_from_slice(&line);
+            } else {
+                // Keep lines whole: carry a partial line into the next chunk
+                let mut rest = Vec::new();
+                (&mut self.reader)
+                    .take(self.chunk_size as u64)
+                    .read_until(b'\n', &mut rest)?;
+                line.extend_from_slice(&rest);
+                if line.len() > self.chunk_size {
+                    let tail = line.split_off(self.chunk_size);
+                    chunk.extend_from_slice(&line);
+                    self.pending = tail;
+                } else {
+                    self.pending = line;
+                }
+                break;
+            }
+        }
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(String::from_utf8_lossy(&chunk).into_owned()))
+        }
+    }
+}
+
+impl<R: Read> Iterator for ChunkedReader<R> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().map_err(Into::into).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_are_line_aligned_and_bounded() {
+        let content = "A=1\nBB=22\nCCC=333\nDDDD=4444\n";
+        let chunks: Vec<String> = ChunkedReader::new(content.as_bytes(), 12)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(chunks, vec!["A=1\nBB=22\n", "CCC=333\n", "DDDD=4444\n"]);
+        assert!(chunks.iter().all(|c| c.len() <= 12));
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn test_long_lines_are_split() {
+        let content = "0123456789abcdef\nxy";
+        let chunks: Vec<String> = ChunkedReader::new(content.as_bytes(), 8)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert!(chunks.iter().all(|c| c.len() <= 8));
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn test_binary_detection() {
+        assert!(!looks_binary(b"OPENAI_API_KEY=sk-test\n\tindented\r\n"));
+        assert!(looks_binary(b"PK\x03\x04\x14\x00\x00\x00"));
+        assert!(looks_binary(&[0x01, 0x02, 0x03, b'a']));
+        assert!(!looks_binary(b""));
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("image.bin");
+        std::fs::write(&path, [0x89, b'P', b'N', b'G', 0x00, 0x00]).unwrap();
+        assert!(matches!(
+            read_bounded(&path, 1024).unwrap(),
+            BoundedRead::Binary
+        ));
+    }
+
+    #[test]
+    fn test_read_bounded_switches_to_chunks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join(".env");
+        std::fs::write(&path, "OPENAI_API_KEY=sk-test\nOTHER=value\n").unwrap();
+
+        assert!(matches!(
+            read_bounded(&path, 1024).unwrap(),
+            BoundedRead::Complete(content) if content.starts_with("OPENAI_API_KEY")
+        ));
+
+        let BoundedRead::Chunked(chunks) = read_bounded(&path, 24).unwrap() else {
+            panic!("expected chunked read");
+        };
+        let chunks: Vec<String> = chunks.collect::<Result<_>>().unwrap();
+        assert_eq!(chunks, vec!["OPENAI_API_KEY=sk-test\n", "OTHER=value\n"]);
+    }
+}
@@ -51,12 +51,22 @@
 //! let temp_dir = tempfile::tempdir()?;
 //! let options = ScanOptions {
 //!     home_dir: Some(temp_dir.path().to_path_buf()),
-//!     include_full_values: false,
+//!     redaction: aicred_core::RedactionMode::First8,
 //!     max_file_size: 1024 * 1024, // 1MB
 //!     only_providers: None,
 //!     exclude_providers: None,
+//!     only_value_types: None,
 //!     probe_models: false,
 //!     probe_timeout_secs: 30,
+//!     extra_roots: Vec::new(),
+//!     scan_archives: false,
+//!     detection_rules: Vec::new(),
+//!     fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+//!     scanner_settings: std::collections::HashMap::new(),
+//!     network: aicred_core::http::NetworkOptions::default(),
+//!     offline: false,
+//!     cancellation: None,
+//!     scanners: None,
 //! };
 //!
 //! // Run the scan
@@ -76,12 +86,22 @@
 //! let temp_dir = tempfile::tempdir()?;
 //! let options = ScanOptions {
 //!     home_dir: Some(temp_dir.path().to_path_buf()),
-//!     include_full_values: false,
+//!     redaction: aicred_core::RedactionMode::First8,
 //!     max_file_size: 1024 * 1024,
 //!     only_providers: None,
 //!     exclude_providers: None,
+//!     only_value_types: None,
 //!     probe_models: false,
 //!     probe_timeout_secs: 30,
+//!     extra_roots: Vec::new(),
+//!     scan_archives: false,
+//!     detection_rules: Vec::new(),
+//!     fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+//!     scanner_settings: std::collections::HashMap::new(),
+//!     network: aicred_core::http::NetworkOptions::default(),
+//!     offline: false,
+//!     cancellation: None,
+//!     scanners: None,
 //! };
 //!
 //! let result = scan(&options)?;
@@ -97,18 +117,49 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 
+pub mod archive;
+pub mod audit;
+pub mod baseline;
+pub mod budget;
+pub mod bulk;
+pub mod bundle;
+pub mod cancel;
+pub mod cleanup;
+pub mod crypto;
 pub mod discovery;
 pub mod env_resolver;
 pub mod error;
+pub mod fingerprint;
+pub mod health;
+pub mod history;
+pub mod http;
+pub mod io;
+pub mod migration;
 pub mod models;
+pub mod notifications;
 pub mod parser;
+pub mod paths;
 pub mod plugins;
+pub mod policy;
+pub mod probe;
 pub mod providers;
+pub mod resolve;
+pub mod revocation;
+pub mod rotation;
+pub mod rules;
 pub mod scanners; // Backward compatibility re-export
+pub mod secrets;
+pub mod store;
+pub mod usage;
 pub mod utils;
 
+pub use baseline::{Baseline, BaselineEntry};
+pub use bulk::{InstanceSelector, RemovalPlan};
+pub use cancel::CancellationToken;
 pub use env_resolver::{EnvResolutionResult, EnvResolver, EnvResolverBuilder, EnvVarMapping};
 pub use error::{Error, Result};
+pub use secrets::{SecretBackend, SecretBackends};
+pub use store::{ConfigStore, StoreLock};
 
 // Primary API exports (v0.2.0 - canonical types)
 pub use models::{
@@ -129,14 +180,24 @@ pub use models::{
     // Models
     Model,
     ModelCapabilities,
+    ModelEntry,
+    ModelLifecycleWarning,
+    ModelMatch,
     ModelMetadata,
     ModelPricing,
+    ModelQuery,
+    ModelRegistry,
+    ModelRequirements,
+    ModelStatus,
     // Providers
     Provider,
     ProviderCollection,
     ProviderInstance,
     RateLimit,
+    RedactionMode,
+    RiskLevel,
     // Scan
+    ScanEvent,
     ScanResult,
     ScanSummary,
     TokenCost,
@@ -156,6 +217,7 @@ pub use plugins::{
     register_builtin_plugins,
     register_builtin_providers,
     CommonConfigPlugin,
+    IssuedKey,
     // Legacy (still used internally)
     PluginRegistry,
     // Core traits
@@ -165,11 +227,14 @@ pub use plugins::{
 };
 
 // Discovery system (application-specific credential scanners)
+#[allow(deprecated)]
 pub use crate::discovery::{
-    register_builtin_scanners, ScannerConfig, ScannerPlugin, ScannerRegistry, DEFAULT_MAX_FILE_SIZE,
+    register_builtin_scanners, Finding, Scanner, ScannerConfig, ScannerContext, ScannerPlugin,
+    ScannerRegistry, DEFAULT_MAX_DEPTH, DEFAULT_MAX_FILE_SIZE,
 };
 pub use utils::provider_model_tuple::ProviderModelTuple;
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tracing::debug;
 
@@ -178,30 +243,66 @@ use tracing::debug;
 pub struct ScanOptions {
     /// Home directory to scan (defaults to user's home directory).
     pub home_dir: Option<PathBuf>,
-    /// Whether to include full key values in results (default: false for security).
-    pub include_full_values: bool,
+    /// How key values are redacted in results (default: first eight characters).
+    /// [`RedactionMode::None`] includes full values.
+    pub redaction: RedactionMode,
     /// Maximum file size to scan in bytes (default: 1MB).
     pub max_file_size: usize,
     /// Only scan specific providers (optional).
     pub only_providers: Option<Vec<String>>,
     /// Exclude specific providers (optional).
     pub exclude_providers: Option<Vec<String>>,
+    /// Only report values of these types, e.g. API keys and access tokens (optional).
+    pub only_value_types: Option<Vec<ValueType>>,
     /// Whether to probe provider instances for available models (default: false).
     pub probe_models: bool,
     /// Timeout for model probing in seconds (default: 30).
     pub probe_timeout_secs: u64,
+    /// Additional directories to scan as if they were home directories (e.g. mounted
+    /// backups or exported homes).
+    pub extra_roots: Vec<PathBuf>,
+    /// Whether to extract and scan `.zip`/`.tar`/`.tar.gz` archives that are scan
+    /// roots or sit directly inside one (default: false).
+    pub scan_archives: bool,
+    /// Custom detection rules run on every file beneath the scan roots.
+    pub detection_rules: Vec<rules::DetectionRule>,
+    /// How the `hash` of each finding is computed (default: SHA-256).
+    pub fingerprint: fingerprint::Fingerprinter,
+    /// Settings of individual scanners by scanner name, such as extra paths
+    /// for an application installed in a nonstandard location.
+    pub scanner_settings: HashMap<String, ScannerContext>,
+    /// Proxy, CA bundle, timeout and retry settings of model probing.
+    pub network: http::NetworkOptions,
+    /// Whether the scan must not touch the network, skipping model probing
+    /// even if `probe_models` is set (default: false).
+    pub offline: bool,
+    /// Token that stops the scan at the next scanner once cancelled
+    pub cancellation: Option<CancellationToken>,
+    /// Application scanners to run instead of the built-in ones, so that
+    /// embedders can add scanners of their own (default: the built-in scanners)
+    pub scanners: Option<ScannerRegistry>,
 }
 
 impl Default for ScanOptions {
     fn default() -> Self {
         Self {
             home_dir: None,
-            include_full_values: false,
+            redaction: RedactionMode::default(),
             max_file_size: DEFAULT_MAX_FILE_SIZE,
             only_providers: None,
             exclude_providers: None,
+            only_value_types: None,
             probe_models: false,
             probe_timeout_secs: 30,
+            extra_roots: Vec::new(),
+            scan_archives: false,
+            detection_rules: Vec::new(),
+            fingerprint: fingerprint::Fingerprinter::sha256(),
+            scanner_settings: HashMap::new(),
+            network: http::NetworkOptions::default(),
+            offline: false,
+            cancellation: None,
+            scanners: None,
         }
     }
 }
@@ -220,13 +321,37 @@ impl ScanOptions {
         self
     }
 
-    /// Sets whether to include full key values.
+    /// Sets how the `hash` of each finding is computed.
     #[must_use]
-    pub const fn with_full_values(mut self, include: bool) -> Self {
-        self.include_full_values = include;
+    pub const fn with_fingerprint(mut self, fingerprint: fingerprint::Fingerprinter) -> Self {
+        self.fingerprint = fingerprint;
         self
     }
 
+    /// Sets whether to include full key values, shorthand for
+    /// [`with_redaction`](Self::with_redaction) with `none` or the default mode.
+    #[must_use]
+    pub fn with_full_values(self, include: bool) -> Self {
+        self.with_redaction(if include {
+            RedactionMode::None
+        } else {
+            RedactionMode::default()
+        })
+    }
+
+    /// Sets how key values are redacted.
+    #[must_use]
+    pub const fn with_redaction(mut self, redaction: RedactionMode) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Whether full key values are kept in the results.
+    #[must_use]
+    pub const fn includes_full_values(&self) -> bool {
+        matches!(self.redaction, RedactionMode::None)
+    }
+
     /// Sets the maximum file size to scan.
     #[must_use]
     pub const fn with_max_file_size(mut self, size: usize) -> Self {
@@ -248,20 +373,118 @@ impl ScanOptions {
         self
     }
 
+    /// Sets the value types to report.
+    #[must_use]
+    pub fn with_only_value_types(mut self, value_types: Vec<ValueType>) -> Self {
+        self.only_value_types = Some(value_types);
+        self
+    }
+
+    /// Sets the settings of a scanner.
+    #[must_use]
+    pub fn with_scanner_settings(
+        mut self,
+        scanner: impl Into<String>,
+        settings: ScannerContext,
+    ) -> Self {
+        self.scanner_settings.insert(scanner.into(), settings);
+        self
+    }
+
+    /// Sets the application scanners to run instead of the built-in ones.
+    #[must_use]
+    pub fn with_scanners(mut self, scanners: ScannerRegistry) -> Self {
+        self.scanners = Some(scanners);
+        self
+    }
+
+    /// Adds an additional root directory to scan.
+    #[must_use]
+    pub fn with_extra_root(mut self, root: PathBuf) -> Self {
+        self.extra_roots.push(root);
+        self
+    }
+
+    /// Sets the additional root directories to scan.
+    #[must_use]
+    pub fn with_extra_roots(mut self, roots: Vec<PathBuf>) -> Self {
+        self.extra_roots = roots;
+        self
+    }
+
+    /// Sets whether to extract and scan archives.
+    #[must_use]
+    pub const fn with_scan_archives(mut self, scan_archives: bool) -> Self {
+        self.scan_archives = scan_archives;
+        self
+    }
+
+    /// Sets custom detection rules to run in addition to the scanners.
+    #[must_use]
+    pub fn with_detection_rules(mut self, rules: Vec<rules::DetectionRule>) -> Self {
+        self.detection_rules = rules;
+        self
+    }
+
+    /// Sets a token to cancel the scan with.
+    #[must_use]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Fails if the scan was cancelled.
+    fn check_cancelled(&self) -> Result<()> {
+        self.cancellation
+            .as_ref()
+            .map_or(Ok(()), CancellationToken::check)
+    }
+
+    /// Checks whether findings for a provider are included by the provider filters.
+    #[must_use]
+    pub fn includes_provider(&self, provider: &str) -> bool {
+        let listed = |providers: &Option<Vec<String>>| {
+            providers
+                .as_ref()
+                .map(|list| list.iter().any(|p| p.eq_ignore_ascii_case(provider)))
+        };
+        listed(&self.only_providers).unwrap_or(true)
+            && !listed(&self.exclude_providers).unwrap_or(false)
+    }
+
+    /// Checks whether findings of a value type are included by the type filter.
+    #[must_use]
+    pub fn includes_value_type(&self, value_type: &ValueType) -> bool {
+        self.only_value_types
+            .as_ref()
+            .is_none_or(|types| types.contains(value_type))
+    }
+
+    /// Gets every root to scan: the home directory followed by any extra roots,
+    /// without duplicates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined from the system.
+    pub fn scan_roots(&self) -> Result<Vec<PathBuf>> {
+        let mut roots = vec![self.get_home_dir()?];
+        for root in &self.extra_roots {
+            if !roots.contains(root) {
+                roots.push(root.clone());
+            }
+        }
+        Ok(roots)
+    }
+
     /// Gets the effective home directory (either provided or user's home).
     ///
     /// # Errors
     ///
     /// Returns an error if the home directory cannot be determined from the system.
     pub fn get_home_dir(&self) -> Result<PathBuf> {
-        self.home_dir.as_ref().map_or_else(
-            || {
-                dirs_next::home_dir().ok_or_else(|| {
-                    Error::ConfigError("Could not determine home directory".to_string())
-                })
-            },
-            |home| Ok(home.clone()),
-        )
+        self.home_dir
+            .as_ref()
+            .map_or_else(paths::home_dir, |home| Ok(home.clone()))
     }
 }
 
@@ -285,8 +508,21 @@ impl ScanOptions {
 /// # Errors
 ///
 /// Returns an error if the scan fails due to IO errors, invalid configuration, etc.
-#[allow(clippy::too_many_lines)]
 pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
+    scan_with_progress(options, |_| {})
+}
+
+/// Scans like [`scan`], reporting each scanner run and each new credential to
+/// `on_event` as the scan goes.
+///
+/// # Errors
+///
+/// Returns an error if the scan fails due to IO errors, invalid configuration, etc.
+#[allow(clippy::too_many_lines)]
+pub fn scan_with_progress(
+    options: &ScanOptions,
+    on_event: impl FnMut(ScanEvent<'_>),
+) -> Result<ScanResult> {
     // Get the home directory to scan
     let home_dir = options.get_home_dir()?;
 
@@ -294,7 +530,10 @@ pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
     let provider_registry = create_default_registry();
 
     // Create scanner registry and register available scanners (applications and providers)
-    let scanner_registry = create_default_scanner_registry()?;
+    let scanner_registry = match &options.scanners {
+        Some(scanners) => scanners.clone(),
+        None => create_default_scanner_registry()?,
+    };
 
     // Filter plugins based on options (for key validation only)
     let filtered_provider_registry = filter_registry(&provider_registry, options)?;
@@ -313,24 +552,100 @@ pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
         scan_started_at,
     );
 
-    // Run targeted scanner-specific scanning only
-    let scanner_results = scan_with_scanners(
-        &filtered_scanner_registry,
-        &filtered_provider_registry,
-        &home_dir,
-    );
+    // Honor the global ignore file and per-directory .aicredignore files
+    let scanner_config = ScannerConfig {
+        max_file_size: options.max_file_size,
+        ..ScannerConfig::for_home(&home_dir)
+    };
+
+    // Custom detection rules for the selected providers
+    let rule_matcher = rules::RuleMatcher::new(
+        &options
+            .detection_rules
+            .iter()
+            .filter(|rule| options.includes_provider(&rule.provider))
+            .cloned()
+            .collect::<Vec<_>>(),
+    )?;
+
+    // Run targeted scanner-specific scanning only, once per scan root
+    let scan_roots = options.scan_roots()?;
+    let targets = scan_targets(&scan_roots, options);
+    let on_event = std::cell::RefCell::new(on_event);
+    let total = targets.len() * filtered_scanner_registry.list().len();
+    let mut completed = 0;
+    let scanner_results = targets.iter().flat_map(|target| {
+        debug!("Scanning root: {}", target.label);
+        let mut results = scan_with_scanners(
+            &filtered_scanner_registry,
+            &filtered_provider_registry,
+            &target.dir,
+            &scanner_config,
+            &options.scanner_settings,
+            options.cancellation.as_ref(),
+            &mut |scanner| {
+                (on_event.borrow_mut())(ScanEvent::Scanner {
+                    scanner,
+                    root: &target.label,
+                    completed,
+                    total,
+                });
+                completed += 1;
+            },
+        );
+        if !rule_matcher.is_empty() {
+            let mut rule_result = scanners::ScanResult::new();
+            rule_result.add_keys(rule_matcher.scan_dir(&target.dir, &scanner_config));
+            results.push(("detection-rules".to_string(), rule_result));
+        }
+        results
+            .into_iter()
+            .map(move |(scanner_name, scan_result)| (scanner_name, target, scan_result))
+    });
 
     // Process scanner results and validate keys with provider plugins
     // Use a HashSet to track unique config instances by instance_id
     let mut seen_instances = std::collections::HashSet::new();
 
-    for (scanner_name, mut scan_result) in scanner_results {
+    for (scanner_name, target, mut scan_result) in scanner_results {
+        options.check_cancelled()?;
         debug!(
-            "Processing {} keys from scanner: {}",
+            "Processing {} keys from scanner: {} (root {})",
             scan_result.keys.len(),
-            scanner_name
+            scanner_name,
+            target.label
         );
 
+        // Report files from archives as `archive!/inner/path`
+        if let Some(archive) = &target.archive {
+            relabel_archive_paths(&mut scan_result, archive);
+        }
+
+        // Drop findings of value types that weren't asked for
+        scan_result
+            .keys
+            .retain(|key| options.includes_value_type(&key.value_type));
+        for instance in &mut scan_result.instances {
+            instance
+                .keys
+                .retain(|key| options.includes_value_type(&key.value_type));
+        }
+
+        // Record which root and scanner each finding came from
+        for key in &mut scan_result.keys {
+            key.scan_root = Some(target.label.clone());
+            key.scanner.get_or_insert_with(|| scanner_name.clone());
+        }
+        for instance in &mut scan_result.instances {
+            instance
+                .metadata
+                .insert("scan_root".to_string(), target.label.clone());
+            for key in &mut instance.keys {
+                key.scan_root = Some(target.label.clone());
+                key.scanner.get_or_insert_with(|| scanner_name.clone());
+            }
+        }
+
         // Validate discovered keys using provider plugins for confidence scoring
         for key in &mut scan_result.keys {
             if let Some(plugin) = filtered_provider_registry.get(&key.provider) {
@@ -356,6 +671,14 @@ pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
         );
         let keys_before = result.keys.len();
         result.add_keys(scan_result.keys);
+        for key in &result.keys[keys_before..] {
+            if options.includes_full_values() {
+                (on_event.borrow_mut())(ScanEvent::Key(key));
+            } else {
+                let redacted = redact_keys(vec![key.clone()], options.redaction);
+                (on_event.borrow_mut())(ScanEvent::Key(&redacted[0]));
+            }
+        }
         debug!(
             "Result now has {} keys (added {})",
             result.keys.len(),
@@ -379,13 +702,37 @@ pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
         }
     }
 
+    options.check_cancelled()?;
+
+    if scan_roots.len() > 1 {
+        let metadata = result
+            .metadata
+            .get_or_insert_with(std::collections::HashMap::new);
+        metadata.insert(
+            "scan_roots".to_string(),
+            serde_json::json!(scan_roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()),
+        );
+    }
+
+    // Record key age and assess risk now that every source of each key is known
+    for key in &mut result.keys {
+        key.refresh_timestamps();
+        key.risk = key.assess_risk();
+    }
+
     // Probe provider instances for available models if requested
-    if options.probe_models {
+    if options.probe_models && options.offline {
+        debug!("Offline scan: skipping model probing");
+    } else if options.probe_models {
         debug!("Probing provider instances for available models...");
         let probe_stats = probe_provider_instances_async(
             &mut result.config_instances,
             &filtered_provider_registry,
             options.probe_timeout_secs,
+            &options.network,
         );
 
         debug!(
@@ -411,46 +758,44 @@ pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
             "probe_failures".to_string(),
             serde_json::json!(probe_stats.probe_failures),
         );
+        metadata.insert(
+            "probe_rate_limited".to_string(),
+            serde_json::json!(probe_stats.rate_limited),
+        );
         metadata.insert(
             "probe_models_discovered".to_string(),
             serde_json::json!(probe_stats.total_models_discovered),
         );
     }
 
+    // Fingerprint the values that are still held in full
+    if options.fingerprint.algorithm() != fingerprint::HashAlgorithm::Sha256 {
+        rehash_keys(&mut result.keys, &options.fingerprint);
+        for instance in &mut result.config_instances {
+            rehash_keys(&mut instance.keys, &options.fingerprint);
+        }
+        result
+            .metadata
+            .get_or_insert_with(std::collections::HashMap::new)
+            .insert(
+                "hash_algorithm".to_string(),
+                serde_json::json!(options.fingerprint.algorithm().as_str()),
+            );
+    }
+
     // Apply selective redaction if needed
     // Always keep full values for non-sensitive fields like ModelId, but redact API keys
-    if !options.include_full_values {
+    if !options.includes_full_values() {
         let keys_before_redaction = result.keys.len();
-        result.keys = result
-            .keys
-            .into_iter()
-            .map(|key| {
-                // Keep full values for non-sensitive value types
-                use crate::models::credentials::ValueType as OldValueType;
-                let should_preserve = match &key.value_type {
-                    OldValueType::ModelId => {
-                        tracing::debug!("Preserving ModelId key: {}", key.redacted_value());
-                        true
-                    }
-                    OldValueType::Custom(name) if name == "ModelId" || name.contains("Model") => {
-                        tracing::debug!("Preserving custom Model key: {}", name);
-                        true
-                    }
-                    OldValueType::Custom(name) if name == "Temperature" || name == "BaseUrl" => {
-                        true
-                    }
-                    // Redact sensitive values like API keys
-                    _ => false,
-                };
-
-                if should_preserve {
-                    key
-                } else {
-                    tracing::trace!("Redacting key of type: {:?}", key.value_type);
-                    key.with_full_value(false)
+        result.keys = redact_keys(std::mem::take(&mut result.keys), options.redaction);
+        for instance in &mut result.config_instances {
+            instance.keys = redact_keys(std::mem::take(&mut instance.keys), options.redaction);
+            for provider_instance in instance.provider_instances.instances.values_mut() {
+                if provider_instance.has_non_empty_api_key() {
+                    provider_instance.api_key = options.redaction.apply(&provider_instance.api_key);
                 }
-            })
-            .collect();
+            }
+        }
 
         tracing::info!(
             "Redaction complete: {} keys before, {} keys after ({} ModelId keys preserved)",
@@ -470,6 +815,69 @@ pub fn scan(options: &ScanOptions) -> Result<ScanResult> {
     Ok(result)
 }
 
+/// A directory to run scanners against, possibly holding an extracted archive.
+struct ScanTarget {
+    /// Directory scanned as a home directory
+    dir: PathBuf,
+    /// Root recorded on findings
+    label: String,
+    /// Extracted archive backing `dir`, removed when the target is dropped
+    archive: Option<archive::ExtractedArchive>,
+}
+
+/// Expands scan roots into scan targets, extracting archives when enabled.
+fn scan_targets(scan_roots: &[PathBuf], options: &ScanOptions) -> Vec<ScanTarget> {
+    let limits = archive::ArchiveLimits {
+        max_entry_size: options.max_file_size as u64,
+        ..archive::ArchiveLimits::default()
+    };
+
+    let mut targets = Vec::new();
+    for root in scan_roots {
+        if !root.is_file() {
+            targets.push(ScanTarget {
+                dir: root.clone(),
+                label: root.display().to_string(),
+                archive: None,
+            });
+        }
+        if !options.scan_archives {
+            continue;
+        }
+        for archive_path in archive::find_archives(root) {
+            match archive::ExtractedArchive::extract(&archive_path, &limits) {
+                Ok(extracted) => targets.push(ScanTarget {
+                    dir: extracted.path().to_path_buf(),
+                    label: archive_path.display().to_string(),
+                    archive: Some(extracted),
+                }),
+                Err(e) => tracing::warn!("Skipping archive {}: {}", archive_path.display(), e),
+            }
+        }
+    }
+    targets
+}
+
+/// Rewrites extracted file paths in scanner results to `archive!/inner/path`.
+fn relabel_archive_paths(
+    scan_result: &mut scanners::ScanResult,
+    archive: &archive::ExtractedArchive,
+) {
+    let relabel_key = |key: &mut DiscoveredCredential| {
+        key.source_file = archive.display_path(&key.source_file);
+        for source in &mut key.sources {
+            *source = archive.display_path(source);
+        }
+    };
+
+    scan_result.keys.iter_mut().for_each(relabel_key);
+    for instance in &mut scan_result.instances {
+        instance.config_path =
+            PathBuf::from(archive.display_path(&instance.config_path.display().to_string()));
+        instance.keys.iter_mut().for_each(relabel_key);
+    }
+}
+
 /// Creates a default plugin registry with built-in plugins.
 fn create_default_registry() -> ProviderRegistry {
     register_builtin_providers()
@@ -491,11 +899,23 @@ fn scan_with_scanners(
     scanner_registry: &ScannerRegistry,
     plugin_registry: &ProviderRegistry,
     home_dir: &std::path::Path,
+    scanner_config: &ScannerConfig,
+    scanner_settings: &HashMap<String, ScannerContext>,
+    cancellation: Option<&CancellationToken>,
+    on_scanner: &mut dyn FnMut(&str),
 ) -> Vec<(String, scanners::ScanResult)> {
     let mut results = Vec::new();
+    let default_context = ScannerContext::default();
 
     for scanner_name in scanner_registry.list() {
+        if cancellation.is_some_and(CancellationToken::is_cancelled) {
+            break;
+        }
         debug!("Running scanner: {}", scanner_name);
+        on_scanner(&scanner_name);
+        let context = scanner_settings
+            .get(&scanner_name)
+            .unwrap_or(&default_context);
 
         // Create scanner-specific instances to call _with_registry methods
         let mut scan_result = scanners::ScanResult::new();
@@ -506,225 +926,91 @@ fn scan_with_scanners(
                 if let Ok(instances) =
                     scanner.scan_instances_with_registry(home_dir, Some(plugin_registry))
                 {
-                    debug!(
-                        "Scanner {} found {} instances",
-                        scanner_name,
-                        instances.len()
+                    add_scanned_instances(
+                        &scanner_name,
+                        instances,
+                        scanner_config,
+                        &mut scan_result,
                     );
-                    for instance in instances {
-                        scan_result.add_instance(instance);
-                    }
                 }
-
-                let app_paths = scanner.scan_paths(home_dir);
-                debug!(
-                    "Scanner {} found {} app paths",
-                    scanner_name,
-                    app_paths.len()
+                scan_app_paths(
+                    &scanner_name,
+                    context.scan_paths(scanner.scan_paths(home_dir), home_dir),
+                    scanner_config,
+                    context,
+                    &mut scan_result,
+                    |path, content| {
+                        scanner.parse_config_with_registry(path, content, Some(plugin_registry))
+                    },
                 );
-
-                let mut scanned_paths = std::collections::HashSet::new();
-                for path in app_paths {
-                    if path.exists() && scanned_paths.insert(path.clone()) {
-                        debug!("Scanner {} scanning path: {}", scanner_name, path.display());
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            if let Ok(result) = scanner.parse_config_with_registry(
-                                &path,
-                                &content,
-                                Some(plugin_registry),
-                            ) {
-                                debug!(
-                                    "Scanner {} found {} keys and {} instances in {}",
-                                    scanner_name,
-                                    result.keys.len(),
-                                    result.instances.len(),
-                                    path.display()
-                                );
-
-                                for key in result.keys {
-                                    debug!(
-                                        "Scanner {} adding key for provider: {} (hash: {})",
-                                        scanner_name,
-                                        key.provider,
-                                        &key.hash[..8]
-                                    );
-                                    scan_result.add_key(key);
-                                }
-
-                                for instance in result.instances {
-                                    scan_result.add_instance(instance);
-                                }
-                            }
-                        }
-                    }
-                }
             }
             "gsh" => {
                 let scanner = scanners::GshScanner;
                 if let Ok(instances) =
                     scanner.scan_instances_with_registry(home_dir, Some(plugin_registry))
                 {
-                    debug!(
-                        "Scanner {} found {} instances",
-                        scanner_name,
-                        instances.len()
+                    add_scanned_instances(
+                        &scanner_name,
+                        instances,
+                        scanner_config,
+                        &mut scan_result,
                     );
-                    for instance in instances {
-                        scan_result.add_instance(instance);
-                    }
                 }
-
-                let app_paths = scanner.scan_paths(home_dir);
-                debug!(
-                    "Scanner {} found {} app paths",
-                    scanner_name,
-                    app_paths.len()
+                scan_app_paths(
+                    &scanner_name,
+                    context.scan_paths(scanner.scan_paths(home_dir), home_dir),
+                    scanner_config,
+                    context,
+                    &mut scan_result,
+                    |path, content| {
+                        scanner.parse_config_with_registry(path, content, Some(plugin_registry))
+                    },
                 );
-
-                let mut scanned_paths = std::collections::HashSet::new();
-                for path in app_paths {
-                    if path.exists() && scanned_paths.insert(path.clone()) {
-                        debug!("Scanner {} scanning path: {}", scanner_name, path.display());
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            if let Ok(result) = scanner.parse_config_with_registry(
-                                &path,
-                                &content,
-                                Some(plugin_registry),
-                            ) {
-                                debug!(
-                                    "Scanner {} found {} keys and {} instances in {}",
-                                    scanner_name,
-                                    result.keys.len(),
-                                    result.instances.len(),
-                                    path.display()
-                                );
-
-                                for key in result.keys {
-                                    debug!(
-                                        "Scanner {} adding key for provider: {} (hash: {})",
-                                        scanner_name,
-                                        key.provider,
-                                        &key.hash[..8]
-                                    );
-                                    scan_result.add_key(key);
-                                }
-
-                                for instance in result.instances {
-                                    scan_result.add_instance(instance);
-                                }
-                            }
-                        }
-                    }
-                }
             }
             "roo-code" => {
                 let scanner = scanners::RooCodeScanner;
                 if let Ok(instances) = scanner.scan_instances(home_dir) {
-                    debug!(
-                        "Scanner {} found {} instances",
-                        scanner_name,
-                        instances.len()
+                    add_scanned_instances(
+                        &scanner_name,
+                        instances,
+                        scanner_config,
+                        &mut scan_result,
                     );
-                    for instance in instances {
-                        scan_result.add_instance(instance);
-                    }
                 }
-
-                let app_paths = scanner.scan_paths(home_dir);
-                debug!(
-                    "Scanner {} found {} app paths",
-                    scanner_name,
-                    app_paths.len()
+                scan_app_paths(
+                    &scanner_name,
+                    context.scan_paths(scanner.scan_paths(home_dir), home_dir),
+                    scanner_config,
+                    context,
+                    &mut scan_result,
+                    |path, content| {
+                        scanner.parse_config_with_registry(path, content, Some(plugin_registry))
+                    },
                 );
-
-                let mut scanned_paths = std::collections::HashSet::new();
-                for path in app_paths {
-                    if path.exists() && scanned_paths.insert(path.clone()) {
-                        debug!("Scanner {} scanning path: {}", scanner_name, path.display());
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            if let Ok(result) = scanner.parse_config_with_registry(
-                                &path,
-                                &content,
-                                Some(plugin_registry),
-                            ) {
-                                debug!(
-                                    "Scanner {} found {} keys and {} instances in {}",
-                                    scanner_name,
-                                    result.keys.len(),
-                                    result.instances.len(),
-                                    path.display()
-                                );
-
-                                for key in result.keys {
-                                    debug!(
-                                        "Scanner {} adding key for provider: {} (hash: {})",
-                                        scanner_name,
-                                        key.provider,
-                                        &key.hash[..8]
-                                    );
-                                    scan_result.add_key(key);
-                                }
-
-                                for instance in result.instances {
-                                    scan_result.add_instance(instance);
-                                }
-                            }
-                        }
-                    }
-                }
             }
             _ => {
                 // For other scanners, use the default trait methods
                 if let Some(scanner) = scanner_registry.get(&scanner_name) {
                     if let Ok(instances) = scanner.scan_instances(home_dir) {
-                        debug!(
-                            "Scanner {} found {} instances",
-                            scanner_name,
-                            instances.len()
+                        add_scanned_instances(
+                            &scanner_name,
+                            instances,
+                            scanner_config,
+                            &mut scan_result,
                         );
-                        for instance in instances {
-                            scan_result.add_instance(instance);
-                        }
                     }
-
-                    let app_paths = scanner.scan_paths(home_dir);
-                    debug!(
-                        "Scanner {} found {} app paths",
-                        scanner_name,
-                        app_paths.len()
+                    scan_app_paths(
+                        &scanner_name,
+                        context.scan_paths(scanner.scan_paths(home_dir), home_dir),
+                        scanner_config,
+                        context,
+                        &mut scan_result,
+                        |path, content| {
+                            scanner
+                                .scan_file(path, content, context)
+                                .map(scanners::ScanResult::from)
+                        },
                     );
-
-                    let mut scanned_paths = std::collections::HashSet::new();
-                    for path in app_paths {
-                        if path.exists() && scanned_paths.insert(path.clone()) {
-                            debug!("Scanner {} scanning path: {}", scanner_name, path.display());
-                            if let Ok(content) = std::fs::read_to_string(&path) {
-                                if let Ok(result) = scanner.parse_config(&path, &content) {
-                                    debug!(
-                                        "Scanner {} found {} keys and {} instances in {}",
-                                        scanner_name,
-                                        result.keys.len(),
-                                        result.instances.len(),
-                                        path.display()
-                                    );
-
-                                    for key in result.keys {
-                                        debug!(
-                                            "Scanner {} adding key for provider: {} (hash: {})",
-                                            scanner_name,
-                                            key.provider,
-                                            &key.hash[..8]
-                                        );
-                                        scan_result.add_key(key);
-                                    }
-
-                                    for instance in result.instances {
-                                        scan_result.add_instance(instance);
-                                    }
-                                }
-                            }
-                        }
-                    }
                 }
             }
         }
@@ -743,6 +1029,219 @@ fn scan_with_scanners(
 
     results
 }
+
+/// Recomputes the hash of keys holding their full value.
+fn rehash_keys(keys: &mut [DiscoveredCredential], fingerprint: &fingerprint::Fingerprinter) {
+    for key in keys {
+        if let Some(value) = key.full_value() {
+            key.hash = fingerprint.fingerprint(value);
+        }
+    }
+}
+
+/// Redacts sensitive values such as API keys, keeping model IDs, base URLs and
+/// temperatures in full.
+fn redact_keys(keys: Vec<DiscoveredCredential>, mode: RedactionMode) -> Vec<DiscoveredCredential> {
+    keys.into_iter()
+        .map(|key| {
+            // Keep full values for non-sensitive value types
+            let should_preserve = match &key.value_type {
+                ValueType::ModelId => {
+                    tracing::debug!("Preserving ModelId key: {}", key.redacted_value());
+                    true
+                }
+                ValueType::Custom(name) if name == "ModelId" || name.contains("Model") => {
+                    tracing::debug!("Preserving custom Model key: {}", name);
+                    true
+                }
+                ValueType::Custom(name) if name == "Temperature" || name == "BaseUrl" => true,
+                // Redact sensitive values like API keys
+                _ => false,
+            };
+
+            if should_preserve {
+                key
+            } else {
+                tracing::trace!("Redacting key of type: {:?}", key.value_type);
+                key.with_redaction(mode)
+            }
+        })
+        .collect()
+}
+
+/// Adds instances discovered by a scanner, skipping those whose config file is ignored.
+fn add_scanned_instances(
+    scanner_name: &str,
+    instances: Vec<ConfigInstance>,
+    scanner_config: &ScannerConfig,
+    scan_result: &mut scanners::ScanResult,
+) {
+    debug!(
+        "Scanner {} found {} instances",
+        scanner_name,
+        instances.len()
+    );
+    for mut instance in instances {
+        if scanner_config.should_scan(&instance.config_path) {
+            annotate_instance_keys(&mut instance, scanner_config);
+            scan_result.add_instance(instance);
+        } else {
+            debug!(
+                "Scanner {} skipping ignored instance: {}",
+                scanner_name,
+                instance.config_path.display()
+            );
+        }
+    }
+}
+
+/// Fills in positions and context snippets for keys parsed from `content`.
+fn annotate_scan_result(result: &mut scanners::ScanResult, content: &str, line_offset: usize) {
+    crate::discovery::annotate_positions(&mut result.keys, content, line_offset);
+    for instance in &mut result.instances {
+        crate::discovery::annotate_positions(&mut instance.keys, content, line_offset);
+    }
+}
+
+/// Fills in positions and context snippets for instance keys read from the
+/// instance's own config file.
+fn annotate_instance_keys(instance: &mut ConfigInstance, scanner_config: &ScannerConfig) {
+    let config_path = instance.config_path.display().to_string();
+    if !instance
+        .keys
+        .iter()
+        .any(|key| key.source_file == config_path && key.context_snippet.is_none())
+    {
+        return;
+    }
+    if let Ok(crate::io::BoundedRead::Complete(content)) =
+        crate::io::read_bounded(&instance.config_path, scanner_config.max_file_size)
+    {
+        for key in instance
+            .keys
+            .iter_mut()
+            .filter(|key| key.source_file == config_path)
+        {
+            crate::discovery::annotate_positions(std::slice::from_mut(key), &content, 0);
+        }
+    }
+}
+
+/// Reads and parses each existing, non-ignored path returned by a scanner,
+/// descending into directories and applying the scanner's settings.
+fn scan_app_paths<F>(
+    scanner_name: &str,
+    app_paths: Vec<PathBuf>,
+    scanner_config: &ScannerConfig,
+    settings: &ScannerContext,
+    scan_result: &mut scanners::ScanResult,
+    parse: F,
+) where
+    F: Fn(&std::path::Path, &str) -> Result<scanners::ScanResult>,
+{
+    debug!(
+        "Scanner {} found {} app paths",
+        scanner_name,
+        app_paths.len()
+    );
+
+    // Directories are expanded with bounded, cycle-safe traversal
+    let app_paths = app_paths.into_iter().flat_map(|path| {
+        if path.is_dir() {
+            crate::discovery::walk_files(&path, scanner_config)
+        } else {
+            vec![path]
+        }
+    });
+
+    let mut scanned_paths = std::collections::HashSet::new();
+    for path in app_paths {
+        if !path.exists() || !scanned_paths.insert(path.clone()) {
+            continue;
+        }
+        if !scanner_config.should_scan(&path) {
+            debug!(
+                "Scanner {} skipping ignored path: {}",
+                scanner_name,
+                path.display()
+            );
+            continue;
+        }
+
+        debug!("Scanner {} scanning path: {}", scanner_name, path.display());
+        let results = match crate::io::read_bounded(&path, scanner_config.max_file_size) {
+            Ok(crate::io::BoundedRead::Complete(content)) => parse(&path, &content)
+                .into_iter()
+                .map(|mut result| {
+                    settings.apply(&mut result, &content);
+                    annotate_scan_result(&mut result, &content, 0);
+                    for instance in &mut result.instances {
+                        crate::discovery::app_info::enrich(instance, &path, &content, &result.keys);
+                    }
+                    result
+                })
+                .collect::<Vec<_>>(),
+            // Files over the size limit are parsed chunk by chunk to bound memory
+            Ok(crate::io::BoundedRead::Chunked(chunks)) => {
+                let mut line_offset = 0;
+                chunks
+                    .map_while(Result::ok)
+                    .filter_map(|chunk| {
+                        let parsed = parse(&path, &chunk).ok().map(|mut result| {
+                            settings.apply(&mut result, &chunk);
+                            annotate_scan_result(&mut result, &chunk, line_offset);
+                            result
+                        });
+                        line_offset += chunk.matches('\n').count();
+                        parsed
+                    })
+                    .collect()
+            }
+            Ok(crate::io::BoundedRead::Binary) => {
+                debug!(
+                    "Scanner {} skipping binary file: {}",
+                    scanner_name,
+                    path.display()
+                );
+                continue;
+            }
+            Err(e) => {
+                debug!(
+                    "Scanner {} could not read {}: {}",
+                    scanner_name,
+                    path.display(),
+                    e
+                );
+                continue;
+            }
+        };
+
+        for result in results {
+            debug!(
+                "Scanner {} found {} keys and {} instances in {}",
+                scanner_name,
+                result.keys.len(),
+                result.instances.len(),
+                path.display()
+            );
+
+            for key in result.keys {
+                debug!(
+                    "Scanner {} adding key for provider: {} (hash: {})",
+                    scanner_name,
+                    key.provider,
+                    &key.hash[..8]
+                );
+                scan_result.add_key(key);
+            }
+
+            for instance in result.instances {
+                scan_result.add_instance(instance);
+            }
+        }
+    }
+}
+
 /// Statistics from probing provider instances.
 #[derive(Debug, Clone)]
 pub struct ProbeStatistics {
@@ -752,21 +1251,59 @@ pub struct ProbeStatistics {
     pub probed_successfully: usize,
     /// Number of instances that failed to probe.
     pub probe_failures: usize,
+    /// Number of the failed instances whose provider kept rate limiting them.
+    pub rate_limited: usize,
     /// Total number of models discovered across all instances.
     pub total_models_discovered: usize,
 }
 
+/// Probes a provider for the models an API key can use, without scanning.
+///
+/// `base_url` replaces the provider's default endpoint. The probe goes through
+/// `network` like the probes of a scan, is tried again while rate limited and
+/// gives up after `timeout`.
+///
+/// # Errors
+///
+/// Returns [`Error::NotFound`] for an unknown provider, or the error of the
+/// probe, such as [`Error::NetworkTimeout`].
+pub fn probe_models(
+    provider: &str,
+    api_key: &str,
+    base_url: Option<&str>,
+    network: &http::NetworkOptions,
+    timeout: std::time::Duration,
+) -> Result<Vec<ModelMetadata>> {
+    let registry = create_default_registry();
+    let plugin = registry
+        .get(provider)
+        .ok_or_else(|| Error::NotFound(format!("Unknown provider: {provider}")))?;
+    let client = network.client()?;
+    let coordinator = probe::ProbeCoordinator::new(network);
+
+    tokio::runtime::Runtime::new()?.block_on(coordinator.run(provider, || async {
+        tokio::time::timeout(
+            timeout,
+            plugin.probe_models_with(&client, api_key, base_url),
+        )
+        .await
+        .map_err(|_| Error::NetworkTimeout("probe timed out".to_string()))?
+    }))
+}
+
 /// Probes provider instances asynchronously to discover available models.
 ///
 /// This function takes a mutable slice of `ConfigInstance`s and attempts to probe
 /// each provider instance for available models using the provider's plugin.
-/// Probing is done concurrently with a timeout to ensure responsiveness.
+/// Probing is done concurrently with a timeout to ensure responsiveness, within
+/// the per-provider caps of a [`probe::ProbeCoordinator`].
 ///
 /// # Arguments
 ///
 /// * `instances` - Mutable slice of config instances to probe
 /// * `plugin_registry` - Registry containing provider plugins
 /// * `timeout_secs` - Timeout in seconds for each probe operation
+/// * `network` - Proxy, CA bundle and retry settings of the probe requests
 ///
 /// # Returns
 ///
@@ -780,6 +1317,7 @@ fn probe_provider_instances_async(
     instances: &mut [ConfigInstance],
     plugin_registry: &ProviderRegistry,
     timeout_secs: u64,
+    network: &http::NetworkOptions,
 ) -> ProbeStatistics {
     use tokio::time::{timeout, Duration};
 
@@ -787,6 +1325,7 @@ fn probe_provider_instances_async(
         total_instances: 0,
         probed_successfully: 0,
         probe_failures: 0,
+        rate_limited: 0,
         total_models_discovered: 0,
     };
 
@@ -800,6 +1339,16 @@ fn probe_provider_instances_async(
     };
 
     runtime.block_on(async {
+        let client = match network.client() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to create HTTP client for probing: {}", e);
+                return;
+            }
+        };
+
+        let coordinator = std::sync::Arc::new(probe::ProbeCoordinator::new(network));
+
         // Collect all probe tasks with their instance IDs for later lookup
         let mut probe_tasks = Vec::new();
 
@@ -831,6 +1380,8 @@ fn probe_provider_instances_async(
 
                 // Clone what we need for the async task
                 let plugin_clone = plugin.clone();
+                let client_clone = client.clone();
+                let coordinator_clone = coordinator.clone();
                 let api_key_clone = api_key.clone();
                 let base_url_clone = base_url.map(String::from);
                 let provider_name = provider_instance.provider_type.clone();
@@ -839,11 +1390,20 @@ fn probe_provider_instances_async(
 
                 // Spawn probe task with timeout
                 let task = tokio::spawn(async move {
-                    let probe_result = timeout(
-                        Duration::from_secs(timeout_secs),
-                        plugin_clone.probe_models_async(&api_key_clone, base_url_clone.as_deref()),
-                    )
-                    .await;
+                    let probe_result = coordinator_clone
+                        .run(&provider_name, || async {
+                            timeout(
+                                Duration::from_secs(timeout_secs),
+                                plugin_clone.probe_models_with(
+                                    &client_clone,
+                                    &api_key_clone,
+                                    base_url_clone.as_deref(),
+                                ),
+                            )
+                            .await
+                            .map_err(|_| Error::NetworkTimeout("probe timed out".to_string()))?
+                        })
+                        .await;
 
                     (
                         provider_instance_id,
@@ -887,7 +1447,7 @@ fn probe_provider_instances_async(
                     );
 
                     match probe_result {
-                        Ok(Ok(models)) => {
+                        Ok(models) => {
                             tracing::info!(
                                 "Successfully probed {} models from provider {} (instance: {})",
                                 models.len(),
@@ -910,12 +1470,11 @@ fn probe_provider_instances_async(
                                 provider_instance.models.len().to_string(),
                             );
                         }
-                        Ok(Err(e)) => {
+                        Err(Error::NetworkTimeout(_)) => {
                             tracing::warn!(
-                                "Failed to probe provider {} (instance: {}): {}",
+                                "Probe timeout for provider {} (instance: {})",
                                 provider_name,
-                                instance_id,
-                                e
+                                instance_id
                             );
                             stats.probe_failures += 1;
                             provider_instance
@@ -923,13 +1482,17 @@ fn probe_provider_instances_async(
                                 .insert("probe_success".to_string(), "false".to_string());
                             provider_instance
                                 .metadata
-                                .insert("probe_error".to_string(), e.to_string());
+                                .insert("probe_error".to_string(), "timeout".to_string());
                         }
-                        Err(_) => {
+                        Err(e) => {
+                            if matches!(e, Error::RateLimited { .. }) {
+                                stats.rate_limited += 1;
+                            }
                             tracing::warn!(
-                                "Probe timeout for provider {} (instance: {})",
+                                "Failed to probe provider {} (instance: {}): {}",
                                 provider_name,
-                                instance_id
+                                instance_id,
+                                e
                             );
                             stats.probe_failures += 1;
                             provider_instance
@@ -937,7 +1500,7 @@ fn probe_provider_instances_async(
                                 .insert("probe_success".to_string(), "false".to_string());
                             provider_instance
                                 .metadata
-                                .insert("probe_error".to_string(), "timeout".to_string());
+                                .insert("probe_error".to_string(), e.to_string());
                         }
                     }
                     break;
@@ -963,7 +1526,7 @@ fn filter_scanner_registry(
     // regardless of which providers are configured.
     for scanner_name in all_scanners {
         if let Some(scanner) = registry.get(&scanner_name) {
-            filtered_registry.register(scanner)?;
+            filtered_registry.register_scanner(scanner)?;
         }
     }
 
@@ -1012,8 +1575,7 @@ fn filter_registry(registry: &ProviderRegistry, options: &ScanOptions) -> Result
 ///
 /// Returns an error if the home directory cannot be determined from the system.
 pub fn default_home_dir() -> Result<PathBuf> {
-    dirs_next::home_dir()
-        .ok_or_else(|| Error::ConfigError("Could not determine home directory".to_string()))
+    paths::home_dir()
 }
 
 /// Utility function to check if a path is a configuration file.
@@ -1045,7 +1607,7 @@ mod tests {
     #[test]
     fn test_scan_options_default() {
         let options = ScanOptions::default();
-        assert!(!options.include_full_values);
+        assert!(!options.includes_full_values());
         assert_eq!(options.max_file_size, DEFAULT_MAX_FILE_SIZE);
         assert!(options.only_providers.is_none());
         assert!(options.exclude_providers.is_none());
@@ -1057,8 +1619,62 @@ mod tests {
             .with_full_values(true)
             .with_max_file_size(2048);
 
-        assert!(options.include_full_values);
+        assert!(options.includes_full_values());
         assert_eq!(options.max_file_size, 2048);
+
+        let options = options.with_redaction(RedactionMode::Last4);
+        assert!(!options.includes_full_values());
+        assert_eq!(options.redaction, RedactionMode::Last4);
+    }
+
+    #[test]
+    fn test_redaction_modes_render_values() {
+        let value = "sk-abcdefghijklmnop1234";
+        let key = DiscoveredCredential::new(
+            "openai".to_string(),
+            "/tmp/.env".to_string(),
+            ValueType::ApiKey,
+            Confidence::High,
+            value.to_string(),
+        )
+        .with_context_snippet("OPENAI_API_KEY=****1234".to_string());
+
+        assert_eq!(key.display_value(RedactionMode::None), value);
+        assert_eq!(key.display_value(RedactionMode::Mask), "********");
+        assert_eq!(key.display_value(RedactionMode::First8), "sk-abcde****");
+        assert_eq!(key.display_value(RedactionMode::Last4), "****1234");
+        assert!(key
+            .display_value(RedactionMode::Fingerprint)
+            .starts_with(&format!("sha256:{}", &key.hash[..12])));
+
+        let masked = key.clone().with_redaction(RedactionMode::Mask);
+        assert!(masked.full_value().is_none());
+        assert_eq!(masked.redacted_value(), "********");
+        assert_eq!(
+            masked.context_snippet.as_deref(),
+            Some("OPENAI_API_KEY=********")
+        );
+        let json = serde_json::to_string(&masked).unwrap();
+        assert!(!json.contains("1234"));
+        let parsed: DiscoveredCredential = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.redacted_value(), "********");
+
+        let last4 = key.clone().with_redaction(RedactionMode::Last4);
+        assert_eq!(last4.redacted_value(), "****1234");
+        assert_eq!(key.with_full_value(false).redacted_value(), "sk-abcde****");
+
+        // Values redacted before modes existed keep their prefix
+        let legacy: CredentialValue =
+            serde_json::from_str(r#"{"Redacted":{"sha256":"00","prefix":"sk-abcde"}}"#).unwrap();
+        assert!(matches!(
+            legacy,
+            CredentialValue::Redacted {
+                mode: RedactionMode::First8,
+                ..
+            }
+        ));
+        assert_eq!("last-4".parse::<RedactionMode>(), Ok(RedactionMode::Last4));
+        assert!("partial".parse::<RedactionMode>().is_err());
     }
 
     #[test]
@@ -0,0 +1,332 @@
+#![allow(clippy::missing_errors_doc)]
+//! Schema versioning and migration of stored configuration files.
+//!
+//! Every file written by [`ConfigStore`](crate::store::ConfigStore) carries a
+//! schema version header; files without one are version 0. On load, a
+//! [`ConfigMigrator`] runs the chain of [`Upgrader`]s from the file's version up
+//! to [`SCHEMA_VERSION`] and reports what changed. Files from a newer version are
+//! rejected rather than silently downgraded.
+
+use crate::error::{Error, Result};
+use crate::models::providers::Capabilities;
+use crate::store::{schema_version, INSTANCES_DIR, SCHEMA_VERSION};
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// Kind of stored configuration file, which selects its upgraders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigKind {
+    /// A provider instance file in the instances directory
+    Instance,
+    /// Tag definitions (`tags.yaml`)
+    Tags,
+    /// Tag assignments (`tag_assignments.yaml`)
+    TagAssignments,
+    /// Label assignments (`labels.yaml`)
+    Labels,
+    /// Label definitions (`labels_metadata.yaml`)
+    LabelsMetadata,
+    /// Keys revoked through `aicred revoke` (`revoked_keys.yaml`)
+    RevokedKeys,
+    /// Custom detection rules (`detection_rules.yaml`)
+    DetectionRules,
+}
+
+impl ConfigKind {
+    /// All kinds stored as a single file, with their file names.
+    pub const SINGLE_FILES: [(Self, &'static str); 6] = [
+        (Self::Tags, "tags.yaml"),
+        (Self::TagAssignments, "tag_assignments.yaml"),
+        (Self::Labels, "labels.yaml"),
+        (Self::LabelsMetadata, "labels_metadata.yaml"),
+        (Self::RevokedKeys, "revoked_keys.yaml"),
+        (Self::DetectionRules, "detection_rules.yaml"),
+    ];
+
+    /// Determines the kind of a store-relative path, if it is a known config file.
+    #[must_use]
+    pub fn from_path(name: &Path) -> Option<Self> {
+        if name.parent().is_some_and(|p| p == Path::new(INSTANCES_DIR)) {
+            return Some(Self::Instance);
+        }
+        let name = name.to_str()?;
+        Self::SINGLE_FILES
+            .iter()
+            .find(|(_, file)| *file == name)
+            .map(|(kind, _)| *kind)
+    }
+}
+
+/// What a migration changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Schema version the content was stored with
+    pub from_version: u32,
+    /// Schema version after migration
+    pub to_version: u32,
+    /// Human-readable description of each change
+    pub changes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Checks whether the content was upgraded.
+    #[must_use]
+    pub const fn is_migrated(&self) -> bool {
+        self.from_version < self.to_version
+    }
+}
+
+/// Upgrades content from one schema version to the next.
+pub trait Upgrader: Send + Sync {
+    /// Version this upgrader migrates from; it produces `source_version() + 1`.
+    fn source_version(&self) -> u32;
+
+    /// Rewrites `value` in place, describing each change in `changes`.
+    fn upgrade(&self, value: &mut Value, changes: &mut Vec<String>) -> Result<()>;
+}
+
+/// Runs the chain of upgraders for one kind of config file.
+pub struct ConfigMigrator {
+    current: u32,
+    upgraders: Vec<Box<dyn Upgrader>>,
+}
+
+impl std::fmt::Debug for ConfigMigrator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigMigrator")
+            .field("current", &self.current)
+            .field("upgraders", &self.upgraders.len())
+            .finish()
+    }
+}
+
+impl ConfigMigrator {
+    /// Creates a migrator targeting `current` with no upgraders.
+    #[must_use]
+    pub fn new(current: u32) -> Self {
+        Self {
+            current,
+            upgraders: Vec::new(),
+        }
+    }
+
+    /// Creates the migrator with the builtin upgraders for a kind of file.
+    #[must_use]
+    pub fn for_kind(kind: ConfigKind) -> Self {
+        let migrator = Self::new(SCHEMA_VERSION);
+        match kind {
+            ConfigKind::Instance => migrator.with_upgrader(Box::new(LegacyInstanceUpgrader)),
+            _ => migrator.with_upgrader(Box::new(HeaderUpgrader)),
+        }
+    }
+
+    /// Adds an upgrader.
+    #[must_use]
+    pub fn with_upgrader(mut self, upgrader: Box<dyn Upgrader>) -> Self {
+        self.upgraders.push(upgrader);
+        self
+    }
+
+    /// Parses stored content and upgrades it to the current version.
+    ///
+    /// Content from a newer schema version is rejected.
+    pub fn migrate(&self, content: &str) -> Result<(Value, MigrationReport)> {
+        let from_version = schema_version(content).unwrap_or(0);
+        if from_version > self.current {
+            return Err(Error::ConfigError(format!(
+                "schema version {from_version} is newer than the supported version {}; upgrade aicred to read it",
+                self.current
+            )));
+        }
+
+        let mut value: Value =
+            serde_yaml::from_str(content).map_err(|e| Error::SerializationError(e.to_string()))?;
+        let mut changes = Vec::new();
+        for version in from_version..self.current {
+            let upgrader = self
+                .upgraders
+                .iter()
+                .find(|u| u.source_version() == version)
+                .ok_or_else(|| {
+                    Error::ConfigError(format!("no upgrader from schema version {version}"))
+                })?;
+            upgrader.upgrade(&mut value, &mut changes)?;
+        }
+
+        Ok((
+            value,
+            MigrationReport {
+                from_version,
+                to_version: self.current,
+                changes,
+            },
+        ))
+    }
+}
+
+/// Version 0 to 1 for files whose layout did not change: only adds the header.
+struct HeaderUpgrader;
+
+impl Upgrader for HeaderUpgrader {
+    fn source_version(&self) -> u32 {
+        0
+    }
+
+    fn upgrade(&self, _value: &mut Value, changes: &mut Vec<String>) -> Result<()> {
+        changes.push("added schema version header".to_string());
+        Ok(())
+    }
+}
+
+/// Version 0 to 1 for instance files: converts the legacy `keys` list and model
+/// maps and fills in fields that older layouts omitted.
+struct LegacyInstanceUpgrader;
+
+impl LegacyInstanceUpgrader {
+    fn key(name: &str) -> Value {
+        Value::String(name.to_string())
+    }
+
+    /// Extracts the secret from a legacy `keys` entry.
+    fn legacy_key_value(entry: &Value) -> Option<String> {
+        match entry {
+            Value::String(s) => Some(s.clone()),
+            Value::Mapping(m) => m
+                .get(Self::key("api_key"))
+                .or_else(|| m.get(Self::key("value")))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        }
+    }
+}
+
+impl Upgrader for LegacyInstanceUpgrader {
+    fn source_version(&self) -> u32 {
+        0
+    }
+
+    fn upgrade(&self, value: &mut Value, changes: &mut Vec<String>) -> Result<()> {
+        let Value::Mapping(map) = value else {
+            return Err(Error::ConfigError(
+                "instance file is not a YAML mapping".to_string(),
+            ));
+        };
+
+        if let Some(keys) = map.remove(Self::key("keys")) {
+            let first = keys
+                .as_sequence()
+                .and_then(|seq| seq.first())
+                .and_then(Self::legacy_key_value);
+            if let Some(api_key) = first {
+                if !map.contains_key(Self::key("api_key")) {
+                    map.insert(Self::key("api_key"), Value::String(api_key));
+                    changes.push("moved the first entry of `keys` to `api_key`".to_string());
+                }
+            }
+            changes.push("removed legacy `keys` list".to_string());
+        }
+
+        if let Some(Value::Sequence(models)) = map.get_mut(Self::key("models")) {
+            let mut converted = false;
+            for model in models.iter_mut() {
+                if let Value::Mapping(m) = model {
+                    if let Some(id) = m.get(Self::key("model_id")).cloned() {
+                        *model = id;
+                        converted = true;
+                    }
+                }
+            }
+            if converted {
+                changes.push("converted model entries to model IDs".to_string());
+            }
+        }
+
+        for (field, default) in [
+            ("api_key", Value::String(String::new())),
+            ("models", Value::Sequence(Vec::new())),
+            (
+                "capabilities",
+                serde_yaml::to_value(Capabilities::default())
+                    .unwrap_or_else(|_| Value::Mapping(Mapping::new())),
+            ),
+        ] {
+            if !map.contains_key(Self::key(field)) {
+                map.insert(Self::key(field), default);
+                changes.push(format!("added missing `{field}`"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProviderInstance;
+    use crate::store::SCHEMA_HEADER_PREFIX;
+
+    #[test]
+    fn test_legacy_instance_is_upgraded() {
+        let legacy = r#"---
+id: "openai-prod"
+display_name: "OpenAI"
+provider_type: "openai"
+base_url: "https://api.openai.com/v1"
+keys:
+  - api_key: "sk-legacy1234567890"
+models:
+  - model_id: "gpt-4"
+  - "gpt-3.5-turbo"
+"#;
+        let (value, report) = ConfigMigrator::for_kind(ConfigKind::Instance)
+            .migrate(legacy)
+            .unwrap();
+
+        assert!(report.is_migrated());
+        assert_eq!(
+            (report.from_version, report.to_version),
+            (0, SCHEMA_VERSION)
+        );
+        assert!(report.changes.iter().any(|c| c.contains("`keys`")));
+
+        let instance: ProviderInstance = serde_yaml::from_value(value).unwrap();
+        assert_eq!(instance.api_key, "sk-legacy1234567890");
+        assert_eq!(instance.models, vec!["gpt-4", "gpt-3.5-turbo"]);
+    }
+
+    #[test]
+    fn test_current_version_is_unchanged() {
+        let content = format!("{SCHEMA_HEADER_PREFIX}{SCHEMA_VERSION}\n- name: prod\n");
+        let (value, report) = ConfigMigrator::for_kind(ConfigKind::Tags)
+            .migrate(&content)
+            .unwrap();
+
+        assert!(!report.is_migrated());
+        assert!(report.changes.is_empty());
+        assert!(value.is_sequence());
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let content = format!("{SCHEMA_HEADER_PREFIX}{}\n[]\n", SCHEMA_VERSION + 1);
+        let err = ConfigMigrator::for_kind(ConfigKind::Labels)
+            .migrate(&content)
+            .unwrap_err();
+        assert!(err.to_string().contains("newer"));
+    }
+
+    #[test]
+    fn test_kind_from_path() {
+        assert_eq!(
+            ConfigKind::from_path(Path::new("inference_services/openai-1a2b.yaml")),
+            Some(ConfigKind::Instance)
+        );
+        assert_eq!(
+            ConfigKind::from_path(Path::new("tags.yaml")),
+            Some(ConfigKind::Tags)
+        );
+        assert_eq!(ConfigKind::from_path(Path::new("models/gpt-4.yaml")), None);
+    }
+}
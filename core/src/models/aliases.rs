@@ -0,0 +1,267 @@
+//! Resolution of the different IDs tools write for the same model.
+//!
+//! One tool writes `gpt-4o`, another `openai/gpt-4o`, a third the dated
+//! snapshot `gpt-4o-2024-08-06`. [`ModelRegistry::canonical_id`] maps them to
+//! the registry's ID, trying in turn:
+//!
+//! 1. the ID itself, then an alias of an entry ([`ModelEntry::aliases`]),
+//!    ignoring case
+//! 2. the ID without a routing prefix such as `openai/` or `models/`
+//! 3. the ID without a date or `-latest` suffix, such as `-2024-08-06`,
+//!    `-20240806` or `-0613`
+//!
+//! An ID none of these finds is kept as it is.
+
+use super::providers::ProviderInstance;
+use super::registry::{ModelEntry, ModelRegistry};
+
+/// Builtin aliases: alias, registry ID. Undated IDs such as
+/// `claude-3-5-sonnet` and the dotted ones `OpenRouter` lists, such as
+/// `claude-3.5-sonnet`, are here; routing prefixes are stripped before the
+/// lookup.
+pub(super) const BUILTIN_ALIASES: &[(&str, &str)] = &[
+    ("claude-opus-4-0", "claude-opus-4-20250514"),
+    ("claude-opus-4", "claude-opus-4-20250514"),
+    ("claude-sonnet-4-0", "claude-sonnet-4-20250514"),
+    ("claude-sonnet-4", "claude-sonnet-4-20250514"),
+    ("claude-3-7-sonnet-latest", "claude-3-7-sonnet-20250219"),
+    ("claude-3-7-sonnet", "claude-3-7-sonnet-20250219"),
+    ("claude-3.7-sonnet", "claude-3-7-sonnet-20250219"),
+    ("claude-3-5-sonnet-latest", "claude-3-5-sonnet-20241022"),
+    ("claude-3-5-sonnet", "claude-3-5-sonnet-20241022"),
+    ("claude-3.5-sonnet", "claude-3-5-sonnet-20241022"),
+    ("claude-3-5-haiku-latest", "claude-3-5-haiku-20241022"),
+    ("claude-3-5-haiku", "claude-3-5-haiku-20241022"),
+    ("claude-3.5-haiku", "claude-3-5-haiku-20241022"),
+    ("claude-3-opus-latest", "claude-3-opus-20240229"),
+    ("claude-3-opus", "claude-3-opus-20240229"),
+    ("claude-3-haiku", "claude-3-haiku-20240307"),
+    ("mistral-large", "mistral-large-latest"),
+    ("mistral-small", "mistral-small-latest"),
+    ("deepseek-v3", "deepseek-chat"),
+    ("deepseek-r1", "deepseek-reasoner"),
+    ("llama-3.3-70b", "llama-3.3-70b-versatile"),
+    ("llama-3.3-70b-instruct", "llama-3.3-70b-versatile"),
+    ("llama-3.1-8b", "llama-3.1-8b-instant"),
+    ("llama-3.1-8b-instruct", "llama-3.1-8b-instant"),
+];
+
+impl ModelRegistry {
+    /// The registry ID of a model ID as some tool writes it, or the ID itself
+    /// if the registry doesn't know the model.
+    #[must_use]
+    pub fn canonical_id(&self, model_id: &str) -> String {
+        self.resolve(model_id)
+            .map_or_else(|| model_id.trim().to_string(), |entry| entry.id.clone())
+    }
+
+    /// The registry IDs of model IDs, without the duplicates this leaves,
+    /// keeping the first occurrence.
+    #[must_use]
+    pub fn canonical_ids<'a>(&self, model_ids: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for model_id in model_ids {
+            let canonical = self.canonical_id(model_id);
+            if !ids.contains(&canonical) {
+                ids.push(canonical);
+            }
+        }
+        ids
+    }
+
+    /// Looks up a model by any ID resolving to it, see [`Self::canonical_id`].
+    #[must_use]
+    pub fn resolve(&self, model_id: &str) -> Option<&ModelEntry> {
+        let model_id = model_id.trim();
+        if let Some(entry) = self.lookup(model_id) {
+            return Some(entry);
+        }
+        let unprefixed = model_id.rsplit('/').next().unwrap_or(model_id);
+        if unprefixed != model_id {
+            if let Some(entry) = self.lookup(unprefixed) {
+                return Some(entry);
+            }
+        }
+        strip_version_suffix(unprefixed).and_then(|base| self.lookup(base))
+    }
+
+    /// Looks up an ID or alias, ignoring case.
+    fn lookup(&self, id: &str) -> Option<&ModelEntry> {
+        self.get(id).or_else(|| {
+            self.entries().iter().find(|entry| {
+                entry.id.eq_ignore_ascii_case(id)
+                    || entry
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(id))
+            })
+        })
+    }
+}
+
+/// The ID without a trailing date or `-latest`, if it has one.
+fn strip_version_suffix(id: &str) -> Option<&str> {
+    if let Some(base) = id.strip_suffix("-latest") {
+        return Some(base);
+    }
+    let digits = |s: &str, n: usize| s.len() == n && s.bytes().all(|b| b.is_ascii_digit());
+    // -YYYY-MM-DD
+    if id.len() > 11 {
+        let (base, date) = id.split_at(id.len() - 11);
+        let parts: Vec<&str> = date[1..].split('-').collect();
+        if date.starts_with('-')
+            && parts.len() == 3
+            && digits(parts[0], 4)
+            && digits(parts[1], 2)
+            && digits(parts[2], 2)
+        {
+            return Some(base);
+        }
+    }
+    // -YYYYMMDD or -MMDD
+    let (base, suffix) = id.rsplit_once('-')?;
+    (digits(suffix, 8) || digits(suffix, 4)).then_some(base)
+}
+
+impl ProviderInstance {
+    /// Replaces the instance's model IDs with their registry IDs and drops
+    /// the duplicates this leaves, keeping the first occurrence.
+    pub fn canonicalize_models(&mut self, registry: &ModelRegistry) {
+        self.models = registry.canonical_ids(self.models.iter().map(String::as_str));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_id_resolves_aliases_prefixes_and_snapshots() {
+        let registry = ModelRegistry::builtin();
+        for alias in [
+            "gpt-4o",
+            "GPT-4o",
+            "openai/gpt-4o",
+            "gpt-4o-2024-08-06",
+            "openrouter/openai/gpt-4o-2024-11-20",
+        ] {
+            assert_eq!(registry.canonical_id(alias), "gpt-4o", "{alias}");
+        }
+        assert_eq!(
+            registry.canonical_id("gpt-4o-mini-2024-07-18"),
+            "gpt-4o-mini"
+        );
+        assert_eq!(
+            registry.canonical_id("claude-sonnet-4-0"),
+            "claude-sonnet-4-20250514"
+        );
+        assert_eq!(
+            registry.canonical_id("anthropic/claude-3-5-haiku-latest"),
+            "claude-3-5-haiku-20241022"
+        );
+        assert_eq!(registry.canonical_id("gpt-3.5-turbo-0125"), "gpt-3.5-turbo");
+        assert_eq!(
+            registry.canonical_id("models/gemini-2.0-flash"),
+            "gemini-2.0-flash"
+        );
+        assert_eq!(
+            registry.canonical_id("acme-ft-2024-01-01"),
+            "acme-ft-2024-01-01"
+        );
+    }
+
+    #[test]
+    fn test_canonical_id_resolves_every_form_of_the_builtin_models() {
+        let registry = ModelRegistry::builtin();
+        for (id, forms) in [
+            (
+                "claude-opus-4-20250514",
+                &["claude-opus-4", "anthropic/claude-opus-4"][..],
+            ),
+            (
+                "claude-sonnet-4-20250514",
+                &["claude-sonnet-4", "anthropic/claude-sonnet-4"],
+            ),
+            (
+                "claude-3-7-sonnet-20250219",
+                &[
+                    "claude-3-7-sonnet",
+                    "claude-3.7-sonnet",
+                    "anthropic/claude-3.7-sonnet",
+                ],
+            ),
+            (
+                "claude-3-5-sonnet-20241022",
+                &[
+                    "claude-3-5-sonnet",
+                    "claude-3.5-sonnet",
+                    "anthropic/claude-3.5-sonnet",
+                ],
+            ),
+            (
+                "claude-3-5-haiku-20241022",
+                &[
+                    "claude-3-5-haiku",
+                    "claude-3.5-haiku",
+                    "anthropic/claude-3.5-haiku",
+                ],
+            ),
+            (
+                "claude-3-opus-20240229",
+                &["claude-3-opus", "anthropic/claude-3-opus"],
+            ),
+            (
+                "claude-3-haiku-20240307",
+                &["claude-3-haiku", "anthropic/claude-3-haiku"],
+            ),
+            ("gemini-2.5-pro", &["google/gemini-2.5-pro"]),
+            (
+                "mistral-large-latest",
+                &["mistral-large", "mistralai/mistral-large"],
+            ),
+            (
+                "mistral-small-latest",
+                &["mistral-small", "mistralai/mistral-small"],
+            ),
+            ("deepseek-chat", &["deepseek-v3", "deepseek/deepseek-chat"]),
+            (
+                "deepseek-reasoner",
+                &["deepseek-r1", "deepseek/deepseek-r1"],
+            ),
+            (
+                "llama-3.3-70b-versatile",
+                &["llama-3.3-70b", "meta-llama/llama-3.3-70b-instruct"],
+            ),
+            (
+                "llama-3.1-8b-instant",
+                &["llama-3.1-8b", "meta-llama/llama-3.1-8b-instruct"],
+            ),
+        ] {
+            for form in forms {
+                assert_eq!(registry.canonical_id(form), id, "{form}");
+            }
+        }
+        assert!(registry
+            .resolve("anthropic/claude-3.5-sonnet")
+            .and_then(|entry| entry.cost(1_000_000, 0))
+            .is_some());
+    }
+
+    #[test]
+    fn test_canonicalize_models_dedupes() {
+        let mut instance = ProviderInstance::new(
+            "openai".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            String::new(),
+            vec![
+                "openai/gpt-4o".to_string(),
+                "gpt-4o-2024-08-06".to_string(),
+                "ft:gpt-4o:acme".to_string(),
+                "gpt-4o".to_string(),
+            ],
+        );
+        instance.canonicalize_models(&ModelRegistry::builtin());
+        assert_eq!(instance.models, ["gpt-4o", "ft:gpt-4o:acme"]);
+    }
+}
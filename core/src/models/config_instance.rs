@@ -27,6 +27,30 @@ pub struct ConfigInstance {
     pub provider_instances: ProviderCollection,
     /// Optional metadata (version, settings, etc.)
     pub metadata: HashMap<String, String>,
+    /// Version of the application, if detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    /// Summary of the application's settings
+    #[serde(default, skip_serializing_if = "SettingsSummary::is_empty")]
+    pub settings: SettingsSummary,
+}
+
+/// Summary of the settings of an application config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsSummary {
+    /// MCP servers configured
+    pub mcp_servers: usize,
+    /// MCP servers whose settings contain a credential
+    pub mcp_servers_with_credentials: usize,
+}
+
+impl SettingsSummary {
+    /// Checks if nothing was summarized.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.mcp_servers == 0 && self.mcp_servers_with_credentials == 0
+    }
 }
 
 impl ConfigInstance {
@@ -41,6 +65,8 @@ impl ConfigInstance {
             keys: Vec::new(),
             provider_instances: ProviderCollection::new(),
             metadata: HashMap::new(),
+            app_version: None,
+            settings: SettingsSummary::default(),
         }
     }
 
@@ -142,6 +168,26 @@ impl ConfigInstance {
         &self.metadata
     }
 
+    /// Describes the application, its version and settings, such as
+    /// "claude-desktop 0.9.2 with 3 MCP servers, 2 containing credentials".
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let mut description = self.app_name.clone();
+        if let Some(version) = &self.app_version {
+            description = format!("{description} {version}");
+        }
+        let servers = self.settings.mcp_servers;
+        if servers > 0 {
+            let plural = if servers == 1 { "" } else { "s" };
+            description = format!("{description} with {servers} MCP server{plural}");
+            let with_credentials = self.settings.mcp_servers_with_credentials;
+            if with_credentials > 0 {
+                description = format!("{description}, {with_credentials} containing credentials");
+            }
+        }
+        description
+    }
+
     /// Gets the config path as a string.
     #[must_use]
     pub fn config_path_string(&self) -> String {
@@ -340,6 +386,26 @@ mod tests {
         assert_eq!(instance.config_path_string(), "/test/path/config.json");
     }
 
+    #[test]
+    fn test_describe() {
+        let mut instance = ConfigInstance::new(
+            "claude_1".to_string(),
+            "claude-desktop".to_string(),
+            PathBuf::from("/test/claude_desktop_config.json"),
+        );
+        assert_eq!(instance.describe(), "claude-desktop");
+
+        instance.app_version = Some("0.9.2".to_string());
+        instance.settings = SettingsSummary {
+            mcp_servers: 3,
+            mcp_servers_with_credentials: 2,
+        };
+        assert_eq!(
+            instance.describe(),
+            "claude-desktop 0.9.2 with 3 MCP servers, 2 containing credentials"
+        );
+    }
+
     #[test]
     fn test_current_format_deserialization() {
         let current_content = r#"
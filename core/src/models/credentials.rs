@@ -19,10 +19,14 @@ pub struct DiscoveredCredential {
     pub value: CredentialValue,
     /// Confidence level for this discovery
     pub confidence: Confidence,
-    /// SHA-256 hash of the credential value
+    /// Fingerprint of the credential value: SHA-256 unless `ScanOptions::fingerprint`
+    /// selects HMAC-SHA256
     pub hash: String,
     /// Source file where credential was found
     pub source_file: String,
+    /// All source files where this credential was found, including `source_file`
+    #[serde(default)]
+    pub sources: Vec<String>,
     /// Line number in source file (if applicable)
     pub source_line: Option<usize>,
     /// Column number in the source file (if applicable)
@@ -35,6 +39,24 @@ pub struct DiscoveredCredential {
     pub value_type: ValueType,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
+    /// Risk level computed from confidence, storage location and file permissions
+    #[serde(default)]
+    pub risk: RiskLevel,
+    /// Oldest modification time of the source files
+    #[serde(default)]
+    pub source_modified_at: Option<DateTime<Utc>>,
+    /// When the key was created, if recorded in the source config
+    #[serde(default)]
+    pub key_created_at: Option<DateTime<Utc>>,
+    /// Scan root the credential was discovered under
+    #[serde(default)]
+    pub scan_root: Option<String>,
+    /// Scanner that found the credential, e.g. `gsh`
+    #[serde(default)]
+    pub scanner: Option<String>,
+    /// Source line containing the credential, with the value redacted
+    #[serde(default)]
+    pub context_snippet: Option<String>,
 }
 
 impl DiscoveredCredential {
@@ -55,13 +77,20 @@ impl DiscoveredCredential {
             value: CredentialValue::full(full_value),
             confidence,
             hash,
+            sources: vec![source_file.clone()],
             source_file,
             source_line: None,
             column_number: None,
             environment: Environment::UserConfig,
             discovered_at,
+            risk: RiskLevel::from_score(RiskLevel::base_score(confidence, &value_type)),
             value_type,
             metadata: None,
+            source_modified_at: None,
+            key_created_at: None,
+            scan_root: None,
+            scanner: None,
+            context_snippet: None,
         }
     }
 
@@ -82,39 +111,51 @@ impl DiscoveredCredential {
             value: CredentialValue::redact(full_value),
             confidence,
             hash,
+            sources: vec![source_file.clone()],
             source_file,
             source_line: None,
             column_number: None,
             environment: Environment::UserConfig,
             discovered_at,
+            risk: RiskLevel::from_score(RiskLevel::base_score(confidence, &value_type)),
             value_type,
             metadata: None,
+            source_modified_at: None,
+            key_created_at: None,
+            scan_root: None,
+            scanner: None,
+            context_snippet: None,
         }
     }
 
     /// Returns the redacted version of the credential value
+    ///
+    /// Full values show their last four characters; redacted values are shown
+    /// as their redaction mode left them.
     #[must_use]
     pub fn redacted_value(&self) -> String {
         match &self.value {
-            CredentialValue::Full(value) => {
-                if value.chars().count() <= 8 {
-                    let prefix: String = value.chars().take(2).collect();
-                    format!("{prefix}****")
-                } else {
-                    let last_chars: String = value
-                        .chars()
-                        .rev()
-                        .take(4)
-                        .collect::<String>()
-                        .chars()
-                        .rev()
-                        .collect();
-                    format!("****{last_chars}")
-                }
-            }
-            CredentialValue::Redacted { prefix, .. } => {
-                format!("{prefix}****")
-            }
+            CredentialValue::Full(value) => RedactionMode::Last4.apply(value),
+            CredentialValue::Redacted {
+                sha256,
+                prefix,
+                suffix,
+                mode,
+            } => match mode {
+                RedactionMode::Last4 => format!("****{suffix}"),
+                RedactionMode::Mask => MASK.to_string(),
+                RedactionMode::Fingerprint => fingerprint(sha256),
+                RedactionMode::First8 | RedactionMode::None => format!("{prefix}****"),
+            },
+        }
+    }
+
+    /// Returns the value as shown under a redaction mode
+    #[must_use]
+    pub fn display_value(&self, mode: RedactionMode) -> String {
+        match (&self.value, mode) {
+            (CredentialValue::Full(value), mode) => mode.apply(value),
+            (CredentialValue::Redacted { .. }, _) => self.redacted_value(),
         }
     }
 
@@ -129,23 +170,29 @@ impl DiscoveredCredential {
 
     /// Sets whether to include the full value (converts between Full and Redacted)
     #[must_use]
-    pub fn with_full_value(mut self, include: bool) -> Self {
-        let current_value = self.full_value();
-        match (include, current_value) {
-            (true, Some(full)) => {
-                // Already has full value
-                self.value = CredentialValue::Full(full.to_string());
-            }
-            (true, None) => {
-                // Don't have full value, can't include it - keep as redacted
-                // This is a limitation of the redacted approach
-            }
-            (false, Some(full)) => {
-                // Convert to redacted
-                self.value = CredentialValue::redact(full);
+    pub fn with_full_value(self, include: bool) -> Self {
+        if include {
+            // A redacted value can't be restored, so it is kept as it is
+            self
+        } else {
+            self.with_redaction(RedactionMode::default())
+        }
+    }
+
+    /// Redacts a full value with a redaction mode, also in the context snippet
+    #[must_use]
+    pub fn with_redaction(mut self, mode: RedactionMode) -> Self {
+        if mode == RedactionMode::None {
+            return self;
+        }
+        if let Some(full) = self.full_value().map(str::to_string) {
+            if let Some(snippet) = &mut self.context_snippet {
+                *snippet = snippet.replace(&RedactionMode::Last4.apply(&full), &mode.apply(&full));
             }
-            (false, None) => {
-                // Already redacted
+            self.value = CredentialValue::redact_with(&full, mode);
+            // Keep the configured fingerprint rather than a plain SHA-256
+            if let CredentialValue::Redacted { sha256, .. } = &mut self.value {
+                sha256.clone_from(&self.hash);
             }
         }
         self
@@ -165,6 +212,21 @@ impl DiscoveredCredential {
         self
     }
 
+    /// Sets the redacted source line shown as context for the credential
+    #[must_use]
+    pub fn with_context_snippet(mut self, snippet: String) -> Self {
+        self.context_snippet = Some(snippet);
+        self
+    }
+
+    /// Sets the source file where the credential was found
+    #[must_use]
+    pub fn with_source_file(mut self, source_file: String) -> Self {
+        self.sources = vec![source_file.clone()];
+        self.source_file = source_file;
+        self
+    }
+
     /// Sets the environment where the credential was discovered
     #[must_use]
     pub fn with_environment(mut self, environment: Environment) -> Self {
@@ -202,6 +264,83 @@ impl DiscoveredCredential {
         )
     }
 
+    /// Records an additional source file for this credential, ignoring duplicates
+    pub fn add_source(&mut self, source: &str) {
+        if self.sources.is_empty() {
+            self.sources.push(self.source_file.clone());
+        }
+        if !self.sources.iter().any(|existing| existing == source) {
+            self.sources.push(source.to_string());
+        }
+    }
+
+    /// Gets every source file of this credential, starting with `source_file`
+    pub fn source_files(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.source_file.as_str()).chain(
+            self.sources
+                .iter()
+                .map(String::as_str)
+                .filter(|source| *source != self.source_file),
+        )
+    }
+
+    /// Assesses the risk of this credential from its confidence, where it is stored,
+    /// and the permissions of the files holding it, scoring the most exposed source
+    #[must_use]
+    pub fn assess_risk(&self) -> RiskLevel {
+        let score = RiskLevel::base_score(self.confidence, &self.value_type);
+        if score == 0 {
+            return RiskLevel::Low;
+        }
+
+        let exposure = self.source_files().map(file_exposure).max().unwrap_or(0);
+        RiskLevel::from_score(score + exposure)
+    }
+
+    /// Records the oldest mtime of the source files and any creation timestamp found
+    /// in the metadata (`created_at` or `createdAt`, RFC 3339)
+    pub fn refresh_timestamps(&mut self) {
+        self.source_modified_at = self
+            .source_files()
+            .filter_map(|source| std::fs::metadata(source).and_then(|m| m.modified()).ok())
+            .min()
+            .map(DateTime::<Utc>::from);
+
+        if let Some(created_at) = self.metadata.as_ref().and_then(|metadata| {
+            ["created_at", "createdAt"]
+                .iter()
+                .find_map(|field| metadata.get(*field).and_then(|v| v.as_str()))
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        }) {
+            self.key_created_at = Some(created_at.with_timezone(&Utc));
+        }
+    }
+
+    /// Gets the best estimate of when this key was last rotated
+    #[must_use]
+    pub fn last_rotated_at(&self) -> Option<DateTime<Utc>> {
+        self.key_created_at.or(self.source_modified_at)
+    }
+
+    /// Gets the number of days since this key was last rotated
+    #[must_use]
+    pub fn age_days(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.last_rotated_at().map(|at| (now - at).num_days())
+    }
+
+    /// Checks whether this key has not been rotated in at least `days` days
+    #[must_use]
+    pub fn is_stale(&self, days: u32, now: DateTime<Utc>) -> bool {
+        self.age_days(now).is_some_and(|age| age >= i64::from(days))
+    }
+
+    /// Recomputes the risk level using [`Self::assess_risk`]
+    #[must_use]
+    pub fn with_assessed_risk(mut self) -> Self {
+        self.risk = self.assess_risk();
+        self
+    }
+
     /// Gets the source field name for backward compatibility with `DiscoveredKey`
     #[must_use]
     pub const fn source(&self) -> &String {
@@ -222,34 +361,75 @@ impl std::fmt::Display for DiscoveredCredential {
     }
 }
 
+/// Scores how exposed a credential is by the file holding it.
+fn file_exposure(path: &str) -> u8 {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_file() {
+        return 0;
+    }
+
+    // Plaintext files are riskier than environment variables or keychains
+    let mut score = 1;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode();
+        if mode & 0o004 != 0 {
+            // Full value is readable by any user on the system
+            score += 2;
+        } else if mode & 0o040 != 0 {
+            score += 1;
+        }
+    }
+    score
+}
+
 /// Credential value (full or redacted for security).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CredentialValue {
     /// Full credential value (use with caution)
     Full(String),
-    /// Redacted credential with hash and prefix
+    /// Redacted credential with hash and the characters the redaction mode keeps
     Redacted {
-        /// SHA-256 hash of the full value
+        /// Hash of the full value, as in [`DiscoveredCredential::hash`]
         sha256: String,
-        /// First few characters (for identification)
+        /// First few characters (for identification), kept by `first8`
         prefix: String,
+        /// Last four characters, kept by `last4`
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        suffix: String,
+        /// Mode the value was redacted with
+        #[serde(default)]
+        mode: RedactionMode,
     },
 }
 
 impl CredentialValue {
-    /// Creates a redacted credential value
+    /// Creates a redacted credential value keeping its first eight characters
     #[must_use]
     pub fn redact(key: &str) -> Self {
+        Self::redact_with(key, RedactionMode::First8)
+    }
+
+    /// Creates a redacted credential value keeping what a redaction mode shows
+    #[must_use]
+    pub fn redact_with(key: &str, mode: RedactionMode) -> Self {
         let hash = Sha256::digest(key.as_bytes());
-        let prefix = if key.len() >= 8 {
-            key[..8].to_string()
-        } else {
-            key.to_string()
+        let (prefix, suffix) = match mode {
+            RedactionMode::First8 | RedactionMode::None => {
+                (key.chars().take(8).collect(), String::new())
+            }
+            RedactionMode::Last4 => (String::new(), last_chars(key, 4)),
+            RedactionMode::Mask | RedactionMode::Fingerprint => (String::new(), String::new()),
         };
 
         Self::Redacted {
             sha256: hex::encode(hash),
             prefix,
+            suffix,
+            mode,
         }
     }
 
@@ -275,6 +455,92 @@ impl CredentialValue {
     }
 }
 
+/// Placeholder shown for a fully masked value
+const MASK: &str = "********";
+
+/// How key values are shown in scan results, tables and exports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    /// Full values are shown (use with caution)
+    None,
+    /// Nothing of the value is shown
+    Mask,
+    /// The last four characters are shown
+    Last4,
+    /// The first eight characters are shown
+    #[default]
+    First8,
+    /// Only a fingerprint of the SHA-256 hash is shown
+    Fingerprint,
+}
+
+impl RedactionMode {
+    /// Renders a value as this mode shows it
+    ///
+    /// Values of eight characters or fewer only keep two characters with
+    /// `last4` so that most of a short value stays hidden.
+    #[must_use]
+    pub fn apply(self, value: &str) -> String {
+        match self {
+            Self::None => value.to_string(),
+            Self::Mask => MASK.to_string(),
+            Self::Last4 if value.chars().count() <= 8 => {
+                format!("{}****", value.chars().take(2).collect::<String>())
+            }
+            Self::Last4 => format!("****{}", last_chars(value, 4)),
+            Self::First8 => format!("{}****", value.chars().take(8).collect::<String>()),
+            Self::Fingerprint => fingerprint(&hex::encode(Sha256::digest(value.as_bytes()))),
+        }
+    }
+
+    /// Name of the mode as accepted by [`FromStr`](std::str::FromStr)
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Mask => "mask",
+            Self::Last4 => "last4",
+            Self::First8 => "first8",
+            Self::Fingerprint => "fingerprint",
+        }
+    }
+}
+
+impl std::fmt::Display for RedactionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RedactionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "none" | "full" => Ok(Self::None),
+            "mask" => Ok(Self::Mask),
+            "last4" => Ok(Self::Last4),
+            "first8" => Ok(Self::First8),
+            "fingerprint" => Ok(Self::Fingerprint),
+            _ => Err(format!(
+                "Invalid redaction mode '{s}' (expected none, mask, last4, first8 or fingerprint)"
+            )),
+        }
+    }
+}
+
+/// The last `count` characters of a value
+fn last_chars(value: &str, count: usize) -> String {
+    let skip = value.chars().count().saturating_sub(count);
+    value.chars().skip(skip).collect()
+}
+
+/// Short fingerprint of a hex SHA-256 hash
+fn fingerprint(sha256: &str) -> String {
+    format!("sha256:{}", sha256.chars().take(12).collect::<String>())
+}
+
 /// Confidence level for discovered credentials.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
 pub enum Confidence {
@@ -313,6 +579,74 @@ impl std::fmt::Display for Confidence {
     }
 }
 
+/// Risk level for a discovered credential.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash, Default,
+)]
+pub enum RiskLevel {
+    /// Non-secret values or unlikely matches
+    #[default]
+    Low = 0,
+    /// Likely secret stored with restrictive permissions
+    Medium = 1,
+    /// Secret stored in plaintext with relaxed permissions or very high confidence
+    High = 2,
+    /// Secret stored in plaintext that other users can read
+    Critical = 3,
+}
+
+impl RiskLevel {
+    /// Scores the intrinsic risk of a credential before storage is considered
+    #[must_use]
+    pub const fn base_score(confidence: Confidence, value_type: &ValueType) -> u8 {
+        match value_type {
+            ValueType::ApiKey
+            | ValueType::AccessToken
+            | ValueType::SecretKey
+            | ValueType::BearerToken => confidence as u8,
+            _ => 0,
+        }
+    }
+
+    /// Maps a combined risk score to a risk level
+    #[must_use]
+    pub const fn from_score(score: u8) -> Self {
+        match score {
+            0 | 1 => Self::Low,
+            2 | 3 => Self::Medium,
+            4 => Self::High,
+            _ => Self::Critical,
+        }
+    }
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "Low"),
+            Self::Medium => write!(f, "Medium"),
+            Self::High => write!(f, "High"),
+            Self::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+impl std::str::FromStr for RiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(format!(
+                "Invalid risk level '{s}' (expected low, medium, high or critical)"
+            )),
+        }
+    }
+}
+
 /// Type of discovered value.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ValueType {
@@ -355,6 +689,33 @@ impl std::fmt::Display for ValueType {
     }
 }
 
+impl std::str::FromStr for ValueType {
+    type Err = String;
+
+    /// Parses a variant name such as `ApiKey` or its display name such as
+    /// `api key`, ignoring case, spaces, `-` and `_`. Other names are custom types.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        if name.is_empty() {
+            return Err("Value type must not be empty".to_string());
+        }
+        Ok(
+            match name.to_lowercase().replace([' ', '-', '_'], "").as_str() {
+                "apikey" => Self::ApiKey,
+                "accesstoken" => Self::AccessToken,
+                "secretkey" => Self::SecretKey,
+                "bearertoken" => Self::BearerToken,
+                "modelid" => Self::ModelId,
+                "baseurl" => Self::BaseUrl,
+                "temperature" => Self::Temperature,
+                "paralleltoolcalls" => Self::ParallelToolCalls,
+                "headers" => Self::Headers,
+                _ => Self::Custom(name.to_string()),
+            },
+        )
+    }
+}
+
 /// Environment where credential was discovered.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Environment {
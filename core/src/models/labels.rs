@@ -29,6 +29,20 @@ pub struct LabelAssignment {
     pub assigned_at: DateTime<Utc>,
     /// Who made the assignment (optional)
     pub assigned_by: Option<String>,
+    /// Targets tried in order when the primary target is unavailable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<LabelTarget>,
+    /// Capabilities of the cheapest configured model used when no target is
+    /// available (see [`ModelCapabilities::NAMES`](super::ModelCapabilities::NAMES))
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+}
+
+impl LabelAssignment {
+    /// Gets the primary target followed by the fallbacks, in the order they are tried
+    pub fn targets(&self) -> impl Iterator<Item = &LabelTarget> {
+        std::iter::once(&self.target).chain(&self.fallbacks)
+    }
 }
 
 /// Target of a label assignment.
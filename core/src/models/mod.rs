@@ -8,10 +8,15 @@
 //! Core data models for the aicred library.
 
 // ==== CONSOLIDATED MODELS (v0.2.0) ====
+pub mod aliases;
 pub mod credentials;
 pub mod labels;
 pub mod models;
+pub mod overrides;
 pub mod providers;
+pub mod registry;
+pub mod remote;
+pub mod routing;
 pub mod scan;
 
 // ==== SPECIALIZED MODELS ====
@@ -22,7 +27,8 @@ pub mod config_validator;
 
 // Credentials & Discovery
 pub use credentials::{
-    Confidence, CredentialValue, DiscoveredCredential, Environment, ValidationStatus, ValueType,
+    Confidence, CredentialValue, DiscoveredCredential, Environment, RedactionMode, RiskLevel,
+    ValidationStatus, ValueType,
 };
 
 // Labels (semantic tagging)
@@ -30,14 +36,20 @@ pub use labels::{Label, LabelAssignment, LabelTarget, LabelWithAssignments};
 
 // Models & Metadata
 pub use models::{Model, ModelCapabilities, ModelMetadata, ModelPricing, TokenCost};
+pub use overrides::ModelOverride;
+pub use registry::{
+    ModelEntry, ModelLifecycleWarning, ModelQuery, ModelRegistry, ModelRequirements, ModelStatus,
+};
+pub use remote::{RefreshOptions, RefreshReport, RefreshSource};
+pub use routing::ModelMatch;
 
 // Providers & Instances
 pub use providers::{
-    AuthMethod, Capabilities, Provider, ProviderCollection, ProviderInstance, RateLimit,
+    AuthMethod, Capabilities, Provenance, Provider, ProviderCollection, ProviderInstance, RateLimit,
 };
 
 // Scan Results
-pub use scan::{ScanResult, ScanSummary};
+pub use scan::{ScanEvent, ScanResult, ScanSummary};
 
 // Config Instance
-pub use config_instance::ConfigInstance;
+pub use config_instance::{ConfigInstance, SettingsSummary};
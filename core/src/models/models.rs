@@ -40,6 +40,46 @@ pub struct ModelCapabilities {
     pub json_mode: bool,
 }
 
+impl ModelCapabilities {
+    /// Names accepted by [`Self::supports`], besides their aliases.
+    pub const NAMES: &'static [&'static str] = &[
+        "chat",
+        "completion",
+        "embedding",
+        "function_calling",
+        "vision",
+        "json_mode",
+    ];
+
+    /// Checks whether a name, or an alias such as `tools`, is a capability.
+    #[must_use]
+    pub fn is_known(capability: &str) -> bool {
+        let all = Self {
+            chat: true,
+            completion: true,
+            embedding: true,
+            function_calling: true,
+            vision: true,
+            json_mode: true,
+        };
+        all.supports(capability)
+    }
+
+    /// Checks if a capability is supported, by name (see [`Self::NAMES`]).
+    #[must_use]
+    pub fn supports(&self, capability: &str) -> bool {
+        match capability.to_lowercase().as_str() {
+            "chat" => self.chat,
+            "completion" => self.completion,
+            "embedding" => self.embedding,
+            "function_calling" | "functions" | "tools" => self.function_calling,
+            "vision" => self.vision,
+            "json_mode" | "json" => self.json_mode,
+            _ => false,
+        }
+    }
+}
+
 /// Pricing information for a model.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ModelPricing {
@@ -153,15 +193,7 @@ impl Model {
     /// Checks if the model supports a specific capability
     #[must_use]
     pub fn has_capability(&self, capability: &str) -> bool {
-        match capability.to_lowercase().as_str() {
-            "chat" => self.capabilities.chat,
-            "completion" => self.capabilities.completion,
-            "embedding" => self.capabilities.embedding,
-            "function_calling" | "functions" | "tools" => self.capabilities.function_calling,
-            "vision" => self.capabilities.vision,
-            "json_mode" | "json" => self.capabilities.json_mode,
-            _ => false,
-        }
+        self.capabilities.supports(capability)
     }
 }
 
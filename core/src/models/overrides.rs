@@ -0,0 +1,382 @@
+//! Model registry entries added or changed by the user.
+//!
+//! Every `*.yaml` file in the `model-overrides` directory of the config
+//! directory holds one entry, a list of entries, or a list under `models:`:
+//!
+//! ```yaml
+//! models:
+//!   - id: acme-support-ft-2
+//!     provider: openai
+//!     name: Support fine-tune
+//!     capabilities: [chat, function_calling]
+//!     context_window: 128000
+//!     input_per_million: 3.75
+//!     output_per_million: 15.0
+//!     aliases: [support-bot]
+//!   - id: gpt-4o
+//!     input_per_million: 2.0
+//!     pricing_updated_at: 2026-01-15
+//!   - id: gpt-4-turbo
+//!     sunset_at: 2026-04-30
+//! ```
+//!
+//! An entry whose `id` the registry already knows only changes the fields it
+//! gives; any other entry is added and needs a `provider`. Files are applied
+//! in name order, so a later file wins over an earlier one. The model records
+//! `aicred scan --update` writes to the `models` directory are not overrides.
+
+use super::models::{ModelCapabilities, ModelPricing};
+use super::registry::{ModelEntry, ModelRegistry, ModelStatus};
+use crate::error::{Error, Result};
+use crate::paths::AppDirs;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Directory of the override files within the config directory.
+pub const OVERRIDES_DIR: &str = "model-overrides";
+
+/// An entry of an override file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelOverride {
+    /// Model ID as used in API requests
+    pub id: String,
+    /// Provider type serving the model, required for new models
+    pub provider: Option<String>,
+    /// Human-readable model name, the ID if not given for a new model
+    pub name: Option<String>,
+    /// Capability names (see [`ModelCapabilities::NAMES`])
+    pub capabilities: Option<Vec<String>>,
+    /// Context window size (in tokens)
+    pub context_window: Option<u32>,
+    /// Price per million input tokens
+    pub input_per_million: Option<f64>,
+    /// Price per million output tokens
+    pub output_per_million: Option<f64>,
+    /// Currency code of the prices, USD if not given
+    pub currency: Option<String>,
+    /// Lifecycle status
+    pub status: Option<ModelStatus>,
+    /// Day the prices were last checked
+    pub pricing_updated_at: Option<NaiveDate>,
+    /// Day the provider deprecated the model
+    pub deprecated_at: Option<NaiveDate>,
+    /// Day the provider stops serving the model
+    pub sunset_at: Option<NaiveDate>,
+    /// Further IDs resolving to the model
+    pub aliases: Option<Vec<String>>,
+}
+
+/// Layouts an override file may have.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OverrideFile {
+    Wrapped { models: Vec<ModelOverride> },
+    List(Vec<ModelOverride>),
+    Single(ModelOverride),
+}
+
+impl ModelOverride {
+    fn capabilities(&self, path: &Path) -> Result<Option<ModelCapabilities>> {
+        let Some(names) = &self.capabilities else {
+            return Ok(None);
+        };
+        let mut capabilities = ModelCapabilities::default();
+        for name in names {
+            match name.to_lowercase().as_str() {
+                "chat" => capabilities.chat = true,
+                "completion" => capabilities.completion = true,
+                "embedding" => capabilities.embedding = true,
+                "function_calling" | "functions" | "tools" => capabilities.function_calling = true,
+                "vision" => capabilities.vision = true,
+                "json_mode" | "json" => capabilities.json_mode = true,
+                _ => {
+                    return Err(invalid(
+                        path,
+                        format!("model {}: unknown capability '{name}'", self.id),
+                    ))
+                }
+            }
+        }
+        Ok(Some(capabilities))
+    }
+
+    /// Applies the override to the existing entry of its ID, or turns it
+    /// into a new entry.
+    fn apply(self, existing: Option<&ModelEntry>, path: &Path) -> Result<ModelEntry> {
+        let capabilities = self.capabilities(path)?;
+        let mut entry = if let Some(entry) = existing {
+            entry.clone()
+        } else {
+            let provider = self
+                .provider
+                .clone()
+                .ok_or_else(|| invalid(path, format!("new model {} needs a provider", self.id)))?;
+            ModelEntry {
+                id: self.id.clone(),
+                provider,
+                name: self.id.clone(),
+                capabilities: ModelCapabilities::default(),
+                context_window: None,
+                pricing: None,
+                status: ModelStatus::Active,
+                pricing_updated_at: None,
+                deprecated_at: None,
+                sunset_at: None,
+                aliases: Vec::new(),
+            }
+        };
+        if let Some(provider) = self.provider {
+            entry.provider = provider;
+        }
+        if let Some(name) = self.name {
+            entry.name = name;
+        }
+        if let Some(capabilities) = capabilities {
+            entry.capabilities = capabilities;
+        }
+        if self.context_window.is_some() {
+            entry.context_window = self.context_window;
+        }
+        if let Some(status) = self.status {
+            entry.status = status;
+        }
+        if self.deprecated_at.is_some() {
+            entry.deprecated_at = self.deprecated_at;
+        }
+        if self.sunset_at.is_some() {
+            entry.sunset_at = self.sunset_at;
+        }
+        for alias in self.aliases.into_iter().flatten() {
+            if !entry.aliases.contains(&alias) {
+                entry.aliases.push(alias);
+            }
+        }
+        if self.input_per_million.is_some()
+            || self.output_per_million.is_some()
+            || self.currency.is_some()
+        {
+            let pricing = entry.pricing.take();
+            let per_token = |per_million: Option<f64>, current: Option<f64>| {
+                per_million.map_or_else(|| current.unwrap_or(0.0), |price| price / 1_000_000.0)
+            };
+            entry.pricing = Some(ModelPricing {
+                input_cost_per_token: per_token(
+                    self.input_per_million,
+                    pricing.as_ref().map(|p| p.input_cost_per_token),
+                ),
+                output_cost_per_token: per_token(
+                    self.output_per_million,
+                    pricing.as_ref().map(|p| p.output_cost_per_token),
+                ),
+                currency: self
+                    .currency
+                    .or_else(|| pricing.map(|p| p.currency))
+                    .unwrap_or_else(|| "USD".to_string()),
+            });
+            entry.pricing_updated_at = self.pricing_updated_at;
+        } else if self.pricing_updated_at.is_some() {
+            entry.pricing_updated_at = self.pricing_updated_at;
+        }
+        Ok(entry)
+    }
+}
+
+fn invalid(path: &Path, message: String) -> Error {
+    Error::ParseFailed {
+        path: path.to_path_buf(),
+        format: "yaml".to_string(),
+        message,
+    }
+}
+
+/// Override files in `dir`, in name order.
+fn override_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let read = match std::fs::read_dir(dir) {
+        Ok(read) => read,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut files: Vec<PathBuf> = read
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Reads the entries of one override file.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not a valid override file.
+pub fn read_override_file(path: &Path) -> Result<Vec<ModelOverride>> {
+    let content = std::fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let file: OverrideFile =
+        serde_yaml::from_str(&content).map_err(|e| invalid(path, e.to_string()))?;
+    Ok(match file {
+        OverrideFile::Wrapped { models } | OverrideFile::List(models) => models,
+        OverrideFile::Single(model) => vec![model],
+    })
+}
+
+impl ModelRegistry {
+    /// Creates the current user's registry: the builtin entries, then the
+    /// model data cached by a [refresh](Self::refresh_from_remote), then the
+    /// user's override files. Invalid files are skipped with a warning.
+    #[must_use]
+    pub fn new() -> Self {
+        AppDirs::current().map_or_else(|_| Self::builtin(), |dirs| Self::for_dirs(&dirs))
+    }
+
+    /// Creates the registry of the user with the home directory `home`, as
+    /// [`Self::new`] does for the current user.
+    #[must_use]
+    pub fn for_home(home: &Path) -> Self {
+        Self::for_dirs(&AppDirs::for_home(home))
+    }
+
+    fn for_dirs(dirs: &AppDirs) -> Self {
+        Self::builtin()
+            .with_cached(dirs.cache_dir())
+            .with_overrides(&dirs.config_dir().join(OVERRIDES_DIR))
+    }
+
+    /// Applies the override files in `dir`, skipping invalid ones with a
+    /// warning.
+    #[must_use]
+    pub fn with_overrides(mut self, dir: &Path) -> Self {
+        let files = override_files(dir).unwrap_or_else(|e| {
+            tracing::warn!("Cannot read model overrides in {}: {}", dir.display(), e);
+            Vec::new()
+        });
+        for path in files {
+            if let Err(e) = self.apply_override_file(&path) {
+                tracing::warn!("Skipping model overrides: {}", e);
+            }
+        }
+        self
+    }
+
+    /// Applies the override files in `dir`, stopping at the first invalid
+    /// one, and returns the number of entries applied.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or a file cannot be read, or a file
+    /// is invalid.
+    pub fn load_overrides(&mut self, dir: &Path) -> Result<usize> {
+        let mut applied = 0;
+        for path in override_files(dir)? {
+            applied += self.apply_override_file(&path)?;
+        }
+        Ok(applied)
+    }
+
+    /// Applies one override file, all of its entries or none.
+    fn apply_override_file(&mut self, path: &Path) -> Result<usize> {
+        let entries = read_override_file(path)?
+            .into_iter()
+            .map(|model| {
+                let existing = self.get(&model.id).cloned();
+                model.apply(existing.as_ref(), path)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let applied = entries.len();
+        self.merge(entries);
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_add_and_change_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("internal.yaml"),
+            "models:\n  - id: acme-ft-1\n    provider: openai\n    aliases: [support-bot]\n    capabilities: [chat, tools]\n    input_per_million: 3.0\n    output_per_million: 12.0\n  - id: gpt-4o\n    input_per_million: 1.0\n    sunset_at: 2026-06-01\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not an override").unwrap();
+
+        assert_eq!(
+            ModelRegistry::builtin().load_overrides(dir.path()).unwrap(),
+            2
+        );
+        let registry = ModelRegistry::builtin().with_overrides(dir.path());
+        let added = registry.get("acme-ft-1").unwrap();
+        assert_eq!(added.name, "acme-ft-1");
+        assert_eq!(registry.canonical_id("corp/support-bot"), "acme-ft-1");
+        assert!(added.capabilities.function_calling);
+        assert!((added.cost(1_000_000, 0).unwrap().total_cost - 3.0).abs() < 1e-9);
+
+        let changed = registry.get("gpt-4o").unwrap();
+        let pricing = changed.pricing.as_ref().unwrap();
+        assert!((pricing.input_cost_per_token - 1.0 / 1_000_000.0).abs() < 1e-15);
+        assert!((pricing.output_cost_per_token - 10.0 / 1_000_000.0).abs() < 1e-15);
+        assert!(changed.capabilities.vision);
+        // New prices without a date are not the builtin table's
+        assert_eq!(changed.pricing_updated_at, None);
+        assert_eq!(changed.sunset_at, "2026-06-01".parse().ok());
+    }
+
+    #[test]
+    fn test_model_records_are_not_overrides() {
+        let home = tempfile::tempdir().unwrap();
+        let config_dir = AppDirs::for_home(home.path()).config_dir().to_path_buf();
+        let scanned = ModelRegistry::builtin().get("gpt-4o").unwrap().to_model();
+        std::fs::create_dir_all(config_dir.join("models")).unwrap();
+        std::fs::write(
+            config_dir.join("models").join("gpt-4o.yaml"),
+            serde_yaml::to_string(&scanned).unwrap(),
+        )
+        .unwrap();
+        std::fs::create_dir_all(config_dir.join(OVERRIDES_DIR)).unwrap();
+        std::fs::write(
+            config_dir.join(OVERRIDES_DIR).join("internal.yaml"),
+            "id: acme-ft-1\nprovider: openai\n",
+        )
+        .unwrap();
+
+        let registry = ModelRegistry::for_home(home.path());
+        assert_eq!(registry.get("acme-ft-1").unwrap().provider, "openai");
+        assert_eq!(registry.get("gpt-4o").unwrap().name, "GPT-4o");
+    }
+
+    #[test]
+    fn test_invalid_files_are_rejected_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.yaml"),
+            "- id: gpt-4o\n  name: Renamed\n- id: no-provider\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("b.yml"), "id: extra\nprovider: groq\n").unwrap();
+
+        let registry = ModelRegistry::builtin().with_overrides(dir.path());
+        assert_eq!(registry.get("gpt-4o").unwrap().name, "GPT-4o");
+        assert_eq!(registry.get("extra").unwrap().provider, "groq");
+
+        let mut strict = ModelRegistry::builtin();
+        assert!(matches!(
+            strict.load_overrides(dir.path()),
+            Err(Error::ParseFailed { .. })
+        ));
+        assert_eq!(
+            ModelRegistry::builtin()
+                .load_overrides(&dir.path().join("missing"))
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -6,6 +6,8 @@
 #![allow(clippy::struct_excessive_bools)]
 //! Provider metadata and instance configuration.
 
+use super::DiscoveredCredential;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -72,12 +74,51 @@ pub struct ProviderInstance {
     /// Additional metadata
     #[serde(default)]
     pub metadata: HashMap<String, String>,
+    /// Where the instance's key was discovered, if it came from a scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 const fn default_active() -> bool {
     true
 }
 
+/// Where a provider instance's key was originally discovered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Provenance {
+    /// Scanner that found the key, e.g. `gsh`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner: Option<String>,
+    /// Files the key was found in
+    #[serde(default)]
+    pub source_files: Vec<String>,
+    /// When the scan that found the key ran
+    pub scanned_at: DateTime<Utc>,
+}
+
+impl Provenance {
+    /// Records where a discovered key came from.
+    #[must_use]
+    pub fn from_key(key: &DiscoveredCredential, scanned_at: DateTime<Utc>) -> Self {
+        let mut provenance = Self {
+            scanner: key.scanner.clone(),
+            source_files: Vec::new(),
+            scanned_at,
+        };
+        provenance.add_sources(key);
+        provenance
+    }
+
+    /// Adds the files a key was found in, keeping the original scanner and time.
+    pub fn add_sources(&mut self, key: &DiscoveredCredential) {
+        for source in std::iter::once(&key.source_file).chain(&key.sources) {
+            if !self.source_files.contains(source) {
+                self.source_files.push(source.clone());
+            }
+        }
+    }
+}
+
 impl ProviderInstance {
     /// Gets the API key for this instance (for backward compatibility).
     ///
@@ -139,6 +180,7 @@ impl ProviderInstance {
             capabilities: Capabilities::default(),
             active: true,
             metadata: HashMap::new(),
+            provenance: None,
         }
     }
 
@@ -180,6 +222,13 @@ impl ProviderInstance {
         self
     }
 
+    /// Builder: sets where the instance's key was discovered.
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
     /// Checks if a model exists in this instance (backward compatibility).
     ///
     /// Returns true if the model ID is in the models list.
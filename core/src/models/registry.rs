@@ -0,0 +1,548 @@
+//! Registry of well-known models with their capabilities and pricing.
+//!
+//! [`ModelRegistry::builtin`] holds builtin entries for popular hosted models,
+//! so costs can be estimated and models compared without calling provider
+//! APIs. Prices are list prices in USD and may lag behind the providers'
+//! pages. [`ModelRegistry::new`] adds the user's own entries, see
+//! [`overrides`](super::overrides).
+
+use super::aliases::BUILTIN_ALIASES;
+use super::models::{Model, ModelCapabilities, ModelMetadata, ModelPricing, TokenCost};
+use super::providers::ProviderInstance;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle status of a model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelStatus {
+    /// Generally available
+    #[default]
+    Active,
+    /// Still served, but scheduled for removal
+    Deprecated,
+    /// No longer served
+    Archived,
+}
+
+/// A model known to the registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelEntry {
+    /// Model ID as used in API requests
+    pub id: String,
+    /// Provider type serving the model (matches `ProviderInstance::provider_type`)
+    pub provider: String,
+    /// Human-readable model name
+    pub name: String,
+    /// Model capabilities
+    pub capabilities: ModelCapabilities,
+    /// Context window size (in tokens)
+    pub context_window: Option<u32>,
+    /// Pricing information
+    pub pricing: Option<ModelPricing>,
+    /// Lifecycle status
+    #[serde(default)]
+    pub status: ModelStatus,
+    /// Day the pricing was last checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing_updated_at: Option<NaiveDate>,
+    /// Day the provider deprecated the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_at: Option<NaiveDate>,
+    /// Day the provider stops serving the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sunset_at: Option<NaiveDate>,
+    /// Other IDs tools write for the model, see
+    /// [`ModelRegistry::canonical_id`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl ModelEntry {
+    /// Calculates the cost of a number of input and output tokens.
+    #[must_use]
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> Option<TokenCost> {
+        self.pricing.as_ref().map(|p| {
+            let input_cost = input_tokens as f64 * p.input_cost_per_token;
+            let output_cost = output_tokens as f64 * p.output_cost_per_token;
+            TokenCost {
+                total_cost: input_cost + output_cost,
+                input_cost,
+                output_cost,
+                currency: p.currency.clone(),
+            }
+        })
+    }
+
+    /// Status of the model on `today`: archived once its sunset date has
+    /// passed and deprecated once its deprecation date has, else its status.
+    #[must_use]
+    pub fn status_on(&self, today: NaiveDate) -> ModelStatus {
+        if self.sunset_at.is_some_and(|sunset| sunset <= today) {
+            ModelStatus::Archived
+        } else if self.status == ModelStatus::Active
+            && self
+                .deprecated_at
+                .is_some_and(|deprecated| deprecated <= today)
+        {
+            ModelStatus::Deprecated
+        } else {
+            self.status
+        }
+    }
+
+    /// Converts the entry to a [`Model`].
+    #[must_use]
+    pub fn to_model(&self) -> Model {
+        Model {
+            id: self.id.clone(),
+            provider: self.provider.clone(),
+            name: self.name.clone(),
+            capabilities: self.capabilities.clone(),
+            context_window: self.context_window,
+            pricing: self.pricing.clone(),
+            metadata: ModelMetadata::default(),
+        }
+    }
+}
+
+/// A configured instance offering a model that is deprecated or archived.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelLifecycleWarning {
+    /// ID of the instance
+    pub instance_id: String,
+    /// ID of the model
+    pub model_id: String,
+    /// Status of the model, deprecated or archived
+    pub status: ModelStatus,
+    /// Day the provider stops serving the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunset_at: Option<NaiveDate>,
+}
+
+impl std::fmt::Display for ModelLifecycleWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.status, self.sunset_at) {
+            (ModelStatus::Archived, _) => write!(
+                f,
+                "{} uses {}, which is no longer served",
+                self.instance_id, self.model_id
+            ),
+            (_, Some(sunset)) => write!(
+                f,
+                "{} uses {}, which is deprecated and shuts down on {sunset}",
+                self.instance_id, self.model_id
+            ),
+            _ => write!(
+                f,
+                "{} uses {}, which is deprecated",
+                self.instance_id, self.model_id
+            ),
+        }
+    }
+}
+
+/// What a model must offer to be recommended.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelRequirements {
+    /// Capabilities the model must support (see [`ModelCapabilities::NAMES`])
+    pub capabilities: Vec<String>,
+    /// Highest acceptable price per million input tokens
+    pub max_input_cost: Option<f64>,
+    /// Highest acceptable price per million output tokens
+    pub max_output_cost: Option<f64>,
+    /// Smallest acceptable context window
+    pub min_context_window: Option<u32>,
+}
+
+impl ModelRequirements {
+    /// Checks whether an entry meets the requirements. Models without pricing
+    /// never meet a price limit.
+    #[must_use]
+    pub fn matches(&self, entry: &ModelEntry) -> bool {
+        let per_million = |cost: fn(&ModelPricing) -> f64, limit: Option<f64>| {
+            limit.is_none_or(|limit| {
+                entry
+                    .pricing
+                    .as_ref()
+                    .is_some_and(|p| cost(p) * 1_000_000.0 <= limit + f64::EPSILON)
+            })
+        };
+        self.capabilities
+            .iter()
+            .all(|capability| entry.capabilities.supports(capability))
+            && per_million(|p| p.input_cost_per_token, self.max_input_cost)
+            && per_million(|p| p.output_cost_per_token, self.max_output_cost)
+            && self
+                .min_context_window
+                .is_none_or(|min| entry.context_window.is_some_and(|window| window >= min))
+    }
+}
+
+/// Filters of [`ModelRegistry::query`]; empty filters match every entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelQuery {
+    /// Provider type serving the model
+    pub provider: Option<String>,
+    /// Capabilities the model must support (see [`ModelCapabilities::NAMES`])
+    pub capabilities: Vec<String>,
+    /// Text the model ID or name must contain, ignoring case
+    pub search: Option<String>,
+}
+
+impl ModelQuery {
+    /// Checks whether an entry matches the filters.
+    #[must_use]
+    pub fn matches(&self, entry: &ModelEntry) -> bool {
+        let search = self.search.as_deref().map(str::to_lowercase);
+        self.provider
+            .as_deref()
+            .is_none_or(|provider| entry.provider.eq_ignore_ascii_case(provider))
+            && self
+                .capabilities
+                .iter()
+                .all(|capability| entry.capabilities.supports(capability))
+            && search.is_none_or(|search| {
+                entry.id.to_lowercase().contains(&search)
+                    || entry.name.to_lowercase().contains(&search)
+            })
+    }
+}
+
+/// Day the prices of the builtin table were last checked.
+const BUILTIN_PRICING_DATE: (i32, u32, u32) = (2025, 7, 1);
+
+/// Capability flags used in the builtin table.
+const CHAT: u8 = 1;
+const TOOLS: u8 = 1 << 1;
+const VISION: u8 = 1 << 2;
+const JSON: u8 = 1 << 3;
+const EMBED: u8 = 1 << 4;
+
+/// Builtin entry: provider, ID, name, context window, USD per million input
+/// and output tokens, capabilities, status.
+type BuiltinEntry = (
+    &'static str,
+    &'static str,
+    &'static str,
+    u32,
+    f64,
+    f64,
+    u8,
+    ModelStatus,
+);
+
+#[rustfmt::skip]
+const BUILTIN: &[BuiltinEntry] = &[
+    ("openai", "gpt-4o", "GPT-4o", 128_000, 2.50, 10.00, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "gpt-4o-mini", "GPT-4o mini", 128_000, 0.15, 0.60, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "gpt-4.1", "GPT-4.1", 1_047_576, 2.00, 8.00, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "gpt-4.1-mini", "GPT-4.1 mini", 1_047_576, 0.40, 1.60, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "gpt-4.1-nano", "GPT-4.1 nano", 1_047_576, 0.10, 0.40, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "o3", "o3", 200_000, 2.00, 8.00, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "o4-mini", "o4-mini", 200_000, 1.10, 4.40, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("openai", "gpt-4-turbo", "GPT-4 Turbo", 128_000, 10.00, 30.00, CHAT | TOOLS | VISION | JSON, ModelStatus::Deprecated),
+    ("openai", "gpt-3.5-turbo", "GPT-3.5 Turbo", 16_385, 0.50, 1.50, CHAT | TOOLS | JSON, ModelStatus::Deprecated),
+    ("openai", "text-embedding-3-small", "Text Embedding 3 Small", 8_191, 0.02, 0.0, EMBED, ModelStatus::Active),
+    ("openai", "text-embedding-3-large", "Text Embedding 3 Large", 8_191, 0.13, 0.0, EMBED, ModelStatus::Active),
+    ("anthropic", "claude-opus-4-20250514", "Claude Opus 4", 200_000, 15.00, 75.00, CHAT | TOOLS | VISION, ModelStatus::Active),
+    ("anthropic", "claude-sonnet-4-20250514", "Claude Sonnet 4", 200_000, 3.00, 15.00, CHAT | TOOLS | VISION, ModelStatus::Active),
+    ("anthropic", "claude-3-7-sonnet-20250219", "Claude 3.7 Sonnet", 200_000, 3.00, 15.00, CHAT | TOOLS | VISION, ModelStatus::Active),
+    ("anthropic", "claude-3-5-sonnet-20241022", "Claude 3.5 Sonnet", 200_000, 3.00, 15.00, CHAT | TOOLS | VISION, ModelStatus::Deprecated),
+    ("anthropic", "claude-3-5-haiku-20241022", "Claude 3.5 Haiku", 200_000, 0.80, 4.00, CHAT | TOOLS, ModelStatus::Active),
+    ("anthropic", "claude-3-opus-20240229", "Claude 3 Opus", 200_000, 15.00, 75.00, CHAT | TOOLS | VISION, ModelStatus::Deprecated),
+    ("anthropic", "claude-3-haiku-20240307", "Claude 3 Haiku", 200_000, 0.25, 1.25, CHAT | TOOLS | VISION, ModelStatus::Active),
+    ("gemini", "gemini-2.5-pro", "Gemini 2.5 Pro", 1_048_576, 1.25, 10.00, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("gemini", "gemini-2.5-flash", "Gemini 2.5 Flash", 1_048_576, 0.30, 2.50, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("gemini", "gemini-2.0-flash", "Gemini 2.0 Flash", 1_048_576, 0.10, 0.40, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("gemini", "gemini-1.5-pro", "Gemini 1.5 Pro", 2_097_152, 1.25, 5.00, CHAT | TOOLS | VISION | JSON, ModelStatus::Deprecated),
+    ("groq", "llama-3.3-70b-versatile", "Llama 3.3 70B Versatile", 131_072, 0.59, 0.79, CHAT | TOOLS | JSON, ModelStatus::Active),
+    ("groq", "llama-3.1-8b-instant", "Llama 3.1 8B Instant", 131_072, 0.05, 0.08, CHAT | TOOLS | JSON, ModelStatus::Active),
+    ("mistral", "mistral-large-latest", "Mistral Large", 131_072, 2.00, 6.00, CHAT | TOOLS | JSON, ModelStatus::Active),
+    ("mistral", "mistral-small-latest", "Mistral Small", 131_072, 0.10, 0.30, CHAT | TOOLS | VISION | JSON, ModelStatus::Active),
+    ("deepseek", "deepseek-chat", "DeepSeek V3", 65_536, 0.27, 1.10, CHAT | TOOLS | JSON, ModelStatus::Active),
+    ("deepseek", "deepseek-reasoner", "DeepSeek R1", 65_536, 0.55, 2.19, CHAT | JSON, ModelStatus::Active),
+];
+
+/// Registry of models, keyed by model ID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRegistry {
+    entries: Vec<ModelEntry>,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelRegistry {
+    /// Creates a registry with only the builtin entries, see [`Self::new`]
+    /// for one with the user's overrides.
+    #[must_use]
+    pub fn builtin() -> Self {
+        let (year, month, day) = BUILTIN_PRICING_DATE;
+        let pricing_updated_at = NaiveDate::from_ymd_opt(year, month, day);
+        let entries = BUILTIN
+            .iter()
+            .map(
+                |&(provider, id, name, context, input, output, caps, status)| ModelEntry {
+                    id: id.to_string(),
+                    provider: provider.to_string(),
+                    name: name.to_string(),
+                    capabilities: ModelCapabilities {
+                        chat: caps & CHAT != 0,
+                        completion: caps & CHAT != 0,
+                        embedding: caps & EMBED != 0,
+                        function_calling: caps & TOOLS != 0,
+                        vision: caps & VISION != 0,
+                        json_mode: caps & JSON != 0,
+                    },
+                    context_window: Some(context),
+                    pricing: Some(ModelPricing {
+                        input_cost_per_token: input / 1_000_000.0,
+                        output_cost_per_token: output / 1_000_000.0,
+                        currency: "USD".to_string(),
+                    }),
+                    status,
+                    pricing_updated_at,
+                    deprecated_at: None,
+                    sunset_at: None,
+                    aliases: BUILTIN_ALIASES
+                        .iter()
+                        .filter(|(_, target)| *target == id)
+                        .map(|(alias, _)| (*alias).to_string())
+                        .collect(),
+                },
+            )
+            .collect();
+        Self { entries }
+    }
+
+    /// Creates a registry without entries.
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Gets all entries.
+    #[must_use]
+    pub fn entries(&self) -> &[ModelEntry] {
+        &self.entries
+    }
+
+    /// Looks up a model by ID.
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&ModelEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Gets the entries served by a provider type.
+    pub fn for_provider<'a>(&'a self, provider: &'a str) -> impl Iterator<Item = &'a ModelEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.provider == provider)
+    }
+
+    /// Gets the entries matching a query, in registry order.
+    #[must_use]
+    pub fn query(&self, query: &ModelQuery) -> Vec<&ModelEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .collect()
+    }
+
+    /// Gets the active entries meeting the requirements, cheapest first by
+    /// the combined input and output price, then by largest context window.
+    #[must_use]
+    pub fn recommend(&self, requirements: &ModelRequirements) -> Vec<&ModelEntry> {
+        let price = |entry: &ModelEntry| {
+            entry.pricing.as_ref().map_or(f64::INFINITY, |p| {
+                p.input_cost_per_token + p.output_cost_per_token
+            })
+        };
+        let mut entries: Vec<&ModelEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.status == ModelStatus::Active && requirements.matches(entry))
+            .collect();
+        entries.sort_by(|a, b| {
+            price(a)
+                .total_cmp(&price(b))
+                .then_with(|| b.context_window.cmp(&a.context_window))
+        });
+        entries
+    }
+
+    /// Finds the models of `instances` that are deprecated or archived on
+    /// `today`. Models the registry doesn't know are not reported.
+    #[must_use]
+    pub fn lifecycle_warnings<'a>(
+        &self,
+        instances: impl IntoIterator<Item = &'a ProviderInstance>,
+        today: NaiveDate,
+    ) -> Vec<ModelLifecycleWarning> {
+        let mut warnings = Vec::new();
+        for instance in instances {
+            for model_id in &instance.models {
+                let Some(entry) = self.resolve(model_id) else {
+                    continue;
+                };
+                let status = entry.status_on(today);
+                if status != ModelStatus::Active {
+                    warnings.push(ModelLifecycleWarning {
+                        instance_id: instance.id.clone(),
+                        model_id: model_id.clone(),
+                        status,
+                        sunset_at: entry.sunset_at,
+                    });
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Adds an entry, replacing any entry with the same ID.
+    pub fn insert(&mut self, entry: ModelEntry) {
+        match self.entries.iter_mut().find(|e| e.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_entries_have_unique_ids_and_pricing() {
+        let registry = ModelRegistry::builtin();
+        let mut ids: Vec<&str> = registry.entries().iter().map(|e| e.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), registry.entries().len());
+        assert!(registry.entries().iter().all(|e| e.pricing.is_some()));
+        assert!(registry.for_provider("anthropic").count() > 0);
+    }
+
+    #[test]
+    fn test_cost_of_a_million_tokens() {
+        let registry = ModelRegistry::builtin();
+        let cost = registry
+            .get("gpt-4o")
+            .unwrap()
+            .cost(1_000_000, 200_000)
+            .unwrap();
+        assert!((cost.input_cost - 2.50).abs() < 1e-9);
+        assert!((cost.output_cost - 2.00).abs() < 1e-9);
+        assert!((cost.total_cost - 4.50).abs() < 1e-9);
+        assert_eq!(cost.currency, "USD");
+    }
+
+    #[test]
+    fn test_recommend_filters_and_ranks_by_price() {
+        let registry = ModelRegistry::builtin();
+        let requirements = ModelRequirements {
+            capabilities: vec!["vision".to_string(), "tools".to_string()],
+            max_input_cost: Some(1.0),
+            ..ModelRequirements::default()
+        };
+        let ids: Vec<&str> = registry
+            .recommend(&requirements)
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect();
+        // Ties on price go to the larger context window
+        assert_eq!(
+            &ids[..3],
+            &["mistral-small-latest", "gemini-2.0-flash", "gpt-4.1-nano"]
+        );
+        assert!(ids.contains(&"gpt-4o-mini"));
+        assert!(!ids.contains(&"gpt-4o"));
+        // No embeddings model calls tools, nor is a deprecated one recommended
+        assert!(!ids.contains(&"text-embedding-3-small"));
+        assert!(!ids.contains(&"gpt-3.5-turbo"));
+    }
+
+    #[test]
+    fn test_query_filters_by_provider_capability_and_text() {
+        let registry = ModelRegistry::builtin();
+        let ids = |query: &ModelQuery| -> Vec<String> {
+            registry
+                .query(query)
+                .iter()
+                .map(|entry| entry.id.clone())
+                .collect()
+        };
+
+        let vision = ids(&ModelQuery {
+            provider: Some("Anthropic".to_string()),
+            capabilities: vec!["vision".to_string()],
+            ..ModelQuery::default()
+        });
+        assert!(vision.contains(&"claude-sonnet-4-20250514".to_string()));
+        assert!(!vision.contains(&"claude-3-5-haiku-20241022".to_string()));
+
+        let search = ids(&ModelQuery {
+            search: Some("FLASH".to_string()),
+            ..ModelQuery::default()
+        });
+        assert_eq!(search, ["gemini-2.5-flash", "gemini-2.0-flash"]);
+        assert_eq!(ids(&ModelQuery::default()).len(), registry.entries().len());
+    }
+
+    #[test]
+    fn test_lifecycle_warnings_follow_status_and_dates() {
+        let day = |d: &str| d.parse::<NaiveDate>().unwrap();
+        let mut registry = ModelRegistry::builtin();
+        let mut sunsetting = registry.get("gpt-4o").unwrap().clone();
+        sunsetting.deprecated_at = Some(day("2026-01-01"));
+        sunsetting.sunset_at = Some(day("2026-06-01"));
+        registry.insert(sunsetting);
+
+        let instance = ProviderInstance::new(
+            "openai-work".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            String::new(),
+            vec![
+                "gpt-4o".to_string(),
+                "gpt-3.5-turbo".to_string(),
+                "gpt-4o-mini".to_string(),
+                "internal-model".to_string(),
+            ],
+        );
+
+        assert_eq!(
+            registry
+                .lifecycle_warnings([&instance], day("2025-12-01"))
+                .len(),
+            1
+        );
+        let warnings = registry.lifecycle_warnings([&instance], day("2026-03-01"));
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].status, ModelStatus::Deprecated);
+        assert_eq!(
+            warnings[0].to_string(),
+            "openai-work uses gpt-4o, which is deprecated and shuts down on 2026-06-01"
+        );
+        let warnings = registry.lifecycle_warnings([&instance], day("2026-06-01"));
+        assert_eq!(warnings[0].status, ModelStatus::Archived);
+    }
+
+    #[test]
+    fn test_insert_replaces_by_id() {
+        let mut registry = ModelRegistry::empty();
+        let mut entry = ModelRegistry::builtin().get("gpt-4o").unwrap().clone();
+        registry.insert(entry.clone());
+        entry.name = "Internal GPT-4o".to_string();
+        registry.insert(entry);
+        assert_eq!(registry.entries().len(), 1);
+        assert_eq!(registry.get("gpt-4o").unwrap().name, "Internal GPT-4o");
+    }
+}
@@ -0,0 +1,457 @@
+//! Model data fetched from remote sources into the [`ModelRegistry`].
+//!
+//! [`ModelRegistry::refresh_from_remote`] pulls current models and pricing
+//! from `OpenRouter`'s public models API, and the model lists of any provider
+//! instances given, into [`CACHE_FILE`] in the cache directory. The cache is
+//! reused until its [`RefreshOptions::ttl`] runs out, and
+//! [`ModelRegistry::with_cached`] merges it over the builtin entries without
+//! touching the network.
+//!
+//! Every `OpenRouter` model is added under the `openrouter` provider by its
+//! `OpenRouter` ID, such as `openai/gpt-4o`. Where that names a builtin entry
+//! of the vendor, such as `gpt-4o`, the builtin entry also takes the current
+//! price and context window.
+
+use super::models::{ModelCapabilities, ModelPricing};
+use super::providers::ProviderInstance;
+use super::registry::{ModelEntry, ModelRegistry, ModelStatus};
+use crate::error::{Error, Result};
+use crate::http::NetworkOptions;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Name of the cache file in the cache directory.
+pub const CACHE_FILE: &str = "models.json";
+
+/// How long fetched model data is used before it is fetched again.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_hours(24);
+
+/// `OpenRouter`'s public models API, which needs no key.
+pub const OPENROUTER_MODELS_URL: &str = "https://openrouter.ai/api/v1/models";
+
+/// `OpenRouter` vendor prefixes of the provider types in the builtin table.
+const OPENROUTER_VENDORS: &[(&str, &str)] = &[
+    ("openai", "openai"),
+    ("anthropic", "anthropic"),
+    ("google", "gemini"),
+    ("mistralai", "mistral"),
+    ("deepseek", "deepseek"),
+];
+
+/// Where and how to refresh the registry.
+#[derive(Debug, Clone)]
+pub struct RefreshOptions {
+    /// Cache file of the fetched data.
+    pub cache_path: PathBuf,
+    /// How long the cache is used before fetching again.
+    pub ttl: Duration,
+    /// Fetch even if the cache is still fresh.
+    pub force: bool,
+    /// Never fetch; use the cache, however old.
+    pub offline: bool,
+    /// URL of the `OpenRouter` models API.
+    pub openrouter_url: String,
+    /// Instances, with resolved API keys, whose model lists are added.
+    pub instances: Vec<ProviderInstance>,
+    /// Proxy, CA bundle and timeout of the requests.
+    pub network: NetworkOptions,
+}
+
+impl RefreshOptions {
+    /// Options caching in `cache_dir` with the default TTL.
+    #[must_use]
+    pub fn new(cache_dir: &Path) -> Self {
+        Self {
+            cache_path: cache_dir.join(CACHE_FILE),
+            ttl: DEFAULT_CACHE_TTL,
+            force: false,
+            offline: false,
+            openrouter_url: OPENROUTER_MODELS_URL.to_string(),
+            instances: Vec::new(),
+            network: NetworkOptions::default(),
+        }
+    }
+}
+
+/// Where refreshed entries came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshSource {
+    /// Fetched just now
+    Remote,
+    /// A cache within its TTL
+    Cache,
+    /// An expired cache, because fetching failed or was not allowed
+    StaleCache,
+}
+
+/// Outcome of a refresh.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshReport {
+    /// Where the entries came from
+    pub source: RefreshSource,
+    /// When the entries were fetched
+    pub fetched_at: DateTime<Utc>,
+    /// Entries merged into the registry
+    pub entries: usize,
+    /// Sources that could not be fetched
+    pub warnings: Vec<String>,
+}
+
+/// Contents of the cache file.
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteCache {
+    fetched_at: DateTime<Utc>,
+    entries: Vec<ModelEntry>,
+}
+
+impl RemoteCache {
+    fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content)
+            .map_err(|e| {
+                tracing::warn!("Ignoring unreadable model cache {}: {}", path.display(), e);
+            })
+            .ok()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        (Utc::now() - self.fetched_at)
+            .to_std()
+            .is_ok_and(|age| age < ttl)
+    }
+}
+
+impl ModelRegistry {
+    /// Merges current model data over the entries, from the cache while it
+    /// is fresh, else from `OpenRouter` and the instances' providers.
+    ///
+    /// When fetching fails, or `offline` is set, an expired cache is used.
+    /// A provider instance whose models cannot be listed only adds a warning.
+    pub fn refresh_from_remote(&mut self, options: &RefreshOptions) -> Result<RefreshReport> {
+        let cached = RemoteCache::load(&options.cache_path);
+        if let Some(cache) = cached
+            .as_ref()
+            .filter(|cache| options.offline || (!options.force && cache.is_fresh(options.ttl)))
+        {
+            let source = if cache.is_fresh(options.ttl) {
+                RefreshSource::Cache
+            } else {
+                RefreshSource::StaleCache
+            };
+            return Ok(self.merge_cache(cache, source, Vec::new()));
+        }
+        if options.offline {
+            return Err(Error::NotFound(format!(
+                "No cached model data in {} to use offline",
+                options.cache_path.display()
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        let fetched = match fetch(self, options, &mut warnings) {
+            Ok(entries) => entries,
+            Err(e) => {
+                let Some(cache) = &cached else {
+                    return Err(e);
+                };
+                warnings.push(format!("could not fetch model data: {e}"));
+                return Ok(self.merge_cache(cache, RefreshSource::StaleCache, warnings));
+            }
+        };
+        let cache = RemoteCache {
+            fetched_at: Utc::now(),
+            entries: fetched,
+        };
+        cache.save(&options.cache_path)?;
+        Ok(self.merge_cache(&cache, RefreshSource::Remote, warnings))
+    }
+
+    /// Merges cached model data of any age over the entries, if there is any
+    /// in `cache_dir`.
+    #[must_use]
+    pub fn with_cached(mut self, cache_dir: &Path) -> Self {
+        if let Some(cache) = RemoteCache::load(&cache_dir.join(CACHE_FILE)) {
+            self.merge(cache.entries);
+        }
+        self
+    }
+
+    /// Adds entries, replacing those with the same ID.
+    pub fn merge(&mut self, entries: impl IntoIterator<Item = ModelEntry>) {
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+
+    fn merge_cache(
+        &mut self,
+        cache: &RemoteCache,
+        source: RefreshSource,
+        warnings: Vec<String>,
+    ) -> RefreshReport {
+        self.merge(cache.entries.iter().cloned());
+        RefreshReport {
+            source,
+            fetched_at: cache.fetched_at,
+            entries: cache.entries.len(),
+            warnings,
+        }
+    }
+}
+
+/// Fetches the entries of all sources. Fails only if `OpenRouter` does.
+fn fetch(
+    registry: &ModelRegistry,
+    options: &RefreshOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ModelEntry>> {
+    let client = options.network.blocking_client()?;
+    let response = client.get(&options.openrouter_url).send()?;
+    if !response.status().is_success() {
+        return Err(Error::ApiError(format!(
+            "OpenRouter models API answered {}",
+            response.status()
+        )));
+    }
+    let body: Value = response
+        .json()
+        .map_err(|e| Error::SerializationError(format!("Invalid OpenRouter models list: {e}")))?;
+    let mut entries = openrouter_entries(registry, &body, Utc::now().date_naive());
+
+    for instance in &options.instances {
+        match crate::health::list_models(instance, &options.network) {
+            Ok(models) => {
+                for id in models {
+                    if registry.resolve(&id).is_none()
+                        && !entries.iter().any(|entry| entry.id == id)
+                    {
+                        entries.push(listed_entry(&instance.provider_type, id));
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("could not list the models of {}: {e}", instance.id)),
+        }
+    }
+    Ok(entries)
+}
+
+/// Entries of an `OpenRouter` models list fetched on `today`, plus updated
+/// builtin entries of the models' vendors.
+fn openrouter_entries(registry: &ModelRegistry, body: &Value, today: NaiveDate) -> Vec<ModelEntry> {
+    let models = body.get("data").and_then(Value::as_array);
+    let mut entries = Vec::new();
+    for model in models.into_iter().flatten() {
+        let Some(id) = model.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let entry = ModelEntry {
+            id: id.to_string(),
+            provider: "openrouter".to_string(),
+            name: model
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or(id)
+                .to_string(),
+            capabilities: openrouter_capabilities(model),
+            context_window: model
+                .get("context_length")
+                .and_then(Value::as_u64)
+                .and_then(|window| u32::try_from(window).ok()),
+            pricing: openrouter_pricing(model),
+            status: ModelStatus::Active,
+            pricing_updated_at: None,
+            deprecated_at: None,
+            sunset_at: None,
+            aliases: Vec::new(),
+        };
+        let entry = ModelEntry {
+            pricing_updated_at: entry.pricing.is_some().then_some(today),
+            ..entry
+        };
+
+        let vendor_entry = id.split_once('/').and_then(|(vendor, model_id)| {
+            let (_, provider) = OPENROUTER_VENDORS
+                .iter()
+                .find(|(name, _)| *name == vendor)?;
+            registry
+                .resolve(model_id)
+                .filter(|builtin| builtin.provider == *provider)
+        });
+        if let Some(builtin) = vendor_entry {
+            entries.push(ModelEntry {
+                pricing: entry.pricing.clone().or_else(|| builtin.pricing.clone()),
+                pricing_updated_at: entry.pricing_updated_at.or(builtin.pricing_updated_at),
+                context_window: entry.context_window.or(builtin.context_window),
+                ..builtin.clone()
+            });
+        }
+        entries.push(entry);
+    }
+    entries
+}
+
+fn openrouter_capabilities(model: &Value) -> ModelCapabilities {
+    let architecture = model.get("architecture");
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_ascii_lowercase)
+            .collect()
+    };
+    let inputs = strings(architecture.and_then(|a| a.get("input_modalities")));
+    let outputs = strings(architecture.and_then(|a| a.get("output_modalities")));
+    let modality = architecture
+        .and_then(|a| a.get("modality"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let parameters = strings(model.get("supported_parameters"));
+    let has = |list: &[String], name: &str| list.iter().any(|item| item == name);
+
+    let embedding = has(&outputs, "embeddings") || modality.ends_with("->embeddings");
+    ModelCapabilities {
+        chat: !embedding,
+        completion: !embedding,
+        embedding,
+        function_calling: has(&parameters, "tools"),
+        vision: has(&inputs, "image") || modality.contains("image->"),
+        json_mode: has(&parameters, "response_format") || has(&parameters, "structured_outputs"),
+    }
+}
+
+/// Pricing of an `OpenRouter` model, which gives USD per token as strings.
+fn openrouter_pricing(model: &Value) -> Option<ModelPricing> {
+    let pricing = model.get("pricing")?;
+    let price = |key: &str| -> Option<f64> {
+        match pricing.get(key)? {
+            Value::String(price) => price.parse().ok(),
+            price => price.as_f64(),
+        }
+    };
+    Some(ModelPricing {
+        input_cost_per_token: price("prompt")?,
+        output_cost_per_token: price("completion").unwrap_or_default(),
+        currency: "USD".to_string(),
+    })
+}
+
+/// Entry of a model only known from a provider's model list.
+fn listed_entry(provider: &str, id: String) -> ModelEntry {
+    let embedding = id.contains("embed");
+    ModelEntry {
+        name: id.clone(),
+        id,
+        provider: provider.to_string(),
+        capabilities: ModelCapabilities {
+            chat: !embedding,
+            completion: !embedding,
+            embedding,
+            ..ModelCapabilities::default()
+        },
+        context_window: None,
+        pricing: None,
+        status: ModelStatus::Active,
+        pricing_updated_at: None,
+        deprecated_at: None,
+        sunset_at: None,
+        aliases: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODELS: &str = r#"{"data": [
+        {"id": "openai/gpt-4o", "name": "OpenAI: GPT-4o", "context_length": 128000,
+         "pricing": {"prompt": "0.000002", "completion": "0.000008"},
+         "architecture": {"input_modalities": ["text", "image"], "output_modalities": ["text"]},
+         "supported_parameters": ["tools", "response_format"]},
+        {"id": "acme/tiny", "context_length": 4096, "pricing": {"prompt": "0", "completion": "0"}}
+    ]}"#;
+
+    #[test]
+    fn test_refreshes_from_openrouter_and_caches() {
+        let mut server = mockito::Server::new();
+        let api = server
+            .mock("GET", "/models")
+            .with_status(200)
+            .with_body(MODELS)
+            .expect(1)
+            .create();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let options = RefreshOptions {
+            openrouter_url: format!("{}/models", server.url()),
+            ..RefreshOptions::new(cache_dir.path())
+        };
+
+        let mut registry = ModelRegistry::builtin();
+        let report = registry.refresh_from_remote(&options).unwrap();
+        assert_eq!(report.source, RefreshSource::Remote);
+        assert_eq!(report.entries, 3);
+
+        let routed = registry.get("openai/gpt-4o").unwrap();
+        assert_eq!(routed.provider, "openrouter");
+        assert!(routed.capabilities.vision && routed.capabilities.function_calling);
+        // The builtin entry takes the current price but keeps its name
+        let gpt4o = registry.get("gpt-4o").unwrap();
+        assert_eq!(gpt4o.name, "GPT-4o");
+        assert!((gpt4o.pricing.as_ref().unwrap().input_cost_per_token - 2e-6).abs() < 1e-12);
+        assert_eq!(
+            gpt4o.pricing_updated_at,
+            Some(report.fetched_at.date_naive())
+        );
+        assert_eq!(registry.get("acme/tiny").unwrap().name, "acme/tiny");
+
+        // A fresh cache is used without another request
+        let report = ModelRegistry::builtin()
+            .refresh_from_remote(&options)
+            .unwrap();
+        assert_eq!(report.source, RefreshSource::Cache);
+        api.assert();
+        drop(server);
+        assert!(ModelRegistry::builtin()
+            .with_cached(cache_dir.path())
+            .get("acme/tiny")
+            .is_some());
+    }
+
+    #[test]
+    fn test_falls_back_to_stale_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let options = RefreshOptions {
+            openrouter_url: "http://127.0.0.1:9/models".to_string(),
+            ttl: Duration::ZERO,
+            ..RefreshOptions::new(cache_dir.path())
+        };
+        let mut registry = ModelRegistry::builtin();
+        assert!(registry.refresh_from_remote(&options).is_err());
+
+        RemoteCache {
+            fetched_at: Utc::now(),
+            entries: vec![listed_entry("openai", "ft:gpt-4o:corp".to_string())],
+        }
+        .save(&options.cache_path)
+        .unwrap();
+        let report = registry.refresh_from_remote(&options).unwrap();
+        assert_eq!(report.source, RefreshSource::StaleCache);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(registry.get("ft:gpt-4o:corp").is_some());
+    }
+}
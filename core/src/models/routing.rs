@@ -0,0 +1,121 @@
+//! Routing of requirements to the configured models meeting them.
+//!
+//! [`ProviderCollection::find_model`] intersects the models of the active
+//! instances with what the [`ModelRegistry`] knows about their capabilities
+//! and pricing. `models recommend` lists its matches, and label resolution
+//! falls back to them when none of a label's targets is usable.
+
+use super::providers::{ProviderCollection, ProviderInstance};
+use super::registry::{ModelEntry, ModelRegistry, ModelRequirements};
+use std::collections::HashMap;
+
+/// A model of a configured instance meeting some requirements.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelMatch<'a> {
+    /// Instance offering the model
+    pub instance: &'a ProviderInstance,
+    /// Model ID as the instance lists it
+    pub model_id: &'a str,
+    /// Registry entry of the model
+    pub entry: &'a ModelEntry,
+}
+
+impl ProviderCollection {
+    /// Finds the models of active instances meeting the requirements, ranked
+    /// like [`ModelRegistry::recommend`], with the instances offering the same
+    /// model in ID order. Models the registry doesn't know never match.
+    #[must_use]
+    pub fn find_model<'a>(
+        &'a self,
+        registry: &'a ModelRegistry,
+        requirements: &ModelRequirements,
+    ) -> Vec<ModelMatch<'a>> {
+        let mut instances = self.active_instances();
+        instances.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut offered: HashMap<&str, Vec<(&ProviderInstance, &str)>> = HashMap::new();
+        for instance in instances {
+            for model_id in &instance.models {
+                if let Some(entry) = registry.resolve(model_id) {
+                    offered
+                        .entry(entry.id.as_str())
+                        .or_default()
+                        .push((instance, model_id.as_str()));
+                }
+            }
+        }
+
+        registry
+            .recommend(requirements)
+            .into_iter()
+            .flat_map(|entry| {
+                offered.get(entry.id.as_str()).into_iter().flatten().map(
+                    move |&(instance, model_id)| ModelMatch {
+                        instance,
+                        model_id,
+                        entry,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: &str, provider_type: &str, models: &[&str]) -> ProviderInstance {
+        ProviderInstance::new(
+            id.to_string(),
+            provider_type.to_string(),
+            format!("https://{provider_type}.example.com"),
+            format!("sk-{id}"),
+            models.iter().map(ToString::to_string).collect(),
+        )
+    }
+
+    #[test]
+    fn test_find_model_ranks_configured_models_meeting_requirements() {
+        let mut instances = ProviderCollection::new();
+        for instance in [
+            instance("work", "openai", &["gpt-4o", "gpt-4o-mini-2024-07-18"]),
+            instance("home", "openai", &["openai/gpt-4o-mini", "in-house"]),
+            instance("claude", "anthropic", &["claude-3-5-haiku-latest"]),
+        ] {
+            instances.add(instance.id.clone(), instance);
+        }
+        let mut inactive = instance("old", "openai", &["gpt-4o-mini"]);
+        inactive.active = false;
+        instances.add(inactive.id.clone(), inactive);
+
+        let registry = ModelRegistry::builtin();
+        let requirements = ModelRequirements {
+            capabilities: vec!["vision".to_string()],
+            max_input_cost: Some(1.0),
+            ..ModelRequirements::default()
+        };
+        let matches: Vec<(&str, &str, &str)> = instances
+            .find_model(&registry, &requirements)
+            .iter()
+            .map(|m| (m.instance.id.as_str(), m.model_id, m.entry.id.as_str()))
+            .collect();
+        assert_eq!(
+            matches,
+            [
+                ("home", "openai/gpt-4o-mini", "gpt-4o-mini"),
+                ("work", "gpt-4o-mini-2024-07-18", "gpt-4o-mini"),
+            ]
+        );
+
+        assert!(instances
+            .find_model(
+                &registry,
+                &ModelRequirements {
+                    capabilities: vec!["embedding".to_string()],
+                    ..ModelRequirements::default()
+                }
+            )
+            .is_empty());
+    }
+}
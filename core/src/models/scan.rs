@@ -7,11 +7,31 @@
 //! `ScanResult` model for collecting and querying discovered credentials.
 
 use crate::models::config_instance::ConfigInstance;
-use crate::models::credentials::{Confidence, DiscoveredCredential, ValueType};
+use crate::models::credentials::{Confidence, DiscoveredCredential, RiskLevel, ValueType};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Progress of a running scan, as reported to
+/// [`scan_with_progress`](crate::scan_with_progress).
+#[derive(Debug, Clone, Copy)]
+pub enum ScanEvent<'a> {
+    /// A scanner is starting on a scan root
+    Scanner {
+        /// Name of the scanner
+        scanner: &'a str,
+        /// Scan root being scanned
+        root: &'a str,
+        /// Scanner runs finished so far
+        completed: usize,
+        /// Scanner runs of the whole scan
+        total: usize,
+    },
+    /// A new credential was found, redacted as the scan options ask. Its risk
+    /// is only assessed once the scan completes.
+    Key(&'a DiscoveredCredential),
+}
+
 /// Results from scanning for API keys.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResult {
@@ -57,19 +77,21 @@ impl ScanResult {
     }
 
     /// Adds a discovered key to the result.
-    /// Only adds the key if no key with the same hash already exists.
+    /// If a key with the same hash and provider already exists, the new key's
+    /// source paths are merged into the existing key's `sources` instead.
     pub fn add_key(&mut self, key: DiscoveredCredential) {
-        // Check if a key with the same hash already exists
-        if self
-            .keys
-            .iter()
-            .any(|existing_key| existing_key.hash == key.hash)
-        {
+        if let Some(existing_key) = self.keys.iter_mut().find(|existing_key| {
+            existing_key.hash == key.hash && existing_key.provider == key.provider
+        }) {
             tracing::debug!(
-                "Skipping duplicate key for provider: {} (hash: {})",
+                "Merging duplicate key for provider: {} (hash: {})",
                 key.provider,
                 &key.hash[..8]
             );
+            existing_key.add_source(&key.source_file);
+            for source in &key.sources {
+                existing_key.add_source(source);
+            }
         } else {
             tracing::debug!(
                 "Adding key for provider: {} (hash: {})",
@@ -81,7 +103,7 @@ impl ScanResult {
     }
 
     /// Adds multiple discovered keys to the result.
-    /// Only adds keys that don't have duplicate hashes.
+    /// Duplicate keys (same hash and provider) are merged rather than repeated.
     pub fn add_keys(&mut self, keys: Vec<DiscoveredCredential>) {
         for key in keys {
             self.add_key(key);
@@ -183,6 +205,55 @@ impl ScanResult {
             .collect()
     }
 
+    /// Filters keys by risk level (minimum risk).
+    #[must_use]
+    pub fn filter_by_risk(&self, min_risk: RiskLevel) -> Vec<&DiscoveredCredential> {
+        self.keys
+            .iter()
+            .filter(|key| key.risk >= min_risk)
+            .collect()
+    }
+
+    /// Gets keys for a provider that were found in the given source file.
+    pub fn keys_for_source<'a>(
+        &'a self,
+        provider: &'a str,
+        source: &'a str,
+    ) -> impl Iterator<Item = &'a DiscoveredCredential> + 'a {
+        self.keys.iter().filter(move |key| {
+            key.provider == provider
+                && (key.source_file == source || key.sources.iter().any(|s| s == source))
+        })
+    }
+
+    /// Gets the highest risk among keys for a provider found in the given source file.
+    #[must_use]
+    pub fn risk_for_source(&self, provider: &str, source: &str) -> Option<RiskLevel> {
+        self.keys_for_source(provider, source)
+            .map(|key| key.risk)
+            .max()
+    }
+
+    /// Filters keys that have not been rotated in at least `days` days.
+    #[must_use]
+    pub fn stale_keys(&self, days: u32) -> Vec<&DiscoveredCredential> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .filter(|key| key.is_stale(days, now))
+            .collect()
+    }
+
+    /// Removes keys below the given risk level.
+    pub fn retain_min_risk(&mut self, min_risk: RiskLevel) {
+        self.keys.retain(|key| key.risk >= min_risk);
+    }
+
+    /// Sorts keys from highest to lowest risk.
+    pub fn sort_by_risk(&mut self) {
+        self.keys.sort_by_key(|key| std::cmp::Reverse(key.risk));
+    }
+
     /// Gets high confidence keys (High and `VeryHigh`).
     #[must_use]
     pub fn high_confidence_credentials(&self) -> Vec<&DiscoveredCredential> {
@@ -329,6 +400,82 @@ mod tests {
         assert!(result.has_keys());
     }
 
+    #[test]
+    fn test_duplicate_keys_merge_sources() {
+        let mut result = ScanResult::new("/home/user".to_string(), vec![], Utc::now());
+
+        let make_key = |provider: &str, source: &str| {
+            DiscoveredCredential::new_redacted(
+                provider.to_string(),
+                source.to_string(),
+                ValueType::ApiKey,
+                Confidence::High,
+                "sk-shared-credential-value",
+            )
+        };
+
+        result.add_keys(vec![
+            make_key("openai", "/home/user/project/.env"),
+            make_key("openai", "/home/user/.config/gsh/gshrc"),
+            make_key("openai", "/home/user/project/.env"),
+            make_key("groq", "/home/user/project/.env"),
+        ]);
+
+        // Same hash and provider collapse into one key; a different provider stays separate
+        assert_eq!(result.total_keys(), 2);
+        let openai_keys = result.filter_by_provider("openai");
+        assert_eq!(openai_keys.len(), 1);
+        assert_eq!(
+            openai_keys[0].sources,
+            vec![
+                "/home/user/project/.env".to_string(),
+                "/home/user/.config/gsh/gshrc".to_string()
+            ]
+        );
+        assert_eq!(result.filter_by_provider("groq")[0].sources.len(), 1);
+    }
+
+    #[test]
+    fn test_risk_filtering_and_sorting() {
+        let mut result = ScanResult::new("/home/user".to_string(), vec![], Utc::now());
+
+        let mut low = create_test_credential("openai", ValueType::ApiKey, Confidence::Low);
+        low.risk = RiskLevel::Low;
+        let mut critical = create_test_credential("groq", ValueType::ApiKey, Confidence::High);
+        critical.risk = RiskLevel::Critical;
+        result.add_keys(vec![low, critical]);
+
+        result.sort_by_risk();
+        assert_eq!(result.keys[0].risk, RiskLevel::Critical);
+        assert_eq!(result.filter_by_risk(RiskLevel::High).len(), 1);
+        assert_eq!(
+            result.risk_for_source("groq", "/test"),
+            Some(RiskLevel::Critical)
+        );
+        assert_eq!(result.risk_for_source("anthropic", "/test"), None);
+
+        result.retain_min_risk(RiskLevel::Medium);
+        assert_eq!(result.total_keys(), 1);
+    }
+
+    #[test]
+    fn test_stale_keys() {
+        let mut result = ScanResult::new("/home/user".to_string(), vec![], Utc::now());
+
+        let mut old = create_test_credential("openai", ValueType::ApiKey, Confidence::High);
+        old.source_modified_at = Some(Utc::now() - chrono::Duration::days(200));
+        let mut rotated = create_test_credential("groq", ValueType::ApiKey, Confidence::High);
+        rotated.source_modified_at = Some(Utc::now() - chrono::Duration::days(200));
+        rotated.key_created_at = Some(Utc::now() - chrono::Duration::days(3));
+        let unknown = create_test_credential("anthropic", ValueType::ApiKey, Confidence::High);
+        result.add_keys(vec![old, rotated, unknown]);
+
+        let stale = result.stale_keys(90);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].provider, "openai");
+        assert!(stale[0].age_days(Utc::now()).unwrap() >= 200);
+    }
+
     #[test]
     fn test_filtering_keys() {
         let temp_dir = tempfile::tempdir().unwrap();
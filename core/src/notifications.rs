@@ -0,0 +1,491 @@
+#![allow(clippy::missing_errors_doc)]
+//! Notifications of scan results to webhooks, Slack and syslog.
+//!
+//! Sinks are configured in the `notifications` section of [`CONFIG_FILE`] in
+//! the config store:
+//!
+//! ```yaml
+//! notifications:
+//!   - type: webhook
+//!     url: https://siem.example.com/hooks/aicred
+//!     headers:
+//!       Authorization: Bearer s3cr3t
+//!   - type: slack
+//!     url: https://hooks.slack.com/services/T000/B000/XXXX
+//!     events: [policy_violations]
+//!   - type: syslog
+//! ```
+//!
+//! Events carry counts, providers, file paths and key hashes, never key values.
+
+use crate::error::{Error, Result};
+use crate::models::{RiskLevel, ScanResult};
+use crate::policy::PolicyViolation;
+use crate::store::ConfigStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Store file holding user settings, including notification sinks.
+pub const CONFIG_FILE: &str = "config.yaml";
+
+/// Default local syslog socket.
+pub const SYSLOG_SOCKET: &str = "/dev/log";
+
+/// Kinds of events sinks can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A scan finished
+    ScanCompleted,
+    /// A scan matched policy rules
+    PolicyViolations,
+}
+
+/// Where notifications are delivered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkTarget {
+    /// POSTs the event as JSON
+    Webhook {
+        /// Endpoint URL
+        url: String,
+        /// Extra request headers, such as `Authorization`
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+    /// POSTs a one-line summary to a Slack-compatible incoming webhook
+    Slack {
+        /// Incoming webhook URL
+        url: String,
+    },
+    /// Writes a one-line summary to the local syslog daemon
+    Syslog {
+        /// Program name in the message (default: aicred)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ident: Option<String>,
+        /// Socket path (default: [`SYSLOG_SOCKET`])
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        socket: Option<PathBuf>,
+    },
+}
+
+/// A configured notification sink.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sink {
+    /// Delivery target
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    /// Events sent to the sink (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventKind>,
+}
+
+impl Sink {
+    /// Creates a sink receiving every event.
+    #[must_use]
+    pub const fn new(target: SinkTarget) -> Self {
+        Self {
+            target,
+            events: Vec::new(),
+        }
+    }
+
+    /// Restricts the sink to some events.
+    #[must_use]
+    pub fn with_events(mut self, events: Vec<EventKind>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Checks if the sink subscribes to an event kind.
+    #[must_use]
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+
+    /// Short name of the sink for messages.
+    #[must_use]
+    pub fn name(&self) -> String {
+        match &self.target {
+            SinkTarget::Webhook { url, .. } => format!("webhook {url}"),
+            SinkTarget::Slack { .. } => "slack".to_string(),
+            SinkTarget::Syslog { .. } => "syslog".to_string(),
+        }
+    }
+}
+
+/// A scan event sent to sinks.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A scan finished
+    ScanCompleted {
+        /// When the scan finished
+        scanned_at: DateTime<Utc>,
+        /// Home directory that was scanned
+        home_dir: String,
+        /// Number of keys found
+        keys: usize,
+        /// Number of keys assessed as high or critical risk
+        high_risk: usize,
+        /// Number of application configurations found
+        config_instances: usize,
+        /// Providers of the keys found
+        providers: Vec<String>,
+    },
+    /// A scan matched policy rules
+    PolicyViolations {
+        /// When the scan finished
+        scanned_at: DateTime<Utc>,
+        /// Home directory that was scanned
+        home_dir: String,
+        /// Rule matches
+        violations: Vec<PolicyViolation>,
+    },
+}
+
+impl Event {
+    /// Summarizes a finished scan.
+    #[must_use]
+    pub fn scan_completed(result: &ScanResult) -> Self {
+        let mut providers: Vec<String> = result.keys.iter().map(|k| k.provider.clone()).collect();
+        providers.sort();
+        providers.dedup();
+        Self::ScanCompleted {
+            scanned_at: result.scan_completed_at,
+            home_dir: result.home_directory.clone(),
+            keys: result.keys.len(),
+            high_risk: result
+                .keys
+                .iter()
+                .filter(|k| k.risk >= RiskLevel::High)
+                .count(),
+            config_instances: result.config_instances.len(),
+            providers,
+        }
+    }
+
+    /// Reports the policy rules a scan matched.
+    #[must_use]
+    pub fn policy_violations(result: &ScanResult, violations: &[PolicyViolation]) -> Self {
+        Self::PolicyViolations {
+            scanned_at: result.scan_completed_at,
+            home_dir: result.home_directory.clone(),
+            violations: violations.to_vec(),
+        }
+    }
+
+    /// Kind of the event.
+    #[must_use]
+    pub const fn kind(&self) -> EventKind {
+        match self {
+            Self::ScanCompleted { .. } => EventKind::ScanCompleted,
+            Self::PolicyViolations { .. } => EventKind::PolicyViolations,
+        }
+    }
+
+    /// One-line description of the event.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        match self {
+            Self::ScanCompleted {
+                home_dir,
+                keys,
+                high_risk,
+                config_instances,
+                ..
+            } => format!(
+                "aicred scan of {home_dir} found {keys} key(s) ({high_risk} high risk) and {config_instances} configuration(s)"
+            ),
+            Self::PolicyViolations {
+                home_dir,
+                violations,
+                ..
+            } => {
+                let mut rules: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+                rules.sort_unstable();
+                rules.dedup();
+                format!(
+                    "aicred scan of {home_dir} matched {} policy violation(s): {}",
+                    violations.len(),
+                    rules.join(", ")
+                )
+            }
+        }
+    }
+
+    /// Syslog severity of the event (notice or warning).
+    const fn severity(&self) -> u8 {
+        match self {
+            Self::ScanCompleted { .. } => 5,
+            Self::PolicyViolations { .. } => 4,
+        }
+    }
+}
+
+/// The `notifications` section of the settings file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct SettingsFile {
+    #[serde(default)]
+    notifications: Vec<Sink>,
+}
+
+/// Delivers events to configured sinks.
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    sinks: Vec<Sink>,
+}
+
+impl Notifier {
+    /// Creates a notifier for the given sinks.
+    #[must_use]
+    pub const fn new(sinks: Vec<Sink>) -> Self {
+        Self { sinks }
+    }
+
+    /// Parses the sinks from the `notifications` section of settings YAML.
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let settings: Option<SettingsFile> = serde_yaml::from_str(content)
+            .map_err(|e| Error::ConfigError(format!("invalid notification settings: {e}")))?;
+        Ok(Self::new(settings.unwrap_or_default().notifications))
+    }
+
+    /// Loads the sinks configured in a config store.
+    pub fn load_from_store(store: &ConfigStore) -> Result<Self> {
+        let settings: Option<SettingsFile> = store.load_yaml(CONFIG_FILE)?;
+        Ok(Self::new(settings.unwrap_or_default().notifications))
+    }
+
+    /// Configured sinks.
+    #[must_use]
+    pub fn sinks(&self) -> &[Sink] {
+        &self.sinks
+    }
+
+    /// Checks if no sinks are configured.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Sends an event to every subscribed sink, returning the sinks that failed.
+    #[must_use]
+    pub fn notify(&self, event: &Event) -> Vec<(String, Error)> {
+        self.sinks
+            .iter()
+            .filter(|sink| sink.accepts(event.kind()))
+            .filter_map(|sink| send(sink, event).err().map(|e| (sink.name(), e)))
+            .collect()
+    }
+}
+
+/// Sends an event to one sink.
+fn send(sink: &Sink, event: &Event) -> Result<()> {
+    match &sink.target {
+        SinkTarget::Webhook { url, headers } => {
+            let request = headers
+                .iter()
+                .fold(client()?.post(url).json(event), |request, (name, value)| {
+                    request.header(name, value)
+                });
+            check_status(&request.send()?)
+        }
+        SinkTarget::Slack { url } => {
+            let body = serde_json::json!({ "text": event.summary() });
+            check_status(&client()?.post(url).json(&body).send()?)
+        }
+        SinkTarget::Syslog { ident, socket } => send_syslog(
+            ident.as_deref().unwrap_or("aicred"),
+            socket
+                .as_deref()
+                .unwrap_or_else(|| std::path::Path::new(SYSLOG_SOCKET)),
+            event,
+        ),
+    }
+}
+
+/// Creates a blocking client for notification requests.
+fn client() -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| Error::PluginError(format!("Failed to create HTTP client: {e}")))
+}
+
+/// Maps an error status to an error.
+fn check_status(response: &reqwest::blocking::Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(Error::ApiError(format!(
+            "notification endpoint returned {status}"
+        )))
+    }
+}
+
+/// Writes an RFC 3164 message with the `user` facility to a syslog socket.
+#[cfg(unix)]
+fn send_syslog(ident: &str, socket: &std::path::Path, event: &Event) -> Result<()> {
+    const FACILITY_USER: u8 = 1;
+    let message = format!(
+        "<{}>{} {ident}[{}]: {}",
+        FACILITY_USER * 8 + event.severity(),
+        chrono::Local::now().format("%b %e %H:%M:%S"),
+        std::process::id(),
+        event.summary()
+    );
+    let datagram = std::os::unix::net::UnixDatagram::unbound()?;
+    datagram.send_to(message.as_bytes(), socket).map_err(|e| {
+        Error::ConfigError(format!(
+            "Failed to write to syslog socket {}: {e}",
+            socket.display()
+        ))
+    })?;
+    Ok(())
+}
+
+/// Syslog sinks need a Unix socket.
+#[cfg(not(unix))]
+fn send_syslog(_ident: &str, _socket: &std::path::Path, _event: &Event) -> Result<()> {
+    Err(Error::ConfigError(
+        "syslog notifications are only supported on Unix".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Confidence, DiscoveredCredential, ValueType};
+    use crate::policy::PolicyAction;
+
+    fn scan_result() -> ScanResult {
+        let mut result = ScanResult::new("/home/u".to_string(), Vec::new(), chrono::Utc::now());
+        result.add_key(DiscoveredCredential::new(
+            "openai".to_string(),
+            "/home/u/.env".to_string(),
+            ValueType::ApiKey,
+            Confidence::High,
+            "sk-proj-notifysecret1234567890".to_string(),
+        ));
+        result
+    }
+
+    #[test]
+    fn test_sinks_parse_from_settings() {
+        let notifier = Notifier::from_yaml(
+            r"
+format: table
+notifications:
+  - type: webhook
+    url: https://siem.example.com/hook
+    headers:
+      Authorization: Bearer abc
+  - type: slack
+    url: https://hooks.slack.com/services/x
+    events: [policy_violations]
+  - type: syslog
+",
+        )
+        .unwrap();
+
+        let sinks = notifier.sinks();
+        assert_eq!(sinks.len(), 3);
+        assert!(matches!(
+            &sinks[0].target,
+            SinkTarget::Webhook { headers, .. } if headers["Authorization"] == "Bearer abc"
+        ));
+        assert!(!sinks[1].accepts(EventKind::ScanCompleted));
+        assert!(sinks[1].accepts(EventKind::PolicyViolations));
+        assert!(sinks[2].accepts(EventKind::ScanCompleted));
+        assert!(Notifier::from_yaml("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_events_carry_no_secrets() {
+        let result = scan_result();
+        let violation = PolicyViolation {
+            rule: "no-dotenv".to_string(),
+            description: None,
+            action: PolicyAction::Fail,
+            provider: "openai".to_string(),
+            source_file: "/home/u/.env".to_string(),
+            hash: result.keys[0].hash.clone(),
+        };
+
+        for event in [
+            Event::scan_completed(&result),
+            Event::policy_violations(&result, &[violation]),
+        ] {
+            let payload = serde_json::to_string(&event).unwrap();
+            assert!(!payload.contains("notifysecret"));
+            assert!(!event.summary().contains("notifysecret"));
+        }
+        let payload = serde_json::to_value(Event::scan_completed(&result)).unwrap();
+        assert_eq!(payload["event"], "scan_completed");
+        assert_eq!(payload["keys"], 1);
+        assert_eq!(payload["providers"][0], "openai");
+    }
+
+    #[test]
+    fn test_webhook_receives_event() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("scan_completed") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        let notifier = Notifier::new(vec![Sink::new(SinkTarget::Webhook {
+            url,
+            headers: BTreeMap::from([("X-Token".to_string(), "t0k".to_string())]),
+        })]);
+        let failures = notifier.notify(&Event::scan_completed(&scan_result()));
+        assert!(failures.is_empty(), "{failures:?}");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook"));
+        assert!(request.to_lowercase().contains("x-token: t0k"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_syslog_writes_datagram() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let socket = temp.path().join("log");
+        let receiver = std::os::unix::net::UnixDatagram::bind(&socket).unwrap();
+
+        let notifier = Notifier::new(vec![Sink::new(SinkTarget::Syslog {
+            ident: None,
+            socket: Some(socket),
+        })
+        .with_events(vec![EventKind::ScanCompleted])]);
+        assert!(notifier
+            .notify(&Event::scan_completed(&scan_result()))
+            .is_empty());
+
+        let mut buf = [0u8; 1024];
+        let n = receiver.recv(&mut buf).unwrap();
+        let message = String::from_utf8_lossy(&buf[..n]);
+        assert!(message.starts_with("<13>"));
+        assert!(message.contains("aicred["));
+        assert!(message.contains("found 1 key(s)"));
+    }
+}
@@ -150,8 +150,9 @@ impl ConfigParser {
 
     /// Parses JSON configuration.
     fn parse_json(content: &str) -> Result<HashMap<String, String>> {
-        let json: JsonValue = serde_json::from_str(content).map_err(|e| Error::ParseError {
+        let json: JsonValue = serde_json::from_str(content).map_err(|e| Error::ParseFailed {
             path: Path::new("json").to_path_buf(),
+            format: "json".to_string(),
             message: format!("Invalid JSON: {e}"),
         })?;
 
@@ -187,20 +188,14 @@ impl ConfigParser {
                     Self::extract_json_values(val, new_prefix, result);
                 }
             }
-            JsonValue::String(s) => {
-                if !prefix.is_empty() {
-                    result.insert(prefix, s.clone());
-                }
+            JsonValue::String(s) if !prefix.is_empty() => {
+                result.insert(prefix, s.clone());
             }
-            JsonValue::Number(n) => {
-                if !prefix.is_empty() {
-                    result.insert(prefix, n.to_string());
-                }
+            JsonValue::Number(n) if !prefix.is_empty() => {
+                result.insert(prefix, n.to_string());
             }
-            JsonValue::Bool(b) => {
-                if !prefix.is_empty() {
-                    result.insert(prefix, b.to_string());
-                }
+            JsonValue::Bool(b) if !prefix.is_empty() => {
+                result.insert(prefix, b.to_string());
             }
             _ => {}
         }
@@ -208,8 +203,9 @@ impl ConfigParser {
 
     /// Parses YAML configuration.
     fn parse_yaml(content: &str) -> Result<HashMap<String, String>> {
-        let yaml: JsonValue = serde_yaml::from_str(content).map_err(|e| Error::ParseError {
+        let yaml: JsonValue = serde_yaml::from_str(content).map_err(|e| Error::ParseFailed {
             path: Path::new("yaml").to_path_buf(),
+            format: "yaml".to_string(),
             message: format!("Invalid YAML: {e}"),
         })?;
 
@@ -220,8 +216,9 @@ impl ConfigParser {
 
     /// Parses TOML configuration.
     fn parse_toml(content: &str) -> Result<HashMap<String, String>> {
-        let toml: JsonValue = toml::from_str(content).map_err(|e| Error::ParseError {
+        let toml: JsonValue = toml::from_str(content).map_err(|e| Error::ParseFailed {
             path: Path::new("toml").to_path_buf(),
+            format: "toml".to_string(),
             message: format!("Invalid TOML: {e}"),
         })?;
 
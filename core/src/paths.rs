@@ -0,0 +1,338 @@
+#![allow(clippy::missing_errors_doc)]
+//! Directories aicred keeps its files in.
+//!
+//! | Directory | Linux and other Unix | macOS | Windows |
+//! |-----------|----------------------|-------|---------|
+//! | config | `~/.config/aicred` | `~/Library/Application Support/aicred` | `%APPDATA%\aicred` |
+//! | data | `~/.local/share/aicred` | `~/Library/Application Support/aicred` | `%APPDATA%\aicred\data` |
+//! | cache | `~/.cache/aicred` | `~/Library/Caches/aicred` | `%LOCALAPPDATA%\aicred\cache` |
+//!
+//! For the current user, `XDG_CONFIG_HOME`, `XDG_DATA_HOME` and
+//! `XDG_CACHE_HOME` move the Unix directories, and `APPDATA` and `LOCALAPPDATA`
+//! the Windows ones. For any other home directory, as given with `--home`, the
+//! directories are laid out under that home and these variables are ignored.
+//!
+//! [`CONFIG_DIR_ENV`], [`DATA_DIR_ENV`] and [`CACHE_DIR_ENV`] override a
+//! directory in either case, which lets tests keep their files apart.
+//!
+//! On macOS and Windows, a `~/.config/aicred` or `~/.local/share/aicred`
+//! written before aicred used the platform directories stays in use while the
+//! platform directory does not exist, so upgrading doesn't lose the stored
+//! instances.
+
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the config directory.
+pub const CONFIG_DIR_ENV: &str = "AICRED_CONFIG_DIR";
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "AICRED_DATA_DIR";
+
+/// Environment variable overriding the cache directory.
+pub const CACHE_DIR_ENV: &str = "AICRED_CACHE_DIR";
+
+/// Name of aicred's directory within the platform directories.
+const APP_DIR: &str = "aicred";
+
+/// Platform whose directory layout is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// Linux and other Unix systems, following the XDG base directories.
+    Unix,
+    /// macOS, using `~/Library`.
+    MacOs,
+    /// Windows, using `AppData`.
+    Windows,
+}
+
+impl Platform {
+    /// Platform the library was built for.
+    #[must_use]
+    pub const fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+
+    /// Directory applications keep their per-user settings in under `home`:
+    /// `~/.config`, `~/Library/Application Support` or `%APPDATA%`.
+    #[must_use]
+    pub fn app_settings_dir(self, home: &Path) -> PathBuf {
+        match self {
+            Self::Unix => home.join(".config"),
+            Self::MacOs => home.join("Library").join("Application Support"),
+            Self::Windows => home.join("AppData").join("Roaming"),
+        }
+    }
+
+    /// Directory applications keep data for this machine only in under
+    /// `home`: `~/.local/share`, `~/Library/Application Support` or
+    /// `%LOCALAPPDATA%`.
+    #[must_use]
+    pub fn app_local_dir(self, home: &Path) -> PathBuf {
+        match self {
+            Self::Unix => home.join(".local").join("share"),
+            Self::MacOs => home.join("Library").join("Application Support"),
+            Self::Windows => home.join("AppData").join("Local"),
+        }
+    }
+}
+
+/// The config, data and cache directories of one user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppDirs {
+    config: PathBuf,
+    data: PathBuf,
+    cache: PathBuf,
+}
+
+impl AppDirs {
+    /// Directories of the user with the home directory `home`.
+    #[must_use]
+    pub fn for_home(home: &Path) -> Self {
+        Self::resolve(home, Platform::current(), false, |name| {
+            std::env::var_os(name)
+        })
+    }
+
+    /// Directories of the current user.
+    pub fn current() -> Result<Self> {
+        Ok(Self::resolve(
+            &home_dir()?,
+            Platform::current(),
+            true,
+            |name| std::env::var_os(name),
+        ))
+    }
+
+    /// Resolves the directories for a platform, reading variables through
+    /// `var`. The platform's own variables are only read for the current user.
+    #[must_use]
+    pub fn resolve(
+        home: &Path,
+        platform: Platform,
+        current_user: bool,
+        var: impl Fn(&str) -> Option<std::ffi::OsString>,
+    ) -> Self {
+        let var_dir = |name: &str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        let user_dir = |name: &str| current_user.then(|| var_dir(name)).flatten();
+
+        let (config, data, cache) = match platform {
+            Platform::Unix => (
+                user_dir("XDG_CONFIG_HOME")
+                    .unwrap_or_else(|| home.join(".config"))
+                    .join(APP_DIR),
+                user_dir("XDG_DATA_HOME")
+                    .unwrap_or_else(|| home.join(".local").join("share"))
+                    .join(APP_DIR),
+                user_dir("XDG_CACHE_HOME")
+                    .unwrap_or_else(|| home.join(".cache"))
+                    .join(APP_DIR),
+            ),
+            Platform::MacOs => {
+                let support = home
+                    .join("Library")
+                    .join("Application Support")
+                    .join(APP_DIR);
+                (
+                    support.clone(),
+                    support,
+                    home.join("Library").join("Caches").join(APP_DIR),
+                )
+            }
+            Platform::Windows => {
+                let roaming = user_dir("APPDATA")
+                    .unwrap_or_else(|| home.join("AppData").join("Roaming"))
+                    .join(APP_DIR);
+                let local = user_dir("LOCALAPPDATA")
+                    .unwrap_or_else(|| home.join("AppData").join("Local"))
+                    .join(APP_DIR);
+                (roaming.clone(), roaming.join("data"), local.join("cache"))
+            }
+        };
+
+        let (config, data) = if platform == Platform::Unix {
+            (config, data)
+        } else {
+            (
+                legacy_or(home.join(".config").join(APP_DIR), config),
+                legacy_or(home.join(".local").join("share").join(APP_DIR), data),
+            )
+        };
+        Self {
+            config: var_dir(CONFIG_DIR_ENV).unwrap_or(config),
+            data: var_dir(DATA_DIR_ENV).unwrap_or(data),
+            cache: var_dir(CACHE_DIR_ENV).unwrap_or(cache),
+        }
+    }
+
+    /// Directory of the configuration store.
+    #[must_use]
+    pub fn config_dir(&self) -> &Path {
+        &self.config
+    }
+
+    /// Directory of files aicred writes as it runs, such as the scan history.
+    #[must_use]
+    pub fn data_dir(&self) -> &Path {
+        &self.data
+    }
+
+    /// Directory of files that can be fetched or computed again.
+    #[must_use]
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache
+    }
+}
+
+/// Home directory of the current user, preferring `$HOME` over the platform
+/// home directory.
+pub fn home_dir() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(dirs_next::home_dir)
+        .ok_or_else(|| Error::NotFound("Could not determine home directory".to_string()))
+}
+
+/// Directory other applications keep their per-user settings in, as the
+/// scanners look for them: `%APPDATA%` when `home` is the current user's home
+/// on Windows, else [`Platform::app_settings_dir`].
+#[must_use]
+pub fn app_settings_dir(home: &Path) -> PathBuf {
+    current_user_dir(home, "APPDATA").unwrap_or_else(|| Platform::current().app_settings_dir(home))
+}
+
+/// Directory other applications keep data for this machine only in:
+/// `%LOCALAPPDATA%` when `home` is the current user's home on Windows, else
+/// [`Platform::app_local_dir`].
+#[must_use]
+pub fn app_local_dir(home: &Path) -> PathBuf {
+    current_user_dir(home, "LOCALAPPDATA")
+        .unwrap_or_else(|| Platform::current().app_local_dir(home))
+}
+
+/// A Windows directory variable, if `home` is the current user's home.
+fn current_user_dir(home: &Path, name: &str) -> Option<PathBuf> {
+    if Platform::current() != Platform::Windows || home_dir().ok().as_deref() != Some(home) {
+        return None;
+    }
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// The legacy directory if only it exists, else `dir`.
+fn legacy_or(legacy: PathBuf, dir: PathBuf) -> PathBuf {
+    if legacy.is_dir() && !dir.exists() {
+        legacy
+    } else {
+        dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+        let vars: HashMap<String, OsString> = vars
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), OsString::from(value)))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_resolves_platform_directories() {
+        let home = Path::new("/home/user");
+        let xdg = env(&[
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_CACHE_HOME", "relative"),
+        ]);
+
+        let unix = AppDirs::resolve(home, Platform::Unix, true, &xdg);
+        assert_eq!(unix.config_dir(), Path::new("/xdg/config/aicred"));
+        assert_eq!(unix.data_dir(), Path::new("/home/user/.local/share/aicred"));
+        assert_eq!(unix.cache_dir(), Path::new("/home/user/.cache/aicred"));
+
+        let other_home = AppDirs::resolve(home, Platform::Unix, false, &xdg);
+        assert_eq!(
+            other_home.config_dir(),
+            Path::new("/home/user/.config/aicred")
+        );
+
+        let mac = AppDirs::resolve(home, Platform::MacOs, true, env(&[]));
+        assert_eq!(
+            mac.config_dir(),
+            Path::new("/home/user/Library/Application Support/aicred")
+        );
+        assert_eq!(
+            mac.cache_dir(),
+            Path::new("/home/user/Library/Caches/aicred")
+        );
+
+        let windows = AppDirs::resolve(home, Platform::Windows, false, env(&[]));
+        assert_eq!(
+            windows.config_dir(),
+            Path::new("/home/user/AppData/Roaming/aicred")
+        );
+        assert_eq!(
+            windows.cache_dir(),
+            Path::new("/home/user/AppData/Local/aicred/cache")
+        );
+
+        let overridden = AppDirs::resolve(
+            home,
+            Platform::Unix,
+            false,
+            env(&[(CONFIG_DIR_ENV, "/tmp/aicred-test")]),
+        );
+        assert_eq!(overridden.config_dir(), Path::new("/tmp/aicred-test"));
+    }
+
+    #[test]
+    fn test_app_directories_follow_platform_layout() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            Platform::Windows.app_settings_dir(home),
+            Path::new("/home/user/AppData/Roaming")
+        );
+        assert_eq!(
+            Platform::Windows.app_local_dir(home),
+            Path::new("/home/user/AppData/Local")
+        );
+        assert_eq!(
+            Platform::MacOs.app_settings_dir(home),
+            Path::new("/home/user/Library/Application Support")
+        );
+        assert_eq!(
+            Platform::Unix.app_settings_dir(home),
+            Path::new("/home/user/.config")
+        );
+    }
+
+    #[test]
+    fn test_keeps_legacy_directory_on_other_platforms() {
+        let home = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(home.path().join(".config/aicred")).unwrap();
+
+        let mac = AppDirs::resolve(home.path(), Platform::MacOs, false, env(&[]));
+        assert_eq!(mac.config_dir(), home.path().join(".config/aicred"));
+        assert_eq!(
+            mac.data_dir(),
+            home.path().join("Library/Application Support/aicred")
+        );
+    }
+}
@@ -24,6 +24,17 @@ pub trait ProviderPlugin: Send + Sync {
     /// Returns the name of this plugin.
     fn name(&self) -> &str;
 
+    /// Returns the name of the provider as people write it, for listings.
+    fn display_name(&self) -> &str {
+        self.name()
+    }
+
+    /// Returns a one-line description of what the plugin finds, for listings.
+    #[allow(clippy::unnecessary_literal_bound)] // Implementations may return their own data
+    fn description(&self) -> &str {
+        ""
+    }
+
     /// Returns a confidence score for a potential key (0.0 to 1.0).
     fn confidence_score(&self, key: &str) -> f32;
 
@@ -79,7 +90,9 @@ pub trait ProviderPlugin: Send + Sync {
         use crate::models::Model;
 
         // Try to load the base model from the models directory
-        let config_dir = home_dir.join(".config").join("aicred").join("models");
+        let config_dir = crate::paths::AppDirs::for_home(home_dir)
+            .config_dir()
+            .join("models");
 
         let model_file_name = format!("{}.yaml", model_id.replace(['/', ':'], "-"));
         let model_file_path = config_dir.join(&model_file_name);
@@ -174,6 +187,93 @@ pub trait ProviderPlugin: Send + Sync {
         // Default implementation - no async API probing
         Ok(Vec::new())
     }
+
+    /// Probes for available models like [`probe_models_async`](Self::probe_models_async),
+    /// sending requests through `client` so scans honour their configured
+    /// proxy, CA bundle and retries.
+    ///
+    /// The default implementation calls `probe_models_async`. Providers that
+    /// probe over HTTP should override this method and have
+    /// `probe_models_async` call it with a default client.
+    async fn probe_models_with(
+        &self,
+        _client: &crate::http::HttpClient,
+        api_key: &str,
+        base_url: Option<&str>,
+    ) -> Result<Vec<ModelMetadata>> {
+        self.probe_models_async(api_key, base_url).await
+    }
+
+    /// Environment variable holding the admin key for this provider's key
+    /// management API, if it has one.
+    fn admin_key_env(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Whether [`create_key`](Self::create_key) is supported, so keys can be rotated.
+    fn supports_key_creation(&self) -> bool {
+        false
+    }
+
+    /// Creates a new API key through the provider's key management API.
+    ///
+    /// # Default Implementation
+    /// Returns an error; providers with a management API override this.
+    fn create_key(&self, _admin_key: &str, _name: &str) -> Result<IssuedKey> {
+        Err(Error::PluginError(format!(
+            "{} does not support creating keys through its API",
+            self.name()
+        )))
+    }
+
+    /// Revokes an API key through the provider's key management API.
+    ///
+    /// # Default Implementation
+    /// Returns an error; providers with a management API override this.
+    fn revoke_key(&self, _admin_key: &str, _key_id: &str) -> Result<()> {
+        Err(Error::PluginError(format!(
+            "{} does not support revoking keys through its API",
+            self.name()
+        )))
+    }
+
+    /// Looks up the provider-side ID of an API key through the key management
+    /// API, returning `None` if the organization has no such key.
+    ///
+    /// # Default Implementation
+    /// Returns an error; providers with a management API override this.
+    fn find_key_id(&self, _admin_key: &str, _api_key: &str) -> Result<Option<String>> {
+        Err(Error::PluginError(format!(
+            "{} does not support looking up keys through its API",
+            self.name()
+        )))
+    }
+
+    /// Verifies that an API key is accepted by the provider.
+    ///
+    /// # Default Implementation
+    /// Probes the provider's models with the key.
+    fn verify_key(&self, api_key: &str) -> Result<()> {
+        self.probe_models(api_key).map(drop)
+    }
+}
+
+/// A key issued by a provider's key management API.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IssuedKey {
+    /// Provider-side identifier used to manage the key later
+    pub id: String,
+    /// The secret value
+    pub secret: String,
+}
+
+impl std::fmt::Debug for IssuedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IssuedKey")
+            .field("id", &self.id)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
 }
 
 /// Type alias for provider plugin registry (v0.2.0+ simplified API).
@@ -226,9 +326,7 @@ impl PluginRegistry {
 
         let name = plugin.name().to_string();
         if plugins.contains_key(&name) {
-            return Err(Error::PluginError(format!(
-                "Plugin '{name}' is already registered"
-            )));
+            return Err(Error::PluginConflict(name));
         }
 
         plugins.insert(name, plugin);
@@ -527,6 +625,19 @@ mod tests {
         assert!(score3 > 0.8);
     }
 
+    #[test]
+    fn test_builtin_providers_describe_themselves() {
+        let registry = register_builtin_providers();
+        for (name, plugin) in &registry {
+            assert!(
+                !plugin.description().is_empty(),
+                "{name} has no description"
+            );
+        }
+        assert_eq!(registry["huggingface"].display_name(), "Hugging Face");
+        assert_eq!(CommonConfigPlugin.display_name(), CommonConfigPlugin.name());
+    }
+
     #[tokio::test]
     async fn test_default_probe_models_async() {
         let plugin = CommonConfigPlugin;
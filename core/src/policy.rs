@@ -0,0 +1,415 @@
+#![allow(clippy::missing_errors_doc)]
+//! Policy engine for evaluating scan findings against user-defined rules.
+//!
+//! Policies are written in YAML:
+//!
+//! ```yaml
+//! rules:
+//!   - name: openai-outside-aicred
+//!     description: High-confidence OpenAI keys must live in ~/.config/aicred
+//!     action: fail
+//!     providers: [openai]
+//!     min_confidence: High
+//!     outside_paths: ["~/.config/aicred"]
+//!   - name: shell-history
+//!     action: warn
+//!     paths: ["~/.*_history"]
+//! ```
+//!
+//! Every condition on a rule is optional; a finding matches a rule when it
+//! satisfies all of the conditions that are set.
+
+use crate::error::{Error, Result};
+use crate::models::{Confidence, DiscoveredCredential, ScanResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What happens when a rule matches a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// Report the finding and fail the run
+    #[default]
+    Fail,
+    /// Report the finding without failing the run
+    Warn,
+}
+
+impl std::fmt::Display for PolicyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fail => write!(f, "fail"),
+            Self::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+/// A single policy rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Rule name, used in reports
+    pub name: String,
+    /// Human-readable description of the rule
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Action to take when the rule matches
+    #[serde(default)]
+    pub action: PolicyAction,
+    /// Only match findings for these providers
+    #[serde(default)]
+    pub providers: Option<Vec<String>>,
+    /// Only match findings with at least this confidence
+    #[serde(default)]
+    pub min_confidence: Option<Confidence>,
+    /// Only match findings whose source is under one of these paths (globs allowed)
+    #[serde(default)]
+    pub paths: Option<Vec<String>>,
+    /// Only match findings whose source is not under any of these paths (globs allowed)
+    #[serde(default)]
+    pub outside_paths: Option<Vec<String>>,
+    /// Compiled `paths` and `outside_paths`, filled in by [`Policy::validate`]
+    #[serde(skip)]
+    patterns: OnceLock<RulePatterns>,
+}
+
+/// The compiled path patterns of a rule.
+#[derive(Debug, Clone)]
+struct RulePatterns {
+    paths: Vec<PathPattern>,
+    outside_paths: Vec<PathPattern>,
+}
+
+impl RulePatterns {
+    /// Compiles the patterns of a rule, skipping any that are invalid
+    fn lossy(rule: &PolicyRule) -> Self {
+        let compile = |patterns: &Option<Vec<String>>| {
+            patterns
+                .iter()
+                .flatten()
+                .filter_map(|pattern| PathPattern::new(pattern).ok())
+                .collect()
+        };
+        Self {
+            paths: compile(&rule.paths),
+            outside_paths: compile(&rule.outside_paths),
+        }
+    }
+}
+
+impl PolicyRule {
+    /// Checks whether a finding matches every condition of this rule
+    #[must_use]
+    pub fn matches(&self, key: &DiscoveredCredential, home_dir: &Path) -> bool {
+        self.matching_source(key, home_dir).is_some()
+    }
+
+    /// Gets the first source of a finding that matches every condition of this rule
+    ///
+    /// A key found in several files matches if any one of them does.
+    fn matching_source<'a>(
+        &self,
+        key: &'a DiscoveredCredential,
+        home_dir: &Path,
+    ) -> Option<&'a str> {
+        if let Some(providers) = &self.providers {
+            if !providers
+                .iter()
+                .any(|p| p.eq_ignore_ascii_case(&key.provider))
+            {
+                return None;
+            }
+        }
+
+        if let Some(min_confidence) = self.min_confidence {
+            if key.confidence < min_confidence {
+                return None;
+            }
+        }
+
+        let patterns = self.patterns.get_or_init(|| RulePatterns::lossy(self));
+        key.source_files().find(|source| {
+            let under = |patterns: &[PathPattern]| {
+                patterns
+                    .iter()
+                    .any(|pattern| pattern.matches(source, home_dir))
+            };
+            (self.paths.is_none() || under(&patterns.paths))
+                && (self.outside_paths.is_none() || !under(&patterns.outside_paths))
+        })
+    }
+}
+
+/// A set of rules loaded from a policy file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    /// Rules evaluated against every finding
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+impl Policy {
+    /// Parses a policy from YAML content
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let policy: Self = serde_yaml::from_str(content)
+            .map_err(|e| Error::ConfigError(format!("Invalid policy: {e}")))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Loads a policy from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_yaml(&content).map_err(|e| Error::ParseFailed {
+            path: path.to_path_buf(),
+            format: "yaml".to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Validates rule names and path patterns
+    pub fn validate(&self) -> Result<()> {
+        for rule in &self.rules {
+            if rule.name.trim().is_empty() {
+                return Err(Error::ValidationError(
+                    "Policy rule name cannot be empty".to_string(),
+                ));
+            }
+            let patterns = rule.paths.iter().chain(rule.outside_paths.iter()).flatten();
+            for pattern in patterns {
+                PathPattern::new(pattern).map_err(|e| {
+                    Error::ValidationError(format!(
+                        "Invalid path pattern '{pattern}' in rule '{}': {e}",
+                        rule.name
+                    ))
+                })?;
+            }
+            let _ = rule.patterns.set(RulePatterns::lossy(rule));
+        }
+        Ok(())
+    }
+
+    /// Evaluates every rule against every key in a scan result
+    #[must_use]
+    pub fn evaluate(&self, result: &ScanResult, home_dir: &Path) -> PolicyReport {
+        let mut violations = Vec::new();
+        for key in &result.keys {
+            for rule in &self.rules {
+                if let Some(source) = rule.matching_source(key, home_dir) {
+                    violations.push(PolicyViolation {
+                        rule: rule.name.clone(),
+                        description: rule.description.clone(),
+                        action: rule.action,
+                        provider: key.provider.clone(),
+                        source_file: source.to_string(),
+                        hash: key.hash.clone(),
+                    });
+                }
+            }
+        }
+        PolicyReport { violations }
+    }
+}
+
+/// A finding that matched a policy rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    /// Name of the rule that matched
+    pub rule: String,
+    /// Description of the rule that matched
+    pub description: Option<String>,
+    /// Action configured on the rule
+    pub action: PolicyAction,
+    /// Provider of the matching finding
+    pub provider: String,
+    /// Source file of the matching finding that broke the rule
+    pub source_file: String,
+    /// Hash of the matching finding
+    pub hash: String,
+}
+
+/// Outcome of evaluating a policy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyReport {
+    /// All rule matches, in key order
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl PolicyReport {
+    /// Checks if any `fail` rule matched
+    #[must_use]
+    pub fn has_failures(&self) -> bool {
+        self.violations
+            .iter()
+            .any(|v| v.action == PolicyAction::Fail)
+    }
+
+    /// Checks if any `warn` rule matched
+    #[must_use]
+    pub fn has_warnings(&self) -> bool {
+        self.violations
+            .iter()
+            .any(|v| v.action == PolicyAction::Warn)
+    }
+
+    /// Gets violations with the given action
+    #[must_use]
+    pub fn by_action(&self, action: PolicyAction) -> Vec<&PolicyViolation> {
+        self.violations
+            .iter()
+            .filter(|v| v.action == action)
+            .collect()
+    }
+}
+
+/// A compiled path pattern.
+///
+/// A leading `~` expands to the home directory. Patterns without wildcards match
+/// the path itself and anything beneath it; `*` matches within a path segment and
+/// `**` matches across segments.
+#[derive(Debug, Clone)]
+struct PathPattern {
+    /// Whether the pattern starts with `~` and matches paths under the home directory
+    home_relative: bool,
+    regex: Regex,
+}
+
+impl PathPattern {
+    fn new(pattern: &str) -> std::result::Result<Self, regex::Error> {
+        let (home_relative, rest) = pattern
+            .strip_prefix('~')
+            .map_or((false, pattern), |rest| (true, rest));
+        let rest = rest.trim_end_matches('/');
+
+        let mut regex = String::from("^");
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    regex.push_str(".*");
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        // Match the path itself or anything beneath it
+        regex.push_str("(/.*)?$");
+        Ok(Self {
+            home_relative,
+            regex: Regex::new(&regex)?,
+        })
+    }
+
+    /// Checks a source path against this pattern
+    fn matches(&self, source: &str, home_dir: &Path) -> bool {
+        if !self.home_relative {
+            return self.regex.is_match(source);
+        }
+        let home = home_dir.display().to_string();
+        source
+            .strip_prefix(home.trim_end_matches('/'))
+            .is_some_and(|rest| self.regex.is_match(rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ValueType;
+    use chrono::Utc;
+
+    const POLICY: &str = r#"
+rules:
+  - name: openai-outside-aicred
+    action: fail
+    providers: [openai]
+    min_confidence: High
+    outside_paths: ["~/.config/aicred"]
+  - name: shell-history
+    action: warn
+    paths: ["~/.*_history"]
+"#;
+
+    fn key(provider: &str, source: &str, confidence: Confidence) -> DiscoveredCredential {
+        DiscoveredCredential::new_redacted(
+            provider.to_string(),
+            source.to_string(),
+            ValueType::ApiKey,
+            confidence,
+            &format!("{provider}-{source}-secret"),
+        )
+    }
+
+    fn evaluate(keys: Vec<DiscoveredCredential>) -> PolicyReport {
+        let policy = Policy::from_yaml(POLICY).unwrap();
+        let mut result = ScanResult::new("/home/user".to_string(), vec![], Utc::now());
+        result.add_keys(keys);
+        policy.evaluate(&result, Path::new("/home/user"))
+    }
+
+    #[test]
+    fn test_fail_rule_respects_outside_paths() {
+        let report = evaluate(vec![
+            key(
+                "openai",
+                "/home/user/.config/aicred/inference_services/openai-1a2b.yaml",
+                Confidence::High,
+            ),
+            key("openai", "/home/user/project/.env", Confidence::High),
+            key("openai", "/home/user/other/.env", Confidence::Low),
+        ]);
+
+        assert!(report.has_failures());
+        let failures = report.by_action(PolicyAction::Fail);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].source_file, "/home/user/project/.env");
+    }
+
+    #[test]
+    fn test_warn_rule_matches_globs() {
+        let report = evaluate(vec![key(
+            "anthropic",
+            "/home/user/.zsh_history",
+            Confidence::Medium,
+        )]);
+
+        assert!(!report.has_failures());
+        assert!(report.has_warnings());
+        assert_eq!(report.violations[0].rule, "shell-history");
+    }
+
+    #[test]
+    fn test_rules_match_any_source_of_a_key() {
+        let mut stored = key(
+            "openai",
+            "/home/user/.config/aicred/inference_services/openai-1a2b.yaml",
+            Confidence::High,
+        );
+        stored.add_source("/home/user/.bash_history");
+        stored.add_source("/home/user/project/.env");
+
+        let report = evaluate(vec![stored]);
+        let failures = report.by_action(PolicyAction::Fail);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].source_file, "/home/user/.bash_history");
+        let warnings = report.by_action(PolicyAction::Warn);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].source_file, "/home/user/.bash_history");
+
+        let inside = key(
+            "openai",
+            "/home/user/.config/aicred/inference_services/openai-1a2b.yaml",
+            Confidence::High,
+        );
+        assert!(evaluate(vec![inside]).violations.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_policy_is_rejected() {
+        assert!(Policy::from_yaml("rules:\n  - name: ''\n").is_err());
+        assert!(Policy::from_yaml("rules:\n  - name: x\n    action: explode\n").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Coordination of many provider probes at once.
+//!
+//! Scans, the setup wizard and `instances check` can probe dozens of
+//! instances, often several of the same provider. [`ProbeCoordinator`] caps
+//! how many probes of a provider run at once and, when a provider answers 429
+//! Too Many Requests, pauses all its probes for the `Retry-After` it asked for
+//! before trying again. A probe that still fails returns its own error, so
+//! callers can report the instances that did answer.
+
+use crate::error::{Error, Result};
+use crate::http::NetworkOptions;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+/// Probe slots of one provider.
+#[derive(Debug)]
+struct ProviderSlots {
+    permits: Semaphore,
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl ProviderSlots {
+    /// Waits until a pause requested by a rate-limited probe has passed.
+    async fn wait_until_resumed(&self) {
+        loop {
+            let paused_until = *self
+                .paused_until
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            match paused_until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => return,
+            }
+        }
+    }
+
+    /// Pauses the provider's probes for `wait`, unless already paused longer.
+    fn pause_for(&self, wait: Duration) {
+        let until = Instant::now() + wait;
+        let mut paused_until = self
+            .paused_until
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if paused_until.is_none_or(|current| current < until) {
+            *paused_until = Some(until);
+        }
+    }
+}
+
+/// Runs probes within per-provider concurrency caps, waiting out rate limits.
+#[derive(Debug)]
+pub struct ProbeCoordinator {
+    max_per_provider: usize,
+    max_retry_after: Duration,
+    retries: u32,
+    retry_backoff: Duration,
+    total: Option<Semaphore>,
+    providers: Mutex<HashMap<String, Arc<ProviderSlots>>>,
+}
+
+impl ProbeCoordinator {
+    /// Creates a coordinator with the limits and retries of `network`.
+    #[must_use]
+    pub fn new(network: &NetworkOptions) -> Self {
+        Self {
+            max_per_provider: network.max_per_provider.max(1),
+            max_retry_after: network.max_retry_after(),
+            retries: network.retries,
+            retry_backoff: Duration::from_millis(network.retry_backoff_ms),
+            total: None,
+            providers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Also caps the probes run at once across all providers.
+    #[must_use]
+    pub fn with_max_concurrent(mut self, limit: usize) -> Self {
+        self.total = Some(Semaphore::new(limit.max(1)));
+        self
+    }
+
+    /// Runs `probe` for `provider` once a slot is free, running it again
+    /// after the provider's `Retry-After` while it fails with
+    /// [`Error::RateLimited`] and retries are left.
+    ///
+    /// # Errors
+    /// Returns the error of the last attempt of `probe`.
+    pub async fn run<T, F, Fut>(&self, provider: &str, mut probe: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let slots = self.slots(provider);
+        let mut attempt = 0;
+        loop {
+            slots.wait_until_resumed().await;
+            let result = {
+                let _provider_permit = slots.permits.acquire().await;
+                let _total_permit = match &self.total {
+                    Some(total) => Some(total.acquire().await),
+                    None => None,
+                };
+                probe().await
+            };
+            match result {
+                Err(Error::RateLimited {
+                    retry_after_secs, ..
+                }) if attempt < self.retries => {
+                    let wait = retry_after_secs
+                        .map_or(self.retry_backoff, Duration::from_secs)
+                        .min(self.max_retry_after);
+                    tracing::debug!(
+                        "{} is rate limiting probes, pausing them for {}s",
+                        provider,
+                        wait.as_secs()
+                    );
+                    slots.pause_for(wait);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn slots(&self, provider: &str) -> Arc<ProviderSlots> {
+        self.providers
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry(provider.to_string())
+            .or_insert_with(|| {
+                Arc::new(ProviderSlots {
+                    permits: Semaphore::new(self.max_per_provider),
+                    paused_until: Mutex::new(None),
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_caps_probes_per_provider() {
+        let coordinator = Arc::new(ProbeCoordinator::new(&NetworkOptions {
+            max_per_provider: 2,
+            ..NetworkOptions::default()
+        }));
+        let running = Arc::new(AtomicUsize::new(0));
+        let most_running = Arc::new(AtomicUsize::new(0));
+
+        let probes: Vec<_> = (0..6)
+            .map(|_| {
+                let (coordinator, running, most_running) =
+                    (coordinator.clone(), running.clone(), most_running.clone());
+                tokio::spawn(async move {
+                    coordinator
+                        .run("openai", || async {
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            most_running.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(10)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                            Ok(())
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for probe in probes {
+            probe.await.unwrap().unwrap();
+        }
+        assert_eq!(most_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_waits_out_retry_after_then_gives_up() {
+        let coordinator = ProbeCoordinator::new(&NetworkOptions {
+            retries: 1,
+            ..NetworkOptions::default()
+        });
+        let attempts = AtomicU32::new(0);
+        let rate_limited = || Error::RateLimited {
+            retry_after_secs: Some(5),
+            message: "slow down".to_string(),
+        };
+
+        let started = Instant::now();
+        let result = coordinator
+            .run("openai", || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(rate_limited())
+                } else {
+                    Ok("models")
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "models");
+        assert!(started.elapsed() >= Duration::from_secs(5));
+
+        let result: Result<()> = coordinator
+            .run("openai", || async { Err(rate_limited()) })
+            .await;
+        assert!(matches!(result, Err(Error::RateLimited { .. })));
+    }
+}
@@ -3,8 +3,10 @@
 use crate::error::{Error, Result};
 use crate::models::ProviderInstance;
 use crate::plugins::ProviderPlugin;
+use crate::providers::key_admin;
 use reqwest::blocking::Client;
 use serde::Deserialize;
+use serde_json::Value;
 use std::time::Duration;
 
 /// Response structure for Anthropic models API
@@ -27,6 +29,14 @@ impl ProviderPlugin for AnthropicPlugin {
         "anthropic"
     }
 
+    fn display_name(&self) -> &'static str {
+        "Anthropic"
+    }
+
+    fn description(&self) -> &'static str {
+        "Anthropic (Claude) API keys"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // Anthropic keys have very specific patterns
         if key.starts_with("sk-ant-") {
@@ -106,9 +116,59 @@ impl ProviderPlugin for AnthropicPlugin {
         // Use the existing fetch_supported_models method
         Self::fetch_supported_models(api_key)
     }
+
+    fn admin_key_env(&self) -> Option<&'static str> {
+        Some("ANTHROPIC_ADMIN_KEY")
+    }
+
+    fn find_key_id(&self, admin_key: &str, api_key: &str) -> Result<Option<String>> {
+        let client = key_admin::client()?;
+        let keys = key_admin::list_all(|cursor| {
+            let mut request = Self::admin_request(client.get(Self::ADMIN_KEYS_URL), admin_key)
+                .query(&[("limit", "100")]);
+            if let Some(after) = cursor {
+                request = request.query(&[("after_id", after)]);
+            }
+            key_admin::send_json("Anthropic", "list keys", request)
+        })?;
+        Ok(keys
+            .iter()
+            .find(|key| {
+                key.get("partial_key_hint")
+                    .and_then(Value::as_str)
+                    .is_some_and(|hint| key_admin::key_matches_hint(api_key, hint))
+            })
+            .and_then(|key| key.get("id"))
+            .and_then(Value::as_str)
+            .map(str::to_string))
+    }
+
+    fn revoke_key(&self, admin_key: &str, key_id: &str) -> Result<()> {
+        // Archiving is permanent, unlike setting the key inactive
+        let client = key_admin::client()?;
+        let request = Self::admin_request(
+            client.post(format!("{}/{key_id}", Self::ADMIN_KEYS_URL)),
+            admin_key,
+        )
+        .json(&serde_json::json!({ "status": "archived" }));
+        key_admin::send_json("Anthropic", "revoke the key", request).map(drop)
+    }
 }
 
 impl AnthropicPlugin {
+    /// Admin API endpoint for the organization's API keys
+    const ADMIN_KEYS_URL: &'static str = "https://api.anthropic.com/v1/organizations/api_keys";
+
+    /// Adds admin API authentication headers to a request.
+    fn admin_request(
+        request: reqwest::blocking::RequestBuilder,
+        admin_key: &str,
+    ) -> reqwest::blocking::RequestBuilder {
+        request
+            .header("x-api-key", admin_key)
+            .header("anthropic-version", "2023-06-01")
+    }
+
     /// Fetch supported models from the Anthropic API
     ///
     /// Makes a blocking HTTP GET request to the Anthropic models endpoint.
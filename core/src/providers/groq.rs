@@ -12,6 +12,14 @@ impl ProviderPlugin for GroqPlugin {
         "groq"
     }
 
+    fn display_name(&self) -> &'static str {
+        "Groq"
+    }
+
+    fn description(&self) -> &'static str {
+        "Groq API keys"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // Groq keys have very specific patterns
         if key.starts_with("gsk_") || key.starts_with("gsk-") {
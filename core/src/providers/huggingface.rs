@@ -12,6 +12,14 @@ impl ProviderPlugin for HuggingFacePlugin {
         "huggingface"
     }
 
+    fn display_name(&self) -> &'static str {
+        "Hugging Face"
+    }
+
+    fn description(&self) -> &'static str {
+        "Hugging Face tokens"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // Hugging Face tokens have very specific patterns
         if key.starts_with("hf_") {
@@ -0,0 +1,126 @@
+//! Shared helpers for provider key management (admin) APIs.
+
+use crate::error::{Error, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Maximum number of pages read from a paginated list endpoint.
+const MAX_PAGES: usize = 50;
+
+/// Creates a blocking client for admin API calls.
+pub fn client() -> Result<Client> {
+    Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| Error::PluginError(format!("Failed to create HTTP client: {e}")))
+}
+
+/// Sends an admin API request and parses its JSON body, mapping error statuses.
+pub fn send_json(provider: &str, action: &str, request: RequestBuilder) -> Result<Value> {
+    let response = request.send()?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(Error::ApiError(format!(
+            "{provider} rejected the admin key while trying to {action} ({status})"
+        )));
+    }
+    if !status.is_success() {
+        return Err(Error::ApiError(format!(
+            "{provider} failed to {action}: {status}"
+        )));
+    }
+    let body = response.text()?;
+    if body.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_str(&body)
+        .map_err(|e| Error::SerializationError(format!("Failed to parse API response: {e}")))
+}
+
+/// Reads every item of a cursor-paginated list endpoint.
+///
+/// `page` is called with the cursor of the previous page's last item and
+/// returns the page body, whose `data` array holds the items and `has_more`
+/// says whether to continue.
+pub fn list_all(mut page: impl FnMut(Option<&str>) -> Result<Value>) -> Result<Vec<Value>> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_PAGES {
+        let body = page(cursor.as_deref())?;
+        let data = body
+            .get("data")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        cursor = body
+            .get("last_id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                data.last()
+                    .and_then(|item| item.get("id"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            });
+        items.extend(data);
+        if !body
+            .get("has_more")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+            || cursor.is_none()
+        {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// Checks whether a key matches a redacted hint such as `sk-abc...wxyz` or
+/// `sk-abc****wxyz`.
+pub fn key_matches_hint(key: &str, hint: &str) -> bool {
+    let (prefix, suffix) = if let Some(split) = hint.split_once("...") {
+        split
+    } else if let Some(start) = hint.find('*') {
+        (&hint[..start], hint[start..].trim_start_matches('*'))
+    } else {
+        return false;
+    };
+    !prefix.is_empty()
+        && !suffix.is_empty()
+        && prefix.len() + suffix.len() < key.len()
+        && key.starts_with(prefix)
+        && key.ends_with(suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_matches_hint() {
+        let key = "sk-ant-REDACTED";
+        assert!(key_matches_hint(key, "sk-ant-api03-R2D...igAA"));
+        assert!(key_matches_hint("sk-abc1234567890wxyz", "sk-abc****wxyz"));
+        assert!(!key_matches_hint(key, "sk-ant-api03-XXX...igAA"));
+        assert!(!key_matches_hint(key, "...igAA"));
+        assert!(!key_matches_hint(key, key));
+    }
+
+    #[test]
+    fn test_list_all_follows_cursor() {
+        let mut cursors = Vec::new();
+        let items = list_all(|cursor| {
+            cursors.push(cursor.map(str::to_string));
+            Ok(match cursor {
+                None => json!({ "data": [{ "id": "a" }, { "id": "b" }], "has_more": true }),
+                Some(_) => json!({ "data": [{ "id": "c" }], "has_more": false }),
+            })
+        })
+        .unwrap();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(cursors, vec![None, Some("b".to_string())]);
+    }
+}
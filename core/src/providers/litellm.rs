@@ -12,6 +12,14 @@ impl ProviderPlugin for LiteLLMPlugin {
         "litellm"
     }
 
+    fn display_name(&self) -> &'static str {
+        "LiteLLM"
+    }
+
+    fn description(&self) -> &'static str {
+        "LiteLLM configurations"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // LiteLLM keys are typically longer and more complex
         if (key.len() >= 40 && key.contains('-') && key.chars().any(char::is_uppercase))
@@ -5,6 +5,7 @@
 pub mod anthropic;
 pub mod groq;
 pub mod huggingface;
+mod key_admin;
 pub mod litellm;
 pub mod ollama;
 pub mod openai;
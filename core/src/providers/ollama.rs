@@ -12,6 +12,14 @@ impl ProviderPlugin for OllamaPlugin {
         "ollama"
     }
 
+    fn display_name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn description(&self) -> &'static str {
+        "Ollama local configurations"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // Ollama configuration is less critical than API keys, so lower confidence
         if key.starts_with("http://") || key.starts_with("https://") {
@@ -35,8 +43,7 @@ impl ProviderPlugin for OllamaPlugin {
             Some(h) if h == "127.0.0.1" || h == "0.0.0.0" => true,
             Some(h) => h
                 .parse::<std::net::IpAddr>()
-                .map(|ip| ip.is_loopback())
-                .unwrap_or(false),
+                .is_ok_and(|ip| ip.is_loopback()),
             None => false,
         } || matches!(url.host(), Some(url::Host::Ipv6(addr)) if addr.is_loopback());
         let port_ok = url.port() == Some(11434);
@@ -3,6 +3,8 @@
 use crate::error::{Error, Result};
 use crate::models::ProviderInstance;
 use crate::plugins::ProviderPlugin;
+use crate::providers::key_admin;
+use serde_json::Value;
 use url::Url;
 
 /// Configuration for `OpenAI` provider defaults
@@ -76,6 +78,14 @@ impl ProviderPlugin for OpenAIPlugin {
         "openai"
     }
 
+    fn display_name(&self) -> &'static str {
+        "OpenAI"
+    }
+
+    fn description(&self) -> &'static str {
+        "OpenAI API keys"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // OpenAI keys have very specific patterns
         if key.starts_with("sk-proj-") || key.starts_with("sk-") {
@@ -166,9 +176,69 @@ impl ProviderPlugin for OpenAIPlugin {
 
         Ok(())
     }
+
+    fn admin_key_env(&self) -> Option<&'static str> {
+        Some("OPENAI_ADMIN_KEY")
+    }
+
+    fn find_key_id(&self, admin_key: &str, api_key: &str) -> Result<Option<String>> {
+        let client = key_admin::client()?;
+        let list = |url: String, cursor: Option<&str>| {
+            let mut request = client
+                .get(url)
+                .bearer_auth(admin_key)
+                .query(&[("limit", "100")]);
+            if let Some(after) = cursor {
+                request = request.query(&[("after", after)]);
+            }
+            key_admin::send_json("OpenAI", "list keys", request)
+        };
+
+        let projects = key_admin::list_all(|cursor| {
+            list(format!("{}/projects", Self::ADMIN_BASE_URL), cursor)
+        })?;
+        for project in projects
+            .iter()
+            .filter_map(|p| p.get("id").and_then(Value::as_str))
+        {
+            let keys = key_admin::list_all(|cursor| {
+                list(
+                    format!("{}/projects/{project}/api_keys", Self::ADMIN_BASE_URL),
+                    cursor,
+                )
+            })?;
+            let found = keys.iter().find(|key| {
+                key.get("redacted_value")
+                    .and_then(Value::as_str)
+                    .is_some_and(|hint| key_admin::key_matches_hint(api_key, hint))
+            });
+            if let Some(id) = found.and_then(|key| key.get("id")).and_then(Value::as_str) {
+                return Ok(Some(format!("{project}/{id}")));
+            }
+        }
+        Ok(None)
+    }
+
+    fn revoke_key(&self, admin_key: &str, key_id: &str) -> Result<()> {
+        let (project, id) = key_id.split_once('/').ok_or_else(|| {
+            Error::PluginError(format!(
+                "OpenAI key IDs have the form <project-id>/<key-id>, got '{key_id}'"
+            ))
+        })?;
+        let request = key_admin::client()?
+            .delete(format!(
+                "{}/projects/{project}/api_keys/{id}",
+                Self::ADMIN_BASE_URL
+            ))
+            .bearer_auth(admin_key);
+        key_admin::send_json("OpenAI", "revoke the key", request).map(drop)
+    }
 }
 
 impl OpenAIPlugin {
+    /// Base URL of the organization admin API
+    const ADMIN_BASE_URL: &'static str = "https://api.openai.com/v1/organization";
+
     /// Helper method to perform base instance validation
     fn validate_base_instance(instance: &ProviderInstance) -> Result<()> {
         if instance.base_url.is_empty() {
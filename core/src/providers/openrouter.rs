@@ -2,7 +2,7 @@
 
 use crate::error::{Error, Result};
 use crate::models::ModelMetadata;
-use crate::plugins::ProviderPlugin;
+use crate::plugins::{IssuedKey, ProviderPlugin};
 use async_trait::async_trait;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -17,6 +17,22 @@ pub struct OpenRouterPlugin;
 impl OpenRouterPlugin {
     /// Default base URL for `OpenRouter` API
     const DEFAULT_BASE_URL: &'static str = "https://openrouter.ai/api/v1";
+
+    /// Environment variable holding a provisioning key for the keys API
+    const PROVISIONING_KEY_ENV: &'static str = "OPENROUTER_PROVISIONING_KEY";
+}
+
+/// Response from `OpenRouter`'s key creation endpoint
+#[derive(Debug, Deserialize)]
+struct OpenRouterCreatedKey {
+    data: OpenRouterKeyData,
+    key: String,
+}
+
+/// Key details from `OpenRouter`'s keys API
+#[derive(Debug, Deserialize)]
+struct OpenRouterKeyData {
+    hash: String,
 }
 
 /// Response structure from `OpenRouter`'s /models endpoint
@@ -56,6 +72,43 @@ struct OpenRouterArchitecture {
 }
 
 impl OpenRouterPlugin {
+    /// Creates a blocking client for the keys API.
+    fn blocking_client() -> Result<reqwest::blocking::Client> {
+        reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::PluginError(format!("Failed to create HTTP client: {e}")))
+    }
+
+    /// Maps a keys API response to an error unless it succeeded.
+    fn check_status(
+        response: reqwest::blocking::Response,
+        action: &str,
+    ) -> Result<reqwest::blocking::Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(Error::ApiError(format!(
+                "OpenRouter rejected the key while trying to {action} ({status})"
+            )));
+        }
+        Err(Error::ApiError(format!(
+            "OpenRouter failed to {action}: {status}"
+        )))
+    }
+
+    /// Parses the response to a key creation request.
+    fn parse_created_key(body: &str) -> Result<IssuedKey> {
+        let created: OpenRouterCreatedKey = serde_json::from_str(body)
+            .map_err(|e| Error::SerializationError(format!("Failed to parse API response: {e}")))?;
+        Ok(IssuedKey {
+            id: created.data.hash,
+            secret: created.key,
+        })
+    }
+
     /// Converts `OpenRouter` pricing string to f64
     fn parse_price(price_str: Option<String>) -> Option<f64> {
         price_str.and_then(|s| s.parse::<f64>().ok())
@@ -83,6 +136,14 @@ impl ProviderPlugin for OpenRouterPlugin {
         "openrouter"
     }
 
+    fn display_name(&self) -> &'static str {
+        "OpenRouter"
+    }
+
+    fn description(&self) -> &'static str {
+        "OpenRouter API keys"
+    }
+
     fn confidence_score(&self, key: &str) -> f32 {
         // OpenRouter keys typically start with "sk-or-"
         let mut score: f32 = 0.3;
@@ -121,19 +182,36 @@ impl ProviderPlugin for OpenRouterPlugin {
         api_key: &str,
         base_url: Option<&str>,
     ) -> Result<Vec<ModelMetadata>> {
-        let url = format!("{}/models", base_url.unwrap_or(Self::DEFAULT_BASE_URL));
+        let client = crate::http::NetworkOptions::default().client()?;
+        self.probe_models_with(&client, api_key, base_url).await
+    }
 
-        // Create HTTP client
-        let client = reqwest::Client::new();
+    async fn probe_models_with(
+        &self,
+        client: &crate::http::HttpClient,
+        api_key: &str,
+        base_url: Option<&str>,
+    ) -> Result<Vec<ModelMetadata>> {
+        let url = format!("{}/models", base_url.unwrap_or(Self::DEFAULT_BASE_URL));
 
         // Make API request
         let response = client
-            .get(&url)
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json")
-            .send()
+            .send(
+                client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {api_key}"))
+                    .header("Content-Type", "application/json"),
+            )
             .await?;
 
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(Error::RateLimited {
+                retry_after_secs: crate::http::retry_after(response.headers())
+                    .map(|wait| wait.as_secs()),
+                message: "OpenRouter is rate limiting model listing".to_string(),
+            });
+        }
+
         // Check for authentication errors
         if response.status() == reqwest::StatusCode::UNAUTHORIZED {
             return Err(Error::ApiError(
@@ -164,6 +242,40 @@ impl ProviderPlugin for OpenRouterPlugin {
 
         Ok(models)
     }
+
+    fn admin_key_env(&self) -> Option<&'static str> {
+        Some(Self::PROVISIONING_KEY_ENV)
+    }
+
+    fn supports_key_creation(&self) -> bool {
+        true
+    }
+
+    fn create_key(&self, admin_key: &str, name: &str) -> Result<IssuedKey> {
+        let response = Self::blocking_client()?
+            .post(format!("{}/keys", Self::DEFAULT_BASE_URL))
+            .bearer_auth(admin_key)
+            .json(&serde_json::json!({ "name": name }))
+            .send()?;
+        let body = Self::check_status(response, "create a key")?.text()?;
+        Self::parse_created_key(&body)
+    }
+
+    fn revoke_key(&self, admin_key: &str, key_id: &str) -> Result<()> {
+        let response = Self::blocking_client()?
+            .delete(format!("{}/keys/{key_id}", Self::DEFAULT_BASE_URL))
+            .bearer_auth(admin_key)
+            .send()?;
+        Self::check_status(response, "revoke the key").map(drop)
+    }
+
+    fn verify_key(&self, api_key: &str) -> Result<()> {
+        let response = Self::blocking_client()?
+            .get(format!("{}/key", Self::DEFAULT_BASE_URL))
+            .bearer_auth(api_key)
+            .send()?;
+        Self::check_status(response, "verify the key").map(drop)
+    }
 }
 
 #[cfg(test)]
@@ -171,6 +283,17 @@ mod tests {
     #![allow(clippy::no_effect_underscore_binding)]
     use super::*;
 
+    #[test]
+    fn test_parse_created_key() {
+        let body =
+            r#"{"data":{"hash":"abc123","name":"aicred","limit":null},"key":"sk-or-v1-new"}"#;
+        let issued = OpenRouterPlugin::parse_created_key(body).unwrap();
+        assert_eq!(issued.id, "abc123");
+        assert_eq!(issued.secret, "sk-or-v1-new");
+        assert!(!format!("{issued:?}").contains("sk-or-v1-new"));
+        assert!(OpenRouterPlugin::parse_created_key("{}").is_err());
+    }
+
     #[test]
     fn test_openrouter_plugin_name() {
         let plugin = OpenRouterPlugin;
@@ -0,0 +1,333 @@
+#![allow(clippy::missing_errors_doc)]
+//! Resolution of labels to the instance and model they point at.
+//!
+//! Labels are assigned in `labels.yaml` of a [`ConfigStore`], either to a whole
+//! instance, which stands for its first model, or to one of its models, with
+//! optional fallback targets tried in order, then the cheapest configured
+//! model with the capabilities the label requires.
+//! [`resolve_label`] follows an assignment to a ready-to-use instance, with
+//! its key decrypted or fetched from its secret backend, and describes the
+//! model with what the [`ModelRegistry`] knows about it.
+
+use crate::crypto::{self, SecretCipher};
+use crate::error::{Error, Result};
+use crate::health::{HealthStatus, STATUS_METADATA};
+use crate::models::{
+    LabelAssignment, LabelTarget, Model, ModelRegistry, ModelRequirements, ProviderCollection,
+    ProviderInstance,
+};
+use crate::secrets::SecretBackends;
+use crate::store::ConfigStore;
+use serde::{Deserialize, Serialize};
+
+/// Store file holding the label assignments.
+pub const LABELS_FILE: &str = "labels.yaml";
+
+/// A label resolved to an instance and model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedLabel {
+    /// Label that was resolved
+    pub label: String,
+    /// Instance the label points at
+    pub instance: ProviderInstance,
+    /// Model the label points at
+    pub model: Model,
+    /// Why the targets tried before this one were passed over
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+/// Resolves a label in a store, revealing the instance's API key.
+pub fn resolve_label(store: &ConfigStore, name: &str) -> Result<ResolvedLabel> {
+    let assignments: Vec<LabelAssignment> = store.load_yaml(LABELS_FILE)?.unwrap_or_default();
+    let mut resolved = resolve_in(&assignments, &store.load_instances()?, name)?;
+    reveal_api_key(
+        &mut resolved.instance,
+        store.cipher()?.as_ref(),
+        &SecretBackends::builtin(),
+    )?;
+    Ok(resolved)
+}
+
+/// Resolves a label against loaded assignments and instances, leaving the
+/// instance's API key as stored.
+///
+/// The label's targets are tried in order; a target is passed over if its
+/// instance is missing or inactive, has no model to offer, or failed its last
+/// health check. If none is usable and the label requires capabilities, the
+/// cheapest model with them that a usable instance offers is used.
+pub fn resolve_in(
+    assignments: &[LabelAssignment],
+    instances: &ProviderCollection,
+    name: &str,
+) -> Result<ResolvedLabel> {
+    let assignment = assignments
+        .iter()
+        .find(|assignment| assignment.label_name == name)
+        .ok_or_else(|| Error::NotFound(format!("Label '{name}' is not assigned")))?;
+
+    let mut skipped = Vec::new();
+    for target in assignment.targets() {
+        match usable_target(instances, target) {
+            Ok((instance, model_id)) => {
+                return Ok(ResolvedLabel {
+                    label: name.to_string(),
+                    instance: instance.clone(),
+                    model: describe_model(&instance.provider_type, model_id),
+                    skipped,
+                });
+            }
+            Err(reason) => skipped.push(reason),
+        }
+    }
+
+    if !assignment.requires.is_empty() {
+        let registry = ModelRegistry::new();
+        let requirements = ModelRequirements {
+            capabilities: assignment.requires.clone(),
+            ..ModelRequirements::default()
+        };
+        if let Some(found) = instances
+            .find_model(&registry, &requirements)
+            .into_iter()
+            .find(|found| usable_instance(found.instance).is_ok())
+        {
+            return Ok(ResolvedLabel {
+                label: name.to_string(),
+                instance: found.instance.clone(),
+                model: describe_model(&found.instance.provider_type, found.model_id),
+                skipped,
+            });
+        }
+        skipped.push(format!(
+            "no usable instance offers a model with {}",
+            assignment.requires.join(", ")
+        ));
+    }
+
+    if assignment.fallbacks.is_empty()
+        && assignment.requires.is_empty()
+        && instances
+            .get_instance(assignment.target.instance_id())
+            .is_none()
+    {
+        return Err(Error::NotFound(format!(
+            "Label '{name}' points at {}",
+            skipped.join("")
+        )));
+    }
+    Err(Error::ConfigError(format!(
+        "No target of label '{name}' is available: {}",
+        skipped.join("; ")
+    )))
+}
+
+/// Gets the instance and model of a target, or why it cannot be used.
+fn usable_target<'a>(
+    instances: &'a ProviderCollection,
+    target: &'a LabelTarget,
+) -> std::result::Result<(&'a ProviderInstance, &'a str), String> {
+    let instance_id = target.instance_id();
+    let instance = instances
+        .get_instance(instance_id)
+        .ok_or_else(|| format!("missing instance '{instance_id}'"))?;
+    usable_instance(instance)?;
+    let model_id = target
+        .model_id()
+        .or_else(|| instance.models.first().map(String::as_str))
+        .ok_or_else(|| format!("instance '{instance_id}' has no models"))?;
+    Ok((instance, model_id))
+}
+
+/// Checks that an instance is active and passed its last health check.
+fn usable_instance(instance: &ProviderInstance) -> std::result::Result<(), String> {
+    let instance_id = &instance.id;
+    if !instance.active {
+        return Err(format!("instance '{instance_id}' is inactive"));
+    }
+    if let Some(status) = instance
+        .metadata
+        .get(STATUS_METADATA)
+        .filter(|status| status.as_str() != HealthStatus::Ok.as_str())
+    {
+        return Err(format!(
+            "instance '{instance_id}' failed its last health check ({status})"
+        ));
+    }
+    Ok(())
+}
+
+/// Describes a model from the registry, or by its ID alone if it is unknown.
+/// The ID stays as configured, even where the registry knows it by another.
+fn describe_model(provider_type: &str, model_id: &str) -> Model {
+    ModelRegistry::new().resolve(model_id).map_or_else(
+        || {
+            let mut model = Model::new(model_id.to_string(), model_id.to_string());
+            model.provider = provider_type.to_string();
+            model
+        },
+        |entry| Model {
+            id: model_id.to_string(),
+            ..entry.to_model()
+        },
+    )
+}
+
+/// Replaces an encrypted or backend-referenced API key with its value.
+pub fn reveal_api_key(
+    instance: &mut ProviderInstance,
+    cipher: Option<&SecretCipher>,
+    backends: &SecretBackends,
+) -> Result<()> {
+    if crypto::is_encrypted(&instance.api_key) {
+        let cipher = cipher.ok_or_else(|| {
+            Error::SecurityError(format!(
+                "Instance '{}' has an encrypted key but no passphrase or identity is available",
+                instance.id
+            ))
+        })?;
+        instance.api_key = cipher.decrypt(&instance.api_key)?;
+    } else if backends.is_reference(&instance.api_key) {
+        instance.api_key = backends.resolve(&instance.api_key)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn assignment(label: &str, target: LabelTarget) -> LabelAssignment {
+        LabelAssignment {
+            label_name: label.to_string(),
+            target,
+            assigned_at: Utc::now(),
+            assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
+        }
+    }
+
+    fn instances() -> ProviderCollection {
+        let mut instances = ProviderCollection::new();
+        let instance = ProviderInstance::new(
+            "work".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            "sk-work".to_string(),
+            vec!["gpt-4o-mini".to_string(), "in-house".to_string()],
+        );
+        instances.add(instance.id.clone(), instance);
+        instances
+    }
+
+    #[test]
+    fn test_resolve_in_follows_model_and_instance_targets() {
+        let assignments = vec![
+            assignment(
+                "fast",
+                LabelTarget::ProviderInstance {
+                    instance_id: "work".to_string(),
+                },
+            ),
+            assignment(
+                "custom",
+                LabelTarget::ProviderModel {
+                    instance_id: "work".to_string(),
+                    model_id: "in-house".to_string(),
+                },
+            ),
+        ];
+
+        let fast = resolve_in(&assignments, &instances(), "fast").unwrap();
+        assert_eq!(fast.instance.id, "work");
+        assert_eq!(fast.model.id, "gpt-4o-mini");
+        assert!(fast.model.pricing.is_some());
+
+        let custom = resolve_in(&assignments, &instances(), "custom").unwrap();
+        assert_eq!(custom.model.id, "in-house");
+        assert_eq!(custom.model.provider, "openai");
+        assert!(custom.model.pricing.is_none());
+
+        assert!(matches!(
+            resolve_in(&assignments, &instances(), "smart"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_in_falls_back_past_unusable_targets() {
+        let mut instances = instances();
+        let mut down = ProviderInstance::new(
+            "down".to_string(),
+            "openrouter".to_string(),
+            "https://openrouter.ai/api/v1".to_string(),
+            "sk-or".to_string(),
+            vec!["anthropic/claude-3.5-sonnet".to_string()],
+        );
+        down.metadata
+            .insert(STATUS_METADATA.to_string(), "unreachable".to_string());
+        instances.add(down.id.clone(), down);
+
+        let mut smart = assignment(
+            "smart",
+            LabelTarget::ProviderModel {
+                instance_id: "down".to_string(),
+                model_id: "anthropic/claude-3.5-sonnet".to_string(),
+            },
+        );
+        smart.fallbacks = vec![
+            LabelTarget::ProviderInstance {
+                instance_id: "gone".to_string(),
+            },
+            LabelTarget::ProviderModel {
+                instance_id: "work".to_string(),
+                model_id: "in-house".to_string(),
+            },
+        ];
+        let resolved = resolve_in(&[smart.clone()], &instances, "smart").unwrap();
+        assert_eq!(resolved.instance.id, "work");
+        assert_eq!(resolved.model.id, "in-house");
+        assert_eq!(resolved.skipped.len(), 2);
+        assert!(resolved.skipped[0].contains("failed its last health check (unreachable)"));
+
+        smart.fallbacks.pop();
+        smart.requires = vec!["vision".to_string()];
+        let resolved = resolve_in(&[smart.clone()], &instances, "smart").unwrap();
+        assert_eq!(resolved.instance.id, "work");
+        assert_eq!(resolved.model.id, "gpt-4o-mini");
+
+        smart.requires = vec!["embedding".to_string()];
+        let error = resolve_in(&[smart], &instances, "smart").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No target of label 'smart' is available"));
+        assert!(error.to_string().contains("a model with embedding"));
+    }
+
+    #[test]
+    fn test_resolve_label_reads_the_store() {
+        let home = tempfile::tempdir().unwrap();
+        let store = ConfigStore::for_home(home.path());
+        let lock = store.lock().unwrap();
+        for instance in instances().all_instances() {
+            lock.save_instance(instance).unwrap();
+        }
+        lock.save_yaml(
+            LABELS_FILE,
+            &vec![assignment(
+                "fast",
+                LabelTarget::ProviderInstance {
+                    instance_id: "work".to_string(),
+                },
+            )],
+        )
+        .unwrap();
+        drop(lock);
+
+        let resolved = resolve_label(&store, "fast").unwrap();
+        assert_eq!(resolved.instance.api_key, "sk-work");
+        assert_eq!(resolved.model.id, "gpt-4o-mini");
+    }
+}
@@ -0,0 +1,237 @@
+#![allow(clippy::missing_errors_doc)]
+//! Revocation of leaked keys through provider key management APIs.
+//!
+//! Revoked keys are recorded by hash in [`REVOKED_KEYS_FILE`] so later scans can
+//! tell them apart from live keys, and an instance holding a revoked key is
+//! deactivated.
+
+use crate::error::{Error, Result};
+use crate::models::DiscoveredCredential;
+use crate::plugins::ProviderPlugin;
+use crate::rotation::KEY_ID_METADATA;
+use crate::store::ConfigStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Store file recording revoked keys.
+pub const REVOKED_KEYS_FILE: &str = "revoked_keys.yaml";
+
+/// Instance metadata key holding when the instance's key was revoked.
+pub const REVOKED_AT_METADATA: &str = "revoked_at";
+
+/// A key revoked through its provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevokedKey {
+    /// SHA-256 hash of the key, as reported by scans
+    pub hash: String,
+    /// Provider the key belongs to
+    pub provider: String,
+    /// Provider-side ID of the key
+    pub key_id: String,
+    /// Instance that held the key, if any
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    /// When the key was revoked
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// A key to revoke.
+#[derive(Clone)]
+pub struct RevocationTarget {
+    provider: String,
+    secret: String,
+    instance_id: Option<String>,
+    key_id: Option<String>,
+}
+
+impl std::fmt::Debug for RevocationTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RevocationTarget")
+            .field("provider", &self.provider)
+            .field("secret", &"<redacted>")
+            .field("instance_id", &self.instance_id)
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
+impl RevocationTarget {
+    /// Creates a target for a provider's key.
+    #[must_use]
+    pub fn new(provider: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            secret: secret.into(),
+            instance_id: None,
+            key_id: None,
+        }
+    }
+
+    /// Marks the instance holding the key, which is deactivated on revocation.
+    #[must_use]
+    pub fn with_instance(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Sets the provider-side key ID, skipping the lookup by key value.
+    #[must_use]
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Provider the key belongs to.
+    #[must_use]
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// SHA-256 hash of the key.
+    #[must_use]
+    pub fn hash(&self) -> String {
+        DiscoveredCredential::hash_value(&self.secret)
+    }
+}
+
+/// Loads the keys recorded as revoked.
+pub fn load_revoked_keys(store: &ConfigStore) -> Result<Vec<RevokedKey>> {
+    Ok(store.load_yaml(REVOKED_KEYS_FILE)?.unwrap_or_default())
+}
+
+/// Checks whether a key hash was recorded as revoked.
+#[must_use]
+pub fn is_revoked(revoked: &[RevokedKey], hash: &str) -> bool {
+    revoked.iter().any(|key| key.hash == hash)
+}
+
+/// Revokes a key through its provider and records the revocation in the store.
+pub fn revoke(
+    store: &ConfigStore,
+    plugin: &dyn ProviderPlugin,
+    admin_key: &str,
+    target: &RevocationTarget,
+) -> Result<RevokedKey> {
+    let key_id = match &target.key_id {
+        Some(id) => id.clone(),
+        None => plugin
+            .find_key_id(admin_key, &target.secret)?
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "no {} key matching this value is visible to the admin key",
+                    target.provider
+                ))
+            })?,
+    };
+    plugin.revoke_key(admin_key, &key_id)?;
+
+    let revoked = RevokedKey {
+        hash: target.hash(),
+        provider: target.provider.clone(),
+        key_id,
+        instance_id: target.instance_id.clone(),
+        revoked_at: Utc::now(),
+    };
+
+    let lock = store.lock()?;
+    let mut recorded = load_revoked_keys(store)?;
+    recorded.retain(|key| key.hash != revoked.hash);
+    recorded.push(revoked.clone());
+    lock.save_yaml(REVOKED_KEYS_FILE, &recorded)?;
+
+    if let Some(instance_id) = &target.instance_id {
+        if let Some((file, mut instance)) = store.find_instance(instance_id)? {
+            instance.active = false;
+            instance.metadata.insert(
+                REVOKED_AT_METADATA.to_string(),
+                revoked.revoked_at.to_rfc3339(),
+            );
+            instance.metadata.remove(KEY_ID_METADATA);
+            lock.save_yaml(file, &instance)?;
+        }
+    }
+
+    Ok(revoked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProviderInstance;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct AdminPlugin {
+        revoked: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderPlugin for AdminPlugin {
+        fn name(&self) -> &'static str {
+            "admin"
+        }
+
+        fn confidence_score(&self, _key: &str) -> f32 {
+            0.0
+        }
+
+        fn find_key_id(&self, _admin_key: &str, api_key: &str) -> Result<Option<String>> {
+            Ok((api_key == "sk-leaked-1234567890").then(|| "key_01".to_string()))
+        }
+
+        fn revoke_key(&self, _admin_key: &str, key_id: &str) -> Result<()> {
+            self.revoked.lock().unwrap().push(key_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_revoke_marks_instance_and_records_hash() {
+        let temp = TempDir::new().unwrap();
+        let store = ConfigStore::for_home(temp.path());
+        let instance = ProviderInstance::new(
+            "admin-prod".to_string(),
+            "admin".to_string(),
+            "https://api.example.com".to_string(),
+            "sk-leaked-1234567890".to_string(),
+            Vec::new(),
+        );
+        store.lock().unwrap().save_instance(&instance).unwrap();
+
+        let plugin = AdminPlugin::default();
+        let target =
+            RevocationTarget::new("admin", "sk-leaked-1234567890").with_instance("admin-prod");
+        let revoked = revoke(&store, &plugin, "admin-key", &target).unwrap();
+
+        assert_eq!(revoked.key_id, "key_01");
+        assert_eq!(*plugin.revoked.lock().unwrap(), vec!["key_01"]);
+        let recorded = load_revoked_keys(&store).unwrap();
+        assert!(is_revoked(
+            &recorded,
+            &DiscoveredCredential::hash_value("sk-leaked-1234567890")
+        ));
+
+        let (_, stored) = store.find_instance("admin-prod").unwrap().unwrap();
+        assert!(!stored.active);
+        assert!(stored.metadata.contains_key(REVOKED_AT_METADATA));
+    }
+
+    #[test]
+    fn test_unknown_key_is_not_revoked() {
+        let temp = TempDir::new().unwrap();
+        let store = ConfigStore::for_home(temp.path());
+        let plugin = AdminPlugin::default();
+
+        let err = revoke(
+            &store,
+            &plugin,
+            "admin-key",
+            &RevocationTarget::new("admin", "sk-other-1234567890"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+        assert!(plugin.revoked.lock().unwrap().is_empty());
+        assert!(load_revoked_keys(&store).unwrap().is_empty());
+    }
+}
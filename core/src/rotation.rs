@@ -0,0 +1,382 @@
+#![allow(clippy::missing_errors_doc)]
+//! Rotation of stored instance keys through provider key management APIs.
+//!
+//! A rotation creates a new key, stores it in place of the old one (keeping it
+//! encrypted or in the same secret backend), verifies it with a probe call and
+//! optionally revokes the old key. If verification fails the old key is put
+//! back and the new one revoked, so a failed rotation leaves the instance
+//! working.
+
+use crate::crypto;
+use crate::error::{Error, Result};
+use crate::models::ProviderInstance;
+use crate::plugins::ProviderPlugin;
+use crate::secrets::SecretBackends;
+use crate::store::{ConfigStore, StoreLock};
+use std::path::PathBuf;
+
+/// Instance metadata key holding the provider-side ID of the current key.
+pub const KEY_ID_METADATA: &str = "key_id";
+
+/// One completed step of a rotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationStep {
+    /// A new key was created
+    Created {
+        /// Provider-side ID of the new key
+        key_id: String,
+    },
+    /// The instance now stores the new key
+    Stored {
+        /// File the instance was written to
+        path: PathBuf,
+    },
+    /// The new key was accepted by the provider
+    Verified,
+    /// The old key was revoked
+    Revoked {
+        /// Provider-side ID of the old key
+        key_id: String,
+    },
+    /// The old key was left active
+    RevokeSkipped {
+        /// Why the old key was not revoked
+        reason: String,
+    },
+}
+
+/// Options for a rotation.
+#[derive(Debug, Clone, Default)]
+pub struct RotateOptions {
+    /// Revoke the old key once the new one is verified
+    pub revoke_old: bool,
+    /// Name given to the new key at the provider
+    pub key_name: Option<String>,
+}
+
+impl RotateOptions {
+    /// Creates options that keep the old key active.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the old key is revoked.
+    #[must_use]
+    pub const fn with_revoke_old(mut self, revoke_old: bool) -> Self {
+        self.revoke_old = revoke_old;
+        self
+    }
+
+    /// Sets the name given to the new key.
+    #[must_use]
+    pub fn with_key_name(mut self, name: impl Into<String>) -> Self {
+        self.key_name = Some(name.into());
+        self
+    }
+}
+
+/// Rotates keys of instances in a config store.
+pub struct KeyRotator<'a> {
+    store: &'a ConfigStore,
+    plugin: &'a dyn ProviderPlugin,
+    backends: SecretBackends,
+}
+
+impl std::fmt::Debug for KeyRotator<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyRotator")
+            .field("store", &self.store)
+            .field("plugin", &self.plugin.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> KeyRotator<'a> {
+    /// Creates a rotator using the builtin secret backends.
+    #[must_use]
+    pub fn new(store: &'a ConfigStore, plugin: &'a dyn ProviderPlugin) -> Self {
+        Self {
+            store,
+            plugin,
+            backends: SecretBackends::builtin(),
+        }
+    }
+
+    /// Sets the secret backends references are stored through.
+    #[must_use]
+    pub fn with_backends(mut self, backends: SecretBackends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Rotates an instance's key, calling `on_step` as each step completes.
+    pub fn rotate(
+        &self,
+        instance_id: &str,
+        admin_key: &str,
+        options: &RotateOptions,
+        mut on_step: impl FnMut(&RotationStep),
+    ) -> Result<Vec<RotationStep>> {
+        let mut steps = Vec::new();
+        let mut record = |step: RotationStep| {
+            on_step(&step);
+            steps.push(step);
+        };
+
+        let lock = self.store.lock()?;
+        let (file, old) = self.store.find_instance(instance_id)?.ok_or_else(|| {
+            Error::NotFound(format!("provider instance '{instance_id}' not found"))
+        })?;
+        // A backend entry is overwritten in place, so keep the old secret to restore
+        let previous_secret = self
+            .backends
+            .owner(&old.api_key)
+            .map(|backend| backend.fetch(&old.api_key))
+            .transpose()?;
+
+        let name = options
+            .key_name
+            .clone()
+            .unwrap_or_else(|| format!("aicred-{instance_id}"));
+        let issued = self.plugin.create_key(admin_key, &name)?;
+        record(RotationStep::Created {
+            key_id: issued.id.clone(),
+        });
+
+        let mut rotated = old.clone();
+        rotated.api_key = match self.stored_value(&lock, &old, &issued.secret) {
+            Ok(value) => value,
+            Err(e) => return Err(self.abandon(admin_key, &issued.id, e)),
+        };
+        rotated
+            .metadata
+            .insert(KEY_ID_METADATA.to_string(), issued.id.clone());
+        let path = match lock.save_yaml(&file, &rotated) {
+            Ok(path) => path,
+            Err(e) => return Err(self.abandon(admin_key, &issued.id, e)),
+        };
+        record(RotationStep::Stored { path });
+
+        if let Err(e) = self.plugin.verify_key(&issued.secret) {
+            lock.save_yaml(&file, &old)?;
+            if let (Some(backend), Some(secret)) =
+                (self.backends.owner(&old.api_key), &previous_secret)
+            {
+                backend.store(&old.id, secret)?;
+            }
+            return Err(self.abandon(
+                admin_key,
+                &issued.id,
+                Error::ValidationError(format!(
+                    "new key failed verification and the old key was restored: {e}"
+                )),
+            ));
+        }
+        record(RotationStep::Verified);
+
+        match old.metadata.get(KEY_ID_METADATA) {
+            _ if !options.revoke_old => record(RotationStep::RevokeSkipped {
+                reason: "revocation not requested".to_string(),
+            }),
+            Some(old_id) => {
+                self.plugin.revoke_key(admin_key, old_id)?;
+                record(RotationStep::Revoked {
+                    key_id: old_id.clone(),
+                });
+            }
+            None => record(RotationStep::RevokeSkipped {
+                reason: format!(
+                    "the provider ID of the old key is unknown; revoke it in the {} console",
+                    self.plugin.name()
+                ),
+            }),
+        }
+
+        Ok(steps)
+    }
+
+    /// Stores the new secret the way the old one was stored.
+    fn stored_value(
+        &self,
+        lock: &StoreLock<'_>,
+        old: &ProviderInstance,
+        secret: &str,
+    ) -> Result<String> {
+        if crypto::is_encrypted(&old.api_key) {
+            let cipher = lock.store().cipher()?.ok_or_else(|| {
+                Error::ConfigError(
+                    "the old key is encrypted but no passphrase or identity is available"
+                        .to_string(),
+                )
+            })?;
+            return cipher.encrypt(secret);
+        }
+        self.backends.owner(&old.api_key).map_or_else(
+            || Ok(secret.to_string()),
+            |backend| backend.store(&old.id, secret),
+        )
+    }
+
+    /// Revokes a new key after a failed rotation, keeping the original error.
+    fn abandon(&self, admin_key: &str, key_id: &str, error: Error) -> Error {
+        match self.plugin.revoke_key(admin_key, key_id) {
+            Ok(()) => error,
+            Err(e) => Error::ApiError(format!(
+                "{error}; revoking the new key {key_id} also failed: {e}"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::IssuedKey;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    struct ManagedPlugin {
+        accept: bool,
+        revoked: Mutex<Vec<String>>,
+    }
+
+    impl ManagedPlugin {
+        fn new(accept: bool) -> Self {
+            Self {
+                accept,
+                revoked: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderPlugin for ManagedPlugin {
+        fn name(&self) -> &'static str {
+            "managed"
+        }
+
+        fn confidence_score(&self, _key: &str) -> f32 {
+            0.0
+        }
+
+        fn create_key(&self, _admin_key: &str, name: &str) -> Result<IssuedKey> {
+            Ok(IssuedKey {
+                id: format!("{name}-2"),
+                secret: "sk-new-1234567890".to_string(),
+            })
+        }
+
+        fn revoke_key(&self, _admin_key: &str, key_id: &str) -> Result<()> {
+            self.revoked.lock().unwrap().push(key_id.to_string());
+            Ok(())
+        }
+
+        fn verify_key(&self, _api_key: &str) -> Result<()> {
+            if self.accept {
+                Ok(())
+            } else {
+                Err(Error::ApiError("401".to_string()))
+            }
+        }
+    }
+
+    fn store_with_instance(key_id: Option<&str>) -> (TempDir, ConfigStore) {
+        let temp = TempDir::new().unwrap();
+        let store = ConfigStore::for_home(temp.path());
+        let mut instance = ProviderInstance::new(
+            "managed-prod".to_string(),
+            "managed".to_string(),
+            "https://api.example.com".to_string(),
+            "sk-old-1234567890".to_string(),
+            Vec::new(),
+        );
+        if let Some(id) = key_id {
+            instance
+                .metadata
+                .insert(KEY_ID_METADATA.to_string(), id.to_string());
+        }
+        store.lock().unwrap().save_instance(&instance).unwrap();
+        (temp, store)
+    }
+
+    fn stored(store: &ConfigStore) -> ProviderInstance {
+        store.load_instances().unwrap().list()[0].clone()
+    }
+
+    #[test]
+    fn test_rotate_and_revoke_old_key() {
+        let (_temp, store) = store_with_instance(Some("old-1"));
+        let plugin = ManagedPlugin::new(true);
+
+        let mut seen = 0;
+        let steps = KeyRotator::new(&store, &plugin)
+            .rotate(
+                "managed-prod",
+                "admin",
+                &RotateOptions::new().with_revoke_old(true),
+                |_| seen += 1,
+            )
+            .unwrap();
+
+        assert_eq!(seen, steps.len());
+        assert!(matches!(steps[0], RotationStep::Created { .. }));
+        assert!(steps.contains(&RotationStep::Verified));
+        assert_eq!(
+            steps.last(),
+            Some(&RotationStep::Revoked {
+                key_id: "old-1".to_string()
+            })
+        );
+
+        let instance = stored(&store);
+        assert_eq!(instance.api_key, "sk-new-1234567890");
+        assert_eq!(
+            instance.metadata.get(KEY_ID_METADATA).map(String::as_str),
+            Some("aicred-managed-prod-2")
+        );
+        assert_eq!(*plugin.revoked.lock().unwrap(), vec!["old-1"]);
+    }
+
+    #[test]
+    fn test_unknown_old_key_is_not_revoked() {
+        let (_temp, store) = store_with_instance(None);
+        let plugin = ManagedPlugin::new(true);
+        let steps = KeyRotator::new(&store, &plugin)
+            .rotate(
+                "managed-prod",
+                "admin",
+                &RotateOptions::new().with_revoke_old(true),
+                |_| {},
+            )
+            .unwrap();
+
+        assert!(matches!(
+            steps.last(),
+            Some(RotationStep::RevokeSkipped { .. })
+        ));
+        assert!(plugin.revoked.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_verification_restores_old_key() {
+        let (_temp, store) = store_with_instance(Some("old-1"));
+        let plugin = ManagedPlugin::new(false);
+        let err = KeyRotator::new(&store, &plugin)
+            .rotate(
+                "managed-prod",
+                "admin",
+                &RotateOptions::new().with_revoke_old(true),
+                |_| {},
+            )
+            .unwrap_err();
+
+        assert!(err.to_string().contains("restored"));
+        assert_eq!(stored(&store).api_key, "sk-old-1234567890");
+        // The new key is revoked, the old one kept
+        assert_eq!(
+            *plugin.revoked.lock().unwrap(),
+            vec!["aicred-managed-prod-2"]
+        );
+    }
+}
@@ -0,0 +1,360 @@
+#![allow(clippy::missing_errors_doc)]
+//! Custom detection rules matched against file contents during scans.
+//!
+//! Rules are stored in [`DETECTION_RULES_FILE`] in the config store:
+//!
+//! ```yaml
+//! rules:
+//!   - id: acme-ai-key
+//!     description: Acme inference API key
+//!     provider: acme
+//!     regex: '\b(acme_[A-Za-z0-9]{32})\b'
+//!     keywords: [acme_]
+//!     entropy: 3.5
+//!     allowlist: ['^acme_example']
+//! ```
+//!
+//! A rule only runs on files containing one of its keywords (case-insensitive),
+//! and reports the first non-empty capture group of each match, or the group
+//! selected by `secret_group`. Rules can be imported from other scanners, see
+//! [`gitleaks`].
+
+pub mod gitleaks;
+
+use crate::discovery::{annotate_positions, walk_files, ScannerConfig};
+use crate::error::{Error, Result};
+use crate::models::{Confidence, DiscoveredCredential, ValueType};
+use crate::store::ConfigStore;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Store file holding custom detection rules.
+pub const DETECTION_RULES_FILE: &str = "detection_rules.yaml";
+
+/// Metadata key naming the rule that found a credential.
+pub const RULE_METADATA: &str = "rule";
+
+/// A user-defined pattern for a provider's credentials.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectionRule {
+    /// Unique rule ID
+    pub id: String,
+    /// Human-readable description of the rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Provider credentials found by the rule belong to
+    pub provider: String,
+    /// Pattern matched against file contents
+    pub regex: String,
+    /// Capture group holding the secret (0 for the whole match)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_group: Option<usize>,
+    /// Only run on files containing one of these strings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// Only run on files whose path matches this pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Minimum Shannon entropy of the secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<f64>,
+    /// Patterns of secrets to ignore, such as examples
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowlist: Vec<String>,
+    /// Confidence of findings (default: Medium)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+}
+
+impl DetectionRule {
+    /// Creates a rule reporting matches of `regex` as `provider` credentials.
+    #[must_use]
+    pub fn new(
+        id: impl Into<String>,
+        provider: impl Into<String>,
+        regex: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            description: None,
+            provider: provider.into(),
+            regex: regex.into(),
+            secret_group: None,
+            keywords: Vec::new(),
+            path: None,
+            entropy: None,
+            allowlist: Vec::new(),
+            confidence: None,
+        }
+    }
+
+    /// Sets the keywords a file must contain for the rule to run.
+    #[must_use]
+    pub fn with_keywords(mut self, keywords: Vec<String>) -> Self {
+        self.keywords = keywords;
+        self
+    }
+
+    /// Sets the minimum entropy of reported secrets.
+    #[must_use]
+    pub const fn with_entropy(mut self, entropy: f64) -> Self {
+        self.entropy = Some(entropy);
+        self
+    }
+}
+
+/// A set of detection rules.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectionRules {
+    /// Rules run on every scanned file
+    #[serde(default)]
+    pub rules: Vec<DetectionRule>,
+}
+
+impl DetectionRules {
+    /// Parses rules from YAML.
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        serde_yaml::from_str(content)
+            .map_err(|e| Error::ConfigError(format!("invalid detection rules: {e}")))
+    }
+
+    /// Loads rules from a YAML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_yaml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Loads the rules kept in a config store.
+    pub fn load_from_store(store: &ConfigStore) -> Result<Self> {
+        Ok(store.load_yaml(DETECTION_RULES_FILE)?.unwrap_or_default())
+    }
+
+    /// Adds rules, replacing existing rules with the same ID. Returns how many were replaced.
+    pub fn merge(&mut self, rules: Vec<DetectionRule>) -> usize {
+        let mut replaced = 0;
+        for rule in rules {
+            if let Some(existing) = self.rules.iter_mut().find(|r| r.id == rule.id) {
+                *existing = rule;
+                replaced += 1;
+            } else {
+                self.rules.push(rule);
+            }
+        }
+        replaced
+    }
+}
+
+/// A detection rule with its patterns compiled.
+#[derive(Debug)]
+struct CompiledRule {
+    rule: DetectionRule,
+    regex: Regex,
+    path: Option<Regex>,
+    keywords: Vec<String>,
+    allowlist: Vec<Regex>,
+}
+
+/// Detection rules compiled for scanning.
+#[derive(Debug, Default)]
+pub struct RuleMatcher {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleMatcher {
+    /// Compiles rules, failing on the first invalid pattern.
+    pub fn new(rules: &[DetectionRule]) -> Result<Self> {
+        let compile = |id: &str, pattern: &str| {
+            Regex::new(pattern).map_err(|e| {
+                Error::ValidationError(format!("detection rule '{id}' has an invalid pattern: {e}"))
+            })
+        };
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    regex: compile(&rule.id, &rule.regex)?,
+                    path: rule
+                        .path
+                        .as_deref()
+                        .map(|p| compile(&rule.id, p))
+                        .transpose()?,
+                    keywords: rule.keywords.iter().map(|k| k.to_lowercase()).collect(),
+                    allowlist: rule
+                        .allowlist
+                        .iter()
+                        .map(|p| compile(&rule.id, p))
+                        .collect::<Result<_>>()?,
+                    rule: rule.clone(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Checks if there are no rules.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Finds credentials matching the rules in a file's content.
+    #[must_use]
+    pub fn scan_content(&self, path: &Path, content: &str) -> Vec<DiscoveredCredential> {
+        let source = path.display().to_string();
+        let lowered = content.to_lowercase();
+        let mut keys = Vec::new();
+
+        for compiled in &self.rules {
+            if compiled.path.as_ref().is_some_and(|p| !p.is_match(&source)) {
+                continue;
+            }
+            if !compiled.keywords.is_empty()
+                && !compiled.keywords.iter().any(|k| lowered.contains(k))
+            {
+                continue;
+            }
+
+            for captures in compiled.regex.captures_iter(content) {
+                let secret = compiled.rule.secret_group.map_or_else(
+                    || {
+                        captures
+                            .iter()
+                            .skip(1)
+                            .flatten()
+                            .find(|m| !m.as_str().is_empty())
+                            .or_else(|| captures.get(0))
+                    },
+                    |group| captures.get(group),
+                );
+                let Some(secret) = secret.map(|m| m.as_str()).filter(|s| !s.is_empty()) else {
+                    continue;
+                };
+                if compiled
+                    .rule
+                    .entropy
+                    .is_some_and(|min| shannon_entropy(secret) < min)
+                {
+                    continue;
+                }
+                if compiled.allowlist.iter().any(|a| a.is_match(secret)) {
+                    continue;
+                }
+
+                let mut key = DiscoveredCredential::new(
+                    compiled.rule.provider.clone(),
+                    source.clone(),
+                    ValueType::ApiKey,
+                    compiled.rule.confidence.unwrap_or(Confidence::Medium),
+                    secret.to_string(),
+                );
+                key.metadata = Some(serde_json::json!({ RULE_METADATA: compiled.rule.id }));
+                keys.push(key);
+            }
+        }
+
+        annotate_positions(&mut keys, content, 0);
+        keys
+    }
+
+    /// Finds credentials matching the rules in the files beneath a scan root.
+    #[must_use]
+    pub fn scan_dir(&self, root: &Path, config: &ScannerConfig) -> Vec<DiscoveredCredential> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        walk_files(root, config)
+            .into_iter()
+            .filter_map(|path| {
+                let content = std::fs::read_to_string(&path).ok()?;
+                Some(self.scan_content(&path, &content))
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+/// Shannon entropy of a string in bits per character.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn shannon_entropy(value: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    let len = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acme_rule() -> DetectionRule {
+        DetectionRule::new("acme-ai-key", "acme", r"\b(acme_[A-Za-z0-9]{16})\b")
+            .with_keywords(vec!["ACME_".to_string()])
+    }
+
+    #[test]
+    fn test_rule_reports_capture_group() {
+        let matcher = RuleMatcher::new(&[acme_rule()]).unwrap();
+        let keys = matcher.scan_content(
+            Path::new("/home/u/app.toml"),
+            "[ai]\ntoken = \"acme_Ab3dEf6hIj9kLm2n\"\n",
+        );
+
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].provider, "acme");
+        assert_eq!(keys[0].full_value(), Some("acme_Ab3dEf6hIj9kLm2n"));
+        assert_eq!(keys[0].source_line, Some(2));
+        assert_eq!(
+            keys[0].metadata.as_ref().unwrap()[RULE_METADATA],
+            "acme-ai-key"
+        );
+    }
+
+    #[test]
+    fn test_keywords_entropy_and_allowlist_filter_matches() {
+        let mut rule = acme_rule().with_entropy(3.0);
+        rule.allowlist = vec!["^acme_Example".to_string()];
+        let matcher = RuleMatcher::new(&[rule]).unwrap();
+        let path = Path::new("/home/u/.env");
+
+        assert!(matcher.scan_content(path, "nothing here").is_empty());
+        assert!(matcher
+            .scan_content(path, "KEY=acme_aaaaaaaaaaaaaaaa")
+            .is_empty());
+        assert!(matcher
+            .scan_content(path, "KEY=acme_ExampleExample12")
+            .is_empty());
+        assert_eq!(
+            matcher
+                .scan_content(path, "KEY=acme_Ab3dEf6hIj9kLm2n")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let rule = DetectionRule::new("broken", "acme", "(unclosed");
+        let err = RuleMatcher::new(&[rule]).unwrap_err();
+        assert!(err.to_string().contains("broken"));
+    }
+
+    #[test]
+    fn test_merge_replaces_rules_by_id() {
+        let mut rules = DetectionRules::default();
+        assert_eq!(rules.merge(vec![acme_rule()]), 0);
+        let mut updated = acme_rule();
+        updated.provider = "acme-ai".to_string();
+        assert_eq!(rules.merge(vec![updated]), 1);
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.rules[0].provider, "acme-ai");
+    }
+}
@@ -0,0 +1,136 @@
+//! Resolution of `bw://` secret references through the Bitwarden CLI.
+
+use super::{run_tool, SecretBackend};
+use crate::error::{Error, Result};
+use serde_json::Value;
+
+const SCHEME: &str = "bw://";
+
+/// Fields `bw get` can return directly; anything else is a custom field.
+const BUILTIN_FIELDS: [&str; 5] = ["password", "username", "notes", "totp", "uri"];
+
+/// Resolves `bw://<item>[/<field>]` references with `bw get`.
+///
+/// The item is a name or ID and the field defaults to `password`; other names
+/// select a custom field. The vault must be unlocked, with `BW_SESSION` set.
+/// Removing an instance leaves the item in place.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitwardenBackend;
+
+impl BitwardenBackend {
+    /// Splits a reference into its item and field.
+    pub fn parse_reference(reference: &str) -> Result<(&str, &str)> {
+        let rest = reference
+            .strip_prefix(SCHEME)
+            .filter(|rest| !rest.is_empty())
+            .ok_or_else(|| {
+                Error::ConfigError(format!(
+                    "invalid Bitwarden reference '{reference}'; expected bw://<item>[/<field>]"
+                ))
+            })?;
+        let (item, field) = rest.rsplit_once('/').unwrap_or((rest, "password"));
+        if item.is_empty() || field.is_empty() {
+            return Err(Error::ConfigError(format!(
+                "invalid Bitwarden reference '{reference}'; expected bw://<item>[/<field>]"
+            )));
+        }
+        Ok((item, field))
+    }
+
+    /// Finds a custom field's value in `bw get item` output.
+    fn custom_field(item: &Value, field: &str) -> Option<String> {
+        item.get("fields")?
+            .as_array()?
+            .iter()
+            .find(|f| f.get("name").and_then(Value::as_str) == Some(field))?
+            .get("value")?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    fn bw(args: &[&str]) -> Result<String> {
+        let mut full = vec!["--nointeraction"];
+        full.extend_from_slice(args);
+        let output = run_tool("Bitwarden backend", "bw", &full, &[], None)?;
+        if !output.status.success() {
+            return Err(Error::SecurityError(format!(
+                "`bw {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches(['\r', '\n'])
+            .to_string())
+    }
+}
+
+impl SecretBackend for BitwardenBackend {
+    fn name(&self) -> &'static str {
+        "bw"
+    }
+
+    fn owns(&self, value: &str) -> bool {
+        value.starts_with(SCHEME)
+    }
+
+    fn store(&self, _account: &str, _secret: &str) -> Result<String> {
+        Err(Error::ConfigError(
+            "Bitwarden items are not created by aicred; add the key in Bitwarden and use its bw:// reference as the API key".to_string(),
+        ))
+    }
+
+    fn fetch(&self, reference: &str) -> Result<String> {
+        let (item, field) = Self::parse_reference(reference)?;
+        if BUILTIN_FIELDS.contains(&field) {
+            return Self::bw(&["get", field, item]);
+        }
+
+        let json: Value = serde_json::from_str(&Self::bw(&["get", "item", item])?)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        Self::custom_field(&json, field).ok_or_else(|| {
+            Error::NotFound(format!("Bitwarden item '{item}' has no field '{field}'"))
+        })
+    }
+
+    fn delete(&self, _reference: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references() {
+        assert!(BitwardenBackend.owns("bw://OpenAI"));
+        assert_eq!(
+            BitwardenBackend::parse_reference("bw://OpenAI").unwrap(),
+            ("OpenAI", "password")
+        );
+        assert_eq!(
+            BitwardenBackend::parse_reference("bw://OpenAI/api_key").unwrap(),
+            ("OpenAI", "api_key")
+        );
+        for invalid in ["bw://", "bw:///password", "bw://OpenAI/"] {
+            assert!(BitwardenBackend::parse_reference(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_custom_field_lookup() {
+        let item = serde_json::json!({
+            "name": "OpenAI",
+            "fields": [
+                { "name": "org", "value": "org-123" },
+                { "name": "api_key", "value": "sk-bw1234567890" }
+            ]
+        });
+        assert_eq!(
+            BitwardenBackend::custom_field(&item, "api_key").as_deref(),
+            Some("sk-bw1234567890")
+        );
+        assert_eq!(BitwardenBackend::custom_field(&item, "missing"), None);
+    }
+}
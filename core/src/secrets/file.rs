@@ -0,0 +1,30 @@
+//! Inline storage of secrets in the config files themselves.
+
+use super::SecretBackend;
+use crate::error::Result;
+
+/// Keeps the secret inline in the instance file; the default backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileBackend;
+
+impl SecretBackend for FileBackend {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn owns(&self, _value: &str) -> bool {
+        false
+    }
+
+    fn store(&self, _account: &str, secret: &str) -> Result<String> {
+        Ok(secret.to_string())
+    }
+
+    fn fetch(&self, reference: &str) -> Result<String> {
+        Ok(reference.to_string())
+    }
+
+    fn delete(&self, _reference: &str) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+//! Storage of secrets in the OS keyring.
+//!
+//! Uses the platform's own tooling so no native libraries are linked: the
+//! `security` tool for the macOS Keychain, `secret-tool` for the Secret Service
+//! on Linux and the BSDs, and the Credential Manager API through PowerShell on
+//! Windows. Secrets are always passed on stdin, never on the command line.
+
+use super::{find_program, run_tool, SecretBackend};
+use crate::error::{Error, Result};
+use std::process::Output;
+
+/// Keyring service secrets are stored under unless configured otherwise.
+pub const DEFAULT_SERVICE: &str = "aicred";
+
+const SCHEME: &str = "keyring:";
+
+/// Stores secrets in the OS keyring and keeps `keyring:<service>/<account>`
+/// references in the config.
+#[derive(Debug, Clone)]
+pub struct KeyringBackend {
+    service: String,
+}
+
+impl Default for KeyringBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyringBackend {
+    /// Creates a backend using [`DEFAULT_SERVICE`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            service: DEFAULT_SERVICE.to_string(),
+        }
+    }
+
+    /// Sets the service secrets are stored under.
+    #[must_use]
+    pub fn with_service(mut self, service: impl Into<String>) -> Self {
+        self.service = service.into();
+        self
+    }
+
+    /// Builds the reference for an account under this backend's service.
+    #[must_use]
+    pub fn reference(&self, account: &str) -> String {
+        format!("{SCHEME}{}/{account}", self.service)
+    }
+
+    /// Checks that the platform keyring tooling is installed and usable.
+    pub fn check_available() -> Result<()> {
+        platform::check()
+    }
+
+    /// Splits a reference into its service and account.
+    pub fn parse_reference(reference: &str) -> Result<(&str, &str)> {
+        reference
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.split_once('/'))
+            .filter(|(service, account)| !service.is_empty() && !account.is_empty())
+            .ok_or_else(|| {
+                Error::ConfigError(format!(
+                    "invalid keyring reference '{reference}'; expected keyring:<service>/<account>"
+                ))
+            })
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn store(&self, account: &str, secret: &str) -> Result<String> {
+        if account.is_empty() {
+            return Err(Error::ValidationError(
+                "keyring account cannot be empty".to_string(),
+            ));
+        }
+        platform::set(&self.service, account, secret)?;
+        Ok(self.reference(account))
+    }
+
+    fn fetch(&self, reference: &str) -> Result<String> {
+        let (service, account) = Self::parse_reference(reference)?;
+        platform::get(service, account)
+    }
+
+    fn delete(&self, reference: &str) -> Result<()> {
+        let (service, account) = Self::parse_reference(reference)?;
+        platform::delete(service, account)
+    }
+}
+
+/// Runs a keyring tool, feeding `input` on stdin.
+fn run(program: &str, args: &[&str], envs: &[(&str, &str)], input: Option<&str>) -> Result<Output> {
+    run_tool("OS keyring", program, args, envs, input)
+}
+
+/// Converts a tool's output into its trimmed stdout, or an error.
+fn finish(action: &str, service: &str, account: &str, output: &Output) -> Result<String> {
+    if output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Ok(stdout.trim_end_matches(['\r', '\n']).to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if action == "lookup" {
+        return Err(Error::NotFound(format!(
+            "no keyring entry for {service}/{account}"
+        )));
+    }
+    Err(Error::SecurityError(format!(
+        "keyring {action} for {service}/{account} failed: {}",
+        stderr.trim()
+    )))
+}
+
+/// Checks that a keyring tool is on `PATH`.
+fn require(program: &str) -> Result<()> {
+    find_program(program).map(drop).ok_or_else(|| {
+        Error::ConfigError(format!(
+            "`{program}` is required to use the OS keyring but was not found"
+        ))
+    })
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{finish, require, run};
+    use crate::error::Result;
+
+    pub fn check() -> Result<()> {
+        require("security")
+    }
+
+    /// Quotes an argument for `security -i`.
+    fn quote(value: &str) -> String {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    pub fn set(service: &str, account: &str, secret: &str) -> Result<()> {
+        // Interactive mode reads the command from stdin, keeping the secret off argv
+        let command = format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            quote(service),
+            quote(account),
+            quote(secret)
+        );
+        let output = run("security", &["-i"], &[], Some(&command))?;
+        finish("store", service, account, &output).map(drop)
+    }
+
+    pub fn get(service: &str, account: &str) -> Result<String> {
+        let output = run(
+            "security",
+            &["find-generic-password", "-s", service, "-a", account, "-w"],
+            &[],
+            None,
+        )?;
+        finish("lookup", service, account, &output)
+    }
+
+    pub fn delete(service: &str, account: &str) -> Result<()> {
+        let output = run(
+            "security",
+            &["delete-generic-password", "-s", service, "-a", account],
+            &[],
+            None,
+        )?;
+        finish("delete", service, account, &output).map(drop)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{finish, require, run};
+    use crate::error::Result;
+
+    pub fn check() -> Result<()> {
+        require("powershell.exe")
+    }
+
+    /// Credential Manager access through advapi32; the action and target come
+    /// from the environment and the secret from stdin.
+    const SCRIPT: &str = r#"
+$ErrorActionPreference = 'Stop'
+Add-Type -TypeDefinition @'
+using System;
+using System.Runtime.InteropServices;
+using System.Text;
+public static class AicredCredential {
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    private struct CREDENTIAL {
+        public int Flags; public int Type; public string TargetName; public string Comment;
+        public System.Runtime.InteropServices.ComTypes.FILETIME LastWritten;
+        public int CredentialBlobSize; public IntPtr CredentialBlob; public int Persist;
+        public int AttributeCount; public IntPtr Attributes; public string TargetAlias; public string UserName;
+    }
+    [DllImport("advapi32.dll", SetLastError = true, CharSet = CharSet.Unicode)]
+    private static extern bool CredWriteW(ref CREDENTIAL credential, int flags);
+    [DllImport("advapi32.dll", SetLastError = true, CharSet = CharSet.Unicode)]
+    private static extern bool CredReadW(string target, int type, int flags, out IntPtr credential);
+    [DllImport("advapi32.dll", SetLastError = true, CharSet = CharSet.Unicode)]
+    private static extern bool CredDeleteW(string target, int type, int flags);
+    [DllImport("advapi32.dll")]
+    private static extern void CredFree(IntPtr buffer);
+    public static void Write(string target, string user, string secret) {
+        byte[] blob = Encoding.Unicode.GetBytes(secret);
+        CREDENTIAL credential = new CREDENTIAL();
+        credential.Type = 1; credential.Persist = 2; credential.TargetName = target; credential.UserName = user;
+        credential.CredentialBlobSize = blob.Length;
+        credential.CredentialBlob = Marshal.AllocHGlobal(blob.Length);
+        try {
+            Marshal.Copy(blob, 0, credential.CredentialBlob, blob.Length);
+            if (!CredWriteW(ref credential, 0)) { throw new System.ComponentModel.Win32Exception(); }
+        } finally { Marshal.FreeHGlobal(credential.CredentialBlob); }
+    }
+    public static string Read(string target) {
+        IntPtr pointer;
+        if (!CredReadW(target, 1, 0, out pointer)) { throw new System.ComponentModel.Win32Exception(); }
+        try {
+            CREDENTIAL credential = (CREDENTIAL)Marshal.PtrToStructure(pointer, typeof(CREDENTIAL));
+            return Marshal.PtrToStringUni(credential.CredentialBlob, credential.CredentialBlobSize / 2);
+        } finally { CredFree(pointer); }
+    }
+    public static void Delete(string target) {
+        if (!CredDeleteW(target, 1, 0)) { throw new System.ComponentModel.Win32Exception(); }
+    }
+}
+'@
+$target = $env:AICRED_KEYRING_TARGET
+switch ($env:AICRED_KEYRING_ACTION) {
+    'store' { [AicredCredential]::Write($target, $env:AICRED_KEYRING_ACCOUNT, [Console]::In.ReadToEnd()) }
+    'lookup' { [Console]::Out.Write([AicredCredential]::Read($target)) }
+    'delete' { [AicredCredential]::Delete($target) }
+}
+"#;
+
+    fn credential(
+        action: &str,
+        service: &str,
+        account: &str,
+        input: Option<&str>,
+    ) -> Result<String> {
+        let target = format!("{service}/{account}");
+        let output = run(
+            "powershell.exe",
+            &["-NoProfile", "-NonInteractive", "-Command", SCRIPT],
+            &[
+                ("AICRED_KEYRING_ACTION", action),
+                ("AICRED_KEYRING_TARGET", &target),
+                ("AICRED_KEYRING_ACCOUNT", account),
+            ],
+            Some(input.unwrap_or_default()),
+        )?;
+        finish(action, service, account, &output)
+    }
+
+    pub fn set(service: &str, account: &str, secret: &str) -> Result<()> {
+        credential("store", service, account, Some(secret)).map(drop)
+    }
+
+    pub fn get(service: &str, account: &str) -> Result<String> {
+        credential("lookup", service, account, None)
+    }
+
+    pub fn delete(service: &str, account: &str) -> Result<()> {
+        credential("delete", service, account, None).map(drop)
+    }
+}
+
+#[cfg(all(not(target_os = "macos"), not(windows)))]
+mod platform {
+    use super::{finish, require, run};
+    use crate::error::{Error, Result};
+
+    pub fn check() -> Result<()> {
+        require("secret-tool")?;
+        // The Secret Service is reached over the session bus
+        if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none_or(|address| address.is_empty()) {
+            return Err(Error::ConfigError(
+                "no D-Bus session bus is available for the Secret Service".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn set(service: &str, account: &str, secret: &str) -> Result<()> {
+        let label = format!("{service}: {account}");
+        let output = run(
+            "secret-tool",
+            &[
+                "store", "--label", &label, "service", service, "account", account,
+            ],
+            &[],
+            Some(secret),
+        )?;
+        finish("store", service, account, &output).map(drop)
+    }
+
+    pub fn get(service: &str, account: &str) -> Result<String> {
+        let output = run(
+            "secret-tool",
+            &["lookup", "service", service, "account", account],
+            &[],
+            None,
+        )?;
+        finish("lookup", service, account, &output)
+    }
+
+    pub fn delete(service: &str, account: &str) -> Result<()> {
+        let output = run(
+            "secret-tool",
+            &["clear", "service", service, "account", account],
+            &[],
+            None,
+        )?;
+        finish("delete", service, account, &output).map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_format() {
+        let backend = KeyringBackend::new().with_service("aicred-test");
+        let reference = backend.reference("openai-prod");
+        assert_eq!(reference, "keyring:aicred-test/openai-prod");
+        assert!(backend.owns(&reference));
+        assert_eq!(
+            KeyringBackend::parse_reference(&reference).unwrap(),
+            ("aicred-test", "openai-prod")
+        );
+
+        for invalid in [
+            "keyring:",
+            "keyring:aicred",
+            "keyring:/id",
+            "keyring:aicred/",
+        ] {
+            assert!(KeyringBackend::parse_reference(invalid).is_err());
+        }
+        assert!(!backend.owns("sk-keyring1234567890"));
+    }
+}
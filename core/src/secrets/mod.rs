@@ -0,0 +1,278 @@
+#![allow(clippy::missing_errors_doc)]
+//! Pluggable storage backends for instance secrets.
+//!
+//! An instance's `api_key` holds either the secret itself or a reference such
+//! as `keyring:aicred/openai-prod` that names the backend holding it. References
+//! are resolved only when the secret is actually needed, so listing or editing
+//! instances never touches the backend.
+
+mod bitwarden;
+mod file;
+mod keyring;
+mod onepassword;
+pub mod vault;
+
+pub use bitwarden::BitwardenBackend;
+pub use file::FileBackend;
+pub use keyring::{KeyringBackend, DEFAULT_SERVICE};
+pub use onepassword::OnePasswordBackend;
+pub use vault::VaultBackend;
+
+use crate::error::{Error, Result};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// A place secrets can be stored and fetched from.
+pub trait SecretBackend: Send + Sync {
+    /// Name used to select the backend and as its reference scheme.
+    fn name(&self) -> &'static str;
+
+    /// Checks whether a stored value is a reference into this backend.
+    fn owns(&self, value: &str) -> bool {
+        value
+            .strip_prefix(self.name())
+            .is_some_and(|rest| rest.starts_with(':'))
+    }
+
+    /// Stores a secret under `account` and returns the value to keep in the config.
+    fn store(&self, account: &str, secret: &str) -> Result<String>;
+
+    /// Fetches the secret a reference points at.
+    fn fetch(&self, reference: &str) -> Result<String>;
+
+    /// Deletes the secret a reference points at.
+    fn delete(&self, reference: &str) -> Result<()>;
+}
+
+/// The set of backends references are resolved against.
+pub struct SecretBackends {
+    backends: Vec<Box<dyn SecretBackend>>,
+}
+
+impl std::fmt::Debug for SecretBackends {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.backends.iter().map(|b| b.name()))
+            .finish()
+    }
+}
+
+impl Default for SecretBackends {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl SecretBackends {
+    /// Creates an empty set; values resolve to themselves.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            backends: Vec::new(),
+        }
+    }
+
+    /// Creates the set of builtin backends.
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self::new()
+            .with_backend(Box::new(FileBackend))
+            .with_backend(Box::new(KeyringBackend::new()))
+            .with_backend(Box::new(VaultBackend::from_env()))
+            .with_backend(Box::new(OnePasswordBackend))
+            .with_backend(Box::new(BitwardenBackend))
+    }
+
+    /// Adds a backend.
+    #[must_use]
+    pub fn with_backend(mut self, backend: Box<dyn SecretBackend>) -> Self {
+        self.backends.push(backend);
+        self
+    }
+
+    /// Gets a backend by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&dyn SecretBackend> {
+        self.backends
+            .iter()
+            .find(|b| b.name() == name)
+            .map(AsRef::as_ref)
+    }
+
+    /// Names of all registered backends.
+    #[must_use]
+    pub fn names(&self) -> Vec<&'static str> {
+        self.backends.iter().map(|b| b.name()).collect()
+    }
+
+    /// Gets the backend a stored value refers to, if it is a reference.
+    #[must_use]
+    pub fn owner(&self, value: &str) -> Option<&dyn SecretBackend> {
+        self.backends
+            .iter()
+            .find(|b| b.owns(value))
+            .map(AsRef::as_ref)
+    }
+
+    /// Checks whether a stored value is a reference rather than a secret.
+    #[must_use]
+    pub fn is_reference(&self, value: &str) -> bool {
+        self.owner(value).is_some()
+    }
+
+    /// Stores a secret in the named backend and returns the value to keep.
+    pub fn store(&self, backend: &str, account: &str, secret: &str) -> Result<String> {
+        let backend = self.get(backend).ok_or_else(|| {
+            Error::ConfigError(format!(
+                "unknown secret store '{backend}'; expected one of: {}",
+                self.names().join(", ")
+            ))
+        })?;
+        backend.store(account, secret)
+    }
+
+    /// Resolves a stored value to the secret, returning plain values unchanged.
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        self.owner(value)
+            .map_or_else(|| Ok(value.to_string()), |b| b.fetch(value))
+    }
+
+    /// Deletes the secret a stored value refers to; plain values are a no-op.
+    pub fn delete(&self, value: &str) -> Result<()> {
+        self.owner(value).map_or(Ok(()), |b| b.delete(value))
+    }
+}
+
+/// Finds a program on `PATH`.
+pub(crate) fn find_program(program: &str) -> Option<std::path::PathBuf> {
+    std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Runs the command-line tool a backend relies on, feeding `input` on stdin.
+fn run_tool(
+    backend: &str,
+    program: &str,
+    args: &[&str],
+    envs: &[(&str, &str)],
+    input: Option<&str>,
+) -> Result<Output> {
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                Error::ConfigError(format!(
+                    "`{program}` is required to use the {backend} but was not found"
+                ))
+            } else {
+                e.into()
+            }
+        })?;
+
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryBackend {
+        secrets: Mutex<HashMap<String, String>>,
+    }
+
+    impl SecretBackend for MemoryBackend {
+        fn name(&self) -> &'static str {
+            "memory"
+        }
+
+        fn store(&self, account: &str, secret: &str) -> Result<String> {
+            self.secrets
+                .lock()
+                .unwrap()
+                .insert(account.to_string(), secret.to_string());
+            Ok(format!("memory:{account}"))
+        }
+
+        fn fetch(&self, reference: &str) -> Result<String> {
+            let account = reference.trim_start_matches("memory:");
+            self.secrets
+                .lock()
+                .unwrap()
+                .get(account)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(reference.to_string()))
+        }
+
+        fn delete(&self, reference: &str) -> Result<()> {
+            let account = reference.trim_start_matches("memory:");
+            self.secrets.lock().unwrap().remove(account);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_reference_round_trip() {
+        let backends = SecretBackends::new().with_backend(Box::new(MemoryBackend::default()));
+
+        let reference = backends
+            .store("memory", "openai-prod", "sk-secret1234567890")
+            .unwrap();
+        assert_eq!(reference, "memory:openai-prod");
+        assert!(backends.is_reference(&reference));
+        assert_eq!(backends.resolve(&reference).unwrap(), "sk-secret1234567890");
+
+        backends.delete(&reference).unwrap();
+        assert!(matches!(
+            backends.resolve(&reference),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_plain_values_resolve_to_themselves() {
+        let backends = SecretBackends::builtin();
+        for value in [
+            "sk-plain1234567890",
+            "keyringless",
+            "",
+            "memory:unregistered",
+        ] {
+            assert!(!backends.is_reference(value));
+            assert_eq!(backends.resolve(value).unwrap(), value);
+        }
+        assert!(backends.is_reference("keyring:aicred/openai-prod"));
+        assert!(backends.is_reference("vault:secret/aicred/openai-prod#api_key"));
+        assert!(backends.is_reference("op://Private/OpenAI/credential"));
+        assert!(backends.is_reference("bw://OpenAI"));
+    }
+
+    #[test]
+    fn test_file_backend_stores_inline() {
+        let backends = SecretBackends::builtin();
+        let stored = backends
+            .store("file", "openai-prod", "sk-inline1234567890")
+            .unwrap();
+        assert_eq!(stored, "sk-inline1234567890");
+
+        let err = backends.store("vaultish", "openai-prod", "x").unwrap_err();
+        assert!(err.to_string().contains("file, keyring, vault"));
+    }
+}
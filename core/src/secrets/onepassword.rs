@@ -0,0 +1,90 @@
+//! Resolution of `op://` secret references through the 1Password CLI.
+
+use super::{run_tool, SecretBackend};
+use crate::error::{Error, Result};
+
+const SCHEME: &str = "op://";
+
+/// Resolves `op://<vault>/<item>[/<section>]/<field>` references with `op read`.
+///
+/// Items stay owned by 1Password: references are written by hand, and removing
+/// an instance leaves the item in place.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OnePasswordBackend;
+
+impl OnePasswordBackend {
+    /// Checks that a reference names a vault, an item and a field.
+    pub fn validate_reference(reference: &str) -> Result<()> {
+        let segments: Vec<&str> = reference
+            .strip_prefix(SCHEME)
+            .map(|rest| rest.split('/').collect())
+            .unwrap_or_default();
+        if !(3..=4).contains(&segments.len()) || segments.iter().any(|s| s.is_empty()) {
+            return Err(Error::ConfigError(format!(
+                "invalid 1Password reference '{reference}'; expected op://<vault>/<item>/<field>"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl SecretBackend for OnePasswordBackend {
+    fn name(&self) -> &'static str {
+        "op"
+    }
+
+    fn owns(&self, value: &str) -> bool {
+        value.starts_with(SCHEME)
+    }
+
+    fn store(&self, _account: &str, _secret: &str) -> Result<String> {
+        Err(Error::ConfigError(
+            "1Password items are not created by aicred; add the key in 1Password and use its op:// reference as the API key".to_string(),
+        ))
+    }
+
+    fn fetch(&self, reference: &str) -> Result<String> {
+        Self::validate_reference(reference)?;
+        let output = run_tool(
+            "1Password backend",
+            "op",
+            &["read", "--no-newline", reference],
+            &[],
+            None,
+        )?;
+        if !output.status.success() {
+            return Err(Error::SecurityError(format!(
+                "`op read {reference}` failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn delete(&self, _reference: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references() {
+        let backend = OnePasswordBackend;
+        assert!(backend.owns("op://Private/OpenAI/credential"));
+        assert!(!backend.owns("sk-op1234567890"));
+
+        for valid in [
+            "op://Private/OpenAI/credential",
+            "op://Work/Anthropic/api/key",
+        ] {
+            assert!(OnePasswordBackend::validate_reference(valid).is_ok());
+        }
+        for invalid in ["op://", "op://Private/OpenAI", "op://Private//credential"] {
+            assert!(OnePasswordBackend::validate_reference(invalid).is_err());
+        }
+        assert!(backend.store("openai-prod", "sk-x").is_err());
+    }
+}
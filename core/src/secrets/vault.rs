@@ -0,0 +1,319 @@
+//! Storage of secrets in the KV secrets engine of a Vault server.
+//!
+//! Configuration follows the Vault CLI: `VAULT_ADDR`, `VAULT_TOKEN` (falling back
+//! to `~/.vault-token`) and `VAULT_NAMESPACE`. Secrets are written to
+//! `<mount>/<path>/<account>` under the `api_key` field; the mount and path
+//! default to `secret` and `aicred` and can be set with [`MOUNT_ENV`] and
+//! [`PATH_ENV`].
+
+use super::SecretBackend;
+use crate::error::{Error, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Environment variable overriding the KV mount secrets are written to.
+pub const MOUNT_ENV: &str = "AICRED_VAULT_MOUNT";
+
+/// Environment variable overriding the path within the mount.
+pub const PATH_ENV: &str = "AICRED_VAULT_PATH";
+
+/// Field of the Vault secret holding the key.
+pub const SECRET_FIELD: &str = "api_key";
+
+const SCHEME: &str = "vault:";
+const DEFAULT_ADDR: &str = "https://127.0.0.1:8200";
+const DEFAULT_MOUNT: &str = "secret";
+const DEFAULT_PATH: &str = "aicred";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Stores secrets in Vault and keeps `vault:<mount>/<path>#<field>` references
+/// in the config.
+#[derive(Clone)]
+pub struct VaultBackend {
+    addr: String,
+    token: Option<String>,
+    namespace: Option<String>,
+    mount: String,
+    path: String,
+    kv_version: u8,
+}
+
+impl std::fmt::Debug for VaultBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultBackend")
+            .field("addr", &self.addr)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("namespace", &self.namespace)
+            .field("mount", &self.mount)
+            .field("path", &self.path)
+            .field("kv_version", &self.kv_version)
+            .finish()
+    }
+}
+
+impl VaultBackend {
+    /// Creates a backend for a Vault server using a KV version 2 engine.
+    #[must_use]
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into().trim_end_matches('/').to_string(),
+            token: None,
+            namespace: None,
+            mount: DEFAULT_MOUNT.to_string(),
+            path: DEFAULT_PATH.to_string(),
+            kv_version: 2,
+        }
+    }
+
+    /// Creates a backend configured from the environment, as the Vault CLI is.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let token = var("VAULT_TOKEN").or_else(|| {
+            let home = dirs_next::home_dir()?;
+            std::fs::read_to_string(home.join(".vault-token"))
+                .ok()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+        });
+
+        let mut backend = Self::new(var("VAULT_ADDR").unwrap_or_else(|| DEFAULT_ADDR.to_string()));
+        backend.token = token;
+        backend.namespace = var("VAULT_NAMESPACE");
+        if let Some(mount) = var(MOUNT_ENV) {
+            backend.mount = mount;
+        }
+        if let Some(path) = var(PATH_ENV) {
+            backend.path = path;
+        }
+        backend
+    }
+
+    /// Sets the token used to authenticate.
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Sets the Vault Enterprise namespace.
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Sets the KV mount secrets are written to.
+    #[must_use]
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Sets the path within the mount secrets are written under.
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Sets the KV engine version, 1 or 2.
+    #[must_use]
+    pub const fn with_kv_version(mut self, version: u8) -> Self {
+        self.kv_version = version;
+        self
+    }
+
+    /// Builds the reference for an account under the configured path.
+    #[must_use]
+    pub fn reference(&self, account: &str) -> String {
+        let path = if self.path.is_empty() {
+            account.to_string()
+        } else {
+            format!("{}/{account}", self.path)
+        };
+        format!("{SCHEME}{}/{path}#{SECRET_FIELD}", self.mount)
+    }
+
+    /// Splits a reference into its mount, secret path and field.
+    ///
+    /// The first path segment is the mount; the field defaults to [`SECRET_FIELD`].
+    pub fn parse_reference(reference: &str) -> Result<(&str, &str, &str)> {
+        let invalid = || {
+            Error::ConfigError(format!(
+                "invalid vault reference '{reference}'; expected vault:<mount>/<path>[#field]"
+            ))
+        };
+        let rest = reference.strip_prefix(SCHEME).ok_or_else(invalid)?;
+        let (location, field) = rest.split_once('#').unwrap_or((rest, SECRET_FIELD));
+        let (mount, path) = location.split_once('/').ok_or_else(invalid)?;
+        if mount.is_empty() || path.is_empty() || field.is_empty() {
+            return Err(invalid());
+        }
+        Ok((mount, path, field))
+    }
+
+    /// URL of a secret's data for the configured KV version.
+    fn data_url(&self, mount: &str, path: &str) -> String {
+        if self.kv_version == 1 {
+            format!("{}/v1/{mount}/{path}", self.addr)
+        } else {
+            format!("{}/v1/{mount}/data/{path}", self.addr)
+        }
+    }
+
+    /// URL removing a secret and all its versions.
+    fn delete_url(&self, mount: &str, path: &str) -> String {
+        if self.kv_version == 1 {
+            format!("{}/v1/{mount}/{path}", self.addr)
+        } else {
+            format!("{}/v1/{mount}/metadata/{path}", self.addr)
+        }
+    }
+
+    /// Extracts a field from a read response.
+    fn extract_field(&self, body: &Value, field: &str) -> Option<String> {
+        let data = if self.kv_version == 1 {
+            body.get("data")
+        } else {
+            body.get("data").and_then(|d| d.get("data"))
+        };
+        data?.get(field)?.as_str().map(str::to_string)
+    }
+
+    /// Builds an authenticated request.
+    fn request(&self, build: impl FnOnce(&Client) -> RequestBuilder) -> Result<RequestBuilder> {
+        let token = self.token.as_deref().ok_or_else(|| {
+            Error::ConfigError(
+                "no Vault token; set VAULT_TOKEN or log in with `vault login`".to_string(),
+            )
+        })?;
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let mut request = build(&client).header("X-Vault-Token", token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        Ok(request)
+    }
+
+    /// Sends a request, mapping Vault error responses.
+    fn send(request: RequestBuilder, what: &str) -> Result<reqwest::blocking::Response> {
+        let response = request.send()?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::NotFound(format!("no Vault secret at {what}")));
+        }
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(Error::ApiError(format!(
+                "Vault request for {what} failed with {status}: {}",
+                body.trim()
+            )));
+        }
+        Ok(response)
+    }
+}
+
+impl SecretBackend for VaultBackend {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    fn store(&self, account: &str, secret: &str) -> Result<String> {
+        let reference = self.reference(account);
+        let (mount, path, field) = Self::parse_reference(&reference)?;
+        let data = json!({ field: secret });
+        let body = if self.kv_version == 1 {
+            data
+        } else {
+            json!({ "data": data })
+        };
+        let url = self.data_url(mount, path);
+        Self::send(self.request(|c| c.post(&url).json(&body))?, path)?;
+        Ok(reference)
+    }
+
+    fn fetch(&self, reference: &str) -> Result<String> {
+        let (mount, path, field) = Self::parse_reference(reference)?;
+        let url = self.data_url(mount, path);
+        let body: Value = Self::send(self.request(|c| c.get(&url))?, path)?.json()?;
+        self.extract_field(&body, field).ok_or_else(|| {
+            Error::NotFound(format!(
+                "Vault secret at {mount}/{path} has no field '{field}'"
+            ))
+        })
+    }
+
+    fn delete(&self, reference: &str) -> Result<()> {
+        let (mount, path, _) = Self::parse_reference(reference)?;
+        let url = self.delete_url(mount, path);
+        match Self::send(self.request(|c| c.delete(&url))?, path) {
+            Ok(_) | Err(Error::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_format() {
+        let backend = VaultBackend::new("https://vault.example.com/")
+            .with_mount("kv")
+            .with_path("/team/aicred/");
+        let reference = backend.reference("openai-prod");
+        assert_eq!(reference, "vault:kv/team/aicred/openai-prod#api_key");
+        assert!(backend.owns(&reference));
+        assert_eq!(
+            VaultBackend::parse_reference(&reference).unwrap(),
+            ("kv", "team/aicred/openai-prod", "api_key")
+        );
+        assert_eq!(
+            VaultBackend::parse_reference("vault:secret/openai").unwrap(),
+            ("secret", "openai", SECRET_FIELD)
+        );
+        for invalid in [
+            "vault:",
+            "vault:secret",
+            "vault:/path",
+            "vault:secret/path#",
+        ] {
+            assert!(VaultBackend::parse_reference(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_kv_versions() {
+        let v2 = VaultBackend::new("https://vault.example.com");
+        assert_eq!(
+            v2.data_url("secret", "aicred/openai"),
+            "https://vault.example.com/v1/secret/data/aicred/openai"
+        );
+        assert_eq!(
+            v2.delete_url("secret", "aicred/openai"),
+            "https://vault.example.com/v1/secret/metadata/aicred/openai"
+        );
+        let body = json!({ "data": { "data": { "api_key": "sk-v2" }, "metadata": {} } });
+        assert_eq!(v2.extract_field(&body, "api_key").as_deref(), Some("sk-v2"));
+
+        let v1 = v2.with_kv_version(1);
+        assert_eq!(
+            v1.data_url("secret", "aicred/openai"),
+            "https://vault.example.com/v1/secret/aicred/openai"
+        );
+        let body = json!({ "data": { "api_key": "sk-v1" } });
+        assert_eq!(v1.extract_field(&body, "api_key").as_deref(), Some("sk-v1"));
+        assert_eq!(v1.extract_field(&body, "token"), None);
+    }
+
+    #[test]
+    fn test_missing_token_is_reported() {
+        let backend = VaultBackend::new("http://127.0.0.1:1");
+        let err = backend.fetch("vault:secret/aicred/openai").unwrap_err();
+        assert!(err.to_string().contains("VAULT_TOKEN"));
+        assert!(!format!("{:?}", backend.with_token("hvs.secret")).contains("hvs.secret"));
+    }
+}
@@ -0,0 +1,734 @@
+#![allow(clippy::missing_errors_doc)]
+//! Canonical on-disk configuration store under `~/.config/aicred`.
+//!
+//! Every write goes through [`StoreLock`], which holds an advisory lock on the
+//! store directory so concurrent CLI, GUI and FFI invocations are serialized.
+//! Files are written to a temporary file and renamed into place, the previous
+//! version is kept as `<file>.bak`, and each file starts with a schema version
+//! header. Provider instances are stored one per file in [`INSTANCES_DIR`].
+//!
+//! Loading migrates older schema versions through [`crate::migration`], and
+//! writes refuse to replace a file written by a newer schema version.
+//!
+//! Named profiles are complete stores of their own under [`PROFILES_DIR`], so
+//! separate sets of instances, tags and labels can live side by side.
+
+use crate::crypto::{self, KeySource, SecretCipher};
+use crate::discovery::ids;
+use crate::error::{Error, Result};
+use crate::migration::{ConfigKind, ConfigMigrator, MigrationReport};
+use crate::models::{ProviderCollection, ProviderInstance};
+use crate::paths::AppDirs;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::OnceCell;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// Current on-disk schema version written by the store.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Prefix of the header line recording the schema version of a stored file.
+pub const SCHEMA_HEADER_PREFIX: &str = "# aicred-schema-version: ";
+
+/// Directory, relative to the store, holding one YAML file per provider instance.
+pub const INSTANCES_DIR: &str = "inference_services";
+
+/// Directory, relative to the store, holding one sub-store per named profile.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Lock file used to serialize writers.
+const LOCK_FILE: &str = ".lock";
+
+/// Suffix of the backup kept for the previous version of a file.
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// Reads the schema version header of stored content, if present.
+#[must_use]
+pub fn schema_version(content: &str) -> Option<u32> {
+    content
+        .lines()
+        .next()?
+        .strip_prefix(SCHEMA_HEADER_PREFIX)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Gets the default file name for an instance: its ID if it follows the
+/// [`ids`](crate::discovery::ids) scheme, else provider type plus the first four
+/// characters of its ID.
+#[must_use]
+pub fn instance_file_name(instance: &ProviderInstance) -> String {
+    if ids::is_instance_id(&instance.id) {
+        return format!("{}.yaml", instance.id);
+    }
+    let prefix: String = instance.id.chars().take(4).collect();
+    format!("{}-{prefix}.yaml", instance.provider_type)
+}
+
+/// Configuration store rooted at an aicred config directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigStore {
+    dir: PathBuf,
+}
+
+impl ConfigStore {
+    /// Creates a store rooted at `dir`.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Creates the store in the config directory of a home directory, such as
+    /// `<home>/.config/aicred` on Linux (see [`crate::paths`]).
+    #[must_use]
+    pub fn for_home(home: &Path) -> Self {
+        Self::new(AppDirs::for_home(home).config_dir())
+    }
+
+    /// Creates the store in the current user's config directory.
+    pub fn default_location() -> Result<Self> {
+        Ok(Self::new(AppDirs::current()?.config_dir()))
+    }
+
+    /// Gets the store directory.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Gets the directory holding provider instance files.
+    #[must_use]
+    pub fn instances_dir(&self) -> PathBuf {
+        self.dir.join(INSTANCES_DIR)
+    }
+
+    /// Gets the store for a named profile (`<store>/profiles/<name>`).
+    ///
+    /// Names may contain ASCII letters, digits, `-` and `_`.
+    pub fn profile(&self, name: &str) -> Result<Self> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(Error::ValidationError(format!(
+                "Invalid profile name '{name}': use letters, digits, '-' and '_'"
+            )));
+        }
+        Ok(Self::new(self.dir.join(PROFILES_DIR).join(name)))
+    }
+
+    /// Lists the names of the profiles in this store, sorted.
+    pub fn profiles(&self) -> Result<Vec<String>> {
+        let dir = self.dir.join(PROFILES_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    if self.profile(name).is_ok() {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Resolves a store-relative file name, rejecting absolute paths and `..`.
+    pub fn path(&self, name: impl AsRef<Path>) -> Result<PathBuf> {
+        let name = name.as_ref();
+        if name
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return Err(Error::ValidationError(format!(
+                "Invalid store path: {}",
+                name.display()
+            )));
+        }
+        Ok(self.dir.join(name))
+    }
+
+    /// Creates a store-relative directory and its parents, accessible only by the owner.
+    pub fn ensure_dir(&self, name: impl AsRef<Path>) -> Result<PathBuf> {
+        let path = self.path(name)?;
+        create_private_dirs(&path)?;
+        Ok(path)
+    }
+
+    /// Acquires the exclusive store lock, blocking until other writers finish.
+    pub fn lock(&self) -> Result<StoreLock<'_>> {
+        create_private_dirs(&self.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.dir.join(LOCK_FILE))?;
+        file.lock()?;
+        Ok(StoreLock {
+            store: self,
+            _file: file,
+            cipher: OnceCell::new(),
+        })
+    }
+
+    /// Reads a stored file, returning `None` if it does not exist.
+    pub fn read(&self, name: impl AsRef<Path>) -> Result<Option<String>> {
+        match std::fs::read_to_string(self.path(name)?) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Reads and deserializes a stored YAML file, returning `None` if it does not exist.
+    ///
+    /// Known config files are migrated to the current schema in memory first.
+    pub fn load_yaml<T: DeserializeOwned>(&self, name: impl AsRef<Path>) -> Result<Option<T>> {
+        let path = self.path(&name)?;
+        let Some(content) = self.read(&name)? else {
+            return Ok(None);
+        };
+        let value = self.migrate_content(name.as_ref(), &content)?.0;
+        serde_yaml::from_value(value)
+            .map(Some)
+            .map_err(|e| Error::StoreCorrupt {
+                path,
+                message: e.to_string(),
+            })
+    }
+
+    /// Parses stored content, upgrading it if it is a known config file.
+    fn migrate_content(
+        &self,
+        name: &Path,
+        content: &str,
+    ) -> Result<(serde_yaml::Value, Option<MigrationReport>)> {
+        let Some(kind) = ConfigKind::from_path(name) else {
+            let value = serde_yaml::from_str(content).map_err(|e| Error::StoreCorrupt {
+                path: self.dir.join(name),
+                message: e.to_string(),
+            })?;
+            return Ok((value, None));
+        };
+
+        let (value, report) =
+            ConfigMigrator::for_kind(kind)
+                .migrate(content)
+                .map_err(|e| match e {
+                    Error::SerializationError(message) => Error::StoreCorrupt {
+                        path: self.dir.join(name),
+                        message,
+                    },
+                    e => Error::ConfigError(format!("{}: {e}", self.dir.join(name).display())),
+                })?;
+        if report.is_migrated() {
+            tracing::info!(
+                "Migrated {} from schema v{} to v{}: {}",
+                name.display(),
+                report.from_version,
+                report.to_version,
+                report.changes.join(", ")
+            );
+        }
+        Ok((value, Some(report)))
+    }
+
+    /// Upgrades every known config file to the current schema, returning what
+    /// changed per file. With `dry_run`, nothing is written.
+    pub fn migrate(&self, dry_run: bool) -> Result<Vec<(PathBuf, MigrationReport)>> {
+        let mut names: Vec<PathBuf> = ConfigKind::SINGLE_FILES
+            .iter()
+            .map(|(_, file)| PathBuf::from(file))
+            .collect();
+        names.extend(self.instance_files()?.into_iter().filter_map(|(path, _)| {
+            path.file_name()
+                .map(|file| Path::new(INSTANCES_DIR).join(file))
+        }));
+
+        let lock = if dry_run { None } else { Some(self.lock()?) };
+        let mut reports = Vec::new();
+        for name in names {
+            let Some(content) = self.read(&name)? else {
+                continue;
+            };
+            let (value, Some(report)) = self.migrate_content(&name, &content)? else {
+                continue;
+            };
+            if !report.is_migrated() {
+                continue;
+            }
+            if let Some(lock) = &lock {
+                lock.save_yaml(&name, &value)?;
+            }
+            reports.push((self.dir.join(&name), report));
+        }
+        Ok(reports)
+    }
+
+    /// Serializes and writes a YAML file under the store lock.
+    pub fn save_yaml<T: Serialize>(&self, name: impl AsRef<Path>, value: &T) -> Result<PathBuf> {
+        self.lock()?.save_yaml(name, value)
+    }
+
+    /// Reads every instance file as `(path, content)`, sorted by path.
+    pub fn instance_files(&self) -> Result<Vec<(PathBuf, String)>> {
+        let dir = self.instances_dir();
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+            .collect();
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => files.push((path, content)),
+                Err(e) => tracing::warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+        Ok(files)
+    }
+
+    /// Parses an instance file's content, migrating older layouts.
+    pub fn parse_instance(&self, content: &str) -> Result<(ProviderInstance, MigrationReport)> {
+        let (value, report) = ConfigMigrator::for_kind(ConfigKind::Instance).migrate(content)?;
+        let instance =
+            serde_yaml::from_value(value).map_err(|e| Error::SerializationError(e.to_string()))?;
+        Ok((instance, report))
+    }
+
+    /// Finds an instance by ID, returning its store-relative file name and its
+    /// stored contents (keys are not decrypted or resolved).
+    pub fn find_instance(&self, id: &str) -> Result<Option<(PathBuf, ProviderInstance)>> {
+        for (path, content) in self.instance_files()? {
+            let Ok((instance, _)) = self.parse_instance(&content) else {
+                continue;
+            };
+            if instance.id == id {
+                if let Some(file) = path.file_name() {
+                    return Ok(Some((Path::new(INSTANCES_DIR).join(file), instance)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Loads all provider instances, skipping files that do not parse.
+    pub fn load_instances(&self) -> Result<ProviderCollection> {
+        let mut instances = ProviderCollection::new();
+        for (path, content) in self.instance_files()? {
+            match self.parse_instance(&content) {
+                Ok((instance, _)) => instances.add(instance.id.clone(), instance),
+                Err(e) => tracing::warn!("Skipping invalid instance {}: {}", path.display(), e),
+            }
+        }
+        Ok(instances)
+    }
+
+    /// Gets the path of the default identity file for encrypted secrets.
+    #[must_use]
+    pub fn identity_path(&self) -> PathBuf {
+        self.dir.join(crypto::IDENTITY_FILE)
+    }
+
+    /// Creates the cipher for the key source configured in the environment, if any.
+    pub fn cipher(&self) -> Result<Option<SecretCipher>> {
+        KeySource::from_env(&self.identity_path())
+            .map(SecretCipher::new)
+            .transpose()
+    }
+
+    /// Applies `update` to every parseable instance file under a single lock,
+    /// rewriting the files it reports as changed. Returns how many changed.
+    pub fn update_instances(
+        &self,
+        mut update: impl FnMut(&mut ProviderInstance) -> Result<bool>,
+    ) -> Result<usize> {
+        let lock = self.lock()?;
+        let mut changed = 0;
+        for (path, content) in self.instance_files()? {
+            let Ok((mut instance, _)) = self.parse_instance(&content) else {
+                continue;
+            };
+            if update(&mut instance)? {
+                if let Some(file) = path.file_name() {
+                    lock.save_yaml(Path::new(INSTANCES_DIR).join(file), &instance)?;
+                    changed += 1;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Saves every instance in a collection under a single lock.
+    pub fn save_instances(&self, instances: &ProviderCollection) -> Result<()> {
+        let lock = self.lock()?;
+        for instance in instances.all_instances() {
+            lock.save_instance(instance)?;
+        }
+        Ok(())
+    }
+}
+
+/// Exclusive lock on a [`ConfigStore`]; all writes go through it.
+///
+/// The lock is released when this value is dropped.
+#[derive(Debug)]
+pub struct StoreLock<'a> {
+    store: &'a ConfigStore,
+    _file: File,
+    cipher: OnceCell<Option<SecretCipher>>,
+}
+
+impl StoreLock<'_> {
+    /// Gets the locked store.
+    #[must_use]
+    pub const fn store(&self) -> &ConfigStore {
+        self.store
+    }
+
+    /// Atomically replaces a stored file, prefixing the schema header and keeping
+    /// the previous version as a backup.
+    pub fn write(&self, name: impl AsRef<Path>, content: &str) -> Result<PathBuf> {
+        let path = self.store.path(name)?;
+        let parent = path.parent().unwrap_or_else(|| self.store.dir());
+        create_private_dirs(parent)?;
+
+        if let Some(existing) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|existing| schema_version(&existing))
+            .filter(|&version| version > SCHEMA_VERSION)
+        {
+            return Err(Error::ConfigError(format!(
+                "{} uses schema version {existing}, newer than {SCHEMA_VERSION}; refusing to downgrade it",
+                path.display()
+            )));
+        }
+
+        let body = if schema_version(content).is_some() {
+            content.split_once('\n').map_or("", |(_, rest)| rest)
+        } else {
+            content
+        };
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp_path = parent.join(format!(".{file_name}.{}.tmp", std::process::id()));
+
+        let written = (|| -> std::io::Result<()> {
+            let mut file = open_private(&temp_path)?;
+            writeln!(file, "{SCHEMA_HEADER_PREFIX}{SCHEMA_VERSION}")?;
+            file.write_all(body.as_bytes())?;
+            file.sync_all()?;
+
+            if path.exists() {
+                std::fs::copy(&path, parent.join(format!("{file_name}{BACKUP_SUFFIX}")))?;
+            }
+            std::fs::rename(&temp_path, &path)
+        })();
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+
+        // Persist the rename itself; not supported on every platform
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+        Ok(path)
+    }
+
+    /// Removes a stored file, keeping it as a backup. Returns whether it existed.
+    pub fn remove(&self, name: impl AsRef<Path>) -> Result<bool> {
+        let path = self.store.path(name)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        let mut backup = path.clone().into_os_string();
+        backup.push(BACKUP_SUFFIX);
+        std::fs::rename(&path, backup)?;
+        Ok(true)
+    }
+
+    /// Serializes and writes a YAML file.
+    pub fn save_yaml<T: Serialize>(&self, name: impl AsRef<Path>, value: &T) -> Result<PathBuf> {
+        let content =
+            serde_yaml::to_string(value).map_err(|e| Error::SerializationError(e.to_string()))?;
+        self.write(name, &content)
+    }
+
+    /// Saves an instance under its default file name.
+    pub fn save_instance(&self, instance: &ProviderInstance) -> Result<PathBuf> {
+        self.save_instance_as(&instance_file_name(instance), instance)
+    }
+
+    /// Saves an instance under a specific file name in the instances directory.
+    ///
+    /// If the stored key is encrypted, a plain replacement key is encrypted too
+    /// rather than written back in the clear.
+    pub fn save_instance_as(
+        &self,
+        file_name: &str,
+        instance: &ProviderInstance,
+    ) -> Result<PathBuf> {
+        let name = Path::new(INSTANCES_DIR).join(file_name);
+        let stored_encrypted = self
+            .store
+            .read(&name)?
+            .and_then(|content| self.store.parse_instance(&content).ok())
+            .is_some_and(|(stored, _)| crypto::is_encrypted(&stored.api_key));
+
+        if stored_encrypted
+            && !instance.api_key.is_empty()
+            && !crypto::is_encrypted(&instance.api_key)
+        {
+            let cipher = self.cipher()?.ok_or_else(|| {
+                Error::SecurityError(format!(
+                    "{} holds an encrypted key but no passphrase or identity is available; refusing to store it in plain text",
+                    name.display()
+                ))
+            })?;
+            let mut instance = instance.clone();
+            instance.api_key = cipher.encrypt(&instance.api_key)?;
+            return self.save_yaml(name, &instance);
+        }
+        self.save_yaml(name, instance)
+    }
+
+    /// Gets the cipher for the configured key source, created once per lock.
+    fn cipher(&self) -> Result<Option<&SecretCipher>> {
+        if self.cipher.get().is_none() {
+            let cipher = self.store.cipher()?;
+            let _ = self.cipher.set(cipher);
+        }
+        Ok(self.cipher.get().and_then(Option::as_ref))
+    }
+}
+
+/// Creates a directory and its missing parents, accessible only by the owner.
+fn create_private_dirs(path: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+/// Creates a new file readable only by the owner.
+fn open_private(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: &str) -> ProviderInstance {
+        ProviderInstance::new(
+            id.to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            "sk-test1234567890".to_string(),
+            vec!["gpt-4".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_write_adds_header_and_keeps_backup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+
+        let lock = store.lock().unwrap();
+        lock.write("tags.yaml", "- first\n").unwrap();
+        lock.write("tags.yaml", "- second\n").unwrap();
+        drop(lock);
+
+        let content = store.read("tags.yaml").unwrap().unwrap();
+        assert_eq!(schema_version(&content), Some(SCHEMA_VERSION));
+        assert!(content.ends_with("- second\n"));
+
+        let backup = store.read("tags.yaml.bak").unwrap().unwrap();
+        assert!(backup.ends_with("- first\n"));
+        assert_eq!(backup.matches(SCHEMA_HEADER_PREFIX).count(), 1);
+        assert_eq!(store.read("missing.yaml").unwrap(), None);
+    }
+
+    #[test]
+    fn test_instances_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::for_home(temp_dir.path());
+
+        let mut instances = ProviderCollection::new();
+        instances.add("abcd1234".to_string(), instance("abcd1234"));
+        store.save_instances(&instances).unwrap();
+        std::fs::write(store.instances_dir().join("broken.yaml"), "id: [").unwrap();
+
+        assert!(store.instances_dir().join("openai-abcd.yaml").exists());
+        let loaded = store.load_instances().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get("abcd1234").unwrap().models, vec!["gpt-4"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_creates_private_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::for_home(temp_dir.path());
+        let mut instances = ProviderCollection::new();
+        instances.add("abcd1234".to_string(), instance("abcd1234"));
+        store.save_instances(&instances).unwrap();
+
+        for dir in [store.dir().to_path_buf(), store.instances_dir()] {
+            let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700, "{}", dir.display());
+        }
+
+        let models_dir = store.ensure_dir("models").unwrap();
+        let mode = std::fs::metadata(&models_dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_store() {
+        let store = ConfigStore::new("/tmp/aicred-store");
+        assert!(store.path("../escape.yaml").is_err());
+        assert!(store.path("/etc/passwd").is_err());
+        assert!(store.path("inference_services/openai.yaml").is_ok());
+    }
+
+    #[test]
+    fn test_profiles_are_separate_stores() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+        assert!(store.profiles().unwrap().is_empty());
+
+        let work = store.profile("work").unwrap();
+        work.save_yaml("tags.yaml", &vec!["work"]).unwrap();
+        store.profile("home_2").unwrap().lock().unwrap();
+
+        assert_eq!(work.dir(), temp_dir.path().join("profiles").join("work"));
+        assert_eq!(store.profiles().unwrap(), vec!["home_2", "work"]);
+        assert_eq!(store.read("tags.yaml").unwrap(), None);
+        assert!(store.profile("../escape").is_err());
+        assert!(store.profile("").is_err());
+    }
+
+    #[test]
+    fn test_concurrent_writers_do_not_corrupt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let store = store.clone();
+                scope.spawn(move || {
+                    let value: Vec<String> = (0..50).map(|j| format!("tag-{i}-{j}")).collect();
+                    store.save_yaml("tags.yaml", &value).unwrap();
+                });
+            }
+        });
+
+        let tags: Vec<String> = store.load_yaml("tags.yaml").unwrap().unwrap();
+        assert_eq!(tags.len(), 50);
+    }
+
+    #[test]
+    fn test_migrate_upgrades_legacy_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+        std::fs::create_dir_all(store.instances_dir()).unwrap();
+        std::fs::write(temp_dir.path().join("tags.yaml"), "[]\n").unwrap();
+        std::fs::write(
+            store.instances_dir().join("openai-abcd.yaml"),
+            "id: abcd1234\nprovider_type: openai\nbase_url: https://api.openai.com/v1\nkeys:\n  - sk-legacy1234567890\n",
+        )
+        .unwrap();
+
+        let planned = store.migrate(true).unwrap();
+        assert_eq!(planned.len(), 2);
+        assert_eq!(store.read("tags.yaml").unwrap().as_deref(), Some("[]\n"));
+
+        let applied = store.migrate(false).unwrap();
+        assert_eq!(applied, planned);
+        assert!(store.migrate(false).unwrap().is_empty());
+
+        let instances = store.load_instances().unwrap();
+        assert_eq!(
+            instances.get("abcd1234").unwrap().api_key,
+            "sk-legacy1234567890"
+        );
+        let content = store
+            .read("inference_services/openai-abcd.yaml")
+            .unwrap()
+            .unwrap();
+        assert_eq!(schema_version(&content), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_refuses_to_downgrade_newer_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+        let newer = format!("{SCHEMA_HEADER_PREFIX}{}\n[]\n", SCHEMA_VERSION + 1);
+        std::fs::write(temp_dir.path().join("tags.yaml"), &newer).unwrap();
+
+        assert!(store.load_yaml::<Vec<String>>("tags.yaml").is_err());
+        assert!(store.save_yaml("tags.yaml", &Vec::<String>::new()).is_err());
+        assert_eq!(store.read("tags.yaml").unwrap(), Some(newer));
+    }
+
+    #[test]
+    fn test_encrypted_keys_stay_encrypted_on_save() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path());
+        crypto::generate_identity(&store.identity_path()).unwrap();
+        let cipher = SecretCipher::new(KeySource::IdentityFile(store.identity_path())).unwrap();
+
+        let mut stored = instance("abcd1234");
+        stored.api_key = cipher.encrypt(&stored.api_key).unwrap();
+        store.lock().unwrap().save_instance(&stored).unwrap();
+
+        // A caller that loaded and decrypted the key saves it back in plain text
+        let mut updated = instance("abcd1234");
+        updated.api_key = "sk-rotated1234567890".to_string();
+        store.lock().unwrap().save_instance(&updated).unwrap();
+
+        let content = store
+            .read("inference_services/openai-abcd.yaml")
+            .unwrap()
+            .unwrap();
+        assert!(!content.contains("sk-rotated"));
+        let saved = store.load_instances().unwrap();
+        let key = &saved.get("abcd1234").unwrap().api_key;
+        assert_eq!(cipher.decrypt(key).unwrap(), "sk-rotated1234567890");
+
+        std::fs::remove_file(store.identity_path()).unwrap();
+        assert!(store.lock().unwrap().save_instance(&updated).is_err());
+    }
+}
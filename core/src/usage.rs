@@ -0,0 +1,370 @@
+#![allow(clippy::missing_errors_doc)]
+//! Local ledger of token usage and spend.
+//!
+//! Tools report what they used through `aicred usage record`, the file named
+//! by [`USAGE_FILE_ENV`] that `aicred wrap` hands the command it runs, or
+//! `aicred_record_usage` of the C API. Each report is a [`UsageRecord`]
+//! appended to a JSON Lines file in the data directory (see
+//! [`crate::paths`]), and [`totals`] adds them up per instance, label or day.
+
+use crate::budget::BudgetStatus;
+use crate::error::{Error, Result};
+use crate::models::ModelRegistry;
+use crate::paths::AppDirs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Ledger file name.
+pub const USAGE_FILE: &str = "usage.jsonl";
+
+/// Variable naming the file a wrapped command appends its usage records to.
+pub const USAGE_FILE_ENV: &str = "AICRED_USAGE_FILE";
+
+/// Key of the totals of records without the instance or label grouped by.
+pub const UNATTRIBUTED: &str = "unattributed";
+
+/// Tokens used by one request, or a batch of them, on one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// When the usage happened
+    #[serde(default = "Utc::now")]
+    pub recorded_at: DateTime<Utc>,
+    /// Model used, in any form the registry resolves
+    pub model: String,
+    /// Instance the requests went through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    /// Label the tool resolved to pick the instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Input tokens used
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// Output tokens used
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// Cost as reported by the tool, or as priced from the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// Currency of the cost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Tool that reported the usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl UsageRecord {
+    /// Creates a record of usage happening now.
+    #[must_use]
+    pub fn new(model: impl Into<String>, input_tokens: u64, output_tokens: u64) -> Self {
+        Self {
+            recorded_at: Utc::now(),
+            model: model.into(),
+            instance_id: None,
+            label: None,
+            input_tokens,
+            output_tokens,
+            cost: None,
+            currency: None,
+            source: None,
+        }
+    }
+
+    /// Prices the tokens with the registry's pricing, unless the tool
+    /// reported a cost or the registry has no price for the model.
+    pub fn price(&mut self, registry: &ModelRegistry) {
+        if self.cost.is_some() {
+            return;
+        }
+        if let Some(cost) = registry
+            .resolve(&self.model)
+            .and_then(|entry| entry.cost(self.input_tokens, self.output_tokens))
+        {
+            self.cost = Some(cost.total_cost);
+            self.currency = Some(cost.currency);
+        }
+    }
+}
+
+/// What [`totals`] groups records by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGrouping {
+    /// Instance the requests went through
+    #[default]
+    Instance,
+    /// Label the tool resolved
+    Label,
+    /// UTC day of the usage
+    Day,
+}
+
+impl UsageGrouping {
+    /// Name of the grouping as accepted by [`FromStr`](std::str::FromStr)
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Instance => "instance",
+            Self::Label => "label",
+            Self::Day => "day",
+        }
+    }
+
+    fn key(self, record: &UsageRecord) -> String {
+        match self {
+            Self::Instance => record.instance_id.clone(),
+            Self::Label => record.label.clone(),
+            Self::Day => Some(record.recorded_at.format("%Y-%m-%d").to_string()),
+        }
+        .unwrap_or_else(|| UNATTRIBUTED.to_string())
+    }
+}
+
+impl std::fmt::Display for UsageGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for UsageGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "instance" => Ok(Self::Instance),
+            "label" => Ok(Self::Label),
+            "day" => Ok(Self::Day),
+            _ => Err(format!(
+                "Invalid grouping '{s}' (expected instance, label or day)"
+            )),
+        }
+    }
+}
+
+/// Usage of one instance, label or day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageTotal {
+    /// Instance ID, label or day, or [`UNATTRIBUTED`]
+    pub key: String,
+    /// Records added up
+    pub requests: usize,
+    /// Input tokens used
+    pub input_tokens: u64,
+    /// Output tokens used
+    pub output_tokens: u64,
+    /// Cost of the priced records
+    pub cost: f64,
+    /// Records without a cost
+    pub unpriced: usize,
+    /// Spending of this month against the budget of the instance or label
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetStatus>,
+}
+
+/// The usage ledger file.
+#[derive(Debug, Clone)]
+pub struct UsageLedger {
+    path: PathBuf,
+}
+
+impl UsageLedger {
+    /// Opens the ledger at a specific path.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Opens the ledger kept under a home directory.
+    #[must_use]
+    pub fn for_home(home_dir: &Path) -> Self {
+        Self::new(AppDirs::for_home(home_dir).data_dir().join(USAGE_FILE))
+    }
+
+    /// Opens the current user's ledger.
+    pub fn default_location() -> Result<Self> {
+        Ok(Self::new(AppDirs::current()?.data_dir().join(USAGE_FILE)))
+    }
+
+    /// Path of the ledger file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends records to the ledger.
+    pub fn record<'a>(&self, records: impl IntoIterator<Item = &'a UsageRecord>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(
+                &serde_json::to_string(record)
+                    .map_err(|e| Error::SerializationError(e.to_string()))?,
+            );
+            lines.push('\n');
+        }
+        options.open(&self.path)?.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /// Appends the records of a file a wrapped command wrote, priced with the
+    /// registry, and returns them.
+    pub fn import(&self, path: &Path, registry: &ModelRegistry) -> Result<Vec<UsageRecord>> {
+        let mut records = read_records(path)?;
+        for record in &mut records {
+            record.price(registry);
+        }
+        self.record(&records)?;
+        Ok(records)
+    }
+
+    /// Loads every record, oldest first, skipping unreadable lines.
+    pub fn load(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = read_records(&self.path)?;
+        records.sort_by_key(|record| record.recorded_at);
+        Ok(records)
+    }
+}
+
+/// Reads the records of a JSON Lines file, skipping unreadable lines. A
+/// missing file holds no records.
+fn read_records(path: &Path) -> Result<Vec<UsageRecord>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut records = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => tracing::warn!("Skipping unreadable usage record: {}", e),
+        }
+    }
+    Ok(records)
+}
+
+/// Adds up the records from `since` on, by day in date order, otherwise most
+/// expensive first.
+#[must_use]
+pub fn totals(
+    records: &[UsageRecord],
+    by: UsageGrouping,
+    since: Option<DateTime<Utc>>,
+) -> Vec<UsageTotal> {
+    let mut totals: BTreeMap<String, UsageTotal> = BTreeMap::new();
+    for record in records
+        .iter()
+        .filter(|record| since.is_none_or(|since| record.recorded_at >= since))
+    {
+        let key = by.key(record);
+        let total = totals.entry(key.clone()).or_insert_with(|| UsageTotal {
+            key,
+            requests: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost: 0.0,
+            unpriced: 0,
+            budget: None,
+        });
+        total.requests += 1;
+        total.input_tokens += record.input_tokens;
+        total.output_tokens += record.output_tokens;
+        match record.cost {
+            Some(cost) => total.cost += cost,
+            None => total.unpriced += 1,
+        }
+    }
+
+    let mut totals: Vec<UsageTotal> = totals.into_values().collect();
+    if by != UsageGrouping::Day {
+        totals.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn record(day: u32, instance_id: Option<&str>, model: &str) -> UsageRecord {
+        UsageRecord {
+            recorded_at: Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+            instance_id: instance_id.map(str::to_string),
+            ..UsageRecord::new(model, 1_000_000, 100_000)
+        }
+    }
+
+    #[test]
+    fn test_import_prices_and_appends_records() {
+        let temp = TempDir::new().unwrap();
+        let ledger = UsageLedger::for_home(temp.path());
+        assert!(ledger.load().unwrap().is_empty());
+
+        ledger.record(&[record(2, Some("work"), "gpt-4o")]).unwrap();
+        let reported = temp.path().join("reported.jsonl");
+        std::fs::write(
+            &reported,
+            "{\"model\": \"openai/gpt-4o-mini\", \"input_tokens\": 1000000, \"label\": \"fast\"}\n\
+             not json\n\
+             {\"model\": \"in-house\", \"output_tokens\": 5, \"cost\": 0.5}\n",
+        )
+        .unwrap();
+        let imported = ledger.import(&reported, &ModelRegistry::builtin()).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert!((imported[0].cost.unwrap() - 0.15).abs() < 1e-9);
+        assert_eq!(imported[0].currency.as_deref(), Some("USD"));
+        assert_eq!(imported[1].cost, Some(0.5));
+
+        let loaded = ledger.load().unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[0].instance_id.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_totals_group_by_instance_label_and_day() {
+        let mut priced = record(1, Some("work"), "gpt-4o");
+        priced.cost = Some(2.0);
+        let mut labelled = record(2, Some("home"), "gpt-4o-mini");
+        labelled.cost = Some(3.0);
+        labelled.label = Some("fast".to_string());
+        let records = [priced, labelled, record(2, None, "in-house")];
+
+        let by_instance = totals(&records, UsageGrouping::Instance, None);
+        let keys: Vec<&str> = by_instance.iter().map(|t| t.key.as_str()).collect();
+        assert_eq!(keys, ["home", "work", UNATTRIBUTED]);
+        assert_eq!(by_instance[2].unpriced, 1);
+
+        let by_label = totals(&records, UsageGrouping::Label, None);
+        assert_eq!(by_label[0].key, "fast");
+        assert_eq!(by_label[1].requests, 2);
+
+        let since = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let by_day = totals(&records, UsageGrouping::Day, Some(since));
+        assert_eq!(by_day.len(), 1);
+        assert_eq!(by_day[0].key, "2026-03-02");
+        assert_eq!(by_day[0].input_tokens, 2_000_000);
+        assert!((by_day[0].cost - 3.0).abs() < f64::EPSILON);
+
+        assert_eq!("Day".parse::<UsageGrouping>(), Ok(UsageGrouping::Day));
+        assert!("model".parse::<UsageGrouping>().is_err());
+    }
+}
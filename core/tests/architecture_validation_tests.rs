@@ -9,7 +9,9 @@
 //! - Correctly maps settings (temperature, base_url, etc.)
 //! - Handles edge cases (missing keys, invalid configs, mixed providers)
 
-use aicred_core::scanners::{ClaudeDesktopScanner, GshScanner, RooCodeScanner, ScannerPlugin};
+use aicred_core::scanners::{
+    ClaudeDesktopScanner, GshScanner, RooCodeScanner, ScannerContext, ScannerPlugin,
+};
 use std::path::Path;
 
 #[test]
@@ -23,7 +25,7 @@ fn test_gsh_scanner_architecture() {
 
     // Parse the configuration
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Failed to parse GSH config");
 
     println!("\n=== GSH Scanner Architecture Test ===");
@@ -131,7 +133,7 @@ fn test_claude_desktop_scanner_architecture() {
 
     // Parse the configuration
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Failed to parse Claude Desktop config");
 
     println!("\n=== Claude Desktop Scanner Architecture Test ===");
@@ -296,7 +298,7 @@ fn test_roo_code_scanner_architecture() {
 
     // Parse the configuration
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Failed to parse Roo Code config");
 
     println!("\n=== Roo Code Scanner Architecture Test ===");
@@ -381,7 +383,7 @@ fn test_gsh_missing_keys_edge_case() {
 
     // Parse the configuration
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Failed to parse GSH config with missing keys");
 
     println!("\n=== GSH Missing Keys Edge Case Test ===");
@@ -438,7 +440,7 @@ fn test_invalid_config_edge_case() {
 
     // Parse the configuration
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Failed to parse invalid config");
 
     println!("\n=== Invalid Config Edge Case Test ===");
@@ -485,7 +487,7 @@ fn test_no_duplicate_keys_across_scanners() {
     let gsh_config_path = Path::new(manifest_dir).join("tests/fixtures/test_gsh_config.gshrc");
     let gsh_content = std::fs::read_to_string(&gsh_config_path).expect("Failed to read GSH config");
     let gsh_result = gsh_scanner
-        .parse_config(&gsh_config_path, &gsh_content)
+        .parse_config(&gsh_config_path, &gsh_content, &ScannerContext::default())
         .expect("Failed to parse GSH config");
 
     // Collect all key hashes
@@ -525,7 +527,7 @@ fn test_provider_instance_counts() {
     let gsh_config_path = Path::new(manifest_dir).join("tests/fixtures/test_gsh_config.gshrc");
     let gsh_content = std::fs::read_to_string(&gsh_config_path).expect("Failed to read GSH config");
     let gsh_result = gsh_scanner
-        .parse_config(&gsh_config_path, &gsh_content)
+        .parse_config(&gsh_config_path, &gsh_content, &ScannerContext::default())
         .expect("Failed to parse GSH config");
 
     let config_instance = &gsh_result.instances[0];
@@ -592,7 +594,7 @@ fn test_settings_mapping() {
     let content =
         std::fs::read_to_string(&config_path).expect("Failed to read Claude Desktop config");
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Failed to parse Claude Desktop config");
 
     let config_instance = &result.instances[0];
@@ -625,7 +627,7 @@ fn test_settings_mapping() {
     let gsh_config_path = Path::new(manifest_dir).join("tests/fixtures/test_gsh_config.gshrc");
     let gsh_content = std::fs::read_to_string(&gsh_config_path).expect("Failed to read GSH config");
     let gsh_result = gsh_scanner
-        .parse_config(&gsh_config_path, &gsh_content)
+        .parse_config(&gsh_config_path, &gsh_content, &ScannerContext::default())
         .expect("Failed to parse GSH config");
 
     let gsh_config_instance = &gsh_result.instances[0];
@@ -4,7 +4,7 @@
 
 use aicred_core::scanners::{
     register_builtin_scanners, ClaudeDesktopScanner, GshScanner, LangChainScanner, RagitScanner,
-    RooCodeScanner, ScannerPlugin, ScannerRegistry,
+    RooCodeScanner, ScannerContext, ScannerPlugin, ScannerRegistry,
 };
 use std::fs;
 use std::path::Path;
@@ -54,7 +54,11 @@ fn test_langchain_scanner_parse_config() {
     }"#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join("config.json"), json_config)
+        .parse_config(
+            &temp_dir.path().join("config.json"),
+            json_config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 3 keys: langchain, openai, anthropic
@@ -79,7 +83,11 @@ llm:
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join("config.yaml"), yaml_config)
+        .parse_config(
+            &temp_dir.path().join("config.yaml"),
+            yaml_config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     assert_eq!(result.keys.len(), 2);
@@ -133,7 +141,11 @@ fn test_ragit_scanner_parse_config() {
     }"#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join("config.json"), json_config)
+        .parse_config(
+            &temp_dir.path().join("config.json"),
+            json_config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 3 keys: ragit, openai, huggingface
@@ -161,7 +173,7 @@ ANTHROPIC_API_KEY=sk-ant-REDACTED
 "#;
 
     let result = scanner
-        .parse_config(Path::new(".env"), env_content)
+        .parse_config(Path::new(".env"), env_content, &ScannerContext::default())
         .unwrap();
 
     assert_eq!(result.keys.len(), 3);
@@ -194,6 +206,30 @@ fn test_claude_desktop_scanner() {
     assert_eq!(scanner.app_name(), "Claude Desktop");
 }
 
+#[test]
+fn test_claude_desktop_config_found_in_platform_settings_dir() {
+    let temp_home = TempDir::new().unwrap();
+    let config_dir = aicred_core::paths::Platform::current()
+        .app_settings_dir(temp_home.path())
+        .join("Claude");
+    fs::create_dir_all(&config_dir).unwrap();
+    let config_path = config_dir.join("claude_desktop_config.json");
+    fs::write(
+        &config_path,
+        r#"{"mcpServers": {"github": {"command": "npx", "env": {"GITHUB_TOKEN": "ghp_1234567890abcdef"}}}}"#,
+    )
+    .unwrap();
+
+    let options = aicred_core::ScanOptions::new().with_home_dir(temp_home.path().to_path_buf());
+    let result = aicred_core::scan(&options).unwrap();
+    let instance = result
+        .config_instances
+        .iter()
+        .find(|instance| instance.config_path == config_path)
+        .expect("the desktop config should be scanned");
+    assert_eq!(instance.settings.mcp_servers_with_credentials, 1);
+}
+
 #[test]
 fn test_roo_code_scanner() {
     let temp_home = TempDir::new().unwrap();
@@ -341,7 +377,11 @@ GOOGLE_API_KEY=AIzaSyTest1234567890abcdef
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 3 keys
@@ -378,7 +418,11 @@ HUGGING_FACE_HUB_TOKEN='hf_test1234567890abcdef'
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 4 keys
@@ -410,7 +454,11 @@ export COHERE_API_KEY=sk-cohere1234567890abcdef
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 7 keys
@@ -445,7 +493,11 @@ GOOGLE_API_KEY=AIzaSyTest1234567890abcdef
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 3 keys despite comments and empty lines
@@ -472,7 +524,11 @@ export COHERE_API_KEY="sk-cohere1234567890abcdef"
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 9 keys (all providers)
@@ -502,7 +558,11 @@ without any key patterns
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), malformed_config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            malformed_config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 0 keys - but the scanner might find patterns that look like keys
@@ -517,7 +577,11 @@ GSH_MODEL="gpt-4"
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), no_keys_config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            no_keys_config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 0 keys but still create an instance
@@ -535,7 +599,11 @@ export ANTHROPIC_API_KEY="sk-ant-REDACTED"
 "#;
 
     let result = scanner
-        .parse_config(&temp_dir.path().join(".gshrc"), partial_config)
+        .parse_config(
+            &temp_dir.path().join(".gshrc"),
+            partial_config,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     // Should find 2 valid keys (ignoring the invalid one)
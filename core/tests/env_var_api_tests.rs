@@ -527,6 +527,7 @@ fn test_backward_compatibility() {
             &self,
             _path: &std::path::Path,
             _content: &str,
+            _context: &aicred_core::ScannerContext,
         ) -> aicred_core::error::Result<aicred_core::scanners::ScanResult> {
             let result = aicred_core::scanners::ScanResult::new();
             Ok(result)
@@ -36,12 +36,22 @@ export GSH_SLOW_MODEL_ID="deepseek/deepseek-v3.2-exp"#;
     // Step 1: Run scan to discover instances
     let scan_options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let scan_result = aicred_core::scan(&scan_options).unwrap();
@@ -70,23 +80,25 @@ export GSH_SLOW_MODEL_ID="deepseek/deepseek-v3.2-exp"#;
 
     // Step 4: Simulate the update_yaml_config process
     // This is where the ID mismatch would be caught
+    // update_yaml_config names an instance after its key
+    let storage_ids: Vec<String> = scan_result
+        .keys
+        .iter()
+        .chain(
+            scan_result
+                .config_instances
+                .iter()
+                .flat_map(|config_instance| &config_instance.keys),
+        )
+        .filter(|key| key.value_type == aicred_core::models::ValueType::ApiKey)
+        .map(aicred_core::discovery::ids::key_instance_id)
+        .collect();
     for instance in discovered_instances {
-        // The update_yaml_config function generates IDs using SHA-256 hash
-        // Use the actual source path format that the scanner uses
-        let source_path = gshrc_file.to_string_lossy().to_string();
-        let instance_id_source = format!("{}:{}", instance.provider_type, source_path);
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(instance_id_source.as_bytes());
-        let hash_result = hasher.finalize();
-        let full_hash = format!("{:x}", hash_result);
-        let expected_storage_id = full_hash[..4].to_string();
-
         // The discovered instance ID should match the storage ID
-        assert_eq!(
-            instance.id, expected_storage_id,
-            "Instance ID '{}' from discovery should match storage ID '{}' for provider '{}' using source path '{}'",
-            instance.id, expected_storage_id, instance.provider_type, source_path
+        assert!(
+            storage_ids.contains(&instance.id),
+            "Instance ID '{}' from discovery should be one of the storage IDs {:?} for provider '{}'",
+            instance.id, storage_ids, instance.provider_type
         );
     }
 }
@@ -118,6 +130,7 @@ fn test_label_assignment_for_newly_discovered_instances() {
             &self,
             _path: &Path,
             _content: &str,
+            _context: &aicred_core::ScannerContext,
         ) -> Result<ScanResult, aicred_core::error::Error> {
             Ok(ScanResult::new())
         }
@@ -201,12 +214,22 @@ fn test_complete_scan_update_workflow_with_id_validation() {
     // Step 1: Initial scan (this would discover instances)
     let scan_options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let scan_result = aicred_core::scan(&scan_options).unwrap();
@@ -273,21 +296,12 @@ fn test_complete_scan_update_workflow_with_id_validation() {
     // Step 3: Simulate update_yaml_config (this would persist with same IDs)
     // In the buggy version, this would generate different IDs
     for instance_id in &discovered_ids {
-        // Verify the ID format is consistent (SHA-256 hash format)
+        // Verify the ID follows the provider-fingerprint scheme
         assert!(
-            instance_id.len() == 4,
-            "Instance ID should be 4-character hash, got: {}",
+            aicred_core::discovery::ids::is_instance_id(instance_id),
+            "Instance ID should be a provider slug and a 12-character hash, got: {}",
             instance_id
         );
-
-        // Verify it's hex characters
-        for c in instance_id.chars() {
-            assert!(
-                c.is_ascii_hexdigit(),
-                "Instance ID should be hex characters, got: {}",
-                instance_id
-            );
-        }
     }
 
     // Step 4: Simulate label/tag retrieval (this is where the error occurred)
@@ -323,3 +337,88 @@ fn get_labels_for_target(
         }
     }
 }
+
+#[test]
+fn test_instance_id_derivations_are_stable() {
+    use aicred_core::discovery::ids::{
+        config_instance_id, instance_id, is_instance_id, is_legacy_instance_id,
+        legacy_provider_instance_id, unique_instance_id,
+    };
+
+    // IDs of configs stored by earlier releases; changing them orphans those configs
+    let path = Path::new("/home/user/.config/app.json");
+    assert_eq!(config_instance_id("gsh", path), "gsh_84ab1e555519");
+    assert_eq!(config_instance_id("roo", path), "roo_84ab1e555519");
+    assert_eq!(config_instance_id("ragit", path), "ragit_84ab1e5555");
+    assert_eq!(config_instance_id("claude", path), "claude_84ab1e555");
+    assert_eq!(config_instance_id("langchain", path), "langchain_84ab1e");
+    assert_eq!(
+        legacy_provider_instance_id("openai", "/home/user/.env"),
+        "12ac"
+    );
+    assert_eq!(instance_id("OpenAI", "fingerprint"), "openai-44863b03e990");
+    assert_eq!(
+        instance_id("Azure OpenAI", "fingerprint"),
+        "azure-openai-44863b03e990"
+    );
+
+    assert!(is_instance_id("openai-44863b03e990"));
+    assert!(is_instance_id("azure-openai-44863b03e990-2"));
+    assert!(!is_instance_id("openai-prod"));
+    assert!(!is_instance_id("12ac"));
+    assert!(is_legacy_instance_id("12ac"));
+    assert!(!is_legacy_instance_id("openai-44863b03e990"));
+
+    let taken = ["openai-44863b03e990", "openai-44863b03e990-2"];
+    assert_eq!(
+        unique_instance_id("openai-44863b03e990", |id| taken.contains(&id)),
+        "openai-44863b03e990-3"
+    );
+    assert_eq!(
+        unique_instance_id("groq-44863b03e990", |id| taken.contains(&id)),
+        "groq-44863b03e990"
+    );
+
+    // The old `scanners` path re-exports the same items
+    let registry: aicred_core::discovery::ScannerRegistry =
+        aicred_core::scanners::ScannerRegistry::new();
+    assert!(registry.list().is_empty());
+    assert_eq!(
+        aicred_core::scanners::ids::config_instance_id("gsh", path),
+        "gsh_84ab1e555519"
+    );
+}
+
+#[test]
+fn test_key_instance_ids_survive_hmac_fingerprints() {
+    use aicred_core::discovery::ids::{key_instance_id, legacy_key_instance_id};
+    use aicred_core::fingerprint::Fingerprinter;
+
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-test1234567890abcdef\n",
+    )
+    .unwrap();
+
+    let openai_ids = |options: ScanOptions| {
+        let result = aicred_core::scan(&options).unwrap();
+        let key = result
+            .keys
+            .iter()
+            .find(|k| k.provider == "openai")
+            .expect("the key should be found")
+            .clone();
+        assert_eq!(legacy_key_instance_id(&key), "7484");
+        (key.hash.clone(), key_instance_id(&key))
+    };
+    let options = ScanOptions::default()
+        .with_home_dir(temp_home.path().to_path_buf())
+        .with_full_values(true);
+    let (sha256_hash, sha256_id) = openai_ids(options.clone());
+    let (hmac_hash, hmac_id) = openai_ids(options.with_fingerprint(Fingerprinter::hmac(b"salt")));
+
+    assert_ne!(sha256_hash, hmac_hash);
+    assert_eq!(sha256_id, "openai-7484bce3a9d8");
+    assert_eq!(hmac_id, sha256_id);
+}
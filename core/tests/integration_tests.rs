@@ -5,7 +5,10 @@
 #![allow(clippy::absurd_extreme_comparisons)]
 #![allow(unused_comparisons)]
 
-use aicred_core::{scan, ScanOptions};
+use aicred_core::models::ValueType;
+use aicred_core::{
+    scan, scan_with_progress, CancellationToken, ScanEvent, ScanOptions, ScannerContext,
+};
 use std::fs;
 use tempfile::TempDir;
 
@@ -43,12 +46,22 @@ ANTHROPIC_API_KEY=sk-ant-REDACTED
     // Run scan against the temp home
     let result = scan(&ScanOptions {
         home_dir: Some(temp_home.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1_048_576,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     })
     .expect("scan should succeed");
 
@@ -89,12 +102,22 @@ OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345
     // Run scan without provider filtering to find all providers
     let result = scan(&ScanOptions {
         home_dir: Some(temp_home.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1_048_576,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     })
     .expect("scan should succeed");
 
@@ -161,12 +184,22 @@ fn test_application_scanner_integration() {
     // Run scan to discover application instances
     let result = scan(&ScanOptions {
         home_dir: Some(temp_home.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1_048_576,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     })
     .expect("scan should succeed");
 
@@ -189,12 +222,22 @@ fn test_anthropic_auto_model_detection() {
     // Run scan to discover the Anthropic configuration
     let result = scan(&ScanOptions {
         home_dir: Some(temp_home.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1_048_576,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     })
     .expect("scan should succeed");
 
@@ -290,12 +333,22 @@ fn test_anthropic_model_detection_without_api_key() {
     // Run scan
     let result = scan(&ScanOptions {
         home_dir: Some(temp_home.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1_048_576,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     })
     .expect("scan should succeed");
 
@@ -341,3 +394,413 @@ fn test_anthropic_model_detection_without_api_key() {
         "Expected to find model as a discovered key"
     );
 }
+
+#[cfg(unix)]
+#[test]
+fn test_scan_assigns_risk_from_file_permissions() {
+    use aicred_core::RiskLevel;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_home = TempDir::new().unwrap();
+    let env_path = temp_home.path().join(".env");
+    fs::write(
+        &env_path,
+        "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n",
+    )
+    .unwrap();
+
+    let options = ScanOptions {
+        home_dir: Some(temp_home.path().to_path_buf()),
+        ..Default::default()
+    };
+
+    fs::set_permissions(&env_path, fs::Permissions::from_mode(0o600)).unwrap();
+    let private = scan(&options).expect("scan should succeed");
+    let private_key = private
+        .keys
+        .iter()
+        .find(|k| k.provider == "openai")
+        .expect("openai key should be found");
+    assert_eq!(private_key.risk, RiskLevel::Medium);
+
+    fs::set_permissions(&env_path, fs::Permissions::from_mode(0o644)).unwrap();
+    let shared = scan(&options).expect("scan should succeed");
+    let shared_key = shared
+        .keys
+        .iter()
+        .find(|k| k.provider == "openai")
+        .expect("openai key should be found");
+    assert_eq!(shared_key.risk, RiskLevel::Critical);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_risk_counts_the_most_exposed_source() {
+    use aicred_core::models::{Confidence, DiscoveredCredential, ValueType};
+    use aicred_core::RiskLevel;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_home = TempDir::new().unwrap();
+    let private_path = temp_home.path().join(".env");
+    let shared_path = temp_home.path().join("project.env");
+    for (path, mode) in [(&private_path, 0o600), (&shared_path, 0o644)] {
+        fs::write(path, "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n").unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    let mut key = DiscoveredCredential::new(
+        "openai".to_string(),
+        private_path.display().to_string(),
+        ValueType::ApiKey,
+        Confidence::High,
+        "sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345".to_string(),
+    );
+    assert_eq!(key.assess_risk(), RiskLevel::Medium);
+
+    key.add_source(&shared_path.display().to_string());
+    assert_eq!(key.assess_risk(), RiskLevel::Critical);
+}
+
+#[test]
+fn test_key_age_counts_the_oldest_source() {
+    use aicred_core::models::{Confidence, DiscoveredCredential, ValueType};
+    use std::time::{Duration, SystemTime};
+
+    let temp_home = TempDir::new().unwrap();
+    let new_path = temp_home.path().join(".env");
+    let old_path = temp_home.path().join("project.env");
+    let now = SystemTime::now();
+    let old = now - Duration::from_secs(200 * 24 * 60 * 60);
+    for (path, modified) in [(&new_path, now), (&old_path, old)] {
+        fs::write(path, "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    let mut key = DiscoveredCredential::new(
+        "openai".to_string(),
+        new_path.display().to_string(),
+        ValueType::ApiKey,
+        Confidence::High,
+        "sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345".to_string(),
+    );
+    key.add_source(&old_path.display().to_string());
+    key.refresh_timestamps();
+
+    assert_eq!(key.age_days(chrono::Utc::now()), Some(200));
+    assert!(key.is_stale(180, chrono::Utc::now()));
+}
+
+#[test]
+fn test_scan_honors_ignore_files() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n",
+    )
+    .unwrap();
+
+    let options = ScanOptions {
+        home_dir: Some(temp_home.path().to_path_buf()),
+        ..Default::default()
+    };
+    let has_openai_key = |options: &ScanOptions| {
+        scan(options)
+            .expect("scan should succeed")
+            .keys
+            .iter()
+            .any(|k| k.provider == "openai")
+    };
+
+    assert!(has_openai_key(&options));
+
+    // The global ignore file excludes the .env file from every scanner
+    let global = aicred_core::paths::AppDirs::for_home(temp_home.path())
+        .config_dir()
+        .join(aicred_core::discovery::GLOBAL_IGNORE_FILE);
+    fs::create_dir_all(global.parent().unwrap()).unwrap();
+    fs::write(&global, ".env\n").unwrap();
+    assert!(!has_openai_key(&options));
+
+    // A per-directory .aicredignore can re-include it
+    fs::write(
+        temp_home
+            .path()
+            .join(aicred_core::discovery::IGNORE_FILE_NAME),
+        "!.env\n",
+    )
+    .unwrap();
+    assert!(has_openai_key(&options));
+}
+
+#[test]
+fn test_scan_extra_roots_records_root_per_finding() {
+    let temp_home = TempDir::new().unwrap();
+    let backup = TempDir::new().unwrap();
+    fs::write(
+        backup.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    let options = ScanOptions::default()
+        .with_home_dir(temp_home.path().to_path_buf())
+        .with_extra_root(backup.path().to_path_buf());
+    let result = scan(&options).expect("scan should succeed");
+
+    let key = result
+        .keys
+        .iter()
+        .find(|k| k.provider == "anthropic")
+        .expect("key in extra root should be found");
+    assert_eq!(
+        key.scan_root.as_deref(),
+        Some(backup.path().display().to_string().as_str())
+    );
+    assert!(result
+        .metadata
+        .as_ref()
+        .is_some_and(|m| m.contains_key("scan_roots")));
+}
+
+#[test]
+fn test_scan_streams_files_over_size_limit() {
+    let temp_home = TempDir::new().unwrap();
+    let mut content = "# padding line for a large log-like env file\n".repeat(200);
+    content.push_str("OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n");
+    fs::write(temp_home.path().join(".env"), &content).unwrap();
+
+    let options = ScanOptions::default()
+        .with_home_dir(temp_home.path().to_path_buf())
+        .with_max_file_size(1024);
+    let result = scan(&options).expect("scan should succeed");
+
+    assert!(content.len() > 1024);
+    assert!(result.keys.iter().any(|k| k.provider == "openai"));
+}
+
+#[test]
+fn test_scan_archives_in_extra_root() {
+    use std::io::Write;
+
+    let temp_home = TempDir::new().unwrap();
+    let backups = TempDir::new().unwrap();
+
+    // A single-entry ustar archive holding a home-relative .env file
+    let content = b"OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n";
+    let mut header = [0u8; 512];
+    header[..4].copy_from_slice(b".env");
+    header[124..135].copy_from_slice(format!("{:011o}", content.len()).as_bytes());
+    header[156] = b'0';
+    header[257..262].copy_from_slice(b"ustar");
+    let mut tar = header.to_vec();
+    tar.extend_from_slice(content);
+    tar.resize(1024 + 1024, 0);
+
+    let archive = backups.path().join("home-backup.tar.gz");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&tar).unwrap();
+    fs::write(&archive, encoder.finish().unwrap()).unwrap();
+
+    let options = ScanOptions::default()
+        .with_home_dir(temp_home.path().to_path_buf())
+        .with_extra_root(backups.path().to_path_buf());
+    let without_archives = scan(&options).expect("scan should succeed");
+    assert!(!without_archives.keys.iter().any(|k| k.provider == "openai"));
+
+    let result = scan(&options.with_scan_archives(true)).expect("scan should succeed");
+    let key = result
+        .keys
+        .iter()
+        .find(|k| k.provider == "openai")
+        .expect("key inside archive should be found");
+    assert_eq!(key.source_file, format!("{}!/.env", archive.display()));
+    assert_eq!(
+        key.scan_root.as_deref(),
+        Some(archive.display().to_string().as_str())
+    );
+}
+
+#[test]
+fn test_every_builtin_scanner_reports_positions() {
+    let temp_home = TempDir::new().unwrap();
+    let home = temp_home.path();
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    fs::copy(
+        fixtures.join("test_claude_desktop_config.json"),
+        home.join(".claude.json"),
+    )
+    .unwrap();
+    fs::copy(fixtures.join("test_gsh_config.gshrc"), home.join(".gshrc")).unwrap();
+    fs::create_dir_all(home.join(".roo-code")).unwrap();
+    fs::copy(
+        fixtures.join("test_roo_code_config.json"),
+        home.join(".roo-code/config.json"),
+    )
+    .unwrap();
+    fs::create_dir_all(home.join(".ragit")).unwrap();
+    fs::write(
+        home.join(".ragit/config.json"),
+        "{\n  \"ragit_version\": \"1.0.0\",\n  \"api_key\": \"sk-ragit1234567890abcdef\"\n}\n",
+    )
+    .unwrap();
+    fs::create_dir_all(home.join(".langchain")).unwrap();
+    fs::write(
+        home.join(".langchain/config.json"),
+        "{\n  \"langchain_version\": \"0.1.0\",\n  \"api_key\": \"sk-langchain1234567890abcdef\"\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        home.join(".env"),
+        "# keys\nOPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n",
+    )
+    .unwrap();
+
+    let options = ScanOptions::default()
+        .with_home_dir(home.to_path_buf())
+        .with_full_values(true);
+    let result = scan(&options).expect("scan should succeed");
+    assert!(!result.keys.is_empty());
+
+    for key in &result.keys {
+        let value = key.full_value().unwrap();
+        assert!(
+            key.source_line.is_some() && key.column_number.is_some(),
+            "{} key from {} has no position",
+            key.provider,
+            key.source_file
+        );
+        let snippet = key.context_snippet.as_deref().unwrap_or_else(|| {
+            panic!(
+                "{} key from {} has no snippet",
+                key.provider, key.source_file
+            )
+        });
+        if key.value_type == ValueType::ApiKey {
+            assert!(!snippet.contains(value), "snippet leaks value: {snippet}");
+        }
+    }
+}
+
+#[test]
+fn test_scan_only_value_types() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n\
+         OPENAI_BASE_URL=https://api.openai.com/v1\n\
+         OPENAI_MODEL=gpt-4o\n",
+    )
+    .unwrap();
+
+    let options = ScanOptions::default().with_home_dir(temp_home.path().to_path_buf());
+    let all = scan(&options).expect("scan should succeed");
+    assert!(all.keys.iter().any(|k| k.value_type != ValueType::ApiKey));
+
+    let options = options.with_only_value_types(vec!["api key".parse().unwrap()]);
+    let result = scan(&options).expect("scan should succeed");
+    assert!(!result.keys.is_empty());
+    assert!(result
+        .keys
+        .iter()
+        .all(|k| k.value_type == ValueType::ApiKey));
+    assert!(result
+        .config_instances
+        .iter()
+        .flat_map(|instance| &instance.keys)
+        .all(|k| k.value_type == ValueType::ApiKey));
+}
+
+#[test]
+fn test_scan_scanner_settings_cover_nonstandard_install() {
+    let temp_home = TempDir::new().unwrap();
+    let install = temp_home.path().join("Apps").join("Claude");
+    fs::create_dir_all(&install).unwrap();
+    fs::write(
+        install.join("claude.json"),
+        r#"{"userID": "sk-ant-REDACTED"}"#,
+    )
+    .unwrap();
+
+    let options = ScanOptions::default().with_home_dir(temp_home.path().to_path_buf());
+    let result = scan(&options).expect("scan should succeed");
+    assert!(!result.keys.iter().any(|k| k.provider == "anthropic"));
+
+    let options = options.with_scanner_settings(
+        "claude-desktop",
+        ScannerContext {
+            extra_paths: vec!["~/Apps/Claude/claude.json".into()],
+            ..ScannerContext::default()
+        },
+    );
+    let result = scan(&options).expect("scan should succeed");
+    let key = result
+        .keys
+        .iter()
+        .find(|k| k.provider == "anthropic")
+        .expect("key in the extra path should be found");
+    assert!(key.source_file.ends_with("claude.json"));
+
+    let options = options.with_scanner_settings(
+        "claude-desktop",
+        ScannerContext {
+            extra_paths: vec!["~/Apps/Claude/claude.json".into()],
+            disabled_patterns: vec!["user*".to_string()],
+            ..ScannerContext::default()
+        },
+    );
+    let result = scan(&options).expect("scan should succeed");
+    assert!(!result.keys.iter().any(|k| k.provider == "anthropic"));
+}
+
+#[test]
+fn test_scan_with_progress_reports_scanners_and_redacted_keys() {
+    let temp_home = TempDir::new().unwrap();
+    fs::write(
+        temp_home.path().join(".env"),
+        "OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\n",
+    )
+    .unwrap();
+
+    let options = ScanOptions::default().with_home_dir(temp_home.path().to_path_buf());
+    let mut runs = Vec::new();
+    let mut keys = Vec::new();
+    let result = scan_with_progress(&options, |event| match event {
+        ScanEvent::Scanner {
+            completed, total, ..
+        } => runs.push((completed, total)),
+        ScanEvent::Key(key) => keys.push(key.redacted_value()),
+    })
+    .expect("scan should succeed");
+
+    let total = runs[0].1;
+    assert_eq!(runs.len(), total);
+    assert!(runs.iter().enumerate().all(|(i, run)| *run == (i, total)));
+    assert_eq!(keys.len(), result.keys.len());
+    assert!(keys.iter().all(|value| !value.contains("ABCDEFGHIJKLMNOP")));
+}
+
+#[test]
+fn test_cancelled_scan_stops_at_next_scanner() {
+    let temp_home = TempDir::new().unwrap();
+    let token = CancellationToken::new();
+    let options = ScanOptions::default()
+        .with_home_dir(temp_home.path().to_path_buf())
+        .with_cancellation(token.clone());
+
+    let mut runs = 0;
+    let result = scan_with_progress(&options, |event| {
+        if let ScanEvent::Scanner { .. } = event {
+            runs += 1;
+            token.cancel();
+        }
+    });
+    assert!(matches!(result, Err(aicred_core::Error::Cancelled)));
+    assert_eq!(runs, 1);
+    assert!(matches!(scan(&options), Err(aicred_core::Error::Cancelled)));
+}
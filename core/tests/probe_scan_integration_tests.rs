@@ -12,12 +12,22 @@ fn test_scan_with_probe_models_disabled() {
     // Create scan options with probing disabled
     let options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: true,
+        redaction: aicred_core::RedactionMode::None,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     // Run scan
@@ -43,12 +53,22 @@ fn test_scan_with_probe_models_enabled() {
     // Create scan options with probing enabled
     let options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: true,
+        redaction: aicred_core::RedactionMode::None,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: true,
         probe_timeout_secs: 5,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     // Run scan
@@ -85,12 +105,22 @@ fn test_scan_graceful_error_handling_with_probing() {
     // Create scan options with probing enabled
     let options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: true,
+        redaction: aicred_core::RedactionMode::None,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: true,
         probe_timeout_secs: 5,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     // Run scan - should succeed even if no instances are found
@@ -110,12 +140,22 @@ fn test_probe_statistics_in_metadata() {
     // Create scan options with probing enabled
     let options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: true,
+        redaction: aicred_core::RedactionMode::None,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: true,
         probe_timeout_secs: 5,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     // Run scan
@@ -161,3 +201,55 @@ fn test_probe_statistics_in_metadata() {
         "Total should equal successful + failures"
     );
 }
+
+/// Scan options for a home whose gsh config points OpenRouter at `base_url`.
+fn gsh_probe_options(home: &std::path::Path, base_url: &str, offline: bool) -> ScanOptions {
+    std::fs::write(
+        home.join(".gshrc"),
+        format!(
+            "export GSH_SLOW_MODEL_API_KEY=\"sk-or-v1-abcdef1234567890abcdef1234567890\"\n\
+             export GSH_SLOW_MODEL_BASE_URL=\"{base_url}\"\n"
+        ),
+    )
+    .unwrap();
+    ScanOptions {
+        home_dir: Some(home.to_path_buf()),
+        probe_models: true,
+        probe_timeout_secs: 5,
+        network: aicred_core::http::NetworkOptions {
+            retries: 0,
+            ..aicred_core::http::NetworkOptions::default()
+        },
+        offline,
+        ..ScanOptions::default()
+    }
+}
+
+#[test]
+fn test_offline_scan_makes_no_requests() {
+    let mut server = mockito::Server::new();
+    let models = server
+        .mock("GET", "/models")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .expect(0)
+        .create();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let result = scan(&gsh_probe_options(temp_dir.path(), &server.url(), true)).unwrap();
+    assert!(
+        !result.config_instances.is_empty(),
+        "the gsh config should be found"
+    );
+    models.assert();
+
+    // The same scan online does reach the server, so the check above is not vacuous
+    let online = server
+        .mock("GET", "/models")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .expect_at_least(1)
+        .create();
+    scan(&gsh_probe_options(temp_dir.path(), &server.url(), false)).unwrap();
+    online.assert();
+}
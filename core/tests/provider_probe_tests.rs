@@ -167,13 +167,14 @@ async fn test_openrouter_probe_network_error() {
 async fn test_openrouter_probe_server_error() {
     let mut server = Server::new_async().await;
 
-    // Create mock endpoint that returns 500
+    // Create mock endpoint that returns 500, which is retried before failing
     let mock = server
         .mock("GET", "/models")
         .match_header("authorization", "Bearer test-api-key")
         .with_status(500)
         .with_header("content-type", "application/json")
         .with_body(r#"{"error": "Internal server error"}"#)
+        .expect(1 + aicred_core::http::DEFAULT_RETRIES as usize)
         .create_async()
         .await;
 
@@ -256,3 +257,32 @@ async fn test_openrouter_probe_with_minimal_model_data() {
     // Note: pricing field doesn't exist in new ModelMetadata
     assert!(models[0].architecture.is_none());
 }
+
+#[test]
+fn test_probe_models_without_scanning() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/models")
+        .match_header("authorization", "Bearer test-api-key")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(create_mock_models_response().to_string())
+        .create();
+
+    let network = aicred_core::http::NetworkOptions::default();
+    let timeout = std::time::Duration::from_secs(5);
+    let models = aicred_core::probe_models(
+        "openrouter",
+        "test-api-key",
+        Some(&server.url()),
+        &network,
+        timeout,
+    )
+    .unwrap();
+    mock.assert();
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].id, Some("openai/gpt-4".to_string()));
+
+    let unknown = aicred_core::probe_models("nope", "test-api-key", None, &network, timeout);
+    assert!(matches!(unknown, Err(Error::NotFound(_))));
+}
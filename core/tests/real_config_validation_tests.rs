@@ -5,7 +5,9 @@
 //! This test suite validates the scanner architecture against real-world configuration files
 //! to ensure accurate provider detection, model counting, and output formatting.
 
-use aicred_core::scanners::{ClaudeDesktopScanner, GshScanner, RooCodeScanner, ScannerPlugin};
+use aicred_core::scanners::{
+    ClaudeDesktopScanner, GshScanner, RooCodeScanner, ScannerContext, ScannerPlugin,
+};
 use std::path::Path;
 
 /// Helper function to get fixture path
@@ -26,7 +28,7 @@ fn test_gsh_real_config_validation() {
     let content = std::fs::read_to_string(&config_path).expect("Failed to read GSH config");
 
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("GSH scan should succeed");
 
     // Print detailed results for manual verification
@@ -131,7 +133,7 @@ fn test_claude_desktop_real_config_validation() {
         std::fs::read_to_string(&config_path).expect("Failed to read Claude Desktop config");
 
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Claude Desktop scan should succeed");
 
     // Print detailed results
@@ -223,7 +225,7 @@ fn test_roo_code_real_config_validation() {
     let content = std::fs::read_to_string(&config_path).expect("Failed to read Roo Code config");
 
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Roo Code scan should succeed");
 
     // Print detailed results
@@ -327,7 +329,7 @@ fn test_no_duplicates_for_scanner(name: &str, scanner: &dyn ScannerPlugin, filen
     let content = std::fs::read_to_string(&config_path).expect("Failed to read config");
 
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Scan should succeed");
 
     assert_eq!(result.instances.len(), 1, "Should have one config instance");
@@ -384,7 +386,7 @@ fn test_format_for_scanner(name: &str, scanner: &dyn ScannerPlugin, filename: &s
     let content = std::fs::read_to_string(&config_path).expect("Failed to read config");
 
     let result = scanner
-        .parse_config(&config_path, &content)
+        .parse_config(&config_path, &content, &ScannerContext::default())
         .expect("Scan should succeed");
 
     assert_eq!(result.instances.len(), 1, "Should have one config instance");
@@ -434,7 +436,7 @@ fn test_model_count_accuracy() {
     let gsh_path = fixture_path("test_gsh_config.gshrc");
     let gsh_content = std::fs::read_to_string(&gsh_path).expect("Failed to read GSH config");
     let gsh_result = gsh_scanner
-        .parse_config(&gsh_path, &gsh_content)
+        .parse_config(&gsh_path, &gsh_content, &ScannerContext::default())
         .expect("GSH scan should succeed");
 
     assert_eq!(gsh_result.instances.len(), 1);
@@ -451,7 +453,7 @@ fn test_model_count_accuracy() {
     let claude_content =
         std::fs::read_to_string(&claude_path).expect("Failed to read Claude config");
     let claude_result = claude_scanner
-        .parse_config(&claude_path, &claude_content)
+        .parse_config(&claude_path, &claude_content, &ScannerContext::default())
         .expect("Claude scan should succeed");
 
     assert_eq!(claude_result.instances.len(), 1);
@@ -474,7 +476,7 @@ fn test_model_count_accuracy() {
     let roo_path = fixture_path("test_roo_code_config.json");
     let roo_content = std::fs::read_to_string(&roo_path).expect("Failed to read Roo config");
     let roo_result = roo_scanner
-        .parse_config(&roo_path, &roo_content)
+        .parse_config(&roo_path, &roo_content, &ScannerContext::default())
         .expect("Roo scan should succeed");
 
     assert_eq!(roo_result.instances.len(), 1);
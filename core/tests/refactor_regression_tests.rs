@@ -13,12 +13,22 @@ fn test_basic_scan_flow() {
     let temp = TempDir::new().unwrap();
     let options = ScanOptions {
         home_dir: Some(temp.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let result = scan(&options);
@@ -112,15 +122,28 @@ fn test_provider_model_tuple_parsing() {
 fn test_scan_options_defaults() {
     let options = ScanOptions {
         home_dir: None,
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
-    assert!(!options.include_full_values, "Should default to redacted");
+    assert!(
+        !options.includes_full_values(),
+        "Should default to redacted"
+    );
     assert_eq!(options.max_file_size, 1024 * 1024);
     assert!(!options.probe_models, "Should default to no probing");
 }
@@ -135,11 +158,13 @@ fn test_error_types() {
     let validation = Error::ValidationError("test".to_string());
     assert!(matches!(validation, Error::ValidationError(_)));
 
-    let parse = Error::ParseError {
+    let parse = Error::ParseFailed {
         path: PathBuf::from("/test/path"),
+        format: "json".to_string(),
         message: "test error".to_string(),
     };
-    assert!(matches!(parse, Error::ParseError { .. }));
+    assert!(matches!(parse, Error::ParseFailed { .. }));
+    assert_eq!(parse.code(), 200);
 }
 
 #[test]
@@ -164,12 +189,22 @@ fn test_scan_with_filters() {
     // Test with only_providers filter
     let options = ScanOptions {
         home_dir: Some(temp.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: Some(vec!["openai".to_string()]),
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let result = scan(&options);
@@ -178,12 +213,22 @@ fn test_scan_with_filters() {
     // Test with exclude_providers filter
     let options_exclude = ScanOptions {
         home_dir: Some(temp.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: Some(vec!["groq".to_string()]),
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let result_exclude = scan(&options_exclude);
@@ -6,7 +6,7 @@
 #![allow(unused_imports)]
 
 use aicred_core::models::{Confidence, ConfigInstance, DiscoveredCredential, ValueType};
-use aicred_core::scanners::{ScanResult, ScannerPlugin, ScannerRegistry};
+use aicred_core::scanners::{ScanResult, ScannerContext, ScannerPlugin, ScannerRegistry};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -34,6 +34,7 @@ impl ScannerPlugin for MockScanner {
         &self,
         path: &Path,
         content: &str,
+        _context: &aicred_core::ScannerContext,
     ) -> Result<ScanResult, aicred_core::error::Error> {
         use aicred_core::scanners::ScannerPluginExt;
 
@@ -117,7 +118,7 @@ fn test_scanner_parse_config() {
     // Test parsing with API key
     let content_with_key = r#"{"api_key": "sk-test1234567890abcdef", "mock_app": true}"#;
     let result = scanner
-        .parse_config(&config_path, content_with_key)
+        .parse_config(&config_path, content_with_key, &ScannerContext::default())
         .unwrap();
 
     assert_eq!(result.keys.len(), 1);
@@ -157,7 +158,11 @@ fn test_scanner_parse_config() {
     // Test parsing without API key
     let content_without_key = r#"{"mock_app": true}"#;
     let result = scanner
-        .parse_config(&config_path, content_without_key)
+        .parse_config(
+            &config_path,
+            content_without_key,
+            &ScannerContext::default(),
+        )
         .unwrap();
 
     assert_eq!(result.keys.len(), 0);
@@ -291,6 +296,7 @@ fn test_scanner_filtering_ignores_provider_filters() {
             &self,
             _path: &Path,
             _content: &str,
+            _context: &aicred_core::ScannerContext,
         ) -> Result<ScanResult, aicred_core::error::Error> {
             Ok(ScanResult::new())
         }
@@ -325,12 +331,22 @@ fn test_scanner_filtering_ignores_provider_filters() {
     // This should succeed because scanners are not filtered by provider names
     let scan_options = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: Some(vec!["openai".to_string(), "anthropic".to_string()]),
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let result = aicred_core::scan(&scan_options);
@@ -346,12 +362,22 @@ fn test_scanner_filtering_ignores_provider_filters() {
     // Even if we exclude providers with names like "mock", the mock scanner should still run
     let scan_options_exclude = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: Some(vec!["mock".to_string(), "another_mock".to_string()]),
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let result = aicred_core::scan(&scan_options_exclude);
@@ -367,12 +393,22 @@ fn test_scanner_filtering_ignores_provider_filters() {
     // by ensuring scans don't fail due to "no scanners" when provider filters are applied
     let scan_options_no_providers = ScanOptions {
         home_dir: Some(temp_dir.path().to_path_buf()),
-        include_full_values: false,
+        redaction: aicred_core::RedactionMode::First8,
         max_file_size: 1024 * 1024,
         only_providers: None,
         exclude_providers: None,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: None,
+        scanners: None,
     };
 
     let result = aicred_core::scan(&scan_options_no_providers);
@@ -1152,7 +1188,9 @@ fn test_complete_flow_with_provider_instances() {
 
     // Test complete flow: parse config -> discover keys -> build provider instances
     let content = r#"{"api_key": "sk-test1234567890abcdef", "mock_app": true}"#;
-    let result = scanner.parse_config(&config_path, content).unwrap();
+    let result = scanner
+        .parse_config(&config_path, content, &ScannerContext::default())
+        .unwrap();
 
     // Verify keys were discovered
     assert_eq!(result.keys.len(), 1);
@@ -1873,15 +1911,15 @@ fn test_instance_id_generation() {
     assert_eq!(instances.len(), 1);
     let instance = &instances[0];
 
-    // Instance ID should be a 4-character hash based on provider name and file path
+    // Instance ID should be the provider and a 12-character hash of the key
     assert_eq!(
-        instance.id.len(),
-        4,
-        "Instance ID should be 4 characters long"
+        instance.id,
+        aicred_core::discovery::ids::key_instance_id(&keys[0]),
+        "Instance ID should be derived from the key"
     );
     assert!(
-        instance.id.chars().all(|c| c.is_ascii_hexdigit()),
-        "Instance ID should be a valid hex string"
+        instance.id.starts_with("openai-") && instance.id.len() == "openai-".len() + 12,
+        "Instance ID should be the provider slug and a 12-character hash"
     );
 
     // Verify consistency: same inputs should produce same hash
@@ -1949,3 +1987,106 @@ fn test_multiple_models_same_provider() {
     assert!(model_ids.contains(&"gpt-3.5-turbo"));
     assert!(model_ids.contains(&"gpt-4-turbo"));
 }
+
+/// Scanner written against the version 2 API
+struct FindingScanner;
+
+impl aicred_core::Scanner for FindingScanner {
+    fn name(&self) -> &str {
+        "finding"
+    }
+
+    fn app_name(&self) -> &str {
+        "Finding Application"
+    }
+
+    fn scan_paths(&self, home_dir: &Path) -> Vec<PathBuf> {
+        vec![home_dir.join("finding.env")]
+    }
+
+    fn can_handle_file(&self, path: &Path) -> bool {
+        path.ends_with("finding.env")
+    }
+
+    fn scan_file(
+        &self,
+        path: &Path,
+        content: &str,
+        _context: &ScannerContext,
+    ) -> Result<aicred_core::scanners::ScanOutput, aicred_core::error::Error> {
+        use aicred_core::scanners::{FindingSource, ScanOutput, Span};
+
+        let source = FindingSource::new(path, self);
+        let findings = content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let value = line.strip_prefix("FINDING_KEY=")?;
+                let key = DiscoveredCredential::new(
+                    "openai".to_string(),
+                    path.display().to_string(),
+                    ValueType::ApiKey,
+                    Confidence::High,
+                    value.to_string(),
+                );
+                Some(
+                    aicred_core::Finding::new(key, source.clone()).with_span(Span {
+                        line: index + 1,
+                        column: 13,
+                        len: value.len(),
+                    }),
+                )
+            })
+            .collect();
+        Ok(ScanOutput {
+            findings,
+            instances: Vec::new(),
+        })
+    }
+}
+
+#[test]
+fn test_registry_runs_v2_and_legacy_scanners() {
+    use aicred_core::scanners::ScanOutput;
+    use aicred_core::Scanner;
+
+    let registry = ScannerRegistry::new();
+    registry
+        .register_scanner(std::sync::Arc::new(FindingScanner))
+        .unwrap();
+    registry.register(std::sync::Arc::new(MockScanner)).unwrap();
+    assert!(registry
+        .register_scanner(std::sync::Arc::new(FindingScanner))
+        .is_err());
+
+    let scanner = registry.get("finding").unwrap();
+    let output = scanner
+        .scan_file(
+            Path::new("finding.env"),
+            "# keys\nFINDING_KEY=sk-finding1234567890\n",
+            &ScannerContext::default(),
+        )
+        .unwrap();
+    assert_eq!(output.findings.len(), 1);
+    let finding = &output.findings[0];
+    assert_eq!(finding.span.unwrap().line, 2);
+    assert_eq!(finding.source.scanner, "finding");
+    assert!(finding.remediation.is_some());
+
+    let result = ScanResult::from(ScanOutput {
+        findings: output.findings,
+        instances: Vec::new(),
+    });
+    assert_eq!(result.keys[0].source_line, Some(2));
+    assert_eq!(result.keys[0].column_number, Some(13));
+
+    // Plugins of the old trait run through the adapter
+    let legacy = registry.get("mock").unwrap();
+    assert_eq!(legacy.app_name(), "Mock Application");
+    assert_eq!(
+        registry
+            .get_scanners_for_file(Path::new("mock_config.json"))
+            .len(),
+        1
+    );
+}
@@ -46,6 +46,8 @@ mod integration_tests {
             },
             assigned_at: Utc::now(),
             assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
 
         assert_eq!(instance_assignment.target.instance_id(), "provider-123");
@@ -60,6 +62,8 @@ mod integration_tests {
             },
             assigned_at: Utc::now(),
             assigned_by: None,
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
 
         assert_eq!(model_assignment.target.instance_id(), "provider-123");
@@ -80,6 +84,8 @@ mod integration_tests {
             },
             assigned_at: Utc::now(),
             assigned_by: Some("user-1".to_string()),
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
 
         let assignment2 = LabelAssignment {
@@ -89,6 +95,8 @@ mod integration_tests {
             },
             assigned_at: Utc::now(),
             assigned_by: Some("user-2".to_string()),
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
 
         assert_eq!(assignment1.label_name, "fast");
@@ -131,6 +139,8 @@ mod integration_tests {
             },
             assigned_at: Utc::now(),
             assigned_by: Some("system".to_string()),
+            fallbacks: Vec::new(),
+            requires: Vec::new(),
         };
 
         // Test serialization to JSON
@@ -158,6 +168,8 @@ mod integration_tests {
                 },
                 assigned_at: Utc::now(),
                 assigned_by: None,
+                fallbacks: Vec::new(),
+                requires: Vec::new(),
             },
             LabelAssignment {
                 label_name: label.name.clone(),
@@ -167,6 +179,8 @@ mod integration_tests {
                 },
                 assigned_at: Utc::now(),
                 assigned_by: Some("user-1".to_string()),
+                fallbacks: Vec::new(),
+                requires: Vec::new(),
             },
         ];
 
@@ -3,23 +3,34 @@ extern crate cbindgen;
 use std::env;
 use std::path::PathBuf;
 
+/// Header kept for hosts including it by its old name
+const COMPAT_HEADER: &str = "/* Compatibility header: include aicred.h instead. */
+#ifndef GENAI_KEYFINDER_H
+#define GENAI_KEYFINDER_H
+
+#include \"aicred.h\"
+
+#endif /* GENAI_KEYFINDER_H */
+";
+
 fn main() {
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    let output_file = PathBuf::from(&crate_dir)
-        .join("include")
-        .join("genai_keyfinder.h");
+    let include_dir = PathBuf::from(&crate_dir).join("include");
 
     // Create the include directory if it doesn't exist
-    if let Some(parent) = output_file.parent() {
-        std::fs::create_dir_all(parent).expect("Failed to create include directory");
-    }
+    std::fs::create_dir_all(&include_dir).expect("Failed to create include directory");
 
     cbindgen::Builder::new()
         .with_crate(crate_dir)
         .with_language(cbindgen::Language::C)
         .with_documentation(true)
-        .with_include_guard("GENAI_KEYFINDER_H")
+        .with_include_guard("AICRED_H")
         .generate()
         .expect("Unable to generate bindings")
-        .write_to_file(output_file);
+        .write_to_file(include_dir.join("aicred.h"));
+
+    let compat = include_dir.join("genai_keyfinder.h");
+    if std::fs::read_to_string(&compat).ok().as_deref() != Some(COMPAT_HEADER) {
+        std::fs::write(&compat, COMPAT_HEADER).expect("Failed to write compatibility header");
+    }
 }
@@ -0,0 +1,809 @@
+#ifndef AICRED_H
+#define AICRED_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * No error occurred
+ */
+#define AICRED_ERROR_NONE 0
+
+/**
+ * An argument was null, not UTF-8 or not valid JSON
+ */
+#define AICRED_ERROR_INVALID_ARGUMENT 900
+
+/**
+ * A result could not be serialized to JSON
+ */
+#define AICRED_ERROR_SERIALIZATION 901
+
+/**
+ * The library panicked
+ */
+#define AICRED_ERROR_PANIC 902
+
+/**
+ * A caller-provided buffer was too small for a result
+ */
+#define AICRED_ERROR_BUFFER_TOO_SMALL 903
+
+/**
+ * Major version of the ABI this header describes
+ */
+#define AICRED_ABI_VERSION_MAJOR 1
+
+/**
+ * Minor version of the ABI this header describes
+ */
+#define AICRED_ABI_VERSION_MINOR 3
+
+/**
+ * Patch version of the ABI this header describes
+ */
+#define AICRED_ABI_VERSION_PATCH 0
+
+/**
+ * The scan of a job is still running
+ */
+#define AICRED_SCAN_RUNNING 0
+
+/**
+ * The scan of a job is done and its result ready
+ */
+#define AICRED_SCAN_DONE 1
+
+/**
+ * The scan of a job failed, or was cancelled
+ */
+#define AICRED_SCAN_FAILED 2
+
+/**
+ * A [`aicred_scan_event_t`] reporting progress
+ */
+#define AICRED_SCAN_EVENT_PROGRESS 1
+
+/**
+ * A [`aicred_scan_event_t`] reporting a key
+ */
+#define AICRED_SCAN_EVENT_KEY 2
+
+/**
+ * Cancellation handle, freed with [`aicred_cancellation_free`]
+ */
+typedef struct aicred_cancellation_t aicred_cancellation_t;
+
+/**
+ * Result of [`aicred_scan_ex`], freed with [`aicred_result_free`]
+ */
+typedef struct aicred_result_t aicred_result_t;
+
+/**
+ * Scan job, freed with [`aicred_scan_free`]
+ */
+typedef struct aicred_scan_job_t aicred_scan_job_t;
+
+/**
+ * A semantic version of the ABI
+ */
+typedef struct aicred_abi_version_t {
+  /**
+   * Changes with incompatible changes
+   */
+  uint32_t major;
+  /**
+   * Changes when functions are added
+   */
+  uint32_t minor;
+  /**
+   * Changes with fixes
+   */
+  uint32_t patch;
+} aicred_abi_version_t;
+
+/**
+ * A scanner starting on a scan root
+ */
+typedef struct aicred_progress_t {
+  /**
+   * Name of the scanner
+   */
+  const char *scanner;
+  /**
+   * Scan root being scanned
+   */
+  const char *root;
+  /**
+   * Scanner runs finished so far
+   */
+  uintptr_t completed;
+  /**
+   * Scanner runs of the whole scan
+   */
+  uintptr_t total;
+} aicred_progress_t;
+
+/**
+ * A new key, redacted as the scan options ask
+ */
+typedef struct aicred_key_event_t {
+  /**
+   * Provider of the key, such as `openai`
+   */
+  const char *provider;
+  /**
+   * Type of the value, such as `API Key`
+   */
+  const char *value_type;
+  /**
+   * Value of the key
+   */
+  const char *value;
+  /**
+   * File the key was found in
+   */
+  const char *source;
+  /**
+   * Line of the file the key was found on, 0 if unknown
+   */
+  uint64_t line;
+} aicred_key_event_t;
+
+/**
+ * An event of a running scan
+ *
+ * `progress` is set for [`AICRED_SCAN_EVENT_PROGRESS`] and `key` for
+ * [`AICRED_SCAN_EVENT_KEY`], the other is NULL. Both are only valid during
+ * the callback.
+ */
+typedef struct aicred_scan_event_t {
+  /**
+   * `AICRED_SCAN_EVENT_PROGRESS` or `AICRED_SCAN_EVENT_KEY`
+   */
+  uint32_t kind;
+  /**
+   * Progress of the scan
+   */
+  const struct aicred_progress_t *progress;
+  /**
+   * Key found
+   */
+  const struct aicred_key_event_t *key;
+} aicred_scan_event_t;
+
+/**
+ * Callback receiving the events of a scan and the `user_data` of
+ * [`aicred_scan_with_callback`]
+ */
+typedef void (*aicred_scan_callback_t)(const struct aicred_scan_event_t *event, void *user_data);
+
+/**
+ * Scan for GenAI credentials and configurations
+ *
+ * # Parameters
+ * - `home_path`: UTF-8 encoded home directory path (null-terminated C string)
+ * - `options_json`: UTF-8 encoded JSON options (null-terminated C string)
+ *
+ * # Returns
+ * UTF-8 encoded JSON string containing scan results. Caller must free with [`aicred_free`].
+ * Returns NULL on error.
+ *
+ * # Example options_json:
+ * ```json
+ * {
+ *   "redaction": "first8",
+ *   "max_file_size": 1048576,
+ *   "only_providers": ["openai", "anthropic"],
+ *   "exclude_providers": []
+ * }
+ * ```
+ *
+ * `redaction` is one of `none`, `mask`, `last4`, `first8` or `fingerprint`;
+ * the older `"include_full_values": true` is shorthand for `none`.
+ *
+ * # Safety
+ *
+ * Both pointers must be either null or point to valid null-terminated C strings.
+ */
+char *aicred_scan(const char *home_path, const char *options_json);
+
+/**
+ * Free a string returned by aicred_scan
+ *
+ * # Safety
+ *
+ * The pointer must be either null or point to a string allocated by this library.
+ */
+void aicred_free(char *ptr);
+
+/**
+ * Get library version string
+ *
+ * Returns a static version string that does not need to be freed.
+ */
+const char *aicred_version(void);
+
+/**
+ * Get last error message (thread-local)
+ *
+ * Returns a pointer to the last error message, or null if no error occurred.
+ * The returned pointer is valid until the next call to any aicred function.
+ */
+const char *aicred_last_error(void);
+
+/**
+ * Get the code of the last error (thread-local)
+ *
+ * Returns `AICRED_ERROR_NONE` (0) if the last call succeeded, the stable code
+ * of the core library's error, or one of the `AICRED_ERROR_*` codes.
+ */
+uint32_t aicred_last_error_code(void);
+
+/**
+ * Get a short description of an error code, such as "Not found" for 102
+ *
+ * Covers the codes of the core library and the `AICRED_ERROR_*` codes.
+ * Returns a static string that does not need to be freed, or NULL for an
+ * unknown code.
+ */
+const char *aicred_error_message(uint32_t code);
+
+/**
+ * Get list of available provider plugins
+ *
+ * Returns a JSON array of provider names as a UTF-8 encoded string.
+ * Caller must free the returned string with [`aicred_free`].
+ * Returns NULL on error.
+ *
+ * # Example return value:
+ * ```json
+ * ["openai", "anthropic", "huggingface", "groq", "ollama", "litellm", "common-config"]
+ * ```
+ *
+ * # Safety
+ *
+ * The returned pointer must be freed by the caller using [`aicred_free`].
+ */
+char *aicred_list_providers(void);
+
+/**
+ * Get list of available scanner plugins
+ *
+ * Returns a JSON array of scanner names as a UTF-8 encoded string.
+ * Caller must free the returned string with [`aicred_free`].
+ * Returns NULL on error.
+ *
+ * # Example return value:
+ * ```json
+ * ["ragit", "claude-desktop", "roo-code", "langchain", "gsh"]
+ * ```
+ *
+ * # Safety
+ *
+ * The returned pointer must be freed by the caller using [`aicred_free`].
+ */
+char *aicred_list_scanners(void);
+
+/**
+ * List stored provider instances
+ *
+ * Returns a JSON object mapping instance IDs to instances, read from the
+ * configuration store in the config directory of `home_path`, such as
+ * `<home_path>/.config/aicred` on Linux.
+ * Caller must free the returned string with [`aicred_free`].
+ * Returns NULL on error.
+ *
+ * # Safety
+ *
+ * `home_path` must be either null or point to a valid null-terminated C string.
+ * The returned pointer must be freed by the caller using [`aicred_free`].
+ */
+char *aicred_list_instances(const char *home_path);
+
+/**
+ * Query the model registry
+ *
+ * Returns a JSON array of model registry entries, each with its ID,
+ * provider, name, capabilities, context window, pricing per token and
+ * status. The registry holds the builtin entries, the model data last
+ * fetched by `aicred models refresh` and the overrides in the config
+ * directory of `home_path`, or of the current user if `home_path` is null.
+ * Caller must free the returned string with [`aicred_free`].
+ * Returns NULL on error.
+ *
+ * # Example query_json:
+ * ```json
+ * {"provider": "openai", "capabilities": ["vision"], "search": "mini"}
+ * ```
+ *
+ * Every field is optional; a null `query_json` returns every entry.
+ *
+ * # Safety
+ *
+ * Both pointers must be either null or point to valid null-terminated C strings.
+ * The returned pointer must be freed by the caller using [`aicred_free`].
+ */
+char *aicred_model_registry(const char *home_path, const char *query_json);
+
+/**
+ * Record token usage in the usage ledger
+ *
+ * Appends a usage record to the ledger in the data directory of `home_path`,
+ * or of the current user if `home_path` is null, pricing it from the model
+ * registry unless it holds a cost. Returns the record as stored, as JSON.
+ * Caller must free the returned string with [`aicred_free`].
+ * Returns NULL on error.
+ *
+ * # Example record_json:
+ * ```json
+ * {"model": "gpt-4o", "instance_id": "openai-work", "input_tokens": 1200, "output_tokens": 300}
+ * ```
+ *
+ * Only `model` is required; `recorded_at` defaults to now.
+ *
+ * # Safety
+ *
+ * `home_path` must be either null or point to a valid null-terminated C string.
+ * `record_json` must point to a valid null-terminated C string.
+ * The returned pointer must be freed by the caller using [`aicred_free`].
+ */
+char *aicred_record_usage(const char *home_path, const char *record_json);
+
+/**
+ * Probe a provider for the models an API key can use
+ *
+ * Validates a key and lists its models without a scan, through the same
+ * provider plugins as the probes of `aicred scan --probe-models`. `base_url`
+ * replaces the provider's default endpoint if not null. Returns a JSON array
+ * of model metadata. Caller must free the returned string with
+ * [`aicred_free`]. Returns NULL on error, with error code 102 for an unknown
+ * provider.
+ *
+ * # Safety
+ *
+ * `provider` and `api_key` must point to valid null-terminated C strings.
+ * `base_url` must be either null or point to a valid null-terminated C string.
+ * The returned pointer must be freed by the caller using [`aicred_free`].
+ */
+char *aicred_probe_models(const char *provider, const char *api_key, const char *base_url);
+
+/**
+ * Get the ABI version of the loaded library
+ */
+struct aicred_abi_version_t aicred_abi_version(void);
+
+/**
+ * Check whether the loaded library serves hosts built against an ABI version
+ *
+ * True if the library has the same major version and at least the given
+ * minor version, so every function the host may call exists and behaves as
+ * it expects.
+ */
+bool aicred_abi_compatible(uint32_t major, uint32_t minor);
+
+/**
+ * Scan like [`aicred_scan`](crate::aicred_scan), writing the JSON into `buf`
+ *
+ * Returns false on error or if the buffer is too small, which runs the whole
+ * scan; to size a buffer without scanning twice, use
+ * [`aicred_result_json_into`] or [`aicred_scan_result_into`] instead.
+ *
+ * # Safety
+ *
+ * Both strings must be either null or point to valid null-terminated C
+ * strings. `buf` must be either null or valid for `buf_len` bytes of writes,
+ * and `out_needed` either null or valid for writes.
+ */
+bool aicred_scan_into(const char *home_path,
+                      const char *options_json,
+                      char *buf,
+                      uintptr_t buf_len,
+                      uintptr_t *out_needed);
+
+/**
+ * Write the JSON of a result handle into `buf`, as
+ * [`aicred_result_json`](crate::aicred_result_json) returns it
+ *
+ * Returns false on error or if the buffer is too small.
+ *
+ * # Safety
+ *
+ * `result` must be either null or a live handle from
+ * [`aicred_scan_ex`](crate::aicred_scan_ex). `buf` must be either null or
+ * valid for `buf_len` bytes of writes, and `out_needed` either null or valid
+ * for writes.
+ */
+bool aicred_result_json_into(const struct aicred_result_t *result,
+                             char *buf,
+                             uintptr_t buf_len,
+                             uintptr_t *out_needed);
+
+/**
+ * Write the JSON of a finished job into `buf`, as
+ * [`aicred_scan_result`](crate::aicred_scan_result) returns it
+ *
+ * Returns false with the error of the scan set if it failed, or if the
+ * buffer is too small.
+ *
+ * # Safety
+ *
+ * `job` must be either null or a live handle from
+ * [`aicred_scan_start`](crate::aicred_scan_start). `buf` must be either null
+ * or valid for `buf_len` bytes of writes, and `out_needed` either null or
+ * valid for writes.
+ */
+bool aicred_scan_result_into(const struct aicred_scan_job_t *job,
+                             char *buf,
+                             uintptr_t buf_len,
+                             uintptr_t *out_needed);
+
+/**
+ * Write the last error message into `buf`, an empty string if there is none
+ *
+ * Unlike the other `*_into` functions, this keeps the last error, so a too
+ * small buffer can be retried. Returns false if the buffer is too small.
+ *
+ * # Safety
+ *
+ * `buf` must be either null or valid for `buf_len` bytes of writes, and
+ * `out_needed` either null or valid for writes.
+ */
+bool aicred_last_error_into(char *buf, uintptr_t buf_len, uintptr_t *out_needed);
+
+/**
+ * Create a cancellation handle
+ *
+ * Caller must free it with [`aicred_cancellation_free`] once no scan uses it.
+ */
+struct aicred_cancellation_t *aicred_cancellation_new(void);
+
+/**
+ * Cancel the scans using a handle
+ *
+ * Safe to call from any thread, and more than once.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from
+ * [`aicred_cancellation_new`].
+ */
+void aicred_cancellation_cancel(const struct aicred_cancellation_t *cancellation);
+
+/**
+ * Free a cancellation handle
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a handle returned by
+ * [`aicred_cancellation_new`], not yet freed and no longer used by a scan.
+ */
+void aicred_cancellation_free(struct aicred_cancellation_t *cancellation);
+
+/**
+ * Start a scan in the background, returning a job handle
+ *
+ * Takes the same arguments as [`aicred_scan`](crate::aicred_scan). Invalid
+ * arguments fail at once with NULL; errors of the scan itself are reported by
+ * [`aicred_scan_result`]. The job must be freed with [`aicred_scan_free`].
+ *
+ * # Safety
+ *
+ * Both strings must be either null or point to valid null-terminated C
+ * strings.
+ */
+struct aicred_scan_job_t *aicred_scan_start(const char *home_path, const char *options_json);
+
+/**
+ * Check on a job without blocking
+ *
+ * Returns [`AICRED_SCAN_RUNNING`], [`AICRED_SCAN_DONE`] or
+ * [`AICRED_SCAN_FAILED`], which is also returned for a null job.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from
+ * [`aicred_scan_start`].
+ */
+uint32_t aicred_scan_poll(const struct aicred_scan_job_t *job);
+
+/**
+ * Progress of a job: scanner runs finished and in all
+ *
+ * Either pointer may be null. The total is 0 until the first scanner starts.
+ *
+ * # Safety
+ *
+ * `job` must be either null or a live handle from [`aicred_scan_start`];
+ * `completed` and `total` must be either null or valid for writes.
+ */
+void aicred_scan_progress(const struct aicred_scan_job_t *job,
+                          uintptr_t *completed,
+                          uintptr_t *total);
+
+/**
+ * The result of a finished job as JSON, as [`aicred_scan`](crate::aicred_scan)
+ * returns it
+ *
+ * Returns NULL with the error of the scan set if it failed, and with code 900
+ * if it is still running. Caller must free the returned string with
+ * [`aicred_free`](crate::aicred_free).
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from
+ * [`aicred_scan_start`].
+ */
+char *aicred_scan_result(const struct aicred_scan_job_t *job);
+
+/**
+ * Cancel a running job
+ *
+ * The job then fails with error code 700. Safe to call from any thread, and
+ * more than once.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from
+ * [`aicred_scan_start`].
+ */
+void aicred_scan_cancel(const struct aicred_scan_job_t *job);
+
+/**
+ * Free a job
+ *
+ * A job still running is cancelled, and its thread exits on its own.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a handle returned by
+ * [`aicred_scan_start`] and not yet freed.
+ */
+void aicred_scan_free(struct aicred_scan_job_t *job);
+
+/**
+ * Scan for GenAI credentials and configurations, reporting progress
+ *
+ * Takes the arguments of [`aicred_scan`](crate::aicred_scan) and returns the
+ * same JSON, but calls `callback` with `user_data` on the calling thread
+ * before each scanner runs and for each new key. A NULL callback scans
+ * without reporting, and a non-NULL `cancellation` handle cancels the scan.
+ * Caller must free the returned string with
+ * [`aicred_free`](crate::aicred_free). Returns NULL on error.
+ *
+ * # Safety
+ *
+ * Both strings must be either null or point to valid null-terminated C
+ * strings. `user_data` is only passed to `callback`. `cancellation` must be
+ * either null or a live handle from
+ * [`aicred_cancellation_new`](crate::aicred_cancellation_new).
+ */
+char *aicred_scan_with_callback(const char *home_path,
+                                const char *options_json,
+                                aicred_scan_callback_t callback,
+                                void *user_data,
+                                const struct aicred_cancellation_t *cancellation);
+
+/**
+ * Scan for GenAI credentials and configurations, returning a result handle
+ *
+ * Takes the same arguments as [`aicred_scan`](crate::aicred_scan), and
+ * optionally a handle to cancel the scan with. The result is read with the
+ * `aicred_result_*` functions and must be freed with [`aicred_result_free`].
+ * Returns NULL on error.
+ *
+ * # Safety
+ *
+ * Both strings must be either null or point to valid null-terminated C
+ * strings. `cancellation` must be either null or a live handle from
+ * [`aicred_cancellation_new`](crate::aicred_cancellation_new).
+ */
+struct aicred_result_t *aicred_scan_ex(const char *home_path,
+                                       const char *options_json,
+                                       const struct aicred_cancellation_t *cancellation);
+
+/**
+ * Free a result returned by [`aicred_scan_ex`]
+ *
+ * Strings returned by the accessors of the result are freed with it.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a handle returned by [`aicred_scan_ex`]
+ * and not yet freed.
+ */
+void aicred_result_free(struct aicred_result_t *result);
+
+/**
+ * Number of keys found, 0 for a null result
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+uintptr_t aicred_result_key_count(const struct aicred_result_t *result);
+
+/**
+ * Provider of key `index`, such as `openai`
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_key_provider(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Value of key `index`, redacted as the scan options asked
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_key_value(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Type of key `index`, such as `API Key`
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_key_type(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * File key `index` was found in
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_key_source(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Line of its source file key `index` was found on, 0 if unknown
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+uint64_t aicred_result_key_line(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Fingerprint of key `index`
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_key_hash(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Risk level of key `index`: `Low`, `Medium`, `High` or `Critical`
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_key_risk(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Number of application configurations found, 0 for a null result
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+uintptr_t aicred_result_config_count(const struct aicred_result_t *result);
+
+/**
+ * Application of configuration `index`, such as `roo-code`
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_config_app(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Path of configuration `index`
+ *
+ * Returns NULL if `index` is out of range. The string is owned by the result.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+const char *aicred_result_config_path(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * Number of keys in configuration `index`, 0 if out of range
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+uintptr_t aicred_result_config_key_count(const struct aicred_result_t *result, uintptr_t index);
+
+/**
+ * The whole result as JSON, as [`aicred_scan`](crate::aicred_scan) returns it
+ *
+ * For the fields the accessors don't cover. Caller must free the returned
+ * string with [`aicred_free`](crate::aicred_free). Returns NULL on error.
+ *
+ * # Safety
+ *
+ * The pointer must be either null or a live handle from [`aicred_scan_ex`].
+ */
+char *aicred_result_json(const struct aicred_result_t *result);
+
+/**
+ * Add a provider instance to the configuration store
+ *
+ * The instance is validated and refused if an instance with its ID is
+ * already stored. Its API key is stored as given. Returns the stored instance
+ * as JSON. Caller must free the returned string with
+ * [`aicred_free`](crate::aicred_free). Returns NULL on error.
+ *
+ * # Example instance_json:
+ * ```json
+ * {"id": "openai-work", "provider_type": "openai", "base_url": "https://api.openai.com/v1",
+ *  "api_key": "sk-...", "models": ["gpt-4o"]}
+ * ```
+ *
+ * `api_key` and `models` are optional, and `active` defaults to true.
+ *
+ * # Safety
+ *
+ * `home_path` must be either null or point to a valid null-terminated C string.
+ * `instance_json` must point to a valid null-terminated C string.
+ */
+char *aicred_instance_add(const char *home_path, const char *instance_json);
+
+/**
+ * Remove a provider instance from the configuration store
+ *
+ * Like `aicred instances remove`, this keeps the file as a backup, drops the
+ * label and tag assignments pointing at the instance and deletes the secret
+ * its key references if it can. Returns false on error, with error code 102
+ * if no instance has the ID.
+ *
+ * # Safety
+ *
+ * `home_path` must be either null or point to a valid null-terminated C string.
+ * `id` must point to a valid null-terminated C string.
+ */
+bool aicred_instance_remove(const char *home_path, const char *id);
+
+/**
+ * Resolve a label to the instance and model it points at
+ *
+ * Follows the label's targets and fallbacks like `aicred resolve`, returning
+ * the label, the instance with its API key revealed, the model and the
+ * reasons any targets were passed over, as JSON. Caller must free the
+ * returned string with [`aicred_free`](crate::aicred_free). Returns NULL on
+ * error.
+ *
+ * # Safety
+ *
+ * `home_path` must be either null or point to a valid null-terminated C string.
+ * `name` must point to a valid null-terminated C string.
+ */
+char *aicred_label_resolve(const char *home_path, const char *name);
+
+#endif /* AICRED_H */
@@ -0,0 +1,49 @@
+//! Version of the C ABI
+//!
+//! The ABI is versioned apart from the library: the major version changes
+//! when a function or struct changes incompatibly, the minor version when
+//! functions are added and the patch version for fixes. A host built against
+//! one header can check at runtime that the loaded library is compatible with
+//! [`aicred_abi_compatible`]`(AICRED_ABI_VERSION_MAJOR, AICRED_ABI_VERSION_MINOR)`.
+
+/// Major version of the ABI this header describes
+pub const AICRED_ABI_VERSION_MAJOR: u32 = 1;
+
+/// Minor version of the ABI this header describes
+pub const AICRED_ABI_VERSION_MINOR: u32 = 3;
+
+/// Patch version of the ABI this header describes
+pub const AICRED_ABI_VERSION_PATCH: u32 = 0;
+
+/// A semantic version of the ABI
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct aicred_abi_version_t {
+    /// Changes with incompatible changes
+    pub major: u32,
+    /// Changes when functions are added
+    pub minor: u32,
+    /// Changes with fixes
+    pub patch: u32,
+}
+
+/// Get the ABI version of the loaded library
+#[no_mangle]
+pub extern "C" fn aicred_abi_version() -> aicred_abi_version_t {
+    aicred_abi_version_t {
+        major: AICRED_ABI_VERSION_MAJOR,
+        minor: AICRED_ABI_VERSION_MINOR,
+        patch: AICRED_ABI_VERSION_PATCH,
+    }
+}
+
+/// Check whether the loaded library serves hosts built against an ABI version
+///
+/// True if the library has the same major version and at least the given
+/// minor version, so every function the host may call exists and behaves as
+/// it expects.
+#[no_mangle]
+pub extern "C" fn aicred_abi_compatible(major: u32, minor: u32) -> bool {
+    major == AICRED_ABI_VERSION_MAJOR && minor <= AICRED_ABI_VERSION_MINOR
+}
@@ -0,0 +1,159 @@
+//! Results written into caller-provided buffers
+//!
+//! The `*_into` functions copy a result into a buffer the caller owns instead
+//! of returning a string to free with [`aicred_free`](crate::aicred_free), so
+//! hosts whose allocator differs from the library's, as across DLLs on
+//! Windows, never free memory they did not allocate. Each stores the size the
+//! result needs, NUL included, in `out_needed`, and fails with
+//! [`AICRED_ERROR_BUFFER_TOO_SMALL`] if the buffer is smaller: call again with
+//! a buffer of that size, or with a NULL buffer to ask for the size alone.
+
+use crate::job::{self, aicred_scan_job_t};
+use crate::result::{self, aicred_result_t};
+use crate::{
+    clear_last_error, get_last_error, safe_execute, scan_json, set_last_error, FfiError,
+    AICRED_ERROR_BUFFER_TOO_SMALL,
+};
+
+/// Copies a string and its NUL into a buffer of `buf_len` bytes
+///
+/// # Safety
+///
+/// `buf` must be either null or valid for `buf_len` bytes of writes;
+/// `out_needed` must be either null or valid for writes.
+unsafe fn write_into(
+    value: &str,
+    buf: *mut libc::c_char,
+    buf_len: usize,
+    out_needed: *mut usize,
+) -> Result<(), FfiError> {
+    let bytes = result::c_string(value).into_bytes_with_nul();
+    if let Some(out_needed) = out_needed.as_mut() {
+        *out_needed = bytes.len();
+    }
+    if buf.is_null() || buf_len < bytes.len() {
+        return Err(FfiError {
+            code: AICRED_ERROR_BUFFER_TOO_SMALL,
+            message: format!("Buffer too small: {} bytes needed", bytes.len()),
+        });
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf.cast::<u8>(), bytes.len());
+    Ok(())
+}
+
+/// Runs `f` and writes the string it returns into a buffer, or sets the last
+/// error
+///
+/// # Safety
+///
+/// As for [`write_into`].
+unsafe fn into_buffer(
+    f: impl FnOnce() -> Result<String, FfiError>,
+    buf: *mut libc::c_char,
+    buf_len: usize,
+    out_needed: *mut usize,
+) -> bool {
+    clear_last_error();
+
+    if let Some(out_needed) = out_needed.as_mut() {
+        *out_needed = 0;
+    }
+    match safe_execute(|| write_into(&f()?, buf, buf_len, out_needed)) {
+        Ok(()) => true,
+        Err(err) => {
+            set_last_error(err);
+            false
+        }
+    }
+}
+
+/// Scan like [`aicred_scan`](crate::aicred_scan), writing the JSON into `buf`
+///
+/// Returns false on error or if the buffer is too small, which runs the whole
+/// scan; to size a buffer without scanning twice, use
+/// [`aicred_result_json_into`] or [`aicred_scan_result_into`] instead.
+///
+/// # Safety
+///
+/// Both strings must be either null or point to valid null-terminated C
+/// strings. `buf` must be either null or valid for `buf_len` bytes of writes,
+/// and `out_needed` either null or valid for writes.
+#[no_mangle]
+pub extern "C" fn aicred_scan_into(
+    home_path: *const libc::c_char,
+    options_json: *const libc::c_char,
+    buf: *mut libc::c_char,
+    buf_len: usize,
+    out_needed: *mut usize,
+) -> bool {
+    unsafe {
+        into_buffer(
+            || scan_json(home_path, options_json),
+            buf,
+            buf_len,
+            out_needed,
+        )
+    }
+}
+
+/// Write the JSON of a result handle into `buf`, as
+/// [`aicred_result_json`](crate::aicred_result_json) returns it
+///
+/// Returns false on error or if the buffer is too small.
+///
+/// # Safety
+///
+/// `result` must be either null or a live handle from
+/// [`aicred_scan_ex`](crate::aicred_scan_ex). `buf` must be either null or
+/// valid for `buf_len` bytes of writes, and `out_needed` either null or valid
+/// for writes.
+#[no_mangle]
+pub extern "C" fn aicred_result_json_into(
+    result: *const aicred_result_t,
+    buf: *mut libc::c_char,
+    buf_len: usize,
+    out_needed: *mut usize,
+) -> bool {
+    unsafe { into_buffer(|| result::result_json(result), buf, buf_len, out_needed) }
+}
+
+/// Write the JSON of a finished job into `buf`, as
+/// [`aicred_scan_result`](crate::aicred_scan_result) returns it
+///
+/// Returns false with the error of the scan set if it failed, or if the
+/// buffer is too small.
+///
+/// # Safety
+///
+/// `job` must be either null or a live handle from
+/// [`aicred_scan_start`](crate::aicred_scan_start). `buf` must be either null
+/// or valid for `buf_len` bytes of writes, and `out_needed` either null or
+/// valid for writes.
+#[no_mangle]
+pub extern "C" fn aicred_scan_result_into(
+    job: *const aicred_scan_job_t,
+    buf: *mut libc::c_char,
+    buf_len: usize,
+    out_needed: *mut usize,
+) -> bool {
+    unsafe { into_buffer(|| job::result_json(job), buf, buf_len, out_needed) }
+}
+
+/// Write the last error message into `buf`, an empty string if there is none
+///
+/// Unlike the other `*_into` functions, this keeps the last error, so a too
+/// small buffer can be retried. Returns false if the buffer is too small.
+///
+/// # Safety
+///
+/// `buf` must be either null or valid for `buf_len` bytes of writes, and
+/// `out_needed` either null or valid for writes.
+#[no_mangle]
+pub extern "C" fn aicred_last_error_into(
+    buf: *mut libc::c_char,
+    buf_len: usize,
+    out_needed: *mut usize,
+) -> bool {
+    let message = get_last_error().unwrap_or_default();
+    unsafe { write_into(&message, buf, buf_len, out_needed) }.is_ok()
+}
@@ -0,0 +1,69 @@
+//! Cancellation handles for scans
+//!
+//! A handle from [`aicred_cancellation_new`] is passed to
+//! [`aicred_scan_ex`](crate::aicred_scan_ex) or
+//! [`aicred_scan_with_callback`](crate::aicred_scan_with_callback) and may be
+//! cancelled from any thread while the scan runs. The scan then fails with
+//! error code 700.
+
+use aicred_core::CancellationToken;
+
+/// Cancellation handle, freed with [`aicred_cancellation_free`]
+#[allow(non_camel_case_types)]
+pub struct aicred_cancellation_t {
+    token: CancellationToken,
+}
+
+/// The token of a cancellation handle, if any
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from
+/// [`aicred_cancellation_new`].
+pub(crate) unsafe fn token(
+    cancellation: *const aicred_cancellation_t,
+) -> Option<CancellationToken> {
+    cancellation
+        .as_ref()
+        .map(|cancellation| cancellation.token.clone())
+}
+
+/// Create a cancellation handle
+///
+/// Caller must free it with [`aicred_cancellation_free`] once no scan uses it.
+#[no_mangle]
+pub extern "C" fn aicred_cancellation_new() -> *mut aicred_cancellation_t {
+    Box::into_raw(Box::new(aicred_cancellation_t {
+        token: CancellationToken::new(),
+    }))
+}
+
+/// Cancel the scans using a handle
+///
+/// Safe to call from any thread, and more than once.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from
+/// [`aicred_cancellation_new`].
+#[no_mangle]
+pub extern "C" fn aicred_cancellation_cancel(cancellation: *const aicred_cancellation_t) {
+    if let Some(cancellation) = unsafe { cancellation.as_ref() } {
+        cancellation.token.cancel();
+    }
+}
+
+/// Free a cancellation handle
+///
+/// # Safety
+///
+/// The pointer must be either null or a handle returned by
+/// [`aicred_cancellation_new`], not yet freed and no longer used by a scan.
+#[no_mangle]
+pub extern "C" fn aicred_cancellation_free(cancellation: *mut aicred_cancellation_t) {
+    if !cancellation.is_null() {
+        unsafe {
+            drop(Box::from_raw(cancellation));
+        }
+    }
+}
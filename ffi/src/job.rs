@@ -0,0 +1,242 @@
+//! Scans running in the background, driven by polling
+//!
+//! [`aicred_scan_start`] runs the scan on its own thread and returns at once,
+//! so single-threaded hosts such as Node's event loop can poll the job with
+//! [`aicred_scan_poll`] instead of blocking until the scan is done, then take
+//! its result with [`aicred_scan_result`].
+
+use crate::{
+    clear_last_error, safe_execute, scan_options, set_last_error, string_to_c_str, FfiError,
+};
+use aicred_core::{CancellationToken, ScanEvent};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The scan of a job is still running
+pub const AICRED_SCAN_RUNNING: u32 = 0;
+
+/// The scan of a job is done and its result ready
+pub const AICRED_SCAN_DONE: u32 = 1;
+
+/// The scan of a job failed, or was cancelled
+pub const AICRED_SCAN_FAILED: u32 = 2;
+
+/// Where the scan of a job has got to
+enum Outcome {
+    Running,
+    Done(String),
+    Failed(FfiError),
+}
+
+/// State a job shares with its scan thread
+struct Shared {
+    outcome: Outcome,
+    completed: usize,
+    total: usize,
+}
+
+/// Scan job, freed with [`aicred_scan_free`]
+#[allow(non_camel_case_types)]
+pub struct aicred_scan_job_t {
+    shared: Arc<Mutex<Shared>>,
+    cancellation: CancellationToken,
+}
+
+impl aicred_scan_job_t {
+    fn with_shared<T>(&self, f: impl FnOnce(&mut Shared) -> T) -> T {
+        f(&mut self.shared.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// The result of a finished job as JSON, or the error it failed with
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from
+/// [`aicred_scan_start`].
+pub(crate) unsafe fn result_json(job: *const aicred_scan_job_t) -> Result<String, FfiError> {
+    let job = job.as_ref().ok_or_else(|| "Invalid scan job".to_string())?;
+    job.with_shared(|shared| match &shared.outcome {
+        Outcome::Running => Err(FfiError::from("Scan is still running".to_string())),
+        Outcome::Done(json) => Ok(json.clone()),
+        Outcome::Failed(err) => Err(err.clone()),
+    })
+}
+
+/// Start a scan in the background, returning a job handle
+///
+/// Takes the same arguments as [`aicred_scan`](crate::aicred_scan). Invalid
+/// arguments fail at once with NULL; errors of the scan itself are reported by
+/// [`aicred_scan_result`]. The job must be freed with [`aicred_scan_free`].
+///
+/// # Safety
+///
+/// Both strings must be either null or point to valid null-terminated C
+/// strings.
+#[no_mangle]
+pub extern "C" fn aicred_scan_start(
+    home_path: *const libc::c_char,
+    options_json: *const libc::c_char,
+) -> *mut aicred_scan_job_t {
+    clear_last_error();
+
+    let job = safe_execute(|| {
+        let mut options = scan_options(home_path, options_json)?;
+        let cancellation = CancellationToken::new();
+        options.cancellation = Some(cancellation.clone());
+        let shared = Arc::new(Mutex::new(Shared {
+            outcome: Outcome::Running,
+            completed: 0,
+            total: 0,
+        }));
+
+        let thread_shared = Arc::clone(&shared);
+        std::thread::Builder::new()
+            .name("aicred-scan".to_string())
+            .spawn(move || {
+                let lock = || thread_shared.lock().unwrap_or_else(PoisonError::into_inner);
+                let outcome = safe_execute(|| {
+                    let scan_result = aicred_core::scan_with_progress(&options, |event| {
+                        if let ScanEvent::Scanner {
+                            completed, total, ..
+                        } = event
+                        {
+                            let mut shared = lock();
+                            shared.completed = completed;
+                            shared.total = total;
+                        }
+                    })
+                    .map_err(|e| FfiError::core("Scan failed", &e))?;
+                    serde_json::to_string(&scan_result)
+                        .map_err(|e| FfiError::serialization("Failed to serialize result", &e))
+                });
+
+                let mut shared = lock();
+                shared.completed = shared.total;
+                shared.outcome = match outcome {
+                    Ok(json) => Outcome::Done(json),
+                    Err(err) => Outcome::Failed(err),
+                };
+            })
+            .map_err(|e| format!("Failed to start scan: {}", e))?;
+
+        Ok(aicred_scan_job_t {
+            shared,
+            cancellation,
+        })
+    });
+
+    match job {
+        Ok(job) => Box::into_raw(Box::new(job)),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Check on a job without blocking
+///
+/// Returns [`AICRED_SCAN_RUNNING`], [`AICRED_SCAN_DONE`] or
+/// [`AICRED_SCAN_FAILED`], which is also returned for a null job.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from
+/// [`aicred_scan_start`].
+#[no_mangle]
+pub extern "C" fn aicred_scan_poll(job: *const aicred_scan_job_t) -> u32 {
+    unsafe { job.as_ref() }.map_or(AICRED_SCAN_FAILED, |job| {
+        job.with_shared(|shared| match shared.outcome {
+            Outcome::Running => AICRED_SCAN_RUNNING,
+            Outcome::Done(_) => AICRED_SCAN_DONE,
+            Outcome::Failed(_) => AICRED_SCAN_FAILED,
+        })
+    })
+}
+
+/// Progress of a job: scanner runs finished and in all
+///
+/// Either pointer may be null. The total is 0 until the first scanner starts.
+///
+/// # Safety
+///
+/// `job` must be either null or a live handle from [`aicred_scan_start`];
+/// `completed` and `total` must be either null or valid for writes.
+#[no_mangle]
+pub extern "C" fn aicred_scan_progress(
+    job: *const aicred_scan_job_t,
+    completed: *mut usize,
+    total: *mut usize,
+) {
+    let (done, all) = unsafe { job.as_ref() }.map_or((0, 0), |job| {
+        job.with_shared(|shared| (shared.completed, shared.total))
+    });
+    unsafe {
+        if let Some(completed) = completed.as_mut() {
+            *completed = done;
+        }
+        if let Some(total) = total.as_mut() {
+            *total = all;
+        }
+    }
+}
+
+/// The result of a finished job as JSON, as [`aicred_scan`](crate::aicred_scan)
+/// returns it
+///
+/// Returns NULL with the error of the scan set if it failed, and with code 900
+/// if it is still running. Caller must free the returned string with
+/// [`aicred_free`](crate::aicred_free).
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from
+/// [`aicred_scan_start`].
+#[no_mangle]
+pub extern "C" fn aicred_scan_result(job: *const aicred_scan_job_t) -> *mut libc::c_char {
+    clear_last_error();
+
+    let result = safe_execute(|| unsafe { result_json(job) });
+
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Cancel a running job
+///
+/// The job then fails with error code 700. Safe to call from any thread, and
+/// more than once.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from
+/// [`aicred_scan_start`].
+#[no_mangle]
+pub extern "C" fn aicred_scan_cancel(job: *const aicred_scan_job_t) {
+    if let Some(job) = unsafe { job.as_ref() } {
+        job.cancellation.cancel();
+    }
+}
+
+/// Free a job
+///
+/// A job still running is cancelled, and its thread exits on its own.
+///
+/// # Safety
+///
+/// The pointer must be either null or a handle returned by
+/// [`aicred_scan_start`] and not yet freed.
+#[no_mangle]
+pub extern "C" fn aicred_scan_free(job: *mut aicred_scan_job_t) {
+    if !job.is_null() {
+        unsafe {
+            let job = Box::from_raw(job);
+            job.cancellation.cancel();
+        }
+    }
+}
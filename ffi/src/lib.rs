@@ -13,6 +13,12 @@
 //! - Strings returned by functions must be freed by the caller using [`aicred_free`]
 //! - The library uses thread-local storage for error messages
 //! - All functions are panic-safe using `std::panic::catch_unwind`
+//!
+//! # Error Codes
+//!
+//! After a failed call, [`aicred_last_error_code`] returns the stable code of
+//! the error: the code of the core library's error (see
+//! `aicred_core::error`), or one of the `AICRED_ERROR_*` codes of this layer.
 
 // Allow clippy lints for the FFI crate
 #![allow(unused_doc_comments)]
@@ -26,11 +32,31 @@ use std::cell::RefCell;
 use std::ffi::{CStr, CString};
 use std::path::PathBuf;
 
+mod abi;
+mod buffer;
+mod cancel;
+mod job;
+mod progress;
+mod result;
+mod store;
+pub use abi::*;
+pub use buffer::*;
+pub use cancel::*;
+pub use job::*;
+pub use progress::*;
+pub use result::*;
+pub use store::*;
+
 /// Thread-local storage for the last error message
 thread_local! {
     static LAST_ERROR: RefCell<Option<String>> = RefCell::new(None);
 }
 
+/// Thread-local storage for the code of the last error
+thread_local! {
+    static LAST_ERROR_CODE: std::cell::Cell<u32> = std::cell::Cell::new(AICRED_ERROR_NONE);
+}
+
 /// Thread-local storage for error buffer (used by aicred_last_error)
 thread_local! {
     static ERROR_BUFFER: RefCell<Option<CString>> = RefCell::new(None);
@@ -39,14 +65,65 @@ thread_local! {
 /// Version string for the library
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Sets the last error message
-fn set_last_error(err: String) {
-    LAST_ERROR.with(|e| *e.borrow_mut() = Some(err));
+/// No error occurred
+pub const AICRED_ERROR_NONE: u32 = 0;
+
+/// An argument was null, not UTF-8 or not valid JSON
+pub const AICRED_ERROR_INVALID_ARGUMENT: u32 = 900;
+
+/// A result could not be serialized to JSON
+pub const AICRED_ERROR_SERIALIZATION: u32 = 901;
+
+/// The library panicked
+pub const AICRED_ERROR_PANIC: u32 = 902;
+
+/// A caller-provided buffer was too small for a result
+pub const AICRED_ERROR_BUFFER_TOO_SMALL: u32 = 903;
+
+/// An error returned across the C ABI: its code and message
+#[derive(Clone)]
+struct FfiError {
+    code: u32,
+    message: String,
+}
+
+impl FfiError {
+    /// Wraps an error of the core library, keeping its code
+    fn core(context: &str, e: &aicred_core::error::Error) -> Self {
+        Self {
+            code: e.code(),
+            message: format!("{}: {}", context, e),
+        }
+    }
+
+    /// A serialization failure of a result
+    fn serialization(context: &str, e: &serde_json::Error) -> Self {
+        Self {
+            code: AICRED_ERROR_SERIALIZATION,
+            message: format!("{}: {}", context, e),
+        }
+    }
 }
 
-/// Clears the last error message
+impl From<String> for FfiError {
+    fn from(message: String) -> Self {
+        Self {
+            code: AICRED_ERROR_INVALID_ARGUMENT,
+            message,
+        }
+    }
+}
+
+/// Sets the last error message and code
+fn set_last_error(err: FfiError) {
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(err.message));
+    LAST_ERROR_CODE.with(|code| code.set(err.code));
+}
+
+/// Clears the last error message and code
 fn clear_last_error() {
     LAST_ERROR.with(|e| *e.borrow_mut() = None);
+    LAST_ERROR_CODE.with(|code| code.set(AICRED_ERROR_NONE));
 }
 
 /// Gets the last error message
@@ -76,16 +153,89 @@ fn string_to_c_str(s: String) -> *mut libc::c_char {
     }
 }
 
-/// Safely executes a closure, catching any panics and converting them to error strings
-fn safe_execute<T, F>(f: F) -> Result<T, String>
+/// Safely executes a closure, catching any panics and converting them to errors
+fn safe_execute<T, F>(f: F) -> Result<T, FfiError>
 where
-    F: FnOnce() -> Result<T, String>,
+    F: FnOnce() -> Result<T, FfiError>,
 {
     std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f()))
-        .map_err(|_| "Panic occurred during execution".to_string())
+        .map_err(|_| FfiError {
+            code: AICRED_ERROR_PANIC,
+            message: "Panic occurred during execution".to_string(),
+        })
         .and_then(|result| result)
 }
 
+/// Builds the scan options of [`aicred_scan`] and [`aicred_scan_ex`]
+///
+/// # Safety
+///
+/// Both pointers must be either null or point to valid null-terminated C strings.
+fn scan_options(
+    home_path: *const libc::c_char,
+    options_json: *const libc::c_char,
+) -> Result<ScanOptions, FfiError> {
+    // Parse home path
+    let home_path_str =
+        unsafe { c_str_to_string(home_path) }.ok_or_else(|| "Invalid home path".to_string())?;
+
+    // Parse options JSON
+    let options_str = unsafe { c_str_to_string(options_json) }
+        .ok_or_else(|| "Invalid options JSON".to_string())?;
+
+    // Parse JSON options
+    let json_options: serde_json::Value = serde_json::from_str(&options_str)
+        .map_err(|e| format!("Failed to parse options JSON: {}", e))?;
+
+    // Build ScanOptions
+    let mut options = ScanOptions::new();
+
+    // Set home directory
+    options.home_dir = Some(PathBuf::from(home_path_str));
+
+    // Parse other options
+    if let Some(include_full_values) = json_options
+        .get("include_full_values")
+        .and_then(|v| v.as_bool())
+    {
+        options = options.with_full_values(include_full_values);
+    }
+
+    if let Some(redaction) = json_options.get("redaction").and_then(|v| v.as_str()) {
+        options.redaction = redaction.parse()?;
+    }
+
+    if let Some(max_file_size) = json_options.get("max_file_size").and_then(|v| v.as_u64()) {
+        options.max_file_size = max_file_size as usize;
+    }
+
+    if let Some(only_providers) = json_options
+        .get("only_providers")
+        .and_then(|v| v.as_array())
+    {
+        options.only_providers = Some(
+            only_providers
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+        );
+    }
+
+    if let Some(exclude_providers) = json_options
+        .get("exclude_providers")
+        .and_then(|v| v.as_array())
+    {
+        options.exclude_providers = Some(
+            exclude_providers
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+        );
+    }
+
+    Ok(options)
+}
+
 /// Scan for GenAI credentials and configurations
 ///
 /// # Parameters
@@ -99,13 +249,16 @@ where
 /// # Example options_json:
 /// ```json
 /// {
-///   "include_full_values": false,
+///   "redaction": "first8",
 ///   "max_file_size": 1048576,
 ///   "only_providers": ["openai", "anthropic"],
 ///   "exclude_providers": []
 /// }
 /// ```
 ///
+/// `redaction` is one of `none`, `mask`, `last4`, `first8` or `fingerprint`;
+/// the older `"include_full_values": true` is shorthand for `none`.
+///
 /// # Safety
 ///
 /// Both pointers must be either null or point to valid null-terminated C strings.
@@ -116,70 +269,7 @@ pub extern "C" fn aicred_scan(
 ) -> *mut libc::c_char {
     clear_last_error();
 
-    let result = safe_execute(|| {
-        // Parse home path
-        let home_path_str =
-            unsafe { c_str_to_string(home_path) }.ok_or_else(|| "Invalid home path".to_string())?;
-
-        // Parse options JSON
-        let options_str = unsafe { c_str_to_string(options_json) }
-            .ok_or_else(|| "Invalid options JSON".to_string())?;
-
-        // Parse JSON options
-        let json_options: serde_json::Value = serde_json::from_str(&options_str)
-            .map_err(|e| format!("Failed to parse options JSON: {}", e))?;
-
-        // Build ScanOptions
-        let mut options = ScanOptions::new();
-
-        // Set home directory
-        options.home_dir = Some(PathBuf::from(home_path_str));
-
-        // Parse other options
-        if let Some(include_full_values) = json_options
-            .get("include_full_values")
-            .and_then(|v| v.as_bool())
-        {
-            options.include_full_values = include_full_values;
-        }
-
-        if let Some(max_file_size) = json_options.get("max_file_size").and_then(|v| v.as_u64()) {
-            options.max_file_size = max_file_size as usize;
-        }
-
-        if let Some(only_providers) = json_options
-            .get("only_providers")
-            .and_then(|v| v.as_array())
-        {
-            options.only_providers = Some(
-                only_providers
-                    .iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect(),
-            );
-        }
-
-        if let Some(exclude_providers) = json_options
-            .get("exclude_providers")
-            .and_then(|v| v.as_array())
-        {
-            options.exclude_providers = Some(
-                exclude_providers
-                    .iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect(),
-            );
-        }
-
-        // Run the scan
-        let scan_result = scan(&options).map_err(|e| format!("Scan failed: {}", e))?;
-
-        // Serialize result to JSON
-        let json_result = serde_json::to_string(&scan_result)
-            .map_err(|e| format!("Failed to serialize result: {}", e))?;
-
-        Ok(json_result)
-    });
+    let result = safe_execute(|| scan_json(home_path, options_json));
 
     match result {
         Ok(json_string) => string_to_c_str(json_string),
@@ -190,6 +280,25 @@ pub extern "C" fn aicred_scan(
     }
 }
 
+/// Runs the scan of [`aicred_scan`] and serializes its result
+///
+/// # Safety
+///
+/// As for [`scan_options`].
+fn scan_json(
+    home_path: *const libc::c_char,
+    options_json: *const libc::c_char,
+) -> Result<String, FfiError> {
+    let options = scan_options(home_path, options_json)?;
+
+    // Run the scan
+    let scan_result = scan(&options).map_err(|e| FfiError::core("Scan failed", &e))?;
+
+    // Serialize result to JSON
+    serde_json::to_string(&scan_result)
+        .map_err(|e| FfiError::serialization("Failed to serialize result", &e))
+}
+
 /// Free a string returned by aicred_scan
 ///
 /// # Safety
@@ -260,6 +369,43 @@ pub extern "C" fn aicred_last_error() -> *const libc::c_char {
     }
 }
 
+/// Get the code of the last error (thread-local)
+///
+/// Returns `AICRED_ERROR_NONE` (0) if the last call succeeded, the stable code
+/// of the core library's error, or one of the `AICRED_ERROR_*` codes.
+#[no_mangle]
+pub extern "C" fn aicred_last_error_code() -> u32 {
+    LAST_ERROR_CODE.with(|code| code.get())
+}
+
+/// Get a short description of an error code, such as "Not found" for 102
+///
+/// Covers the codes of the core library and the `AICRED_ERROR_*` codes.
+/// Returns a static string that does not need to be freed, or NULL for an
+/// unknown code.
+#[no_mangle]
+pub extern "C" fn aicred_error_message(code: u32) -> *const libc::c_char {
+    static MESSAGES: std::sync::OnceLock<Vec<(u32, CString)>> = std::sync::OnceLock::new();
+
+    MESSAGES
+        .get_or_init(|| {
+            [
+                (AICRED_ERROR_NONE, "No error"),
+                (AICRED_ERROR_INVALID_ARGUMENT, "Invalid argument"),
+                (AICRED_ERROR_SERIALIZATION, "Serialization failed"),
+                (AICRED_ERROR_PANIC, "Library panicked"),
+                (AICRED_ERROR_BUFFER_TOO_SMALL, "Buffer too small"),
+            ]
+            .iter()
+            .chain(aicred_core::error::CODES)
+            .filter_map(|(code, message)| Some((*code, CString::new(*message).ok()?)))
+            .collect()
+        })
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map_or(std::ptr::null(), |(_, message)| message.as_ptr())
+}
+
 /// Get list of available provider plugins
 ///
 /// Returns a JSON array of provider names as a UTF-8 encoded string.
@@ -287,7 +433,7 @@ pub extern "C" fn aicred_list_providers() -> *mut libc::c_char {
 
         // Serialize to JSON
         let json_result = serde_json::to_string(&providers)
-            .map_err(|e| format!("Failed to serialize providers: {}", e))?;
+            .map_err(|e| FfiError::serialization("Failed to serialize providers", &e))?;
 
         Ok(json_result)
     });
@@ -323,14 +469,14 @@ pub extern "C" fn aicred_list_scanners() -> *mut libc::c_char {
         // Create a scanner registry and register built-in scanners
         let registry = aicred_core::scanners::ScannerRegistry::new();
         aicred_core::scanners::register_builtin_scanners(&registry)
-            .map_err(|e| format!("Failed to register scanners: {}", e))?;
+            .map_err(|e| FfiError::core("Failed to register scanners", &e))?;
 
         // Get the list of scanner names
         let scanners = registry.list();
 
         // Serialize to JSON
         let json_result = serde_json::to_string(&scanners)
-            .map_err(|e| format!("Failed to serialize scanners: {}", e))?;
+            .map_err(|e| FfiError::serialization("Failed to serialize scanners", &e))?;
 
         Ok(json_result)
     });
@@ -344,6 +490,227 @@ pub extern "C" fn aicred_list_scanners() -> *mut libc::c_char {
     }
 }
 
+/// List stored provider instances
+///
+/// Returns a JSON object mapping instance IDs to instances, read from the
+/// configuration store in the config directory of `home_path`, such as
+/// `<home_path>/.config/aicred` on Linux.
+/// Caller must free the returned string with [`aicred_free`].
+/// Returns NULL on error.
+///
+/// # Safety
+///
+/// `home_path` must be either null or point to a valid null-terminated C string.
+/// The returned pointer must be freed by the caller using [`aicred_free`].
+#[no_mangle]
+pub extern "C" fn aicred_list_instances(home_path: *const libc::c_char) -> *mut libc::c_char {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let home_path_str =
+            unsafe { c_str_to_string(home_path) }.ok_or_else(|| "Invalid home path".to_string())?;
+
+        let store = aicred_core::ConfigStore::for_home(&PathBuf::from(home_path_str));
+        let instances = store
+            .load_instances()
+            .map_err(|e| FfiError::core("Failed to load instances", &e))?;
+
+        serde_json::to_string(&instances)
+            .map_err(|e| FfiError::serialization("Failed to serialize instances", &e))
+    });
+
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Query the model registry
+///
+/// Returns a JSON array of model registry entries, each with its ID,
+/// provider, name, capabilities, context window, pricing per token and
+/// status. The registry holds the builtin entries, the model data last
+/// fetched by `aicred models refresh` and the overrides in the config
+/// directory of `home_path`, or of the current user if `home_path` is null.
+/// Caller must free the returned string with [`aicred_free`].
+/// Returns NULL on error.
+///
+/// # Example query_json:
+/// ```json
+/// {"provider": "openai", "capabilities": ["vision"], "search": "mini"}
+/// ```
+///
+/// Every field is optional; a null `query_json` returns every entry.
+///
+/// # Safety
+///
+/// Both pointers must be either null or point to valid null-terminated C strings.
+/// The returned pointer must be freed by the caller using [`aicred_free`].
+#[no_mangle]
+pub extern "C" fn aicred_model_registry(
+    home_path: *const libc::c_char,
+    query_json: *const libc::c_char,
+) -> *mut libc::c_char {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let home = unsafe { c_str_to_string(home_path) };
+        if !home_path.is_null() && home.is_none() {
+            return Err("Invalid home path".to_string().into());
+        }
+        let query: aicred_core::ModelQuery = if query_json.is_null() {
+            aicred_core::ModelQuery::default()
+        } else {
+            let query_str = unsafe { c_str_to_string(query_json) }
+                .ok_or_else(|| "Invalid query JSON".to_string())?;
+            serde_json::from_str(&query_str)
+                .map_err(|e| format!("Failed to parse query JSON: {}", e))?
+        };
+
+        let registry = match home {
+            Some(home) => aicred_core::ModelRegistry::for_home(&PathBuf::from(home)),
+            None => aicred_core::ModelRegistry::new(),
+        };
+        serde_json::to_string(&registry.query(&query))
+            .map_err(|e| FfiError::serialization("Failed to serialize models", &e))
+    });
+
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Record token usage in the usage ledger
+///
+/// Appends a usage record to the ledger in the data directory of `home_path`,
+/// or of the current user if `home_path` is null, pricing it from the model
+/// registry unless it holds a cost. Returns the record as stored, as JSON.
+/// Caller must free the returned string with [`aicred_free`].
+/// Returns NULL on error.
+///
+/// # Example record_json:
+/// ```json
+/// {"model": "gpt-4o", "instance_id": "openai-work", "input_tokens": 1200, "output_tokens": 300}
+/// ```
+///
+/// Only `model` is required; `recorded_at` defaults to now.
+///
+/// # Safety
+///
+/// `home_path` must be either null or point to a valid null-terminated C string.
+/// `record_json` must point to a valid null-terminated C string.
+/// The returned pointer must be freed by the caller using [`aicred_free`].
+#[no_mangle]
+pub extern "C" fn aicred_record_usage(
+    home_path: *const libc::c_char,
+    record_json: *const libc::c_char,
+) -> *mut libc::c_char {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let home = unsafe { c_str_to_string(home_path) };
+        if !home_path.is_null() && home.is_none() {
+            return Err("Invalid home path".to_string().into());
+        }
+        let record_str = unsafe { c_str_to_string(record_json) }
+            .ok_or_else(|| "Invalid usage record JSON".to_string())?;
+        let mut record: aicred_core::usage::UsageRecord = serde_json::from_str(&record_str)
+            .map_err(|e| format!("Failed to parse usage record JSON: {}", e))?;
+
+        let (registry, ledger) = match home {
+            Some(home) => {
+                let home = PathBuf::from(home);
+                (
+                    aicred_core::ModelRegistry::for_home(&home),
+                    aicred_core::usage::UsageLedger::for_home(&home),
+                )
+            }
+            None => (
+                aicred_core::ModelRegistry::new(),
+                aicred_core::usage::UsageLedger::default_location()
+                    .map_err(|e| FfiError::core("Failed to find the usage ledger", &e))?,
+            ),
+        };
+        record.price(&registry);
+        ledger
+            .record([&record])
+            .map_err(|e| FfiError::core("Failed to record usage", &e))?;
+
+        serde_json::to_string(&record)
+            .map_err(|e| FfiError::serialization("Failed to serialize usage record", &e))
+    });
+
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Probe a provider for the models an API key can use
+///
+/// Validates a key and lists its models without a scan, through the same
+/// provider plugins as the probes of `aicred scan --probe-models`. `base_url`
+/// replaces the provider's default endpoint if not null. Returns a JSON array
+/// of model metadata. Caller must free the returned string with
+/// [`aicred_free`]. Returns NULL on error, with error code 102 for an unknown
+/// provider.
+///
+/// # Safety
+///
+/// `provider` and `api_key` must point to valid null-terminated C strings.
+/// `base_url` must be either null or point to a valid null-terminated C string.
+/// The returned pointer must be freed by the caller using [`aicred_free`].
+#[no_mangle]
+pub extern "C" fn aicred_probe_models(
+    provider: *const libc::c_char,
+    api_key: *const libc::c_char,
+    base_url: *const libc::c_char,
+) -> *mut libc::c_char {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let provider =
+            unsafe { c_str_to_string(provider) }.ok_or_else(|| "Invalid provider".to_string())?;
+        let api_key =
+            unsafe { c_str_to_string(api_key) }.ok_or_else(|| "Invalid API key".to_string())?;
+        let base_url_str = unsafe { c_str_to_string(base_url) };
+        if !base_url.is_null() && base_url_str.is_none() {
+            return Err("Invalid base URL".to_string().into());
+        }
+
+        let options = ScanOptions::default();
+        let models = aicred_core::probe_models(
+            &provider,
+            &api_key,
+            base_url_str.as_deref(),
+            &options.network,
+            std::time::Duration::from_secs(options.probe_timeout_secs),
+        )
+        .map_err(|e| FfiError::core("Probe failed", &e))?;
+
+        serde_json::to_string(&models)
+            .map_err(|e| FfiError::serialization("Failed to serialize models", &e))
+    });
+
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,6 +767,53 @@ mod tests {
 
             let error = aicred_last_error();
             assert!(!error.is_null());
+            assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+        }
+    }
+
+    #[test]
+    fn test_model_registry_query() {
+        unsafe {
+            let home = tempfile::tempdir().unwrap();
+            let home_path = CString::new(home.path().to_str().unwrap()).unwrap();
+            let query = CString::new(r#"{"provider": "openai", "search": "4o-mini"}"#).unwrap();
+
+            let result = aicred_model_registry(home_path.as_ptr(), query.as_ptr());
+            assert!(!result.is_null());
+            let models: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(result).to_str().unwrap()).unwrap();
+            assert_eq!(models[0]["id"], "gpt-4o-mini");
+            assert!(models[0]["pricing"]["input_cost_per_token"].is_number());
+            aicred_free(result);
+
+            let bad = CString::new("{\"provider\": 1}").unwrap();
+            assert!(aicred_model_registry(home_path.as_ptr(), bad.as_ptr()).is_null());
+            assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+        }
+    }
+
+    #[test]
+    fn test_record_usage() {
+        unsafe {
+            let home = tempfile::tempdir().unwrap();
+            let home_path = CString::new(home.path().to_str().unwrap()).unwrap();
+            let record = CString::new(
+                r#"{"model": "openai/gpt-4o-mini", "instance_id": "work", "input_tokens": 1000000}"#,
+            )
+            .unwrap();
+
+            let result = aicred_record_usage(home_path.as_ptr(), record.as_ptr());
+            assert!(!result.is_null());
+            let stored: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(result).to_str().unwrap()).unwrap();
+            assert!((stored["cost"].as_f64().unwrap() - 0.15).abs() < 1e-9);
+            aicred_free(result);
+            let ledger = aicred_core::usage::UsageLedger::for_home(home.path());
+            assert_eq!(ledger.load().unwrap().len(), 1);
+
+            let bad = CString::new(r#"{"input_tokens": 5}"#).unwrap();
+            assert!(aicred_record_usage(home_path.as_ptr(), bad.as_ptr()).is_null());
+            assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
         }
     }
 
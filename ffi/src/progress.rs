@@ -0,0 +1,168 @@
+//! Scan progress reported to a C callback
+//!
+//! [`aicred_scan_with_callback`] calls back before each scanner runs and for
+//! each new key, so host-language UIs can show a running scan.
+
+use crate::cancel::{self, aicred_cancellation_t};
+use crate::result::c_string;
+use crate::FfiError;
+use crate::{clear_last_error, safe_execute, scan_options, set_last_error, string_to_c_str};
+use aicred_core::models::RedactionMode;
+use aicred_core::ScanEvent;
+use std::ffi::CString;
+
+/// A [`aicred_scan_event_t`] reporting progress
+pub const AICRED_SCAN_EVENT_PROGRESS: u32 = 1;
+
+/// A [`aicred_scan_event_t`] reporting a key
+pub const AICRED_SCAN_EVENT_KEY: u32 = 2;
+
+/// A scanner starting on a scan root
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct aicred_progress_t {
+    /// Name of the scanner
+    pub scanner: *const libc::c_char,
+    /// Scan root being scanned
+    pub root: *const libc::c_char,
+    /// Scanner runs finished so far
+    pub completed: usize,
+    /// Scanner runs of the whole scan
+    pub total: usize,
+}
+
+/// A new key, redacted as the scan options ask
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct aicred_key_event_t {
+    /// Provider of the key, such as `openai`
+    pub provider: *const libc::c_char,
+    /// Type of the value, such as `API Key`
+    pub value_type: *const libc::c_char,
+    /// Value of the key
+    pub value: *const libc::c_char,
+    /// File the key was found in
+    pub source: *const libc::c_char,
+    /// Line of the file the key was found on, 0 if unknown
+    pub line: u64,
+}
+
+/// An event of a running scan
+///
+/// `progress` is set for [`AICRED_SCAN_EVENT_PROGRESS`] and `key` for
+/// [`AICRED_SCAN_EVENT_KEY`], the other is NULL. Both are only valid during
+/// the callback.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct aicred_scan_event_t {
+    /// `AICRED_SCAN_EVENT_PROGRESS` or `AICRED_SCAN_EVENT_KEY`
+    pub kind: u32,
+    /// Progress of the scan
+    pub progress: *const aicred_progress_t,
+    /// Key found
+    pub key: *const aicred_key_event_t,
+}
+
+/// Callback receiving the events of a scan and the `user_data` of
+/// [`aicred_scan_with_callback`]
+#[allow(non_camel_case_types)]
+pub type aicred_scan_callback_t =
+    Option<extern "C" fn(event: *const aicred_scan_event_t, user_data: *mut libc::c_void)>;
+
+/// Passes a scan event to a C callback
+fn report(
+    callback: extern "C" fn(*const aicred_scan_event_t, *mut libc::c_void),
+    user_data: *mut libc::c_void,
+    event: ScanEvent<'_>,
+) {
+    match event {
+        ScanEvent::Scanner {
+            scanner,
+            root,
+            completed,
+            total,
+        } => {
+            let (scanner, root) = (c_string(scanner), c_string(root));
+            let progress = aicred_progress_t {
+                scanner: scanner.as_ptr(),
+                root: root.as_ptr(),
+                completed,
+                total,
+            };
+            let event = aicred_scan_event_t {
+                kind: AICRED_SCAN_EVENT_PROGRESS,
+                progress: &progress,
+                key: std::ptr::null(),
+            };
+            callback(&event, user_data);
+        }
+        ScanEvent::Key(key) => {
+            let strings: [CString; 4] = [
+                c_string(key.provider.as_str()),
+                c_string(key.value_type.to_string()),
+                c_string(key.display_value(RedactionMode::None)),
+                c_string(key.source_file.as_str()),
+            ];
+            let key_event = aicred_key_event_t {
+                provider: strings[0].as_ptr(),
+                value_type: strings[1].as_ptr(),
+                value: strings[2].as_ptr(),
+                source: strings[3].as_ptr(),
+                line: key.source_line.map_or(0, |line| line as u64),
+            };
+            let event = aicred_scan_event_t {
+                kind: AICRED_SCAN_EVENT_KEY,
+                progress: std::ptr::null(),
+                key: &key_event,
+            };
+            callback(&event, user_data);
+        }
+    }
+}
+
+/// Scan for GenAI credentials and configurations, reporting progress
+///
+/// Takes the arguments of [`aicred_scan`](crate::aicred_scan) and returns the
+/// same JSON, but calls `callback` with `user_data` on the calling thread
+/// before each scanner runs and for each new key. A NULL callback scans
+/// without reporting, and a non-NULL `cancellation` handle cancels the scan.
+/// Caller must free the returned string with
+/// [`aicred_free`](crate::aicred_free). Returns NULL on error.
+///
+/// # Safety
+///
+/// Both strings must be either null or point to valid null-terminated C
+/// strings. `user_data` is only passed to `callback`. `cancellation` must be
+/// either null or a live handle from
+/// [`aicred_cancellation_new`](crate::aicred_cancellation_new).
+#[no_mangle]
+pub extern "C" fn aicred_scan_with_callback(
+    home_path: *const libc::c_char,
+    options_json: *const libc::c_char,
+    callback: aicred_scan_callback_t,
+    user_data: *mut libc::c_void,
+    cancellation: *const aicred_cancellation_t,
+) -> *mut libc::c_char {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let mut options = scan_options(home_path, options_json)?;
+        options.cancellation = unsafe { cancel::token(cancellation) };
+        let scan_result = aicred_core::scan_with_progress(&options, |event| {
+            if let Some(callback) = callback {
+                report(callback, user_data, event);
+            }
+        })
+        .map_err(|e| FfiError::core("Scan failed", &e))?;
+        serde_json::to_string(&scan_result)
+            .map_err(|e| FfiError::serialization("Failed to serialize result", &e))
+    });
+
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
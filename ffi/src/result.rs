@@ -0,0 +1,357 @@
+//! Handle-based access to scan results
+//!
+//! [`aicred_scan_ex`] returns an opaque [`aicred_result_t`] instead of JSON.
+//! Its accessors return numbers, or strings owned by the handle that stay
+//! valid until [`aicred_result_free`], so C and Go callers neither parse JSON
+//! nor free each string.
+
+use crate::cancel::{self, aicred_cancellation_t};
+use crate::{
+    clear_last_error, safe_execute, scan_options, set_last_error, string_to_c_str, FfiError,
+};
+use aicred_core::models::{DiscoveredCredential, RedactionMode, ScanResult};
+use std::ffi::CString;
+
+/// A discovered key, its strings ready for C
+struct KeyEntry {
+    provider: CString,
+    value: CString,
+    value_type: CString,
+    source: CString,
+    line: u64,
+    hash: CString,
+    risk: CString,
+}
+
+/// A discovered application configuration, its strings ready for C
+struct ConfigEntry {
+    app_name: CString,
+    path: CString,
+    key_count: usize,
+}
+
+/// Result of [`aicred_scan_ex`], freed with [`aicred_result_free`]
+#[allow(non_camel_case_types)]
+pub struct aicred_result_t {
+    result: ScanResult,
+    keys: Vec<KeyEntry>,
+    configs: Vec<ConfigEntry>,
+}
+
+/// Converts a string to a C string, dropping interior NUL bytes
+pub(crate) fn c_string(value: impl Into<String>) -> CString {
+    let mut value = value.into();
+    value.retain(|c| c != '\0');
+    CString::new(value).unwrap_or_default()
+}
+
+impl KeyEntry {
+    fn new(key: &DiscoveredCredential) -> Self {
+        Self {
+            provider: c_string(key.provider.as_str()),
+            value: c_string(key.display_value(RedactionMode::None)),
+            value_type: c_string(key.value_type.to_string()),
+            source: c_string(key.source_file.as_str()),
+            line: key.source_line.map_or(0, |line| line as u64),
+            hash: c_string(key.hash.as_str()),
+            risk: c_string(key.risk.to_string()),
+        }
+    }
+}
+
+impl aicred_result_t {
+    fn new(result: ScanResult) -> Self {
+        Self {
+            keys: result.keys.iter().map(KeyEntry::new).collect(),
+            configs: result
+                .config_instances
+                .iter()
+                .map(|config| ConfigEntry {
+                    app_name: c_string(config.app_name.as_str()),
+                    path: c_string(config.config_path.to_string_lossy()),
+                    key_count: config.keys.len(),
+                })
+                .collect(),
+            result,
+        }
+    }
+}
+
+/// Borrows a result handle
+///
+/// # Safety
+///
+/// The pointer must be either null or a handle returned by [`aicred_scan_ex`]
+/// and not yet freed.
+unsafe fn handle<'a>(result: *const aicred_result_t) -> Option<&'a aicred_result_t> {
+    result.as_ref()
+}
+
+/// Gets a key of a result by index
+///
+/// # Safety
+///
+/// As for [`handle`].
+unsafe fn key<'a>(result: *const aicred_result_t, index: usize) -> Option<&'a KeyEntry> {
+    handle(result).and_then(|result| result.keys.get(index))
+}
+
+/// Gets a configuration of a result by index
+///
+/// # Safety
+///
+/// As for [`handle`].
+unsafe fn config<'a>(result: *const aicred_result_t, index: usize) -> Option<&'a ConfigEntry> {
+    handle(result).and_then(|result| result.configs.get(index))
+}
+
+/// Serializes the result of a handle
+///
+/// # Safety
+///
+/// As for [`handle`].
+pub(crate) unsafe fn result_json(result: *const aicred_result_t) -> Result<String, FfiError> {
+    let result = handle(result).ok_or_else(|| "Invalid result handle".to_string())?;
+    serde_json::to_string(&result.result)
+        .map_err(|e| FfiError::serialization("Failed to serialize result", &e))
+}
+
+/// Scan for GenAI credentials and configurations, returning a result handle
+///
+/// Takes the same arguments as [`aicred_scan`](crate::aicred_scan), and
+/// optionally a handle to cancel the scan with. The result is read with the
+/// `aicred_result_*` functions and must be freed with [`aicred_result_free`].
+/// Returns NULL on error.
+///
+/// # Safety
+///
+/// Both strings must be either null or point to valid null-terminated C
+/// strings. `cancellation` must be either null or a live handle from
+/// [`aicred_cancellation_new`](crate::aicred_cancellation_new).
+#[no_mangle]
+pub extern "C" fn aicred_scan_ex(
+    home_path: *const libc::c_char,
+    options_json: *const libc::c_char,
+    cancellation: *const aicred_cancellation_t,
+) -> *mut aicred_result_t {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let mut options = scan_options(home_path, options_json)?;
+        options.cancellation = unsafe { cancel::token(cancellation) };
+        let scan_result =
+            aicred_core::scan(&options).map_err(|e| FfiError::core("Scan failed", &e))?;
+        Ok(aicred_result_t::new(scan_result))
+    });
+
+    match result {
+        Ok(result) => Box::into_raw(Box::new(result)),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Free a result returned by [`aicred_scan_ex`]
+///
+/// Strings returned by the accessors of the result are freed with it.
+///
+/// # Safety
+///
+/// The pointer must be either null or a handle returned by [`aicred_scan_ex`]
+/// and not yet freed.
+#[no_mangle]
+pub extern "C" fn aicred_result_free(result: *mut aicred_result_t) {
+    if !result.is_null() {
+        unsafe {
+            drop(Box::from_raw(result));
+        }
+    }
+}
+
+/// Number of keys found, 0 for a null result
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_count(result: *const aicred_result_t) -> usize {
+    unsafe { handle(result) }.map_or(0, |result| result.keys.len())
+}
+
+/// Provider of key `index`, such as `openai`
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_provider(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { key(result, index) }.map_or(std::ptr::null(), |key| key.provider.as_ptr())
+}
+
+/// Value of key `index`, redacted as the scan options asked
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_value(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { key(result, index) }.map_or(std::ptr::null(), |key| key.value.as_ptr())
+}
+
+/// Type of key `index`, such as `API Key`
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_type(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { key(result, index) }.map_or(std::ptr::null(), |key| key.value_type.as_ptr())
+}
+
+/// File key `index` was found in
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_source(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { key(result, index) }.map_or(std::ptr::null(), |key| key.source.as_ptr())
+}
+
+/// Line of its source file key `index` was found on, 0 if unknown
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_line(result: *const aicred_result_t, index: usize) -> u64 {
+    unsafe { key(result, index) }.map_or(0, |key| key.line)
+}
+
+/// Fingerprint of key `index`
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_hash(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { key(result, index) }.map_or(std::ptr::null(), |key| key.hash.as_ptr())
+}
+
+/// Risk level of key `index`: `Low`, `Medium`, `High` or `Critical`
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_key_risk(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { key(result, index) }.map_or(std::ptr::null(), |key| key.risk.as_ptr())
+}
+
+/// Number of application configurations found, 0 for a null result
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_config_count(result: *const aicred_result_t) -> usize {
+    unsafe { handle(result) }.map_or(0, |result| result.configs.len())
+}
+
+/// Application of configuration `index`, such as `roo-code`
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_config_app(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { config(result, index) }.map_or(std::ptr::null(), |config| config.app_name.as_ptr())
+}
+
+/// Path of configuration `index`
+///
+/// Returns NULL if `index` is out of range. The string is owned by the result.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_config_path(
+    result: *const aicred_result_t,
+    index: usize,
+) -> *const libc::c_char {
+    unsafe { config(result, index) }.map_or(std::ptr::null(), |config| config.path.as_ptr())
+}
+
+/// Number of keys in configuration `index`, 0 if out of range
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_config_key_count(
+    result: *const aicred_result_t,
+    index: usize,
+) -> usize {
+    unsafe { config(result, index) }.map_or(0, |config| config.key_count)
+}
+
+/// The whole result as JSON, as [`aicred_scan`](crate::aicred_scan) returns it
+///
+/// For the fields the accessors don't cover. Caller must free the returned
+/// string with [`aicred_free`](crate::aicred_free). Returns NULL on error.
+///
+/// # Safety
+///
+/// The pointer must be either null or a live handle from [`aicred_scan_ex`].
+#[no_mangle]
+pub extern "C" fn aicred_result_json(result: *const aicred_result_t) -> *mut libc::c_char {
+    clear_last_error();
+
+    let json = safe_execute(|| unsafe { result_json(result) });
+
+    match json {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Managing the configuration store
+//!
+//! These functions read and write the same configuration store as the CLI:
+//! the one in the config directory of `home_path`, such as
+//! `<home_path>/.config/aicred` on Linux, or of the current user if
+//! `home_path` is null.
+
+use crate::FfiError;
+use crate::{c_str_to_string, clear_last_error, safe_execute, set_last_error, string_to_c_str};
+use aicred_core::models::ProviderInstance;
+use aicred_core::{ConfigStore, InstanceSelector, SecretBackends};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Instance given to [`aicred_instance_add`]
+#[derive(Deserialize)]
+struct NewInstance {
+    id: String,
+    provider_type: String,
+    base_url: String,
+    #[serde(default)]
+    api_key: String,
+    #[serde(default)]
+    models: Vec<String>,
+    #[serde(default = "active_by_default")]
+    active: bool,
+}
+
+const fn active_by_default() -> bool {
+    true
+}
+
+impl From<NewInstance> for ProviderInstance {
+    fn from(new: NewInstance) -> Self {
+        let mut instance = Self::new(
+            new.id,
+            new.provider_type,
+            new.base_url,
+            new.api_key,
+            new.models,
+        );
+        instance.active = new.active;
+        instance
+    }
+}
+
+/// Opens the configuration store of `home_path`, or of the current user if null
+///
+/// # Safety
+///
+/// `home_path` must be either null or point to a valid null-terminated C string.
+unsafe fn config_store(home_path: *const libc::c_char) -> Result<ConfigStore, FfiError> {
+    if home_path.is_null() {
+        return ConfigStore::default_location()
+            .map_err(|e| FfiError::core("Failed to find the configuration store", &e));
+    }
+    let home = c_str_to_string(home_path).ok_or_else(|| "Invalid home path".to_string())?;
+    Ok(ConfigStore::for_home(Path::new(&home)))
+}
+
+/// Returns a JSON result as a C string, or NULL after setting the last error
+fn json_result(result: Result<String, FfiError>) -> *mut libc::c_char {
+    match result {
+        Ok(json_string) => string_to_c_str(json_string),
+        Err(err) => {
+            set_last_error(err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Add a provider instance to the configuration store
+///
+/// The instance is validated and refused if an instance with its ID is
+/// already stored. Its API key is stored as given. Returns the stored instance
+/// as JSON. Caller must free the returned string with
+/// [`aicred_free`](crate::aicred_free). Returns NULL on error.
+///
+/// # Example instance_json:
+/// ```json
+/// {"id": "openai-work", "provider_type": "openai", "base_url": "https://api.openai.com/v1",
+///  "api_key": "sk-...", "models": ["gpt-4o"]}
+/// ```
+///
+/// `api_key` and `models` are optional, and `active` defaults to true.
+///
+/// # Safety
+///
+/// `home_path` must be either null or point to a valid null-terminated C string.
+/// `instance_json` must point to a valid null-terminated C string.
+#[no_mangle]
+pub extern "C" fn aicred_instance_add(
+    home_path: *const libc::c_char,
+    instance_json: *const libc::c_char,
+) -> *mut libc::c_char {
+    clear_last_error();
+
+    json_result(safe_execute(|| {
+        let store = unsafe { config_store(home_path) }?;
+        let instance_str = unsafe { c_str_to_string(instance_json) }
+            .ok_or_else(|| "Invalid instance JSON".to_string())?;
+        let instance: ProviderInstance = serde_json::from_str::<NewInstance>(&instance_str)
+            .map_err(|e| format!("Failed to parse instance JSON: {}", e))?
+            .into();
+        instance
+            .validate()
+            .map_err(|e| format!("Invalid instance configuration: {}", e))?;
+
+        let lock = store
+            .lock()
+            .map_err(|e| FfiError::core("Failed to lock the configuration store", &e))?;
+        let existing = store
+            .find_instance(&instance.id)
+            .map_err(|e| FfiError::core("Failed to load instances", &e))?;
+        if existing.is_some() {
+            return Err(
+                format!("Provider instance with ID '{}' already exists", instance.id).into(),
+            );
+        }
+        lock.save_instance(&instance)
+            .map_err(|e| FfiError::core("Failed to save instance", &e))?;
+
+        serde_json::to_string(&instance)
+            .map_err(|e| FfiError::serialization("Failed to serialize instance", &e))
+    }))
+}
+
+/// Remove a provider instance from the configuration store
+///
+/// Like `aicred instances remove`, this keeps the file as a backup, drops the
+/// label and tag assignments pointing at the instance and deletes the secret
+/// its key references if it can. Returns false on error, with error code 102
+/// if no instance has the ID.
+///
+/// # Safety
+///
+/// `home_path` must be either null or point to a valid null-terminated C string.
+/// `id` must point to a valid null-terminated C string.
+#[no_mangle]
+pub extern "C" fn aicred_instance_remove(
+    home_path: *const libc::c_char,
+    id: *const libc::c_char,
+) -> bool {
+    clear_last_error();
+
+    let result = safe_execute(|| {
+        let store = unsafe { config_store(home_path) }?;
+        let id = unsafe { c_str_to_string(id) }.ok_or_else(|| "Invalid instance ID".to_string())?;
+        let removed = aicred_core::bulk::remove(&store, &InstanceSelector::default().with_id(&id))
+            .map_err(|e| FfiError::core("Failed to remove instance", &e))?;
+        if removed.is_empty() {
+            let e = aicred_core::Error::NotFound(format!("Provider instance '{}'", id));
+            return Err(FfiError::core("Failed to remove instance", &e));
+        }
+
+        // The instances are gone either way; a secret that can't be deleted
+        // is only left behind
+        let backends = SecretBackends::builtin();
+        for instance in &removed {
+            let _ = backends.delete(&instance.api_key);
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => true,
+        Err(err) => {
+            set_last_error(err);
+            false
+        }
+    }
+}
+
+/// Resolve a label to the instance and model it points at
+///
+/// Follows the label's targets and fallbacks like `aicred resolve`, returning
+/// the label, the instance with its API key revealed, the model and the
+/// reasons any targets were passed over, as JSON. Caller must free the
+/// returned string with [`aicred_free`](crate::aicred_free). Returns NULL on
+/// error.
+///
+/// # Safety
+///
+/// `home_path` must be either null or point to a valid null-terminated C string.
+/// `name` must point to a valid null-terminated C string.
+#[no_mangle]
+pub extern "C" fn aicred_label_resolve(
+    home_path: *const libc::c_char,
+    name: *const libc::c_char,
+) -> *mut libc::c_char {
+    clear_last_error();
+
+    json_result(safe_execute(|| {
+        let store = unsafe { config_store(home_path) }?;
+        let name = unsafe { c_str_to_string(name) }.ok_or_else(|| "Invalid label".to_string())?;
+        let resolved = aicred_core::resolve::resolve_label(&store, &name)
+            .map_err(|e| FfiError::core("Failed to resolve label", &e))?;
+        serde_json::to_string(&resolved)
+            .map_err(|e| FfiError::serialization("Failed to serialize resolved label", &e))
+    }))
+}
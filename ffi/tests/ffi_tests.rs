@@ -324,3 +324,427 @@ fn test_concurrent_scans_10() {
         h.join().unwrap();
     }
 }
+
+#[test]
+fn test_list_instances() {
+    let temp_home = tempfile::tempdir().unwrap();
+    let store = aicred_core::ConfigStore::for_home(temp_home.path());
+    let mut instances = aicred_core::models::ProviderCollection::new();
+    instances.add(
+        "abcd1234".to_string(),
+        aicred_core::models::ProviderInstance::new(
+            "abcd1234".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+            "sk-test1234567890".to_string(),
+            Vec::new(),
+        ),
+    );
+    store.save_instances(&instances).unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let result = aicred_list_instances(home.as_ptr());
+        assert!(!result.is_null());
+
+        let json = CStr::from_ptr(result).to_str().unwrap();
+        let instances: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(instances["abcd1234"]["provider_type"], "openai");
+        aicred_free(result);
+
+        assert!(aicred_list_instances(ptr::null()).is_null());
+    }
+}
+
+#[test]
+fn test_scan_rejects_unknown_redaction_mode() {
+    unsafe {
+        let home = CString::new("/tmp/test").unwrap();
+        let options = CString::new(r#"{"redaction": "partial"}"#).unwrap();
+
+        let result = aicred_scan(home.as_ptr(), options.as_ptr());
+        assert!(result.is_null());
+
+        let error = CStr::from_ptr(aicred_last_error()).to_str().unwrap();
+        assert!(error.contains("Invalid redaction mode"));
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+
+        let providers = aicred_list_providers();
+        assert!(!providers.is_null());
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_NONE);
+        aicred_free(providers);
+
+        let message = CStr::from_ptr(aicred_error_message(AICRED_ERROR_INVALID_ARGUMENT));
+        assert_eq!(message.to_str().unwrap(), "Invalid argument");
+        let message = CStr::from_ptr(aicred_error_message(102));
+        assert_eq!(message.to_str().unwrap(), "Not found");
+        assert!(aicred_error_message(42).is_null());
+    }
+}
+
+#[test]
+fn test_scan_ex_result_accessors() {
+    let temp_home = tempfile::tempdir().unwrap();
+    std::fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let options = CString::new("{}").unwrap();
+        let result = aicred_scan_ex(home.as_ptr(), options.as_ptr(), ptr::null());
+        assert!(!result.is_null());
+
+        let count = aicred_result_key_count(result);
+        let index = (0..count)
+            .find(|&i| {
+                CStr::from_ptr(aicred_result_key_provider(result, i)).to_bytes() == b"anthropic"
+            })
+            .expect("anthropic key found");
+        let value = CStr::from_ptr(aicred_result_key_value(result, index));
+        assert_eq!(value.to_str().unwrap(), "sk-ant-1****");
+        let source = CStr::from_ptr(aicred_result_key_source(result, index));
+        assert!(source.to_str().unwrap().ends_with(".env"));
+        assert_eq!(aicred_result_key_line(result, index), 1);
+        assert!(aicred_result_key_provider(result, count).is_null());
+
+        let json = aicred_result_json(result);
+        assert!(CStr::from_ptr(json).to_str().unwrap().contains("anthropic"));
+        aicred_free(json);
+        aicred_result_free(result);
+
+        assert_eq!(aicred_result_key_count(ptr::null()), 0);
+        aicred_result_free(ptr::null_mut());
+        assert!(aicred_scan_ex(ptr::null(), options.as_ptr(), ptr::null()).is_null());
+    }
+}
+
+#[derive(Default)]
+struct ScanEvents {
+    progress: usize,
+    total: usize,
+    providers: Vec<String>,
+}
+
+extern "C" fn collect_scan_event(event: *const aicred_scan_event_t, user_data: *mut libc::c_void) {
+    unsafe {
+        let events = &mut *(user_data as *mut ScanEvents);
+        let event = &*event;
+        match event.kind {
+            AICRED_SCAN_EVENT_PROGRESS => {
+                let progress = &*event.progress;
+                assert!(event.key.is_null());
+                assert_eq!(progress.completed, events.progress);
+                events.progress += 1;
+                events.total = progress.total;
+            }
+            AICRED_SCAN_EVENT_KEY => {
+                let key = &*event.key;
+                let value = CStr::from_ptr(key.value).to_str().unwrap();
+                assert!(!value.contains("567890abcdef"));
+                let provider = CStr::from_ptr(key.provider).to_str().unwrap();
+                events.providers.push(provider.to_string());
+            }
+            kind => panic!("unexpected event kind {}", kind),
+        }
+    }
+}
+
+#[test]
+fn test_scan_with_callback_reports_progress_and_keys() {
+    let temp_home = tempfile::tempdir().unwrap();
+    std::fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let options = CString::new("{}").unwrap();
+        let mut events = ScanEvents::default();
+        let result = aicred_scan_with_callback(
+            home.as_ptr(),
+            options.as_ptr(),
+            Some(collect_scan_event),
+            &mut events as *mut ScanEvents as *mut libc::c_void,
+            ptr::null(),
+        );
+        assert!(!result.is_null());
+        aicred_free(result);
+
+        assert!(events.progress > 0);
+        assert_eq!(events.progress, events.total);
+        assert!(events
+            .providers
+            .iter()
+            .any(|provider| provider == "anthropic"));
+
+        let result = aicred_scan_with_callback(
+            home.as_ptr(),
+            options.as_ptr(),
+            None,
+            ptr::null_mut(),
+            ptr::null(),
+        );
+        assert!(!result.is_null());
+        aicred_free(result);
+    }
+}
+
+extern "C" fn cancel_on_first_event(
+    _event: *const aicred_scan_event_t,
+    user_data: *mut libc::c_void,
+) {
+    aicred_cancellation_cancel(user_data as *const aicred_cancellation_t);
+}
+
+#[test]
+fn test_cancelled_scan_fails_with_cancelled_code() {
+    let temp_home = tempfile::tempdir().unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let options = CString::new("{}").unwrap();
+        let cancellation = aicred_cancellation_new();
+
+        let result = aicred_scan_with_callback(
+            home.as_ptr(),
+            options.as_ptr(),
+            Some(cancel_on_first_event),
+            cancellation as *mut libc::c_void,
+            cancellation,
+        );
+        assert!(result.is_null());
+        assert_eq!(aicred_last_error_code(), 700);
+
+        let result = aicred_scan_ex(home.as_ptr(), options.as_ptr(), cancellation);
+        assert!(result.is_null());
+        assert_eq!(aicred_last_error_code(), 700);
+        aicred_cancellation_free(cancellation);
+
+        aicred_cancellation_cancel(ptr::null());
+        aicred_cancellation_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_scan_job_is_polled_to_completion() {
+    let temp_home = tempfile::tempdir().unwrap();
+    std::fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let options = CString::new("{}").unwrap();
+        let job = aicred_scan_start(home.as_ptr(), options.as_ptr());
+        assert!(!job.is_null());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(60);
+        while aicred_scan_poll(job) == AICRED_SCAN_RUNNING {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "scan job never finished"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(aicred_scan_poll(job), AICRED_SCAN_DONE);
+
+        let (mut completed, mut total) = (0, 0);
+        aicred_scan_progress(job, &mut completed, &mut total);
+        assert!(total > 0);
+        assert_eq!(completed, total);
+
+        let result = aicred_scan_result(job);
+        assert!(!result.is_null());
+        let json = CStr::from_ptr(result).to_str().unwrap();
+        assert!(json.contains("anthropic"));
+        aicred_free(result);
+        aicred_scan_free(job);
+
+        let invalid = CString::new("{invalid").unwrap();
+        assert!(aicred_scan_start(home.as_ptr(), invalid.as_ptr()).is_null());
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+
+        assert_eq!(aicred_scan_poll(ptr::null()), AICRED_SCAN_FAILED);
+        assert!(aicred_scan_result(ptr::null()).is_null());
+        aicred_scan_progress(ptr::null(), ptr::null_mut(), ptr::null_mut());
+        aicred_scan_cancel(ptr::null());
+        aicred_scan_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn test_results_written_into_caller_buffers() {
+    let temp_home = tempfile::tempdir().unwrap();
+    std::fs::write(
+        temp_home.path().join(".env"),
+        "ANTHROPIC_API_KEY=sk-ant-REDACTED\n",
+    )
+    .unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let options = CString::new("{}").unwrap();
+        let mut needed = 0;
+        assert!(!aicred_scan_into(
+            home.as_ptr(),
+            options.as_ptr(),
+            ptr::null_mut(),
+            0,
+            &mut needed
+        ));
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_BUFFER_TOO_SMALL);
+        assert!(needed > 1);
+
+        let mut buf = vec![0 as libc::c_char; needed];
+        assert!(aicred_scan_into(
+            home.as_ptr(),
+            options.as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut needed
+        ));
+        assert_eq!(needed, buf.len());
+        let json = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+        assert!(json.contains("anthropic"));
+
+        let result = aicred_scan_ex(home.as_ptr(), options.as_ptr(), ptr::null());
+        let mut small = [0 as libc::c_char; 4];
+        assert!(!aicred_result_json_into(
+            result,
+            small.as_mut_ptr(),
+            small.len(),
+            &mut needed
+        ));
+        let mut buf = vec![0 as libc::c_char; needed];
+        assert!(aicred_result_json_into(
+            result,
+            buf.as_mut_ptr(),
+            buf.len(),
+            ptr::null_mut()
+        ));
+        assert!(CStr::from_ptr(buf.as_ptr())
+            .to_str()
+            .unwrap()
+            .contains("anthropic"));
+        aicred_result_free(result);
+
+        assert!(!aicred_scan_result_into(
+            ptr::null(),
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut needed
+        ));
+        assert_eq!(needed, 0);
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+        assert!(!aicred_last_error_into(ptr::null_mut(), 0, &mut needed));
+        let mut message = vec![0 as libc::c_char; needed];
+        assert!(aicred_last_error_into(
+            message.as_mut_ptr(),
+            message.len(),
+            ptr::null_mut()
+        ));
+        assert_eq!(
+            CStr::from_ptr(message.as_ptr()).to_str().unwrap(),
+            "Invalid scan job"
+        );
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+    }
+}
+
+#[test]
+fn test_manage_instances_and_resolve_label() {
+    let temp_home = tempfile::tempdir().unwrap();
+
+    unsafe {
+        let home = CString::new(temp_home.path().to_str().unwrap()).unwrap();
+        let instance = CString::new(
+            r#"{"id": "openai-work", "provider_type": "openai",
+                "base_url": "https://api.openai.com/v1", "api_key": "sk-test1234567890",
+                "models": ["gpt-4o-mini"]}"#,
+        )
+        .unwrap();
+        let added = aicred_instance_add(home.as_ptr(), instance.as_ptr());
+        assert!(
+            !added.is_null(),
+            "{:?}",
+            CStr::from_ptr(aicred_last_error())
+        );
+        aicred_free(added);
+
+        // Adding it twice is refused
+        assert!(aicred_instance_add(home.as_ptr(), instance.as_ptr()).is_null());
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+
+        let store = aicred_core::ConfigStore::for_home(temp_home.path());
+        store
+            .lock()
+            .unwrap()
+            .write(
+                "labels.yaml",
+                "- label_name: fast\n  target:\n    type: provider_model\n    instance_id: openai-work\n    model_id: gpt-4o-mini\n  assigned_at: 2026-01-01T00:00:00Z\n",
+            )
+            .unwrap();
+
+        let label = CString::new("fast").unwrap();
+        let resolved = aicred_label_resolve(home.as_ptr(), label.as_ptr());
+        assert!(
+            !resolved.is_null(),
+            "{:?}",
+            CStr::from_ptr(aicred_last_error())
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(CStr::from_ptr(resolved).to_str().unwrap()).unwrap();
+        assert_eq!(json["instance"]["id"], "openai-work");
+        assert_eq!(json["instance"]["api_key"], "sk-test1234567890");
+        assert_eq!(json["model"]["id"], "gpt-4o-mini");
+        aicred_free(resolved);
+
+        let id = CString::new("openai-work").unwrap();
+        assert!(aicred_instance_remove(home.as_ptr(), id.as_ptr()));
+        assert!(!aicred_instance_remove(home.as_ptr(), id.as_ptr()));
+        assert_eq!(aicred_last_error_code(), 102);
+
+        let instances = aicred_list_instances(home.as_ptr());
+        assert_eq!(CStr::from_ptr(instances).to_str().unwrap(), "{}");
+        aicred_free(instances);
+        assert!(aicred_label_resolve(home.as_ptr(), label.as_ptr()).is_null());
+    }
+}
+
+#[test]
+fn test_abi_version_compatibility() {
+    let version = aicred_abi_version();
+    assert_eq!(
+        (version.major, version.minor, version.patch),
+        (
+            AICRED_ABI_VERSION_MAJOR,
+            AICRED_ABI_VERSION_MINOR,
+            AICRED_ABI_VERSION_PATCH
+        )
+    );
+    assert!(aicred_abi_compatible(version.major, version.minor));
+    assert!(aicred_abi_compatible(version.major, 0));
+    assert!(!aicred_abi_compatible(version.major, version.minor + 1));
+    assert!(!aicred_abi_compatible(version.major + 1, 0));
+}
+
+#[test]
+fn test_probe_models_rejects_unknown_provider() {
+    unsafe {
+        let provider = CString::new("not-a-provider").unwrap();
+        let api_key = CString::new("sk-test1234567890").unwrap();
+        let result = aicred_probe_models(provider.as_ptr(), api_key.as_ptr(), ptr::null());
+        assert!(result.is_null());
+        assert_eq!(aicred_last_error_code(), 102);
+
+        assert!(aicred_probe_models(ptr::null(), api_key.as_ptr(), ptr::null()).is_null());
+        assert_eq!(aicred_last_error_code(), AICRED_ERROR_INVALID_ARGUMENT);
+    }
+}
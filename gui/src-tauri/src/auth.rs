@@ -0,0 +1,97 @@
+//! Asks the user to prove who they are to the operating system before a full
+//! key value leaves the backend: Touch ID or the account password on macOS,
+//! Windows Hello on Windows and polkit on Linux.
+
+const DENIED: &str = "Authentication was cancelled or failed";
+
+/// Blocks until the user authenticates, with `reason` shown in the prompt
+pub fn authenticate(reason: &str) -> Result<(), String> {
+    platform::authenticate(reason)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::DENIED;
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_local_authentication::{LAContext, LAPolicy};
+    use std::sync::mpsc;
+
+    pub fn authenticate(reason: &str) -> Result<(), String> {
+        let (sender, receiver) = mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut NSError| {
+            let _ = sender.send(success.as_bool());
+        });
+        // Touch ID or a paired watch, falling back to the account password
+        unsafe {
+            let context = LAContext::new();
+            context.evaluatePolicy_localizedReason_reply(
+                LAPolicy::DeviceOwnerAuthentication,
+                &NSString::from_str(reason),
+                &reply,
+            );
+        }
+        match receiver.recv() {
+            Ok(true) => Ok(()),
+            _ => Err(DENIED.to_string()),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::DENIED;
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{UserConsentVerificationResult, UserConsentVerifier};
+
+    pub fn authenticate(reason: &str) -> Result<(), String> {
+        let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+            .and_then(|operation| operation.get())
+            .map_err(|e| format!("Windows Hello is unavailable: {}", e))?;
+        match result {
+            UserConsentVerificationResult::Verified => Ok(()),
+            UserConsentVerificationResult::DeviceNotPresent
+            | UserConsentVerificationResult::NotConfiguredForUser
+            | UserConsentVerificationResult::DisabledByPolicy => {
+                Err("Windows Hello is not set up for this account".to_string())
+            }
+            _ => Err(DENIED.to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::DENIED;
+    use std::process::Command;
+
+    /// polkit action of `polkit/com.aicred.policy`, installed with the packages
+    const ACTION_ID: &str = "com.aicred.reveal-key";
+
+    // pkcheck asks the session's authentication agent for the password, as
+    // the policy requires it on every reveal. `reason` is fixed by the policy.
+    pub fn authenticate(_reason: &str) -> Result<(), String> {
+        let status = Command::new("pkcheck")
+            .args(["--action-id", ACTION_ID, "--allow-user-interaction"])
+            .arg("--process")
+            .arg(std::process::id().to_string())
+            .status()
+            .map_err(|e| format!("polkit is unavailable (pkcheck: {})", e))?;
+        match status.code() {
+            Some(0) => Ok(()),
+            Some(1 | 2) => Err(DENIED.to_string()),
+            _ => Err(format!(
+                "polkit could not check {}; is polkit/com.aicred.policy installed?",
+                ACTION_ID
+            )),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+mod platform {
+    pub fn authenticate(_reason: &str) -> Result<(), String> {
+        Err("OS authentication is not supported on this platform".to_string())
+    }
+}
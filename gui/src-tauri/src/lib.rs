@@ -1,17 +1,35 @@
-use aicred_core::{scan, ScanOptions as CoreScanOptions};
-use serde::{Deserialize, Serialize};
+mod auth;
 
-// Import CLI command functions
-use aicred_cli::commands::labels::{handle_set_label, handle_unset_label, load_label_assignments};
-use aicred_cli::commands::tags::{
-    handle_add_tag, handle_assign_tag, handle_remove_tag, handle_unassign_tag, handle_update_tag,
-    load_tag_assignments, load_tags,
+use aicred_app::budgets::load_budgets;
+use aicred_app::instances::{self as app_instances, InstanceEntry};
+use aicred_app::labels::{self as app_labels, load_label_assignments};
+use aicred_app::plan::ChangePlan;
+use aicred_app::profiles::config_store;
+use aicred_app::settings::Settings;
+use aicred_app::tags::{self as app_tags, load_tag_assignments, load_tags, tag_target};
+use aicred_core::budget::{month_start, BudgetScope};
+use aicred_core::bulk::{self, InstanceSelector};
+use aicred_core::models::{LabelAssignment, LabelTarget, ProviderInstance};
+use aicred_core::resolve::reveal_api_key;
+use aicred_core::usage::{self, UsageGrouping, UsageLedger, UsageRecord, UsageTotal};
+use aicred_core::{
+    register_builtin_providers, register_builtin_scanners, scan_with_progress, CancellationToken,
+    ConfigStore, ModelRegistry, RedactionMode, ScanEvent, ScanOptions as CoreScanOptions,
+    ScannerRegistry, SecretBackends,
 };
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScanOptions {
     pub home_dir: Option<String>,
-    pub include_full_values: bool,
+    /// Redaction mode (`mask`, `last4`, `first8` or `fingerprint`); full
+    /// values are only returned by `reveal_key`
+    #[serde(default)]
+    pub redaction: Option<String>,
     pub max_file_size: usize,
     pub only_providers: Option<Vec<String>>,
     pub exclude_providers: Option<Vec<String>>,
@@ -39,45 +57,212 @@ pub struct AssignmentData {
     pub model_id: Option<String>,
 }
 
-#[tauri::command]
-fn perform_scan(options: ScanOptions) -> Result<String, String> {
-    let core_options = CoreScanOptions {
+/// Payload of the `scan://progress` event, sent before each scanner runs
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgress {
+    pub scanner: String,
+    pub root: String,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Payload of the `scan://finding` event, sent for each new key
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanFinding {
+    pub provider: String,
+    pub value_type: String,
+    /// Value as the redaction mode of the scan leaves it
+    pub redacted: String,
+    pub source: String,
+    pub line: Option<usize>,
+}
+
+/// Cancellation token of the running scan, for `cancel_scan`
+#[derive(Default)]
+struct ScanState(Mutex<Option<CancellationToken>>);
+
+impl ScanState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<CancellationToken>> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// The core options of a GUI scan, refusing to return full key values
+fn core_options(
+    options: ScanOptions,
+    cancellation: CancellationToken,
+) -> Result<CoreScanOptions, String> {
+    let redaction = match options.redaction.as_deref() {
+        Some(mode) => mode.parse()?,
+        None => RedactionMode::default(),
+    };
+    if redaction == RedactionMode::None {
+        return Err("Scans never return full values; reveal a key with reveal_key".to_string());
+    }
+    Ok(CoreScanOptions {
         home_dir: options.home_dir.map(std::path::PathBuf::from),
-        include_full_values: options.include_full_values,
+        redaction,
         max_file_size: options.max_file_size,
         only_providers: options.only_providers,
         exclude_providers: options.exclude_providers,
+        only_value_types: None,
         probe_models: false,
         probe_timeout_secs: 30,
+        extra_roots: Vec::new(),
+        scan_archives: false,
+        detection_rules: Vec::new(),
+        fingerprint: aicred_core::fingerprint::Fingerprinter::sha256(),
+        scanner_settings: std::collections::HashMap::new(),
+        network: aicred_core::http::NetworkOptions::default(),
+        offline: false,
+        cancellation: Some(cancellation),
+        scanners: None,
+    })
+}
+
+/// Runs a scan on a blocking thread, emitting `scan://progress` and
+/// `scan://finding` events as it goes, and returns the result as JSON
+#[tauri::command]
+async fn perform_scan(
+    app: AppHandle,
+    state: State<'_, ScanState>,
+    options: ScanOptions,
+) -> Result<String, String> {
+    let cancellation = CancellationToken::new();
+    {
+        let mut running = state.lock();
+        if running.is_some() {
+            return Err("A scan is already running".to_string());
+        }
+        *running = Some(cancellation.clone());
+    }
+
+    let outcome = match core_options(options, cancellation) {
+        Ok(core_options) => tauri::async_runtime::spawn_blocking(move || {
+            scan_with_progress(&core_options, |event| emit_scan_event(&app, event))
+        })
+        .await
+        .map_err(|e| format!("Scan failed: {}", e)),
+        Err(e) => Err(e),
     };
+    state.lock().take();
 
-    match scan(&core_options) {
-        Ok(result) => {
-            serde_json::to_string(&result).map_err(|e| format!("Failed to serialize result: {}", e))
+    let result = match outcome? {
+        Ok(result) => result,
+        Err(aicred_core::error::Error::Cancelled) => return Err("Scan cancelled".to_string()),
+        Err(e) => return Err(format!("Scan failed: {}", e)),
+    };
+    let mut json =
+        serde_json::to_value(&result).map_err(|e| format!("Failed to serialize result: {}", e))?;
+    // Shown in the results table
+    if let Some(keys) = json.get_mut("keys").and_then(|keys| keys.as_array_mut()) {
+        for (entry, key) in keys.iter_mut().zip(&result.keys) {
+            entry["redacted"] = serde_json::Value::String(key.redacted_value());
         }
-        Err(e) => Err(format!("Scan failed: {}", e)),
     }
+    serde_json::to_string(&json).map_err(|e| format!("Failed to serialize result: {}", e))
 }
 
+fn emit_scan_event(app: &AppHandle, event: ScanEvent<'_>) {
+    // A closed window only misses the events
+    let _ = match event {
+        ScanEvent::Scanner {
+            scanner,
+            root,
+            completed,
+            total,
+        } => app.emit(
+            "scan://progress",
+            ScanProgress {
+                scanner: scanner.to_string(),
+                root: root.to_string(),
+                completed,
+                total,
+            },
+        ),
+        ScanEvent::Key(key) => app.emit(
+            "scan://finding",
+            ScanFinding {
+                provider: key.provider.clone(),
+                value_type: key.value_type.to_string(),
+                redacted: key.redacted_value(),
+                source: key.source_file.clone(),
+                line: key.source_line,
+            },
+        ),
+    };
+}
+
+/// Cancels the running scan, returning whether one was running
 #[tauri::command]
-fn get_providers() -> Vec<String> {
-    vec![
-        "openai".to_string(),
-        "anthropic".to_string(),
-        "huggingface".to_string(),
-        "ollama".to_string(),
-        "litellm".to_string(),
-    ]
+fn cancel_scan(state: State<'_, ScanState>) -> bool {
+    match state.lock().as_ref() {
+        Some(cancellation) => {
+            cancellation.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// A provider plugin or application scanner of the core library
+#[derive(Debug, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+    /// Whether scans use it, that is `config.yaml` does not exclude it
+    pub enabled: bool,
+}
+
+/// Names `config.yaml` excludes from scans
+fn excluded_names() -> Vec<String> {
+    Settings::load(None)
+        .map(|config| config.exclude)
+        .unwrap_or_default()
+}
+
+fn sorted(mut plugins: Vec<PluginInfo>) -> Vec<PluginInfo> {
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
 }
 
 #[tauri::command]
-fn get_scanners() -> Vec<String> {
-    vec![
-        "roo-code".to_string(),
-        "claude-desktop".to_string(),
-        "ragit".to_string(),
-        "langchain".to_string(),
-    ]
+fn get_providers() -> Vec<PluginInfo> {
+    let excluded = excluded_names();
+    sorted(
+        register_builtin_providers()
+            .values()
+            .map(|plugin| PluginInfo {
+                name: plugin.name().to_string(),
+                display_name: plugin.display_name().to_string(),
+                description: plugin.description().to_string(),
+                enabled: !excluded.iter().any(|name| name == plugin.name()),
+            })
+            .collect(),
+    )
+}
+
+#[tauri::command]
+fn get_scanners() -> Result<Vec<PluginInfo>, String> {
+    let registry = ScannerRegistry::new();
+    register_builtin_scanners(&registry).map_err(|e| format!("Failed to load scanners: {}", e))?;
+    let excluded = excluded_names();
+    Ok(sorted(
+        registry
+            .list()
+            .iter()
+            .filter_map(|name| registry.get(name))
+            .map(|scanner| PluginInfo {
+                name: scanner.name().to_string(),
+                display_name: scanner.app_name().to_string(),
+                description: scanner.description().to_string(),
+                enabled: !excluded.iter().any(|name| name == scanner.name()),
+            })
+            .collect(),
+    ))
 }
 
 #[tauri::command]
@@ -102,7 +287,9 @@ fn add_tag(
     color: Option<String>,
     description: Option<String>,
 ) -> Result<String, String> {
-    match handle_add_tag(name, color, description, None) {
+    // Tags have no color yet
+    let _ = color;
+    match app_tags::add_tag(&name, description, None) {
         Ok(_) => Ok("Tag added successfully".to_string()),
         Err(e) => Err(format!("Failed to add tag: {}", e)),
     }
@@ -114,15 +301,31 @@ fn update_tag(
     color: Option<String>,
     description: Option<String>,
 ) -> Result<String, String> {
-    match handle_update_tag(name, color, description, None) {
+    let _ = color;
+    match app_tags::update_tag(&name, description, None) {
         Ok(_) => Ok("Tag updated successfully".to_string()),
         Err(e) => Err(format!("Failed to update tag: {}", e)),
     }
 }
 
+/// Removes a tag and, with `force`, its assignments; without it a tag still
+/// assigned is kept
 #[tauri::command]
 fn remove_tag(name: String, force: bool) -> Result<String, String> {
-    match handle_remove_tag(name, force, None) {
+    if !force {
+        let assigned = load_tag_assignments(None)
+            .map_err(|e| format!("Failed to load tag assignments: {}", e))?
+            .iter()
+            .filter(|assignment| assignment.label_name == name)
+            .count();
+        if assigned > 0 {
+            return Err(format!(
+                "Failed to remove tag: '{}' has {} assignments; remove it with force",
+                name, assigned
+            ));
+        }
+    }
+    match app_tags::remove_tag(&name, None) {
         Ok(_) => Ok("Tag removed successfully".to_string()),
         Err(e) => Err(format!("Failed to remove tag: {}", e)),
     }
@@ -134,7 +337,9 @@ fn assign_tag(
     instance_id: Option<String>,
     model_id: Option<String>,
 ) -> Result<String, String> {
-    match handle_assign_tag(tag_name, instance_id, model_id, None) {
+    let assigned = tag_target(instance_id, model_id)
+        .and_then(|target| app_tags::assign_tag(&tag_name, target, None));
+    match assigned {
         Ok(_) => Ok("Tag assigned successfully".to_string()),
         Err(e) => Err(format!("Failed to assign tag: {}", e)),
     }
@@ -146,7 +351,9 @@ fn unassign_tag(
     instance_id: Option<String>,
     model_id: Option<String>,
 ) -> Result<String, String> {
-    match handle_unassign_tag(tag_name, instance_id, model_id, None) {
+    let unassigned = tag_target(instance_id, model_id)
+        .and_then(|target| app_tags::unassign_tag(&tag_name, &target, None));
+    match unassigned {
         Ok(_) => Ok("Tag unassigned successfully".to_string()),
         Err(e) => Err(format!("Failed to unassign tag: {}", e)),
     }
@@ -164,7 +371,7 @@ fn list_tag_assignments() -> Result<String, String> {
 // Label management commands
 #[tauri::command]
 fn list_labels() -> Result<String, String> {
-    match load_label_assignments() {
+    match load_label_assignments(None) {
         Ok(labels) => {
             serde_json::to_string(&labels).map_err(|e| format!("Failed to serialize labels: {}", e))
         }
@@ -172,62 +379,122 @@ fn list_labels() -> Result<String, String> {
     }
 }
 
+/// Parses the targets of a label, erroring unless the configuration store
+/// has all of them
+fn label_targets(targets: &[String]) -> Result<Vec<LabelTarget>, String> {
+    let instances = instance_store()?
+        .load_instances()
+        .map_err(|e| format!("Failed to load instances: {}", e))?;
+    let targets = targets
+        .iter()
+        .map(|target| app_labels::label_target(target, &instances))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if targets.is_empty() {
+        return Err("A label needs an instance:model target".to_string());
+    }
+    Ok(targets)
+}
+
+/// Points a label at its first target, with the others as the fallbacks
+/// tried after it
+fn set_label(
+    name: &str,
+    mut targets: Vec<LabelTarget>,
+    description: Option<String>,
+    requires: Vec<String>,
+) -> Result<bool, String> {
+    app_labels::check_capabilities(&requires).map_err(|e| e.to_string())?;
+    let target = targets.remove(0);
+    app_labels::assign_label_with_fallbacks(name, target, targets, requires, description, None)
+        .map_err(|e| e.to_string())
+}
+
+fn find_label(name: &str) -> Result<Option<LabelAssignment>, String> {
+    app_labels::find_label(name, None).map_err(|e| format!("Failed to load labels: {}", e))
+}
+
+/// Creates a label pointing at `targets`, `instance:model` tuples or instance
+/// IDs tried in order
 #[tauri::command]
 fn add_label(
     name: String,
-    color: Option<String>,
+    targets: Vec<String>,
     description: Option<String>,
+    requires: Option<Vec<String>>,
 ) -> Result<String, String> {
-    // For the new system, we need a tuple to set a label
-    // This is a temporary solution - GUI should be updated to require tuple
-    let dummy_tuple = "unknown:placeholder".to_string();
-    match handle_set_label(name, dummy_tuple, color, description, None) {
-        Ok(_) => Ok("Label added successfully".to_string()),
-        Err(e) => Err(format!("Failed to add label: {}", e)),
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Label name cannot be empty".to_string());
+    }
+    if find_label(name)?.is_some() {
+        return Err(format!("Label '{}' already exists", name));
     }
+    let targets = label_targets(&targets).map_err(|e| format!("Failed to add label: {}", e))?;
+    set_label(name, targets, description, requires.unwrap_or_default())
+        .map_err(|e| format!("Failed to add label: {}", e))?;
+    Ok("Label added successfully".to_string())
 }
 
+/// Updates a label, keeping its targets or capability requirements when they
+/// are left out
 #[tauri::command]
 fn update_label(
     name: String,
-    color: Option<String>,
+    targets: Option<Vec<String>>,
     description: Option<String>,
+    requires: Option<Vec<String>>,
 ) -> Result<String, String> {
-    // For the new system, we need a tuple to update a label
-    // This is a temporary solution - GUI should be updated to require tuple
-    let dummy_tuple = "unknown:placeholder".to_string();
-    match handle_set_label(name, dummy_tuple, color, description, None) {
-        Ok(_) => Ok("Label updated successfully".to_string()),
-        Err(e) => Err(format!("Failed to update label: {}", e)),
-    }
+    let existing = find_label(&name)?.ok_or_else(|| format!("Label '{}' not found", name))?;
+    let targets = match targets.filter(|targets| !targets.is_empty()) {
+        Some(targets) => {
+            label_targets(&targets).map_err(|e| format!("Failed to update label: {}", e))?
+        }
+        None => std::iter::once(existing.target)
+            .chain(existing.fallbacks)
+            .collect(),
+    };
+    set_label(
+        &name,
+        targets,
+        description,
+        requires.unwrap_or(existing.requires),
+    )
+    .map_err(|e| format!("Failed to update label: {}", e))?;
+    Ok("Label updated successfully".to_string())
 }
 
+/// Removes a label; it must be confirmed with `force`
 #[tauri::command]
 fn remove_label(name: String, force: bool) -> Result<String, String> {
-    match handle_unset_label(name, force, None) {
+    if !force {
+        return Err(format!(
+            "Failed to remove label: removing '{}' needs force",
+            name
+        ));
+    }
+    match app_labels::unset_label(&name, None) {
         Ok(_) => Ok("Label removed successfully".to_string()),
         Err(e) => Err(format!("Failed to remove label: {}", e)),
     }
 }
 
+/// Points a label at an instance, or at one of its models
 #[tauri::command]
 fn assign_label(
     label_name: String,
     instance_id: Option<String>,
     model_id: Option<String>,
 ) -> Result<String, String> {
-    // Convert instance_id and model_id to tuple format
-    let tuple_str = match (instance_id, model_id) {
+    let target = match (instance_id, model_id) {
         (Some(instance), Some(model)) => format!("{}:{}", instance, model),
         (Some(instance), None) => instance,
-        (None, Some(model)) => format!("unknown:{}", model),
-        (None, None) => return Err("Either instance_id or model_id must be provided".to_string()),
+        (None, _) => return Err("An instance_id must be provided".to_string()),
     };
-
-    match handle_set_label(label_name, tuple_str, None, None, None) {
-        Ok(_) => Ok("Label assigned successfully".to_string()),
-        Err(e) => Err(format!("Failed to assign label: {}", e)),
-    }
+    let targets = label_targets(&[target]).map_err(|e| format!("Failed to assign label: {}", e))?;
+    set_label(&label_name, targets, None, Vec::new())
+        .map_err(|e| format!("Failed to assign label: {}", e))?;
+    Ok("Label assigned successfully".to_string())
 }
 
 #[tauri::command]
@@ -238,7 +505,7 @@ fn unassign_label(
 ) -> Result<String, String> {
     // For the new system, unset removes the entire label assignment
     // The instance_id and model_id parameters are not used in the new system
-    match handle_unset_label(label_name, false, None) {
+    match app_labels::unset_label(&label_name, None) {
         Ok(_) => Ok("Label unassigned successfully".to_string()),
         Err(e) => Err(format!("Failed to unassign label: {}", e)),
     }
@@ -246,19 +513,422 @@ fn unassign_label(
 
 #[tauri::command]
 fn list_label_assignments() -> Result<String, String> {
-    match load_label_assignments() {
+    match load_label_assignments(None) {
         Ok(assignments) => serde_json::to_string(&assignments)
             .map_err(|e| format!("Failed to serialize label assignments: {}", e)),
         Err(e) => Err(format!("Failed to load label assignments: {}", e)),
     }
 }
 
+// Instance commands
+/// Lists the instances sorted by ID, without their keys
+#[tauri::command]
+fn list_instances() -> Result<Vec<InstanceEntry>, String> {
+    let instances = instance_store()?
+        .load_instances()
+        .map_err(|e| format!("Failed to load instances: {}", e))?;
+    let mut entries: Vec<InstanceEntry> = instances
+        .list()
+        .into_iter()
+        .map(InstanceEntry::from)
+        .collect();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(entries)
+}
+
+/// A new instance from the instance form
+#[derive(Debug, Deserialize)]
+pub struct InstanceFormData {
+    pub id: String,
+    pub provider_type: String,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(default = "active_by_default")]
+    pub active: bool,
+    /// Secret backend of the API key, by default the configured one
+    pub secret_backend: Option<String>,
+}
+
+const fn active_by_default() -> bool {
+    true
+}
+
+/// Changes to an instance; fields left out are kept
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct InstanceUpdate {
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub models: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+/// Outcome of `validate_instance`
+#[derive(Debug, Serialize)]
+pub struct InstanceValidation {
+    pub id: String,
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+/// Outcome of an instance command: the instance it changed, or with `dry_run`
+/// the plan of the changes it would make
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum InstanceOutcome {
+    Changed(InstanceEntry),
+    Planned(ChangePlan),
+}
+
+/// The configuration store of the active profile, as the CLI uses it
+fn instance_store() -> Result<ConfigStore, String> {
+    config_store(None).map_err(|e| format!("Failed to find the configuration store: {}", e))
+}
+
+fn find_instance(store: &ConfigStore, id: &str) -> Result<(PathBuf, ProviderInstance), String> {
+    store
+        .find_instance(id)
+        .map_err(|e| format!("Failed to load instances: {}", e))?
+        .ok_or_else(|| format!("Provider instance with ID '{}' not found", id))
+}
+
+fn model_list(models: Vec<String>) -> Vec<String> {
+    models
+        .into_iter()
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty())
+        .collect()
+}
+
+#[tauri::command]
+fn get_instance(id: String) -> Result<InstanceEntry, String> {
+    let (_, instance) = find_instance(&instance_store()?, &id)?;
+    Ok(InstanceEntry::from(&instance))
+}
+
+/// Adds an instance like `aicred instances add`, keeping only a reference to
+/// a key stored outside the file backend
+#[tauri::command]
+fn add_instance(
+    instance: InstanceFormData,
+    dry_run: Option<bool>,
+) -> Result<InstanceOutcome, String> {
+    let backends = SecretBackends::builtin();
+    let backend = match instance.secret_backend {
+        Some(backend) => backend,
+        None => Settings::load(None)
+            .map_err(|e| format!("Failed to load settings: {}", e))?
+            .secret_backend(None),
+    };
+    if backends.get(&backend).is_none() {
+        return Err(format!(
+            "Unknown secret store '{}'. Supported: {}",
+            backend,
+            backends.names().join(", ")
+        ));
+    }
+
+    let mut new = ProviderInstance::new(
+        instance.id,
+        instance.provider_type,
+        instance.base_url,
+        String::new(),
+        model_list(instance.models),
+    );
+    new.active = instance.active;
+    new.validate()
+        .map_err(|e| format!("Invalid instance configuration: {}", e))?;
+    let api_key = instance.api_key.filter(|key| !key.is_empty());
+
+    let store = instance_store()?;
+    if dry_run.unwrap_or(false) {
+        ensure_absent(&store, &new.id)?;
+        let secret_backend = api_key.as_ref().map(|_| backend.as_str());
+        return Ok(InstanceOutcome::Planned(app_instances::plan_add(
+            &store,
+            &new,
+            secret_backend,
+        )));
+    }
+
+    let lock = store
+        .lock()
+        .map_err(|e| format!("Failed to lock the configuration store: {}", e))?;
+    ensure_absent(&store, &new.id)?;
+    if let Some(key) = api_key {
+        let key = backends
+            .store(&backend, &new.id, &key)
+            .map_err(|e| format!("Failed to store the API key: {}", e))?;
+        new.set_api_key(key);
+    }
+    lock.save_instance(&new)
+        .map_err(|e| format!("Failed to save instance: {}", e))?;
+    Ok(InstanceOutcome::Changed(InstanceEntry::from(&new)))
+}
+
+fn ensure_absent(store: &ConfigStore, id: &str) -> Result<(), String> {
+    if store
+        .find_instance(id)
+        .map_err(|e| format!("Failed to load instances: {}", e))?
+        .is_some()
+    {
+        return Err(format!("Provider instance with ID '{}' already exists", id));
+    }
+    Ok(())
+}
+
+/// Updates an instance like `aicred instances update`, storing a new key in
+/// the backend already holding the old one
+#[tauri::command]
+fn update_instance(
+    id: String,
+    update: InstanceUpdate,
+    dry_run: Option<bool>,
+) -> Result<InstanceOutcome, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let store = instance_store()?;
+    let lock = if dry_run {
+        None
+    } else {
+        Some(
+            store
+                .lock()
+                .map_err(|e| format!("Failed to lock the configuration store: {}", e))?,
+        )
+    };
+    let (path, before) = find_instance(&store, &id)?;
+    let mut instance = before.clone();
+
+    if let Some(base_url) = update.base_url {
+        instance.base_url = base_url;
+    }
+    if let Some(active) = update.active {
+        instance.active = active;
+    }
+    if let Some(models) = update.models {
+        instance.models = model_list(models);
+    }
+    instance
+        .validate()
+        .map_err(|e| format!("Invalid instance configuration: {}", e))?;
+    let key_replaced = update.api_key.is_some();
+    let mut secret_backend = None;
+    if let Some(key) = update.api_key {
+        let backends = SecretBackends::builtin();
+        let key = match backends.owner(&instance.api_key) {
+            Some(backend) if !backends.is_reference(&key) => {
+                secret_backend = Some(backend.name());
+                if dry_run {
+                    instance.api_key.clone()
+                } else {
+                    backend
+                        .store(&instance.id, &key)
+                        .map_err(|e| format!("Failed to store the API key: {}", e))?
+                }
+            }
+            _ => key,
+        };
+        instance.set_api_key(key);
+    }
+
+    let Some(lock) = lock else {
+        return Ok(InstanceOutcome::Planned(app_instances::plan_update(
+            &path,
+            &before,
+            &instance,
+            key_replaced,
+            secret_backend,
+        )));
+    };
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("Invalid instance file {}", path.display()))?;
+    lock.save_instance_as(&file_name, &instance)
+        .map_err(|e| format!("Failed to save instance: {}", e))?;
+    Ok(InstanceOutcome::Changed(InstanceEntry::from(&instance)))
+}
+
+/// Returns the full API key of an instance once the user has authenticated to
+/// the operating system
+#[tauri::command]
+async fn reveal_key(instance_id: String) -> Result<String, String> {
+    let store = instance_store()?;
+    let (_, mut instance) = find_instance(&store, &instance_id)?;
+    if !instance.has_non_empty_api_key() {
+        return Err(format!(
+            "Provider instance '{}' has no API key",
+            instance_id
+        ));
+    }
+
+    let reason = format!("reveal the API key of {}", instance.id);
+    tauri::async_runtime::spawn_blocking(move || auth::authenticate(&reason))
+        .await
+        .map_err(|e| format!("Authentication failed: {}", e))??;
+
+    let cipher = store
+        .cipher()
+        .map_err(|e| format!("Failed to load the encryption identity: {}", e))?;
+    reveal_api_key(&mut instance, cipher.as_ref(), &SecretBackends::builtin())
+        .map_err(|e| format!("Failed to reveal the API key: {}", e))?;
+    Ok(instance.api_key)
+}
+
+/// Removes an instance like `aicred instances remove --force`, keeping the
+/// file as a backup and dropping the assignments pointing at it
+#[tauri::command]
+fn remove_instance(id: String, dry_run: Option<bool>) -> Result<InstanceOutcome, String> {
+    let store = instance_store()?;
+    let selector = InstanceSelector::default().with_id(&id);
+    if dry_run.unwrap_or(false) {
+        let plan = app_instances::plan_remove(&store, &selector)
+            .map_err(|e| format!("Failed to plan the removal: {}", e))?;
+        if plan.is_empty() {
+            return Err(format!("Provider instance with ID '{}' not found", id));
+        }
+        return Ok(InstanceOutcome::Planned(plan));
+    }
+    let removed =
+        bulk::remove(&store, &selector).map_err(|e| format!("Failed to remove instance: {}", e))?;
+    let instance = removed
+        .first()
+        .ok_or_else(|| format!("Provider instance with ID '{}' not found", id))?;
+    // The instance is gone either way; a secret that can't be deleted is
+    // only left behind
+    let _ = SecretBackends::builtin().delete(&instance.api_key);
+    Ok(InstanceOutcome::Changed(InstanceEntry::from(instance)))
+}
+
+/// Validates an instance like `aicred instances validate`, adding the checks
+/// of its provider plugin
+#[tauri::command]
+fn validate_instance(id: String) -> Result<InstanceValidation, String> {
+    let (_, instance) = find_instance(&instance_store()?, &id)?;
+    let mut errors = Vec::new();
+    if let Err(e) = instance.validate() {
+        errors.push(e.to_string());
+    }
+    if let Some(plugin) = register_builtin_providers().get(&instance.provider_type) {
+        if let Err(e) = plugin.validate_instance(&instance) {
+            errors.push(e.to_string());
+        }
+    }
+    Ok(InstanceValidation {
+        id: instance.id,
+        valid: errors.is_empty(),
+        errors,
+    })
+}
+
+// Usage and cost commands
+/// Usage over a range of the dashboard, with the totals of each day
+#[derive(Debug, Serialize)]
+pub struct UsageSummary {
+    pub range: String,
+    /// Start of the range, none for all recorded usage
+    pub since: Option<DateTime<Utc>>,
+    pub requests: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Cost of the priced records
+    pub cost: f64,
+    /// Records neither the tool nor the model registry priced
+    pub unpriced: usize,
+    /// Totals per UTC day, in date order
+    pub days: Vec<UsageTotal>,
+}
+
+/// Start of a dashboard range: `all`, `month` for the current calendar month,
+/// a number of days such as `30d`, or a `YYYY-MM-DD` date
+fn range_start(range: &str, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+    let invalid = || {
+        format!(
+            "Invalid range '{}', expected all, month, 30d or YYYY-MM-DD",
+            range
+        )
+    };
+    match range.trim().to_lowercase().as_str() {
+        "all" => Ok(None),
+        "month" => Ok(Some(month_start(now))),
+        range => {
+            if let Some(days) = range.strip_suffix('d') {
+                let days: u32 = days.parse().map_err(|_| invalid())?;
+                // Whole days, counting today
+                let today = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+                return Ok(Some(today - Duration::days(i64::from(days.max(1)) - 1)));
+            }
+            NaiveDate::parse_from_str(range, "%Y-%m-%d")
+                .map(|day| Some(day.and_time(NaiveTime::MIN).and_utc()))
+                .map_err(|_| invalid())
+        }
+    }
+}
+
+/// The recorded usage, priced from the model registry where the tool
+/// reported no cost
+fn priced_usage() -> Result<Vec<UsageRecord>, String> {
+    let mut records = UsageLedger::default_location()
+        .and_then(|ledger| ledger.load())
+        .map_err(|e| format!("Failed to load the usage ledger: {}", e))?;
+    let registry = ModelRegistry::new();
+    for record in &mut records {
+        record.price(&registry);
+    }
+    Ok(records)
+}
+
+#[tauri::command]
+fn get_usage_summary(range: String) -> Result<UsageSummary, String> {
+    let since = range_start(&range, Utc::now())?;
+    let days = usage::totals(&priced_usage()?, UsageGrouping::Day, since);
+    Ok(UsageSummary {
+        range,
+        since,
+        requests: days.iter().map(|day| day.requests).sum(),
+        input_tokens: days.iter().map(|day| day.input_tokens).sum(),
+        output_tokens: days.iter().map(|day| day.output_tokens).sum(),
+        cost: days.iter().map(|day| day.cost).sum(),
+        unpriced: days.iter().map(|day| day.unpriced).sum(),
+        days,
+    })
+}
+
+/// Spend per instance, label or day over a range, by default all recorded
+/// usage, most expensive first. Instances and labels carry the status of
+/// their monthly budget.
+#[tauri::command]
+fn get_cost_breakdown(by: String, range: Option<String>) -> Result<Vec<UsageTotal>, String> {
+    let by: UsageGrouping = by.parse()?;
+    let now = Utc::now();
+    let since = range_start(range.as_deref().unwrap_or("all"), now)?;
+    let records = priced_usage()?;
+    let mut totals = usage::totals(&records, by, since);
+
+    let scope = match by {
+        UsageGrouping::Instance => Some(BudgetScope::Instance),
+        UsageGrouping::Label => Some(BudgetScope::Label),
+        UsageGrouping::Day => None,
+    };
+    if let Some(scope) = scope {
+        let budgets = load_budgets(None).map_err(|e| format!("Failed to load budgets: {}", e))?;
+        for total in &mut totals {
+            total.budget = budgets.status(scope, &total.key, &records, now);
+        }
+    }
+    Ok(totals)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .manage(ScanState::default())
         .invoke_handler(tauri::generate_handler![
             perform_scan,
+            cancel_scan,
             get_providers,
             get_scanners,
             get_version,
@@ -277,7 +947,18 @@ pub fn run() {
             remove_label,
             assign_label,
             unassign_label,
-            list_label_assignments
+            list_label_assignments,
+            // Instance commands
+            list_instances,
+            get_instance,
+            add_instance,
+            update_instance,
+            remove_instance,
+            validate_instance,
+            reveal_key,
+            // Usage commands
+            get_usage_summary,
+            get_cost_breakdown
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
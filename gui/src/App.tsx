@@ -1,5 +1,6 @@
 import { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import './App.css';
 import ScanResults from './components/ScanResults';
 import ScanOptions from './components/ScanOptions';
@@ -34,9 +35,24 @@ interface ConfigInstance {
   keys: DiscoveredKey[];
 }
 
+interface ScanProgress {
+  scanner: string;
+  root: string;
+  completed: number;
+  total: number;
+}
+
+interface ScanFinding {
+  provider: string;
+  value_type: string;
+  redacted: string;
+  source: string;
+  line: number | null;
+}
+
 interface ScanOptionsType {
   home_dir?: string;
-  include_full_values: boolean;
+  redaction?: 'mask' | 'last4' | 'first8' | 'fingerprint';
   max_file_size: number;
   only_providers?: string[];
   exclude_providers?: string[];
@@ -50,6 +66,8 @@ function App() {
   const [error, setError] = useState<string | null>(null);
   const [version, setVersion] = useState<string>('');
   const [currentView, setCurrentView] = useState<ViewType>('scan');
+  const [progress, setProgress] = useState<ScanProgress | null>(null);
+  const [findings, setFindings] = useState<ScanFinding[]>([]);
 
   useState(() => {
     invoke<string>('get_version').then(setVersion);
@@ -58,7 +76,15 @@ function App() {
   const handleScan = async (options: ScanOptionsType) => {
     setIsScanning(true);
     setError(null);
-    
+    setProgress(null);
+    setFindings([]);
+
+    const unlisten = await Promise.all([
+      listen<ScanProgress>('scan://progress', (event) => setProgress(event.payload)),
+      listen<ScanFinding>('scan://finding', (event) =>
+        setFindings((found) => [...found, event.payload])
+      ),
+    ]);
     try {
       const result = await invoke<string>('perform_scan', { options });
       const parsed = JSON.parse(result);
@@ -66,10 +92,15 @@ function App() {
     } catch (err) {
       setError(err as string);
     } finally {
+      unlisten.forEach((stop) => stop());
       setIsScanning(false);
     }
   };
 
+  const handleCancel = () => {
+    invoke<boolean>('cancel_scan');
+  };
+
   const navigationItems = [
     { id: 'scan' as ViewType, label: 'Scan Results', icon: '🔍' },
     { id: 'tags' as ViewType, label: 'Tag Management', icon: '🏷️' },
@@ -102,7 +133,15 @@ function App() {
             {isScanning && (
               <div className="scanning-message">
                 <div className="spinner"></div>
-                <p>Scanning for credentials...</p>
+                <p>
+                  {progress
+                    ? `Scanning ${progress.scanner} (${progress.completed + 1}/${progress.total})`
+                    : 'Scanning for credentials...'}
+                  {findings.length > 0 && ` · ${findings.length} found`}
+                </p>
+                <button className="cancel-button" onClick={handleCancel}>
+                  Cancel
+                </button>
               </div>
             )}
 
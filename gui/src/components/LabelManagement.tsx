@@ -16,7 +16,8 @@ export default function LabelManagement({ onLabelSelect, selectedLabelId }: Labe
   const [formData, setFormData] = useState<LabelFormData>({
     name: '',
     description: '',
-    color: '#17c964'
+    color: '#17c964',
+    targets: ''
   });
 
   const predefinedColors = [
@@ -72,6 +73,15 @@ export default function LabelManagement({ onLabelSelect, selectedLabelId }: Labe
       return;
     }
 
+    const targets = formData.targets
+      .split(',')
+      .map((target) => target.trim())
+      .filter((target) => target.length > 0);
+    if (!editingLabel && targets.length === 0) {
+      setError('Label target is required');
+      return;
+    }
+
     try {
       setError(null);
       
@@ -79,14 +89,14 @@ export default function LabelManagement({ onLabelSelect, selectedLabelId }: Labe
         // Update existing label
         await invoke('update_label', {
           name: editingLabel.name,
-          color: formData.color,
+          targets: targets.length > 0 ? targets : null,
           description: formData.description || null
         });
       } else {
         // Add new label
         await invoke('add_label', {
           name: formData.name,
-          color: formData.color,
+          targets,
           description: formData.description || null
         });
       }
@@ -103,7 +113,8 @@ export default function LabelManagement({ onLabelSelect, selectedLabelId }: Labe
     setFormData({
       name: label.name,
       description: label.description || '',
-      color: label.color || '#17c964'
+      color: label.color || '#17c964',
+      targets: ''
     });
     setShowAddForm(true);
   };
@@ -136,7 +147,8 @@ export default function LabelManagement({ onLabelSelect, selectedLabelId }: Labe
     setFormData({
       name: '',
       description: '',
-      color: '#17c964'
+      color: '#17c964',
+      targets: ''
     });
     setEditingLabel(null);
     setShowAddForm(false);
@@ -220,6 +232,18 @@ export default function LabelManagement({ onLabelSelect, selectedLabelId }: Labe
               />
             </div>
 
+            <div className="form-group">
+              <label htmlFor="label-targets">Target{editingLabel ? '' : ' *'}</label>
+              <input
+                id="label-targets"
+                type="text"
+                value={formData.targets}
+                onChange={(e) => setFormData({ ...formData, targets: e.target.value })}
+                placeholder={editingLabel ? 'Keep the current target' : 'instance:model, fallback:model'}
+                required={!editingLabel}
+              />
+            </div>
+
             <div className="form-group">
               <label htmlFor="label-description">Description</label>
               <textarea
@@ -3,6 +3,13 @@ import { invoke } from '@tauri-apps/api/core';
 import { Tag, Label, ProviderInstance } from '../types/tagging';
 import AssignmentModal from './AssignmentModal';
 
+interface PluginInfo {
+  name: string;
+  display_name: string;
+  description: string;
+  enabled: boolean;
+}
+
 interface ProviderListProps {
   onInstanceSelect?: (instance: ProviderInstance) => void;
   selectedInstanceId?: string;
@@ -12,8 +19,8 @@ interface ProviderListProps {
 }
 
 export default function ProviderList({ onInstanceSelect, selectedInstanceId, scanResult }: ProviderListProps) {
-  const [providers, setProviders] = useState<string[]>([]);
-  const [scanners, setScanners] = useState<string[]>([]);
+  const [providers, setProviders] = useState<PluginInfo[]>([]);
+  const [scanners, setScanners] = useState<PluginInfo[]>([]);
   const [tags, setTags] = useState<Tag[]>([]);
   const [labels, setLabels] = useState<Label[]>([]);
   const [tagAssignments, setTagAssignments] = useState<any[]>([]);
@@ -41,8 +48,8 @@ export default function ProviderList({ onInstanceSelect, selectedInstanceId, sca
   const loadData = async () => {
     try {
       // Load providers and scanners
-      const providersData = await invoke<string[]>('get_providers');
-      const scannersData = await invoke<string[]>('get_scanners');
+      const providersData = await invoke<PluginInfo[]>('get_providers');
+      const scannersData = await invoke<PluginInfo[]>('get_scanners');
       setProviders(providersData);
       setScanners(scannersData);
 
@@ -154,7 +161,9 @@ export default function ProviderList({ onInstanceSelect, selectedInstanceId, sca
         <h3>Providers</h3>
         <ul>
           {providers.map(p => (
-            <li key={p}>{p}</li>
+            <li key={p.name} title={p.description} className={p.enabled ? '' : 'disabled'}>
+              {p.display_name}
+            </li>
           ))}
         </ul>
       </div>
@@ -163,7 +172,9 @@ export default function ProviderList({ onInstanceSelect, selectedInstanceId, sca
         <h3>Scanners</h3>
         <ul>
           {scanners.map(s => (
-            <li key={s}>{s}</li>
+            <li key={s.name} title={s.description} className={s.enabled ? '' : 'disabled'}>
+              {s.display_name}
+            </li>
           ))}
         </ul>
       </div>
@@ -7,14 +7,14 @@ interface ScanOptionsProps {
 
 export default function ScanOptions({ onScan, isScanning }: ScanOptionsProps) {
   const [homeDir, setHomeDir] = useState('');
-  const [includeFullValues, setIncludeFullValues] = useState(false);
+  const [redaction, setRedaction] = useState('first8');
   const [maxFileSize, setMaxFileSize] = useState(1048576);
   const [selectedProviders, setSelectedProviders] = useState<string[]>([]);
 
   const handleScan = () => {
     onScan({
       home_dir: homeDir || undefined,
-      include_full_values: includeFullValues,
+      redaction,
       max_file_size: maxFileSize,
       only_providers: selectedProviders.length > 0 ? selectedProviders : undefined,
     });
@@ -44,14 +44,13 @@ export default function ScanOptions({ onScan, isScanning }: ScanOptionsProps) {
       </div>
 
       <div className="option-group">
-        <label>
-          <input
-            type="checkbox"
-            checked={includeFullValues}
-            onChange={(e) => setIncludeFullValues(e.target.checked)}
-          />
-          Include Full Values (⚠️ Dangerous)
-        </label>
+        <label>Redaction</label>
+        <select value={redaction} onChange={(e) => setRedaction(e.target.value)}>
+          <option value="first8">First 8 characters</option>
+          <option value="last4">Last 4 characters</option>
+          <option value="mask">Full mask</option>
+          <option value="fingerprint">Fingerprint only</option>
+        </select>
       </div>
 
       <button
@@ -84,6 +84,8 @@ export interface LabelFormData {
   name: string;
   description?: string;
   color?: string;
+  // Comma-separated `instance:model` tuples or instance IDs, tried in order
+  targets: string;
 }
 
 export interface AssignmentFormData {
@@ -91,4 +93,57 @@ export interface AssignmentFormData {
   label_id?: string;
   instance_id: string;
   model_id?: string;
-}
\ No newline at end of file
+}
+// Payloads of the instance commands (`get_instance`, `add_instance`, ...)
+export interface InstanceEntry {
+  id: string;
+  provider_type: string;
+  base_url: string;
+  active: boolean;
+  has_api_key: boolean;
+  models: string[];
+  metadata: Record<string, string>;
+  provenance?: {
+    scanner?: string;
+    source_files: string[];
+    scanned_at: string;
+  };
+}
+
+export interface InstanceFormData {
+  id: string;
+  provider_type: string;
+  base_url: string;
+  api_key?: string;
+  models?: string[];
+  active?: boolean;
+  secret_backend?: string;
+}
+
+export interface InstanceUpdate {
+  base_url?: string;
+  api_key?: string;
+  models?: string[];
+  active?: boolean;
+}
+
+export interface InstanceValidation {
+  id: string;
+  valid: boolean;
+  errors: string[];
+}
+
+// Returned instead of the instance by `add_instance`, `update_instance` and
+// `remove_instance` when called with `dryRun: true`
+export interface PlannedChange {
+  action: 'create' | 'update' | 'delete';
+  kind: 'file' | 'instance' | 'secret' | 'provider_key' | 'assignment';
+  target: string;
+  path?: string;
+  details?: string[];
+}
+
+export interface ChangePlan {
+  command: string;
+  changes: PlannedChange[];
+}
@@ -0,0 +1,37 @@
+// Payloads of the usage commands (`get_usage_summary`, `get_cost_breakdown`)
+
+// Start of a range: 'all', 'month', a number of days such as '30d', or a
+// YYYY-MM-DD date
+export type UsageRange = string;
+
+export type UsageGrouping = 'instance' | 'label' | 'day';
+
+export interface BudgetStatus {
+  scope: 'instance' | 'label';
+  name: string;
+  monthly: number;
+  spent: number;
+  refuse: boolean;
+}
+
+export interface UsageTotal {
+  // Instance ID, label or day, or 'unattributed'
+  key: string;
+  requests: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost: number;
+  unpriced: number;
+  budget?: BudgetStatus;
+}
+
+export interface UsageSummary {
+  range: UsageRange;
+  since: string | null;
+  requests: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost: number;
+  unpriced: number;
+  days: UsageTotal[];
+}